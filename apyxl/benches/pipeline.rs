@@ -0,0 +1,153 @@
+//! Benchmarks of each phase of the pipeline over synthetic inputs. Run with `cargo bench`.
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
+
+use apyxl::input::ChunkBuffer;
+use apyxl::model::{Builder, Chunk};
+use apyxl::{generator, output, parser, Executor, Generator, Parser};
+
+/// Number of chunks (files) in each synthetic input.
+const SIZES: &[usize] = &[10, 100, 1000];
+
+/// Creates an input of `chunks` files, each with a nested namespace containing DTOs that refer to
+/// each other, an enum and RPCs.
+fn synthetic_input(chunks: usize) -> ChunkBuffer {
+    let mut input = ChunkBuffer::new();
+    for i in 0..chunks {
+        let mut data = String::new();
+        writeln!(data, "mod ns{} {{", i % 10).unwrap();
+        writeln!(data, "    /// An enum.").unwrap();
+        writeln!(data, "    enum en{} {{ a, b = 5, c }}", i).unwrap();
+        for j in 0..10 {
+            writeln!(data, "    /// A dto.").unwrap();
+            writeln!(data, "    struct dto{} {{", j).unwrap();
+            writeln!(data, "        id: u64,").unwrap();
+            writeln!(data, "        name: String,").unwrap();
+            writeln!(data, "        values: Vec<Option<i32>>,").unwrap();
+            writeln!(data, "        lookup: HashMap<String, en{}>,", i).unwrap();
+            if j > 0 {
+                writeln!(data, "        prev: dto{},", j - 1).unwrap();
+            }
+            writeln!(data, "    }}").unwrap();
+            writeln!(
+                data,
+                "    fn rpc{}(param: dto{}, other: &str) -> Vec<dto{}> {{ body }}",
+                j, j, j
+            )
+            .unwrap();
+        }
+        writeln!(data, "}}").unwrap();
+        input.add_chunk(
+            Chunk::with_relative_file_path(format!("dir{}/file{}.rs", i % 10, i)),
+            data,
+        );
+    }
+    input
+}
+
+fn input_bytes(input: &ChunkBuffer) -> u64 {
+    use apyxl::Input;
+    input
+        .chunks()
+        .iter()
+        .map(|(_, data)| data.len() as u64)
+        .sum()
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    let config = parser::Config::default();
+    for &size in SIZES {
+        let mut input = synthetic_input(size);
+        group.throughput(Throughput::Bytes(input_bytes(&input)));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| {
+                let mut builder = Builder::default();
+                parser::Rust::default()
+                    .parse(&config, &mut input, &mut builder)
+                    .unwrap();
+                black_box(builder.current_namespace().children.len());
+            })
+        });
+    }
+    group.finish();
+}
+
+fn build(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build");
+    let config = parser::Config::default();
+    for &size in SIZES {
+        let mut input = synthetic_input(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            // The builder borrows the input mutably, so it can't be created in a batch setup.
+            b.iter_custom(|iters| {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    let mut builder = Builder::default();
+                    parser::Rust::default()
+                        .parse(&config, &mut input, &mut builder)
+                        .unwrap();
+                    let start = Instant::now();
+                    black_box(builder.build().unwrap());
+                    elapsed += start.elapsed();
+                }
+                elapsed
+            })
+        });
+    }
+    group.finish();
+}
+
+fn generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate");
+    let config = parser::Config::default();
+    for &size in SIZES {
+        let mut input = synthetic_input(size);
+        let mut builder = Builder::default();
+        parser::Rust::default()
+            .parse(&config, &mut input, &mut builder)
+            .unwrap();
+        let model = builder.build().unwrap();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter(|| {
+                let mut output = output::Buffer::default();
+                generator::Rust::default()
+                    .generate(model.view(), &mut output)
+                    .unwrap();
+                output
+            })
+        });
+    }
+    group.finish();
+}
+
+fn execute(c: &mut Criterion) {
+    let mut group = c.benchmark_group("execute");
+    for &size in SIZES {
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_function(BenchmarkId::from_parameter(size), |b| {
+            b.iter_batched(
+                || synthetic_input(size),
+                |input| {
+                    Executor::new(input, parser::Rust::default())
+                        .generator(generator::Rust::default())
+                        .output(output::Buffer::default())
+                        .execute()
+                        .unwrap()
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parse, build, generate, execute);
+criterion_main!(benches);
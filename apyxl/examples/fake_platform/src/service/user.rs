@@ -44,7 +44,13 @@ pub struct Id {
     value: u128,
 }
 
-// todo methods on dto types
-// impl User {
-//
-// }
+// feature: methods on dto types
+impl User {
+    pub fn display_name(&self) -> &str {
+        &self.display.display_name
+    }
+
+    pub fn is_friend(&self, id: Id) -> bool {
+        self.friends.contains_key(&id)
+    }
+}
@@ -0,0 +1,257 @@
+use std::fs;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::diff::diff;
+use crate::model::{Api, EntityId};
+use crate::{Diagnostic, Diagnostics};
+
+/// A committed version of the API that later versions must stay compatible with. When set on an
+/// [crate::Executor::baseline], the run fails before generating anything if the model has any
+/// breaking changes from the baseline, as determined by [crate::diff::Change::is_breaking].
+///
+/// The baseline is the JSON of the API as written by [crate::generator::Json], e.g. generated
+/// from the last release and committed alongside the source.
+///
+/// Intentional breaks can be allowed by adding the [EntityId] of the changed entity to the
+/// allowlist, which also allows breaking changes to everything within it, e.g. allowing a dto
+/// allows removing its fields. Changes to enum values are allowed by the id of their enum.
+///
+/// ```ignore
+/// Executor::new(input::Glob::new("src/**/*.rs")?, parser::Rust::default())
+///     .baseline(Baseline::from_file("api.json")?.allowlist_file("api-breaks.txt")?)
+///     .generator(generator::Rust::default())
+///     .output(output::FileSet::new("generated")?)
+///     .execute()?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct Baseline {
+    json: String,
+    allowlist: Vec<EntityId>,
+}
+
+impl Baseline {
+    /// `json` is the baseline API as written by [crate::generator::Json].
+    pub fn new(json: impl ToString) -> Self {
+        Self {
+            json: json.to_string(),
+            allowlist: vec![],
+        }
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("read baseline '{}'", path.display()))?;
+        Ok(Self::new(json))
+    }
+
+    /// Allow breaking changes to the entity with `entity_id` and everything within it.
+    pub fn allow(mut self, entity_id: EntityId) -> Self {
+        self.allowlist.push(entity_id);
+        self
+    }
+
+    /// Allow breaking changes to each entity listed in the file at `path`: one [EntityId] per
+    /// line, in either long or short form, e.g. `user.dto:User.field:id` or `user.d:User.f:id`.
+    /// Blank lines and lines starting with `#` are ignored.
+    pub fn allowlist_file<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("read allowlist '{}'", path.display()))?;
+        self.allowlist_str(&data)
+            .with_context(|| format!("parse allowlist '{}'", path.display()))
+    }
+
+    /// Like [Baseline::allowlist_file], with the contents of the file.
+    pub fn allowlist_str(mut self, data: &str) -> Result<Self> {
+        for (i, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entity_id = EntityId::try_from(line)
+                .map_err(|err| anyhow!("line {}: invalid entity id '{}': {}", i + 1, line, err))?;
+            self.allowlist.push(entity_id);
+        }
+        Ok(self)
+    }
+
+    /// Compares `api` against the baseline. Returns an error [Diagnostic] for each breaking change
+    /// that isn't allowlisted, and a warning for each allowlist entry that doesn't allow any
+    /// breaking change so that stale entries can be cleaned up.
+    pub fn check(&self, api: &Api) -> Result<Diagnostics> {
+        let baseline: Api = serde_json::from_str(&self.json).context("parse baseline")?;
+        let breaking = diff(&baseline, api)
+            .into_iter()
+            .filter(|change| change.is_breaking())
+            .collect::<Vec<_>>();
+
+        let mut diagnostics = Diagnostics::new();
+        for change in &breaking {
+            if self
+                .allowlist
+                .iter()
+                .any(|id| contains(id, &change.entity_id))
+            {
+                continue;
+            }
+            diagnostics.push(
+                Diagnostic::error(format!("breaking change from baseline: {}", change)).note(
+                    format!(
+                        "if intentional, add '{}' to the allowlist",
+                        change.entity_id
+                    ),
+                ),
+            );
+        }
+        for id in &self.allowlist {
+            if !breaking
+                .iter()
+                .any(|change| contains(id, &change.entity_id))
+            {
+                diagnostics.push(Diagnostic::warning(format!(
+                    "allowlist entry '{}' does not match any breaking change",
+                    id
+                )));
+            }
+        }
+        Ok(diagnostics)
+    }
+}
+
+/// Whether `entity_id` is `ancestor` or within it.
+fn contains(ancestor: &EntityId, entity_id: &EntityId) -> bool {
+    let mut id = Some(entity_id.clone());
+    while let Some(current) = id {
+        if current.is_empty() {
+            return false;
+        }
+        if current == *ancestor {
+            return true;
+        }
+        id = current.parent();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::model::EntityId;
+    use crate::testing::TestExecutor;
+    use crate::{Baseline, Diagnostics};
+
+    const OLD: &str = r#"
+        mod user {
+            struct User { id: u32, name: String }
+            fn get_user(id: u32) -> User {}
+            enum Kind { A = 1, B = 2 }
+        }
+    "#;
+
+    fn run_test(new: &str, baseline: impl FnOnce(Baseline) -> Baseline) -> Result<Diagnostics> {
+        let mut old = TestExecutor::new(OLD);
+        let baseline = baseline(Baseline::new(serde_json::to_string(old.build().api())?));
+        let mut new = TestExecutor::new(new);
+        baseline.check(new.build().api())
+    }
+
+    fn messages<'a>(diagnostics: impl Iterator<Item = &'a crate::Diagnostic>) -> Vec<String> {
+        diagnostics
+            .map(|diagnostic| diagnostic.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn compatible_changes() -> Result<()> {
+        let diagnostics = run_test(
+            r#"
+            mod user {
+                struct User { id: u32, name: String, email: Option<String> }
+                struct Friend {}
+                fn get_user(id: u32) -> User {}
+                fn delete_user(id: u32) {}
+                enum Kind { A = 1, B = 2, C = 3 }
+            }
+            "#,
+            |baseline| baseline,
+        )?;
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        Ok(())
+    }
+
+    #[test]
+    fn breaking_changes() -> Result<()> {
+        let diagnostics = run_test(
+            r#"
+            mod user {
+                struct User { id: u64 }
+                fn get_user(id: u32, verbose: bool) -> User {}
+                enum Kind { A = 3 }
+            }
+            "#,
+            |baseline| baseline,
+        )?;
+        assert_eq!(
+            messages(diagnostics.errors()),
+            vec![
+                "breaking change from baseline: Field `user.User.name`",
+                "breaking change from baseline: Field `user.User.id`: `u32` → `u64`",
+                "breaking change from baseline: Param `user.get_user.verbose`",
+                "breaking change from baseline: Enum value `user.Kind.B` = 2",
+                "breaking change from baseline: Enum value `user.Kind.A`: 1 → 3",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn allowlist() -> Result<()> {
+        let diagnostics = run_test(
+            r#"
+            mod user {
+                struct User { id: u64 }
+                enum Kind { A = 1, B = 2 }
+            }
+            "#,
+            |baseline| {
+                baseline
+                    .allow(EntityId::try_from("user.d:User").unwrap())
+                    .allowlist_str("# intentional\n\nuser.rpc:get_user\nuser.e:Kind\n")
+                    .unwrap()
+            },
+        )?;
+        assert!(!diagnostics.has_errors(), "{}", diagnostics);
+        assert_eq!(
+            messages(diagnostics.warnings()),
+            vec!["allowlist entry 'user.enum:Kind' does not match any breaking change"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_strings() -> Result<()> {
+        let api = r#"
+            /// A "quoted" comment.
+            #[api(path = "C:\dir")]
+            struct User {}
+        "#;
+        let mut old = TestExecutor::new(api);
+        let baseline = Baseline::new(serde_json::to_string(old.build().api())?);
+        let mut new = TestExecutor::new(api);
+        let diagnostics = baseline.check(new.build().api())?;
+        assert!(diagnostics.is_empty(), "{}", diagnostics);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_allowlist() {
+        let err = Baseline::new("{}")
+            .allowlist_str("user.d:User\nuser.x:Bad")
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+}
@@ -0,0 +1,445 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::model::UserTypeName;
+use crate::Mode;
+
+/// Typed options for each component, keyed by the name the component is registered under in the
+/// [crate::Registry]. Each value is deserialized into the component's own options type.
+pub type Sections = BTreeMap<String, serde_json::Value>;
+
+/// Configuration for a run of apyxl, usually loaded from json: options shared by every
+/// [crate::Parser], plus a section of typed options for each parser and generator.
+///
+/// Example:
+/// ```json
+/// {
+///   "user_types": [{ "parse": "Uuid", "name": "uuid" }],
+///   "parser": { "rust": { "file_namespaces": false } },
+///   "generator": { "rust": { "import_comments": false } }
+/// }
+/// ```
+///
+/// Sections are only deserialized by the component they're for, see [Config::parser_options] and
+/// [Config::generator_options]. Use [crate::Registry::validate_config] to check that every section
+/// belongs to a registered component.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// See [UserType].
+    #[serde(default)]
+    pub user_types: Vec<UserType>,
+
+    /// In [Mode::Lenient], parsers should skip syntax they don't understand and report it as a
+    /// warning via [crate::model::Builder::diagnostics_mut]. Overwritten by
+    /// [crate::Executor::mode].
+    #[serde(default)]
+    pub mode: Mode,
+
+    /// Options for each parser, e.g. `parser.rust`.
+    #[serde(default)]
+    pub parser: Sections,
+
+    /// Options for each generator, e.g. `generator.rust`.
+    #[serde(default)]
+    pub generator: Sections,
+}
+
+/// When the `parse` string is seen by a [crate::parser::Parser], it is mapped to a
+/// [crate::model::Type::User] variant with the value `name`. This needs to be implemented by
+/// the [crate::parser::Parser] implementation itself, see [UserType::match_prefix] and
+/// [UserType::matches].
+///
+/// `targets` optionally holds the type each [crate::Generator] should emit in place of `name`,
+/// keyed by the name the generator is registered under, e.g. `{ "rust": "uuid::Uuid" }`. See
+/// [crate::view::UserTypeTargets].
+///
+/// If `glob` is set, each `*` in `parse` matches any type path, and the text it matched replaces
+/// `{1}`, `{2}`, etc. in `name` and `targets`, in order. For example, this maps every type under
+/// `crate::ffi` to a user type of its own:
+/// ```json
+/// { "parse": "crate::ffi::*", "name": "ffi_{1}", "glob": true, "targets": { "rust": "ffi::{1}" } }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UserType {
+    pub parse: String,
+    pub name: UserTypeName,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub glob: bool,
+}
+
+impl UserType {
+    /// The type the generator registered as `generator` should emit for this [UserType], if any.
+    /// For `glob` types this still contains the `{1}`, `{2}`, etc. placeholders.
+    pub fn target(&self, generator: &str) -> Option<&str> {
+        self.targets.get(generator).map(String::as_str)
+    }
+
+    /// Matches `parse` against the start of `input`. Returns the length of the match in bytes and
+    /// the name of the user type, with any placeholders replaced.
+    pub fn match_prefix(&self, input: &str) -> Option<(usize, UserTypeName)> {
+        self.match_parse(input, false)
+    }
+
+    /// Matches `parse` against all of `input`, returning the name of the user type with any
+    /// placeholders replaced.
+    pub fn matches(&self, input: &str) -> Option<UserTypeName> {
+        self.match_parse(input, true).map(|(_, name)| name)
+    }
+
+    fn match_parse(&self, input: &str, anchored: bool) -> Option<(usize, UserTypeName)> {
+        if !self.glob {
+            let is_match = if anchored {
+                input == self.parse
+            } else {
+                input.starts_with(&self.parse)
+            };
+            return is_match.then(|| (self.parse.len(), self.name.clone()));
+        }
+        let mut captures = BTreeMap::new();
+        let len = match_tokens(
+            &glob_tokens(&self.parse),
+            input,
+            is_path_char,
+            anchored,
+            &mut captures,
+        )?;
+        Some((len, substitute(&self.name, &captures)))
+    }
+}
+
+/// Part of a `parse` glob or a `name` template of a [UserType].
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Token<'t> {
+    Literal(&'t str),
+    /// A `*` or `{n}`, with the (1-based) index of the capture.
+    Capture(usize),
+}
+
+fn glob_tokens(glob: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    for (i, literal) in glob.split('*').enumerate() {
+        if i > 0 {
+            tokens.push(Token::Capture(i));
+        }
+        if !literal.is_empty() {
+            tokens.push(Token::Literal(literal));
+        }
+    }
+    tokens
+}
+
+/// Braces that don't hold a number are literal.
+fn template_tokens(template: &str) -> Vec<Token<'_>> {
+    let mut tokens = vec![];
+    let mut literal_start = 0;
+    let mut rest_start = 0;
+    while let Some(open) = template[rest_start..].find('{').map(|i| rest_start + i) {
+        let capture = template[open + 1..].find('}').and_then(|len| {
+            let index = template[open + 1..open + 1 + len].parse::<usize>().ok()?;
+            Some((index, open + len + 2))
+        });
+        match capture {
+            Some((index, end)) => {
+                if literal_start < open {
+                    tokens.push(Token::Literal(&template[literal_start..open]));
+                }
+                tokens.push(Token::Capture(index));
+                literal_start = end;
+                rest_start = end;
+            }
+            None => rest_start = open + 1,
+        }
+    }
+    if literal_start < template.len() {
+        tokens.push(Token::Literal(&template[literal_start..]));
+    }
+    tokens
+}
+
+/// Replaces the `{n}` placeholders of `template` with `captures`. Placeholders without a capture
+/// are removed.
+pub(crate) fn substitute(template: &str, captures: &BTreeMap<usize, &str>) -> String {
+    template_tokens(template)
+        .into_iter()
+        .map(|token| match token {
+            Token::Literal(literal) => literal,
+            Token::Capture(index) => captures.get(&index).copied().unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Matches the `{n}` placeholders of `template` against all of `input`, e.g. `ffi_{1}` against
+/// `ffi_Handle` captures `Handle`. The inverse of [substitute].
+pub(crate) fn template_captures<'i>(
+    template: &str,
+    input: &'i str,
+) -> Option<BTreeMap<usize, &'i str>> {
+    let mut captures = BTreeMap::new();
+    match_tokens(
+        &template_tokens(template),
+        input,
+        |_| true,
+        true,
+        &mut captures,
+    )?;
+    Some(captures)
+}
+
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == ':'
+}
+
+/// Matches `tokens` against the start of `input`, or all of it if `anchored`, returning the length
+/// of the match. Each capture matches one or more `capture_chars`, preferring the longest match.
+/// A capture that appears more than once must match the same text each time.
+fn match_tokens<'i>(
+    tokens: &[Token],
+    input: &'i str,
+    capture_chars: fn(char) -> bool,
+    anchored: bool,
+    captures: &mut BTreeMap<usize, &'i str>,
+) -> Option<usize> {
+    let (token, rest) = match tokens.split_first() {
+        None => return (!anchored || input.is_empty()).then_some(0),
+        Some(split) => split,
+    };
+    let index = match token {
+        Token::Literal(literal) => {
+            let remaining = input.strip_prefix(literal)?;
+            return match_tokens(rest, remaining, capture_chars, anchored, captures)
+                .map(|len| literal.len() + len);
+        }
+        Token::Capture(index) => *index,
+    };
+    if let Some(captured) = captures.get(&index).copied() {
+        let remaining = input.strip_prefix(captured)?;
+        return match_tokens(rest, remaining, capture_chars, anchored, captures)
+            .map(|len| captured.len() + len);
+    }
+
+    let ends = input
+        .char_indices()
+        .take_while(|(_, c)| capture_chars(*c))
+        .map(|(i, c)| i + c.len_utf8())
+        .collect::<Vec<_>>();
+    for end in ends.into_iter().rev() {
+        captures.insert(index, &input[..end]);
+        if let Some(len) = match_tokens(rest, &input[end..], capture_chars, anchored, captures) {
+            return Some(end + len);
+        }
+        captures.remove(&index);
+    }
+    None
+}
+
+impl Config {
+    /// Parses a [Config] in json format. Errors include the path of the offending key, e.g.
+    /// `user_types[0].name`.
+    pub fn from_json(data: &str) -> Result<Self> {
+        let deserializer = &mut serde_json::Deserializer::from_str(data);
+        serde_path_to_error::deserialize(deserializer)
+            .map_err(|err| anyhow!("invalid config at '{}': {}", err.path(), err.inner()))
+    }
+
+    /// JSON schema of the [Config], so that tooling can validate and document config files.
+    /// Schemas of each section are available from [crate::Registry::parser_entries] and
+    /// [crate::Registry::generator_entries].
+    pub fn json_schema() -> serde_json::Value {
+        json_schema::<Config>()
+    }
+
+    /// Deserializes the `parser.<name>` section into `T`, or returns `T::default()` if there is no
+    /// such section. Errors include the path of the offending key, e.g. `parser.rust.some_key`.
+    pub fn parser_options<T: DeserializeOwned + Default>(&self, name: &str) -> Result<T> {
+        section_options(&self.parser, "parser", name)
+    }
+
+    /// Deserializes the `generator.<name>` section into `T`, or returns `T::default()` if there is
+    /// no such section. Errors include the path of the offending key, e.g.
+    /// `generator.rust.some_key`.
+    pub fn generator_options<T: DeserializeOwned + Default>(&self, name: &str) -> Result<T> {
+        section_options(&self.generator, "generator", name)
+    }
+}
+
+/// JSON schema of any `T`, e.g. a component's options.
+pub fn json_schema<T: JsonSchema>() -> serde_json::Value {
+    serde_json::to_value(schemars::schema_for!(T))
+        .expect("schemas are always representable as json")
+}
+
+fn section_options<T: DeserializeOwned + Default>(
+    sections: &Sections,
+    kind: &str,
+    name: &str,
+) -> Result<T> {
+    let value = match sections.get(name) {
+        None => return Ok(T::default()),
+        Some(value) => value,
+    };
+    serde_path_to_error::deserialize(value).map_err(|err| {
+        let path = err.path().to_string();
+        // The root path is displayed as ".".
+        let key = if path == "." {
+            format!("{}.{}", kind, name)
+        } else {
+            format!("{}.{}.{}", kind, name, path)
+        };
+        anyhow!("invalid config at '{}': {}", key, err.inner())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde::Deserialize;
+
+    use crate::config::Config;
+
+    #[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+    #[serde(default, deny_unknown_fields)]
+    struct Options {
+        flag: bool,
+        nested: Nested,
+    }
+
+    #[derive(Debug, Default, Deserialize, Eq, PartialEq)]
+    #[serde(default, deny_unknown_fields)]
+    struct Nested {
+        count: u32,
+    }
+
+    #[test]
+    fn sections() -> Result<()> {
+        let config = Config::from_json(
+            r#"{
+                "parser": { "a": { "flag": true } },
+                "generator": { "b": { "nested": { "count": 2 } } }
+            }"#,
+        )?;
+        assert!(config.parser_options::<Options>("a")?.flag);
+        assert_eq!(
+            config.generator_options::<Options>("b")?.nested,
+            Nested { count: 2 }
+        );
+        assert_eq!(
+            config.generator_options::<Options>("missing")?,
+            Options::default()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn section_errors_point_at_key() -> Result<()> {
+        let config = Config::from_json(
+            r#"{
+                "parser": { "a": { "flg": true } },
+                "generator": { "b": { "nested": { "count": "two" } } }
+            }"#,
+        )?;
+        let err = config.parser_options::<Options>("a").unwrap_err();
+        assert!(err.to_string().contains("'parser.a.flg'"), "{}", err);
+        let err = config.generator_options::<Options>("b").unwrap_err();
+        assert!(
+            err.to_string().contains("'generator.b.nested.count'"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn user_type_targets() -> Result<()> {
+        let config = Config::from_json(
+            r#"{
+                "user_types": [
+                    { "parse": "Uuid", "name": "uuid", "targets": { "rust": "uuid::Uuid" } },
+                    { "parse": "Url", "name": "url" }
+                ]
+            }"#,
+        )?;
+        assert_eq!(config.user_types[0].target("rust"), Some("uuid::Uuid"));
+        assert_eq!(config.user_types[0].target("typescript"), None);
+        assert_eq!(config.user_types[1].target("rust"), None);
+        Ok(())
+    }
+
+    mod user_type {
+        use std::collections::BTreeMap;
+
+        use crate::config::{substitute, template_captures, UserType};
+
+        fn user_type(parse: &str, name: &str, glob: bool) -> UserType {
+            UserType {
+                parse: parse.to_string(),
+                name: name.to_string(),
+                targets: Default::default(),
+                glob,
+            }
+        }
+
+        #[test]
+        fn exact() {
+            let ty = user_type("Uuid", "uuid", false);
+            assert_eq!(ty.matches("Uuid"), Some("uuid".to_string()));
+            assert_eq!(ty.matches("Uuids"), None);
+            assert_eq!(ty.match_prefix("Uuid, b"), Some((4, "uuid".to_string())));
+            assert_eq!(ty.match_prefix("Url"), None);
+        }
+
+        #[test]
+        fn glob() {
+            let ty = user_type("crate::ffi::*", "ffi_{1}", true);
+            assert_eq!(
+                ty.matches("crate::ffi::raw::Handle"),
+                Some("ffi_raw::Handle".to_string())
+            );
+            assert_eq!(ty.matches("crate::other::Handle"), None);
+            assert_eq!(
+                ty.match_prefix("crate::ffi::Handle>, b: u8"),
+                Some((18, "ffi_Handle".to_string()))
+            );
+        }
+
+        #[test]
+        fn glob_backtracks() {
+            let ty = user_type("*::Id<*>", "{2}_{1}", true);
+            assert_eq!(ty.matches("a::b::Id<u8>"), Some("u8_a::b".to_string()));
+            assert_eq!(ty.matches("Id<u8>"), None);
+        }
+
+        #[test]
+        fn templates() {
+            let captures = BTreeMap::from([(1, "a"), (2, "b")]);
+            assert_eq!(substitute("{2}.{1}{3}", &captures), "b.a");
+            assert_eq!(substitute("{x}{1}{", &captures), "{x}a{");
+
+            assert_eq!(
+                template_captures("ffi_{1}", "ffi_Handle"),
+                Some(BTreeMap::from([(1, "Handle")]))
+            );
+            assert_eq!(template_captures("ffi_{1}", "other"), None);
+            assert_eq!(
+                template_captures("{1}_{1}", "a_a"),
+                Some(BTreeMap::from([(1, "a")]))
+            );
+            assert_eq!(template_captures("{1}_{1}", "a_b"), None);
+        }
+    }
+
+    #[test]
+    fn config_errors_point_at_key() {
+        let err =
+            Config::from_json(r#"{ "user_types": [{ "parse": "a", "nam": "b" }] }"#).unwrap_err();
+        assert!(err.to_string().contains("'user_types[0]"), "{}", err);
+        assert!(err.to_string().contains("nam"), "{}", err);
+    }
+}
@@ -0,0 +1,181 @@
+use itertools::Itertools;
+
+use crate::model::{EntityId, EntityType, Model, NamespaceChild};
+use crate::view::EntityIdFilter;
+use crate::{Diagnostic, Diagnostics};
+
+/// Finds the dtos and enums that nothing in the API uses, so they can be cleaned up. When set on
+/// an [crate::Executor::dead_types], each one is reported as a warning, and can optionally be
+/// left out of the views passed to every [crate::Generator].
+///
+/// A type is used if it's reachable from a root through param, return, error and field types,
+/// including within arrays, maps and optionals. Every rpc is a root, as is every dto with methods.
+/// Types that are only used outside of the API, e.g. as the root message of a stream, can be
+/// added as extra [DeadTypes::root]s, as can whole namespaces.
+///
+/// ```ignore
+/// Executor::new(input::Glob::new("src/**/*.rs")?, parser::Rust::default())
+///     .dead_types(DeadTypes::default().root(EntityId::try_from("events")?).filter(true))
+///     .generator(generator::Rust::default())
+///     .output(output::FileSet::new("generated")?)
+///     .execute()?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DeadTypes {
+    roots: Vec<EntityId>,
+    filter: bool,
+}
+
+impl DeadTypes {
+    /// Treat the entity with `entity_id`, and everything within it, as used.
+    pub fn root(mut self, entity_id: EntityId) -> Self {
+        self.roots.push(entity_id);
+        self
+    }
+
+    /// Leave the dead types out of the views passed to generators, rather than only reporting
+    /// them. Namespaces left empty are left out as well. See [DeadTypes::view_filter].
+    pub fn filter(mut self, filter: bool) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn is_filter(&self) -> bool {
+        self.filter
+    }
+
+    /// The ids of the dead dtos and enums in the `model`, in the order they appear in the API.
+    pub fn find(&self, model: &Model) -> Vec<EntityId> {
+        let dependencies = model.dependencies();
+        let roots = dependencies
+            .entity_ids()
+            .filter(|id| self.is_root(model, id))
+            .collect_vec();
+        let reachable = dependencies.reachable_from(roots);
+        dependencies
+            .entity_ids()
+            .filter(|id| !reachable.contains(id))
+            .cloned()
+            .collect_vec()
+    }
+
+    /// A warning for each dead type in the `model`, pointing at the chunk that defines it.
+    pub fn check(&self, model: &Model) -> Diagnostics {
+        self.find(model)
+            .into_iter()
+            .map(|id| {
+                let chunk = model
+                    .api()
+                    .find_child(&id)
+                    .and_then(|child| child.attributes().chunk.as_ref());
+                Diagnostic::warning(format!("'{}' is never used by the API", id))
+                    .chunk_attr(chunk)
+                    .note(format!("if used elsewhere, add '{}' as a root", id))
+            })
+            .collect()
+    }
+
+    /// A view filter that excludes the dead types in the `model`.
+    pub fn view_filter(&self, model: &Model) -> EntityIdFilter {
+        self.find(model)
+            .into_iter()
+            .fold(EntityIdFilter::default(), EntityIdFilter::exclude)
+    }
+
+    fn is_root(&self, model: &Model, entity_id: &EntityId) -> bool {
+        if self.roots.iter().any(|root| entity_id.starts_with(root)) {
+            return true;
+        }
+        match model.api().find_child(entity_id) {
+            Some(NamespaceChild::Dto(dto)) => !dto.rpcs.is_empty(),
+            Some(child) => child.entity_type() == EntityType::Rpc,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::model::EntityId;
+    use crate::testing::TestExecutor;
+    use crate::view::{Namespace, Transformer};
+    use crate::DeadTypes;
+
+    const API: &str = r#"
+        struct Request { kind: Kind }
+        struct Response { items: Vec<Item> }
+        struct Item {}
+        enum Kind {}
+        struct Unused { item: Item }
+        struct Service {}
+        impl Service {
+            fn status(&self) -> Status {}
+        }
+        enum Status {}
+        mod events {
+            struct Event {}
+        }
+        fn rpc(request: Request) -> Option<Response> {}
+    "#;
+
+    fn find(dead_types: DeadTypes) -> Vec<String> {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        dead_types
+            .find(&model)
+            .iter()
+            .map(ToString::to_string)
+            .collect_vec()
+    }
+
+    #[test]
+    fn finds_unreachable_types() {
+        assert_eq!(
+            find(DeadTypes::default()),
+            vec!["dto:Unused", "events.dto:Event"]
+        );
+    }
+
+    #[test]
+    fn roots() {
+        assert_eq!(
+            find(DeadTypes::default().root(EntityId::try_from("events").unwrap())),
+            vec!["dto:Unused"]
+        );
+        assert!(find(
+            DeadTypes::default()
+                .root(EntityId::try_from("events").unwrap())
+                .root(EntityId::try_from("d:Unused").unwrap())
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn check() {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let diagnostics = DeadTypes::default().check(&model);
+        assert_eq!(diagnostics.warnings().count(), 2);
+        assert!(!diagnostics.has_errors());
+    }
+
+    #[test]
+    fn view_filter() {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let filter = DeadTypes::default().view_filter(&model);
+        let view = model.view().with_namespace_transform(filter);
+        let names = |namespace: &Namespace| {
+            namespace
+                .children()
+                .map(|child| child.name().to_string())
+                .collect_vec()
+        };
+        assert_eq!(
+            names(&view.api()),
+            vec!["Request", "Response", "Item", "Kind", "Service", "Status", "rpc"]
+        );
+    }
+}
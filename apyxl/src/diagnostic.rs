@@ -0,0 +1,342 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{chunk, Chunk, ValidationError};
+
+/// A problem found while running apyxl, e.g. a parse error or an invalid API. [Diagnostic]s from
+/// the parse, validation and generation phases are collected by the [crate::Executor].
+///
+/// The [Display] format is meant for terminals, e.g.
+/// ```text
+/// error: unexpected '{'
+///   --> api/dto.rs:3:12
+///    |
+///  3 | struct dto {
+///    |            ^
+///    = note: errors encountered while parsing
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// The [Chunk] the diagnostic refers to, if any.
+    pub chunk: Option<PathBuf>,
+    /// Location within the [Chunk]'s data, if known.
+    pub span: Option<Span>,
+    /// Additional related information.
+    pub notes: Vec<String>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Controls whether problems that apyxl can work around are errors or warnings.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Mode {
+    /// Unknown syntax, unresolved types and duplicate definitions are errors.
+    #[default]
+    Strict,
+
+    /// Unknown syntax is skipped, unresolved types become [crate::model::Type::User] types, and
+    /// only the first of any duplicate definitions is kept. Each is reported as a warning and
+    /// output is generated for everything else.
+    Lenient,
+}
+
+/// A location within a [Chunk]'s data.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Span {
+    /// Byte range within the data.
+    pub range: Range<usize>,
+    /// 1-based line of the start of the range.
+    pub line: usize,
+    /// 1-based column, in chars, of the start of the range.
+    pub column: usize,
+    /// The full line of data containing the start of the range, without the line ending.
+    pub source_line: String,
+}
+
+/// A collection of [Diagnostic]s. Implements [std::error::Error] so that it can be returned
+/// through [anyhow::Error] and accessed again with [anyhow::Error::downcast_ref].
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Diagnostics {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl ToString) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl ToString) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    pub fn new(severity: Severity, message: impl ToString) -> Self {
+        Self {
+            severity,
+            message: message.to_string(),
+            chunk: None,
+            span: None,
+            notes: vec![],
+        }
+    }
+
+    /// Set the [Chunk] this refers to. Ignored if the [Chunk] has no path.
+    pub fn chunk(mut self, chunk: &Chunk) -> Self {
+        self.chunk = chunk.relative_file_path.clone();
+        self
+    }
+
+    /// Set the chunk this refers to from an entity's [chunk::Attribute], if it has one.
+    pub fn chunk_attr(mut self, attr: Option<&chunk::Attribute>) -> Self {
+        self.chunk = attr.and_then(|attr| attr.relative_file_paths.first().cloned());
+        self
+    }
+
+    pub fn span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn note(mut self, note: impl ToString) -> Self {
+        self.notes.push(note.to_string());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Span {
+    /// Creates a [Span] for the byte `range` within `data`. The range is clamped to the data.
+    pub fn new(data: &str, range: Range<usize>) -> Self {
+        let mut start = range.start.min(data.len());
+        while !data.is_char_boundary(start) {
+            start -= 1;
+        }
+        let line_start = data[..start].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = data[start..].find('\n').map_or(data.len(), |i| start + i);
+        Self {
+            range,
+            line: data[..start].matches('\n').count() + 1,
+            column: data[line_start..start].chars().count() + 1,
+            source_line: data[line_start..line_end]
+                .trim_end_matches('\r')
+                .to_string(),
+        }
+    }
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic)
+    }
+
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.diagnostics.extend(other.diagnostics)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics.iter()
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter().filter(|diagnostic| diagnostic.is_error())
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.iter().filter(|diagnostic| !diagnostic.is_error())
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.diagnostics.len()
+    }
+
+    /// Converts any error into [Diagnostics]. If the error already is [Diagnostics] they are
+    /// returned as-is, otherwise the error and its causes become a single [Diagnostic].
+    pub fn from_error(err: &anyhow::Error) -> Self {
+        match err.downcast_ref::<Diagnostics>() {
+            Some(diagnostics) => diagnostics.clone(),
+            None => Diagnostic::error(format!("{:#}", err)).into(),
+        }
+    }
+}
+
+impl From<Diagnostic> for Diagnostics {
+    fn from(value: Diagnostic) -> Self {
+        Self {
+            diagnostics: vec![value],
+        }
+    }
+}
+
+impl FromIterator<Diagnostic> for Diagnostics {
+    fn from_iter<T: IntoIterator<Item = Diagnostic>>(iter: T) -> Self {
+        Self {
+            diagnostics: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Diagnostic;
+    type IntoIter = std::vec::IntoIter<Diagnostic>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.diagnostics.into_iter()
+    }
+}
+
+impl From<&ValidationError> for Diagnostic {
+    fn from(value: &ValidationError) -> Self {
+        Diagnostic::error(value).note("API validation failed")
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}: {}", self.severity, self.message)?;
+        let line_number_width = self
+            .span
+            .as_ref()
+            .map_or(0, |span| span.line.to_string().len());
+        let pad = " ".repeat(line_number_width);
+        match (&self.chunk, &self.span) {
+            (Some(chunk), Some(span)) => writeln!(
+                f,
+                "{} --> {}:{}:{}",
+                pad,
+                chunk.display(),
+                span.line,
+                span.column
+            )?,
+            (Some(chunk), None) => writeln!(f, "{} --> {}", pad, chunk.display())?,
+            (None, Some(span)) => writeln!(f, "{} --> {}:{}", pad, span.line, span.column)?,
+            (None, None) => {}
+        }
+        if let Some(span) = &self.span {
+            let underline = span.range.len().clamp(
+                1,
+                span.source_line
+                    .len()
+                    .saturating_sub(span.column - 1)
+                    .max(1),
+            );
+            writeln!(f, "{}  |", pad)?;
+            writeln!(f, " {} | {}", span.line, span.source_line)?;
+            writeln!(
+                f,
+                "{}  | {}{}",
+                pad,
+                " ".repeat(span.column - 1),
+                "^".repeat(underline)
+            )?;
+        }
+        for note in &self.notes {
+            writeln!(f, "{}  = note: {}", pad, note)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for Diagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for diagnostic in &self.diagnostics {
+            writeln!(f, "{}", diagnostic)?;
+        }
+        let errors = self.errors().count();
+        let warnings = self.len() - errors;
+        write!(f, "{} error(s), {} warning(s)", errors, warnings)
+    }
+}
+
+impl std::error::Error for Diagnostics {}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostic::{Diagnostic, Diagnostics, Span};
+    use crate::model::Chunk;
+
+    #[test]
+    fn span_location() {
+        let span = Span::new("a\nbc de\nf", 5..7);
+        assert_eq!(span.line, 2);
+        assert_eq!(span.column, 4);
+        assert_eq!(span.source_line, "bc de");
+    }
+
+    #[test]
+    fn span_past_end() {
+        let span = Span::new("abc", 10..11);
+        assert_eq!(span.line, 1);
+        assert_eq!(span.column, 4);
+        assert_eq!(span.source_line, "abc");
+    }
+
+    #[test]
+    fn render() {
+        let diagnostic = Diagnostic::error("unexpected 'x'")
+            .chunk(&Chunk::with_relative_file_path("a/b.rs"))
+            .span(Span::new("struct x {}", 7..8))
+            .note("errors encountered while parsing");
+        assert_eq!(
+            diagnostic.to_string(),
+            r#"error: unexpected 'x'
+  --> a/b.rs:1:8
+   |
+ 1 | struct x {}
+   |        ^
+   = note: errors encountered while parsing
+"#
+        );
+    }
+
+    #[test]
+    fn downcast_from_anyhow() {
+        let diagnostics =
+            Diagnostics::from_iter([Diagnostic::warning("w"), Diagnostic::error("e")]);
+        let err = anyhow::Error::from(diagnostics.clone());
+        assert_eq!(Diagnostics::from_error(&err), diagnostics);
+        assert!(diagnostics.has_errors());
+        assert_eq!(diagnostics.warnings().count(), 1);
+    }
+
+    #[test]
+    fn from_other_error() {
+        let err = anyhow::anyhow!("inner").context("outer");
+        let diagnostics = Diagnostics::from_error(&err);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics.iter().next().unwrap().message, "outer: inner");
+    }
+}
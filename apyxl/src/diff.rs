@@ -0,0 +1,725 @@
+use std::fmt::{Display, Formatter};
+
+use crate::model::{
+    Api, Attributes, Dto, EntityId, EntityType, Enum, EnumValueNumber, Field, Namespace, Rpc, Type,
+};
+
+/// A single difference between two versions of an API, as found by [diff].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Change {
+    /// Fully qualified id of the changed entity. Changes to enum values are attributed to their
+    /// enum, since enum values don't have ids.
+    pub entity_id: EntityId,
+    pub subject: Subject,
+    pub kind: ChangeKind,
+    /// How the change affects existing users of the API, see [Change::is_breaking].
+    pub impact: Impact,
+}
+
+/// How a [Change] affects existing users of the API. Ordered from least to most severe, and
+/// corresponds to the patch, minor and major versions of semantic versioning.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Impact {
+    /// Doesn't change the API itself, e.g. changed comments.
+    Cosmetic,
+    /// Extends the API in a compatible way, e.g. a new rpc or optional field.
+    Additive,
+    /// Can break existing users of the API, see [Change::is_breaking].
+    Breaking,
+}
+
+/// What kind of entity a [Change] is about.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Subject {
+    Dto,
+    /// A field of a [crate::model::Dto].
+    Field,
+    Rpc,
+    /// A param of a [crate::model::Rpc].
+    Param,
+    Enum,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ChangeKind {
+    /// The entity only exists in the new API. Children of an added entity are not listed
+    /// separately.
+    Added,
+    /// The entity only exists in the old API. Children of a removed entity are not listed
+    /// separately.
+    Removed,
+    /// The type of a field or param changed.
+    TypeChanged { old: Type, new: Type },
+    /// The return type of an rpc changed.
+    ReturnTypeChanged {
+        old: Option<Type>,
+        new: Option<Type>,
+    },
+    /// The error type of an rpc changed, see [crate::model::Rpc::error_type].
+    ErrorTypeChanged {
+        old: Option<Type>,
+        new: Option<Type>,
+    },
+    EnumValueAdded {
+        name: String,
+        number: EnumValueNumber,
+    },
+    EnumValueRemoved {
+        name: String,
+        number: EnumValueNumber,
+    },
+    /// An enum value with the same name has a different number.
+    EnumValueRenumbered {
+        name: String,
+        old: EnumValueNumber,
+        new: EnumValueNumber,
+    },
+    /// The comments of the entity changed. Changed comments of enum values are attributed to
+    /// their enum.
+    CommentsChanged,
+}
+
+impl Change {
+    /// Whether the change can break existing users of the API, i.e. code generated from the old
+    /// API or data serialized with it. This is the compatibility analysis used by
+    /// [crate::Baseline].
+    ///
+    /// Breaking changes are:
+    /// - Removing any dto, field, rpc, param, enum or enum value.
+    /// - Adding a field that isn't optional, since existing data doesn't have it.
+    /// - Adding an rpc param, since callers must pass it.
+    /// - Changing the type of a field or param, or the return or error type of an rpc.
+    /// - Changing the number of an enum value.
+    ///
+    /// Adding dtos, optional fields, rpcs, enums and enum values is [Impact::Additive], and
+    /// changing comments is [Impact::Cosmetic].
+    pub fn is_breaking(&self) -> bool {
+        self.impact == Impact::Breaking
+    }
+
+    fn new(entity_id: EntityId, subject: Subject, kind: ChangeKind) -> Self {
+        let impact = match kind {
+            ChangeKind::Added if subject == Subject::Param => Impact::Breaking,
+            ChangeKind::Added | ChangeKind::EnumValueAdded { .. } => Impact::Additive,
+            ChangeKind::CommentsChanged => Impact::Cosmetic,
+            ChangeKind::Removed
+            | ChangeKind::TypeChanged { .. }
+            | ChangeKind::ReturnTypeChanged { .. }
+            | ChangeKind::ErrorTypeChanged { .. }
+            | ChangeKind::EnumValueRemoved { .. }
+            | ChangeKind::EnumValueRenumbered { .. } => Impact::Breaking,
+        };
+        Self {
+            entity_id,
+            subject,
+            kind,
+            impact,
+        }
+    }
+}
+
+/// Compatibility report between two versions of an API, classifying every [Change] by its
+/// [Impact] so that releases can be gated on it, e.g. requiring a major version for breaking
+/// changes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BreakingChange {
+    /// Every change, in the order of [diff].
+    pub changes: Vec<Change>,
+}
+
+impl BreakingChange {
+    pub fn new(old: &Api, new: &Api) -> Self {
+        Self {
+            changes: diff(old, new),
+        }
+    }
+
+    /// The most severe [Impact] of any change, or `None` if nothing changed, i.e. the part of the
+    /// semantic version that must be bumped.
+    pub fn impact(&self) -> Option<Impact> {
+        self.changes.iter().map(|change| change.impact).max()
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.impact() == Some(Impact::Breaking)
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &Change> {
+        self.with_impact(Impact::Breaking)
+    }
+
+    pub fn additive(&self) -> impl Iterator<Item = &Change> {
+        self.with_impact(Impact::Additive)
+    }
+
+    pub fn cosmetic(&self) -> impl Iterator<Item = &Change> {
+        self.with_impact(Impact::Cosmetic)
+    }
+
+    fn with_impact(&self, impact: Impact) -> impl Iterator<Item = &Change> {
+        self.changes
+            .iter()
+            .filter(move |change| change.impact == impact)
+    }
+}
+
+/// A short human-readable description, e.g. ``Field `user.User.id`: `u32` → `u64` ``.
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let subject = match self.subject {
+            Subject::Dto => "DTO",
+            Subject::Field => "Field",
+            Subject::Rpc => "RPC",
+            Subject::Param => "Param",
+            Subject::Enum => "Enum",
+        };
+        let path = path(&self.entity_id);
+        match &self.kind {
+            ChangeKind::Added | ChangeKind::Removed => write!(f, "{} `{}`", subject, path),
+            ChangeKind::TypeChanged { old, new } => write!(
+                f,
+                "{} `{}`: `{}` → `{}`",
+                subject,
+                path,
+                type_name(old),
+                type_name(new)
+            ),
+            ChangeKind::ReturnTypeChanged { old, new } => write!(
+                f,
+                "{} `{}` return type: {} → {}",
+                subject,
+                path,
+                return_type_name(old),
+                return_type_name(new)
+            ),
+            ChangeKind::ErrorTypeChanged { old, new } => write!(
+                f,
+                "{} `{}` error type: {} → {}",
+                subject,
+                path,
+                return_type_name(old),
+                return_type_name(new)
+            ),
+            ChangeKind::EnumValueAdded { name, number }
+            | ChangeKind::EnumValueRemoved { name, number } => {
+                write!(f, "Enum value `{}.{}` = {}", path, name, number)
+            }
+            ChangeKind::EnumValueRenumbered { name, old, new } => {
+                write!(f, "Enum value `{}.{}`: {} → {}", path, name, old, new)
+            }
+            ChangeKind::CommentsChanged => write!(f, "{} `{}` comments", subject, path),
+        }
+    }
+}
+
+/// Structural differences between the `old` and `new` versions of a built API, in the order of
+/// the `new` API with removals listed before additions within each namespace, dto, rpc or enum.
+///
+/// Entities are matched by their fully qualified id, so a renamed or moved entity is reported as
+/// removed and added. Namespaces are not reported themselves: the dtos, rpcs and enums within an
+/// added or removed namespace are. Comments are compared, but other attributes are ignored.
+pub fn diff(old: &Api, new: &Api) -> Vec<Change> {
+    let mut changes = vec![];
+    diff_namespace(Some(old), Some(new), &EntityId::default(), &mut changes);
+    changes
+}
+
+fn diff_namespace(
+    old: Option<&Namespace>,
+    new: Option<&Namespace>,
+    namespace_id: &EntityId,
+    changes: &mut Vec<Change>,
+) {
+    // unwraps ok here because we're iterating known children.
+    let child_id = |ty, name: &str| namespace_id.child(ty, name).unwrap();
+    let old_dtos = old.into_iter().flat_map(Namespace::dtos);
+    let new_dtos = new.into_iter().flat_map(Namespace::dtos);
+    for (old_dto, new_dto) in pairs(old_dtos, new_dtos, |dto| dto.name) {
+        let dto_id = child_id(EntityType::Dto, name(old_dto, new_dto, |dto| dto.name));
+        match (old_dto, new_dto) {
+            (Some(old_dto), Some(new_dto)) => diff_dto(old_dto, new_dto, &dto_id, changes),
+            (old_dto, _) => push_presence(dto_id, Subject::Dto, old_dto.is_some(), changes),
+        }
+    }
+
+    let old_rpcs = old.into_iter().flat_map(Namespace::rpcs);
+    let new_rpcs = new.into_iter().flat_map(Namespace::rpcs);
+    for (old_rpc, new_rpc) in pairs(old_rpcs, new_rpcs, |rpc| rpc.name) {
+        let rpc_id = child_id(EntityType::Rpc, name(old_rpc, new_rpc, |rpc| rpc.name));
+        match (old_rpc, new_rpc) {
+            (Some(old_rpc), Some(new_rpc)) => diff_rpc(old_rpc, new_rpc, &rpc_id, changes),
+            (old_rpc, _) => push_presence(rpc_id, Subject::Rpc, old_rpc.is_some(), changes),
+        }
+    }
+
+    let old_enums = old.into_iter().flat_map(Namespace::enums);
+    let new_enums = new.into_iter().flat_map(Namespace::enums);
+    for (old_en, new_en) in pairs(old_enums, new_enums, |en| en.name) {
+        let en_id = child_id(EntityType::Enum, name(old_en, new_en, |en| en.name));
+        match (old_en, new_en) {
+            (Some(old_en), Some(new_en)) => diff_enum(old_en, new_en, &en_id, changes),
+            (old_en, _) => push_presence(en_id, Subject::Enum, old_en.is_some(), changes),
+        }
+    }
+
+    let old_namespaces = old.into_iter().flat_map(Namespace::namespaces);
+    let new_namespaces = new.into_iter().flat_map(Namespace::namespaces);
+    for (old_ns, new_ns) in pairs(old_namespaces, new_namespaces, |ns| ns.name.as_ref()) {
+        let ns_id = child_id(EntityType::Namespace, name(old_ns, new_ns, |ns| &ns.name));
+        diff_namespace(old_ns, new_ns, &ns_id, changes);
+    }
+}
+
+fn diff_dto(old: &Dto, new: &Dto, dto_id: &EntityId, changes: &mut Vec<Change>) {
+    diff_comments(
+        &old.attributes,
+        &new.attributes,
+        dto_id,
+        Subject::Dto,
+        changes,
+    );
+    diff_fields(&old.fields, &new.fields, dto_id, Subject::Field, changes);
+    for (old_rpc, new_rpc) in pairs(old.rpcs.iter(), new.rpcs.iter(), |rpc| rpc.name) {
+        let rpc_id = dto_id
+            .child(EntityType::Rpc, name(old_rpc, new_rpc, |rpc| rpc.name))
+            .unwrap();
+        match (old_rpc, new_rpc) {
+            (Some(old_rpc), Some(new_rpc)) => diff_rpc(old_rpc, new_rpc, &rpc_id, changes),
+            (old_rpc, _) => push_presence(rpc_id, Subject::Rpc, old_rpc.is_some(), changes),
+        }
+    }
+}
+
+fn diff_rpc(old: &Rpc, new: &Rpc, rpc_id: &EntityId, changes: &mut Vec<Change>) {
+    diff_comments(
+        &old.attributes,
+        &new.attributes,
+        rpc_id,
+        Subject::Rpc,
+        changes,
+    );
+    diff_fields(&old.params, &new.params, rpc_id, Subject::Param, changes);
+    if old.return_type != new.return_type {
+        changes.push(Change::new(
+            rpc_id.clone(),
+            Subject::Rpc,
+            ChangeKind::ReturnTypeChanged {
+                old: old.return_type.clone(),
+                new: new.return_type.clone(),
+            },
+        ));
+    }
+    if old.error_type != new.error_type {
+        changes.push(Change::new(
+            rpc_id.clone(),
+            Subject::Rpc,
+            ChangeKind::ErrorTypeChanged {
+                old: old.error_type.clone(),
+                new: new.error_type.clone(),
+            },
+        ));
+    }
+}
+
+fn diff_fields(
+    old: &[Field],
+    new: &[Field],
+    parent_id: &EntityId,
+    subject: Subject,
+    changes: &mut Vec<Change>,
+) {
+    for (old_field, new_field) in pairs(old.iter(), new.iter(), |field| field.name) {
+        let field_id = parent_id
+            .child(
+                EntityType::Field,
+                name(old_field, new_field, |field| field.name),
+            )
+            .unwrap();
+        match (old_field, new_field) {
+            (Some(old_field), Some(new_field)) => {
+                diff_comments(
+                    &old_field.attributes,
+                    &new_field.attributes,
+                    &field_id,
+                    subject,
+                    changes,
+                );
+                if old_field.ty != new_field.ty {
+                    changes.push(Change::new(
+                        field_id,
+                        subject,
+                        ChangeKind::TypeChanged {
+                            old: old_field.ty.clone(),
+                            new: new_field.ty.clone(),
+                        },
+                    ));
+                }
+            }
+            (None, Some(new_field))
+                if subject == Subject::Field && !matches!(new_field.ty, Type::Optional(_)) =>
+            {
+                let mut change = Change::new(field_id, subject, ChangeKind::Added);
+                change.impact = Impact::Breaking;
+                changes.push(change);
+            }
+            (old_field, _) => push_presence(field_id, subject, old_field.is_some(), changes),
+        }
+    }
+}
+
+fn diff_enum(old: &Enum, new: &Enum, en_id: &EntityId, changes: &mut Vec<Change>) {
+    let comments_changed = old.attributes.comments != new.attributes.comments
+        || old.values.iter().any(|old_value| {
+            new.value(old_value.name).is_some_and(|new_value| {
+                new_value.attributes.comments != old_value.attributes.comments
+            })
+        });
+    if comments_changed {
+        changes.push(Change::new(
+            en_id.clone(),
+            Subject::Enum,
+            ChangeKind::CommentsChanged,
+        ));
+    }
+    for (old_value, new_value) in pairs(old.values.iter(), new.values.iter(), |value| value.name) {
+        let kind = match (old_value, new_value) {
+            (Some(old_value), Some(new_value)) if old_value.number != new_value.number => {
+                ChangeKind::EnumValueRenumbered {
+                    name: new_value.name.to_string(),
+                    old: old_value.number,
+                    new: new_value.number,
+                }
+            }
+            (Some(_), Some(_)) => continue,
+            (Some(old_value), None) => ChangeKind::EnumValueRemoved {
+                name: old_value.name.to_string(),
+                number: old_value.number,
+            },
+            (None, Some(new_value)) => ChangeKind::EnumValueAdded {
+                name: new_value.name.to_string(),
+                number: new_value.number,
+            },
+            (None, None) => unreachable!("pairs always has at least one side"),
+        };
+        changes.push(Change::new(en_id.clone(), Subject::Enum, kind));
+    }
+}
+
+fn diff_comments(
+    old: &Attributes,
+    new: &Attributes,
+    entity_id: &EntityId,
+    subject: Subject,
+    changes: &mut Vec<Change>,
+) {
+    if old.comments != new.comments {
+        changes.push(Change::new(
+            entity_id.clone(),
+            subject,
+            ChangeKind::CommentsChanged,
+        ));
+    }
+}
+
+fn push_presence(entity_id: EntityId, subject: Subject, removed: bool, changes: &mut Vec<Change>) {
+    let kind = if removed {
+        ChangeKind::Removed
+    } else {
+        ChangeKind::Added
+    };
+    changes.push(Change::new(entity_id, subject, kind));
+}
+
+/// Matches up `old` and `new` entities by name. Entities only in `old` come first, followed by
+/// `new` entities in order, paired with the `old` entity of the same name if there is one.
+fn pairs<'e, T: 'e>(
+    old: impl Iterator<Item = &'e T>,
+    new: impl Iterator<Item = &'e T>,
+    name: impl Fn(&T) -> &str,
+) -> Vec<(Option<&'e T>, Option<&'e T>)> {
+    let old = old.collect::<Vec<_>>();
+    let new = new.collect::<Vec<_>>();
+    let removed = old
+        .iter()
+        .filter(|o| !new.iter().any(|n| name(n) == name(o)))
+        .map(|o| (Some(*o), None));
+    let kept_or_added = new
+        .iter()
+        .map(|n| (old.iter().find(|o| name(o) == name(n)).copied(), Some(*n)));
+    removed.chain(kept_or_added).collect()
+}
+
+fn name<'e, T>(old: Option<&'e T>, new: Option<&'e T>, name: impl Fn(&'e T) -> &'e str) -> &'e str {
+    new.or(old)
+        .map(name)
+        .expect("pairs always has at least one side")
+}
+
+/// Dot-separated names of the entity and its parents, e.g. `service.user.User.id`.
+fn path(entity_id: &EntityId) -> String {
+    entity_id.component_names().collect::<Vec<_>>().join(".")
+}
+
+fn return_type_name(ty: &Option<Type>) -> String {
+    match ty {
+        None => "none".to_string(),
+        Some(ty) => format!("`{}`", type_name(ty)),
+    }
+}
+
+/// A language-agnostic name for the `ty`.
+fn type_name(ty: &Type) -> String {
+    match ty {
+        Type::Bool => "bool".to_string(),
+        Type::U8 => "u8".to_string(),
+        Type::U16 => "u16".to_string(),
+        Type::U32 => "u32".to_string(),
+        Type::U64 => "u64".to_string(),
+        Type::U128 => "u128".to_string(),
+        Type::I8 => "i8".to_string(),
+        Type::I16 => "i16".to_string(),
+        Type::I32 => "i32".to_string(),
+        Type::I64 => "i64".to_string(),
+        Type::I128 => "i128".to_string(),
+        Type::F8 => "f8".to_string(),
+        Type::F16 => "f16".to_string(),
+        Type::F32 => "f32".to_string(),
+        Type::F64 => "f64".to_string(),
+        Type::F128 => "f128".to_string(),
+        Type::String => "string".to_string(),
+        Type::Bytes => "bytes".to_string(),
+        Type::User(name) => name.clone(),
+        Type::Api(id, args) if args.is_empty() => path(id),
+        Type::Api(id, args) => format!(
+            "{}<{}>",
+            path(id),
+            args.iter().map(type_name).collect::<Vec<_>>().join(", ")
+        ),
+        Type::Generic(name) => name.clone(),
+        Type::Array(ty) => format!("{}[]", type_name(ty)),
+        Type::Map { key, value } => format!("map<{}, {}>", type_name(key), type_name(value)),
+        Type::Optional(ty) => format!("{}?", type_name(ty)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diff::{diff, BreakingChange, Change, ChangeKind, Impact, Subject};
+    use crate::model::{EntityId, Type};
+    use crate::testing::TestExecutor;
+
+    fn run_test(old: &str, new: &str) -> Vec<Change> {
+        let mut old = TestExecutor::new(old);
+        let old = old.build();
+        let mut new = TestExecutor::new(new);
+        let new = new.build();
+        diff(old.api(), new.api())
+    }
+
+    fn change(id: &str, subject: Subject, kind: ChangeKind) -> Change {
+        Change::new(EntityId::try_from(id).unwrap(), subject, kind)
+    }
+
+    #[test]
+    fn unchanged() {
+        let api = "mod a { struct B { c: u32 } fn d(e: B) -> B {} enum F { G = 1 } }";
+        assert_eq!(run_test(api, api), vec![]);
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let changes = run_test(
+            "struct A {} struct B { x: u32 } fn c() {} mod old { enum D {} }",
+            "struct B { y: u32 } struct E {} fn c(z: u32) {} mod new { fn f() {} }",
+        );
+        assert_eq!(
+            changes,
+            vec![
+                change("d:A", Subject::Dto, ChangeKind::Removed),
+                change("d:B.f:x", Subject::Field, ChangeKind::Removed),
+                Change {
+                    impact: Impact::Breaking,
+                    ..change("d:B.f:y", Subject::Field, ChangeKind::Added)
+                },
+                change("d:E", Subject::Dto, ChangeKind::Added),
+                change("r:c.f:z", Subject::Param, ChangeKind::Added),
+                change("old.e:D", Subject::Enum, ChangeKind::Removed),
+                change("new.r:f", Subject::Rpc, ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn types_changed() {
+        let changes = run_test(
+            "struct A { x: u32 } fn b(y: u32) -> u32 {}",
+            "struct A { x: Vec<u64> } fn b(y: A) {}",
+        );
+        assert_eq!(
+            changes,
+            vec![
+                change(
+                    "d:A.f:x",
+                    Subject::Field,
+                    ChangeKind::TypeChanged {
+                        old: Type::U32,
+                        new: Type::Array(Box::new(Type::U64)),
+                    }
+                ),
+                change(
+                    "r:b.f:y",
+                    Subject::Param,
+                    ChangeKind::TypeChanged {
+                        old: Type::U32,
+                        new: Type::Api(EntityId::try_from("d:A").unwrap(), vec![]),
+                    }
+                ),
+                change(
+                    "r:b",
+                    Subject::Rpc,
+                    ChangeKind::ReturnTypeChanged {
+                        old: Some(Type::U32),
+                        new: None,
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn error_type_changed() {
+        let changes = run_test(
+            "fn a() -> Result<u32, String> {}",
+            "fn a() -> Result<u32, u32> {}",
+        );
+        assert_eq!(
+            changes,
+            vec![change(
+                "r:a",
+                Subject::Rpc,
+                ChangeKind::ErrorTypeChanged {
+                    old: Some(Type::String),
+                    new: Some(Type::U32),
+                }
+            )]
+        );
+        assert_eq!(
+            changes[0].to_string(),
+            "RPC `a` error type: `string` → `u32`"
+        );
+    }
+
+    #[test]
+    fn dto_methods() {
+        let changes = run_test(
+            "struct A {} impl A { fn b(&self) {} }",
+            "struct A {} impl A { fn b(&self, x: u32) {} fn c(&self) {} }",
+        );
+        assert_eq!(
+            changes,
+            vec![
+                change("d:A.r:b.f:x", Subject::Param, ChangeKind::Added),
+                change("d:A.r:c", Subject::Rpc, ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn enum_values() {
+        let changes = run_test("enum A { B = 1, C = 2 }", "enum A { C = 3, D = 4 }");
+        assert_eq!(
+            changes,
+            vec![
+                change(
+                    "e:A",
+                    Subject::Enum,
+                    ChangeKind::EnumValueRemoved {
+                        name: "B".to_string(),
+                        number: 1
+                    }
+                ),
+                change(
+                    "e:A",
+                    Subject::Enum,
+                    ChangeKind::EnumValueRenumbered {
+                        name: "C".to_string(),
+                        old: 2,
+                        new: 3
+                    }
+                ),
+                change(
+                    "e:A",
+                    Subject::Enum,
+                    ChangeKind::EnumValueAdded {
+                        name: "D".to_string(),
+                        number: 4
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn impact() {
+        let old = "struct A { x: u32 } /// Gets. \n fn b() {} enum C { D = 1 }";
+        for (new, expected) in [
+            (
+                "struct A { x: u32 } /// Gets. \n fn b() {} enum C { D = 1 }",
+                None,
+            ),
+            (
+                "struct A { x: u32 } /// Gets an A. \n fn b() {} enum C { D = 1 }",
+                Some(Impact::Cosmetic),
+            ),
+            (
+                "struct A { x: u32, y: Option<u32> } /// Gets. \n fn b() {} enum C { D = 1 }",
+                Some(Impact::Additive),
+            ),
+            (
+                "struct A { x: u32 } /// Gets. \n fn b() {} enum C { D = 1, E = 2 }",
+                Some(Impact::Additive),
+            ),
+            (
+                "struct A { x: u32, y: u32 } /// Gets. \n fn b() {} enum C { D = 1 }",
+                Some(Impact::Breaking),
+            ),
+            (
+                "struct A { x: u32 } /// Gets. \n fn b(y: Option<u32>) {} enum C { D = 1 }",
+                Some(Impact::Breaking),
+            ),
+        ] {
+            let mut old = TestExecutor::new(old);
+            let old = old.build();
+            let mut new_exe = TestExecutor::new(new);
+            let new_model = new_exe.build();
+            let report = BreakingChange::new(old.api(), new_model.api());
+            assert_eq!(report.impact(), expected, "{}", new);
+            assert_eq!(
+                report.is_breaking(),
+                expected == Some(Impact::Breaking),
+                "{}",
+                new
+            );
+        }
+    }
+
+    #[test]
+    fn comments_changed() {
+        let changes = run_test(
+            "/// A.\nstruct A { /// X.\n x: u32 } enum B { /// C.\n C = 1 }",
+            "/// A!\nstruct A { /// X!\n x: u32 } enum B { /// C!\n C = 1 }",
+        );
+        assert_eq!(
+            changes,
+            vec![
+                change("d:A", Subject::Dto, ChangeKind::CommentsChanged),
+                change("d:A.f:x", Subject::Field, ChangeKind::CommentsChanged),
+                change("e:B", Subject::Enum, ChangeKind::CommentsChanged),
+            ]
+        );
+        assert!(changes
+            .iter()
+            .all(|change| change.impact == Impact::Cosmetic));
+        assert_eq!(changes[1].to_string(), "Field `A.x` comments");
+    }
+}
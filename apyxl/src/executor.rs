@@ -1,24 +1,58 @@
 use anyhow::{anyhow, Result};
-use itertools::Itertools;
-use log::{debug, info, log_enabled};
+use log::{debug, info, log_enabled, warn};
 use std::cell::RefCell;
-use std::ops::DerefMut;
+use std::ops::{ControlFlow, DerefMut};
 use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::info_span;
 
 use crate::generator::Generator;
-use crate::input::Input;
-use crate::model::ValidationError;
-use crate::output::Output;
+use crate::input::{Input, Reload};
+use crate::output::{Output, PostProcessed, PostProcessor};
 use crate::parser::Parser;
-use crate::{model, parser};
+use crate::stats::EntityCounts;
+use crate::view::{Transformer, Transforms};
+use crate::{model, parser, Baseline, DeadTypes, Diagnostics, Mode, Stats};
 
 type OutputPtr = Rc<RefCell<dyn Output>>;
+type DiagnosticsPtr = Rc<RefCell<Diagnostics>>;
+type StatsPtr = Rc<RefCell<Stats>>;
 
+/// Runs the full apyxl pipeline: [Input] → [Parser] → view transforms → [Generator]s → [Output]s.
+///
+/// ```ignore
+/// Executor::new(input::Glob::new("src/**/*.rs")?, parser::Rust::default())
+///     .with_dto_transform(MyRenamer {})
+///     .generator(generator::Rust::default())
+///     .output(output::FileSet::new("generated")?)
+///     .execute()?;
+/// ```
+///
+/// Each phase of [Executor::execute] runs within a [tracing] span (`parse`, `build`, `generate`
+/// and `finish`) so that any [tracing] subscriber can report how long each took. Parsers and the
+/// [parser::Cache] add a debug-level span per chunk with its path. View transforms are applied
+/// lazily as the [Generator]s read the view, so their cost is part of the `generate` span.
+///
+/// Output is byte-identical across runs and machines for the same input: input files are read in
+/// sorted order, nothing iterates in hash order, and compressed outputs store no modification
+/// times. [crate::output::Banner::with_timestamp] is the one exception, and uses
+/// `SOURCE_DATE_EPOCH` when it is set. Custom [Input]s, [Generator]s and [Output]s must do the
+/// same.
 pub struct Executor<I: Input, P: Parser> {
     input: I,
     parser: P,
     parser_config: Option<parser::Config>,
+    mode: Mode,
+    #[cfg(feature = "fs")]
+    parse_cache: Option<parser::Cache>,
+    baseline: Option<Baseline>,
+    dead_types: Option<DeadTypes>,
+    diagnostics: Option<DiagnosticsPtr>,
+    stats: Option<StatsPtr>,
     generator_infos: Vec<GeneratorInfo>,
+    post_processors: Vec<Rc<dyn PostProcessor>>,
+    xforms: Transforms,
 }
 
 pub struct GeneratorInfo {
@@ -32,7 +66,16 @@ impl<I: Input, P: Parser> Executor<I, P> {
             input,
             parser,
             parser_config: None,
+            mode: Mode::default(),
+            #[cfg(feature = "fs")]
+            parse_cache: None,
+            baseline: None,
+            dead_types: None,
+            diagnostics: None,
+            stats: None,
             generator_infos: vec![],
+            post_processors: vec![],
+            xforms: Transforms::default(),
         }
     }
 
@@ -41,6 +84,50 @@ impl<I: Input, P: Parser> Executor<I, P> {
         self
     }
 
+    /// Whether unknown syntax, unresolved types and duplicate definitions fail the execution or
+    /// are reported as warnings. See [Mode]. Overrides the mode of the [Executor::parser_config].
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Cache the parsed API of each input chunk so that later executions only parse chunks that
+    /// changed. See [parser::Cache].
+    #[cfg(feature = "fs")]
+    pub fn parse_cache(mut self, cache: parser::Cache) -> Self {
+        self.parse_cache = Some(cache);
+        self
+    }
+
+    /// Fail the execution before generating anything if the model has breaking changes from the
+    /// `baseline` that aren't allowlisted. See [Baseline].
+    pub fn baseline(mut self, baseline: Baseline) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// Report the dtos and enums that nothing in the model uses as warnings, and optionally leave
+    /// them out of the views passed to generators. See [DeadTypes].
+    pub fn dead_types(mut self, dead_types: DeadTypes) -> Self {
+        self.dead_types = Some(dead_types);
+        self
+    }
+
+    /// Collect the [crate::Diagnostic]s from every phase into `diagnostics`, so they can be
+    /// accessed after [Executor::execute]. Errors are also returned from [Executor::execute] as
+    /// [Diagnostics].
+    pub fn diagnostics_ptr(mut self, diagnostics: DiagnosticsPtr) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Record per-phase timings and entity counts into `stats` during [Executor::execute]. See
+    /// [Stats].
+    pub fn stats_ptr(mut self, stats: StatsPtr) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
     pub fn generator(mut self, generator: impl Generator + 'static) -> Self {
         self.generator_infos.push(GeneratorInfo {
             generator: Box::new(generator),
@@ -77,7 +164,67 @@ impl<I: Input, P: Parser> Executor<I, P> {
         self
     }
 
+    /// Add a [PostProcessor] that is applied to the content of every chunk, for every generator and
+    /// output. [PostProcessor]s are applied in the order they are added.
+    pub fn post_processor(mut self, processor: impl PostProcessor + 'static) -> Self {
+        self.post_processors.push(Rc::new(processor));
+        self
+    }
+
     pub fn execute(mut self) -> Result<()> {
+        self.run(Finish::FirstError)
+    }
+
+    /// Like [Executor::execute], but intended for outputs that compare rather than write, such as
+    /// [crate::output::Check], to verify that generated code is up to date. Every output is
+    /// finished even if an earlier one fails, so that the returned [Diagnostics] describe the
+    /// differences in all of them.
+    ///
+    /// ```ignore
+    /// Executor::new(input::Glob::new("src/**/*.rs")?, parser::Rust::default())
+    ///     .generator(generator::Rust::default())
+    ///     .output(output::Check::new("generated"))
+    ///     .check()?;
+    /// ```
+    pub fn check(mut self) -> Result<()> {
+        self.run(Finish::All)
+    }
+
+    /// Runs [Executor::execute], then checks the input for changes every `poll_interval` and runs
+    /// again whenever it changed, until `on_run` returns [ControlFlow::Break]. `on_run` is called
+    /// with the result of every run, including failed ones, which don't stop watching.
+    ///
+    /// The [Executor::diagnostics_ptr] and [Executor::stats_ptr] are reset before each run so that
+    /// they always describe the latest run. Use a [parser::Cache] so that only changed chunks are
+    /// parsed again, and outputs that can be written more than once such as
+    /// [crate::output::IncrementalFileSet] so that only changed files are rewritten.
+    pub fn watch<F>(mut self, poll_interval: Duration, mut on_run: F) -> Result<()>
+    where
+        I: Reload,
+        F: FnMut(&Result<()>) -> ControlFlow<()>,
+    {
+        loop {
+            if let Some(ptr) = &self.diagnostics {
+                *ptr.borrow_mut() = Diagnostics::default();
+            }
+            if let Some(ptr) = &self.stats {
+                *ptr.borrow_mut() = Stats::default();
+            }
+            if on_run(&self.run(Finish::FirstError)).is_break() {
+                return Ok(());
+            }
+            loop {
+                thread::sleep(poll_interval);
+                let changed = self.input.reload()?;
+                if !changed.is_empty() {
+                    info!("Input changed: {:?}", changed);
+                    break;
+                }
+            }
+        }
+    }
+
+    fn run(&mut self, finish: Finish) -> Result<()> {
         if self.generator_infos.is_empty() {
             return Err(anyhow!("no 'generators' have been specified"));
         }
@@ -89,41 +236,199 @@ impl<I: Input, P: Parser> Executor<I, P> {
             }
         }
 
-        let parser_config = self.parser_config.unwrap_or(Default::default());
+        let diagnostics_ptr = self.diagnostics.clone();
+        let stats_ptr = self.stats.clone();
+        record(&stats_ptr, |stats| {
+            let chunks = self.input.chunks();
+            stats.chunks = chunks.len();
+            stats.input_bytes = chunks.iter().map(|(_, data)| data.len()).sum();
+        });
+        let mut parser_config = self.parser_config.clone().unwrap_or_default();
+        parser_config.mode = self.mode;
         debug!("Parser Config: {:#?}", parser_config);
 
         info!("Parsing...");
-        let mut model_builder = model::Builder::with_config(builder_config());
-        self.parser
-            .parse(&parser_config, &mut self.input, &mut model_builder)?;
+        let parse_span = info_span!("parse").entered();
+        let start = Instant::now();
+        // Declared before the builder since the builder borrows from it.
+        #[cfg(feature = "fs")]
+        let cached_chunks;
+        let mut model_builder = model::Builder::with_config(builder_config(self.mode));
+        #[cfg(feature = "fs")]
+        let parsed = match &self.parse_cache {
+            None => self
+                .parser
+                .parse(&parser_config, &mut self.input, &mut model_builder),
+            Some(cache) => match cache.load(&self.parser, &parser_config, &self.input) {
+                Ok(chunks) => {
+                    cached_chunks = chunks;
+                    cached_chunks.merge_into(&mut model_builder)
+                }
+                Err(err) => Err(err),
+            },
+        };
+        #[cfg(not(feature = "fs"))]
+        let parsed = self
+            .parser
+            .parse(&parser_config, &mut self.input, &mut model_builder);
+        parse_span.exit();
+        record(&stats_ptr, |stats| stats.parse = start.elapsed());
+        if let Err(err) = parsed {
+            return Err(report(&diagnostics_ptr, Diagnostics::from_error(&err)));
+        }
 
         info!("Validating model...");
-        let model = match model_builder.build() {
+        let start = Instant::now();
+        model_builder.metadata_mut().user_types = parser_config.user_types.clone();
+        let (model, diagnostics) =
+            info_span!("build").in_scope(|| model_builder.build_with_diagnostics());
+        record(&stats_ptr, |stats| stats.build = start.elapsed());
+        // On failure, the diagnostics include an error for each validation error.
+        let model = match model {
             Ok(model) => model,
-            Err(errors) => {
-                return Err(anyhow!(
-                    "API validation failed.\n{}",
-                    errors_to_string(&errors)
-                ))
-            }
+            Err(_) => return Err(report(&diagnostics_ptr, diagnostics)),
         };
+        for warning in diagnostics.iter() {
+            warn!("{}", warning);
+        }
+        if let Some(ptr) = &diagnostics_ptr {
+            ptr.borrow_mut().extend(diagnostics);
+        }
+        record(&stats_ptr, |stats| {
+            stats.entities = EntityCounts::from_api(model.api())
+        });
 
-        for mut info in self.generator_infos {
-            for output in info.outputs {
+        if let Some(baseline) = &self.baseline {
+            info!("Checking compatibility with baseline...");
+            let diagnostics = info_span!("baseline")
+                .in_scope(|| baseline.check(model.api()))
+                .map_err(|err| report(&diagnostics_ptr, Diagnostics::from_error(&err)))?;
+            if diagnostics.has_errors() {
+                return Err(report(&diagnostics_ptr, diagnostics));
+            }
+            for warning in diagnostics.iter() {
+                warn!("{}", warning);
+            }
+            if let Some(ptr) = &diagnostics_ptr {
+                ptr.borrow_mut().extend(diagnostics);
+            }
+        }
+
+        let mut dead_type_filter = None;
+        if let Some(dead_types) = &self.dead_types {
+            info!("Finding dead types...");
+            let diagnostics = info_span!("dead_types").in_scope(|| dead_types.check(&model));
+            for warning in diagnostics.iter() {
+                warn!("{}", warning);
+            }
+            if let Some(ptr) = &diagnostics_ptr {
+                ptr.borrow_mut().extend(diagnostics);
+            }
+            if dead_types.is_filter() {
+                dead_type_filter = Some(dead_types.view_filter(&model));
+            }
+        }
+
+        let mut finished_outputs = Vec::<OutputPtr>::new();
+        for info in &mut self.generator_infos {
+            for (output_index, output) in info.outputs.iter().enumerate() {
                 info!(
                     "Generating for generator '{:?}' to output '{:?}'...",
                     info.generator,
                     output.borrow()
                 );
-                info.generator
-                    .generate(model.view(), output.borrow_mut().deref_mut())?;
+                let _span =
+                    info_span!("generate", generator = ?info.generator, output_index).entered();
+                let start = Instant::now();
+                let mut view = model.view();
+                view.xforms().clone_from(&self.xforms);
+                if let Some(filter) = &dead_type_filter {
+                    view = view.with_namespace_transform(filter.clone());
+                }
+                let generated = if self.post_processors.is_empty() {
+                    info.generator
+                        .generate(view, output.borrow_mut().deref_mut())
+                } else {
+                    let mut output = output.borrow_mut();
+                    let mut processed = PostProcessed::with_processors(
+                        output.deref_mut(),
+                        self.post_processors.clone(),
+                    );
+                    info.generator
+                        .generate(view, &mut processed)
+                        .and_then(|_| processed.flush())
+                };
+                record(&stats_ptr, |stats| stats.generate += start.elapsed());
+                // Transforms that failed along the way, e.g. a script that threw, fail the
+                // generator that used them.
+                let generated = generated.and_then(|_| {
+                    let errors = self.xforms.errors().take();
+                    if errors.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(errors.into())
+                    }
+                });
+                if let Err(err) = generated {
+                    let note = format!("while generating with {:?}", info.generator);
+                    let diagnostics = Diagnostics::from_error(&err)
+                        .into_iter()
+                        .map(|diagnostic| diagnostic.note(&note))
+                        .collect();
+                    return Err(report(&diagnostics_ptr, diagnostics));
+                }
+                if !finished_outputs.iter().any(|o| Rc::ptr_eq(o, output)) {
+                    finished_outputs.push(output.clone());
+                }
             }
         }
-        Ok(())
+
+        // Outputs are only finished once all generators have succeeded so that outputs such as
+        // [crate::output::AtomicFileSet] can discard partial results if any generator fails.
+        let _span = info_span!("finish").entered();
+        let start = Instant::now();
+        let finished = match finish {
+            Finish::FirstError => finished_outputs
+                .iter()
+                .try_for_each(|output| output.borrow_mut().finish())
+                .map_err(|err| Diagnostics::from_error(&err)),
+            Finish::All => {
+                let mut diagnostics = Diagnostics::new();
+                for output in &finished_outputs {
+                    if let Err(err) = output.borrow_mut().finish() {
+                        diagnostics.extend(Diagnostics::from_error(&err));
+                    }
+                }
+                if diagnostics.is_empty() {
+                    Ok(())
+                } else {
+                    Err(diagnostics)
+                }
+            }
+        };
+        record(&stats_ptr, |stats| stats.finish = start.elapsed());
+        finished.map_err(|diagnostics| report(&diagnostics_ptr, diagnostics))
+    }
+}
+
+/// How [Executor::run] finishes outputs.
+#[derive(Copy, Clone)]
+enum Finish {
+    /// Stop at the first output that fails.
+    FirstError,
+    /// Finish every output and combine their errors.
+    All,
+}
+
+/// View transforms added with e.g. [Transformer::with_dto_transform] are applied to the
+/// [crate::view::Model] passed to every [Generator].
+impl<I: Input, P: Parser> Transformer for Executor<I, P> {
+    fn xforms(&mut self) -> &mut Transforms {
+        &mut self.xforms
     }
 }
 
-fn builder_config() -> model::builder::Config {
+fn builder_config(mode: Mode) -> model::builder::Config {
     let print = if log_enabled!(log::Level::Trace) {
         model::builder::PreValidatePrint::Debug
     } else if log_enabled!(log::Level::Debug) {
@@ -134,11 +439,23 @@ fn builder_config() -> model::builder::Config {
 
     model::builder::Config {
         debug_pre_validate_print: print,
+        mode,
+    }
+}
+
+/// Updates the [Executor::stats_ptr], if any.
+fn record(ptr: &Option<StatsPtr>, update: impl FnOnce(&mut Stats)) {
+    if let Some(ptr) = ptr {
+        update(&mut ptr.borrow_mut())
     }
 }
 
-fn errors_to_string(errors: &[ValidationError]) -> String {
-    errors.iter().map(|e| format!("{}", e)).join("\n")
+/// Adds `diagnostics` to the [Executor::diagnostics_ptr], if any, and converts them to an error.
+fn report(ptr: &Option<DiagnosticsPtr>, diagnostics: Diagnostics) -> anyhow::Error {
+    if let Some(ptr) = ptr {
+        ptr.borrow_mut().extend(diagnostics.clone());
+    }
+    diagnostics.into()
 }
 
 #[cfg(test)]
@@ -158,8 +475,18 @@ mod tests {
         use std::cell::RefCell;
         use std::rc::Rc;
 
-        use crate::executor::tests::{FakeGenerator, FakeParser};
-        use crate::{input, output, Executor};
+        use crate::executor::tests::{FailingGenerator, FakeGenerator, FakeParser};
+        use crate::model::Chunk;
+        use crate::testing::TestExecutor;
+        use crate::view::{DtoTransform, Transformer};
+        use crate::{
+            generator, input, output, parser, Baseline, DeadTypes, Diagnostics, Executor, Mode,
+            Stats,
+        };
+        use std::borrow::Cow;
+        #[cfg(feature = "fs")]
+        use std::ops::ControlFlow;
+        use std::time::Duration;
 
         #[test]
         fn happy_path() -> Result<()> {
@@ -195,6 +522,410 @@ mod tests {
             assert_eq!(output2.borrow().to_string(), gen1.expected(&input_vec));
             Ok(())
         }
+
+        #[test]
+        fn applies_post_processors() -> Result<()> {
+            let parser = FakeParser::default();
+            let output = Rc::new(RefCell::new(output::Buffer::default()));
+            Executor::new(input::Buffer::new(parser.test_data(1)), parser.clone())
+                .generator(FakeGenerator::default())
+                .output_ptr(output.clone())
+                .post_processor(|_: &Chunk, content: String| Ok(format!("{}!", content)))
+                .execute()?;
+            assert_eq!(
+                output.borrow().to_string(),
+                format!("{}!", parser.test_data(1))
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn applies_view_transforms() -> Result<()> {
+            let parser = FakeParser::new(",");
+            let output = Rc::new(RefCell::new(output::Buffer::default()));
+            Executor::new(
+                input::Buffer::new(parser.test_data_vec(&vec![1, 2])),
+                parser,
+            )
+            .with_dto_transform(Suffix {})
+            .generator(FakeGenerator::new(","))
+            .output_ptr(output.clone())
+            .execute()?;
+            assert_eq!(output.borrow().to_string(), "1_x,2_x");
+            Ok(())
+        }
+
+        #[cfg(feature = "script")]
+        #[test]
+        fn fails_on_script_errors() {
+            let parser = FakeParser::new(",");
+            let output = Rc::new(RefCell::new(output::Buffer::default()));
+            let script =
+                crate::view::Script::new(r#"fn type_name(name) { throw "bad name"; }"#).unwrap();
+            let err = Executor::new(
+                input::Buffer::new(parser.test_data_vec(&vec![1, 2])),
+                parser,
+            )
+            .with_transforms(script)
+            .generator(FakeGenerator::new(","))
+            .output_ptr(output.clone())
+            .execute()
+            .unwrap_err();
+            let diagnostics = err.downcast_ref::<Diagnostics>().unwrap();
+            assert_eq!(diagnostics.len(), 1, "{}", diagnostics);
+            assert!(
+                diagnostics.to_string().contains("bad name"),
+                "{}",
+                diagnostics
+            );
+        }
+
+        #[cfg(feature = "fs")]
+        #[test]
+        fn uses_parse_cache() -> Result<()> {
+            let cache_dir = tempfile::tempdir()?;
+            let parser = FakeParser::new(",");
+            for _ in 0..2 {
+                let output = Rc::new(RefCell::new(output::Buffer::default()));
+                Executor::new(
+                    input::Buffer::new(parser.test_data_vec(&vec![1, 2])),
+                    parser.clone(),
+                )
+                .parse_cache(parser::Cache::new(cache_dir.path())?)
+                .generator(FakeGenerator::new(","))
+                .output_ptr(output.clone())
+                .execute()?;
+                assert_eq!(output.borrow().to_string(), "1,2");
+            }
+            assert_eq!(std::fs::read_dir(cache_dir.path())?.count(), 1);
+            Ok(())
+        }
+
+        #[test]
+        fn collects_parse_diagnostics() -> Result<()> {
+            let diagnostics = Rc::new(RefCell::new(Diagnostics::new()));
+            let mut input = input::ChunkBuffer::new();
+            input.add_chunk(
+                Chunk::with_relative_file_path("a.rs"),
+                "struct dto {}\nfn !",
+            );
+            let result = Executor::new(input, parser::Rust::default())
+                .diagnostics_ptr(diagnostics.clone())
+                .generator(FakeGenerator::default())
+                .output(output::Buffer::default())
+                .execute();
+            let err = result.expect_err("parse should fail");
+            assert_eq!(
+                err.downcast_ref::<Diagnostics>(),
+                Some(&*diagnostics.borrow())
+            );
+            let diagnostics = diagnostics.borrow();
+            let diagnostic = diagnostics.errors().next().unwrap();
+            assert_eq!(diagnostic.chunk, Some("a.rs".into()));
+            assert_eq!(diagnostic.span.as_ref().unwrap().line, 2);
+            assert_eq!(diagnostic.message, "unexpected 'f'");
+            Ok(())
+        }
+
+        #[test]
+        fn lenient_mode_collects_warnings() -> Result<()> {
+            let diagnostics = Rc::new(RefCell::new(Diagnostics::new()));
+            let mut input = input::ChunkBuffer::new();
+            input.add_chunk(
+                Chunk::with_relative_file_path("a.rs"),
+                "struct dto { field: missing }\nimpl Display for dto {}",
+            );
+            Executor::new(input, parser::Rust::default())
+                .mode(Mode::Lenient)
+                .diagnostics_ptr(diagnostics.clone())
+                .generator(FakeGenerator::default())
+                .output(output::Buffer::default())
+                .execute()?;
+            let diagnostics = diagnostics.borrow();
+            assert!(!diagnostics.has_errors());
+            assert_eq!(diagnostics.warnings().count(), 2);
+            assert!(diagnostics
+                .iter()
+                .all(|diagnostic| diagnostic.chunk == Some("a.rs".into())));
+            Ok(())
+        }
+
+        #[test]
+        fn records_stats() -> Result<()> {
+            let stats = Rc::new(RefCell::new(Stats::default()));
+            let data = "struct dto { a: u32 }\nmod ns { fn rpc() {} }";
+            Executor::new(input::Buffer::new(data), parser::Rust::default())
+                .stats_ptr(stats.clone())
+                .generator(FakeGenerator::default())
+                .output(output::Buffer::default())
+                .execute()?;
+            let stats = stats.borrow();
+            assert_eq!(stats.chunks, 1);
+            assert_eq!(stats.input_bytes, data.len());
+            assert_eq!(stats.entities.namespaces, 1);
+            assert_eq!(stats.entities.dtos, 1);
+            assert_eq!(stats.entities.rpcs, 1);
+            assert_eq!(stats.entities.fields, 1);
+            assert!(stats.parse > Duration::ZERO);
+            Ok(())
+        }
+
+        #[test]
+        fn fails_on_breaking_changes_from_baseline() -> Result<()> {
+            let mut old = TestExecutor::new("struct dto {}");
+            let baseline = serde_json::to_string(old.build().api())?;
+            let run = |baseline: Baseline| {
+                Executor::new(
+                    input::Buffer::new("struct other {}"),
+                    parser::Rust::default(),
+                )
+                .baseline(baseline)
+                .generator(FakeGenerator::default())
+                .output(output::Buffer::default())
+                .execute()
+            };
+            let err = run(Baseline::new(&baseline)).unwrap_err();
+            let diagnostics = err.downcast_ref::<Diagnostics>().unwrap();
+            assert_eq!(diagnostics.len(), 1);
+            assert_eq!(
+                diagnostics.iter().next().unwrap().message,
+                "breaking change from baseline: DTO `dto`"
+            );
+            run(Baseline::new(&baseline).allowlist_str("d:dto")?)
+        }
+
+        #[cfg(feature = "fs")]
+        #[test]
+        fn watch_reruns_on_change() -> Result<()> {
+            let root = tempfile::tempdir()?;
+            let path = root.path().join("a.rs");
+            std::fs::write(&path, "struct a {}")?;
+            let output = Rc::new(RefCell::new(output::Buffer::default()));
+            let mut results = vec![];
+            Executor::new(
+                input::Glob::new_with_root(root.path(), "*.rs")?,
+                parser::Rust::default(),
+            )
+            .generator(generator::Rust::default())
+            .output_ptr(output.clone())
+            .watch(Duration::from_millis(10), |result| {
+                results.push(result.is_ok());
+                let next = match results.len() {
+                    1 => "struct {",
+                    2 => "struct a {}\nstruct b {}",
+                    _ => return ControlFlow::Break(()),
+                };
+                std::fs::write(&path, next).unwrap();
+                ControlFlow::Continue(())
+            })?;
+            // The failed run in between doesn't stop watching.
+            assert_eq!(results, vec![true, false, true]);
+            assert!(output.borrow().to_string().contains("struct b"));
+            Ok(())
+        }
+
+        #[cfg(feature = "fs")]
+        #[test]
+        fn check_reports_all_outputs() -> Result<()> {
+            let root = tempfile::tempdir()?;
+            let input_root = root.path().join("in");
+            std::fs::create_dir(&input_root)?;
+            std::fs::write(input_root.join("a.rs"), "struct a {}")?;
+            let up_to_date = root.path().join("up_to_date");
+            let executor = |outputs: [output::Check; 3]| -> Result<_> {
+                let [a, b, c] = outputs;
+                Ok(Executor::new(
+                    input::Glob::new_with_root(&input_root, "*.rs")?,
+                    parser::Rust::default(),
+                )
+                .generator(generator::Rust::default())
+                .output(a)
+                .output(b)
+                .generator(generator::Rust::default())
+                .output(c))
+            };
+            Executor::new(
+                input::Glob::new_with_root(&input_root, "*.rs")?,
+                parser::Rust::default(),
+            )
+            .generator(generator::Rust::default())
+            .output(output::FileSet::new(&up_to_date)?)
+            .execute()?;
+
+            let result = executor([
+                output::Check::new(root.path().join("missing0")),
+                output::Check::new(&up_to_date),
+                output::Check::new(root.path().join("missing1")),
+            ])?
+            .check();
+            let err = result.unwrap_err();
+            assert_eq!(Diagnostics::from_error(&err).errors().count(), 2);
+
+            executor([
+                output::Check::new(&up_to_date),
+                output::Check::new(&up_to_date),
+                output::Check::new(&up_to_date),
+            ])?
+            .check()
+        }
+
+        #[test]
+        fn collects_generator_diagnostics() -> Result<()> {
+            let diagnostics = Rc::new(RefCell::new(Diagnostics::new()));
+            let parser = FakeParser::default();
+            let result = Executor::new(input::Buffer::new(parser.test_data(1)), parser)
+                .diagnostics_ptr(diagnostics.clone())
+                .generator(FailingGenerator {})
+                .output(output::Buffer::default())
+                .execute();
+            assert!(result.is_err());
+            let diagnostics = diagnostics.borrow();
+            assert_eq!(diagnostics.len(), 1);
+            assert!(diagnostics.iter().next().unwrap().notes[0].contains("FailingGenerator"));
+            Ok(())
+        }
+
+        #[test]
+        fn dead_types() -> Result<()> {
+            let run = |dead_types: DeadTypes| -> Result<(String, Diagnostics)> {
+                let diagnostics = Rc::new(RefCell::new(Diagnostics::new()));
+                let output = Rc::new(RefCell::new(output::Buffer::default()));
+                Executor::new(
+                    input::Buffer::new("struct used {} struct unused {} fn rpc(x: used) {}"),
+                    parser::Rust::default(),
+                )
+                .dead_types(dead_types)
+                .diagnostics_ptr(diagnostics.clone())
+                .generator(generator::Rust::default())
+                .output_ptr(output.clone())
+                .execute()?;
+                let output = output.borrow().to_string();
+                Ok((output, diagnostics.take()))
+            };
+
+            let (output, diagnostics) = run(DeadTypes::default())?;
+            assert_eq!(diagnostics.warnings().count(), 1);
+            assert!(output.contains("struct unused"));
+
+            let (output, _) = run(DeadTypes::default().filter(true))?;
+            assert!(output.contains("struct used"));
+            assert!(!output.contains("struct unused"));
+            Ok(())
+        }
+
+        #[derive(Debug, Clone)]
+        struct Suffix {}
+
+        impl DtoTransform for Suffix {
+            fn name(&self, name: &mut Cow<str>) {
+                *name = Cow::Owned(format!("{}_x", name));
+            }
+        }
+
+        #[cfg(feature = "fs")]
+        #[test]
+        fn outputs_not_finished_if_any_generator_fails() -> Result<()> {
+            let root = tempfile::tempdir()?;
+            let output_root = root.path().join("out");
+            let parser = FakeParser::default();
+            let result = Executor::new(input::Buffer::new(parser.test_data(1)), parser)
+                .generator(FakeGenerator::default())
+                .output(output::AtomicFileSet::new(&output_root)?)
+                .generator(FailingGenerator {})
+                .output(output::Buffer::default())
+                .execute();
+            assert!(result.is_err());
+            assert!(!output_root.exists());
+            Ok(())
+        }
+
+        #[cfg(feature = "fs")]
+        #[test]
+        fn shared_output_finished_once() -> Result<()> {
+            #[derive(Debug)]
+            struct FileGenerator(&'static str);
+            impl crate::Generator for FileGenerator {
+                fn generate(
+                    &mut self,
+                    _: crate::view::Model,
+                    output: &mut dyn crate::Output,
+                ) -> Result<()> {
+                    output.write_chunk(&Chunk::with_relative_file_path(self.0))?;
+                    output.write_str(self.0)
+                }
+            }
+
+            let root = tempfile::tempdir()?;
+            for _ in 0..2 {
+                let output = Rc::new(RefCell::new(
+                    output::IncrementalFileSet::new(root.path())?.prune(true),
+                ));
+                Executor::new(input::Buffer::new(""), parser::Rust::default())
+                    .generator(FileGenerator("a"))
+                    .output_ptr(output.clone())
+                    .generator(FileGenerator("b"))
+                    .output_ptr(output.clone())
+                    .execute()?;
+                // Pruning after the first generator would delete 'b' from the previous run.
+                assert!(output.borrow().pruned().is_empty());
+            }
+            assert!(root.path().join("a").exists());
+            assert!(root.path().join("b").exists());
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    mod reproducible {
+        use std::cell::RefCell;
+        use std::fs;
+        use std::rc::Rc;
+
+        use anyhow::Result;
+        use tempfile::tempdir;
+
+        use crate::{generator, input, output, parser, Executor};
+
+        const FILES: &[(&str, &str)] = &[
+            ("a.rs", "struct A { b: b::B, c: c::C }"),
+            ("b.rs", "struct B { c: c::C }"),
+            ("c/mod.rs", "struct C {}\nfn rpc(a: a::A) -> b::B {}"),
+            ("d.rs", "mod e { struct E { a: a::A } }"),
+        ];
+
+        /// Writes the input files in the given order, so that file system order differs.
+        fn run(files: impl Iterator<Item = (&'static str, &'static str)>) -> Result<Vec<u8>> {
+            let root = tempdir()?;
+            for (path, data) in files {
+                let path = root.path().join("input").join(path);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(path, data)?;
+            }
+            let combined = Rc::new(RefCell::new(output::Buffer::default()));
+            let archive = root.path().join("out.tar.gz");
+            Executor::new(
+                input::Glob::new_with_root(root.path().join("input"), "**/*.rs")?,
+                parser::Rust::default(),
+            )
+            .generator(generator::Rust::default())
+            .output_ptr(combined.clone())
+            .output(output::TarGz::new(&archive)?)
+            .execute()?;
+
+            let mut bytes = combined.borrow().to_string().into_bytes();
+            bytes.extend(fs::read(&archive)?);
+            Ok(bytes)
+        }
+
+        #[test]
+        fn output_is_byte_identical() -> Result<()> {
+            let expected = run(FILES.iter().copied())?;
+            for _ in 0..3 {
+                assert_eq!(run(FILES.iter().copied())?, expected);
+            }
+            assert_eq!(run(FILES.iter().rev().copied())?, expected);
+            Ok(())
+        }
     }
 
     mod validation {
@@ -249,7 +980,7 @@ mod tests {
         }
     }
     impl Parser for FakeParser {
-        fn parse<'a, I: Input + 'a>(
+        fn parse<'a, I: Input + ?Sized + 'a>(
             &self,
             _: &'a parser::Config,
             input: &'a mut I,
@@ -259,7 +990,7 @@ mod tests {
                 name: Cow::Borrowed(UNDEFINED_NAMESPACE),
                 children: input
                     .chunks()
-                    .get(0)
+                    .first()
                     .ok_or_else(|| anyhow!("no input data!"))?
                     .1 // data
                     .split(&self.delimiter)
@@ -301,6 +1032,15 @@ mod tests {
         }
     }
 
+    #[derive(Debug)]
+    struct FailingGenerator {}
+
+    impl Generator for FailingGenerator {
+        fn generate(&mut self, _: view::Model, _: &mut dyn Output) -> Result<()> {
+            Err(anyhow!("failed"))
+        }
+    }
+
     impl Generator for FakeGenerator {
         fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()> {
             let dto_names = model
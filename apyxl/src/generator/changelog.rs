@@ -0,0 +1,222 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::diff::{diff, Change, ChangeKind, Impact};
+use crate::generator::Generator;
+use crate::model::{chunk, Api};
+use crate::output::Output;
+use crate::view;
+
+/// A generator that compares the model against a previous version of the API (the baseline) and
+/// writes a human-readable CHANGELOG section to `CHANGELOG.md`, listing the dtos, fields, rpcs,
+/// params and enums that were added, removed or changed.
+///
+/// The baseline is the JSON of the previous API as written by [crate::generator::Json], e.g.
+/// generated from the last release and committed alongside the source. Both APIs are compared as
+/// parsed, without any view transforms applied. See [crate::diff::diff] for how entities are
+/// matched.
+#[derive(Debug)]
+pub struct Changelog {
+    options: ChangelogOptions,
+    baseline_json: Option<String>,
+}
+
+/// Options for the [Changelog] generator, from the `generator.changelog` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChangelogOptions {
+    /// Path to the JSON of the previous API. Required unless the baseline is set with
+    /// [Changelog::with_baseline_json].
+    pub baseline: Option<PathBuf>,
+
+    /// Heading of the section, e.g. the version being released. Defaults to `Unreleased`.
+    pub title: Option<String>,
+}
+
+impl Changelog {
+    pub fn new(options: ChangelogOptions) -> Self {
+        Self {
+            options,
+            baseline_json: None,
+        }
+    }
+
+    /// Use `json` as the baseline instead of reading [ChangelogOptions::baseline].
+    pub fn with_baseline_json(mut self, json: impl ToString) -> Self {
+        self.baseline_json = Some(json.to_string());
+        self
+    }
+
+    fn read_baseline(&self) -> Result<String> {
+        if let Some(json) = &self.baseline_json {
+            return Ok(json.clone());
+        }
+        let path = self.options.baseline.as_ref().ok_or_else(|| {
+            anyhow!("the changelog generator requires a baseline, set generator.changelog.baseline")
+        })?;
+        fs::read_to_string(path).with_context(|| format!("read baseline '{}'", path.display()))
+    }
+}
+
+impl Generator for Changelog {
+    fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()> {
+        let baseline_json = self.read_baseline()?;
+        let baseline: Api = serde_json::from_str(&baseline_json).context("parse baseline")?;
+        // Cosmetic changes, e.g. to comments, aren't worth listing.
+        let changes = diff(&baseline, model.target().api())
+            .into_iter()
+            .filter(|change| change.impact != Impact::Cosmetic)
+            .collect::<Vec<_>>();
+
+        output.write_chunk(&chunk::Chunk::with_relative_file_path("CHANGELOG.md"))?;
+        let title = self.options.title.as_deref().unwrap_or("Unreleased");
+        output.write_str(&format!("## {}\n", title))?;
+        if changes.is_empty() {
+            return output.write_str("\nNo API changes.\n");
+        }
+        for (heading, section) in [
+            ("Added", Section::Added),
+            ("Removed", Section::Removed),
+            ("Changed", Section::Changed),
+        ] {
+            let mut lines = changes
+                .iter()
+                .filter(|change| Section::of(change) == section)
+                .collect::<Vec<_>>();
+            if lines.is_empty() {
+                continue;
+            }
+            // Stable, so entities of the same kind stay in API order.
+            lines.sort_by_key(|change| change.subject);
+            output.write_str(&format!("\n### {}\n\n", heading))?;
+            for change in lines {
+                output.write_str(&format!("- {}\n", change))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["md"]
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Section {
+    Added,
+    Removed,
+    Changed,
+}
+
+impl Section {
+    fn of(change: &Change) -> Self {
+        match change.kind {
+            ChangeKind::Added | ChangeKind::EnumValueAdded { .. } => Section::Added,
+            ChangeKind::Removed | ChangeKind::EnumValueRemoved { .. } => Section::Removed,
+            ChangeKind::TypeChanged { .. }
+            | ChangeKind::ReturnTypeChanged { .. }
+            | ChangeKind::ErrorTypeChanged { .. }
+            | ChangeKind::EnumValueRenumbered { .. }
+            | ChangeKind::CommentsChanged => Section::Changed,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::generator::{Changelog, ChangelogOptions, Generator};
+    use crate::output::Buffer;
+    use crate::testing::TestExecutor;
+    use crate::view;
+
+    fn run_test(old: &str, new: &str, title: Option<&str>) -> Result<String> {
+        let mut old = TestExecutor::new(old);
+        let baseline = serde_json::to_string(old.build().api())?;
+        let mut new = TestExecutor::new(new);
+        let model = new.build();
+        let mut generator = Changelog::new(ChangelogOptions {
+            baseline: None,
+            title: title.map(str::to_string),
+        })
+        .with_baseline_json(baseline);
+        let mut output = Buffer::default();
+        generator.generate(view::Model::new(&model), &mut output)?;
+        Ok(output.to_string())
+    }
+
+    #[test]
+    fn sections() -> Result<()> {
+        let changelog = run_test(
+            r#"
+            mod user {
+                struct User { id: u32, name: String }
+                struct Old {}
+                fn get_user(id: u32) -> User {}
+                enum Kind { A = 1, B = 2 }
+            }
+            "#,
+            r#"
+            mod user {
+                struct User { id: u64, name: String, friends: Vec<User> }
+                struct New {}
+                fn get_user(id: u32, verbose: Option<bool>) -> Option<User> {}
+                fn delete_user(id: u64) {}
+                enum Kind { A = 1, C = 3 }
+            }
+            "#,
+            Some("1.2.0"),
+        )?;
+        assert_eq!(
+            changelog,
+            r#"## 1.2.0
+
+### Added
+
+- DTO `user.New`
+- Field `user.User.friends`
+- RPC `user.delete_user`
+- Param `user.get_user.verbose`
+- Enum value `user.Kind.C` = 3
+
+### Removed
+
+- DTO `user.Old`
+- Enum value `user.Kind.B` = 2
+
+### Changed
+
+- Field `user.User.id`: `u32` → `u64`
+- RPC `user.get_user` return type: `user.User` → `user.User?`
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_changes() -> Result<()> {
+        let api = "struct A { b: u32 }";
+        assert_eq!(
+            run_test(api, api, None)?,
+            "## Unreleased\n\nNo API changes.\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn requires_baseline() {
+        let mut exe = TestExecutor::new("");
+        let model = exe.build();
+        let mut output = Buffer::default();
+        let err = Changelog::new(ChangelogOptions::default())
+            .generate(view::Model::new(&model), &mut output)
+            .unwrap_err();
+        assert!(err.to_string().contains("requires a baseline"), "{}", err);
+    }
+}
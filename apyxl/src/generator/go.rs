@@ -0,0 +1,798 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use anyhow::Result;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::generator::Generator;
+use crate::model::{Chunk, Comment};
+use crate::output::{Indented, Output};
+use crate::view::{
+    Attributes, Dto, EntityId, Enum, Field, IdentifierPolicy, InnerType, Model, Namespace, Order,
+    Rpc, Transformer, Type, UserTypeTargets,
+};
+
+/// A generator that writes Go declarations for the API, with a package per namespace:
+/// - [Dto]s become structs with a `json` tag of the original name of each field. Optional fields
+///   are pointers, or nil-able slices and maps, tagged `omitempty`.
+/// - [Enum]s become a named `int64` type with a typed constant for each value, e.g. `KindAdmin`.
+/// - The [Rpc]s of each namespace become the methods of its `Service` interface, and the rpcs of
+///   each dto the methods of a `<Dto>Service` interface. Methods take a `context.Context` and
+///   return an `error`, which takes the place of the rpc's error type.
+///
+/// Names are exported by converting them to PascalCase, e.g. `get_user` to `GetUser`. Each
+/// namespace is written to a file named after its package in the directory of its namespace
+/// path, e.g. `user/user.go`, and the root namespace to e.g. `api.go`, see [GoOptions::module].
+/// Package names are the lowercased alphanumeric characters of the namespace name.
+///
+/// Go doesn't allow import cycles, so namespaces can't refer to each other's entities in both
+/// directions. Interface methods can't have type parameters in Go, so the type parameters of
+/// rpcs are written as `any`. 128-bit integers are written as `*big.Int`. Fields aren't aligned
+/// like `gofmt` would, so run it on the output for idiomatic formatting.
+#[derive(Debug, Default)]
+pub struct Go {
+    options: GoOptions,
+}
+
+/// Options for the [Go] generator, from the `generator.go` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct GoOptions {
+    /// Import path of the root namespace's package, e.g. `github.com/example/api`. Packages of
+    /// other namespaces are imported from its subdirectories, e.g. `github.com/example/api/user`.
+    /// The root package is named after the last element. Defaults to `api`.
+    pub module: String,
+
+    /// How non-ASCII identifiers are written. Go allows them, so defaults to
+    /// [IdentifierPolicy::Keep].
+    pub identifiers: IdentifierPolicy,
+
+    /// The order entities are written in within each kind. Defaults to [Order::Source].
+    pub order: Order,
+}
+
+impl Default for GoOptions {
+    fn default() -> Self {
+        Self {
+            module: "api".to_string(),
+            identifiers: IdentifierPolicy::default(),
+            order: Order::default(),
+        }
+    }
+}
+
+impl Go {
+    pub fn new(options: GoOptions) -> Self {
+        Self { options }
+    }
+}
+
+const INDENT: &str = "\t";
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "go";
+
+const KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "fallthrough",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "type",
+    "var",
+];
+
+/// The package being written, which determines how references to other entities are written.
+#[derive(Debug, Copy, Clone)]
+struct Scope<'s> {
+    /// Path of the namespace being written.
+    path: &'s [String],
+    /// Names that the packages of other namespaces are imported as, by namespace path.
+    imports: &'s BTreeMap<Vec<String>, String>,
+    /// Whether type parameters are written as `any`, since they're not allowed in the context.
+    erase_generics: bool,
+}
+
+impl Generator for Go {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model
+            .with_user_type_targets(user_ty_targets)
+            .with_identifier_policy(self.options.identifiers)
+            .with_order(self.options.order);
+        let mut o = Indented::new(output, INDENT);
+        write_package(model.api(), &self.options.module, &[], &mut o)
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["go"]
+    }
+}
+
+/// Writes the file of the namespace at `path`, if it has any entities, then the files of its
+/// child namespaces.
+fn write_package(
+    namespace: Namespace,
+    module: &str,
+    path: &[String],
+    o: &mut Indented,
+) -> Result<()> {
+    let has_entities = namespace.dtos().next().is_some()
+        || namespace.enums().next().is_some()
+        || namespace.rpcs().next().is_some();
+    if has_entities {
+        let name = package_name(path.last().map_or(module_name(module), String::as_str));
+        let file = [path.to_vec(), vec![format!("{}.go", name)]]
+            .concat()
+            .join("/");
+        o.write_chunk(&Chunk::with_relative_file_path(file))?;
+        write_attributes(&namespace.attributes(), o)?;
+        o.write_str("package ")?;
+        o.write_str(&name)?;
+        o.newline()?;
+
+        let dependencies = Dependencies::collect(&namespace, path);
+        let imports = import_names(&dependencies.packages);
+        write_imports(&dependencies, &imports, module, o)?;
+        let scope = Scope {
+            path,
+            imports: &imports,
+            erase_generics: false,
+        };
+
+        for en in namespace.enums() {
+            o.newline()?;
+            write_enum(en, o)?;
+        }
+
+        for dto in namespace.dtos() {
+            o.newline()?;
+            write_dto(&dto, scope, o)?;
+            if dto.rpcs().next().is_some() {
+                o.newline()?;
+                let name = format!("{}Service", pascal_case(&dto.name()));
+                write_service(&name, dto.rpcs(), scope, o)?;
+            }
+        }
+
+        if namespace.rpcs().next().is_some() {
+            o.newline()?;
+            write_service("Service", namespace.rpcs(), scope, o)?;
+        }
+    }
+
+    for nested_ns in namespace.namespaces() {
+        let nested_path = [path, &[nested_ns.name().to_string()]].concat();
+        write_package(nested_ns, module, &nested_path, o)?;
+    }
+    Ok(())
+}
+
+/// Everything the entities directly within a namespace refer to, so that only what's used is
+/// imported.
+#[derive(Debug, Default)]
+struct Dependencies {
+    big: bool,
+    context: bool,
+    /// Paths of other namespaces whose entities are referred to.
+    packages: BTreeSet<Vec<String>>,
+}
+
+impl Dependencies {
+    fn collect(namespace: &Namespace, path: &[String]) -> Self {
+        let mut dependencies = Self::default();
+        let add_rpc = |rpc: Rpc, dependencies: &mut Self| {
+            dependencies.context = true;
+            for param in rpc.params() {
+                dependencies.add_type(param.ty().inner(), path);
+            }
+            if let Some(ty) = rpc.return_type() {
+                dependencies.add_type(ty.inner(), path);
+            }
+        };
+        for dto in namespace.dtos() {
+            for field in dto.fields() {
+                dependencies.add_type(field.ty().inner(), path);
+            }
+            for rpc in dto.rpcs() {
+                add_rpc(rpc, &mut dependencies);
+            }
+        }
+        for rpc in namespace.rpcs() {
+            add_rpc(rpc, &mut dependencies);
+        }
+        dependencies
+    }
+
+    fn add_type(&mut self, ty: InnerType, path: &[String]) {
+        match ty {
+            InnerType::U128 | InnerType::I128 => self.big = true,
+            InnerType::Api(id, args) => {
+                let id_path = id.path();
+                if let Some((_, namespace)) = id_path.split_last() {
+                    if !namespace.iter().eq(path.iter()) {
+                        self.packages
+                            .insert(namespace.iter().map(|s| s.to_string()).collect_vec());
+                    }
+                }
+                for arg in args {
+                    self.add_type(arg, path);
+                }
+            }
+            InnerType::Array(ty) | InnerType::Optional(ty) => self.add_type(*ty, path),
+            InnerType::Map { key, value } => {
+                self.add_type(*key, path);
+                self.add_type(*value, path);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Names that each of the `packages` are referred to by. Packages are imported with their own
+/// name unless it's taken by another import, in which case they're given an alias of their whole
+/// namespace path, e.g. `a_user` and `b_user`.
+fn import_names(packages: &BTreeSet<Vec<String>>) -> BTreeMap<Vec<String>, String> {
+    let names = packages
+        .iter()
+        .map(|path| package_name(path.last().map_or("", String::as_str)))
+        .collect_vec();
+    packages
+        .iter()
+        .zip(&names)
+        .map(|(path, name)| {
+            let is_taken = names.iter().filter(|other| *other == name).count() > 1;
+            let name = if is_taken {
+                path.iter().map(|s| package_name(s)).join("_")
+            } else {
+                name.clone()
+            };
+            (path.clone(), name)
+        })
+        .collect()
+}
+
+fn write_imports(
+    dependencies: &Dependencies,
+    imports: &BTreeMap<Vec<String>, String>,
+    module: &str,
+    o: &mut Indented,
+) -> Result<()> {
+    let mut std = vec![];
+    if dependencies.context {
+        std.push("context");
+    }
+    if dependencies.big {
+        std.push("math/big");
+    }
+    if std.is_empty() && imports.is_empty() {
+        return Ok(());
+    }
+
+    o.newline()?;
+    o.write_str("import (")?;
+    o.indent(1);
+    for package in &std {
+        o.newline()?;
+        o.write('"')?;
+        o.write_str(package)?;
+        o.write('"')?;
+    }
+    if !std.is_empty() && !imports.is_empty() {
+        o.newline()?;
+    }
+    for (path, name) in imports {
+        o.newline()?;
+        if *name != package_name(path.last().map_or("", String::as_str)) {
+            o.write_str(name)?;
+            o.write(' ')?;
+        }
+        o.write('"')?;
+        o.write_str(module)?;
+        for component in path {
+            o.write('/')?;
+            o.write_str(component)?;
+        }
+        o.write('"')?;
+    }
+    o.indent(-1);
+    o.newline()?;
+    o.write(')')?;
+    o.newline()
+}
+
+fn write_dto(dto: &Dto, scope: Scope, o: &mut Indented) -> Result<()> {
+    write_attributes(&dto.attributes(), o)?;
+
+    o.write_str("type ")?;
+    o.write_str(&pascal_case(&dto.name()))?;
+    write_type_params(dto, o)?;
+    if dto.fields().next().is_none() {
+        o.write_str(" struct{}")?;
+        return o.newline();
+    }
+    o.write_str(" struct {")?;
+    o.indent(1);
+    for field in dto.fields() {
+        o.newline()?;
+        write_field(field, scope, o)?;
+    }
+    o.indent(-1);
+    o.newline()?;
+    o.write('}')?;
+    o.newline()
+}
+
+/// Writes e.g. `[K comparable, V any]`, or nothing if the dto has no type params. Params used as
+/// the key type of a map within any of its fields are `comparable`, and the rest `any`.
+fn write_type_params(dto: &Dto, o: &mut dyn Output) -> Result<()> {
+    let type_params = dto.type_params();
+    if type_params.is_empty() {
+        return Ok(());
+    }
+    let mut map_keys = vec![];
+    for field in dto.fields() {
+        collect_map_key_params(field.ty().inner(), &mut map_keys);
+    }
+    // Consecutive params with the same constraint share it, e.g. `[K, V any]`.
+    let groups = type_params
+        .iter()
+        .group_by(|param| map_keys.iter().any(|key| key == *param));
+    let params = groups.into_iter().map(|(comparable, mut params)| {
+        let constraint = if comparable { "comparable" } else { "any" };
+        format!("{} {}", params.join(", "), constraint)
+    });
+    o.write('[')?;
+    o.write_str(&params.collect_vec().join(", "))?;
+    o.write(']')
+}
+
+/// Adds the name of each type param that is the key type of a map within `ty`.
+fn collect_map_key_params(ty: InnerType, params: &mut Vec<String>) {
+    match ty {
+        InnerType::Map { key, value } => {
+            if let InnerType::Generic(name) = *key {
+                params.push(name.to_string());
+            } else {
+                collect_map_key_params(*key, params);
+            }
+            collect_map_key_params(*value, params);
+        }
+        InnerType::Array(ty) | InnerType::Optional(ty) => collect_map_key_params(*ty, params),
+        InnerType::Api(_, args) => {
+            for arg in args {
+                collect_map_key_params(arg, params);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn write_field(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&field.attributes(), o)?;
+
+    o.write_str(&pascal_case(&field.name()))?;
+    o.write(' ')?;
+    let ty = field.ty();
+    let is_optional = matches!(ty.inner(), InnerType::Optional(_));
+    write_type(ty, scope, o)?;
+    o.write_str(" `json:\"")?;
+    o.write_str(&field.name())?;
+    if is_optional {
+        o.write_str(",omitempty")?;
+    }
+    o.write_str("\"`")
+}
+
+fn write_service<'v, 'a: 'v>(
+    name: &str,
+    rpcs: impl Iterator<Item = Rpc<'v, 'a>>,
+    scope: Scope,
+    o: &mut Indented,
+) -> Result<()> {
+    o.write_str("type ")?;
+    o.write_str(name)?;
+    o.write_str(" interface {")?;
+    o.indent(1);
+    for rpc in rpcs {
+        o.newline()?;
+        write_rpc(&rpc, scope, o)?;
+    }
+    o.indent(-1);
+    o.newline()?;
+    o.write('}')?;
+    o.newline()
+}
+
+fn write_rpc(rpc: &Rpc, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&rpc.attributes(), o)?;
+    let scope = Scope {
+        erase_generics: true,
+        ..scope
+    };
+
+    o.write_str(&pascal_case(&rpc.name()))?;
+    o.write_str("(ctx context.Context")?;
+    for param in rpc.params() {
+        o.write_str(", ")?;
+        o.write_str(&ident(&param.name()))?;
+        o.write(' ')?;
+        write_type(param.ty(), scope, o)?;
+    }
+    o.write_str(") ")?;
+    match rpc.return_type() {
+        None => o.write_str("error"),
+        Some(return_type) => {
+            o.write('(')?;
+            write_type(return_type, scope, o)?;
+            o.write_str(", error)")
+        }
+    }
+}
+
+fn write_enum(en: Enum, o: &mut Indented) -> Result<()> {
+    write_attributes(&en.attributes(), o)?;
+
+    let name = pascal_case(&en.name());
+    o.write_str("type ")?;
+    o.write_str(&name)?;
+    o.write_str(" int64")?;
+    o.newline()?;
+
+    if en.values().next().is_none() {
+        return Ok(());
+    }
+    o.newline()?;
+    o.write_str("const (")?;
+    o.indent(1);
+    for value in en.values() {
+        o.newline()?;
+        write_attributes(&value.attributes(), o)?;
+        o.write_str(&name)?;
+        o.write_str(&pascal_case(&value.name()))?;
+        o.write(' ')?;
+        o.write_str(&name)?;
+        o.write_str(" = ")?;
+        o.write_str(&value.number().to_string())?;
+    }
+    o.indent(-1);
+    o.newline()?;
+    o.write(')')?;
+    o.newline()
+}
+
+fn write_attributes(attributes: &Attributes, o: &mut dyn Output) -> Result<()> {
+    write_comments(&attributes.comments(), o)
+}
+
+/// Writes each line of each comment as a `//` comment, with an empty line between each
+/// [Comment].
+fn write_comments(comments: &[Comment], o: &mut dyn Output) -> Result<()> {
+    for (i, comment) in comments.iter().enumerate() {
+        if i > 0 {
+            o.write_str("//")?;
+            o.newline()?;
+        }
+        for line in comment.lines() {
+            o.write_str("//")?;
+            if !line.is_empty() {
+                o.write(' ')?;
+                o.write_str(line)?;
+            }
+            o.newline()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_type(ty: Type, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_inner_type(ty.inner(), scope, o)
+}
+
+fn write_inner_type(ty: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    match ty {
+        InnerType::Bool => o.write_str("bool"),
+        InnerType::U8 => o.write_str("uint8"),
+        InnerType::U16 => o.write_str("uint16"),
+        InnerType::U32 => o.write_str("uint32"),
+        InnerType::U64 => o.write_str("uint64"),
+        InnerType::I8 => o.write_str("int8"),
+        InnerType::I16 => o.write_str("int16"),
+        InnerType::I32 => o.write_str("int32"),
+        InnerType::I64 => o.write_str("int64"),
+        InnerType::U128 | InnerType::I128 => o.write_str("*big.Int"),
+        InnerType::F8 | InnerType::F16 | InnerType::F32 => o.write_str("float32"),
+        InnerType::F64 | InnerType::F128 => o.write_str("float64"),
+        InnerType::String => o.write_str("string"),
+        InnerType::Bytes => o.write_str("[]byte"),
+        // Either the `go` target of the user type, or just its name.
+        InnerType::User(s) => o.write_str(s),
+        InnerType::Api(id, args) => write_api_type(id, args, scope, o),
+        InnerType::Generic(name) => {
+            if scope.erase_generics {
+                o.write_str("any")
+            } else {
+                o.write_str(name)
+            }
+        }
+        InnerType::Array(ty) => {
+            o.write_str("[]")?;
+            write_inner_type(*ty, scope, o)
+        }
+        InnerType::Map { key, value } => {
+            o.write_str("map[")?;
+            write_inner_type(*key, scope, o)?;
+            o.write(']')?;
+            write_inner_type(*value, scope, o)
+        }
+        InnerType::Optional(ty) => {
+            // Types that are already nil-able aren't made pointers.
+            let is_nillable = matches!(
+                *ty,
+                InnerType::Bytes
+                    | InnerType::U128
+                    | InnerType::I128
+                    | InnerType::Array(_)
+                    | InnerType::Map { .. }
+                    | InnerType::Optional(_)
+            );
+            if !is_nillable {
+                o.write('*')?;
+            }
+            write_inner_type(*ty, scope, o)
+        }
+    }
+}
+
+/// Writes e.g. `Page[User]`, or just `Page` if there are no type `args`.
+fn write_api_type(
+    id: EntityId,
+    args: Vec<InnerType>,
+    scope: Scope,
+    o: &mut dyn Output,
+) -> Result<()> {
+    write_entity_id(id, scope, o)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+    o.write('[')?;
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            o.write_str(", ")?;
+        }
+        write_inner_type(arg, scope, o)?;
+    }
+    o.write(']')
+}
+
+fn write_entity_id(entity_id: EntityId, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    let path = entity_id.path();
+    let (name, namespace) = match path.split_last() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    if !namespace.iter().eq(scope.path.iter()) {
+        let namespace = namespace.iter().map(|s| s.to_string()).collect_vec();
+        if let Some(import) = scope.imports.get(&namespace) {
+            o.write_str(import)?;
+            o.write('.')?;
+        }
+    }
+    o.write_str(&pascal_case(name))
+}
+
+/// The last element of the `module` import path.
+fn module_name(module: &str) -> &str {
+    module.rsplit('/').next().unwrap_or(module)
+}
+
+/// Lowercased alphanumeric characters of `name`, e.g. `user_profile` becomes `userprofile`.
+fn package_name(name: &str) -> String {
+    let mut package = name
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect::<String>();
+    if package.is_empty() || package.starts_with(|c: char| c.is_numeric()) {
+        package.insert(0, 'p');
+    }
+    ident(&package)
+}
+
+/// `name` in PascalCase, so that it's exported, e.g. `get_user` becomes `GetUser`.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+            }
+        })
+        .join("")
+}
+
+/// `name`, with a trailing `_` if it's a Go keyword.
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("{}_", name)
+    } else {
+        name.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::generator::go::{import_names, package_name, pascal_case};
+    use crate::generator::{Go, GoOptions};
+    use crate::testing::TestExecutor;
+
+    const API: &str = r#"
+        mod user {
+            /// A user.
+            struct User {
+                user_id: u64,
+                name: Option<String>,
+                tags: Option<Vec<String>>,
+                kind: Kind,
+                page: other::Page,
+            }
+            enum Kind { Admin = 1, Guest = 2 }
+            /// Gets a user.
+            fn get_user(id: u64, verbose: Option<bool>) -> Option<User> {}
+            fn remove_user(type: User) {}
+        }
+        mod other {
+            struct Page { total: u128 }
+            impl Page {
+                fn first<T>(x: T) -> T {}
+            }
+        }
+    "#;
+
+    #[test]
+    fn package_per_namespace() -> Result<()> {
+        let output = TestExecutor::new(API).generate(&mut Go::new(GoOptions {
+            module: "example.com/api".to_string(),
+            ..Default::default()
+        }));
+        assert!(output.get("api.go").is_none());
+        assert_eq!(
+            output.get("user/user.go").unwrap(),
+            r#"package user
+
+import (
+    "context"
+
+    "example.com/api/other"
+)
+
+type Kind int64
+
+const (
+    KindAdmin Kind = 1
+    KindGuest Kind = 2
+)
+
+// A user.
+type User struct {
+    UserId uint64 `json:"user_id"`
+    Name *string `json:"name,omitempty"`
+    Tags []string `json:"tags,omitempty"`
+    Kind Kind `json:"kind"`
+    Page other.Page `json:"page"`
+}
+
+type Service interface {
+    // Gets a user.
+    GetUser(ctx context.Context, id uint64, verbose *bool) (*User, error)
+    RemoveUser(ctx context.Context, type_ User) error
+}
+"#
+            .replace("    ", "\t")
+        );
+        assert_eq!(
+            output.get("other/other.go").unwrap(),
+            r#"package other
+
+import (
+    "context"
+    "math/big"
+)
+
+type Page struct {
+    Total *big.Int `json:"total"`
+}
+
+type PageService interface {
+    First(ctx context.Context, x any) (any, error)
+}
+"#
+            .replace("    ", "\t")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn root_package_and_generics() -> Result<()> {
+        let output = TestExecutor::new(
+            "struct Page<T> { items: Vec<T> } struct Empty {} struct Resp { page: Page<Empty> }",
+        )
+        .generate(&mut Go::default());
+        assert_eq!(
+            output.get("api.go").unwrap(),
+            "package api\n\ntype Page[T any] struct {\n\tItems []T `json:\"items\"`\n}\n\ntype Empty struct{}\n\ntype Resp struct {\n\tPage Page[Empty] `json:\"page\"`\n}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn comparable_map_keys() -> Result<()> {
+        let output = TestExecutor::new(
+            r#"
+            struct Index<K, V> { entries: HashMap<K, V> }
+            struct Pair<A, B> { a: A, b: B }
+            struct Nested<T, K, U> { t: T, keys: Vec<Option<HashMap<K, U>>> }
+            "#,
+        )
+        .generate(&mut Go::default());
+        let output = output.get("api.go").unwrap();
+        assert!(
+            output.contains("type Index[K comparable, V any] struct {"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("type Pair[A, B any] struct {"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("type Nested[T any, K comparable, U any] struct {"),
+            "{}",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(pascal_case("get_user"), "GetUser");
+        assert_eq!(pascal_case("User"), "User");
+        assert_eq!(package_name("user_profile"), "userprofile");
+        assert_eq!(package_name("2fa"), "p2fa");
+        assert_eq!(package_name("type"), "type_");
+
+        let packages = [vec!["a", "user"], vec!["b", "user"], vec!["c"]]
+            .into_iter()
+            .map(|path| path.into_iter().map(str::to_string).collect())
+            .collect();
+        assert_eq!(
+            import_names(&packages).into_values().collect::<Vec<_>>(),
+            vec!["a_user", "b_user", "c"]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::generator::Generator;
+use crate::model::chunk;
+use crate::output::Output;
+use crate::view;
+
+/// A generator that writes the dependencies between namespaces as a graph, so that the coupling
+/// between parts of the API can be visualized. There's a node for each namespace with any dtos,
+/// rpcs or enums, and an edge wherever something in one namespace refers to a type in another,
+/// labeled with the number of references. See [crate::model::Dependencies::namespace_graph].
+///
+/// Written to `dependencies.dot` in the Graphviz DOT language, or `dependencies.mmd` as a Mermaid
+/// flowchart. Like [crate::generator::Report], the API is graphed as parsed, without any view
+/// transforms applied.
+#[derive(Debug, Default)]
+pub struct Graph {
+    options: GraphOptions,
+}
+
+/// Options for the [Graph] generator, from the `generator.graph` section of the
+/// [crate::config::Config].
+#[derive(Debug, Default, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct GraphOptions {
+    /// Defaults to [GraphFormat::Dot].
+    pub format: GraphFormat,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+impl Graph {
+    pub fn new(options: GraphOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Generator for Graph {
+    fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()> {
+        let graph = model.dependencies().namespace_graph();
+        let (path, content) = match self.options.format {
+            GraphFormat::Dot => ("dependencies.dot", graph.to_dot()),
+            GraphFormat::Mermaid => ("dependencies.mmd", graph.to_mermaid()),
+        };
+        output.write_chunk(&chunk::Chunk::with_relative_file_path(path))?;
+        output.write_str(&content)
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["dot", "mmd"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::generator::{Generator, Graph, GraphFormat, GraphOptions};
+    use crate::output::Buffer;
+    use crate::testing::TestExecutor;
+    use crate::view;
+
+    const API: &str = r#"
+        struct Id {}
+        mod user {
+            struct User { id: Id, orders: Vec<order::Order> }
+            fn get_user(id: Id) -> User {}
+        }
+        mod order {
+            struct Order { id: Id }
+        }
+        mod empty {}
+    "#;
+
+    fn run_test(format: GraphFormat) -> Result<String> {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let mut output = Buffer::default();
+        Graph::new(GraphOptions { format }).generate(view::Model::new(&model), &mut output)?;
+        Ok(output.to_string())
+    }
+
+    #[test]
+    fn dot() -> Result<()> {
+        assert_eq!(
+            run_test(GraphFormat::Dot)?,
+            r#"digraph api {
+    "(root)";
+    "order";
+    "user";
+    "order" -> "(root)" [label="1"];
+    "user" -> "(root)" [label="2"];
+    "user" -> "order" [label="1"];
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mermaid() -> Result<()> {
+        assert_eq!(
+            run_test(GraphFormat::Mermaid)?,
+            r#"flowchart LR
+    n0["(root)"]
+    n1["order"]
+    n2["user"]
+    n1 -->|1| n0
+    n2 -->|2| n0
+    n2 -->|1| n1
+"#
+        );
+        Ok(())
+    }
+}
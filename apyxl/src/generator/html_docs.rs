@@ -0,0 +1,597 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::generator::Generator;
+use crate::model::chunk;
+use crate::output::Output;
+use crate::view::{
+    Attributes, Dto, Enum, InnerType, Model, Namespace, Rpc, Transformer, Type, UserTypeTargets,
+};
+
+/// A generator that writes a static HTML site for browsing the documentation of the API:
+/// - `index.html` lists every namespace, along with everything in the root namespace.
+/// - A page per namespace, e.g. `namespace.user.html`, lists its child namespaces, dtos and enums,
+///   and the signature of each of its rpcs.
+/// - A page per [Dto], e.g. `dto.user.User.html`, has a table of its fields and the signature of
+///   each of its rpcs.
+/// - A page per [Enum], e.g. `enum.user.Kind.html`, has a table of its values.
+///
+/// Types are written in the same language-agnostic notation as the changelog, e.g. `u64[]` or
+/// `map<string, user.User?>`, with dtos and enums linked to their pages. Comments become
+/// descriptions. User types are written using their `html` target, or their name. Every page
+/// shares the stylesheet in `style.css`.
+#[derive(Debug, Default)]
+pub struct HtmlDocs {
+    options: HtmlDocsOptions,
+}
+
+/// Options for the [HtmlDocs] generator, from the `generator.html` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct HtmlDocsOptions {
+    /// Title of the site, shown on every page. Defaults to `API`.
+    pub title: String,
+}
+
+impl Default for HtmlDocsOptions {
+    fn default() -> Self {
+        Self {
+            title: "API".to_string(),
+        }
+    }
+}
+
+impl HtmlDocs {
+    pub fn new(options: HtmlDocsOptions) -> Self {
+        Self { options }
+    }
+}
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "html";
+
+const INDEX: &str = "index.html";
+
+const STYLE: &str = r#"body { font-family: sans-serif; max-width: 60em; margin: 0 auto; padding: 1em; color: #222; }
+nav { margin-bottom: 1em; }
+a { color: #0550ae; text-decoration: none; }
+a:hover { text-decoration: underline; }
+code { font-family: monospace; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #ddd; padding: 0.4em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+section.rpc { border-top: 1px solid #ddd; }
+"#;
+
+impl Generator for HtmlDocs {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model.with_user_type_targets(user_ty_targets);
+
+        let mut pages = HashMap::new();
+        collect_pages(model.api(), &[], &mut pages);
+        let site = Site {
+            title: &self.options.title,
+            pages,
+        };
+
+        output.write_chunk(&chunk::Chunk::with_relative_file_path("style.css"))?;
+        output.write_str(STYLE)?;
+        site.write_namespace(model.api(), &[], output)
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["html", "css"]
+    }
+}
+
+/// Adds the page of every dto and enum within the namespace at `path`, recursively, to `pages`.
+fn collect_pages(namespace: Namespace, path: &[String], pages: &mut HashMap<String, String>) {
+    for dto in namespace.dtos() {
+        let qualified_name = qualified_name(path, &dto.name());
+        pages.insert(qualified_name.clone(), page_file("dto", &qualified_name));
+    }
+    for en in namespace.enums() {
+        let qualified_name = qualified_name(path, &en.name());
+        pages.insert(qualified_name.clone(), page_file("enum", &qualified_name));
+    }
+    for nested_ns in namespace.namespaces() {
+        let nested_path = [path, &[nested_ns.name().to_string()]].concat();
+        collect_pages(nested_ns, &nested_path, pages);
+    }
+}
+
+struct Site<'a> {
+    title: &'a str,
+    /// File names of the dto and enum pages, by dot-separated path.
+    pages: HashMap<String, String>,
+}
+
+impl Site<'_> {
+    /// Writes the page of the namespace at `path`, and everything within it, recursively.
+    fn write_namespace(
+        &self,
+        namespace: Namespace,
+        path: &[String],
+        output: &mut dyn Output,
+    ) -> Result<()> {
+        let mut body = description(&namespace.attributes());
+
+        let nested = if path.is_empty() {
+            namespace_tree(&namespace, path)
+        } else {
+            namespace
+                .namespaces()
+                .map(|nested_ns| {
+                    let nested_path = [path, &[nested_ns.name().to_string()]].concat();
+                    list_item(&namespace_file(&nested_path), &nested_ns.name(), "")
+                })
+                .join("")
+        };
+        list_section(&mut body, "Namespaces", &nested);
+
+        let dtos = namespace
+            .dtos()
+            .map(|dto| {
+                let qualified_name = qualified_name(path, &dto.name());
+                list_item(
+                    &page_file("dto", &qualified_name),
+                    &dto.name(),
+                    &summary(&dto.attributes()),
+                )
+            })
+            .join("");
+        list_section(&mut body, "DTOs", &dtos);
+
+        let enums = namespace
+            .enums()
+            .map(|en| {
+                let qualified_name = qualified_name(path, &en.name());
+                list_item(
+                    &page_file("enum", &qualified_name),
+                    &en.name(),
+                    &summary(&en.attributes()),
+                )
+            })
+            .join("");
+        list_section(&mut body, "Enums", &enums);
+
+        self.rpc_section(&mut body, namespace.rpcs());
+
+        let (file, heading) = if path.is_empty() {
+            (INDEX.to_string(), escape(self.title))
+        } else {
+            (
+                namespace_file(path),
+                format!("Namespace <code>{}</code>", escape(&path.join("."))),
+            )
+        };
+        self.write_page(&file, &heading, path, &body, output)?;
+
+        for dto in namespace.dtos() {
+            self.write_dto(&dto, path, output)?;
+        }
+        for en in namespace.enums() {
+            self.write_enum(&en, path, output)?;
+        }
+        for nested_ns in namespace.namespaces() {
+            let nested_path = [path, &[nested_ns.name().to_string()]].concat();
+            self.write_namespace(nested_ns, &nested_path, output)?;
+        }
+        Ok(())
+    }
+
+    fn write_dto(&self, dto: &Dto, path: &[String], output: &mut dyn Output) -> Result<()> {
+        let qualified_name = qualified_name(path, &dto.name());
+        let mut body = description(&dto.attributes());
+
+        let rows = dto
+            .fields()
+            .map(|field| {
+                format!(
+                    "<tr><td><code>{}</code></td><td><code>{}</code></td><td>{}</td></tr>\n",
+                    escape(&field.name()),
+                    self.type_html(&field.ty()),
+                    description(&field.attributes()),
+                )
+            })
+            .join("");
+        if !rows.is_empty() {
+            body.push_str("<h2>Fields</h2>\n<table>\n");
+            body.push_str("<tr><th>Name</th><th>Type</th><th>Description</th></tr>\n");
+            body.push_str(&rows);
+            body.push_str("</table>\n");
+        }
+
+        self.rpc_section(&mut body, dto.rpcs());
+
+        let heading = format!(
+            "DTO <code>{}{}</code>",
+            escape(&qualified_name),
+            type_params(dto.type_params())
+        );
+        self.write_page(
+            &page_file("dto", &qualified_name),
+            &heading,
+            path,
+            &body,
+            output,
+        )
+    }
+
+    fn write_enum(&self, en: &Enum, path: &[String], output: &mut dyn Output) -> Result<()> {
+        let qualified_name = qualified_name(path, &en.name());
+        let mut body = description(&en.attributes());
+
+        let rows = en
+            .values()
+            .map(|value| {
+                format!(
+                    "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                    escape(&value.name()),
+                    value.number(),
+                    description(&value.attributes()),
+                )
+            })
+            .join("");
+        if !rows.is_empty() {
+            body.push_str("<h2>Values</h2>\n<table>\n");
+            body.push_str("<tr><th>Name</th><th>Value</th><th>Description</th></tr>\n");
+            body.push_str(&rows);
+            body.push_str("</table>\n");
+        }
+
+        let heading = format!("Enum <code>{}</code>", escape(&qualified_name));
+        self.write_page(
+            &page_file("enum", &qualified_name),
+            &heading,
+            path,
+            &body,
+            output,
+        )
+    }
+
+    /// Appends a section with the signature and description of each of the `rpcs`, if there are
+    /// any. Each rpc can be linked to by its name as the fragment, e.g. `#get_user`.
+    fn rpc_section<'v, 'a: 'v>(&self, body: &mut String, rpcs: impl Iterator<Item = Rpc<'v, 'a>>) {
+        let sections = rpcs.map(|rpc| self.rpc_html(&rpc)).join("");
+        if !sections.is_empty() {
+            body.push_str("<h2>RPCs</h2>\n");
+            body.push_str(&sections);
+        }
+    }
+
+    fn rpc_html(&self, rpc: &Rpc) -> String {
+        let params = rpc
+            .params()
+            .map(|param| format!("{}: {}", escape(&param.name()), self.type_html(&param.ty())))
+            .join(", ");
+        let return_type = rpc
+            .return_type()
+            .map(|ty| format!(" -&gt; {}", self.type_html(&ty)))
+            .unwrap_or_default();
+        let mut html = format!(
+            "<section class=\"rpc\" id=\"{name}\">\n<h3><code>{async_}{name}{type_params}({params}){return_type}</code></h3>\n",
+            name = escape(&rpc.name()),
+            async_ = if rpc.is_async() { "async " } else { "" },
+            type_params = type_params(rpc.type_params()),
+        );
+        html.push_str(&description(&rpc.attributes()));
+        if let Some(error_type) = rpc.error_type() {
+            html.push_str(&format!(
+                "<p>Errors with <code>{}</code>.</p>\n",
+                self.type_html(&error_type)
+            ));
+        }
+        html.push_str("</section>\n");
+        html
+    }
+
+    fn type_html(&self, ty: &Type) -> String {
+        self.inner_type_html(ty.inner())
+    }
+
+    fn inner_type_html(&self, ty: InnerType) -> String {
+        match ty {
+            InnerType::Bool => "bool".to_string(),
+            InnerType::U8 => "u8".to_string(),
+            InnerType::U16 => "u16".to_string(),
+            InnerType::U32 => "u32".to_string(),
+            InnerType::U64 => "u64".to_string(),
+            InnerType::U128 => "u128".to_string(),
+            InnerType::I8 => "i8".to_string(),
+            InnerType::I16 => "i16".to_string(),
+            InnerType::I32 => "i32".to_string(),
+            InnerType::I64 => "i64".to_string(),
+            InnerType::I128 => "i128".to_string(),
+            InnerType::F8 => "f8".to_string(),
+            InnerType::F16 => "f16".to_string(),
+            InnerType::F32 => "f32".to_string(),
+            InnerType::F64 => "f64".to_string(),
+            InnerType::F128 => "f128".to_string(),
+            InnerType::String => "string".to_string(),
+            InnerType::Bytes => "bytes".to_string(),
+            InnerType::User(name) => escape(name),
+            InnerType::Api(id, args) => {
+                let qualified_name = id.path().join(".");
+                let html = match self.pages.get(&qualified_name) {
+                    // Types that were filtered out of the view have no page to link to.
+                    None => escape(&qualified_name),
+                    Some(file) => link(file, &qualified_name),
+                };
+                if args.is_empty() {
+                    html
+                } else {
+                    let args = args.into_iter().map(|arg| self.inner_type_html(arg));
+                    format!("{}&lt;{}&gt;", html, args.collect::<Vec<_>>().join(", "))
+                }
+            }
+            InnerType::Generic(name) => escape(name),
+            InnerType::Array(ty) => format!("{}[]", self.inner_type_html(*ty)),
+            InnerType::Map { key, value } => format!(
+                "map&lt;{}, {}&gt;",
+                self.inner_type_html(*key),
+                self.inner_type_html(*value)
+            ),
+            InnerType::Optional(ty) => format!("{}?", self.inner_type_html(*ty)),
+        }
+    }
+
+    /// Writes a complete page to `file`, with navigation links to the index and each of the
+    /// namespaces in `path`. `heading` and `body` are HTML.
+    fn write_page(
+        &self,
+        file: &str,
+        heading: &str,
+        path: &[String],
+        body: &str,
+        output: &mut dyn Output,
+    ) -> Result<()> {
+        let mut nav = link(INDEX, self.title);
+        for i in 0..path.len() {
+            nav.push_str(" / ");
+            nav.push_str(&link(&namespace_file(&path[..=i]), &path[i]));
+        }
+        let title = if file == INDEX {
+            escape(self.title)
+        } else {
+            format!("{} - {}", strip_tags(heading), escape(self.title))
+        };
+        output.write_chunk(&chunk::Chunk::with_relative_file_path(file))?;
+        output.write_str(&format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>{title}</title>\n<link rel=\"stylesheet\" href=\"style.css\">\n</head>\n\
+            <body>\n<nav>{nav}</nav>\n<h1>{heading}</h1>\n{body}</body>\n</html>\n"
+        ))
+    }
+}
+
+/// A nested list of every namespace within `namespace`, recursively.
+fn namespace_tree(namespace: &Namespace, path: &[String]) -> String {
+    namespace
+        .namespaces()
+        .map(|nested_ns| {
+            let nested_path = [path, &[nested_ns.name().to_string()]].concat();
+            let nested = namespace_tree(&nested_ns, &nested_path);
+            let nested = if nested.is_empty() {
+                nested
+            } else {
+                format!("<ul>\n{}</ul>\n", nested)
+            };
+            format!(
+                "<li>{}\n{}</li>\n",
+                link(&namespace_file(&nested_path), &nested_ns.name()),
+                nested
+            )
+        })
+        .join("")
+}
+
+/// Appends a section with a `heading` and list of `items`, if there are any.
+fn list_section(body: &mut String, heading: &str, items: &str) {
+    if !items.is_empty() {
+        body.push_str(&format!("<h2>{}</h2>\n<ul>\n{}</ul>\n", heading, items));
+    }
+}
+
+fn list_item(file: &str, name: &str, summary: &str) -> String {
+    if summary.is_empty() {
+        format!("<li>{}</li>\n", link(file, name))
+    } else {
+        format!("<li>{} - {}</li>\n", link(file, name), escape(summary))
+    }
+}
+
+/// A paragraph for each of the comments in `attributes`.
+fn description(attributes: &Attributes) -> String {
+    attributes
+        .comments()
+        .iter()
+        .map(|comment| format!("<p>{}</p>\n", escape(&comment.lines().join("\n"))))
+        .join("")
+}
+
+/// The first line of the first comment in `attributes`, or empty if there isn't one.
+fn summary(attributes: &Attributes) -> String {
+    attributes
+        .comments()
+        .first()
+        .and_then(|comment| comment.lines().next().map(|line| line.trim().to_string()))
+        .unwrap_or_default()
+}
+
+fn type_params(type_params: &[&str]) -> String {
+    if type_params.is_empty() {
+        String::new()
+    } else {
+        format!("&lt;{}&gt;", escape(&type_params.join(", ")))
+    }
+}
+
+fn link(file: &str, text: &str) -> String {
+    format!("<a href=\"{}\">{}</a>", escape(file), escape(text))
+}
+
+fn namespace_file(path: &[String]) -> String {
+    page_file("namespace", &path.join("."))
+}
+
+fn page_file(kind: &str, qualified_name: &str) -> String {
+    format!("{}.{}.html", kind, qualified_name)
+}
+
+fn qualified_name(path: &[String], name: &str) -> String {
+    path.iter()
+        .map(String::as_str)
+        .chain([name])
+        .collect_vec()
+        .join(".")
+}
+
+/// The text of `html` without any tags, still escaped, e.g. for the page title.
+fn strip_tags(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            c if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    text
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+
+    use crate::generator::{HtmlDocs, HtmlDocsOptions};
+    use crate::output;
+    use crate::testing::TestExecutor;
+
+    const API: &str = r#"
+        mod user {
+            // A user.
+            struct User {
+                id: u64,
+                // Display <name>.
+                name: Option<String>,
+                friends: Vec<User>,
+                kind: Kind,
+            }
+            enum Kind { Admin = 1, Guest = 2 }
+            // Gets a user.
+            fn get_user(id: u64) -> Result<Option<User>, Kind> {}
+            mod nested {}
+        }
+        fn ping() {}
+    "#;
+
+    fn run_test() -> Result<output::ChunkBuffer> {
+        let mut exe = TestExecutor::new(API);
+        Ok(exe.generate(&mut HtmlDocs::new(HtmlDocsOptions {
+            title: "Users".to_string(),
+        })))
+    }
+
+    #[test]
+    fn writes_page_per_entity() -> Result<()> {
+        let output = run_test()?;
+        assert_eq!(
+            output.paths().collect::<Vec<_>>(),
+            vec![
+                Path::new("dto.user.User.html"),
+                Path::new("enum.user.Kind.html"),
+                Path::new("index.html"),
+                Path::new("namespace.user.html"),
+                Path::new("namespace.user.nested.html"),
+                Path::new("style.css"),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn index() -> Result<()> {
+        let output = run_test()?;
+        let index = output.get("index.html").unwrap();
+        assert!(index.contains("<title>Users</title>"), "{}", index);
+        assert!(index.contains(
+            "<li><a href=\"namespace.user.html\">user</a>\n\
+            <ul>\n<li><a href=\"namespace.user.nested.html\">nested</a>\n</li>\n</ul>\n</li>"
+        ));
+        assert!(index.contains("<h3><code>ping()</code></h3>"));
+        Ok(())
+    }
+
+    #[test]
+    fn namespace() -> Result<()> {
+        let output = run_test()?;
+        let namespace = output.get("namespace.user.html").unwrap();
+        assert!(namespace.contains(
+            "<nav><a href=\"index.html\">Users</a> / <a href=\"namespace.user.html\">user</a></nav>"
+        ));
+        assert!(namespace.contains("<li><a href=\"dto.user.User.html\">User</a> - A user.</li>"));
+        assert!(namespace.contains("<li><a href=\"enum.user.Kind.html\">Kind</a></li>"));
+        assert!(namespace.contains(
+            "<section class=\"rpc\" id=\"get_user\">\n\
+            <h3><code>get_user(id: u64) -&gt; <a href=\"dto.user.User.html\">user.User</a>?</code></h3>\n\
+            <p>Gets a user.</p>\n\
+            <p>Errors with <code><a href=\"enum.user.Kind.html\">user.Kind</a></code>.</p>\n\
+            </section>"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn dto_fields() -> Result<()> {
+        let output = run_test()?;
+        let dto = output.get("dto.user.User.html").unwrap();
+        assert!(
+            dto.contains("<title>DTO user.User - Users</title>"),
+            "{}",
+            dto
+        );
+        assert!(dto.contains(
+            "<tr><td><code>name</code></td><td><code>string?</code></td>\
+            <td><p>Display &lt;name&gt;.</p>\n</td></tr>"
+        ));
+        assert!(dto.contains(
+            "<tr><td><code>friends</code></td>\
+            <td><code><a href=\"dto.user.User.html\">user.User</a>[]</code></td><td></td></tr>"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn enum_values() -> Result<()> {
+        let output = run_test()?;
+        let en = output.get("enum.user.Kind.html").unwrap();
+        assert!(en.contains("<tr><td><code>Guest</code></td><td>2</td><td></td></tr>"));
+        Ok(())
+    }
+}
@@ -0,0 +1,26 @@
+use anyhow::Result;
+
+use crate::generator::Generator;
+use crate::model::chunk;
+use crate::output::Output;
+use crate::view;
+
+/// A generator that writes out the [crate::model::Api] as pretty-printed JSON to `api.json`, so
+/// that it can be stored and read back later, e.g. as the baseline of a
+/// [crate::generator::Changelog].
+///
+/// The API is written as parsed, without any view transforms applied.
+#[derive(Debug, Default)]
+pub struct Json {}
+
+impl Generator for Json {
+    fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()> {
+        output.write_chunk(&chunk::Chunk::with_relative_file_path("api.json"))?;
+        output.write_str(&serde_json::to_string_pretty(model.target().api())?)?;
+        output.write_str("\n")
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json"]
+    }
+}
@@ -0,0 +1,623 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::generator::Generator;
+use crate::model::{Chunk, Comment};
+use crate::output::{Indented, Output};
+use crate::view::{
+    Attributes, Dto, EntityId, Enum, EnumValue, Field, IdentifierPolicy, InnerType, Model,
+    Namespace, Order, Rpc, Transformer, Type, UserTypeTargets,
+};
+
+/// A generator that writes Kotlin declarations for the API, with a package per namespace:
+/// - [Dto]s become data classes, or plain classes if they have no fields. Their [Rpc]s become
+///   member function stubs that throw until implemented.
+/// - [Enum]s become enum classes with a `value` property for the number of each value.
+/// - The [Rpc]s of each namespace become the functions of an interface named after it, e.g.
+///   `UserService` for `user`, or `ApiService` for the root namespace. Async rpcs are `suspend`.
+///
+/// Each namespace with any entities is written to `Api.kt` in the directory of its package,
+/// e.g. `com/example/api/user/Api.kt`. See [KotlinOptions::package].
+///
+/// Unsigned integers are written as Kotlin's unsigned types, and 128-bit integers as
+/// `java.math.BigInteger`. Names that are Kotlin keywords are escaped with backticks. Error types
+/// have no Kotlin equivalent, since exceptions aren't declared, and are not written.
+#[derive(Debug, Default)]
+pub struct Kotlin {
+    options: KotlinOptions,
+}
+
+/// Options for the [Kotlin] generator, from the `generator.kotlin` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct KotlinOptions {
+    /// Package that the packages of all namespaces are nested within, e.g. `com.example.api` to
+    /// write namespace `user` to package `com.example.api.user`. Defaults to none, which puts the
+    /// root namespace in the default package. Kotlin can't refer to the default package from
+    /// other packages, so set this if other namespaces refer to entities in the root namespace.
+    pub package: String,
+
+    /// How non-ASCII identifiers are written. Kotlin allows them, so defaults to
+    /// [IdentifierPolicy::Keep].
+    pub identifiers: IdentifierPolicy,
+
+    /// The order entities are written in within each kind. Defaults to [Order::Source].
+    pub order: Order,
+}
+
+impl Kotlin {
+    pub fn new(options: KotlinOptions) -> Self {
+        Self { options }
+    }
+}
+
+const INDENT: &str = "    "; // 4 spaces.
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "kotlin";
+
+/// Hard keywords, which can't be used as identifiers without backticks.
+const KEYWORDS: &[&str] = &[
+    "as",
+    "break",
+    "class",
+    "continue",
+    "do",
+    "else",
+    "false",
+    "for",
+    "fun",
+    "if",
+    "in",
+    "interface",
+    "is",
+    "null",
+    "object",
+    "package",
+    "return",
+    "super",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typealias",
+    "typeof",
+    "val",
+    "var",
+    "when",
+    "while",
+];
+
+/// The package being written, which determines how references to other entities are written.
+#[derive(Debug, Copy, Clone)]
+struct Scope<'s> {
+    /// From [KotlinOptions::package].
+    base: &'s [String],
+    /// Path of the namespace being written, relative to `base`.
+    path: &'s [String],
+}
+
+impl Scope<'_> {
+    fn package(&self) -> Vec<String> {
+        [self.base, self.path].concat()
+    }
+}
+
+impl Generator for Kotlin {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model
+            .with_user_type_targets(user_ty_targets)
+            .with_identifier_policy(self.options.identifiers)
+            .with_order(self.options.order);
+        let base = self
+            .options
+            .package
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect_vec();
+        let mut o = Indented::new(output, INDENT);
+        write_package(
+            model.api(),
+            Scope {
+                base: &base,
+                path: &[],
+            },
+            &mut o,
+        )
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["kt"]
+    }
+}
+
+/// Writes the file of the namespace in `scope`, if it has any entities, then the files of its
+/// child namespaces.
+fn write_package(namespace: Namespace, scope: Scope, o: &mut Indented) -> Result<()> {
+    let has_entities = namespace.dtos().next().is_some()
+        || namespace.enums().next().is_some()
+        || namespace.rpcs().next().is_some();
+    if has_entities {
+        let package = scope.package();
+        o.write_chunk(&Chunk::with_relative_file_path(package_file(&package)))?;
+        write_attributes(&namespace.attributes(), o)?;
+        if !package.is_empty() {
+            o.write_str("package ")?;
+            o.write_str(&package.iter().map(|s| ident(s)).join("."))?;
+            o.newline()?;
+            o.newline()?;
+        }
+
+        for en in namespace.enums() {
+            write_enum(en, o)?;
+            o.newline()?;
+        }
+
+        for dto in namespace.dtos() {
+            write_dto(dto, scope, o)?;
+            o.newline()?;
+        }
+
+        if namespace.rpcs().next().is_some() {
+            write_service(&namespace, scope, o)?;
+        }
+    }
+
+    for nested_ns in namespace.namespaces() {
+        let nested_path = [scope.path, &[nested_ns.name().to_string()]].concat();
+        let nested_scope = Scope {
+            path: &nested_path,
+            ..scope
+        };
+        write_package(nested_ns, nested_scope, o)?;
+    }
+    Ok(())
+}
+
+fn write_service(namespace: &Namespace, scope: Scope, o: &mut Indented) -> Result<()> {
+    o.write_str("interface ")?;
+    o.write_str(&service_name(scope.path.last().map(String::as_str)))?;
+    o.write(' ')?;
+    write_block_start(o)?;
+
+    for (i, rpc) in namespace.rpcs().enumerate() {
+        if i > 0 {
+            o.newline()?;
+        }
+        write_rpc_signature(&rpc, scope, o)?;
+        o.newline()?;
+    }
+
+    write_block_end(o)
+}
+
+fn write_dto(dto: Dto, scope: Scope, o: &mut Indented) -> Result<()> {
+    write_attributes(&dto.attributes(), o)?;
+
+    let has_fields = dto.fields().next().is_some();
+    if has_fields {
+        o.write_str("data ")?;
+    }
+    o.write_str("class ")?;
+    o.write_str(&ident(&dto.name()))?;
+    write_type_params(dto.type_params(), o)?;
+
+    if has_fields {
+        o.write('(')?;
+        o.indent(1);
+        for field in dto.fields() {
+            o.newline()?;
+            write_field(field, scope, o)?;
+            o.write(',')?;
+        }
+        o.indent(-1);
+        o.newline()?;
+        o.write(')')?;
+    }
+
+    if dto.rpcs().next().is_some() {
+        o.write(' ')?;
+        write_block_start(o)?;
+        for (i, rpc) in dto.rpcs().enumerate() {
+            if i > 0 {
+                o.newline()?;
+            }
+            write_rpc_signature(&rpc, scope, o)?;
+            o.write_str(" = TODO(\"not implemented: ")?;
+            o.write_str(&rpc.name())?;
+            o.write_str("\")")?;
+            o.newline()?;
+        }
+        write_block_end(o)
+    } else {
+        o.newline()
+    }
+}
+
+/// Writes e.g. `<K, V>`, or nothing if there are no `type_params`.
+fn write_type_params(type_params: &[&str], o: &mut dyn Output) -> Result<()> {
+    if type_params.is_empty() {
+        return Ok(());
+    }
+    o.write('<')?;
+    o.write_str(&type_params.iter().map(|s| ident(s)).join(", "))?;
+    o.write('>')
+}
+
+/// Writes the declaration of `rpc`, without a body.
+fn write_rpc_signature(rpc: &Rpc, scope: Scope, o: &mut Indented) -> Result<()> {
+    write_attributes(&rpc.attributes(), o)?;
+
+    if rpc.is_async() {
+        o.write_str("suspend ")?;
+    }
+    o.write_str("fun ")?;
+    if !rpc.type_params().is_empty() {
+        write_type_params(rpc.type_params(), o)?;
+        o.write(' ')?;
+    }
+    o.write_str(&ident(&rpc.name()))?;
+
+    o.write('(')?;
+    o.indent(1);
+    for param in rpc.params() {
+        o.newline()?;
+        write_param(param, scope, o)?;
+        o.write(',')?;
+    }
+    o.indent(-1);
+    if rpc.params().next().is_some() {
+        o.newline()?;
+    }
+    o.write(')')?;
+
+    if let Some(return_type) = rpc.return_type() {
+        o.write_str(": ")?;
+        write_type(return_type, scope, o)?;
+    }
+    Ok(())
+}
+
+fn write_enum(en: Enum, o: &mut Indented) -> Result<()> {
+    write_attributes(&en.attributes(), o)?;
+
+    o.write_str("enum class ")?;
+    o.write_str(&ident(&en.name()))?;
+    o.write_str("(val value: Long) ")?;
+    write_block_start(o)?;
+
+    for value in en.values() {
+        write_enum_value(value, o)?;
+        o.newline()?;
+    }
+
+    write_block_end(o)
+}
+
+fn write_enum_value(value: EnumValue, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&value.attributes(), o)?;
+
+    o.write_str(&ident(&value.name()))?;
+    o.write('(')?;
+    o.write_str(&value.number().to_string())?;
+    o.write_str("),")
+}
+
+fn write_block_start(o: &mut Indented) -> Result<()> {
+    o.write_str("{")?;
+    o.indent(1);
+    o.newline()
+}
+
+fn write_block_end(o: &mut Indented) -> Result<()> {
+    o.indent(-1);
+    o.write_str("}")?;
+    o.newline()
+}
+
+/// Optional fields default to `null`, so they can be left out when constructing the class.
+fn write_field(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&field.attributes(), o)?;
+
+    o.write_str("val ")?;
+    o.write_str(&ident(&field.name()))?;
+    o.write_str(": ")?;
+    let ty = field.ty();
+    let is_optional = matches!(ty.inner(), InnerType::Optional(_));
+    write_type(ty, scope, o)?;
+    if is_optional {
+        o.write_str(" = null")?;
+    }
+    Ok(())
+}
+
+fn write_param(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&field.attributes(), o)?;
+
+    o.write_str(&ident(&field.name()))?;
+    o.write_str(": ")?;
+    write_type(field.ty(), scope, o)
+}
+
+fn write_attributes(attributes: &Attributes, o: &mut dyn Output) -> Result<()> {
+    write_comments(&attributes.comments(), o)
+}
+
+/// Writes all comments as a single KDoc comment, with an empty line between each [Comment].
+fn write_comments(comments: &[Comment], o: &mut dyn Output) -> Result<()> {
+    if comments.is_empty() {
+        return Ok(());
+    }
+    o.write_str("/**")?;
+    o.newline()?;
+    for (i, comment) in comments.iter().enumerate() {
+        if i > 0 {
+            o.write_str(" *")?;
+            o.newline()?;
+        }
+        for line in comment.lines() {
+            o.write_str(" *")?;
+            if !line.is_empty() {
+                o.write(' ')?;
+                o.write_str(line)?;
+            }
+            o.newline()?;
+        }
+    }
+    o.write_str(" */")?;
+    o.newline()
+}
+
+fn write_type(ty: Type, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_inner_type(ty.inner(), scope, o)
+}
+
+fn write_inner_type(ty: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    match ty {
+        InnerType::Bool => o.write_str("Boolean"),
+        InnerType::U8 => o.write_str("UByte"),
+        InnerType::U16 => o.write_str("UShort"),
+        InnerType::U32 => o.write_str("UInt"),
+        InnerType::U64 => o.write_str("ULong"),
+        InnerType::I8 => o.write_str("Byte"),
+        InnerType::I16 => o.write_str("Short"),
+        InnerType::I32 => o.write_str("Int"),
+        InnerType::I64 => o.write_str("Long"),
+        InnerType::U128 | InnerType::I128 => o.write_str("java.math.BigInteger"),
+        InnerType::F8 | InnerType::F16 | InnerType::F32 => o.write_str("Float"),
+        InnerType::F64 | InnerType::F128 => o.write_str("Double"),
+        InnerType::String => o.write_str("String"),
+        InnerType::Bytes => o.write_str("ByteArray"),
+        // Either the `kotlin` target of the user type, or just its name.
+        InnerType::User(s) => o.write_str(s),
+        InnerType::Api(id, args) => write_api_type(id, args, scope, o),
+        InnerType::Generic(name) => o.write_str(&ident(name)),
+        InnerType::Array(ty) => {
+            o.write_str("List<")?;
+            write_inner_type(*ty, scope, o)?;
+            o.write('>')
+        }
+        InnerType::Map { key, value } => {
+            o.write_str("Map<")?;
+            write_inner_type(*key, scope, o)?;
+            o.write_str(", ")?;
+            write_inner_type(*value, scope, o)?;
+            o.write('>')
+        }
+        InnerType::Optional(ty) => {
+            write_inner_type(*ty, scope, o)?;
+            o.write('?')
+        }
+    }
+}
+
+/// References to entities in other packages are fully qualified, since nothing is imported.
+/// Writes e.g. `Page<User>`, or just `Page` if there are no type `args`.
+fn write_api_type(
+    id: EntityId,
+    args: Vec<InnerType>,
+    scope: Scope,
+    o: &mut dyn Output,
+) -> Result<()> {
+    write_entity_id(id, scope, o)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+    o.write('<')?;
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            o.write_str(", ")?;
+        }
+        write_inner_type(arg, scope, o)?;
+    }
+    o.write('>')
+}
+
+fn write_entity_id(entity_id: EntityId, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    let path = entity_id.path();
+    let (name, namespace) = match path.split_last() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    if !namespace.iter().eq(scope.path.iter()) {
+        for component in scope
+            .base
+            .iter()
+            .map(String::as_str)
+            .chain(namespace.iter().map(Cow::as_ref))
+        {
+            o.write_str(&ident(component))?;
+            o.write('.')?;
+        }
+    }
+    o.write_str(&ident(name))
+}
+
+/// Relative path of the file for `package`, e.g. `com/example/api/Api.kt`.
+fn package_file(package: &[String]) -> String {
+    if package.is_empty() {
+        "Api.kt".to_string()
+    } else {
+        format!("{}/Api.kt", package.join("/"))
+    }
+}
+
+/// Name of the interface for the rpcs of the namespace named `namespace`, e.g. `UserProfileService`
+/// for `user_profile`, or `ApiService` for the root namespace.
+fn service_name(namespace: Option<&str>) -> String {
+    let namespace = match namespace {
+        None => return "ApiService".to_string(),
+        Some(namespace) => namespace,
+    };
+    let mut name = namespace
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+            }
+        })
+        .join("");
+    name.push_str("Service");
+    name
+}
+
+/// `name`, escaped with backticks if it's a Kotlin keyword.
+fn ident(name: &str) -> Cow<'_, str> {
+    if KEYWORDS.contains(&name) {
+        Cow::Owned(format!("`{}`", name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::generator::kotlin::service_name;
+    use crate::generator::{Kotlin, KotlinOptions};
+    use crate::testing::TestExecutor;
+
+    const API: &str = r#"
+        mod user {
+            /// A user.
+            struct User {
+                id: u64,
+                name: Option<String>,
+                tags: Vec<Option<String>>,
+                kind: Kind,
+                val: i32,
+            }
+            struct Empty {}
+            enum Kind { Admin = 1, Guest = 2 }
+            /// Gets a user.
+            async fn get_user(id: u64, verbose: Option<bool>) -> Option<User> {}
+            fn remove_user(user: User) {}
+        }
+        mod other {
+            struct Page<T> { items: Vec<T>, owner: user::User }
+        }
+    "#;
+
+    #[test]
+    fn package_per_namespace() -> Result<()> {
+        let output = TestExecutor::new(API).generate(&mut Kotlin::new(KotlinOptions {
+            package: "com.example".to_string(),
+            ..Default::default()
+        }));
+        assert_eq!(
+            output.get("com/example/user/Api.kt").unwrap(),
+            r#"package com.example.user
+
+enum class Kind(val value: Long) {
+    Admin(1),
+    Guest(2),
+}
+
+/**
+ * A user.
+ */
+data class User(
+    val id: ULong,
+    val name: String? = null,
+    val tags: List<String?>,
+    val kind: Kind,
+    val `val`: Int,
+)
+
+class Empty
+
+interface UserService {
+    /**
+     * Gets a user.
+     */
+    suspend fun get_user(
+        id: ULong,
+        verbose: Boolean?,
+    ): User?
+
+    fun remove_user(
+        user: User,
+    )
+}
+"#
+        );
+        assert_eq!(
+            output.get("com/example/other/Api.kt").unwrap(),
+            r#"package com.example.other
+
+data class Page<T>(
+    val items: List<T>,
+    val owner: com.example.user.User,
+)
+
+"#
+        );
+        assert!(output.get("com/example/Api.kt").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn root_namespace_and_dto_rpcs() -> Result<()> {
+        let output = TestExecutor::new(
+            r#"
+            struct Dto {}
+            impl Dto {
+                fn get(x: u8) -> i128 {}
+            }
+            fn ping() {}
+            "#,
+        )
+        .generate(&mut Kotlin::default());
+        assert_eq!(
+            output.get("Api.kt").unwrap(),
+            r#"class Dto {
+    fun get(
+        x: UByte,
+    ): java.math.BigInteger = TODO("not implemented: get")
+}
+
+interface ApiService {
+    fun ping()
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn service_names() {
+        assert_eq!(service_name(None), "ApiService");
+        assert_eq!(service_name(Some("user")), "UserService");
+        assert_eq!(service_name(Some("user_profile")), "UserProfileService");
+    }
+}
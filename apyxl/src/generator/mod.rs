@@ -1,15 +1,55 @@
 use anyhow::Result;
 use std::fmt::Debug;
 
+pub use changelog::{Changelog, ChangelogOptions};
 pub use dbg::Dbg;
-pub use rust::Rust;
+pub use go::{Go, GoOptions};
+pub use graph::{Graph, GraphFormat, GraphOptions};
+pub use html_docs::{HtmlDocs, HtmlDocsOptions};
+pub use json::Json;
+pub use kotlin::{Kotlin, KotlinOptions};
+pub use openapi::{OpenApi, OpenApiFormat, OpenApiOptions};
+pub use python::{Python, PythonOptions};
+pub use report::{Report, ReportFormat, ReportOptions};
+pub use rust::{Rust, RustOptions};
+pub use template::{Template, TemplateFile, TemplateOptions, TemplateScope};
+pub use typescript::{ModuleStyle, TypeScript, TypeScriptOptions};
+pub use wasm::Wasm;
 
 use crate::output::Output;
 use crate::view;
 
+mod changelog;
 mod dbg;
+mod go;
+mod graph;
+mod html_docs;
+mod json;
+mod kotlin;
+mod openapi;
+mod python;
+mod report;
 mod rust;
+mod template;
+mod typescript;
+mod wasm;
 
 pub trait Generator: Debug {
     fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()>;
+
+    /// File extensions, without the leading `.`, of the files this generator writes. Only used for
+    /// introspection, e.g. by [crate::Registry::generator_entries].
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+impl<G: Generator + ?Sized> Generator for Box<G> {
+    fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()> {
+        (**self).generate(model, output)
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        (**self).extensions()
+    }
 }
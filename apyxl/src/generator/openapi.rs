@@ -0,0 +1,571 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+use crate::generator::Generator;
+use crate::model::chunk;
+use crate::output::Output;
+use crate::view::{
+    Attributes, Dto, EntityId, Enum, Field, InnerType, Model, Namespace, Rpc, Transformer, Type,
+    UserTypeTargets,
+};
+
+/// A generator that writes an [OpenAPI 3](https://spec.openapis.org/oas/v3.0.3) document for the
+/// API, to `openapi.json` or `openapi.yaml`:
+/// - [Dto]s become object schemas in `components.schemas`, named by their dot-separated path,
+///   e.g. `user.User`. Optional fields are not `required`.
+/// - [Enum]s become integer schemas of their value numbers, with the value names in
+///   `x-enum-varnames`.
+/// - [Rpc]s become `POST` operations at their slash-separated path, e.g. `/user/get_user`. Params
+///   are the properties of a JSON request body, and the return type is the `200` response. The
+///   error type, if any, is the `default` response. Operations are tagged with the name of their
+///   [crate::model::Service], e.g. `Users` for `#[service(Users)]`, or otherwise the path of their
+///   namespace.
+/// - The [Rpc]s of a [Dto] become operations at the path of the dto, e.g. `/user/User/get`, tagged
+///   with its dot-separated path, e.g. `user.User`.
+///
+/// User types are written using their `openapi` target, which is either a JSON schema object,
+/// e.g. `{ "type": "string", "format": "uuid" }`, or the name of a type, e.g. `string`.
+#[derive(Debug, Default)]
+pub struct OpenApi {
+    options: OpenApiOptions,
+}
+
+/// Options for the [OpenApi] generator, from the `generator.openapi` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct OpenApiOptions {
+    /// Defaults to [OpenApiFormat::Json].
+    pub format: OpenApiFormat,
+
+    /// `info.title` of the document. Defaults to `API`.
+    pub title: String,
+
+    /// `info.version` of the document. Defaults to `1.0.0`.
+    pub version: String,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OpenApiFormat {
+    #[default]
+    Json,
+    Yaml,
+}
+
+impl Default for OpenApiOptions {
+    fn default() -> Self {
+        Self {
+            format: OpenApiFormat::Json,
+            title: "API".to_string(),
+            version: "1.0.0".to_string(),
+        }
+    }
+}
+
+impl OpenApi {
+    pub fn new(options: OpenApiOptions) -> Self {
+        Self { options }
+    }
+}
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "openapi";
+
+const OPENAPI_VERSION: &str = "3.0.3";
+
+#[derive(Debug, Serialize)]
+struct Document {
+    openapi: &'static str,
+    info: Info,
+    paths: BTreeMap<String, Value>,
+    components: Components,
+}
+
+#[derive(Debug, Serialize)]
+struct Info {
+    title: String,
+    version: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Components {
+    schemas: BTreeMap<String, Value>,
+}
+
+impl Generator for OpenApi {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model.with_user_type_targets(user_ty_targets);
+
+        let mut document = Document {
+            openapi: OPENAPI_VERSION,
+            info: Info {
+                title: self.options.title.clone(),
+                version: self.options.version.clone(),
+            },
+            paths: BTreeMap::new(),
+            components: Components {
+                schemas: BTreeMap::new(),
+            },
+        };
+        collect_namespace(model.api(), &[], &mut document);
+
+        match self.options.format {
+            OpenApiFormat::Json => {
+                output.write_chunk(&chunk::Chunk::with_relative_file_path("openapi.json"))?;
+                output.write_str(&serde_json::to_string_pretty(&document)?)?;
+                output.write_str("\n")
+            }
+            OpenApiFormat::Yaml => {
+                output.write_chunk(&chunk::Chunk::with_relative_file_path("openapi.yaml"))?;
+                output.write_str(&serde_yaml::to_string(&document)?)
+            }
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["json", "yaml"]
+    }
+}
+
+/// Adds everything within the namespace at `path`, recursively, to `document`.
+fn collect_namespace(namespace: Namespace, path: &[String], document: &mut Document) {
+    for dto in namespace.dtos() {
+        let name = qualified_name(path, &dto.name());
+        let dto_path = [path, &[dto.name().to_string()]].concat();
+        for rpc in dto.rpcs() {
+            document.paths.insert(
+                route(&dto_path, &rpc),
+                json!({ "post": operation(&rpc, &dto_path, &name) }),
+            );
+        }
+        document.components.schemas.insert(name, dto_schema(&dto));
+    }
+    for en in namespace.enums() {
+        let name = qualified_name(path, &en.name());
+        document.components.schemas.insert(name, enum_schema(&en));
+    }
+    for service in namespace.services() {
+        let tag = service.name.clone().unwrap_or_else(|| path.join("."));
+        for rpc in &service.rpcs {
+            document.paths.insert(
+                route(path, rpc),
+                json!({ "post": operation(rpc, path, &tag) }),
+            );
+        }
+    }
+    for nested_ns in namespace.namespaces() {
+        let nested_path = [path, &[nested_ns.name().to_string()]].concat();
+        collect_namespace(nested_ns, &nested_path, document);
+    }
+}
+
+/// Slash-separated path of `rpc` within the namespace or dto at `path`, e.g. `/user/get_user`.
+fn route(path: &[String], rpc: &Rpc) -> String {
+    format!("/{}", [path, &[rpc.name().to_string()]].concat().join("/"))
+}
+
+fn dto_schema(dto: &Dto) -> Value {
+    with_description(object_schema(dto.fields()), &dto.attributes())
+}
+
+/// Schema of an object with a property for each field. Optional fields are left out of
+/// `required` rather than made nullable.
+fn object_schema<'v, 'a: 'v>(fields: impl Iterator<Item = Field<'v, 'a>>) -> Value {
+    let mut properties = Map::new();
+    let mut required = vec![];
+    for field in fields {
+        let ty = field.ty();
+        let schema = match ty.inner() {
+            InnerType::Optional(ty) => inner_type_schema(*ty),
+            ty => {
+                required.push(Value::from(field.name().to_string()));
+                inner_type_schema(ty)
+            }
+        };
+        properties.insert(
+            field.name().to_string(),
+            with_description(schema, &field.attributes()),
+        );
+    }
+    let mut schema = json!({ "type": "object", "properties": properties });
+    if !required.is_empty() {
+        schema["required"] = Value::Array(required);
+    }
+    schema
+}
+
+fn enum_schema(en: &Enum) -> Value {
+    let values = en.values().collect_vec();
+    let schema = json!({
+        "type": "integer",
+        "enum": values.iter().map(|value| value.number()).collect_vec(),
+        "x-enum-varnames": values.iter().map(|value| value.name().to_string()).collect_vec(),
+    });
+    with_description(schema, &en.attributes())
+}
+
+/// `tag` is the name of the rpc's [crate::model::Service], if any, or the dot-separated path of its
+/// namespace, which is empty for the root.
+fn operation(rpc: &Rpc, path: &[String], tag: &str) -> Value {
+    let operation_id = qualified_name(path, &rpc.name());
+    let mut operation = json!({ "operationId": operation_id });
+    if !tag.is_empty() {
+        operation["tags"] = json!([tag]);
+    }
+
+    if rpc.params().count() > 0 {
+        operation["requestBody"] = json!({
+            "required": true,
+            "content": { "application/json": { "schema": object_schema(rpc.params()) } },
+        });
+    }
+
+    let mut response = json!({ "description": "Success" });
+    if let Some(return_type) = rpc.return_type() {
+        response["content"] = json!({
+            "application/json": { "schema": type_schema(return_type) }
+        });
+    }
+    operation["responses"] = json!({ "200": response });
+    if let Some(error_type) = rpc.error_type() {
+        operation["responses"]["default"] = json!({
+            "description": "Error",
+            "content": { "application/json": { "schema": type_schema(error_type) } },
+        });
+    }
+
+    with_description(operation, &rpc.attributes())
+}
+
+fn type_schema(ty: Type) -> Value {
+    inner_type_schema(ty.inner())
+}
+
+fn inner_type_schema(ty: InnerType) -> Value {
+    match ty {
+        InnerType::Bool => json!({ "type": "boolean" }),
+        InnerType::U8 | InnerType::U16 => {
+            json!({ "type": "integer", "format": "int32", "minimum": 0 })
+        }
+        InnerType::U32 | InnerType::U64 => {
+            json!({ "type": "integer", "format": "int64", "minimum": 0 })
+        }
+        InnerType::U128 => json!({ "type": "integer", "minimum": 0 }),
+        InnerType::I8 | InnerType::I16 | InnerType::I32 => {
+            json!({ "type": "integer", "format": "int32" })
+        }
+        InnerType::I64 => json!({ "type": "integer", "format": "int64" }),
+        InnerType::I128 => json!({ "type": "integer" }),
+        InnerType::F8 | InnerType::F16 | InnerType::F32 => {
+            json!({ "type": "number", "format": "float" })
+        }
+        InnerType::F64 => json!({ "type": "number", "format": "double" }),
+        InnerType::F128 => json!({ "type": "number" }),
+        InnerType::String => json!({ "type": "string" }),
+        InnerType::Bytes => json!({ "type": "string", "format": "byte" }),
+        InnerType::User(s) => user_type_schema(s),
+        // Schemas can't be generic, so type args are dropped, like the type params of the dto.
+        InnerType::Api(id, _) => json!({ "$ref": schema_ref(&id) }),
+        // OpenAPI has no generics, so a type parameter can be any value.
+        InnerType::Generic(_) => json!({}),
+        InnerType::Array(ty) => json!({ "type": "array", "items": inner_type_schema(*ty) }),
+        // JSON object keys are always strings, so the key type can't be represented.
+        InnerType::Map { value, .. } => {
+            json!({ "type": "object", "additionalProperties": inner_type_schema(*value) })
+        }
+        InnerType::Optional(ty) => nullable(inner_type_schema(*ty)),
+    }
+}
+
+/// Either the `openapi` target of the user type, or just its name.
+fn user_type_schema(target: &str) -> Value {
+    match serde_json::from_str::<Value>(target) {
+        Ok(schema @ Value::Object(_)) => schema,
+        _ => json!({ "type": target }),
+    }
+}
+
+/// `$ref` can't have sibling keys in OpenAPI 3.0, so nullable references are wrapped in `allOf`.
+fn nullable(mut schema: Value) -> Value {
+    if schema.get("$ref").is_some() {
+        return json!({ "allOf": [schema], "nullable": true });
+    }
+    schema["nullable"] = Value::Bool(true);
+    schema
+}
+
+fn schema_ref(entity_id: &EntityId) -> String {
+    format!("#/components/schemas/{}", entity_id.path().join("."))
+}
+
+fn qualified_name(path: &[String], name: &str) -> String {
+    path.iter()
+        .map(String::as_str)
+        .chain([name])
+        .collect_vec()
+        .join(".")
+}
+
+/// Sets the `description` of `value` from the comments in `attributes`, if there are any. Each
+/// comment becomes a paragraph.
+fn with_description(mut value: Value, attributes: &Attributes) -> Value {
+    let comments = attributes.comments();
+    if comments.is_empty() {
+        return value;
+    }
+    let description = comments
+        .iter()
+        .map(|comment| comment.lines().join("\n"))
+        .join("\n\n");
+    value["description"] = Value::String(description);
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+    use serde_json::{json, Value};
+
+    use crate::generator::{Generator, OpenApi, OpenApiFormat, OpenApiOptions};
+    use crate::output::Buffer;
+    use crate::testing::TestExecutor;
+    use crate::{input, output, parser, Executor};
+
+    const API: &str = r#"
+        mod user {
+            // A user.
+            struct User {
+                id: u64,
+                name: Option<String>,
+                friends: Vec<Option<User>>,
+                kind: Kind,
+                tags: HashMap<String, i32>,
+            }
+            enum Kind { Admin = 1, Guest = 2 }
+            // Gets a user.
+            fn get_user(id: u64, verbose: Option<bool>) -> Option<User> {}
+            fn remove_user(id: u64) -> Result<(), Kind> {}
+        }
+        fn ping() {}
+    "#;
+
+    fn run_test(options: OpenApiOptions) -> Result<String> {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let mut output = Buffer::default();
+        OpenApi::new(options).generate(model.view(), &mut output)?;
+        Ok(output.to_string())
+    }
+
+    #[test]
+    fn document() -> Result<()> {
+        let doc: Value = serde_json::from_str(&run_test(OpenApiOptions {
+            title: "Users".to_string(),
+            ..Default::default()
+        })?)?;
+        assert_eq!(doc["openapi"], "3.0.3");
+        assert_eq!(doc["info"], json!({ "title": "Users", "version": "1.0.0" }));
+
+        let schemas = &doc["components"]["schemas"];
+        assert_eq!(
+            schemas["user.User"],
+            json!({
+                "type": "object",
+                "description": "A user.",
+                "properties": {
+                    "id": { "type": "integer", "format": "int64", "minimum": 0 },
+                    "name": { "type": "string" },
+                    "friends": {
+                        "type": "array",
+                        "items": {
+                            "allOf": [{ "$ref": "#/components/schemas/user.User" }],
+                            "nullable": true,
+                        },
+                    },
+                    "kind": { "$ref": "#/components/schemas/user.Kind" },
+                    "tags": {
+                        "type": "object",
+                        "additionalProperties": { "type": "integer", "format": "int32" },
+                    },
+                },
+                "required": ["id", "friends", "kind", "tags"],
+            })
+        );
+        assert_eq!(
+            schemas["user.Kind"],
+            json!({ "type": "integer", "enum": [1, 2], "x-enum-varnames": ["Admin", "Guest"] })
+        );
+
+        assert_eq!(
+            doc["paths"]["/user/get_user"]["post"],
+            json!({
+                "operationId": "user.get_user",
+                "description": "Gets a user.",
+                "tags": ["user"],
+                "requestBody": {
+                    "required": true,
+                    "content": {
+                        "application/json": {
+                            "schema": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "integer", "format": "int64", "minimum": 0 },
+                                    "verbose": { "type": "boolean" },
+                                },
+                                "required": ["id"],
+                            },
+                        },
+                    },
+                },
+                "responses": {
+                    "200": {
+                        "description": "Success",
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "allOf": [{ "$ref": "#/components/schemas/user.User" }],
+                                    "nullable": true,
+                                },
+                            },
+                        },
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            doc["paths"]["/user/remove_user"]["post"]["responses"],
+            json!({
+                "200": { "description": "Success" },
+                "default": {
+                    "description": "Error",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/user.Kind" },
+                        },
+                    },
+                },
+            })
+        );
+        assert_eq!(
+            doc["paths"]["/ping"]["post"],
+            json!({
+                "operationId": "ping",
+                "responses": { "200": { "description": "Success" } },
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn yaml() -> Result<()> {
+        let yaml = run_test(OpenApiOptions {
+            format: OpenApiFormat::Yaml,
+            ..Default::default()
+        })?;
+        assert!(
+            yaml.starts_with("openapi: 3.0.3\ninfo:\n  title: API\n"),
+            "{}",
+            yaml
+        );
+        let doc: Value = serde_yaml::from_str(&yaml)?;
+        assert!(doc["components"]["schemas"]["user.User"].is_object());
+        Ok(())
+    }
+
+    #[test]
+    fn user_type_targets() -> Result<()> {
+        let config = parser::Config::from_json(
+            r#"{
+                "user_types": [
+                    {
+                        "parse": "Uuid",
+                        "name": "uuid",
+                        "targets": { "openapi": "{ \"type\": \"string\", \"format\": \"uuid\" }" }
+                    },
+                    { "parse": "Url", "name": "url", "targets": { "openapi": "string" } }
+                ]
+            }"#,
+        )?;
+        let output = Rc::new(RefCell::new(output::Buffer::default()));
+        Executor::new(
+            input::Buffer::new("struct A { id: Uuid } struct B { url: Url }"),
+            parser::Rust::default(),
+        )
+        .parser_config(config)
+        .generator(OpenApi::default())
+        .output_ptr(output.clone())
+        .execute()?;
+
+        let doc: Value = serde_json::from_str(&output.borrow().to_string())?;
+        let schemas = &doc["components"]["schemas"];
+        assert_eq!(
+            schemas["A"]["properties"]["id"],
+            json!({ "type": "string", "format": "uuid" })
+        );
+        assert_eq!(
+            schemas["B"]["properties"]["url"],
+            json!({ "type": "string" })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn services() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod user {
+                #[service(Admin)]
+                fn remove_user() {}
+                fn get_user() {}
+            }
+            #[service(Health)]
+            fn ping() {}
+            "#,
+        );
+        let model = exe.build();
+        let mut output = Buffer::default();
+        OpenApi::default().generate(model.view(), &mut output)?;
+        let doc: Value = serde_json::from_str(&output.to_string())?;
+        let tags = |route: &str| doc["paths"][route]["post"]["tags"].clone();
+        assert_eq!(tags("/user/remove_user"), json!(["Admin"]));
+        assert_eq!(tags("/user/get_user"), json!(["user"]));
+        assert_eq!(tags("/ping"), json!(["Health"]));
+        Ok(())
+    }
+
+    #[test]
+    fn dto_rpcs() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod user {
+                struct User {}
+                impl User {
+                    fn get(id: u64) -> User {}
+                }
+            }
+            "#,
+        );
+        let model = exe.build();
+        let mut output = Buffer::default();
+        OpenApi::default().generate(model.view(), &mut output)?;
+        let doc: Value = serde_json::from_str(&output.to_string())?;
+        let operation = &doc["paths"]["/user/User/get"]["post"];
+        assert_eq!(operation["operationId"], json!("user.User.get"));
+        assert_eq!(operation["tags"], json!(["user.User"]));
+        assert_eq!(
+            operation["responses"]["200"]["content"]["application/json"]["schema"],
+            json!({ "$ref": "#/components/schemas/user.User" })
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,702 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use anyhow::Result;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::generator::Generator;
+use crate::model::{Chunk, Comment};
+use crate::output::{Indented, Output};
+use crate::view::{
+    Attributes, Dto, EntityId, Enum, EnumValue, Field, IdentifierPolicy, InnerType, Model,
+    Namespace, Order, Rpc, Transformer, Type, UserTypeTargets,
+};
+
+/// A generator that writes a Python package for the API, with a subpackage per namespace:
+/// - [Dto]s become dataclasses with type hints. Their [Rpc]s become method stubs.
+/// - [Enum]s become `enum.IntEnum` subclasses.
+/// - [Rpc]s become client function stubs, `async` if the rpc is.
+///
+/// Stubs raise `NotImplementedError` until implemented. Each namespace is written to the
+/// `__init__.py` of its package, e.g. `api/user/__init__.py` for `user`, see
+/// [PythonOptions::package]. References to entities in other namespaces are qualified by their
+/// package, which is only imported for type checkers so that namespaces can refer to each other.
+///
+/// Names that are Python keywords are written with a trailing `_`, e.g. `from_`. Comments on
+/// dtos, enums and rpcs become docstrings, and comments on fields and enum values become `#`
+/// comments.
+#[derive(Debug, Default)]
+pub struct Python {
+    options: PythonOptions,
+}
+
+/// Options for the [Python] generator, from the `generator.python` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct PythonOptions {
+    /// Name of the top-level package, which contains the root namespace. Defaults to `api`.
+    pub package: String,
+
+    /// How non-ASCII identifiers are written. Python allows them, so defaults to
+    /// [IdentifierPolicy::Keep].
+    pub identifiers: IdentifierPolicy,
+
+    /// The order entities are written in within each kind. Defaults to [Order::Source].
+    pub order: Order,
+}
+
+impl Default for PythonOptions {
+    fn default() -> Self {
+        Self {
+            package: "api".to_string(),
+            identifiers: IdentifierPolicy::default(),
+            order: Order::default(),
+        }
+    }
+}
+
+impl Python {
+    pub fn new(options: PythonOptions) -> Self {
+        Self { options }
+    }
+}
+
+const INDENT: &str = "    "; // 4 spaces.
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "python";
+
+const KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+/// The package being written, which determines how references to other entities are written.
+#[derive(Debug, Copy, Clone)]
+struct Scope<'s> {
+    /// From [PythonOptions::package].
+    base: &'s str,
+    /// Path of the namespace being written.
+    path: &'s [String],
+}
+
+impl Scope<'_> {
+    /// The dotted name of the package of the namespace at `path`.
+    fn package_of<S: AsRef<str>>(&self, path: &[S]) -> String {
+        [ident(self.base)]
+            .into_iter()
+            .chain(path.iter().map(|s| ident(s.as_ref())))
+            .join(".")
+    }
+}
+
+impl Generator for Python {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model
+            .with_user_type_targets(user_ty_targets)
+            .with_identifier_policy(self.options.identifiers)
+            .with_order(self.options.order);
+        let mut o = Indented::new(output, INDENT);
+        let scope = Scope {
+            base: &self.options.package,
+            path: &[],
+        };
+        write_package(model.api(), scope, &mut o)
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["py"]
+    }
+}
+
+/// Writes the `__init__.py` of the namespace in `scope`, then those of its child namespaces.
+fn write_package(namespace: Namespace, scope: Scope, o: &mut Indented) -> Result<()> {
+    let file = format!(
+        "{}/__init__.py",
+        scope.package_of(scope.path).replace('.', "/")
+    );
+    o.write_chunk(&Chunk::with_relative_file_path(file))?;
+    write_docstring(&namespace.attributes().comments(), o)?;
+    write_imports(&namespace, scope, o)?;
+
+    for en in namespace.enums() {
+        write_separator(o)?;
+        write_enum(en, o)?;
+    }
+
+    for dto in namespace.dtos() {
+        write_separator(o)?;
+        write_dto(dto, scope, o)?;
+    }
+
+    for rpc in namespace.rpcs() {
+        write_separator(o)?;
+        write_rpc(&rpc, false, scope, o)?;
+    }
+
+    for nested_ns in namespace.namespaces() {
+        let nested_path = [scope.path, &[nested_ns.name().to_string()]].concat();
+        let nested_scope = Scope {
+            path: &nested_path,
+            ..scope
+        };
+        write_package(nested_ns, nested_scope, o)?;
+    }
+    Ok(())
+}
+
+/// Two blank lines between top-level definitions.
+fn write_separator(o: &mut Indented) -> Result<()> {
+    o.newline()?;
+    o.newline()
+}
+
+/// Everything the entities directly within a namespace refer to, so that only what's used is
+/// imported.
+#[derive(Debug, Default)]
+struct Imports {
+    typing: BTreeSet<&'static str>,
+    type_vars: BTreeSet<String>,
+    /// Paths of other namespaces whose entities are referred to.
+    packages: BTreeSet<Vec<String>>,
+}
+
+impl Imports {
+    fn collect(namespace: &Namespace, scope: Scope) -> Self {
+        let mut imports = Self::default();
+        let add_rpc = |rpc: Rpc, imports: &mut Self| {
+            imports.add_type_params(rpc.type_params());
+            for param in rpc.params() {
+                imports.add_type(param.ty().inner(), scope);
+            }
+            if let Some(ty) = rpc.return_type() {
+                imports.add_type(ty.inner(), scope);
+            }
+        };
+        for dto in namespace.dtos() {
+            imports.add_type_params(dto.type_params());
+            for field in dto.fields() {
+                imports.add_type(field.ty().inner(), scope);
+            }
+            for rpc in dto.rpcs() {
+                add_rpc(rpc, &mut imports);
+            }
+        }
+        for rpc in namespace.rpcs() {
+            add_rpc(rpc, &mut imports);
+        }
+        if !imports.type_vars.is_empty() {
+            imports.typing.insert("TypeVar");
+        }
+        if !imports.packages.is_empty() {
+            imports.typing.insert("TYPE_CHECKING");
+        }
+        imports
+    }
+
+    fn add_type_params(&mut self, type_params: &[&str]) {
+        self.type_vars
+            .extend(type_params.iter().map(|s| ident(s).to_string()));
+    }
+
+    fn add_type(&mut self, ty: InnerType, scope: Scope) {
+        match ty {
+            InnerType::Api(id, args) => {
+                let path = id.path();
+                if let Some((_, namespace)) = path.split_last() {
+                    if !namespace.iter().eq(scope.path.iter()) {
+                        self.packages
+                            .insert(namespace.iter().map(|s| s.to_string()).collect_vec());
+                    }
+                }
+                for arg in args {
+                    self.add_type(arg, scope);
+                }
+            }
+            InnerType::Array(ty) => {
+                self.typing.insert("List");
+                self.add_type(*ty, scope);
+            }
+            InnerType::Optional(ty) => {
+                self.typing.insert("Optional");
+                self.add_type(*ty, scope);
+            }
+            InnerType::Map { key, value } => {
+                self.typing.insert("Dict");
+                self.add_type(*key, scope);
+                self.add_type(*value, scope);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn write_imports(namespace: &Namespace, scope: Scope, o: &mut Indented) -> Result<()> {
+    let mut imports = Imports::collect(namespace, scope);
+    let has_enums = namespace.enums().next().is_some();
+    let has_dtos = namespace.dtos().next().is_some();
+    let has_generic_dtos = namespace.dtos().any(|dto| !dto.type_params().is_empty());
+    if has_generic_dtos {
+        imports.typing.insert("Generic");
+    }
+
+    // Annotations are only evaluated by type checkers, so forward references and references to
+    // packages that are only imported for type checking are fine.
+    o.write_str("from __future__ import annotations")?;
+    o.newline()?;
+    if has_enums || has_dtos || !imports.typing.is_empty() {
+        o.newline()?;
+    }
+    if has_enums {
+        o.write_str("import enum")?;
+        o.newline()?;
+    }
+    if has_dtos {
+        o.write_str("from dataclasses import dataclass")?;
+        o.newline()?;
+    }
+    if !imports.typing.is_empty() {
+        o.write_str("from typing import ")?;
+        o.write_str(&imports.typing.iter().join(", "))?;
+        o.newline()?;
+    }
+
+    if !imports.packages.is_empty() {
+        o.newline()?;
+        o.write_str("if TYPE_CHECKING:")?;
+        o.indent(1);
+        for package in &imports.packages {
+            o.newline()?;
+            o.write_str("import ")?;
+            o.write_str(&scope.package_of(package))?;
+        }
+        o.indent(-1);
+        o.newline()?;
+    }
+
+    if !imports.type_vars.is_empty() {
+        o.newline()?;
+        for type_var in &imports.type_vars {
+            o.write_str(type_var)?;
+            o.write_str(" = TypeVar(\"")?;
+            o.write_str(type_var)?;
+            o.write_str("\")")?;
+            o.newline()?;
+        }
+    }
+    Ok(())
+}
+
+fn write_dto(dto: Dto, scope: Scope, o: &mut Indented) -> Result<()> {
+    o.write_str("@dataclass")?;
+    o.newline()?;
+    o.write_str("class ")?;
+    o.write_str(&ident(&dto.name()))?;
+    if !dto.type_params().is_empty() {
+        o.write_str("(Generic[")?;
+        o.write_str(&dto.type_params().iter().map(|s| ident(s)).join(", "))?;
+        o.write_str("])")?;
+    }
+    o.write(':')?;
+    o.indent(1);
+    o.newline()?;
+
+    let comments = dto.attributes().comments();
+    let mut is_empty = comments.is_empty();
+    write_docstring(&comments, o)?;
+
+    for (i, field) in dto.fields().enumerate() {
+        if i == 0 && !is_empty {
+            o.newline()?;
+        }
+        write_field(field, scope, o)?;
+        o.newline()?;
+        is_empty = false;
+    }
+
+    for rpc in dto.rpcs() {
+        if !is_empty {
+            o.newline()?;
+        }
+        write_rpc(&rpc, true, scope, o)?;
+        is_empty = false;
+    }
+
+    if is_empty {
+        o.write_str("pass")?;
+        o.newline()?;
+    }
+    o.indent(-1);
+    Ok(())
+}
+
+/// Writes `rpc` as a function that raises until implemented, or a method if `is_method`.
+fn write_rpc(rpc: &Rpc, is_method: bool, scope: Scope, o: &mut Indented) -> Result<()> {
+    if rpc.is_async() {
+        o.write_str("async ")?;
+    }
+    o.write_str("def ")?;
+    o.write_str(&ident(&rpc.name()))?;
+    o.write('(')?;
+    let params = rpc.params().collect_vec();
+    if is_method {
+        o.write_str("self")?;
+        if !params.is_empty() {
+            o.write_str(", ")?;
+        }
+    }
+    for (i, param) in params.into_iter().enumerate() {
+        if i > 0 {
+            o.write_str(", ")?;
+        }
+        write_param(param, scope, o)?;
+    }
+    o.write_str(") -> ")?;
+    match rpc.return_type() {
+        Some(return_type) => write_type(return_type, scope, o)?,
+        None => o.write_str("None")?,
+    }
+    o.write(':')?;
+    o.indent(1);
+    o.newline()?;
+
+    write_docstring(&rpc.attributes().comments(), o)?;
+    o.write_str("raise NotImplementedError(\"")?;
+    o.write_str(&rpc.name())?;
+    o.write_str("\")")?;
+    o.newline()?;
+    o.indent(-1);
+    Ok(())
+}
+
+fn write_enum(en: Enum, o: &mut Indented) -> Result<()> {
+    o.write_str("class ")?;
+    o.write_str(&ident(&en.name()))?;
+    o.write_str("(enum.IntEnum):")?;
+    o.indent(1);
+    o.newline()?;
+
+    let comments = en.attributes().comments();
+    let mut is_empty = comments.is_empty();
+    write_docstring(&comments, o)?;
+
+    for (i, value) in en.values().enumerate() {
+        if i == 0 && !is_empty {
+            o.newline()?;
+        }
+        write_enum_value(value, o)?;
+        o.newline()?;
+        is_empty = false;
+    }
+
+    if is_empty {
+        o.write_str("pass")?;
+        o.newline()?;
+    }
+    o.indent(-1);
+    Ok(())
+}
+
+fn write_enum_value(value: EnumValue, o: &mut dyn Output) -> Result<()> {
+    write_comments(&value.attributes(), o)?;
+
+    o.write_str(&ident(&value.name()))?;
+    o.write_str(" = ")?;
+    o.write_str(&value.number().to_string())
+}
+
+fn write_field(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_comments(&field.attributes(), o)?;
+
+    o.write_str(&ident(&field.name()))?;
+    o.write_str(": ")?;
+    write_type(field.ty(), scope, o)
+}
+
+fn write_param(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    o.write_str(&ident(&field.name()))?;
+    o.write_str(": ")?;
+    write_type(field.ty(), scope, o)
+}
+
+/// Writes each line of each comment as a `#` comment.
+fn write_comments(attributes: &Attributes, o: &mut dyn Output) -> Result<()> {
+    for comment in attributes.comments() {
+        for line in comment.lines() {
+            o.write('#')?;
+            if !line.is_empty() {
+                o.write(' ')?;
+                o.write_str(line)?;
+            }
+            o.newline()?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes all comments as a single docstring, with an empty line between each [Comment].
+fn write_docstring(comments: &[Comment], o: &mut dyn Output) -> Result<()> {
+    let lines = comments
+        .iter()
+        .map(|comment| {
+            comment
+                .lines()
+                .map(|line| line.replace("\"\"\"", "\\\"\\\"\\\""))
+        })
+        .map(|lines| lines.collect_vec())
+        .collect_vec()
+        .join(&String::new());
+    match lines.as_slice() {
+        [] => Ok(()),
+        [line] => {
+            o.write_str("\"\"\"")?;
+            o.write_str(line)?;
+            o.write_str("\"\"\"")?;
+            o.newline()
+        }
+        lines => {
+            o.write_str("\"\"\"")?;
+            o.newline()?;
+            for line in lines {
+                o.write_str(line)?;
+                o.newline()?;
+            }
+            o.write_str("\"\"\"")?;
+            o.newline()
+        }
+    }
+}
+
+fn write_type(ty: Type, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_inner_type(ty.inner(), scope, o)
+}
+
+fn write_inner_type(ty: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    match ty {
+        InnerType::Bool => o.write_str("bool"),
+        InnerType::U8
+        | InnerType::U16
+        | InnerType::U32
+        | InnerType::U64
+        | InnerType::U128
+        | InnerType::I8
+        | InnerType::I16
+        | InnerType::I32
+        | InnerType::I64
+        | InnerType::I128 => o.write_str("int"),
+        InnerType::F8 | InnerType::F16 | InnerType::F32 | InnerType::F64 | InnerType::F128 => {
+            o.write_str("float")
+        }
+        InnerType::String => o.write_str("str"),
+        InnerType::Bytes => o.write_str("bytes"),
+        // Either the `python` target of the user type, or just its name.
+        InnerType::User(s) => o.write_str(s),
+        InnerType::Api(id, args) => write_api_type(id, args, scope, o),
+        InnerType::Generic(name) => o.write_str(&ident(name)),
+        InnerType::Array(ty) => {
+            o.write_str("List[")?;
+            write_inner_type(*ty, scope, o)?;
+            o.write(']')
+        }
+        InnerType::Map { key, value } => {
+            o.write_str("Dict[")?;
+            write_inner_type(*key, scope, o)?;
+            o.write_str(", ")?;
+            write_inner_type(*value, scope, o)?;
+            o.write(']')
+        }
+        InnerType::Optional(ty) => {
+            o.write_str("Optional[")?;
+            write_inner_type(*ty, scope, o)?;
+            o.write(']')
+        }
+    }
+}
+
+/// Writes e.g. `Page[User]`, or just `Page` if there are no type `args`.
+fn write_api_type(
+    id: EntityId,
+    args: Vec<InnerType>,
+    scope: Scope,
+    o: &mut dyn Output,
+) -> Result<()> {
+    write_entity_id(id, scope, o)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+    o.write('[')?;
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            o.write_str(", ")?;
+        }
+        write_inner_type(arg, scope, o)?;
+    }
+    o.write(']')
+}
+
+fn write_entity_id(entity_id: EntityId, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    let path = entity_id.path();
+    let (name, namespace) = match path.split_last() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    if !namespace.iter().eq(scope.path.iter()) {
+        o.write_str(&scope.package_of(namespace))?;
+        o.write('.')?;
+    }
+    o.write_str(&ident(name))
+}
+
+/// `name`, with a trailing `_` if it's a Python keyword.
+fn ident(name: &str) -> Cow<'_, str> {
+    if KEYWORDS.contains(&name) {
+        Cow::Owned(format!("{}_", name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::generator::{Python, PythonOptions};
+    use crate::testing::TestExecutor;
+
+    const API: &str = r#"
+        mod user {
+            /// A user.
+            struct User {
+                id: u64,
+                /// Display name.
+                name: Option<String>,
+                tags: Vec<String>,
+                kind: Kind,
+                from: other::Empty,
+            }
+            enum Kind { Admin = 1, Guest = 2 }
+            /// Gets a user.
+            async fn get_user(id: u64) -> Option<User> {}
+            fn remove_user(user: User) {}
+        }
+        mod other {
+            struct Page<T> { items: Vec<T> }
+            struct Empty {}
+            impl Empty {
+                fn is_empty() -> bool {}
+            }
+        }
+    "#;
+
+    #[test]
+    fn package_per_namespace() -> Result<()> {
+        let output = TestExecutor::new(API).generate(&mut Python::new(PythonOptions {
+            package: "client".to_string(),
+            ..Default::default()
+        }));
+        assert_eq!(
+            output.get("client/__init__.py").unwrap(),
+            "from __future__ import annotations\n"
+        );
+        assert_eq!(
+            output.get("client/user/__init__.py").unwrap(),
+            r#"from __future__ import annotations
+
+import enum
+from dataclasses import dataclass
+from typing import List, Optional, TYPE_CHECKING
+
+if TYPE_CHECKING:
+    import client.other
+
+
+class Kind(enum.IntEnum):
+    Admin = 1
+    Guest = 2
+
+
+@dataclass
+class User:
+    """A user."""
+
+    id: int
+    # Display name.
+    name: Optional[str]
+    tags: List[str]
+    kind: Kind
+    from_: client.other.Empty
+
+
+async def get_user(id: int) -> Optional[User]:
+    """Gets a user."""
+    raise NotImplementedError("get_user")
+
+
+def remove_user(user: User) -> None:
+    raise NotImplementedError("remove_user")
+"#
+        );
+        assert_eq!(
+            output.get("client/other/__init__.py").unwrap(),
+            r#"from __future__ import annotations
+
+from dataclasses import dataclass
+from typing import Generic, List, TypeVar
+
+T = TypeVar("T")
+
+
+@dataclass
+class Page(Generic[T]):
+    items: List[T]
+
+
+@dataclass
+class Empty:
+    def is_empty(self) -> bool:
+        raise NotImplementedError("is_empty")
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn multi_line_docstring_and_empty_entities() -> Result<()> {
+        let output = TestExecutor::new(
+            r#"
+            /// First.
+            /// Second.
+            enum Empty {}
+            struct Dto {}
+            "#,
+        )
+        .generate(&mut Python::default());
+        assert_eq!(
+            output.get("api/__init__.py").unwrap(),
+            r#"from __future__ import annotations
+
+import enum
+from dataclasses import dataclass
+
+
+class Empty(enum.IntEnum):
+    """
+    First.
+    Second.
+    """
+
+
+@dataclass
+class Dto:
+    pass
+"#
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::generator::Generator;
+use crate::model::{chunk, EntityId, EntityType, Model, Namespace, Type};
+use crate::output::Output;
+use crate::stats::{ChunkStats, EntityCounts};
+use crate::view;
+
+/// A generator that writes a report of metrics about the shape of the API, for architecture
+/// reviews and dashboards:
+/// - Entity counts per namespace and per chunk, and the deepest nesting of namespaces. See
+///   [crate::model::Model::stats].
+/// - The most referenced dtos, by the number of fields, params and return types that refer to
+///   them, including within arrays, maps and optionals.
+/// - Orphaned dtos and enums that nothing in the API refers to. These may still be used by
+///   code outside of the API, e.g. as the root message of a stream.
+/// - The average number of params per rpc.
+/// - Cycles of dtos that refer to themselves through their fields. See
+///   [crate::model::Dependencies::cycles].
+///
+/// Written to `report.md` in Markdown, or `report.json` in JSON for further processing. The API
+/// is measured as parsed, without any view transforms applied.
+#[derive(Debug, Default)]
+pub struct Report {
+    options: ReportOptions,
+}
+
+/// Options for the [Report] generator, from the `generator.report` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReportOptions {
+    /// Defaults to [ReportFormat::Markdown].
+    pub format: ReportFormat,
+
+    /// Maximum number of most referenced dtos to list. Defaults to 10.
+    pub top: usize,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        Self {
+            format: ReportFormat::Markdown,
+            top: 10,
+        }
+    }
+}
+
+impl Report {
+    pub fn new(options: ReportOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Generator for Report {
+    fn generate(&mut self, model: view::Model, output: &mut dyn Output) -> Result<()> {
+        let metrics = Metrics::new(model.target(), self.options.top);
+        match self.options.format {
+            ReportFormat::Markdown => {
+                output.write_chunk(&chunk::Chunk::with_relative_file_path("report.md"))?;
+                output.write_str(&metrics.to_markdown())
+            }
+            ReportFormat::Json => {
+                output.write_chunk(&chunk::Chunk::with_relative_file_path("report.json"))?;
+                output.write_str(&serde_json::to_string_pretty(&metrics)?)?;
+                output.write_str("\n")
+            }
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["md", "json"]
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Metrics {
+    /// Entities in the whole API.
+    totals: EntityCounts,
+    /// Deepest nesting of namespaces below the root.
+    max_depth: usize,
+    /// Across all rpcs in the API. Zero if there are none.
+    average_rpc_params: f64,
+    /// Sorted by path. The root namespace has an empty path.
+    namespaces: Vec<NamespaceMetrics>,
+    /// Entities defined in each chunk with a file path, in the order they were parsed.
+    chunks: Vec<ChunkStats>,
+    /// Sorted by most references first, then path.
+    most_referenced_dtos: Vec<Referenced>,
+    /// Sorted by path.
+    orphaned: Vec<Orphan>,
+    /// The path of each dto in each cycle, e.g. `[a.A, b.B]` where `A` refers to `B`, which
+    /// refers back to `A`.
+    cycles: Vec<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+struct NamespaceMetrics {
+    path: String,
+    /// Entities directly within the namespace, not within its child namespaces.
+    counts: EntityCounts,
+    average_rpc_params: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct Referenced {
+    path: String,
+    references: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct Orphan {
+    path: String,
+    kind: &'static str,
+}
+
+impl Metrics {
+    fn new(model: &Model, top: usize) -> Self {
+        let api = model.api();
+        let mut references = HashMap::<EntityId, usize>::new();
+        let mut namespaces = vec![];
+        // Dtos and enums, which are the entities that can be referenced.
+        let mut types = vec![];
+        collect(
+            api,
+            &EntityId::default(),
+            &mut namespaces,
+            &mut types,
+            &mut references,
+        );
+        namespaces.sort_by(|a, b| a.metrics.path.cmp(&b.metrics.path));
+
+        let reference_count = |id: &EntityId| references.get(id).copied().unwrap_or(0);
+        let mut most_referenced_dtos = types
+            .iter()
+            .filter(|(_, ty)| *ty == EntityType::Dto)
+            .map(|(id, _)| Referenced {
+                path: path(id),
+                references: reference_count(id),
+            })
+            .filter(|referenced| referenced.references > 0)
+            .collect::<Vec<_>>();
+        most_referenced_dtos.sort_by(|a, b| {
+            b.references
+                .cmp(&a.references)
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        most_referenced_dtos.truncate(top);
+
+        let mut orphaned = types
+            .iter()
+            .filter(|(id, _)| reference_count(id) == 0)
+            .map(|(id, ty)| Orphan {
+                path: path(id),
+                kind: if *ty == EntityType::Dto {
+                    "dto"
+                } else {
+                    "enum"
+                },
+            })
+            .collect::<Vec<_>>();
+        orphaned.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let stats = model.stats();
+        let totals = stats.entities;
+        let params = namespaces
+            .iter()
+            .map(|namespace| namespace.params)
+            .sum::<usize>();
+        Self {
+            totals,
+            max_depth: stats.max_depth,
+            average_rpc_params: average(params, totals.rpcs),
+            namespaces: namespaces.into_iter().map(|ns| ns.metrics).collect(),
+            chunks: stats.chunks,
+            most_referenced_dtos,
+            orphaned,
+            cycles: model
+                .dependencies()
+                .cycles()
+                .iter()
+                .map(|cycle| cycle.path.iter().map(path).collect())
+                .collect(),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut md = String::from("# API Report\n\n");
+        let t = &self.totals;
+        md.push_str(&format!(
+            "{} namespaces, {} dtos, {} rpcs, {} enums, {} fields and params, {} enum values.\n\n",
+            t.namespaces, t.dtos, t.rpcs, t.enums, t.fields, t.enum_values
+        ));
+        md.push_str(&format!("Max namespace depth: {}\n\n", self.max_depth));
+        md.push_str(&format!(
+            "Average rpc params: {:.2}\n\n",
+            self.average_rpc_params
+        ));
+
+        md.push_str("## Namespaces\n\n");
+        md.push_str("| Namespace | DTOs | RPCs | Enums | Fields | Avg RPC params |\n");
+        md.push_str("|---|---:|---:|---:|---:|---:|\n");
+        for ns in &self.namespaces {
+            let path = if ns.path.is_empty() {
+                "(root)".to_string()
+            } else {
+                format!("`{}`", ns.path)
+            };
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} | {:.2} |\n",
+                path,
+                ns.counts.dtos,
+                ns.counts.rpcs,
+                ns.counts.enums,
+                ns.counts.fields,
+                ns.average_rpc_params
+            ));
+        }
+
+        md.push_str("\n## Chunks\n\n");
+        if self.chunks.is_empty() {
+            md.push_str("None.\n");
+        } else {
+            md.push_str("| Chunk | Namespaces | DTOs | RPCs | Enums | Fields | Enum values |\n");
+            md.push_str("|---|---:|---:|---:|---:|---:|---:|\n");
+        }
+        for chunk in &self.chunks {
+            let c = &chunk.entities;
+            md.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} | {} | {} |\n",
+                chunk.path.display(),
+                c.namespaces,
+                c.dtos,
+                c.rpcs,
+                c.enums,
+                c.fields,
+                c.enum_values
+            ));
+        }
+
+        md.push_str("\n## Most referenced DTOs\n\n");
+        if self.most_referenced_dtos.is_empty() {
+            md.push_str("None.\n");
+        }
+        for referenced in &self.most_referenced_dtos {
+            md.push_str(&format!(
+                "- `{}`: {}\n",
+                referenced.path, referenced.references
+            ));
+        }
+
+        md.push_str("\n## Orphaned types\n\n");
+        if self.orphaned.is_empty() {
+            md.push_str("None.\n");
+        }
+        for orphan in &self.orphaned {
+            md.push_str(&format!("- `{}` ({})\n", orphan.path, orphan.kind));
+        }
+
+        md.push_str("\n## Cycles\n\n");
+        if self.cycles.is_empty() {
+            md.push_str("None.\n");
+        }
+        for cycle in &self.cycles {
+            let path = cycle
+                .iter()
+                .chain(cycle.first())
+                .map(|path| format!("`{}`", path))
+                .collect::<Vec<_>>();
+            md.push_str(&format!("- {}\n", path.join(" -> ")));
+        }
+        md
+    }
+}
+
+/// [NamespaceMetrics] along with the total rpc params, to compute the API-wide average.
+struct CollectedNamespace {
+    metrics: NamespaceMetrics,
+    params: usize,
+}
+
+fn collect(
+    namespace: &Namespace,
+    namespace_id: &EntityId,
+    namespaces: &mut Vec<CollectedNamespace>,
+    types: &mut Vec<(EntityId, EntityType)>,
+    references: &mut HashMap<EntityId, usize>,
+) {
+    let mut counts = EntityCounts::default();
+    let mut params = 0;
+    // unwraps ok here because we're iterating known children.
+    for dto in namespace.dtos() {
+        counts.dtos += 1;
+        counts.fields += dto.fields.len();
+        types.push((
+            namespace_id.child(EntityType::Dto, dto.name).unwrap(),
+            EntityType::Dto,
+        ));
+        for field in &dto.fields {
+            count_references(&field.ty, references);
+        }
+    }
+    for rpc in namespace.rpcs() {
+        counts.rpcs += 1;
+        counts.fields += rpc.params.len();
+        params += rpc.params.len();
+        for param in &rpc.params {
+            count_references(&param.ty, references);
+        }
+        if let Some(ty) = &rpc.return_type {
+            count_references(ty, references);
+        }
+    }
+    for en in namespace.enums() {
+        counts.enums += 1;
+        counts.enum_values += en.values.len();
+        types.push((
+            namespace_id.child(EntityType::Enum, en.name).unwrap(),
+            EntityType::Enum,
+        ));
+    }
+    counts.namespaces = namespace.namespaces().count();
+
+    namespaces.push(CollectedNamespace {
+        metrics: NamespaceMetrics {
+            path: path(namespace_id),
+            counts,
+            average_rpc_params: average(params, counts.rpcs),
+        },
+        params,
+    });
+    for child in namespace.namespaces() {
+        let child_id = namespace_id
+            .child(EntityType::Namespace, &child.name)
+            .unwrap();
+        collect(child, &child_id, namespaces, types, references);
+    }
+}
+
+/// Counts each reference to an API type within `ty`. Types are fully qualified once the model is
+/// built.
+fn count_references(ty: &Type, references: &mut HashMap<EntityId, usize>) {
+    match ty {
+        Type::Api(id, args) => {
+            *references.entry(id.clone()).or_default() += 1;
+            for arg in args {
+                count_references(arg, references);
+            }
+        }
+        Type::Array(ty) | Type::Optional(ty) => count_references(ty, references),
+        Type::Map { key, value } => {
+            count_references(key, references);
+            count_references(value, references);
+        }
+        _ => {}
+    }
+}
+
+/// Dot-separated names of the entity and its namespaces, e.g. `service.user.User`.
+fn path(entity_id: &EntityId) -> String {
+    entity_id.component_names().collect::<Vec<_>>().join(".")
+}
+
+fn average(total: usize, count: usize) -> f64 {
+    if count == 0 {
+        0.0
+    } else {
+        total as f64 / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::chunks;
+    use crate::generator::{Generator, Report, ReportFormat, ReportOptions};
+    use crate::output::Buffer;
+    use crate::testing::TestExecutor;
+    use crate::view;
+
+    const API: &str = r#"
+        struct Id {}
+        mod user {
+            struct User { id: Id, friends: Vec<Id>, kind: Option<Kind> }
+            struct Unused {}
+            enum Kind { A, B }
+            fn get_user(id: Id) -> User {}
+            fn list_users() -> Vec<User> {}
+        }
+        mod order {
+            enum Status { Open }
+            fn place_order(user: user::User, id: Id, note: String) {}
+        }
+    "#;
+
+    fn run_test(format: ReportFormat) -> Result<String> {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let mut output = Buffer::default();
+        Report::new(ReportOptions { format, top: 2 })
+            .generate(view::Model::new(&model), &mut output)?;
+        Ok(output.to_string())
+    }
+
+    #[test]
+    fn markdown() -> Result<()> {
+        assert_eq!(
+            run_test(ReportFormat::Markdown)?,
+            r#"# API Report
+
+2 namespaces, 3 dtos, 3 rpcs, 2 enums, 7 fields and params, 3 enum values.
+
+Max namespace depth: 1
+
+Average rpc params: 1.33
+
+## Namespaces
+
+| Namespace | DTOs | RPCs | Enums | Fields | Avg RPC params |
+|---|---:|---:|---:|---:|---:|
+| (root) | 1 | 0 | 0 | 0 | 0.00 |
+| `order` | 0 | 1 | 1 | 3 | 3.00 |
+| `user` | 2 | 2 | 1 | 4 | 0.50 |
+
+## Chunks
+
+None.
+
+## Most referenced DTOs
+
+- `Id`: 4
+- `user.User`: 3
+
+## Orphaned types
+
+- `order.Status` (enum)
+- `user.Unused` (dto)
+
+## Cycles
+
+None.
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn json() -> Result<()> {
+        let json: serde_json::Value = serde_json::from_str(&run_test(ReportFormat::Json)?)?;
+        assert_eq!(json["totals"]["rpcs"], 3);
+        assert_eq!(json["namespaces"][2]["path"], "user");
+        assert_eq!(json["namespaces"][2]["counts"]["dtos"], 2);
+        assert_eq!(json["namespaces"][2]["average_rpc_params"], 0.5);
+        assert_eq!(json["most_referenced_dtos"][0]["path"], "Id");
+        assert_eq!(json["most_referenced_dtos"][0]["references"], 4);
+        assert_eq!(json["orphaned"][1]["kind"], "dto");
+        assert_eq!(json["cycles"], serde_json::json!([]));
+        assert_eq!(json["max_depth"], 1);
+        assert_eq!(json["chunks"], serde_json::json!([]));
+        Ok(())
+    }
+
+    #[test]
+    fn chunks() -> Result<()> {
+        let mut exe = TestExecutor::with_chunks(chunks! {
+            "a.rs" => "struct A {} mod b { mod c { fn rpc(a: A) {} } }",
+            "d.rs" => "enum D { X, Y }",
+        });
+        let model = exe.build();
+        let mut output = Buffer::default();
+        Report::default().generate(view::Model::new(&model), &mut output)?;
+        let md = output.to_string();
+        assert!(md.contains("Max namespace depth: 3\n"), "{}", md);
+        assert!(
+            md.contains(
+                r#"## Chunks
+
+| Chunk | Namespaces | DTOs | RPCs | Enums | Fields | Enum values |
+|---|---:|---:|---:|---:|---:|---:|
+| `a.rs` | 2 | 1 | 1 | 0 | 1 | 0 |
+| `d.rs` | 0 | 0 | 0 | 1 | 0 | 2 |
+"#
+            ),
+            "{}",
+            md
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn cycles() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct Node { children: Vec<tree::Tree> }
+            mod tree {
+                struct Tree { root: Option<Node> }
+            }
+            "#,
+        );
+        let model = exe.build();
+        let mut output = Buffer::default();
+        Report::default().generate(view::Model::new(&model), &mut output)?;
+        let md = output.to_string();
+        assert!(
+            md.ends_with("## Cycles\n\n- `Node` -> `tree.Tree` -> `Node`\n"),
+            "{}",
+            md
+        );
+        Ok(())
+    }
+}
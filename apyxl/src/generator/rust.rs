@@ -2,38 +2,101 @@ use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
 
 use crate::generator::Generator;
-use crate::model::{attribute, Chunk, Comment, Dependencies, EntityType};
+use crate::model::{attribute, Chunk, Comment, CommentStyle, Dependencies, EntityType};
 use crate::output::{Indented, Output};
 use crate::view::{
-    Attributes, Dto, EntityId, Enum, EnumValue, Field, InnerType, Model, Namespace, Rpc, SubView,
-    Type,
+    Attributes, Dto, EntityId, Enum, EnumValue, Field, IdentifierPolicy, InnerType, Model,
+    Namespace, NamespaceChild, Order, Rpc, SubView, Transformer, Type, UserTypeTargets,
 };
 use crate::{model, rust_util};
 
 #[derive(Debug, Default)]
-pub struct Rust {}
+pub struct Rust {
+    options: RustOptions,
+}
+
+/// Options for the [Rust] generator, from the `generator.rust` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct RustOptions {
+    /// Write a comment at the top of each file listing what it would import from other files.
+    /// Defaults to true.
+    pub import_comments: bool,
+
+    /// How non-ASCII identifiers are written. Rust allows them, so defaults to
+    /// [IdentifierPolicy::Keep].
+    pub identifiers: IdentifierPolicy,
+
+    /// Reproduce the structure of the parsed source as closely as possible, so that a model
+    /// parsed from Rust can be written back out after refactoring it, e.g. renaming or moving
+    /// entities, with minimal churn:
+    /// - Entities are written in source order rather than grouped by kind.
+    /// - Comments keep their style, e.g. `///` or `/* */`.
+    /// - Comments after the last entity, field or enum value are kept.
+    ///
+    /// Defaults to false.
+    pub fidelity: bool,
+
+    /// The order entities are written in within each kind, or overall with `fidelity`. Defaults
+    /// to [Order::Source].
+    pub order: Order,
+}
+
+impl Default for RustOptions {
+    fn default() -> Self {
+        Self {
+            import_comments: true,
+            identifiers: IdentifierPolicy::Keep,
+            fidelity: false,
+            order: Order::Source,
+        }
+    }
+}
+
+impl Rust {
+    pub fn new(options: RustOptions) -> Self {
+        Self { options }
+    }
+}
 
 const INDENT: &str = "    "; // 4 spaces.
 
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "rust";
+
 impl Generator for Rust {
     fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model
+            .with_user_type_targets(user_ty_targets)
+            .with_identifier_policy(self.options.identifiers)
+            .with_order(self.options.order);
         let mut o = Indented::new(output, INDENT);
 
         // Write combined API w/out chunks.
-        write_namespace_contents(model.api(), &mut o)?;
+        write_namespace_contents(model.api(), &self.options, &mut o)?;
 
         // Write chunked API.
         for result in model.api_chunked_iter() {
             let (chunk, sub_view) = result?;
             o.write_chunk(chunk)?;
-            write_dependencies(&model, chunk, &sub_view, &mut o)?;
-            write_namespace_contents(sub_view.namespace(), &mut o)?;
+            if self.options.import_comments {
+                write_dependencies(&model, chunk, &sub_view, &mut o)?;
+            }
+            write_namespace_contents(sub_view.namespace(), &self.options, &mut o)?;
         }
 
         Ok(())
     }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rs"]
+    }
 }
 
 fn write_dependencies(
@@ -44,7 +107,7 @@ fn write_dependencies(
 ) -> Result<()> {
     let mut deps = collect_chunk_dependencies(
         &model.api(),
-        &sub_view.root_id(),
+        sub_view.root_id(),
         sub_view.namespace(),
         model.dependencies(),
     );
@@ -67,7 +130,7 @@ fn write_imports<P: AsRef<Path>>(chunk_relative_paths: &[P], o: &mut dyn Output)
     for id in ids {
         o.write_str("// use crate::")?;
         for component in id.component_names() {
-            o.write_str(&component)?;
+            o.write_str(component)?;
             o.write_str("::")?;
         }
         o.write_str("*;")?;
@@ -76,8 +139,8 @@ fn write_imports<P: AsRef<Path>>(chunk_relative_paths: &[P], o: &mut dyn Output)
     Ok(())
 }
 
-fn write_namespace(namespace: Namespace, o: &mut Indented) -> Result<()> {
-    write_attributes(&namespace.attributes(), o)?;
+fn write_namespace(namespace: Namespace, options: &RustOptions, o: &mut Indented) -> Result<()> {
+    write_attributes(&namespace.attributes(), options, o)?;
 
     o.write_str("pub mod ")?;
     o.write_str(&namespace.name())?;
@@ -87,60 +150,113 @@ fn write_namespace(namespace: Namespace, o: &mut Indented) -> Result<()> {
     } else {
         o.write(' ')?;
         write_block_start(o)?;
-        write_namespace_contents(namespace, o)?;
+        write_namespace_contents(namespace, options, o)?;
         write_block_end(o)?;
     }
     Ok(())
 }
 
-fn write_namespace_contents(namespace: Namespace, o: &mut Indented) -> Result<()> {
+fn write_namespace_contents(
+    namespace: Namespace,
+    options: &RustOptions,
+    o: &mut Indented,
+) -> Result<()> {
+    if options.fidelity {
+        write_inner_doc_comments(&namespace.attributes(), o)?;
+        for child in namespace.children() {
+            match child {
+                NamespaceChild::Dto(dto) => write_dto(dto, options, o)?,
+                NamespaceChild::Rpc(rpc) => write_rpc(rpc, options, o)?,
+                NamespaceChild::Enum(en) => write_enum(en, options, o)?,
+                NamespaceChild::Namespace(ns) => write_namespace(ns, options, o)?,
+            }
+            o.newline()?;
+        }
+        return write_trailing_comments(&namespace.attributes(), options, o);
+    }
+
     for rpc in namespace.rpcs() {
-        write_rpc(rpc, o)?;
+        write_rpc(rpc, options, o)?;
         o.newline()?;
     }
 
     for en in namespace.enums() {
-        write_enum(en, o)?;
+        write_enum(en, options, o)?;
         o.newline()?;
     }
 
     for dto in namespace.dtos() {
-        write_dto(dto, o)?;
+        write_dto(dto, options, o)?;
         o.newline()?;
     }
 
     for nested_ns in namespace.namespaces() {
-        write_namespace(nested_ns, o)?;
+        write_namespace(nested_ns, options, o)?;
         o.newline()?;
     }
 
     Ok(())
 }
 
-fn write_dto(dto: Dto, o: &mut Indented) -> Result<()> {
-    write_attributes(&dto.attributes(), o)?;
+fn write_dto(dto: Dto, options: &RustOptions, o: &mut Indented) -> Result<()> {
+    write_attributes(&dto.attributes(), options, o)?;
 
-    write_dto_start(dto, o)?;
+    write_dto_start(&dto, o)?;
 
     for field in dto.fields() {
-        write_field(field, o)?;
+        write_field(field, options, o)?;
         o.newline()?;
     }
+    write_trailing_comments(&dto.attributes(), options, o)?;
 
-    write_block_end(o)
+    write_block_end(o)?;
+
+    if dto.rpcs().next().is_some() {
+        o.newline()?;
+        o.write_str("impl ")?;
+        o.write_str(&dto.name())?;
+        o.write(' ')?;
+        write_block_start(o)?;
+        for (i, rpc) in dto.rpcs().enumerate() {
+            if i > 0 {
+                o.newline()?;
+            }
+            write_fn(rpc, true, options, o)?;
+        }
+        write_block_end(o)?;
+    }
+    Ok(())
 }
 
-fn write_rpc(rpc: Rpc, o: &mut Indented) -> Result<()> {
-    write_attributes(&rpc.attributes(), o)?;
+fn write_rpc(rpc: Rpc, options: &RustOptions, o: &mut Indented) -> Result<()> {
+    write_fn(rpc, false, options, o)
+}
+
+/// `method`s take `&self` as their first param.
+fn write_fn(rpc: Rpc, method: bool, options: &RustOptions, o: &mut Indented) -> Result<()> {
+    write_attributes(&rpc.attributes(), options, o)?;
 
-    o.write_str("pub fn ")?;
+    o.write_str("pub ")?;
+    if rpc.is_async() {
+        o.write_str("async ")?;
+    }
+    o.write_str("fn ")?;
     o.write_str(&rpc.name())?;
+    write_type_params(rpc.type_params(), o)?;
 
     o.write('(')?;
     o.indent(1);
+    if method {
+        if rpc.params().count() > 0 {
+            o.newline()?;
+            o.write_str("&self,")?;
+        } else {
+            o.write_str("&self")?;
+        }
+    }
     for field in rpc.params() {
         o.newline()?;
-        write_field(field, o)?;
+        write_field(field, options, o)?;
     }
     o.indent(-1);
 
@@ -150,17 +266,30 @@ fn write_rpc(rpc: Rpc, o: &mut Indented) -> Result<()> {
 
     o.write(')')?;
 
-    if let Some(return_type) = rpc.return_type() {
-        o.write_str(" -> ")?;
-        write_type(return_type, o)?;
+    match (rpc.return_type(), rpc.error_type()) {
+        (return_type, Some(error_type)) => {
+            o.write_str(" -> Result<")?;
+            match return_type {
+                Some(return_type) => write_type(return_type, o)?,
+                None => o.write_str("()")?,
+            }
+            o.write_str(", ")?;
+            write_type(error_type, o)?;
+            o.write('>')?;
+        }
+        (Some(return_type), None) => {
+            o.write_str(" -> ")?;
+            write_type(return_type, o)?;
+        }
+        (None, None) => {}
     }
 
     o.write_str(" {}")?;
     o.newline()
 }
 
-fn write_enum(en: Enum, o: &mut Indented) -> Result<()> {
-    write_attributes(&en.attributes(), o)?;
+fn write_enum(en: Enum, options: &RustOptions, o: &mut Indented) -> Result<()> {
+    write_attributes(&en.attributes(), options, o)?;
 
     o.write_str("enum ")?;
     o.write_str(&en.name())?;
@@ -168,15 +297,16 @@ fn write_enum(en: Enum, o: &mut Indented) -> Result<()> {
     write_block_start(o)?;
 
     for value in en.values() {
-        write_enum_value(value, o)?;
+        write_enum_value(value, options, o)?;
         o.newline()?;
     }
+    write_trailing_comments(&en.attributes(), options, o)?;
 
     write_block_end(o)
 }
 
-fn write_enum_value(value: EnumValue, o: &mut dyn Output) -> Result<()> {
-    write_attributes(&value.attributes(), o)?;
+fn write_enum_value(value: EnumValue, options: &RustOptions, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&value.attributes(), options, o)?;
 
     o.write_str(&value.name())?;
     o.write_str(" = ")?;
@@ -184,13 +314,24 @@ fn write_enum_value(value: EnumValue, o: &mut dyn Output) -> Result<()> {
     o.write(',')
 }
 
-fn write_dto_start(dto: Dto, o: &mut Indented) -> Result<()> {
+fn write_dto_start(dto: &Dto, o: &mut Indented) -> Result<()> {
     o.write_str("struct ")?;
     o.write_str(&dto.name())?;
+    write_type_params(dto.type_params(), o)?;
     o.write(' ')?;
     write_block_start(o)
 }
 
+/// Writes e.g. `<K, V>`, or nothing if there are no `type_params`.
+fn write_type_params(type_params: &[&str], o: &mut dyn Output) -> Result<()> {
+    if type_params.is_empty() {
+        return Ok(());
+    }
+    o.write('<')?;
+    write_joined_str(type_params, ", ", o)?;
+    o.write('>')
+}
+
 fn write_block_start(o: &mut Indented) -> Result<()> {
     o.write_str("{")?;
     o.indent(1);
@@ -203,37 +344,96 @@ fn write_block_end(o: &mut Indented) -> Result<()> {
     o.newline()
 }
 
-fn write_field(field: Field, o: &mut dyn Output) -> Result<()> {
-    write_param(field, o)?;
+fn write_field(field: Field, options: &RustOptions, o: &mut dyn Output) -> Result<()> {
+    write_param(field, options, o)?;
     o.write(',')
 }
 
-fn write_param(field: Field, o: &mut dyn Output) -> Result<()> {
-    write_attributes(&field.attributes(), o)?;
+fn write_param(field: Field, options: &RustOptions, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&field.attributes(), options, o)?;
 
     o.write_str(&field.name())?;
     o.write_str(": ")?;
     write_type(field.ty(), o)
 }
 
-fn write_attributes(attributes: &Attributes, o: &mut dyn Output) -> Result<()> {
-    write_comments(&attributes.comments(), o)?;
-    write_user_attributes(attributes.user(), o)?;
+fn write_attributes(
+    attributes: &Attributes,
+    options: &RustOptions,
+    o: &mut dyn Output,
+) -> Result<()> {
+    write_comments(&attributes.comments(), options, o)?;
+    write_user_attributes(&attributes.user(), o)?;
     Ok(())
 }
 
-fn write_comments(comments: &[Comment], o: &mut dyn Output) -> Result<()> {
-    write_joined(comments, "\n", o, |comment, o| {
-        for line in comment.lines() {
-            o.write_str("// ")?;
-            o.write_str(line)?;
-            o.newline()?;
-        }
-        Ok(())
-    })?;
+/// Only written with [RustOptions::fidelity].
+fn write_trailing_comments(
+    attributes: &Attributes,
+    options: &RustOptions,
+    o: &mut dyn Output,
+) -> Result<()> {
+    if !options.fidelity {
+        return Ok(());
+    }
+    write_comments(&attributes.trailing_comments(), options, o)
+}
+
+fn write_comments(comments: &[Comment], options: &RustOptions, o: &mut dyn Output) -> Result<()> {
+    if !options.fidelity {
+        return write_joined(comments, "\n", o, |comment, o| {
+            write_line_comment(comment, "//", o)
+        });
+    }
+    // Inner doc comments are written within their namespace, see [write_inner_doc_comments].
+    let comments = comments
+        .iter()
+        .filter(|comment| comment.style() != CommentStyle::InnerDoc)
+        .collect_vec();
+    write_joined(&comments, "\n", o, |comment, o| match comment.style() {
+        CommentStyle::Line => write_line_comment(comment, "//", o),
+        CommentStyle::Doc => write_line_comment(comment, "///", o),
+        CommentStyle::Block => write_block_comment(comment, o),
+        CommentStyle::InnerDoc => Ok(()),
+    })
+}
+
+/// Only written with [RustOptions::fidelity], at the start of the namespace's contents.
+fn write_inner_doc_comments(attributes: &Attributes, o: &mut dyn Output) -> Result<()> {
+    let comments = attributes.comments();
+    let inner_docs = comments
+        .iter()
+        .filter(|comment| comment.style() == CommentStyle::InnerDoc)
+        .collect_vec();
+    for comment in &inner_docs {
+        write_line_comment(comment, "//!", o)?;
+    }
+    if !inner_docs.is_empty() {
+        o.newline()?;
+    }
     Ok(())
 }
 
+fn write_line_comment(comment: &Comment, prefix: &str, o: &mut dyn Output) -> Result<()> {
+    for line in comment.lines() {
+        o.write_str(prefix)?;
+        o.write(' ')?;
+        o.write_str(line)?;
+        o.newline()?;
+    }
+    Ok(())
+}
+
+/// The parser keeps the contents of a block comment as a single line, including any newlines.
+fn write_block_comment(comment: &Comment, o: &mut dyn Output) -> Result<()> {
+    o.write_str("/* ")?;
+    write_joined(&comment.lines().collect_vec(), "\n", o, |line, o| {
+        o.write_str(line)
+    })?;
+    o.write_str(" */")?;
+    o.newline()
+}
+
 fn write_user_attributes(user_attributes: &[attribute::User], o: &mut dyn Output) -> Result<()> {
     if user_attributes.is_empty() {
         return Ok(());
@@ -258,14 +458,19 @@ fn write_user_attribute(
     }
     o.write('(')?;
     write_joined(data, ", ", o, |data, o| {
-        match data.key {
+        match &data.key {
             None => {}
             Some(key) => {
                 o.write_str(key)?;
                 o.write_str(" = ")?;
             }
         }
-        o.write_str(data.value)
+        if data.quoted {
+            // Debug formatting quotes and escapes the same as a rust string literal.
+            o.write_str(&format!("{:?}", data.value))
+        } else {
+            o.write_str(&data.value)
+        }
     })?;
     o.write(')')?;
     Ok(())
@@ -295,15 +500,32 @@ fn write_inner_type(ty: InnerType, o: &mut dyn Output) -> Result<()> {
         InnerType::F128 => o.write_str("f128"),
         InnerType::String => o.write_str("String"),
         InnerType::Bytes => o.write_str("Vec<u8>"),
-        // For the sake of example, just write the user type name.
+        // Either the `rust` target of the user type, or just its name.
         InnerType::User(s) => o.write_str(s),
-        InnerType::Api(id) => write_entity_id(id, o),
+        InnerType::Api(id, args) => write_api_type(id, args, o),
+        InnerType::Generic(name) => o.write_str(name),
         InnerType::Array(ty) => write_vec(*ty, o),
         InnerType::Map { key, value } => write_map(*key, *value, o),
         InnerType::Optional(ty) => write_option(*ty, o),
     }
 }
 
+/// Writes e.g. `Page<User>`, or just `Page` if there are no type `args`.
+fn write_api_type(id: EntityId, args: Vec<InnerType>, o: &mut dyn Output) -> Result<()> {
+    write_entity_id(id, o)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+    o.write('<')?;
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            o.write_str(", ")?;
+        }
+        write_inner_type(arg, o)?;
+    }
+    o.write('>')
+}
+
 fn write_entity_id(entity_id: EntityId, o: &mut dyn Output) -> Result<()> {
     // Fully qualify everything by crate.
     o.write_str("crate::")?;
@@ -370,7 +592,7 @@ fn collect_chunk_dependencies<'v, 'a>(
 ) -> Vec<PathBuf> {
     collect_dependencies_recursively(dependent_id, dependent_ns, dependencies)
         .iter()
-        .flat_map(|id| match root.find_child(&id) {
+        .flat_map(|id| match root.find_child(id) {
             None => vec![],
             Some(child) => match child.attributes().chunk() {
                 None => vec![],
@@ -415,17 +637,20 @@ fn collect_dependencies_recursively<'a>(
 
 #[cfg(test)]
 mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
     use anyhow::Result;
 
     use crate::generator::rust::{
         write_dto, write_entity_id, write_enum, write_field, write_rpc, INDENT,
     };
-    use crate::generator::Rust;
-    use crate::model::{attribute, Attributes};
+    use crate::generator::{Rust, RustOptions};
+    use crate::model::{attribute, Attributes, Chunk};
     use crate::output::Indented;
-    use crate::test_util::executor::TestExecutor;
-    use crate::view::Transforms;
-    use crate::{model, output, view, Generator};
+    use crate::testing::TestExecutor;
+    use crate::view::{IdentifierPolicy, Order, Transforms};
+    use crate::{input, model, output, parser, view, Executor, Generator};
 
     #[test]
     fn full_generation() -> Result<()> {
@@ -480,6 +705,286 @@ pub mod ns0 {
         assert_output(move |o| Rust::default().generate(view, o), expected)
     }
 
+    #[test]
+    fn fidelity() -> Result<()> {
+        let data = r#"
+//! file doc
+
+/// doc
+struct DtoName {
+    /* block */
+    i: i32,
+    // trailing field comment
+}
+
+// line
+pub enum EnumName {
+    One = 1,
+    // trailing value comment
+}
+
+pub fn rpc_name() {}
+
+mod ns {
+    //! ns doc
+    struct Nested {}
+}
+
+// trailing comment
+"#;
+        let expected = r#"//! file doc
+
+/// doc
+struct DtoName {
+    /* block */
+    i: i32,
+    // trailing field comment
+}
+
+// line
+enum EnumName {
+    One = 1,
+    // trailing value comment
+}
+
+pub fn rpc_name() {}
+
+pub mod ns {
+    //! ns doc
+
+    struct Nested {
+    }
+
+}
+
+// trailing comment
+"#;
+        let mut exe = TestExecutor::new(data);
+        let model = exe.model();
+        let view = model.view();
+        assert_output(
+            move |o| {
+                Rust::new(RustOptions {
+                    fidelity: true,
+                    ..Default::default()
+                })
+                .generate(view, o)
+            },
+            expected,
+        )
+    }
+
+    #[test]
+    fn dto_methods() -> Result<()> {
+        let data = r#"
+struct User {}
+impl User {
+    fn name(&self) -> String {}
+    fn rename(&mut self, name: String) -> Self {}
+}
+"#;
+        let expected = r#"struct User {
+}
+
+impl User {
+    pub fn name(&self) -> String {}
+
+    pub fn rename(
+        &self,
+        name: String,
+    ) -> crate::User {}
+}
+
+"#;
+        let mut exe = TestExecutor::new(data);
+        let model = exe.model();
+        let view = model.view();
+        assert_output(move |o| Rust::default().generate(view, o), expected)
+    }
+
+    #[test]
+    fn generics() -> Result<()> {
+        let data = r#"
+struct Page<T> { items: Vec<T> }
+fn first<K, V>(map: HashMap<K, V>) -> Option<V> {}
+fn pages<T>(page: Page<Vec<T>>) -> Page<u8> {}
+"#;
+        let expected = r#"pub fn first<K, V>(
+    map: HashMap<K, V>,
+) -> Option<V> {}
+
+pub fn pages<T>(
+    page: crate::Page<Vec<T>>,
+) -> crate::Page<u8> {}
+
+struct Page<T> {
+    items: Vec<T>,
+}
+
+"#;
+        let mut exe = TestExecutor::new(data);
+        let model = exe.model();
+        let view = model.view();
+        assert_output(move |o| Rust::default().generate(view, o), expected)
+    }
+
+    #[test]
+    fn async_fn() -> Result<()> {
+        let data = "async fn rpc_name() {}";
+        let expected = "pub async fn rpc_name() {}\n\n";
+        let mut exe = TestExecutor::new(data);
+        let model = exe.model();
+        let view = model.view();
+        assert_output(move |o| Rust::default().generate(view, o), expected)
+    }
+
+    #[test]
+    fn chunked_generation() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::with_relative_file_path("a.rs"), "struct A {}");
+        input.add_chunk(
+            Chunk::with_relative_file_path("b/mod.rs"),
+            "struct B { a: a::A }",
+        );
+        let output = Rc::new(RefCell::new(output::ChunkBuffer::new()));
+        Executor::new(input, parser::Rust::default())
+            .generator(Rust::default())
+            .output_ptr(output.clone())
+            .execute()?;
+
+        let output = output.borrow();
+        assert_eq!(output.get("a.rs"), Some("struct A {\n}\n\n"));
+        assert_eq!(
+            output.get("b/mod.rs"),
+            Some("// use crate::a::*;\nstruct B {\n    a: crate::a::A,\n}\n\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chunked_generation_without_import_comments() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::with_relative_file_path("a.rs"), "struct A {}");
+        input.add_chunk(
+            Chunk::with_relative_file_path("b/mod.rs"),
+            "struct B { a: a::A }",
+        );
+        let output = Rc::new(RefCell::new(output::ChunkBuffer::new()));
+        Executor::new(input, parser::Rust::default())
+            .generator(Rust::new(RustOptions {
+                import_comments: false,
+                ..Default::default()
+            }))
+            .output_ptr(output.clone())
+            .execute()?;
+
+        assert_eq!(
+            output.borrow().get("b/mod.rs"),
+            Some("struct B {\n    a: crate::a::A,\n}\n\n")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn identifier_policy() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::default(), "struct Größe { höhe: Größe }");
+        let output = Rc::new(RefCell::new(output::Buffer::default()));
+        Executor::new(input, parser::Rust::default())
+            .generator(Rust::new(RustOptions {
+                identifiers: IdentifierPolicy::Ascii,
+                ..Default::default()
+            }))
+            .output_ptr(output.clone())
+            .execute()?;
+
+        assert_eq!(
+            output.borrow().to_string(),
+            "struct Grosse {\n    hohe: crate::Grosse,\n}\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn order() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::default(), "struct B {} struct A {}");
+        let output = Rc::new(RefCell::new(output::Buffer::default()));
+        Executor::new(input, parser::Rust::default())
+            .generator(Rust::new(RustOptions {
+                order: Order::Name,
+                ..Default::default()
+            }))
+            .output_ptr(output.clone())
+            .execute()?;
+
+        assert_eq!(
+            output.borrow().to_string(),
+            "struct A {\n}\n\nstruct B {\n}\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn user_type_targets() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(
+            Chunk::default(),
+            "struct A { id: Uuid } struct B { url: Url }",
+        );
+        let config = parser::Config::from_json(
+            r#"{
+                "user_types": [
+                    { "parse": "Uuid", "name": "uuid", "targets": { "rust": "uuid::Uuid" } },
+                    { "parse": "Url", "name": "url", "targets": { "typescript": "string" } }
+                ]
+            }"#,
+        )?;
+        let output = Rc::new(RefCell::new(output::Buffer::default()));
+        Executor::new(input, parser::Rust::default())
+            .parser_config(config)
+            .generator(Rust::default())
+            .output_ptr(output.clone())
+            .execute()?;
+
+        assert_eq!(
+            output.borrow().to_string(),
+            "struct A {\n    id: uuid::Uuid,\n}\n\nstruct B {\n    url: url,\n}\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn user_type_glob_targets() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(
+            Chunk::default(),
+            "struct A { handle: crate::ffi::Handle, ids: Vec<crate::ffi::Id> }",
+        );
+        let config = parser::Config::from_json(
+            r#"{
+                "user_types": [{
+                    "parse": "crate::ffi::*",
+                    "name": "ffi_{1}",
+                    "glob": true,
+                    "targets": { "rust": "sys::{1}" }
+                }]
+            }"#,
+        )?;
+        let output = Rc::new(RefCell::new(output::Buffer::default()));
+        Executor::new(input, parser::Rust::default())
+            .parser_config(config)
+            .generator(Rust::default())
+            .output_ptr(output.clone())
+            .execute()?;
+
+        assert_eq!(
+            output.borrow().to_string(),
+            "struct A {\n    handle: sys::Handle,\n    ids: Vec<sys::Id>,\n}\n\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn dto() -> Result<()> {
         assert_output_slice(
@@ -488,6 +993,7 @@ pub mod ns0 {
                     view::Dto::new(
                         &model::Dto {
                             name: "DtoName",
+                            type_params: vec![],
                             fields: vec![
                                 model::Field {
                                     name: "field0",
@@ -500,10 +1006,12 @@ pub mod ns0 {
                                     attributes: test_attributes(),
                                 },
                             ],
+                            rpcs: vec![],
                             attributes: test_attributes(),
                         },
                         &Transforms::default(),
                     ),
+                    &RustOptions::default(),
                     &mut Indented::new(o, INDENT),
                 )
             },
@@ -527,6 +1035,7 @@ pub mod ns0 {
                     view::Rpc::new(
                         &model::Rpc {
                             name: "rpc_name",
+                            type_params: vec![],
                             params: vec![
                                 model::Field {
                                     name: "param0",
@@ -540,10 +1049,13 @@ pub mod ns0 {
                                 },
                             ],
                             return_type: None,
+                            error_type: None,
+                            is_async: false,
                             attributes: test_attributes(),
                         },
                         &Transforms::default(),
                     ),
+                    &RustOptions::default(),
                     &mut Indented::new(o, INDENT),
                 )
             },
@@ -567,12 +1079,16 @@ pub mod ns0 {
                     view::Rpc::new(
                         &model::Rpc {
                             name: "rpc_name",
+                            type_params: vec![],
                             params: vec![],
                             return_type: Some(model::Type::new_api("ReturnType")?),
+                            error_type: None,
+                            is_async: false,
                             attributes: Default::default(),
                         },
                         &Transforms::default(),
                     ),
+                    &RustOptions::default(),
                     &mut Indented::new(o, INDENT),
                 )
             },
@@ -580,6 +1096,54 @@ pub mod ns0 {
         )
     }
 
+    #[test]
+    fn rpc_with_error() -> Result<()> {
+        assert_output(
+            |o| {
+                write_rpc(
+                    view::Rpc::new(
+                        &model::Rpc {
+                            name: "rpc_name",
+                            type_params: vec![],
+                            params: vec![],
+                            return_type: Some(model::Type::new_api("ReturnType")?),
+                            error_type: Some(model::Type::new_api("ErrorType")?),
+                            is_async: false,
+                            attributes: Default::default(),
+                        },
+                        &Transforms::default(),
+                    ),
+                    &RustOptions::default(),
+                    &mut Indented::new(o, INDENT),
+                )
+            },
+            "pub fn rpc_name() -> Result<crate::ReturnType, crate::ErrorType> {}\n",
+        )
+    }
+
+    #[test]
+    fn rpc_with_error_only() -> Result<()> {
+        assert_output(
+            |o| {
+                write_rpc(
+                    view::Rpc::new(
+                        &model::Rpc {
+                            name: "rpc_name",
+                            type_params: vec![],
+                            error_type: Some(model::Type::String),
+                            is_async: false,
+                            ..Default::default()
+                        },
+                        &Transforms::default(),
+                    ),
+                    &RustOptions::default(),
+                    &mut Indented::new(o, INDENT),
+                )
+            },
+            "pub fn rpc_name() -> Result<(), String> {}\n",
+        )
+    }
+
     #[test]
     fn field() -> Result<()> {
         assert_output_slice(
@@ -594,7 +1158,10 @@ pub mod ns0 {
                         &vec![],
                         &vec![],
                         &vec![],
+                        &vec![],
+                        &Default::default(),
                     ),
+                    &RustOptions::default(),
                     o,
                 )
             },
@@ -626,6 +1193,7 @@ pub mod ns0 {
                         },
                         &Transforms::default(),
                     ),
+                    &RustOptions::default(),
                     &mut Indented::new(o, INDENT),
                 )
             },
@@ -659,13 +1227,20 @@ pub mod ns0 {
                         attribute::UserData::new(Some("b"), "2"),
                     ],
                 ),
+                attribute::User::new(
+                    "serde",
+                    vec![
+                        attribute::UserData::new_quoted(Some("rename"), "x y"),
+                        attribute::UserData::new_quoted(Some("alias"), r#"a "b" \"#),
+                    ],
+                ),
             ],
             ..Default::default()
         }
     }
 
     fn expected_attribute_str() -> &'static str {
-        "#[flag, list(Abc, Def), map(a = 1, b = 2)]"
+        r#"#[flag, list(Abc, Def), map(a = 1, b = 2), serde(rename = "x y", alias = "a \"b\" \\")]"#
     }
 
     mod imports {
@@ -769,7 +1344,7 @@ pub mod ns0 {
         test!(
             entity_id,
             "crate::a::b::c",
-            model::Type::Api(model::EntityId::try_from("a.b.c").unwrap())
+            model::Type::Api(model::EntityId::try_from("a.b.c").unwrap(), vec![])
         );
         test!(
             vec,
@@ -788,7 +1363,10 @@ pub mod ns0 {
         );
 
         fn run_test(ty: model::Type, expected: &str) -> Result<()> {
-            assert_output(|o| write_type(Type::new(&ty, &vec![]), o), expected)
+            assert_output(
+                |o| write_type(Type::new(&ty, &vec![], &vec![], &Default::default()), o),
+                expected,
+            )
         }
     }
 
@@ -0,0 +1,577 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use handlebars::{
+    handlebars_helper, Handlebars, Helper, HelperDef, HelperResult, RenderContext,
+    RenderErrorReason,
+};
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::generator::Generator;
+use crate::model::Chunk;
+use crate::output::Output;
+use crate::view::{
+    Attributes, Dto, Enum, Field, IdentifierPolicy, InnerType, Model, Namespace, Order, Rpc,
+    Transformer, Type, UserTypeTargets,
+};
+
+/// A generator that renders the API through a set of user-supplied
+/// [Handlebars](https://handlebarsjs.com) templates, to target languages and formats that don't
+/// have a generator of their own.
+///
+/// Each of [TemplateOptions::files] renders a template once for the whole API, or once for each
+/// namespace, dto or enum, see [TemplateScope]. The context of a template is the JSON of its
+/// entity:
+/// - Namespaces have a `name`, `path`, `qualified_name`, `comments`, and their `dtos`, `rpcs`,
+///   `enums` and child `namespaces`. The API is the root namespace, with an empty `path`.
+/// - Dtos have a `name`, `path`, `qualified_name`, `comments`, `type_params`, `fields` and `rpcs`.
+/// - Rpcs have a `name`, `comments`, `is_async`, `type_params`, `params`, and a `return_type` and
+///   `error_type` that are `null` if absent.
+/// - Enums have a `name`, `path`, `qualified_name`, `comments` and `values`, which each have a
+///   `name`, `number` and `comments`.
+/// - Fields and params have a `name`, `comments` and `ty`.
+/// - Types have a `kind`, e.g. `u64`, `string`, `api`, `array` or `map`. `api` types also have the
+///   `name`, `path` and `qualified_name` of the entity they refer to and their type `args`, e.g.
+///   `User` in `Page<User>`, `user` and `generic` types a `name`, `array` and `optional` types an
+///   `inner` type, and `map` types a `key` and `value`.
+///
+/// `path`s are arrays of names from the root namespace, and `qualified_name`s the same names
+/// joined with `.`, e.g. `user.User`. `comments` are the lines of all comments of the entity.
+///
+/// Output isn't HTML-escaped. Besides the builtin helpers, templates can use:
+/// - `{{type ty}}` to write a type in the target language, see [TemplateOptions::types].
+/// - `{{join path "/"}}` to join an array of strings with a separator.
+/// - `{{pascal_case name}}`, `{{camel_case name}}` and `{{snake_case name}}` to convert the case
+///   of `snake_case` or `PascalCase` names.
+#[derive(Debug, Default)]
+pub struct Template {
+    options: TemplateOptions,
+    sources: BTreeMap<String, String>,
+}
+
+/// Options for the [Template] generator, from the `generator.template` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TemplateOptions {
+    /// Directory of the templates. Every file in it with the extension `.hbs` is registered under
+    /// its file name without the extension, e.g. `dto.kt.hbs` as `dto.kt`. Templates can include
+    /// each other as partials, e.g. `{{> header.kt}}`. Templates can also be added with
+    /// [Template::with_template].
+    pub dir: Option<PathBuf>,
+
+    /// The files to write. At least one is required.
+    pub files: Vec<TemplateFile>,
+
+    /// How the `type` helper writes each kind of type, as a template rendered with the JSON of the
+    /// type, e.g. `{ "u64": "Long", "array": "List<{{type inner}}>" }`. Kinds without a template
+    /// are written in a language-agnostic notation, e.g. `u64`, `user.User`, `string[]`,
+    /// `map<string, u64>` and `bool?`.
+    pub types: BTreeMap<String, String>,
+
+    /// How non-ASCII identifiers are written. Defaults to [IdentifierPolicy::Keep].
+    pub identifiers: IdentifierPolicy,
+
+    /// The order entities are written in within each kind. Defaults to [Order::Source].
+    pub order: Order,
+}
+
+/// A file written by the [Template] generator.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateFile {
+    /// Name of the template to render.
+    pub template: String,
+
+    /// Path of the file, relative to the output. Itself a template, rendered with the same
+    /// context, e.g. `{{join path "/"}}.kt`. Files rendered to the same path are concatenated.
+    pub path: String,
+
+    /// What the template is rendered for. Defaults to [TemplateScope::Api].
+    #[serde(default)]
+    pub each: TemplateScope,
+}
+
+/// The entities a [TemplateFile] is rendered for.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateScope {
+    /// Once, with the root namespace.
+    #[default]
+    Api,
+    /// Once for every namespace, including the root.
+    Namespace,
+    /// Once for every dto.
+    Dto,
+    /// Once for every enum.
+    Enum,
+}
+
+impl Template {
+    pub fn new(options: TemplateOptions) -> Self {
+        Self {
+            options,
+            sources: BTreeMap::new(),
+        }
+    }
+
+    /// Registers the template `source` under `name`, in addition to the templates in
+    /// [TemplateOptions::dir]. Replaces a template of the same name from the directory.
+    pub fn with_template(mut self, name: impl ToString, source: impl ToString) -> Self {
+        self.sources.insert(name.to_string(), source.to_string());
+        self
+    }
+
+    fn registry(&self) -> Result<Handlebars<'static>> {
+        let mut registry = Handlebars::new();
+        registry.register_escape_fn(handlebars::no_escape);
+        registry.register_helper(
+            "type",
+            Box::new(TypeHelper {
+                types: self.options.types.clone(),
+            }),
+        );
+        registry.register_helper("join", Box::new(join));
+        registry.register_helper("pascal_case", Box::new(pascal_case_helper));
+        registry.register_helper("camel_case", Box::new(camel_case_helper));
+        registry.register_helper("snake_case", Box::new(snake_case_helper));
+
+        if let Some(dir) = &self.options.dir {
+            let entries = fs::read_dir(dir)
+                .with_context(|| format!("read template dir '{}'", dir.display()))?;
+            for entry in entries {
+                let path = entry?.path();
+                let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                    continue;
+                };
+                let Some(name) = name.strip_suffix(".hbs") else {
+                    continue;
+                };
+                if self.sources.contains_key(name) {
+                    continue;
+                }
+                let source = fs::read_to_string(&path)
+                    .with_context(|| format!("read template '{}'", path.display()))?;
+                registry
+                    .register_template_string(name, source)
+                    .with_context(|| format!("parse template '{}'", path.display()))?;
+            }
+        }
+        for (name, source) in &self.sources {
+            registry
+                .register_template_string(name, source)
+                .with_context(|| format!("parse template '{}'", name))?;
+        }
+        Ok(registry)
+    }
+}
+
+impl Generator for Template {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        if self.options.files.is_empty() {
+            return Err(anyhow!(
+                "the template generator requires at least one file, set generator.template.files"
+            ));
+        }
+        let registry = self.registry()?;
+
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model
+            .with_user_type_targets(user_ty_targets)
+            .with_identifier_policy(self.options.identifiers)
+            .with_order(self.options.order);
+        let api = namespace_json(&model.api(), &[]);
+
+        for file in &self.options.files {
+            let mut contexts = vec![];
+            collect_contexts(&api, file.each, &mut contexts);
+            for context in contexts {
+                let path = registry
+                    .render_template(&file.path, context)
+                    .with_context(|| format!("render path '{}'", file.path))?;
+                let content = registry
+                    .render(&file.template, context)
+                    .with_context(|| format!("render template '{}'", file.template))?;
+                output.write_chunk(&Chunk::with_relative_file_path(path))?;
+                output.write_str(&content)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "template";
+
+/// Adds the JSON of every entity in `namespace` that `each` renders a template for to `contexts`.
+fn collect_contexts<'j>(namespace: &'j Value, each: TemplateScope, contexts: &mut Vec<&'j Value>) {
+    match each {
+        TemplateScope::Api => {
+            contexts.push(namespace);
+            return;
+        }
+        TemplateScope::Namespace => contexts.push(namespace),
+        TemplateScope::Dto => contexts.extend(entries(namespace, "dtos")),
+        TemplateScope::Enum => contexts.extend(entries(namespace, "enums")),
+    }
+    for child in entries(namespace, "namespaces") {
+        collect_contexts(child, each, contexts);
+    }
+}
+
+fn entries<'j>(json: &'j Value, key: &str) -> impl Iterator<Item = &'j Value> {
+    json[key].as_array().into_iter().flatten()
+}
+
+fn namespace_json(namespace: &Namespace, path: &[String]) -> Value {
+    json!({
+        "name": namespace.name(),
+        "path": path,
+        "qualified_name": path.join("."),
+        "comments": comments_json(&namespace.attributes()),
+        "dtos": namespace.dtos().map(|dto| dto_json(&dto, path)).collect_vec(),
+        "rpcs": namespace.rpcs().map(|rpc| rpc_json(&rpc)).collect_vec(),
+        "enums": namespace.enums().map(|en| enum_json(&en, path)).collect_vec(),
+        "namespaces": namespace
+            .namespaces()
+            .map(|child| {
+                let path = [path.to_vec(), vec![child.name().to_string()]].concat();
+                namespace_json(&child, &path)
+            })
+            .collect_vec(),
+    })
+}
+
+fn dto_json(dto: &Dto, namespace_path: &[String]) -> Value {
+    let path = [namespace_path.to_vec(), vec![dto.name().to_string()]].concat();
+    json!({
+        "name": dto.name(),
+        "qualified_name": path.join("."),
+        "path": path,
+        "comments": comments_json(&dto.attributes()),
+        "type_params": dto.type_params(),
+        "fields": dto.fields().map(|field| field_json(&field)).collect_vec(),
+        "rpcs": dto.rpcs().map(|rpc| rpc_json(&rpc)).collect_vec(),
+    })
+}
+
+fn rpc_json(rpc: &Rpc) -> Value {
+    json!({
+        "name": rpc.name(),
+        "comments": comments_json(&rpc.attributes()),
+        "is_async": rpc.is_async(),
+        "type_params": rpc.type_params(),
+        "params": rpc.params().map(|param| field_json(&param)).collect_vec(),
+        "return_type": rpc.return_type().map(|ty| type_json(&ty)),
+        "error_type": rpc.error_type().map(|ty| type_json(&ty)),
+    })
+}
+
+fn enum_json(en: &Enum, namespace_path: &[String]) -> Value {
+    let path = [namespace_path.to_vec(), vec![en.name().to_string()]].concat();
+    json!({
+        "name": en.name(),
+        "qualified_name": path.join("."),
+        "path": path,
+        "comments": comments_json(&en.attributes()),
+        "values": en
+            .values()
+            .map(|value| json!({
+                "name": value.name(),
+                "number": value.number(),
+                "comments": comments_json(&value.attributes()),
+            }))
+            .collect_vec(),
+    })
+}
+
+fn field_json(field: &Field) -> Value {
+    json!({
+        "name": field.name(),
+        "comments": comments_json(&field.attributes()),
+        "ty": type_json(&field.ty()),
+    })
+}
+
+fn type_json(ty: &Type) -> Value {
+    inner_type_json(ty.inner())
+}
+
+fn inner_type_json(ty: InnerType) -> Value {
+    let primitive = |kind: &str| json!({ "kind": kind });
+    match ty {
+        InnerType::Bool => primitive("bool"),
+        InnerType::U8 => primitive("u8"),
+        InnerType::U16 => primitive("u16"),
+        InnerType::U32 => primitive("u32"),
+        InnerType::U64 => primitive("u64"),
+        InnerType::U128 => primitive("u128"),
+        InnerType::I8 => primitive("i8"),
+        InnerType::I16 => primitive("i16"),
+        InnerType::I32 => primitive("i32"),
+        InnerType::I64 => primitive("i64"),
+        InnerType::I128 => primitive("i128"),
+        InnerType::F8 => primitive("f8"),
+        InnerType::F16 => primitive("f16"),
+        InnerType::F32 => primitive("f32"),
+        InnerType::F64 => primitive("f64"),
+        InnerType::F128 => primitive("f128"),
+        InnerType::String => primitive("string"),
+        InnerType::Bytes => primitive("bytes"),
+        InnerType::User(name) => json!({ "kind": "user", "name": name }),
+        InnerType::Api(id, args) => {
+            let path = id.path();
+            json!({
+                "kind": "api",
+                "name": path.last(),
+                "qualified_name": path.join("."),
+                "path": path,
+                "args": args.into_iter().map(inner_type_json).collect_vec(),
+            })
+        }
+        InnerType::Generic(name) => json!({ "kind": "generic", "name": name }),
+        InnerType::Array(ty) => json!({ "kind": "array", "inner": inner_type_json(*ty) }),
+        InnerType::Map { key, value } => json!({
+            "kind": "map",
+            "key": inner_type_json(*key),
+            "value": inner_type_json(*value),
+        }),
+        InnerType::Optional(ty) => json!({ "kind": "optional", "inner": inner_type_json(*ty) }),
+    }
+}
+
+fn comments_json(attributes: &Attributes) -> Vec<String> {
+    attributes
+        .comments()
+        .iter()
+        .flat_map(|comment| comment.lines().map(|line| line.to_string()))
+        .collect()
+}
+
+/// The `type` helper, which renders the JSON of a type with the template of its kind from
+/// [TemplateOptions::types], or the default template of the kind.
+struct TypeHelper {
+    types: BTreeMap<String, String>,
+}
+
+impl HelperDef for TypeHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'rc>,
+        r: &'reg Handlebars<'reg>,
+        _: &'rc handlebars::Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn handlebars::Output,
+    ) -> HelperResult {
+        let ty = h
+            .param(0)
+            .ok_or(RenderErrorReason::ParamNotFoundForIndex("type", 0))?
+            .value();
+        let kind = ty["kind"]
+            .as_str()
+            .ok_or_else(|| RenderErrorReason::InvalidParamType("type"))?;
+        let template = match self.types.get(kind) {
+            Some(template) => template.as_str(),
+            None => match kind {
+                "api" => "{{qualified_name}}",
+                "user" | "generic" => "{{name}}",
+                "array" => "{{type inner}}[]",
+                "map" => "map<{{type key}}, {{type value}}>",
+                "optional" => "{{type inner}}?",
+                primitive => primitive,
+            },
+        };
+        out.write(&r.render_template(template, ty)?)?;
+        Ok(())
+    }
+}
+
+handlebars_helper!(join: |items: array, separator: str| {
+    items
+        .iter()
+        .map(|item| match item {
+            Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .join(separator)
+});
+handlebars_helper!(pascal_case_helper: |name: str| pascal_case(name));
+handlebars_helper!(camel_case_helper: |name: str| {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first.to_lowercase().chain(chars).collect::<String>(),
+    }
+});
+handlebars_helper!(snake_case_helper: |name: str| snake_case(name));
+
+/// `name`, which is either `snake_case` or `PascalCase`, in PascalCase.
+fn pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+            }
+        })
+        .join("")
+}
+
+/// `name`, which is either `snake_case` or `PascalCase`, in snake_case.
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::generator::{Template, TemplateFile, TemplateOptions, TemplateScope};
+    use crate::testing::TestExecutor;
+    use crate::{chunks, generator, Generator};
+
+    fn file(template: &str, path: &str, each: TemplateScope) -> TemplateFile {
+        TemplateFile {
+            template: template.to_string(),
+            path: path.to_string(),
+            each,
+        }
+    }
+
+    #[test]
+    fn renders_each_dto() -> Result<()> {
+        let mut exe = TestExecutor::with_chunks(chunks! {
+            "user.rs" => r#"
+                /// A user.
+                struct User {
+                    user_id: u64,
+                    tags: Vec<String>,
+                    kind: Option<Kind>,
+                }
+                enum Kind { Admin = 1, Guest = 2 }
+            "#,
+        });
+        let options = TemplateOptions {
+            files: vec![file("dto", "{{join path \"/\"}}.txt", TemplateScope::Dto)],
+            types: [("u64".to_string(), "Long".to_string())].into(),
+            ..Default::default()
+        };
+        let mut generator = Template::new(options).with_template(
+            "dto",
+            "{{#each comments}}// {{this}}\n{{/each}}\
+             class {{name}}<{{qualified_name}}>\n\
+             {{#each fields}}  {{camel_case name}}: {{type ty}}\n{{/each}}",
+        );
+        let output = exe.generate(&mut generator);
+        assert_eq!(
+            output.get("user/User.txt").unwrap(),
+            r#"// A user.
+class User<user.User>
+  userId: Long
+  tags: string[]
+  kind: user.Kind?
+"#
+        );
+        assert_eq!(output.paths().count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn renders_api_with_partials_and_type_templates() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            enum Kind { Admin = 1, Guest = 2 }
+            async fn get(id: u32, tags: HashMap<String, Vec<u32>>) -> Option<Kind> {}
+            "#,
+        );
+        let options = TemplateOptions {
+            files: vec![file("api", "api.txt", TemplateScope::Api)],
+            types: [
+                ("array".to_string(), "List<{{type inner}}>".to_string()),
+                (
+                    "map".to_string(),
+                    "Map<{{type key}}, {{type value}}>".to_string(),
+                ),
+                ("optional".to_string(), "{{type inner}} | null".to_string()),
+            ]
+            .into(),
+            ..Default::default()
+        };
+        let mut generator = Template::new(options)
+            .with_template(
+                "api",
+                "{{#each enums}}{{> enum}}{{/each}}{{#each rpcs}}{{> rpc}}{{/each}}",
+            )
+            .with_template(
+                "enum",
+                "enum {{name}} {{#each values}}{{name}}={{number}} {{/each}}\n",
+            )
+            .with_template(
+                "rpc",
+                "{{#if is_async}}async {{/if}}{{snake_case name}}(\
+                 {{#each params}}{{name}}: {{type ty}}{{#unless @last}}, {{/unless}}{{/each}})\
+                 {{#if return_type}} -> {{type return_type}}{{/if}}\n",
+            );
+        let output = exe.generate(&mut generator);
+        assert_eq!(
+            output.get("api.txt").unwrap(),
+            "enum Kind Admin=1 Guest=2 \n\
+             async get(id: u32, tags: Map<string, List<u32>>) -> Kind | null\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn renders_each_namespace() -> Result<()> {
+        let mut exe = TestExecutor::with_chunks(chunks! {
+            "a.rs" => "struct A {}",
+            "a/b.rs" => "struct B {}",
+        });
+        let options = TemplateOptions {
+            files: vec![file(
+                "ns",
+                "{{#if path}}{{join path \".\"}}{{else}}root{{/if}}.txt",
+                TemplateScope::Namespace,
+            )],
+            ..Default::default()
+        };
+        let mut generator = Template::new(options).with_template(
+            "ns",
+            "{{#each dtos}}{{qualified_name}}{{/each}}{{#each namespaces}} {{name}}{{/each}}",
+        );
+        let output = exe.generate(&mut generator);
+        assert_eq!(output.get("root.txt").unwrap(), " a");
+        assert_eq!(output.get("a.txt").unwrap(), "a.A b");
+        assert_eq!(output.get("a.b.txt").unwrap(), "a.b.B");
+        Ok(())
+    }
+
+    #[test]
+    fn requires_files() {
+        let mut exe = TestExecutor::new("struct A {}");
+        let model = exe.build();
+        let mut output = crate::output::Buffer::default();
+        assert!(generator::Template::default()
+            .generate(model.view(), &mut output)
+            .is_err());
+    }
+}
@@ -0,0 +1,814 @@
+use std::borrow::Cow;
+
+use anyhow::Result;
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::generator::Generator;
+use crate::model::{Chunk, Comment};
+use crate::output::{Indented, Output};
+use crate::view::{
+    Attributes, Dto, EntityId, Enum, EnumValue, Field, IdentifierPolicy, InnerType, Model,
+    Namespace, Order, Rpc, Transformer, Type, UserTypeTargets,
+};
+
+/// A generator that writes TypeScript declarations for the API:
+/// - [Dto]s become interfaces. Their [Rpc]s are written to a namespace of the same name, which
+///   TypeScript merges with the interface, e.g. `User.get`.
+/// - [Enum]s become numeric enums.
+/// - [Rpc]s become typed, async client function stubs that throw until implemented.
+///
+/// Namespaces are written either as nested TypeScript namespaces in a single `api.ts`, or as one
+/// ES module per namespace, see [ModuleStyle].
+///
+/// 64 and 128-bit integers are written as `bigint` since they don't fit in a `number`. User
+/// attributes have no TypeScript equivalent and are not written.
+///
+/// Names that are TypeScript reserved words are written with a trailing `_`, e.g. `class_`. Field
+/// and enum value names are kept as is, since reserved words are allowed as property names.
+#[derive(Debug, Default)]
+pub struct TypeScript {
+    options: TypeScriptOptions,
+}
+
+/// Options for the [TypeScript] generator, from the `generator.typescript` section of the
+/// [crate::config::Config].
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TypeScriptOptions {
+    /// Defaults to [ModuleStyle::Namespaces].
+    pub modules: ModuleStyle,
+
+    /// How non-ASCII identifiers are written. TypeScript allows them, so defaults to
+    /// [IdentifierPolicy::Keep].
+    pub identifiers: IdentifierPolicy,
+
+    /// The order entities are written in within each kind. Defaults to [Order::Source].
+    pub order: Order,
+}
+
+/// How the namespace hierarchy of the API is written.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleStyle {
+    /// Everything is written to `api.ts`, with each namespace as an `export namespace`.
+    #[default]
+    Namespaces,
+
+    /// Each namespace is written to its own module, e.g. `a.b` to `a/b.ts` and the root namespace
+    /// to `index.ts`. Modules import the modules they refer to, and re-export their child
+    /// namespaces, e.g. `index.ts` re-exports `a.ts` as `a`.
+    EsModules,
+}
+
+impl TypeScript {
+    pub fn new(options: TypeScriptOptions) -> Self {
+        Self { options }
+    }
+}
+
+const INDENT: &str = "  "; // 2 spaces.
+
+/// Name of this generator in the [crate::Registry], used to look up user type targets.
+const TARGET: &str = "typescript";
+
+/// Reserved words, including those reserved in strict mode, which ES modules and namespaces are
+/// always in. None of them can be used as the name of a declaration or param.
+const RESERVED_WORDS: &[&str] = &[
+    "arguments",
+    "await",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "eval",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Where a type is being written from, which determines how references to other entities are
+/// written.
+#[derive(Debug, Copy, Clone)]
+enum Scope<'s> {
+    /// All namespaces are within the same file, so references are qualified by their namespaces.
+    Namespaces,
+
+    /// Within the module of the namespace at this path. References to entities in other modules
+    /// go through the alias they were imported as.
+    Module(&'s [String]),
+}
+
+impl Generator for TypeScript {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let user_ty_targets = UserTypeTargets::new(&model.metadata().user_types, TARGET);
+        let model = model
+            .with_user_type_targets(user_ty_targets)
+            .with_identifier_policy(self.options.identifiers)
+            .with_order(self.options.order);
+        let mut o = Indented::new(output, INDENT);
+
+        match self.options.modules {
+            ModuleStyle::Namespaces => {
+                o.write_chunk(&Chunk::with_relative_file_path("api.ts"))?;
+                write_namespace_contents(model.api(), Scope::Namespaces, &mut o)
+            }
+            ModuleStyle::EsModules => write_module(model.api(), &[], &mut o),
+        }
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["ts"]
+    }
+}
+
+fn write_namespace(namespace: Namespace, o: &mut Indented) -> Result<()> {
+    write_attributes(&namespace.attributes(), o)?;
+
+    o.write_str("export namespace ")?;
+    o.write_str(&ident(&namespace.name()))?;
+    o.write(' ')?;
+
+    if namespace.is_empty() {
+        o.write_str("{}")?;
+        return o.newline();
+    }
+    write_block_start(o)?;
+    write_namespace_contents(namespace, Scope::Namespaces, o)?;
+    write_block_end(o)
+}
+
+fn write_namespace_contents(namespace: Namespace, scope: Scope, o: &mut Indented) -> Result<()> {
+    for rpc in namespace.rpcs() {
+        write_rpc(rpc, scope, o)?;
+        o.newline()?;
+    }
+
+    for en in namespace.enums() {
+        write_enum(en, o)?;
+        o.newline()?;
+    }
+
+    for dto in namespace.dtos() {
+        write_dto(dto, scope, o)?;
+        o.newline()?;
+    }
+
+    if let Scope::Namespaces = scope {
+        for nested_ns in namespace.namespaces() {
+            write_namespace(nested_ns, o)?;
+            o.newline()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes the module of the namespace at `path`, then the modules of its child namespaces.
+fn write_module(namespace: Namespace, path: &[String], o: &mut Indented) -> Result<()> {
+    o.write_chunk(&Chunk::with_relative_file_path(module_file(path)))?;
+
+    let imports = collect_module_dependencies(&namespace, path);
+    for import in &imports {
+        o.write_str("import type * as ")?;
+        o.write_str(&module_alias(import))?;
+        o.write_str(" from \"")?;
+        o.write_str(&module_specifier(path, import))?;
+        o.write_str("\";")?;
+        o.newline()?;
+    }
+    if !imports.is_empty() {
+        o.newline()?;
+    }
+
+    write_namespace_contents(namespace.clone(), Scope::Module(path), o)?;
+
+    let children = namespace
+        .namespaces()
+        .map(|ns| ns.name().to_string())
+        .collect_vec();
+    for child in &children {
+        let child_path = [path, std::slice::from_ref(child)].concat();
+        o.write_str("export * as ")?;
+        o.write_str(&ident(child))?;
+        o.write_str(" from \"")?;
+        o.write_str(&module_specifier(path, &child_path))?;
+        o.write_str("\";")?;
+        o.newline()?;
+    }
+
+    for nested_ns in namespace.namespaces() {
+        let child_path = [path, &[nested_ns.name().to_string()]].concat();
+        write_module(nested_ns, &child_path, o)?;
+    }
+    Ok(())
+}
+
+fn write_dto(dto: Dto, scope: Scope, o: &mut Indented) -> Result<()> {
+    write_attributes(&dto.attributes(), o)?;
+
+    o.write_str("export interface ")?;
+    o.write_str(&ident(&dto.name()))?;
+    write_type_params(dto.type_params(), o)?;
+    o.write(' ')?;
+    write_block_start(o)?;
+
+    for field in dto.fields() {
+        write_field(field, scope, o)?;
+        o.newline()?;
+    }
+
+    write_block_end(o)?;
+
+    if dto.rpcs().next().is_none() {
+        return Ok(());
+    }
+    o.write_str("export namespace ")?;
+    o.write_str(&ident(&dto.name()))?;
+    o.write(' ')?;
+    write_block_start(o)?;
+    for (i, rpc) in dto.rpcs().enumerate() {
+        if i > 0 {
+            o.newline()?;
+        }
+        write_rpc(rpc, scope, o)?;
+    }
+    write_block_end(o)
+}
+
+/// Writes e.g. `<K, V>`, or nothing if there are no `type_params`.
+fn write_type_params(type_params: &[&str], o: &mut dyn Output) -> Result<()> {
+    if type_params.is_empty() {
+        return Ok(());
+    }
+    o.write('<')?;
+    o.write_str(&type_params.join(", "))?;
+    o.write('>')
+}
+
+fn write_rpc(rpc: Rpc, scope: Scope, o: &mut Indented) -> Result<()> {
+    write_attributes(&rpc.attributes(), o)?;
+
+    o.write_str("export async function ")?;
+    o.write_str(&ident(&rpc.name()))?;
+    write_type_params(rpc.type_params(), o)?;
+
+    o.write('(')?;
+    o.indent(1);
+    for param in rpc.params() {
+        o.newline()?;
+        write_param(param, scope, o)?;
+        o.write(',')?;
+    }
+    o.indent(-1);
+
+    if rpc.params().count() > 0 {
+        o.newline()?;
+    }
+
+    o.write_str("): Promise<")?;
+    match rpc.return_type() {
+        Some(return_type) => write_type(return_type, scope, o)?,
+        None => o.write_str("void")?,
+    }
+    o.write_str("> ")?;
+
+    write_block_start(o)?;
+    o.write_str("throw new Error(\"not implemented: ")?;
+    o.write_str(&rpc.name())?;
+    o.write_str("\");")?;
+    o.newline()?;
+    write_block_end(o)
+}
+
+fn write_enum(en: Enum, o: &mut Indented) -> Result<()> {
+    write_attributes(&en.attributes(), o)?;
+
+    o.write_str("export enum ")?;
+    o.write_str(&ident(&en.name()))?;
+    o.write(' ')?;
+    write_block_start(o)?;
+
+    for value in en.values() {
+        write_enum_value(value, o)?;
+        o.newline()?;
+    }
+
+    write_block_end(o)
+}
+
+fn write_enum_value(value: EnumValue, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&value.attributes(), o)?;
+
+    o.write_str(&value.name())?;
+    o.write_str(" = ")?;
+    o.write_str(&value.number().to_string())?;
+    o.write(',')
+}
+
+fn write_block_start(o: &mut Indented) -> Result<()> {
+    o.write_str("{")?;
+    o.indent(1);
+    o.newline()
+}
+
+fn write_block_end(o: &mut Indented) -> Result<()> {
+    o.indent(-1);
+    o.write_str("}")?;
+    o.newline()
+}
+
+/// Optional fields are written as optional properties, e.g. `name?: string`.
+fn write_field(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&field.attributes(), o)?;
+
+    o.write_str(&field.name())?;
+    let ty = field.ty();
+    match ty.inner() {
+        InnerType::Optional(ty) => {
+            o.write_str("?: ")?;
+            write_inner_type(*ty, scope, o)?;
+        }
+        ty => {
+            o.write_str(": ")?;
+            write_inner_type(ty, scope, o)?;
+        }
+    }
+    o.write(';')
+}
+
+/// Unlike fields, optional params are written as `T | undefined`, since TypeScript doesn't allow
+/// required params after optional ones.
+fn write_param(field: Field, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_attributes(&field.attributes(), o)?;
+
+    o.write_str(&ident(&field.name()))?;
+    o.write_str(": ")?;
+    write_type(field.ty(), scope, o)
+}
+
+fn write_attributes(attributes: &Attributes, o: &mut dyn Output) -> Result<()> {
+    write_comments(&attributes.comments(), o)
+}
+
+/// Writes all comments as a single JSDoc comment, with an empty line between each [Comment].
+fn write_comments(comments: &[Comment], o: &mut dyn Output) -> Result<()> {
+    if comments.is_empty() {
+        return Ok(());
+    }
+    o.write_str("/**")?;
+    o.newline()?;
+    for (i, comment) in comments.iter().enumerate() {
+        if i > 0 {
+            o.write_str(" *")?;
+            o.newline()?;
+        }
+        for line in comment.lines() {
+            o.write_str(" *")?;
+            if !line.is_empty() {
+                o.write(' ')?;
+                o.write_str(line)?;
+            }
+            o.newline()?;
+        }
+    }
+    o.write_str(" */")?;
+    o.newline()
+}
+
+fn write_type(ty: Type, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_inner_type(ty.inner(), scope, o)
+}
+
+fn write_inner_type(ty: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    match ty {
+        InnerType::Bool => o.write_str("boolean"),
+        InnerType::U8
+        | InnerType::U16
+        | InnerType::U32
+        | InnerType::I8
+        | InnerType::I16
+        | InnerType::I32
+        | InnerType::F8
+        | InnerType::F16
+        | InnerType::F32
+        | InnerType::F64
+        | InnerType::F128 => o.write_str("number"),
+        InnerType::U64 | InnerType::U128 | InnerType::I64 | InnerType::I128 => {
+            o.write_str("bigint")
+        }
+        InnerType::String => o.write_str("string"),
+        InnerType::Bytes => o.write_str("Uint8Array"),
+        // Either the `typescript` target of the user type, or just its name.
+        InnerType::User(s) => o.write_str(s),
+        InnerType::Api(id, args) => write_api_type(id, args, scope, o),
+        InnerType::Generic(name) => o.write_str(name),
+        InnerType::Array(ty) => write_array(*ty, scope, o),
+        InnerType::Map { key, value } => write_map(*key, *value, scope, o),
+        InnerType::Optional(ty) => write_optional(*ty, scope, o),
+    }
+}
+
+/// Writes e.g. `Page<User>`, or just `Page` if there are no type `args`.
+fn write_api_type(
+    id: EntityId,
+    args: Vec<InnerType>,
+    scope: Scope,
+    o: &mut dyn Output,
+) -> Result<()> {
+    write_entity_id(id, scope, o)?;
+    if args.is_empty() {
+        return Ok(());
+    }
+    o.write('<')?;
+    for (i, arg) in args.into_iter().enumerate() {
+        if i > 0 {
+            o.write_str(", ")?;
+        }
+        write_inner_type(arg, scope, o)?;
+    }
+    o.write('>')
+}
+
+fn write_entity_id(entity_id: EntityId, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    let path = entity_id.path();
+    let (name, namespace) = match path.split_last() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    match scope {
+        Scope::Namespaces => {
+            for component in namespace {
+                o.write_str(&ident(component))?;
+                o.write('.')?;
+            }
+        }
+        Scope::Module(module) => {
+            let namespace = namespace.iter().map(|s| s.to_string()).collect_vec();
+            if namespace != module {
+                o.write_str(&module_alias(&namespace))?;
+                o.write('.')?;
+            }
+        }
+    }
+    o.write_str(&ident(name))
+}
+
+fn write_array(ty: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    if let InnerType::Optional(_) = ty {
+        o.write('(')?;
+        write_inner_type(ty, scope, o)?;
+        o.write(')')?;
+    } else {
+        write_inner_type(ty, scope, o)?;
+    }
+    o.write_str("[]")
+}
+
+fn write_map(key: InnerType, value: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    o.write_str("Map<")?;
+    write_inner_type(key, scope, o)?;
+    o.write_str(", ")?;
+    write_inner_type(value, scope, o)?;
+    o.write('>')
+}
+
+fn write_optional(ty: InnerType, scope: Scope, o: &mut dyn Output) -> Result<()> {
+    write_inner_type(ty, scope, o)?;
+    o.write_str(" | undefined")
+}
+
+/// Paths of the namespaces, other than `path` itself, whose entities are referred to by the
+/// entities directly within `namespace`. Sorted.
+fn collect_module_dependencies(namespace: &Namespace, path: &[String]) -> Vec<Vec<String>> {
+    let mut dependencies = vec![];
+    for dto in namespace.dtos() {
+        for field in dto.fields() {
+            collect_type_namespaces(field.ty().inner(), &mut dependencies);
+        }
+    }
+    let dto_rpcs = namespace.dtos().flat_map(|dto| dto.rpcs().collect_vec());
+    for rpc in namespace.rpcs().chain(dto_rpcs) {
+        for param in rpc.params() {
+            collect_type_namespaces(param.ty().inner(), &mut dependencies);
+        }
+        if let Some(ty) = rpc.return_type() {
+            collect_type_namespaces(ty.inner(), &mut dependencies);
+        }
+    }
+    dependencies
+        .into_iter()
+        .filter(|dependency| dependency != path)
+        .sorted()
+        .dedup()
+        .collect_vec()
+}
+
+fn collect_type_namespaces(ty: InnerType, namespaces: &mut Vec<Vec<String>>) {
+    match ty {
+        InnerType::Api(id, args) => {
+            let path = id.path();
+            if let Some((_, namespace)) = path.split_last() {
+                namespaces.push(namespace.iter().map(|s| s.to_string()).collect_vec());
+            }
+            for arg in args {
+                collect_type_namespaces(arg, namespaces);
+            }
+        }
+        InnerType::Array(ty) | InnerType::Optional(ty) => collect_type_namespaces(*ty, namespaces),
+        InnerType::Map { key, value } => {
+            collect_type_namespaces(*key, namespaces);
+            collect_type_namespaces(*value, namespaces);
+        }
+        _ => {}
+    }
+}
+
+/// Relative path of the module for the namespace at `path`, e.g. `a/b.ts`.
+fn module_file(path: &[String]) -> String {
+    if path.is_empty() {
+        "index.ts".to_string()
+    } else {
+        format!("{}.ts", path.join("/"))
+    }
+}
+
+/// Name that the module for the namespace at `path` is imported as, e.g. `a_b`.
+fn module_alias(path: &[String]) -> String {
+    if path.is_empty() {
+        "root".to_string()
+    } else {
+        ident(&path.join("_")).into_owned()
+    }
+}
+
+/// `name`, with a trailing `_` if it's a TypeScript reserved word.
+fn ident(name: &str) -> Cow<'_, str> {
+    if RESERVED_WORDS.contains(&name) {
+        Cow::Owned(format!("{}_", name))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// Relative import specifier of the module for the namespace at `to` from the module for the
+/// namespace at `from`, e.g. `../a/b`.
+fn module_specifier(from: &[String], to: &[String]) -> String {
+    // Modules are files, so relative imports start from the directory containing `from`.
+    let from_dir = &from[..from.len().saturating_sub(1)];
+    let common = from_dir.iter().zip(to).take_while(|(a, b)| a == b).count();
+    let mut specifier = if common == from_dir.len() {
+        "./".to_string()
+    } else {
+        "../".repeat(from_dir.len() - common)
+    };
+    if to.is_empty() {
+        specifier.push_str("index");
+    } else {
+        specifier.push_str(&to[common..].join("/"));
+    }
+    specifier
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+
+    use crate::generator::typescript::module_specifier;
+    use crate::generator::{ModuleStyle, TypeScript, TypeScriptOptions};
+    use crate::output::Buffer;
+    use crate::testing::TestExecutor;
+    use crate::{input, output, parser, Executor, Generator};
+
+    const API: &str = r#"
+        /// A user.
+        struct User {
+            id: u64,
+            name: String,
+            email: Option<String>,
+            tags: Vec<Option<String>>,
+            kind: user::Kind,
+        }
+        fn get_user(id: u64, verbose: Option<bool>) -> Option<User> {}
+        mod user {
+            enum Kind { Admin = 1, Guest = 2 }
+            fn remove_user(user: User) {}
+        }
+    "#;
+
+    #[test]
+    fn namespaces() -> Result<()> {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let mut output = Buffer::default();
+        TypeScript::default().generate(model.view(), &mut output)?;
+        assert_eq!(
+            output.to_string(),
+            r#"export async function get_user(
+  id: bigint,
+  verbose: boolean | undefined,
+): Promise<User | undefined> {
+  throw new Error("not implemented: get_user");
+}
+
+/**
+ * A user.
+ */
+export interface User {
+  id: bigint;
+  name: string;
+  email?: string;
+  tags: (string | undefined)[];
+  kind: user.Kind;
+}
+
+export namespace user {
+  export async function remove_user(
+    user: User,
+  ): Promise<void> {
+    throw new Error("not implemented: remove_user");
+  }
+
+  export enum Kind {
+    Admin = 1,
+    Guest = 2,
+  }
+
+}
+
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn generics() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            "struct Page<T> { items: Vec<T> } fn first<T>(x: T) {} fn page(x: Page<Page<u8>>) {}",
+        );
+        let model = exe.build();
+        let mut output = Buffer::default();
+        TypeScript::default().generate(model.view(), &mut output)?;
+        let output = output.to_string();
+        assert!(
+            output.contains("export async function first<T>(\n  x: T,\n)"),
+            "{}",
+            output
+        );
+        assert!(
+            output.contains("export interface Page<T> {\n  items: T[];\n}"),
+            "{}",
+            output
+        );
+        assert!(output.contains("  x: Page<Page<number>>,\n"), "{}", output);
+        Ok(())
+    }
+
+    #[test]
+    fn dto_rpcs() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct User { id: u64 }
+            impl User {
+                fn get(id: u64) -> User {}
+                fn ping() {}
+            }
+            "#,
+        );
+        let model = exe.build();
+        let mut output = Buffer::default();
+        TypeScript::default().generate(model.view(), &mut output)?;
+        assert_eq!(
+            output.to_string(),
+            r#"export interface User {
+  id: bigint;
+}
+export namespace User {
+  export async function get(
+    id: bigint,
+  ): Promise<User> {
+    throw new Error("not implemented: get");
+  }
+
+  export async function ping(): Promise<void> {
+    throw new Error("not implemented: ping");
+  }
+}
+
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reserved_words() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct Range { class: u32 }
+            fn range(class: u32, in: u32) -> delete::this {}
+            mod delete {
+                enum this { default = 1 }
+            }
+            "#,
+        );
+        let model = exe.build();
+        let mut output = Buffer::default();
+        TypeScript::default().generate(model.view(), &mut output)?;
+        let output = output.to_string();
+        assert!(
+            output.contains(
+                "export async function range(\n  class_: number,\n  in_: number,\n): Promise<delete_.this_>"
+            ),
+            "{}",
+            output
+        );
+        assert!(output.contains("  class: number;\n"), "{}", output);
+        assert!(output.contains("export namespace delete_ {"), "{}", output);
+        assert!(
+            output.contains("export enum this_ {\n    default = 1,"),
+            "{}",
+            output
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn es_modules() -> Result<()> {
+        let output = Rc::new(RefCell::new(output::ChunkBuffer::new()));
+        Executor::new(input::Buffer::new(API), parser::Rust::default())
+            .generator(TypeScript::new(TypeScriptOptions {
+                modules: ModuleStyle::EsModules,
+                ..Default::default()
+            }))
+            .output_ptr(output.clone())
+            .execute()?;
+
+        let output = output.borrow();
+        let index = output.get("index.ts").unwrap();
+        assert!(index.starts_with("import type * as user from \"./user\";\n\n"));
+        assert!(index.contains("  kind: user.Kind;\n"));
+        assert!(index.ends_with("export * as user from \"./user\";\n"));
+        let user = output.get("user.ts").unwrap();
+        assert!(user.starts_with("import type * as root from \"./index\";\n\n"));
+        assert!(user.contains("  user: root.User,\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn specifiers() {
+        let path = |s: &str| {
+            s.split('.')
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(module_specifier(&path(""), &path("a")), "./a");
+        assert_eq!(module_specifier(&path("a"), &path("")), "./index");
+        assert_eq!(module_specifier(&path("a"), &path("a.b")), "./a/b");
+        assert_eq!(module_specifier(&path("a.b"), &path("a.c")), "./c");
+        assert_eq!(module_specifier(&path("a.b.c"), &path("d")), "../../d");
+        assert_eq!(module_specifier(&path("a.b"), &path("")), "../index");
+    }
+}
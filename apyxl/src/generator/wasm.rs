@@ -0,0 +1,436 @@
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use wasmi::core::TrapCode;
+use wasmi::{Config, Engine, Instance, Linker, Memory, Module, Store};
+
+use crate::generator::Generator;
+use crate::model::Chunk;
+use crate::output::Output;
+use crate::view::{
+    Attributes, Dto, Enum, EnumValue, Field, InnerType, Model, Namespace, NamespaceChild, Rpc, Type,
+};
+
+/// A [Generator] implemented by a WebAssembly module, so that generators can be written in any
+/// language that compiles to WASM. The module runs sandboxed: no host functions are provided to
+/// it, so it cannot access the file system, network, etc. Each run is limited to
+/// [Wasm::DEFAULT_FUEL] units of fuel (roughly one per instruction), or the limit set by
+/// [Wasm::with_fuel], so that a module that never returns fails the run rather than hanging it.
+///
+/// The module must export:
+/// - `memory`: its linear memory.
+/// - `alloc(len: i32) -> i32`: returns a pointer to `len` writable bytes.
+/// - `generate(ptr: i32, len: i32) -> i64`: receives the model as UTF-8 JSON at `ptr` and returns
+///   the generated files as UTF-8 JSON, packed as `(ptr << 32) | len`.
+///
+/// The model JSON is in the form
+/// ```json
+/// { "api": <namespace>, "chunks": [{ "path": "a/b.rs", "api": <namespace> }] }
+/// ```
+/// where `api` is the combined API and each entry in `chunks` is the subset of the API within
+/// that chunk. All transforms of the [Model] are applied. Each dto's [Rpc]s are in its `rpcs`
+/// array, in the same form as the rpcs in a namespace's `children`:
+/// ```json
+/// { "type": "dto", "name": "User", "fields": [...], "rpcs": [{ "type": "rpc", ... }] }
+/// ```
+///
+/// The generated JSON must be in the form
+/// ```json
+/// [{ "path": "a/b.rs", "content": "..." }]
+/// ```
+/// where `path` is the chunk's relative file path, or `null` for data without a chunk.
+pub struct Wasm {
+    name: String,
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeneratedChunk {
+    path: Option<PathBuf>,
+    content: String,
+}
+
+impl Wasm {
+    /// The default fuel limit of each run, see [Wasm::with_fuel].
+    pub const DEFAULT_FUEL: u64 = 1_000_000_000;
+
+    /// Loads a WASM module in binary format.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let wasm =
+            fs::read(path).with_context(|| format!("read wasm module '{}'", path.display()))?;
+        Self::new(path.display().to_string(), &wasm)
+    }
+
+    /// Compiles a WASM module in binary format. `name` is used to identify the module in errors.
+    pub fn new(name: impl ToString, wasm: &[u8]) -> Result<Self> {
+        let name = name.to_string();
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, wasm)
+            .map_err(|err| anyhow!("compile wasm module '{}': {}", name, err))?;
+        Ok(Self {
+            name,
+            engine,
+            module,
+            fuel: Self::DEFAULT_FUEL,
+        })
+    }
+
+    /// Limit each run, from instantiation to the end of `generate`, to `fuel` units of fuel. The
+    /// run fails if the module consumes it all. Defaults to [Wasm::DEFAULT_FUEL].
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = fuel;
+        self
+    }
+
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>> {
+        // A new instance per run so that no state carries over between runs.
+        let mut store = Store::new(&self.engine, ());
+        store
+            .set_fuel(self.fuel)
+            .map_err(|err| anyhow!("set fuel: {}", err))?;
+        let instance = Linker::<()>::new(&self.engine)
+            .instantiate(&mut store, &self.module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|err| anyhow!("instantiate: {}", err))?;
+        let memory = memory(&instance, &store)?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|err| anyhow!("export 'alloc': {}", err))?;
+        let generate = instance
+            .get_typed_func::<(i32, i32), i64>(&store, "generate")
+            .map_err(|err| anyhow!("export 'generate': {}", err))?;
+
+        let len = i32::try_from(input.len()).context("model is too large")?;
+        let ptr = alloc
+            .call(&mut store, len)
+            .map_err(|err| anyhow!("alloc: {}", err))?;
+        memory
+            .write(&mut store, ptr as u32 as usize, input)
+            .map_err(|err| anyhow!("write model: {}", err))?;
+
+        let packed = generate
+            .call(&mut store, (ptr, len))
+            .map_err(|err| self.generate_error(err))? as u64;
+        let mut output = vec![0; (packed & 0xffff_ffff) as usize];
+        memory
+            .read(&store, (packed >> 32) as usize, &mut output)
+            .map_err(|err| anyhow!("read output: {}", err))?;
+        Ok(output)
+    }
+
+    fn generate_error(&self, err: wasmi::Error) -> anyhow::Error {
+        match err.as_trap_code() {
+            Some(TrapCode::OutOfFuel) => anyhow!("generate: out of fuel after {} units", self.fuel),
+            _ => anyhow!("generate: {}", err),
+        }
+    }
+}
+
+fn memory(instance: &Instance, store: &Store<()>) -> Result<Memory> {
+    instance
+        .get_memory(store, "memory")
+        .ok_or_else(|| anyhow!("missing export 'memory'"))
+}
+
+impl Generator for Wasm {
+    fn generate(&mut self, model: Model, output: &mut dyn Output) -> Result<()> {
+        let input = serde_json::to_vec(&model_json(&model)?)?;
+        let generated = self
+            .run(&input)
+            .with_context(|| format!("run wasm module '{}'", self.name))?;
+        let chunks: Vec<GeneratedChunk> = serde_json::from_slice(&generated)
+            .with_context(|| format!("parse output of wasm module '{}'", self.name))?;
+        for chunk in chunks {
+            if let Some(path) = chunk.path {
+                output.write_chunk(&Chunk::with_relative_file_path(path))?;
+            }
+            output.write_str(&chunk.content)?;
+        }
+        Ok(())
+    }
+}
+
+impl Debug for Wasm {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Wasm").field("name", &self.name).finish()
+    }
+}
+
+/// The JSON representation of the [Model] passed to WASM modules.
+fn model_json(model: &Model) -> Result<Value> {
+    let mut chunks = vec![];
+    for result in model.api_chunked_iter() {
+        let (chunk, sub_view) = result?;
+        chunks.push(json!({
+            "path": chunk.relative_file_path,
+            "api": namespace_json(sub_view.namespace()),
+        }));
+    }
+    Ok(json!({
+        "api": namespace_json(model.api()),
+        "chunks": chunks,
+    }))
+}
+
+fn namespace_json(namespace: Namespace) -> Value {
+    let children = namespace
+        .children()
+        .map(|child| match child {
+            NamespaceChild::Dto(dto) => dto_json(dto),
+            NamespaceChild::Rpc(rpc) => rpc_json(rpc),
+            NamespaceChild::Enum(en) => enum_json(en),
+            NamespaceChild::Namespace(namespace) => namespace_json(namespace),
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "type": "namespace",
+        "name": namespace.name(),
+        "attributes": attributes_json(&namespace.attributes()),
+        "children": children,
+    })
+}
+
+fn dto_json(dto: Dto) -> Value {
+    let fields = dto.fields().map(field_json).collect::<Vec<_>>();
+    let rpcs = dto.rpcs().map(rpc_json).collect::<Vec<_>>();
+    json!({
+        "type": "dto",
+        "name": dto.name(),
+        "attributes": attributes_json(&dto.attributes()),
+        "type_params": dto.type_params(),
+        "fields": fields,
+        "rpcs": rpcs,
+    })
+}
+
+fn rpc_json(rpc: Rpc) -> Value {
+    let params = rpc.params().map(field_json).collect::<Vec<_>>();
+    json!({
+        "type": "rpc",
+        "name": rpc.name(),
+        "attributes": attributes_json(&rpc.attributes()),
+        "type_params": rpc.type_params(),
+        "is_async": rpc.is_async(),
+        "params": params,
+        "return_type": rpc.return_type().map(|ty| type_json(&ty)),
+        "error_type": rpc.error_type().map(|ty| type_json(&ty)),
+    })
+}
+
+fn enum_json(en: Enum) -> Value {
+    let values = en.values().map(enum_value_json).collect::<Vec<_>>();
+    json!({
+        "type": "enum",
+        "name": en.name(),
+        "attributes": attributes_json(&en.attributes()),
+        "values": values,
+    })
+}
+
+fn enum_value_json(value: EnumValue) -> Value {
+    json!({
+        "name": value.name(),
+        "number": value.number(),
+        "attributes": attributes_json(&value.attributes()),
+    })
+}
+
+fn field_json(field: Field) -> Value {
+    json!({
+        "name": field.name(),
+        "ty": type_json(&field.ty()),
+        "attributes": attributes_json(&field.attributes()),
+    })
+}
+
+fn type_json(ty: &Type) -> Value {
+    inner_type_json(&ty.inner())
+}
+
+fn inner_type_json(ty: &InnerType) -> Value {
+    match ty {
+        InnerType::Bool => json!("bool"),
+        InnerType::U8 => json!("u8"),
+        InnerType::U16 => json!("u16"),
+        InnerType::U32 => json!("u32"),
+        InnerType::U64 => json!("u64"),
+        InnerType::U128 => json!("u128"),
+        InnerType::I8 => json!("i8"),
+        InnerType::I16 => json!("i16"),
+        InnerType::I32 => json!("i32"),
+        InnerType::I64 => json!("i64"),
+        InnerType::I128 => json!("i128"),
+        InnerType::F8 => json!("f8"),
+        InnerType::F16 => json!("f16"),
+        InnerType::F32 => json!("f32"),
+        InnerType::F64 => json!("f64"),
+        InnerType::F128 => json!("f128"),
+        InnerType::String => json!("string"),
+        InnerType::Bytes => json!("bytes"),
+        InnerType::User(name) => json!({ "user": name }),
+        InnerType::Api(id, args) if args.is_empty() => json!({ "api": id.path() }),
+        InnerType::Api(id, args) => json!({
+            "api": id.path(),
+            "args": args.iter().map(inner_type_json).collect::<Vec<_>>(),
+        }),
+        InnerType::Generic(name) => json!({ "generic": name }),
+        InnerType::Array(ty) => json!({ "array": inner_type_json(ty) }),
+        InnerType::Map { key, value } => json!({
+            "map": { "key": inner_type_json(key), "value": inner_type_json(value) }
+        }),
+        InnerType::Optional(ty) => json!({ "optional": inner_type_json(ty) }),
+    }
+}
+
+fn attributes_json(attributes: &Attributes) -> Value {
+    let comments = attributes
+        .comments()
+        .iter()
+        .map(|comment| json!(comment.lines().collect::<Vec<_>>()))
+        .collect::<Vec<_>>();
+    let user = attributes
+        .user()
+        .iter()
+        .map(|user| {
+            let data = user
+                .data
+                .iter()
+                .map(|data| json!({ "key": data.key, "value": data.value }))
+                .collect::<Vec<_>>();
+            json!({ "name": user.name, "data": data })
+        })
+        .collect::<Vec<_>>();
+    json!({
+        "comments": comments,
+        "user": user,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use serde_json::json;
+
+    use crate::generator::wasm::model_json;
+    use crate::generator::{Generator, Wasm};
+    use crate::output::Buffer;
+    use crate::testing::TestExecutor;
+
+    // Ignores the model and returns `[{"path":"a.txt","content":"hi"}]`.
+    const FIXED_OUTPUT: &str = r#"
+        (module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "[{\"path\":\"a.txt\",\"content\":\"hi\"}]")
+            (global $next (mut i32) (i32.const 1024))
+            (func (export "alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $next))
+                (global.set $next (i32.add (global.get $next) (local.get $len)))
+                (local.get $ptr))
+            (func (export "generate") (param i32 i32) (result i64)
+                (i64.const 33)))
+    "#;
+
+    #[test]
+    fn writes_generated_chunks() -> Result<()> {
+        let mut exe = TestExecutor::new("struct dto {}");
+        let model = exe.build();
+        let mut generator = Wasm::new("fixed", &wat::parse_str(FIXED_OUTPUT)?)?;
+        let mut output = Buffer::default();
+        generator.generate(model.view(), &mut output)?;
+        assert_eq!(output.to_string(), "hi");
+        Ok(())
+    }
+
+    #[test]
+    fn infinite_loop_runs_out_of_fuel() -> Result<()> {
+        let mut exe = TestExecutor::new("struct dto {}");
+        let model = exe.build();
+        let wasm = wat::parse_str(
+            r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "alloc") (param i32) (result i32)
+                    (i32.const 0))
+                (func (export "generate") (param i32 i32) (result i64)
+                    (loop $forever (br $forever))
+                    (i64.const 0)))
+            "#,
+        )?;
+        let mut generator = Wasm::new("loop", &wasm)?.with_fuel(10_000);
+        let mut output = Buffer::default();
+        let err = generator.generate(model.view(), &mut output).unwrap_err();
+        assert!(format!("{:#}", err).contains("out of fuel after 10000 units"));
+        Ok(())
+    }
+
+    #[test]
+    fn missing_exports_error() -> Result<()> {
+        let mut exe = TestExecutor::new("struct dto {}");
+        let model = exe.build();
+        let mut generator = Wasm::new("empty", &wat::parse_str("(module)")?)?;
+        let mut output = Buffer::default();
+        assert!(generator.generate(model.view(), &mut output).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_module_errors() {
+        assert!(Wasm::new("invalid", b"not wasm").is_err());
+    }
+
+    #[test]
+    fn model_to_json() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod ns {
+                // comment
+                struct dto {
+                    field: Option<Vec<u32>>,
+                }
+            }
+            "#,
+        );
+        let model = exe.build();
+        let value = model_json(&model.view())?;
+        let dto = &value["api"]["children"][0]["children"][0];
+        assert_eq!(value["api"]["children"][0]["name"], "ns");
+        assert_eq!(dto["type"], "dto");
+        assert_eq!(dto["name"], "dto");
+        assert_eq!(dto["attributes"]["comments"], json!([["comment"]]));
+        assert_eq!(
+            dto["fields"][0]["ty"],
+            json!({ "optional": { "array": "u32" } })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn dto_rpcs_to_json() -> Result<()> {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct dto {}
+            impl dto {
+                fn get(id: u64) -> dto {}
+            }
+            "#,
+        );
+        let model = exe.build();
+        let value = model_json(&model.view())?;
+        let rpc = &value["api"]["children"][0]["rpcs"][0];
+        assert_eq!(rpc["type"], "rpc");
+        assert_eq!(rpc["name"], "get");
+        assert_eq!(rpc["params"][0]["ty"], "u64");
+        assert_eq!(rpc["return_type"], json!({ "api": ["dto"] }));
+        Ok(())
+    }
+}
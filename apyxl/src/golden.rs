@@ -0,0 +1,294 @@
+use std::cell::RefCell;
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use anyhow::{anyhow, Context, Result};
+use similar::TextDiff;
+use walkdir::WalkDir;
+
+use crate::parser::Parser;
+use crate::{input, output, Executor};
+
+/// Name of the file within the expected directory that data written without a chunk path is
+/// compared against, e.g. the combined API written by [crate::generator::Rust].
+pub const UNCHUNKED_FILE_NAME: &str = "_unchunked";
+
+/// Environment variable that, when set to `1`, makes every [Golden] test overwrite its expected
+/// directory with the generated output instead of comparing against it.
+pub const BLESS_ENV_VAR: &str = "APYXL_BLESS";
+
+/// Fixture-based end-to-end test of a [Parser], [crate::Generator], and any transforms: every file
+/// in an input directory is parsed and generated, and each generated file is compared against
+/// the file with the same relative path in an expected directory.
+///
+/// ```ignore
+/// #[test]
+/// fn my_generator() {
+///     Golden::new("tests/fixtures/input", "tests/fixtures/expected").assert(|input| {
+///         Executor::new(input, parser::Rust::default()).generator(MyGenerator::default())
+///     });
+/// }
+/// ```
+///
+/// Failures list every file that differs with a unified diff, every generated file that is not
+/// expected, and every expected file that was not generated. Run with `APYXL_BLESS=1` (see
+/// [BLESS_ENV_VAR]) to write the generated output to the expected directory instead, e.g. after
+/// an intentional change.
+#[derive(Debug, Clone)]
+pub struct Golden {
+    input_dir: PathBuf,
+    expected_dir: PathBuf,
+    glob: String,
+    bless: bool,
+}
+
+impl Golden {
+    pub fn new<I: Into<PathBuf>, E: Into<PathBuf>>(input_dir: I, expected_dir: E) -> Self {
+        Self {
+            input_dir: input_dir.into(),
+            expected_dir: expected_dir.into(),
+            glob: "**/*".to_string(),
+            bless: env::var(BLESS_ENV_VAR).is_ok_and(|value| value == "1"),
+        }
+    }
+
+    /// Only parse the files within the input directory that match `glob`. Defaults to all files.
+    pub fn glob(mut self, glob: impl ToString) -> Self {
+        self.glob = glob.to_string();
+        self
+    }
+
+    /// Overwrite the expected directory with the generated output instead of comparing against it.
+    /// Defaults to whether [BLESS_ENV_VAR] is set to `1`.
+    pub fn bless(mut self, bless: bool) -> Self {
+        self.bless = bless;
+        self
+    }
+
+    /// Runs the [Executor] returned by `setup` and compares its output against the expected
+    /// directory. `setup` is given the input and should add exactly one [crate::Generator], along
+    /// with any config or transforms. Its output is captured by the [Golden], so it shouldn't add
+    /// any outputs itself.
+    ///
+    /// Errors if generation fails or if the output doesn't match.
+    pub fn run<P, F>(&self, setup: F) -> Result<()>
+    where
+        P: Parser,
+        F: FnOnce(input::Glob) -> Executor<input::Glob, P>,
+    {
+        let input = input::Glob::new_with_root(&self.input_dir, &self.glob)?;
+        let buffer = Rc::new(RefCell::new(output::ChunkBuffer::new()));
+        setup(input).output_ptr(buffer.clone()).execute()?;
+        let buffer = buffer.borrow();
+        let generated = generated_files(&buffer);
+        if self.bless {
+            self.write_expected(&generated)
+        } else {
+            self.compare(&generated)
+        }
+    }
+
+    /// Like [Golden::run], but panics with the error. Intended to be called directly from a
+    /// `#[test]`.
+    pub fn assert<P, F>(&self, setup: F)
+    where
+        P: Parser,
+        F: FnOnce(input::Glob) -> Executor<input::Glob, P>,
+    {
+        if let Err(err) = self.run(setup) {
+            panic!("{:#}", err)
+        }
+    }
+
+    fn compare(&self, generated: &[(PathBuf, &str)]) -> Result<()> {
+        let mut expected = self.expected_files()?;
+        let mut report = String::new();
+        for (path, data) in generated {
+            if !expected.remove(path) {
+                writeln!(
+                    report,
+                    "'{}' was generated but not expected",
+                    path.display()
+                )?;
+                continue;
+            }
+            let expected_path = self.expected_dir.join(path);
+            let expected_data = fs::read_to_string(&expected_path)
+                .with_context(|| format!("read expected '{}'", expected_path.display()))?;
+            if expected_data != *data {
+                writeln!(report, "'{}' differs from expected:", path.display())?;
+                let diff = TextDiff::from_lines(expected_data.as_str(), *data);
+                let diff = diff
+                    .unified_diff()
+                    .header("expected", "generated")
+                    .to_string();
+                writeln!(report, "{}", diff)?;
+            }
+        }
+        for path in expected {
+            writeln!(
+                report,
+                "'{}' was expected but not generated",
+                path.display()
+            )?;
+        }
+
+        if report.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "generated output does not match '{}' (set {}=1 to update it):\n{}",
+                self.expected_dir.display(),
+                BLESS_ENV_VAR,
+                report
+            ))
+        }
+    }
+
+    fn write_expected(&self, generated: &[(PathBuf, &str)]) -> Result<()> {
+        for path in self.expected_files()? {
+            fs::remove_file(self.expected_dir.join(path))?;
+        }
+        for (path, data) in generated {
+            let path = self.expected_dir.join(path);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, data).with_context(|| format!("write '{}'", path.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Paths of all files within the expected directory, relative to it.
+    fn expected_files(&self) -> Result<BTreeSet<PathBuf>> {
+        if !self.expected_dir.exists() {
+            return Ok(BTreeSet::new());
+        }
+        let mut paths = BTreeSet::new();
+        for entry in WalkDir::new(&self.expected_dir) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                paths.insert(relative_path(entry.path(), &self.expected_dir)?);
+            }
+        }
+        Ok(paths)
+    }
+}
+
+fn generated_files(buffer: &output::ChunkBuffer) -> Vec<(PathBuf, &str)> {
+    let mut files = buffer
+        .paths()
+        .map(|path| (path.to_path_buf(), buffer.get(path).unwrap_or_default()))
+        .collect::<Vec<_>>();
+    if !buffer.unchunked().is_empty() {
+        files.push((PathBuf::from(UNCHUNKED_FILE_NAME), buffer.unchunked()));
+    }
+    files
+}
+
+fn relative_path(path: &Path, root: &Path) -> Result<PathBuf> {
+    Ok(path.strip_prefix(root)?.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::Path;
+
+    use anyhow::Result;
+    use tempfile::{tempdir, TempDir};
+
+    use crate::golden::{Golden, UNCHUNKED_FILE_NAME};
+    use crate::{generator, parser, Executor};
+
+    const GENERATED_A: &str = "// use crate::b::*;\nstruct A {\n    b: crate::b::B,\n}\n\n";
+    const GENERATED_B: &str = "struct B {\n}\n\n";
+
+    fn fixture() -> Result<TempDir> {
+        let root = tempdir()?;
+        write(&root.path().join("input/a.rs"), "struct A { b: b::B }")?;
+        write(&root.path().join("input/b.rs"), "struct B {}")?;
+        Ok(root)
+    }
+
+    fn write(path: &Path, data: &str) -> Result<()> {
+        fs::create_dir_all(path.parent().unwrap())?;
+        Ok(fs::write(path, data)?)
+    }
+
+    fn golden(root: &TempDir) -> Golden {
+        Golden::new(root.path().join("input"), root.path().join("expected"))
+            .glob("**/*.rs")
+            .bless(false)
+    }
+
+    fn run(golden: &Golden) -> Result<()> {
+        golden.run(|input| {
+            Executor::new(input, parser::Rust::default()).generator(generator::Rust::default())
+        })
+    }
+
+    #[test]
+    fn bless_then_match() -> Result<()> {
+        let root = fixture()?;
+        run(&golden(&root).bless(true))?;
+        let expected = root.path().join("expected");
+        assert_eq!(fs::read_to_string(expected.join("a.rs"))?, GENERATED_A);
+        assert_eq!(fs::read_to_string(expected.join("b.rs"))?, GENERATED_B);
+        assert!(expected.join(UNCHUNKED_FILE_NAME).is_file());
+
+        run(&golden(&root))
+    }
+
+    #[test]
+    fn differences_are_reported_with_diff() -> Result<()> {
+        let root = fixture()?;
+        run(&golden(&root).bless(true))?;
+        let expected = root.path().join("expected");
+        write(
+            &expected.join("a.rs"),
+            &GENERATED_A.replace("crate::b::B", "crate::b::C"),
+        )?;
+        fs::remove_file(expected.join("b.rs"))?;
+        write(&expected.join("c.rs"), "")?;
+
+        let err = run(&golden(&root)).unwrap_err().to_string();
+        assert!(err.contains("'a.rs' differs from expected"), "{}", err);
+        assert!(err.contains("-    b: crate::b::C,"), "{}", err);
+        assert!(err.contains("+    b: crate::b::B,"), "{}", err);
+        assert!(
+            err.contains("'b.rs' was generated but not expected"),
+            "{}",
+            err
+        );
+        assert!(
+            err.contains("'c.rs' was expected but not generated"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bless_removes_files_no_longer_generated() -> Result<()> {
+        let root = fixture()?;
+        let stale = root.path().join("expected/stale/c.rs");
+        write(&stale, "")?;
+        run(&golden(&root).bless(true))?;
+        assert!(!stale.exists());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "was generated but not expected")]
+    fn assert_panics() {
+        let root = fixture().unwrap();
+        golden(&root).assert(|input| {
+            Executor::new(input, parser::Rust::default()).generator(generator::Rust::default())
+        });
+    }
+}
@@ -0,0 +1,248 @@
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use flate2::read::GzDecoder;
+use itertools::Itertools;
+
+use crate::input::{Data, Input};
+use crate::model::Chunk;
+
+/// Input from the files within a `.zip`, `.tar`, `.tar.gz` or `.tgz` archive, e.g. a released
+/// source bundle, without unpacking it first. The format is detected from the extension of the
+/// archive. All matching files are read into memory on creation.
+///
+/// Only files under `root` within the archive are included, with their paths relative to it as the
+/// chunk's `relative_file_path`, like a [crate::input::FileSet] of the unpacked directory. Chunks
+/// are in sorted path order.
+#[derive(Default)]
+pub struct Archive {
+    chunks: Vec<(Chunk, Data)>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+impl Archive {
+    /// Reads all files under `root` within the archive at `archive_path` with one of `extensions`,
+    /// or every file if `extensions` is empty. Use an empty `root` for the whole archive. Errors if
+    /// the archive can't be read, or any of the files aren't valid UTF-8.
+    pub fn new<A: AsRef<Path>, R: AsRef<Path>>(
+        archive_path: A,
+        root: R,
+        extensions: &[&str],
+    ) -> Result<Self> {
+        let archive_path = archive_path.as_ref();
+        let format = format(archive_path)?;
+        let file = File::open(archive_path)
+            .with_context(|| format!("Failed to open archive: {}", archive_path.display()))?;
+        let reader = BufReader::new(file);
+
+        let mut chunks = vec![];
+        let mut add = |path: &Path, read: &mut dyn Read| -> Result<()> {
+            let relative_file_path = match relative_path(path, root.as_ref(), extensions) {
+                None => return Ok(()),
+                Some(relative_file_path) => relative_file_path,
+            };
+            let mut data = String::new();
+            read.read_to_string(&mut data).with_context(|| {
+                format!("Failed to read archive entry to string: {}", path.display())
+            })?;
+            chunks.push((Chunk::with_relative_file_path(relative_file_path), data));
+            Ok(())
+        };
+        match format {
+            Format::Zip => {
+                let mut archive = zip::ZipArchive::new(reader)?;
+                for i in 0..archive.len() {
+                    let mut entry = archive.by_index(i)?;
+                    if entry.is_dir() {
+                        continue;
+                    }
+                    let path = entry
+                        .enclosed_name()
+                        .ok_or_else(|| anyhow!("Unsafe path in archive: {}", entry.name()))?;
+                    add(&path, &mut entry)?;
+                }
+            }
+            Format::Tar => add_tar_entries(tar::Archive::new(reader), &mut add)?,
+            Format::TarGz => add_tar_entries(tar::Archive::new(GzDecoder::new(reader)), &mut add)?,
+        }
+
+        Ok(Self {
+            chunks: chunks
+                .into_iter()
+                .sorted_by(|(a, _), (b, _)| a.relative_file_path.cmp(&b.relative_file_path))
+                .collect(),
+        })
+    }
+}
+
+fn format(archive_path: &Path) -> Result<Format> {
+    let name = archive_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if name.ends_with(".zip") {
+        Ok(Format::Zip)
+    } else if name.ends_with(".tar") {
+        Ok(Format::Tar)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Ok(Format::TarGz)
+    } else {
+        Err(anyhow!(
+            "Unsupported archive format: {}. Expected .zip, .tar, .tar.gz or .tgz",
+            archive_path.display()
+        ))
+    }
+}
+
+fn add_tar_entries<R: Read>(
+    mut archive: tar::Archive<R>,
+    add: &mut dyn FnMut(&Path, &mut dyn Read) -> Result<()>,
+) -> Result<()> {
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.to_path_buf();
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+        {
+            return Err(anyhow!("Unsafe path in archive: {}", path.display()));
+        }
+        add(&path, &mut entry)?;
+    }
+    Ok(())
+}
+
+/// The path of the entry at `path` relative to `root`, or None if it should be left out.
+fn relative_path(path: &Path, root: &Path, extensions: &[&str]) -> Option<PathBuf> {
+    // Normalizes away `./`, which tar entries often start with.
+    let path = path.components().collect::<PathBuf>();
+    let relative_path = path
+        .strip_prefix(root.components().collect::<PathBuf>())
+        .ok()?;
+    let extension = relative_path.extension().and_then(|ext| ext.to_str());
+    if !extensions.is_empty() && !extension.is_some_and(|extension| extensions.contains(&extension))
+    {
+        return None;
+    }
+    Some(relative_path.to_path_buf())
+}
+
+impl Input for Archive {
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
+        self.chunks
+            .iter()
+            .map(|(c, d)| (c, d.as_str()))
+            .collect_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Write;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Result;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::tempdir;
+
+    use crate::input::Archive;
+    use crate::Input;
+
+    const FILES: &[(&str, &str)] = &[
+        ("bundle-1.0/src/b.rs", "struct b {}"),
+        ("bundle-1.0/src/a/c.rs", "struct c {}"),
+        ("bundle-1.0/README.md", "readme"),
+    ];
+
+    fn write_tar<W: Write>(writer: W) -> Result<W> {
+        let mut builder = tar::Builder::new(writer);
+        for (path, data) in FILES {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, path, data.as_bytes())?;
+        }
+        Ok(builder.into_inner()?)
+    }
+
+    fn write_zip(path: &Path) -> Result<()> {
+        let mut writer = zip::ZipWriter::new(File::create(path)?);
+        for (path, data) in FILES {
+            writer.start_file(*path, zip::write::SimpleFileOptions::default())?;
+            writer.write_all(data.as_bytes())?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+
+    fn chunks(input: &Archive) -> Vec<(PathBuf, &str)> {
+        input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, data)| (chunk.relative_file_path.clone().unwrap(), data))
+            .collect()
+    }
+
+    fn expected_src() -> Vec<(PathBuf, &'static str)> {
+        vec![
+            (PathBuf::from("a/c.rs"), "struct c {}"),
+            (PathBuf::from("b.rs"), "struct b {}"),
+        ]
+    }
+
+    #[test]
+    fn zip() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("bundle.zip");
+        write_zip(&path)?;
+        let input = Archive::new(&path, "bundle-1.0/src", &["rs"])?;
+        assert_eq!(chunks(&input), expected_src());
+        Ok(())
+    }
+
+    #[test]
+    fn tar() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("bundle.tar");
+        write_tar(File::create(&path)?)?;
+        let input = Archive::new(&path, "bundle-1.0/src", &[])?;
+        assert_eq!(chunks(&input), expected_src());
+        Ok(())
+    }
+
+    #[test]
+    fn tar_gz() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("bundle.tgz");
+        write_tar(GzEncoder::new(File::create(&path)?, Compression::default()))?.finish()?;
+        let input = Archive::new(&path, "", &["md"])?;
+        assert_eq!(
+            chunks(&input),
+            vec![(PathBuf::from("bundle-1.0/README.md"), "readme")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unsupported_format_errors() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join("bundle.rar");
+        File::create(&path)?;
+        assert!(Archive::new(&path, "", &[]).is_err());
+        Ok(())
+    }
+}
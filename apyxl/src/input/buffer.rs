@@ -18,7 +18,7 @@ impl Buffer {
 }
 
 impl Input for Buffer {
-    fn chunks(&self) -> Vec<(&Chunk, &Data)> {
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
         vec![(&self.chunk, &self.data)]
     }
 }
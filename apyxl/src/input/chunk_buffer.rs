@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use itertools::Itertools;
 
 use crate::input::{Data, Input};
@@ -17,10 +19,41 @@ impl ChunkBuffer {
     pub fn add_chunk(&mut self, chunk: Chunk, data: impl ToString) {
         self.chunks.push((chunk, data.to_string()))
     }
+
+    /// Adds a chunk with `relative_file_path`. See [crate::chunks] for adding several at once.
+    pub fn with_chunk<P: Into<PathBuf>>(
+        mut self,
+        relative_file_path: P,
+        data: impl ToString,
+    ) -> Self {
+        self.add_chunk(Chunk::with_relative_file_path(relative_file_path), data);
+        self
+    }
+}
+
+/// Creates an [crate::input::ChunkBuffer] with a chunk for each `relative_file_path => data`.
+///
+/// ```
+/// use apyxl::{chunks, Input};
+///
+/// let input = chunks! {
+///     "a/b.rs" => "struct Dto {}",
+///     "c.rs" => "struct Other {}",
+/// };
+/// assert_eq!(input.chunks().len(), 2);
+/// ```
+#[macro_export]
+macro_rules! chunks {
+    ($($relative_file_path:expr => $data:expr),* $(,)?) => {
+        $crate::input::ChunkBuffer::new()$(.with_chunk($relative_file_path, $data))*
+    };
 }
 
 impl Input for ChunkBuffer {
-    fn chunks(&self) -> Vec<(&Chunk, &Data)> {
-        self.chunks.iter().map(|(c, d)| (c, d)).collect_vec()
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
+        self.chunks
+            .iter()
+            .map(|(c, d)| (c, d.as_str()))
+            .collect_vec()
     }
 }
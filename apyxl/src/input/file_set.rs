@@ -1,8 +1,9 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use itertools::Itertools;
+use walkdir::WalkDir;
 
 use crate::input::{Data, Input};
 use crate::model::Chunk;
@@ -34,16 +35,55 @@ impl FileSet {
         }
         Ok(s)
     }
+
+    /// Recursively loads all files within the directory at `root_path` with one of `extensions`,
+    /// or every file if `extensions` is empty. Chunk paths are relative to `root_path`, in sorted
+    /// order. Use [crate::input::Glob] for finer control over which files are included.
+    pub fn from_dir<R: AsRef<Path>>(root_path: R, extensions: &[&str]) -> Result<Self> {
+        let relative_paths = dir_relative_paths(root_path.as_ref(), extensions)?;
+        Self::new(root_path, &relative_paths)
+    }
+
+    /// Moves all chunks from `other` into this [FileSet].
+    pub fn append(&mut self, mut other: FileSet) {
+        self.chunks.append(&mut other.chunks);
+    }
+}
+
+/// Paths of the files within the directory at `root_path`, recursively, relative to it and in
+/// sorted order. Only includes files with one of `extensions`, unless it's empty.
+pub(crate) fn dir_relative_paths(root_path: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut relative_paths = vec![];
+    // Sorted so that chunk order doesn't depend on the file system.
+    for entry in WalkDir::new(root_path).sort_by_file_name() {
+        let entry =
+            entry.with_context(|| format!("Failed to walk directory: {}", root_path.display()))?;
+        if entry.file_type().is_dir() {
+            continue;
+        }
+        let extension = entry.path().extension().and_then(|ext| ext.to_str());
+        if !extensions.is_empty()
+            && !extension.is_some_and(|extension| extensions.contains(&extension))
+        {
+            continue;
+        }
+        relative_paths.push(entry.path().strip_prefix(root_path)?.to_path_buf());
+    }
+    Ok(relative_paths)
 }
 
 impl Input for FileSet {
-    fn chunks(&self) -> Vec<(&Chunk, &Data)> {
-        self.chunks.iter().map(|(c, d)| (c, d)).collect_vec()
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
+        self.chunks
+            .iter()
+            .map(|(c, d)| (c, d.as_str()))
+            .collect_vec()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::fs;
     use std::fs::File;
     use std::io::Write;
     use std::path::{Path, PathBuf};
@@ -63,7 +103,7 @@ mod tests {
         File::create(&path1)?.write_all("test1".as_bytes())?;
         let input = FileSet::new("", &[path0, path1])?;
         let chunks = input.chunks;
-        assert_eq!(chunks.get(0).map(|(_, data)| data.as_str()), Some("test0"));
+        assert_eq!(chunks.first().map(|(_, data)| data.as_str()), Some("test0"));
         assert_eq!(chunks.get(1).map(|(_, data)| data.as_str()), Some("test1"));
         assert_eq!(chunks.get(2).map(|(_, data)| data.as_str()), None);
         Ok(())
@@ -78,7 +118,7 @@ mod tests {
         let chunks = input.chunks();
         assert_eq!(
             chunks
-                .get(0)
+                .first()
                 .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap()),
             Some(path0)
         );
@@ -97,6 +137,28 @@ mod tests {
         path
     }
 
+    #[test]
+    fn from_dir_walks_recursively() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a/b"))?;
+        create_file_in(root.path(), "z.rs");
+        create_file_in(root.path(), "a/b/c.rs");
+        create_file_in(root.path(), "a/readme.md");
+        let paths = |input: &FileSet| {
+            input
+                .chunks()
+                .iter()
+                .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(&FileSet::from_dir(&root, &["rs"])?),
+            vec![PathBuf::from("a/b/c.rs"), PathBuf::from("z.rs")]
+        );
+        assert_eq!(paths(&FileSet::from_dir(&root, &[])?).len(), 3);
+        Ok(())
+    }
+
     #[test]
     fn returns_none_when_empty() -> Result<()> {
         let input = FileSet::new::<&str, &str>("", &[])?;
@@ -1,58 +1,183 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
 use std::env;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context, Result};
 use chumsky::prelude::*;
 use walkdir::WalkDir;
 
 use crate::input;
-use crate::input::{Chunk, Data, Input};
+use crate::input::{Chunk, Input, Reload};
 
 /// Input from one or more files in a file system.
+///
+/// Implements [Reload] by checking the modification time and size of every matching file.
 #[derive(Default)]
 pub struct Glob {
+    /// Each glob along with the root that its chunk paths are relative to.
+    globs: Vec<(PathBuf, String)>,
+    /// Full path globs of files to leave out, even if they match one of the `globs`.
+    excludes: Vec<String>,
     file_set: input::FileSet,
+    /// Version of each file in the `file_set` by full path.
+    versions: BTreeMap<PathBuf, FileVersion>,
 }
 
+/// Modification time and size of a file.
+type FileVersion = (Option<SystemTime>, u64);
+
 impl Glob {
+    /// Files matching `glob`, relative to the current working directory. See [resolve_glob] for
+    /// which directory chunk paths are relative to.
     pub fn new(glob: &str) -> Result<Self> {
-        let (root, glob) = match split_glob(glob) {
-            Some((prefix, glob)) if prefix.is_relative() => {
-                (env::current_dir()?.join(prefix), glob)
-            }
-            _ => (env::current_dir()?, glob.to_string()),
-        };
-        Self::new_with_root(root, &glob)
+        Self::new_with_root_multi(env::current_dir()?, &[glob])
     }
 
     pub fn new_with_root<P: AsRef<Path>>(root_path: P, glob: &str) -> Result<Self> {
+        Self::load(
+            vec![(root_path.as_ref().to_path_buf(), glob.to_string())],
+            vec![],
+        )
+    }
+
+    /// Like [Glob::new_with_root], but includes the files matching any of `globs`. Files matched by
+    /// more than one glob are only included once.
+    ///
+    /// As with [Glob::new], the leading path of each glob (e.g. `../src` in `../src/**/*.rs`) is
+    /// used as the root for that glob, so chunk paths are relative to it. See [resolve_glob].
+    pub fn new_with_root_multi<P: AsRef<Path>, S: AsRef<str>>(
+        root_path: P,
+        globs: &[S],
+    ) -> Result<Self> {
+        Self::new_with_root_excluding::<P, S, &str>(root_path, globs, &[])
+    }
+
+    /// Like [Glob::new_with_root_multi], but leaves out files matching any of `excludes`, e.g.
+    /// `**/generated/**`. Excludes are relative to `root_path`.
+    pub fn new_with_root_excluding<P: AsRef<Path>, S: AsRef<str>, E: AsRef<str>>(
+        root_path: P,
+        globs: &[S],
+        excludes: &[E],
+    ) -> Result<Self> {
+        let excludes = excludes
+            .iter()
+            .map(|exclude| {
+                let path = root_path.as_ref().join(exclude.as_ref());
+                path.to_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| anyhow!("could not convert exclude path '{:?}' to OS str", path))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let globs = globs
+            .iter()
+            .map(|glob| resolve_glob(root_path.as_ref(), glob.as_ref()))
+            .collect();
+        Self::load(globs, excludes)
+    }
+
+    fn load(globs: Vec<(PathBuf, String)>, excludes: Vec<String>) -> Result<Self> {
+        let mut file_set = input::FileSet::default();
+        let mut versions = BTreeMap::new();
+        let exclude_set = exclude_set(&excludes)?;
+        for (root, glob) in &globs {
+            let mut paths = vec![];
+            for path in walk_glob(root, glob, &exclude_set)? {
+                // Files matched by more than one glob are only included once.
+                if let Entry::Vacant(entry) = versions.entry(root.join(&path)) {
+                    let version = file_version(entry.key())?;
+                    entry.insert(version);
+                    paths.push(path);
+                }
+            }
+            file_set.append(input::FileSet::new(root, &paths)?);
+        }
         Ok(Self {
-            file_set: input::FileSet::new(&root_path, &walk_glob(root_path.as_ref(), glob)?)?,
+            globs,
+            excludes,
+            file_set,
+            versions,
         })
     }
 }
 
+impl Reload for Glob {
+    fn reload(&mut self) -> Result<Vec<PathBuf>> {
+        let mut versions = BTreeMap::new();
+        let exclude_set = exclude_set(&self.excludes)?;
+        for (root, glob) in &self.globs {
+            for path in walk_glob(root, glob, &exclude_set)? {
+                let path = root.join(path);
+                let version = file_version(&path)?;
+                versions.entry(path).or_insert(version);
+            }
+        }
+        let changed = versions
+            .iter()
+            .filter(|(path, version)| self.versions.get(*path) != Some(version))
+            .map(|(path, _)| path)
+            .chain(
+                self.versions
+                    .keys()
+                    .filter(|path| !versions.contains_key(*path)),
+            )
+            .cloned()
+            .collect::<Vec<_>>();
+        if !changed.is_empty() {
+            *self = Self::load(self.globs.clone(), self.excludes.clone())?;
+        }
+        Ok(changed)
+    }
+}
+
+fn file_version(path: &Path) -> Result<FileVersion> {
+    let metadata =
+        fs::metadata(path).with_context(|| format!("read metadata of '{}'", path.display()))?;
+    Ok((metadata.modified().ok(), metadata.len()))
+}
+
 impl Input for Glob {
-    fn chunks(&self) -> Vec<(&Chunk, &Data)> {
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
         self.file_set.chunks()
     }
 }
 
-fn walk_glob(root: &Path, glob: &str) -> Result<Vec<PathBuf>> {
+fn exclude_set(excludes: &[String]) -> Result<globset::GlobSet> {
+    let mut builder = globset::GlobSetBuilder::new();
+    for exclude in excludes {
+        builder.add(globset::Glob::new(exclude)?);
+    }
+    Ok(builder.build()?)
+}
+
+fn walk_glob(root: &Path, glob: &str, excludes: &globset::GlobSet) -> Result<Vec<PathBuf>> {
     let mut paths = Vec::new();
     let glob_path = root.join(glob);
+    // A literal path, see [resolve_glob]. No need to walk the whole directory.
+    if glob_path.is_file() {
+        if !excludes.is_match(&glob_path) {
+            paths.push(PathBuf::from(glob));
+        }
+        return Ok(paths);
+    }
+    if !root.exists() {
+        return Ok(paths);
+    }
     let glob = globset::Glob::new(
         glob_path
             .to_str()
             .ok_or_else(|| anyhow!("could not convert glob path '{:?}' to OS str", glob_path))?,
     )?
     .compile_matcher();
-    for entry in WalkDir::new(root) {
+    // Sorted so that chunk order doesn't depend on the file system.
+    for entry in WalkDir::new(root).sort_by_file_name() {
         let entry = entry?;
         if entry.file_type().is_dir() {
             continue;
         }
-        if !glob.is_match(entry.path()) {
+        if !glob.is_match(entry.path()) || excludes.is_match(entry.path()) {
             continue;
         }
         paths.push(entry.path().strip_prefix(root)?.to_path_buf());
@@ -60,6 +185,31 @@ fn walk_glob(root: &Path, glob: &str) -> Result<Vec<PathBuf>> {
     Ok(paths)
 }
 
+/// The root that chunk paths of `glob` are relative to, and the glob relative to that root:
+/// - A literal path to a file, e.g. `src/api.rs`, is relative to the file's directory.
+/// - The leading path of a relative glob, e.g. `src` in `src/**/*.rs`, is joined onto `root`.
+/// - An absolute glob within `root` stays relative to `root`, so that its chunk paths (and the
+///   namespaces parsed from them) are the same as for the equivalent relative glob. One outside of
+///   `root` is relative to its leading path instead.
+fn resolve_glob(root: &Path, glob: &str) -> (PathBuf, String) {
+    match split_glob(glob) {
+        Some((prefix, rest)) if rest.is_empty() && root.join(&prefix).is_file() => {
+            let path = root.join(prefix);
+            let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+            let name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (dir, name)
+        }
+        Some((prefix, rest)) if prefix.is_relative() || !prefix.starts_with(root) => {
+            // Normalized so that excludes match, e.g. `a/b` rather than `a/./b`.
+            (root.join(prefix).components().collect(), rest)
+        }
+        _ => (root.to_path_buf(), glob.to_string()),
+    }
+}
+
 /// Splits a glob into prefix path and glob.
 /// e.g.
 ///     a/b/c/**/*.rs
@@ -84,9 +234,11 @@ mod tests {
     use std::path::PathBuf;
 
     use anyhow::Result;
+    use globset::GlobSet;
     use tempfile::tempdir;
 
     use crate::input::glob::walk_glob;
+    use crate::input::{Glob, Input, Reload};
 
     #[test]
     fn test_walk_glob() -> Result<()> {
@@ -102,7 +254,7 @@ mod tests {
         File::create(root.path().join(&path1))?;
         File::create(root.path().join(&path2))?;
         File::create(root.path().join(&path3))?;
-        let paths = walk_glob(root.path(), "a/**/*.rs")?;
+        let paths = walk_glob(root.path(), "a/**/*.rs", &GlobSet::empty())?;
         assert_eq!(paths.len(), 3);
         assert!(paths.contains(&path0));
         assert!(paths.contains(&path1));
@@ -110,6 +262,155 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn multiple_globs() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a"))?;
+        fs::create_dir_all(root.path().join("b"))?;
+        File::create(root.path().join("a/file0.rs"))?;
+        File::create(root.path().join("b/file1.rs"))?;
+        File::create(root.path().join("b/file2.txt"))?;
+        let input = Glob::new_with_root_multi(root.path(), &["**/*.rs", "a/*.rs"])?;
+        let mut paths = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap())
+            .collect::<Vec<_>>();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("a/file0.rs"), PathBuf::from("b/file1.rs")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn excludes() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a/generated"))?;
+        File::create(root.path().join("a/file0.rs"))?;
+        File::create(root.path().join("a/generated/file1.rs"))?;
+        File::create(root.path().join("a/file2_test.rs"))?;
+        let input = Glob::new_with_root_excluding(
+            root.path(),
+            &["**/*.rs"],
+            &["**/generated/**", "**/*_test.rs"],
+        )?;
+        let paths = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![PathBuf::from("a/file0.rs")]);
+        Ok(())
+    }
+
+    #[test]
+    fn multiple_globs_relative_to_prefix() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a"))?;
+        fs::create_dir_all(root.path().join("b/c"))?;
+        File::create(root.path().join("b/c/file0.rs"))?;
+        let input = Glob::new_with_root_multi(root.path().join("a"), &["../b/**/*.rs"])?;
+        let paths = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![PathBuf::from("c/file0.rs")]);
+        Ok(())
+    }
+
+    #[test]
+    fn literal_file_path() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a"))?;
+        fs::write(root.path().join("a/file0.rs"), "data")?;
+        File::create(root.path().join("a/file1.rs"))?;
+        let absolute = root.path().join("a/file0.rs");
+        for glob in ["a/file0.rs", absolute.to_str().unwrap()] {
+            let input = Glob::new_with_root_multi(root.path(), &[glob])?;
+            let chunks = input
+                .chunks()
+                .into_iter()
+                .map(|(chunk, data)| (chunk.relative_file_path.clone().unwrap(), data))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                chunks,
+                vec![(PathBuf::from("file0.rs"), "data")],
+                "{}",
+                glob
+            );
+        }
+        let input = Glob::new_with_root_excluding(root.path(), &["a/file0.rs"], &["**/file0.rs"])?;
+        assert!(input.chunks().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn absolute_glob_relative_to_root() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a"))?;
+        File::create(root.path().join("a/file0.rs"))?;
+        let glob = root.path().join("a/*.rs");
+        let input = Glob::new_with_root_multi(root.path(), &[glob.to_str().unwrap()])?;
+        let paths = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![PathBuf::from("a/file0.rs")]);
+
+        // Outside of the root, relative to the glob's leading path instead.
+        let other = tempdir()?;
+        let input = Glob::new_with_root_multi(other.path(), &[glob.to_str().unwrap()])?;
+        let paths = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, _)| chunk.relative_file_path.clone().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, vec![PathBuf::from("file0.rs")]);
+        Ok(())
+    }
+
+    #[test]
+    fn reload_only_when_changed() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("a.rs"), "a")?;
+        fs::write(root.path().join("b.rs"), "b")?;
+        let mut input = Glob::new_with_root(root.path(), "*.rs")?;
+        assert!(input.reload()?.is_empty());
+
+        fs::write(root.path().join("a.rs"), "changed")?;
+        fs::remove_file(root.path().join("b.rs"))?;
+        fs::write(root.path().join("c.rs"), "c")?;
+        let mut changed = input.reload()?;
+        changed.sort();
+        assert_eq!(
+            changed,
+            vec![
+                root.path().join("a.rs"),
+                root.path().join("b.rs"),
+                root.path().join("c.rs")
+            ]
+        );
+        let mut chunks = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, data)| (chunk.relative_file_path.clone().unwrap(), data.to_string()))
+            .collect::<Vec<_>>();
+        chunks.sort();
+        assert_eq!(
+            chunks,
+            vec![
+                (PathBuf::from("a.rs"), "changed".to_string()),
+                (PathBuf::from("c.rs"), "c".to_string())
+            ]
+        );
+        assert!(input.reload()?.is_empty());
+        Ok(())
+    }
+
     mod split_glob {
         use std::path::PathBuf;
 
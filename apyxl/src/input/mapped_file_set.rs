@@ -0,0 +1,174 @@
+use std::fs::File;
+use std::path::Path;
+
+use anyhow::{anyhow, Context, Result};
+use log::error;
+use memmap2::Mmap;
+
+use crate::input::file_set::dir_relative_paths;
+use crate::input::Input;
+use crate::model::Chunk;
+
+/// Input from one or more files in a file system, like [crate::input::FileSet], but memory-mapped
+/// instead of read up front. The OS pages the data of each file in as it's parsed, so parsing
+/// starts right away and the data doesn't count against the heap, no matter the size of the
+/// input.
+///
+/// Files must not be modified while mapped. Data is only checked to be UTF-8 when it's accessed,
+/// so invalid files are reported by [Input::chunk_iter] rather than on creation.
+#[derive(Default)]
+pub struct MappedFileSet {
+    /// Empty files have no mapping, since not every platform can map zero bytes.
+    chunks: Vec<(Chunk, Option<Mmap>)>,
+}
+
+impl MappedFileSet {
+    /// Maps all files into memory. Errors if any fail to be opened or mapped.
+    pub fn new<R, P>(root_path: R, relative_paths: &[P]) -> Result<Self>
+    where
+        R: AsRef<Path>,
+        P: AsRef<Path>,
+    {
+        let mut s = Self::default();
+        for relative_path in relative_paths {
+            let relative_file_path = relative_path.as_ref().to_path_buf();
+            let file_path = root_path.as_ref().join(&relative_file_path);
+            let file = File::open(&file_path)
+                .with_context(|| format!("Failed to open file: {}", file_path.display()))?;
+            let is_empty = file.metadata()?.len() == 0;
+            let mmap = if is_empty {
+                None
+            } else {
+                // SAFETY: the data is only ever read, and is documented to require that the file
+                // isn't modified while mapped.
+                Some(
+                    unsafe { Mmap::map(&file) }
+                        .with_context(|| format!("Failed to map file: {}", file_path.display()))?,
+                )
+            };
+            s.chunks
+                .push((Chunk::with_relative_file_path(relative_file_path), mmap));
+        }
+        Ok(s)
+    }
+
+    /// Recursively maps all files within the directory at `root_path` with one of `extensions`, or
+    /// every file if `extensions` is empty. See [crate::input::FileSet::from_dir].
+    pub fn from_dir<R: AsRef<Path>>(root_path: R, extensions: &[&str]) -> Result<Self> {
+        let relative_paths = dir_relative_paths(root_path.as_ref(), extensions)?;
+        Self::new(root_path, &relative_paths)
+    }
+}
+
+fn chunk_data<'a>(chunk: &'a Chunk, mmap: &'a Option<Mmap>) -> Result<(&'a Chunk, &'a str)> {
+    let data = match mmap {
+        None => "",
+        Some(mmap) => std::str::from_utf8(mmap).map_err(|err| {
+            anyhow!(
+                "File is not valid UTF-8: {}: {}",
+                chunk
+                    .relative_file_path
+                    .as_ref()
+                    .map(|path| path.display().to_string())
+                    .unwrap_or_default(),
+                err
+            )
+        })?,
+    };
+    Ok((chunk, data))
+}
+
+impl Input for MappedFileSet {
+    /// Files that aren't valid UTF-8 are logged and left out. Use [Input::chunk_iter] to handle
+    /// them as errors instead.
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
+        self.chunks
+            .iter()
+            .filter_map(|(chunk, mmap)| match chunk_data(chunk, mmap) {
+                Ok(chunk) => Some(chunk),
+                Err(err) => {
+                    error!("{:#}", err);
+                    None
+                }
+            })
+            .collect()
+    }
+
+    fn chunk_iter(&self) -> Box<dyn Iterator<Item = Result<(&Chunk, &str)>> + '_> {
+        Box::new(
+            self.chunks
+                .iter()
+                .map(|(chunk, mmap)| chunk_data(chunk, mmap)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::input::MappedFileSet;
+    use crate::model::Builder;
+    use crate::parser::Config;
+    use crate::{parser, Input, Parser};
+
+    #[test]
+    fn maps_each_file_as_chunk() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir_all(root.path().join("a"))?;
+        fs::write(root.path().join("a/b.rs"), "struct dto {}")?;
+        fs::write(root.path().join("empty.rs"), "")?;
+        fs::write(root.path().join("readme.md"), "not rust")?;
+
+        let input = MappedFileSet::from_dir(&root, &["rs"])?;
+        let chunks = input
+            .chunk_iter()
+            .map(|chunk| chunk.map(|(chunk, data)| (chunk.relative_file_path.clone(), data)))
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(
+            chunks,
+            vec![
+                (Some(PathBuf::from("a/b.rs")), "struct dto {}"),
+                (Some(PathBuf::from("empty.rs")), ""),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_utf8_errors_when_accessed() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("a.rs"), "struct dto {}")?;
+        fs::write(root.path().join("b.rs"), [0xff, 0xfe])?;
+
+        let input = MappedFileSet::from_dir(&root, &[])?;
+        let results = input.chunk_iter().collect::<Vec<_>>();
+        assert!(results[0].is_ok());
+        assert!(results[1].is_err());
+        assert_eq!(input.chunks().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parses() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("a.rs"), "struct dto {}")?;
+
+        let mut input = MappedFileSet::from_dir(&root, &[])?;
+        let mut builder = Builder::default();
+        let config = Config::default();
+        parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+        let model = builder.build().unwrap();
+        assert!(model.api().namespace("a").unwrap().dto("dto").is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn missing_file_errors() {
+        assert!(MappedFileSet::new("", &["i/do/not/exist"]).is_err());
+    }
+}
@@ -1,14 +1,30 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+
 use crate::model::Chunk;
+#[cfg(feature = "fs")]
+pub use archive::Archive;
 pub use buffer::Buffer;
 pub use chunk_buffer::ChunkBuffer;
+#[cfg(feature = "fs")]
 pub use file_set::FileSet;
+#[cfg(feature = "fs")]
 pub use glob::Glob;
+#[cfg(feature = "fs")]
+pub use mapped_file_set::MappedFileSet;
 pub use stdin::StdIn;
 
+#[cfg(feature = "fs")]
+mod archive;
 mod buffer;
 mod chunk_buffer;
+#[cfg(feature = "fs")]
 mod file_set;
+#[cfg(feature = "fs")]
 mod glob;
+#[cfg(feature = "fs")]
+mod mapped_file_set;
 mod stdin;
 
 /// An [Input] wraps some form of data retrieval and translates it to the format
@@ -18,8 +34,39 @@ mod stdin;
 /// referring to individual files in a set of input files. [Chunk]s and their associated [Data]
 /// must remain in memory for the duration of parsing. This is a choice that requires more memory,
 /// but allows the parsing and generation process to be nearly copy-free.
-pub trait Input {
-    fn chunks(&self) -> Vec<(&Chunk, &Data)>;
+///
+/// Inputs may load the data of each chunk on demand instead of up front, e.g. [MappedFileSet]
+/// which leaves paging files in to the OS. See [Input::chunk_iter].
+pub trait Input: AsDynInput {
+    /// All chunks and their data. Inputs that load data on demand load all of it first.
+    fn chunks(&self) -> Vec<(&Chunk, &str)>;
+
+    /// Iterates over the chunks, loading the data of each one as it's reached for inputs that load
+    /// data on demand. Errors if the data of a chunk can't be loaded. Parsers use this rather than
+    /// [Input::chunks] so that parsing starts before the entire input is loaded.
+    fn chunk_iter(&self) -> Box<dyn Iterator<Item = Result<(&Chunk, &str)>> + '_> {
+        Box::new(self.chunks().into_iter().map(Ok))
+    }
+}
+
+/// An [Input] whose source can change after it is loaded, e.g. files on disk. Used by
+/// [crate::Executor::watch].
+pub trait Reload: Input {
+    /// Reloads the data if the source changed since it was last loaded. Returns the paths that were
+    /// added, changed or removed, which is empty if nothing changed.
+    fn reload(&mut self) -> Result<Vec<PathBuf>>;
+}
+
+/// Converts any [Input] to a `dyn Input`, including one that is already unsized. Implemented
+/// automatically for every [Input].
+pub trait AsDynInput {
+    fn as_dyn_input(&mut self) -> &mut dyn Input;
+}
+
+impl<T: Input> AsDynInput for T {
+    fn as_dyn_input(&mut self) -> &mut dyn Input {
+        self
+    }
 }
 
 pub type Data = String;
@@ -23,7 +23,7 @@ impl StdIn {
 }
 
 impl Input for StdIn {
-    fn chunks(&self) -> Vec<(&Chunk, &Data)> {
+    fn chunks(&self) -> Vec<(&Chunk, &str)> {
         vec![(&self.chunk, &self.data)]
     }
 }
@@ -1,16 +1,37 @@
+pub use crate::baseline::Baseline;
+pub use crate::dead_types::DeadTypes;
+pub use crate::diagnostic::{Diagnostic, Diagnostics, Mode};
 pub use crate::executor::Executor;
 pub use crate::generator::Generator;
 pub use crate::input::Input;
 pub use crate::output::Output;
 pub use crate::parser::Parser;
+#[cfg(feature = "fs")]
+pub use crate::pipeline::Pipeline;
+pub use crate::registry::{Registry, RegistryEntry};
+pub use crate::stats::Stats;
 
+pub mod baseline;
+pub mod config;
+pub mod dead_types;
+pub mod diagnostic;
+pub mod diff;
 pub mod executor;
 pub mod generator;
+#[cfg(feature = "fs")]
+pub mod golden;
 pub mod input;
 pub mod model;
 pub mod output;
 pub mod parser;
+#[cfg(feature = "fs")]
+pub mod pipeline;
+pub mod query;
+mod registry;
 mod rust_util;
+pub mod stats;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod view;
 
 #[cfg(test)]
@@ -1,39 +1,118 @@
 use std::borrow::Cow;
+use std::ops::Range;
 
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 
-use crate::model::chunk;
+use crate::model::{chunk, EntityId};
 
 /// Additional metadata attached to entities.
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Attributes<'a> {
     pub chunk: Option<chunk::Attribute>,
     pub comments: Vec<Comment<'a>>,
+    #[serde(borrow)]
     pub user: Vec<User<'a>>,
+    /// Byte range of the entity within the data of its chunk, if the parser records it. See
+    /// [crate::query::Query] for looking up entities by location.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Range<usize>>,
+    /// Comments after the last child of a namespace, field of a dto or value of an enum, that
+    /// aren't attached to any entity. Kept so that generators can reproduce them, see
+    /// [crate::generator::RustOptions::fidelity].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trailing_comments: Vec<Comment<'a>>,
+    /// Entities brought into scope of a namespace, e.g. by `use` declarations at the top of a rust
+    /// file. Used to qualify the types referenced within the namespace, see [crate::model::Builder].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<Import>,
 }
 
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Comment<'a> {
     lines: Vec<Cow<'a, str>>,
+    #[serde(default, skip_serializing_if = "CommentStyle::is_line")]
+    style: CommentStyle,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// How a [Comment] was written in the source, so that generators for the same language can
+/// reproduce it. Generators for other languages can ignore it.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub enum CommentStyle {
+    /// A comment that runs to the end of the line, e.g. `// comment`.
+    #[default]
+    Line,
+    /// A documentation comment, e.g. `/// comment`.
+    Doc,
+    /// A documentation comment for the enclosing item rather than the following one, e.g.
+    /// `//! comment` at the top of a file or module. Attached to the enclosing [Namespace].
+    InnerDoc,
+    /// A delimited comment, e.g. `/* comment */`.
+    Block,
+}
+
+/// A user-defined attribute, e.g. `#[name]`, `#[name(a, b)]` or `#[name(key = "value")]`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct User<'a> {
     pub name: &'a str,
+    #[serde(borrow)]
     pub data: Vec<UserData<'a>>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct UserData<'a> {
-    pub key: Option<&'a str>,
-    pub value: &'a str,
+    #[serde(borrow)]
+    pub key: Option<Cow<'a, str>>,
+    /// Without quotes if the value is a string literal, see [UserData::quoted], and with its
+    /// escapes resolved, e.g. `"a \"b\""` is `a "b"`. Owned rather than borrowed when it can't be,
+    /// e.g. when read back from json with escapes.
+    #[serde(borrow)]
+    pub value: Cow<'a, str>,
+    /// Whether the value was a string literal, e.g. `rename = "id"`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub quoted: bool,
+}
+
+/// An entity brought into scope of a namespace, e.g. `use a::b::C;` or `use a::b::*;` in rust.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Import {
+    /// Unqualified path of the imported entity, or of the namespace whose children are all
+    /// imported if `glob`.
+    pub path: EntityId,
+    /// Whether `path` starts at the root namespace. Otherwise it's relative to the importing
+    /// namespace and resolved by walking up the namespace hierarchy like any other type.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub absolute: bool,
+    /// The name the entity is in scope as, if different from the last component of `path`, e.g.
+    /// `D` for `use a::b::C as D;`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
+    /// Whether all children of `path` are imported, e.g. `use a::b::*;`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub glob: bool,
+}
+
+impl Import {
+    /// The name the imported entity is in scope as, or None for a `glob` import.
+    pub fn name(&self) -> Option<&str> {
+        if self.glob {
+            None
+        } else {
+            self.alias.as_deref().or_else(|| self.path.name())
+        }
+    }
 }
 
 impl<'a> Attributes<'a> {
-    pub fn merge(&mut self, other: Self) {
+    pub fn merge(&mut self, mut other: Self) {
         self.merge_chunks(other.chunk);
         self.merge_comments(other.comments);
         self.merge_user(other.user);
+        self.trailing_comments.append(&mut other.trailing_comments);
+        self.imports.append(&mut other.imports);
+        if self.span.is_none() {
+            self.span = other.span;
+        }
     }
 
     fn merge_chunks(&mut self, other: Option<chunk::Attribute>) {
@@ -62,9 +141,19 @@ impl<'a> Comment<'a> {
                 .iter()
                 .map(|s| Cow::Borrowed(s.as_ref()))
                 .collect_vec(),
+            style: CommentStyle::default(),
         }
     }
 
+    pub fn with_style(mut self, style: CommentStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    pub fn style(&self) -> CommentStyle {
+        self.style
+    }
+
     pub fn lines(&self) -> impl Iterator<Item = &Cow<'a, str>> {
         self.lines.iter()
     }
@@ -76,18 +165,28 @@ impl<'a> Comment<'a> {
 
 impl<'a> From<Vec<Cow<'a, str>>> for Comment<'a> {
     fn from(value: Vec<Cow<'a, str>>) -> Self {
-        Self { lines: value }
+        Self {
+            lines: value,
+            style: CommentStyle::default(),
+        }
     }
 }
 
 impl<'a> From<Vec<&'a str>> for Comment<'a> {
     fn from(value: Vec<&'a str>) -> Self {
         Self {
-            lines: value.into_iter().map(|s| Cow::Borrowed(s)).collect_vec(),
+            lines: value.into_iter().map(Cow::Borrowed).collect_vec(),
+            style: CommentStyle::default(),
         }
     }
 }
 
+impl CommentStyle {
+    fn is_line(&self) -> bool {
+        *self == CommentStyle::Line
+    }
+}
+
 impl<'a> User<'a> {
     pub fn new(name: &'a str, data: Vec<UserData<'a>>) -> Self {
         Self { name, data }
@@ -99,8 +198,21 @@ impl<'a> User<'a> {
 }
 
 impl<'a> UserData<'a> {
-    pub fn new(key: Option<&'a str>, value: &'a str) -> Self {
-        Self { key, value }
+    pub fn new(key: Option<&'a str>, value: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            key: key.map(Cow::Borrowed),
+            value: value.into(),
+            quoted: false,
+        }
+    }
+
+    /// A string literal value, without its quotes.
+    pub fn new_quoted(key: Option<&'a str>, value: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            key: key.map(Cow::Borrowed),
+            value: value.into(),
+            quoted: true,
+        }
     }
 }
 
@@ -1,9 +1,32 @@
 use crate::model::{Api, EntityId, EntityType, Namespace, NamespaceChild, Type};
 use itertools::Itertools;
+use petgraph::algo::tarjan_scc;
 use petgraph::graph::{DiGraph, NodeIndex};
-use std::collections::HashMap;
+use petgraph::visit::{Dfs, EdgeFiltered, EdgeRef};
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+
+pub type DependencyGraph = DiGraph<EntityId, Reference>;
+
+/// How one [NamespaceChild] refers to another.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Reference {
+    /// The type of a dto field.
+    Field,
+    /// A param, return or error type of an rpc or a dto method.
+    Rpc,
+}
 
-pub type DependencyGraph = DiGraph<EntityId, ()>;
+/// Dtos that refer to themselves through their fields, e.g. `A -> B -> A`, including through
+/// arrays, maps and optionals. Formats that inline types can't represent these without some form
+/// of indirection. See [Dependencies::cycles].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cycle {
+    /// Each dto in the cycle, in order, starting from the first one in the API. The last refers
+    /// back to the first.
+    pub path: Vec<EntityId>,
+}
 
 /// Tracks all dependencies across the API. Each [NamespaceChild] type is a node in the graph,
 /// and each reference between namespace children are edges.
@@ -13,6 +36,28 @@ pub struct Dependencies {
     node_map: HashMap<EntityId, NodeIndex>,
 }
 
+/// References between namespaces, e.g. to visualize the coupling between parts of the API. See
+/// [Dependencies::namespace_graph].
+///
+/// Namespaces are identified by the dot-separated names of the namespace and its parents, e.g.
+/// `service.user`. The root namespace is empty.
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct NamespaceGraph {
+    /// Every namespace that directly contains a dto, rpc or enum, sorted.
+    pub namespaces: Vec<String>,
+    /// Sorted by `from`, then `to`.
+    pub edges: Vec<NamespaceEdge>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct NamespaceEdge {
+    pub from: String,
+    pub to: String,
+    /// Number of field, param, return and error types within `from` that refer to a type in
+    /// `to`.
+    pub references: usize,
+}
+
 impl Dependencies {
     /// Builds the dependency graph for `api`. Clears any existing data first.
     ///
@@ -49,6 +94,125 @@ impl Dependencies {
             .collect_vec()
     }
 
+    /// Every [NamespaceChild] in the graph, in the order they appear in the API.
+    pub fn entity_ids(&self) -> impl Iterator<Item = &EntityId> {
+        self.graph.node_weights()
+    }
+
+    /// The `roots` and everything they depend on, directly or indirectly.
+    pub fn reachable_from<'d>(
+        &'d self,
+        roots: impl IntoIterator<Item = &'d EntityId>,
+    ) -> HashSet<&'d EntityId> {
+        let mut dfs = Dfs::empty(&self.graph);
+        let mut reachable = HashSet::new();
+        for root in roots {
+            let Some(index) = self.node_map.get(root) else {
+                continue;
+            };
+            dfs.move_to(*index);
+            while let Some(node) = dfs.next(&self.graph) {
+                reachable.insert(&self.graph[node]);
+            }
+        }
+        reachable
+    }
+
+    /// Every cycle of references between dtos through their fields, in the order of the first dto
+    /// of each in the API. Where dtos form several overlapping cycles, only the shortest one
+    /// through the first dto is returned, so fixing every returned cycle may reveal others.
+    pub fn cycles(&self) -> Vec<Cycle> {
+        let fields = EdgeFiltered::from_fn(&self.graph, |edge| *edge.weight() == Reference::Field);
+        let mut cycles = tarjan_scc(&fields)
+            .into_iter()
+            .filter_map(|component| {
+                let start = *component.iter().min()?;
+                let members = component.into_iter().collect::<HashSet<_>>();
+                self.shortest_cycle(start, &members)
+            })
+            .collect_vec();
+        cycles.sort_by_key(|cycle| cycle[0]);
+        cycles
+            .into_iter()
+            .map(|cycle| Cycle {
+                path: cycle
+                    .into_iter()
+                    .map(|node| self.graph[node].clone())
+                    .collect_vec(),
+            })
+            .collect_vec()
+    }
+
+    /// Collapses the graph to one node per namespace, with an edge wherever something in one
+    /// namespace refers to a type in another. References within a namespace are left out.
+    pub fn namespace_graph(&self) -> NamespaceGraph {
+        let namespace = |node: NodeIndex| {
+            self.graph[node]
+                .parent()
+                .unwrap_or_default()
+                .component_names()
+                .join(".")
+        };
+        let namespaces = self
+            .graph
+            .node_indices()
+            .map(namespace)
+            .collect::<BTreeSet<_>>();
+        let mut edges = BTreeMap::<(String, String), usize>::new();
+        for edge in self.graph.edge_references() {
+            let from = namespace(edge.source());
+            let to = namespace(edge.target());
+            if from != to {
+                *edges.entry((from, to)).or_default() += 1;
+            }
+        }
+        NamespaceGraph {
+            namespaces: namespaces.into_iter().collect(),
+            edges: edges
+                .into_iter()
+                .map(|((from, to), references)| NamespaceEdge {
+                    from,
+                    to,
+                    references,
+                })
+                .collect(),
+        }
+    }
+
+    /// The shortest path of field references from `start` back to itself that stays within
+    /// `members`, if any.
+    fn shortest_cycle(
+        &self,
+        start: NodeIndex,
+        members: &HashSet<NodeIndex>,
+    ) -> Option<Vec<NodeIndex>> {
+        let mut previous = HashMap::<NodeIndex, NodeIndex>::new();
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            let fields = self
+                .graph
+                .edges(node)
+                .filter(|edge| *edge.weight() == Reference::Field)
+                .map(|edge| edge.target())
+                .filter(|target| members.contains(target));
+            for target in fields {
+                if target == start {
+                    let mut path = vec![node];
+                    while let Some(prev) = previous.get(path.last().unwrap()) {
+                        path.push(*prev);
+                    }
+                    path.reverse();
+                    return Some(path);
+                }
+                if let Entry::Vacant(entry) = previous.entry(target) {
+                    entry.insert(node);
+                    queue.push_back(target);
+                }
+            }
+        }
+        None
+    }
+
     fn add_nodes_recursively(&mut self, namespace: &Namespace, namespace_id: &EntityId) {
         // unwraps ok here because we're iterating known children.
 
@@ -84,7 +248,16 @@ impl Dependencies {
             let from_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
             let from = *self.node(&from_id).unwrap();
             for field in &dto.fields {
-                self.add_edge(from, namespace_id, &field.ty);
+                self.add_edge(from, namespace_id, &field.ty, Reference::Field);
+            }
+            // Methods aren't nodes themselves, so the dto depends on whatever they use.
+            for rpc in &dto.rpcs {
+                for param in &rpc.params {
+                    self.add_edge(from, namespace_id, &param.ty, Reference::Rpc);
+                }
+                for ty in rpc.return_type.iter().chain(&rpc.error_type) {
+                    self.add_edge(from, namespace_id, ty, Reference::Rpc);
+                }
             }
         }
 
@@ -92,10 +265,10 @@ impl Dependencies {
             let from_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
             let from = *self.node(&from_id).unwrap();
             for param in &rpc.params {
-                self.add_edge(from, namespace_id, &param.ty);
+                self.add_edge(from, namespace_id, &param.ty, Reference::Rpc);
             }
-            if let Some(return_type) = &rpc.return_type {
-                self.add_edge(from, namespace_id, &return_type);
+            for ty in rpc.return_type.iter().chain(&rpc.error_type) {
+                self.add_edge(from, namespace_id, ty, Reference::Rpc);
             }
         }
 
@@ -134,7 +307,13 @@ impl Dependencies {
         None
     }
 
-    fn add_edge(&mut self, from: NodeIndex, namespace_id: &EntityId, ty: &Type) {
+    fn add_edge(
+        &mut self,
+        from: NodeIndex,
+        namespace_id: &EntityId,
+        ty: &Type,
+        reference: Reference,
+    ) {
         match ty {
             Type::Bool
             | Type::U8
@@ -154,15 +333,23 @@ impl Dependencies {
             | Type::F128
             | Type::String
             | Type::Bytes
-            | Type::User(_) => return,
+            | Type::User(_)
+            | Type::Generic(_) => (),
 
-            Type::Api(entity_id) => self.add_edge_relative(from, namespace_id, entity_id),
+            Type::Api(entity_id, args) => {
+                self.add_edge_relative(from, namespace_id, entity_id, reference);
+                for arg in args {
+                    self.add_edge(from, namespace_id, arg, reference);
+                }
+            }
 
-            Type::Array(ty) | Type::Optional(ty) => self.add_edge(from, namespace_id, ty),
+            Type::Array(ty) | Type::Optional(ty) => {
+                self.add_edge(from, namespace_id, ty, reference)
+            }
 
             Type::Map { key, value } => {
-                self.add_edge(from, namespace_id, key);
-                self.add_edge(from, namespace_id, value);
+                self.add_edge(from, namespace_id, key, reference);
+                self.add_edge(from, namespace_id, value, reference);
             }
         }
     }
@@ -172,17 +359,84 @@ impl Dependencies {
         from: NodeIndex,
         namespace_id: &EntityId,
         relative_id: &EntityId,
+        reference: Reference,
     ) {
         // We unwrap nodes here because we assume the api is validated, and all nodes are added first.
         let to = self.node_relative(namespace_id, relative_id).unwrap();
-        self.graph.add_edge(from, *to, ());
+        self.graph.add_edge(from, *to, reference);
+    }
+}
+
+impl NamespaceGraph {
+    /// The graph in the [Graphviz](https://graphviz.org) DOT language, with each edge labeled by
+    /// its number of references.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph api {\n");
+        for namespace in &self.namespaces {
+            dot.push_str(&format!("    \"{}\";\n", label(namespace)));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                label(&edge.from),
+                label(&edge.to),
+                edge.references
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as a [Mermaid](https://mermaid.js.org) flowchart, with each edge labeled by its
+    /// number of references.
+    pub fn to_mermaid(&self) -> String {
+        // Mermaid ids can't contain dots, so refer to namespaces by index.
+        let id = |namespace: &String| {
+            self.namespaces
+                .iter()
+                .position(|n| n == namespace)
+                .unwrap_or_default()
+        };
+        let mut mermaid = String::from("flowchart LR\n");
+        for (i, namespace) in self.namespaces.iter().enumerate() {
+            mermaid.push_str(&format!("    n{}[\"{}\"]\n", i, label(namespace)));
+        }
+        for edge in &self.edges {
+            mermaid.push_str(&format!(
+                "    n{} -->|{}| n{}\n",
+                id(&edge.from),
+                edge.references,
+                id(&edge.to)
+            ));
+        }
+        mermaid
+    }
+}
+
+fn label(namespace: &str) -> &str {
+    if namespace.is_empty() {
+        "(root)"
+    } else {
+        namespace
+    }
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for id in &self.path {
+            write!(f, "{} -> ", id)?;
+        }
+        match self.path.first() {
+            Some(first) => write!(f, "{}", first),
+            None => Ok(()),
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::model::{Api, Dependencies};
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
 
     mod contains_node {
         use crate::model::api::dependencies::tests::run_test;
@@ -351,6 +605,115 @@ mod tests {
         }
     }
 
+    mod reachable_from {
+        use itertools::Itertools;
+
+        use crate::model::api::dependencies::tests::run_test;
+        use crate::model::EntityId;
+
+        #[test]
+        fn transitive() {
+            run_test(
+                r#"
+            struct dto0 { field: Option<dto1> }
+            struct dto1 { field: Vec<en> }
+            struct unused { field: dto0 }
+            enum en {}
+            fn rpc() -> dto0 {}
+            "#,
+                |deps| {
+                    let root = EntityId::try_from("r:rpc").unwrap();
+                    let reachable = deps
+                        .reachable_from([&root])
+                        .into_iter()
+                        .map(ToString::to_string)
+                        .sorted()
+                        .collect_vec();
+                    assert_eq!(
+                        reachable,
+                        vec!["dto:dto0", "dto:dto1", "enum:en", "rpc:rpc"]
+                    );
+                },
+            );
+        }
+
+        #[test]
+        fn unknown_root() {
+            run_test(r#"struct dto {}"#, |deps| {
+                let root = EntityId::try_from("d:missing").unwrap();
+                assert!(deps.reachable_from([&root]).is_empty());
+            });
+        }
+    }
+
+    mod cycles {
+        use itertools::Itertools;
+
+        use crate::model::api::dependencies::tests::run_test;
+
+        fn cycles(data: &str) -> Vec<String> {
+            let mut cycles = vec![];
+            run_test(data, |deps| {
+                cycles = deps.cycles().iter().map(ToString::to_string).collect_vec();
+            });
+            cycles
+        }
+
+        #[test]
+        fn direct() {
+            assert_eq!(
+                cycles("struct dto { child: Option<dto> }"),
+                vec!["dto:dto -> dto:dto"]
+            );
+        }
+
+        #[test]
+        fn indirect_across_namespaces() {
+            assert_eq!(
+                cycles(
+                    r#"
+                struct a { b: Vec<ns::b> }
+                mod ns {
+                    struct b { c: c }
+                    struct c { a: HashMap<String, a> }
+                }
+                "#
+                ),
+                vec!["dto:a -> ns.dto:b -> ns.dto:c -> dto:a"]
+            );
+        }
+
+        #[test]
+        fn shortest_of_overlapping() {
+            assert_eq!(
+                cycles(
+                    r#"
+                struct a { b: b, c: c }
+                struct b { c: c }
+                struct c { a: a }
+                struct d { d: Vec<d> }
+                "#
+                ),
+                vec!["dto:a -> dto:c -> dto:a", "dto:d -> dto:d"]
+            );
+        }
+
+        #[test]
+        fn ignores_rpcs_and_methods() {
+            assert!(cycles(
+                r#"
+                struct a { b: b }
+                struct b {}
+                impl b {
+                    fn get(&self) -> a {}
+                }
+                fn rpc(a: a) -> a {}
+                "#
+            )
+            .is_empty());
+        }
+    }
+
     mod adds_nodes_for_each {
         use crate::model::api::dependencies::tests::run_test;
         use crate::model::EntityId;
@@ -477,6 +840,20 @@ mod tests {
                 },
             );
         }
+
+        #[test]
+        fn rpc_error_types() {
+            run_test(
+                r#"
+            struct dto0 {}
+            struct error {}
+            fn rpc() -> Result<dto0, error> {}
+            "#,
+                |deps| {
+                    assert_eq!(deps.graph.edge_count(), 2);
+                },
+            );
+        }
     }
 
     mod complex_types {
@@ -567,7 +944,7 @@ mod tests {
         assert_eq!(dependencies.graph.node_count(), 0);
     }
 
-    fn run_test<F: Fn(&Dependencies)>(data: &str, f: F) {
+    fn run_test<F: FnOnce(&Dependencies)>(data: &str, f: F) {
         let mut exe = TestExecutor::new(data);
         let model = exe.build();
         let api = model.api;
@@ -1,12 +1,23 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::api::entity::ToEntity;
 use crate::model::entity::{EntityMut, FindEntity};
-use crate::model::{Attributes, Entity, EntityId, EntityType, Field};
+use crate::model::{Attributes, Entity, EntityId, EntityType, Field, Rpc};
 
 /// A single Data Transfer Object (DTO) used in an [Rpc], either directly or nested in another [Dto].
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Dto<'a> {
     pub name: &'a str,
+    /// Names of generic type parameters, e.g. `T` in `struct Page<T>`. Referred to within the
+    /// dto by [crate::model::Type::Generic].
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub type_params: Vec<&'a str>,
+    #[serde(borrow)]
     pub fields: Vec<Field<'a>>,
+    /// Methods of the dto, e.g. from an `impl` block in Rust.
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub rpcs: Vec<Rpc<'a>>,
+    #[serde(borrow)]
     pub attributes: Attributes<'a>,
 }
 
@@ -18,10 +29,18 @@ impl<'a> Dto<'a> {
     pub fn field_mut(&mut self, name: &str) -> Option<&mut Field<'a>> {
         self.fields.iter_mut().find(|field| field.name == name)
     }
+
+    pub fn rpc(&self, name: &str) -> Option<&Rpc<'a>> {
+        self.rpcs.iter().find(|rpc| rpc.name == name)
+    }
+
+    pub fn rpc_mut(&mut self, name: &str) -> Option<&mut Rpc<'a>> {
+        self.rpcs.iter_mut().find(|rpc| rpc.name == name)
+    }
 }
 
 impl ToEntity for Dto<'_> {
-    fn to_entity(&self) -> Entity {
+    fn to_entity(&self) -> Entity<'_, '_> {
         Entity::Dto(self)
     }
 }
@@ -30,12 +49,12 @@ impl<'api> FindEntity<'api> for Dto<'api> {
     fn find_entity<'a>(&'a self, mut id: EntityId) -> Option<Entity<'a, 'api>> {
         if let Some((ty, name)) = id.pop_front() {
             match ty {
-                EntityType::Field => self.field(&name).map_or(None, |x| x.find_entity(id)),
+                EntityType::Field => self.field(&name).and_then(|x| x.find_entity(id)),
+                EntityType::Rpc => self.rpc(&name).and_then(|x| x.find_entity(id)),
 
                 EntityType::None
                 | EntityType::Namespace
                 | EntityType::Dto
-                | EntityType::Rpc
                 | EntityType::Enum
                 | EntityType::Type => None,
             }
@@ -47,14 +66,12 @@ impl<'api> FindEntity<'api> for Dto<'api> {
     fn find_entity_mut<'a>(&'a mut self, mut id: EntityId) -> Option<EntityMut<'a, 'api>> {
         if let Some((ty, name)) = id.pop_front() {
             match ty {
-                EntityType::Field => self
-                    .field_mut(&name)
-                    .map_or(None, |x| x.find_entity_mut(id)),
+                EntityType::Field => self.field_mut(&name).and_then(|x| x.find_entity_mut(id)),
+                EntityType::Rpc => self.rpc_mut(&name).and_then(|x| x.find_entity_mut(id)),
 
                 EntityType::None
                 | EntityType::Namespace
                 | EntityType::Dto
-                | EntityType::Rpc
                 | EntityType::Enum
                 | EntityType::Type => None,
             }
@@ -1,22 +1,27 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::api::entity::ToEntity;
 use crate::model::entity::{EntityMut, FindEntity};
 use crate::model::{Attributes, Entity, EntityId};
 
 /// A single enum type in the within an [Api].
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Enum<'a> {
     pub name: &'a str,
+    #[serde(borrow)]
     pub values: Vec<EnumValue<'a>>,
+    #[serde(borrow)]
     pub attributes: Attributes<'a>,
 }
 
 pub type EnumValueNumber = i64;
 
 /// A single value within an [Enum].
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct EnumValue<'a> {
     pub name: &'a str,
     pub number: EnumValueNumber,
+    #[serde(borrow)]
     pub attributes: Attributes<'a>,
 }
 
@@ -31,7 +36,7 @@ impl<'a> Enum<'a> {
 }
 
 impl ToEntity for Enum<'_> {
-    fn to_entity(&self) -> Entity {
+    fn to_entity(&self) -> Entity<'_, '_> {
         Entity::Enum(self)
     }
 }
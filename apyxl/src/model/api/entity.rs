@@ -1,7 +1,8 @@
-use crate::model::{Dto, EntityId, Enum, Field, Namespace, Rpc, Type};
+use crate::model::{Attributes, Dto, EntityId, Enum, Field, Namespace, Rpc, Type};
 use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone, Serialize, Deserialize)]
 pub enum EntityType {
     None, // Unqualified EntityIds.
     Namespace,
@@ -32,7 +33,7 @@ pub enum EntityMut<'a, 'api> {
     Type(&'a mut Type),
 }
 
-/// Find an entity mutably by qualified [EntityId].
+// Find an entity mutably by qualified [EntityId].
 // pub fn find_entity_mut<'a, 'b>(
 //     ns: &'b mut Namespace<'a>,
 //     mut id: EntityId,
@@ -133,7 +134,7 @@ impl<'api> FindEntity<'api> for EntityMut<'_, 'api> {
 
 pub trait ToEntity {
     /// Create an [Entity] reference to this entity.
-    fn to_entity(&self) -> Entity;
+    fn to_entity(&self) -> Entity<'_, '_>;
 
     fn entity_type(&self) -> EntityType {
         self.to_entity().ty()
@@ -158,6 +159,7 @@ pub mod subtype {
     pub const PARAM_SHORT: &str =     "p";
     pub const TY: &str =              "ty";
     pub const RETURN_TY: &str =       "return_ty";
+    pub const ERROR_TY: &str =        "error_ty";
 
     pub const NAMESPACE_ALL: &[&str] = &[NAMESPACE, NAMESPACE_SHORT];
     pub const DTO_ALL: &[&str] = &[DTO, DTO_SHORT];
@@ -167,6 +169,7 @@ pub mod subtype {
     pub const PARAM_ALL: &[&str] = &[PARAM, PARAM_SHORT];
     pub const TY_ALL: &[&str] = &[TY];
     pub const RETURN_TY_ALL: &[&str] = &[RETURN_TY];
+    pub const ERROR_TY_ALL: &[&str] = &[ERROR_TY];
 }
 
 impl EntityType {
@@ -184,10 +187,9 @@ impl EntityType {
             },
 
             EntityType::Dto => match ty {
-                EntityType::Field => true,
+                EntityType::Field | EntityType::Rpc => true,
                 EntityType::Namespace
                 | EntityType::Dto
-                | EntityType::Rpc
                 | EntityType::Enum
                 | EntityType::Type
                 | EntityType::None => false,
@@ -235,7 +237,7 @@ impl EntityType {
     }
 }
 
-impl Entity<'_, '_> {
+impl<'api> Entity<'_, 'api> {
     pub fn ty(&self) -> EntityType {
         match self {
             Entity::Namespace(_) => EntityType::Namespace,
@@ -246,6 +248,18 @@ impl Entity<'_, '_> {
             Entity::Type(_) => EntityType::Type,
         }
     }
+
+    /// The [Attributes] of the entity. [Type]s don't have any.
+    pub fn attributes(&self) -> Option<&Attributes<'api>> {
+        match self {
+            Entity::Namespace(namespace) => Some(&namespace.attributes),
+            Entity::Dto(dto) => Some(&dto.attributes),
+            Entity::Rpc(rpc) => Some(&rpc.attributes),
+            Entity::Enum(en) => Some(&en.attributes),
+            Entity::Field(field) => Some(&field.attributes),
+            Entity::Type(_) => None,
+        }
+    }
 }
 
 impl TryFrom<&str> for EntityType {
@@ -261,6 +275,7 @@ impl TryFrom<&str> for EntityType {
             _ if subtype::PARAM_ALL.contains(&value) => Ok(EntityType::Field),
             _ if subtype::TY_ALL.contains(&value) => Ok(EntityType::Type),
             _ if subtype::RETURN_TY_ALL.contains(&value) => Ok(EntityType::Type),
+            _ if subtype::ERROR_TY_ALL.contains(&value) => Ok(EntityType::Type),
             _ => Err(anyhow!(
                 "subtype '{}' does not map to a valid EntityType",
                 value
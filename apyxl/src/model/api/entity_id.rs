@@ -2,9 +2,11 @@ use std::cmp::Ordering;
 use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
 use anyhow::{anyhow, Result};
-use itertools::{zip_eq, Itertools};
+use itertools::zip_eq;
+use serde::{Deserialize, Serialize};
 
 use crate::model::api::entity;
 use crate::model::api::entity::EntityType;
@@ -12,7 +14,8 @@ use crate::model::api::entity::EntityType;
 /// An [EntityId] is a unique sequence of components that each define the type and name of an
 /// entity within the API, and together define a path from through the hierarchy to a specific
 /// entity. [EntityId]s are relative to whatever context they are used within, i.e. there is no
-/// such thing as an "absolute path" style [EntityId].
+/// such thing as an "absolute path" style [EntityId]. See [EntityId::resolution_candidates] for
+/// how an [EntityId] referenced from within a namespace is resolved.
 ///
 /// *** Unqualified vs Qualified [EntityId]s ***
 ///
@@ -29,7 +32,8 @@ use crate::model::api::entity::EntityType;
 ///
 /// Each entity within the string is separated by a `.`. Each entity is in the form `subtype:name`,
 /// where `subtype` is used to find the relevant [EntityType], and `name` is the parsed name.
-/// See below for more on `subtypes`.
+/// See below for more on `subtypes`. Names containing `.`, `:` or `\` escape them with a `\`,
+/// e.g. `d:a\.b` is a dto named `a.b`. The empty string is the root, i.e. [EntityId::default].
 ///
 /// In this example:
 ///     `ns:namespace1.ns:namespace2.dto:DtoName.field:field_name.ty`
@@ -55,17 +59,19 @@ use crate::model::api::entity::EntityType;
 ///                                `r`, `rpc`:                [crate::model::Rpc],
 ///                                `e`, `enum`, `en`:         [crate::model::Enum],
 ///     [crate::model::Dto]:       `f`, `field`:              [crate::model::Field],
+///                                `r`, `rpc`:                [crate::model::Rpc] (methods),
 ///     [crate::model::Rpc]:       `p`, `param`:              [crate::model::Field],
 ///                                `return_ty`:               [crate::model::Type] (nameless),
+///                                `error_ty`:                [crate::model::Type] (nameless),
 ///     [crate::model::Field]:     `ty`:                      [crate::model::Type] (nameless),
 ///     [crate::model::Enum]:      <none>
 ///     [crate::model::Type]:      <none>
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct EntityId {
     components: VecDeque<Component>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub struct Component {
     pub ty: EntityType,
     pub name: String,
@@ -153,7 +159,7 @@ impl EntityId {
     /// ```
     pub fn parent(&self) -> Option<Self> {
         let components = &self.components;
-        if components.len() == 0 {
+        if components.is_empty() {
             return None;
         }
         let mut components = components.clone();
@@ -227,6 +233,30 @@ impl EntityId {
         Ok(id)
     }
 
+    /// True if `prefix` is this [EntityId] or one of its ancestors.
+    ///
+    /// Unqualified [EntityId]: Callable. Components without a type match any type.
+    /// ```
+    /// use apyxl::model::EntityId;
+    /// let id = EntityId::try_from("a.b.d:Dto").unwrap();
+    /// assert!(id.starts_with(&EntityId::try_from("a.b").unwrap()));
+    /// assert!(id.starts_with(&EntityId::new_unqualified("a.b.Dto")));
+    /// assert!(!id.starts_with(&EntityId::try_from("a.b.r:Dto").unwrap()));
+    /// ```
+    pub fn starts_with(&self, prefix: &EntityId) -> bool {
+        prefix.len() <= self.len()
+            && prefix
+                .components
+                .iter()
+                .zip(&self.components)
+                .all(|(prefix, component)| {
+                    prefix.name == component.name
+                        && (prefix.ty == component.ty
+                            || prefix.ty == EntityType::None
+                            || component.ty == EntityType::None)
+                })
+    }
+
     /// True if there are namespace entities.
     ///
     /// Unqualified [EntityId]: _Not callable_
@@ -283,6 +313,90 @@ impl EntityId {
         }
     }
 
+    /// Name of the entity this [EntityId] refers to, i.e. of its last component.
+    ///
+    /// Unqualified [EntityId]: Callable.
+    /// ```
+    /// use apyxl::model::EntityId;
+    /// assert_eq!(EntityId::try_from("a.d:Name").unwrap().name(), Some("Name"));
+    /// assert_eq!(EntityId::default().name(), None);
+    /// ```
+    pub fn name(&self) -> Option<&str> {
+        self.components.back().map(|c| c.name.as_str())
+    }
+
+    /// Type of the entity this [EntityId] refers to, i.e. of its last component.
+    ///
+    /// Unqualified [EntityId]: Callable. Returns [EntityType::None].
+    pub fn ty(&self) -> Option<EntityType> {
+        self.components.back().map(|c| c.ty)
+    }
+
+    /// All ancestors of this [EntityId], closest first and ending with the root.
+    ///
+    /// Unqualified [EntityId]: Callable.
+    /// ```
+    /// use apyxl::model::EntityId;
+    /// let id = EntityId::try_from("a.b.d:Name").unwrap();
+    /// assert_eq!(
+    ///     id.ancestors().map(|id| id.to_string()).collect::<Vec<_>>(),
+    ///     vec!["a.b", "a", ""],
+    /// );
+    /// ```
+    pub fn ancestors(&self) -> impl Iterator<Item = EntityId> {
+        std::iter::successors(self.parent(), EntityId::parent)
+    }
+
+    /// This [EntityId] relative to `prefix`, if `prefix` is this [EntityId] or one of its
+    /// ancestors. See [EntityId::starts_with].
+    ///
+    /// Unqualified [EntityId]: Callable.
+    /// ```
+    /// use apyxl::model::EntityId;
+    /// let id = EntityId::try_from("a.b.d:Name").unwrap();
+    /// let relative = id.strip_prefix(&EntityId::try_from("a").unwrap()).unwrap();
+    /// assert_eq!(relative, EntityId::try_from("b.d:Name").unwrap());
+    /// assert_eq!(id.strip_prefix(&EntityId::try_from("b").unwrap()), None);
+    /// ```
+    pub fn strip_prefix(&self, prefix: &EntityId) -> Option<EntityId> {
+        if !self.starts_with(prefix) {
+            return None;
+        }
+        Some(Self {
+            components: self.components.iter().skip(prefix.len()).cloned().collect(),
+        })
+    }
+
+    /// The [EntityId]s that this [EntityId] could refer to when referenced from within the
+    /// namespace `namespace`, in the order they should be looked up: relative to `namespace`
+    /// itself first, then to each of its ancestors, and finally from the root. This is how
+    /// references in parsed source are resolved, e.g. `social.Friend` referenced within
+    /// `service.user` is `service.social.Friend` if there is no `service.user.social.Friend`.
+    ///
+    /// Unqualified [EntityId]: Callable. `namespace` is qualified as namespaces.
+    /// ```
+    /// use apyxl::model::EntityId;
+    /// let id = EntityId::new_unqualified("social.Friend");
+    /// let namespace = EntityId::try_from("service.user").unwrap();
+    /// assert_eq!(
+    ///     id.resolution_candidates(&namespace)
+    ///         .iter()
+    ///         .map(|id| id.to_string())
+    ///         .collect::<Vec<_>>(),
+    ///     vec!["service.user.social.Friend", "service.social.Friend", "social.Friend"],
+    /// );
+    /// ```
+    pub fn resolution_candidates(&self, namespace: &EntityId) -> Vec<EntityId> {
+        let namespace = namespace.to_qualified_namespaces();
+        std::iter::once(namespace.clone())
+            .chain(namespace.ancestors())
+            .map(|mut candidate| {
+                candidate.components.extend(self.components.iter().cloned());
+                candidate
+            })
+            .collect()
+    }
+
     /// Removes and returns the first component of this [EntityId].
     /// ```
     /// use apyxl::model::{EntityId, EntityType};
@@ -325,32 +439,31 @@ impl Display for EntityId {
         let mut path = vec![];
         let mut last_component = Option::<&Component>::None;
         for component in &self.components {
+            let name = escape(&component.name);
             match component.ty {
-                EntityType::None => path.push(component.name.clone()),
-                EntityType::Namespace => path.push(component.name.clone()),
-                EntityType::Dto => {
-                    path.push(format!("{}:{}", entity::subtype::DTO, component.name))
-                }
-                EntityType::Rpc => {
-                    path.push(format!("{}:{}", entity::subtype::RPC, component.name))
-                }
-                EntityType::Enum => {
-                    path.push(format!("{}:{}", entity::subtype::ENUM, component.name))
-                }
-                EntityType::Field => {
-                    path.push(format!("{}:{}", entity::subtype::FIELD, component.name))
-                }
+                EntityType::None => path.push(name),
+                EntityType::Namespace => path.push(name),
+                EntityType::Dto => path.push(format!("{}:{}", entity::subtype::DTO, name)),
+                EntityType::Rpc => path.push(format!("{}:{}", entity::subtype::RPC, name)),
+                EntityType::Enum => path.push(format!("{}:{}", entity::subtype::ENUM, name)),
+                EntityType::Field => path.push(format!("{}:{}", entity::subtype::FIELD, name)),
                 EntityType::Type => match last_component {
                     Some(c) if c.ty == EntityType::Field => {
                         path.push(entity::subtype::TY.to_owned())
                     }
+                    Some(c)
+                        if c.ty == EntityType::Rpc
+                            && entity::subtype::ERROR_TY_ALL.contains(&component.name.as_str()) =>
+                    {
+                        path.push(entity::subtype::ERROR_TY.to_owned())
+                    }
                     Some(c) if c.ty == EntityType::Rpc => {
                         path.push(entity::subtype::RETURN_TY.to_owned())
                     }
                     _ => return Err(std::fmt::Error),
                 },
             }
-            last_component = Some(&component);
+            last_component = Some(component);
         }
         write!(f, "{}", path.join("."))
     }
@@ -368,22 +481,20 @@ impl<S: AsRef<str>> TryFrom<&[S]> for EntityId {
     fn try_from(value: &[S]) -> Result<Self, Self::Error> {
         let mut components = VecDeque::new();
         for s in value.iter().map(AsRef::as_ref) {
-            let split = s.split(":").collect_vec();
+            let split = split_unescaped(s, ':');
             let parent = components.iter().last();
             if split.len() < 2 {
-                let value = split.get(0).unwrap();
+                let value = unescape(split.first().unwrap());
                 // Namespaces are allowed without subtype.
-                if let Ok(c) =
-                    parse_component(entity::subtype::NAMESPACE, value.to_string(), parent)
-                {
+                if let Ok(c) = parse_component(entity::subtype::NAMESPACE, value.clone(), parent) {
                     components.push_back(c);
                     continue;
                 }
                 // "nameless" subtypes are allowed depending on context.
-                components.push_back(parse_component(value, value.to_string(), parent)?);
+                components.push_back(parse_component(&value, value.clone(), parent)?);
             } else if split.len() == 2 {
-                let subtype = split.get(0).unwrap();
-                let name = split.get(1).unwrap().to_string();
+                let subtype = split.first().unwrap();
+                let name = unescape(split.get(1).unwrap());
                 components.push_back(parse_component(subtype, name, parent)?);
             } else {
                 return Err(anyhow!(
@@ -417,8 +528,61 @@ impl TryFrom<&str> for EntityId {
     type Error = anyhow::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        Self::try_from(value.split('.').map(str::to_string).collect_vec())
+        if value.is_empty() {
+            return Ok(Self::default());
+        }
+        Self::try_from(split_unescaped(value, '.'))
+    }
+}
+
+impl FromStr for EntityId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+/// Splits `s` at each `sep` that isn't escaped with a `\`. Escapes are kept in the parts.
+fn split_unescaped(s: &str, sep: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == sep {
+            parts.push(&s[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn unescape(s: &str) -> String {
+    let mut unescaped = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.extend(chars.next()),
+            c => unescaped.push(c),
+        }
+    }
+    unescaped
+}
+
+fn escape(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '.' | ':' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
     }
+    escaped
 }
 
 impl<S: AsRef<str>> TryFrom<&Vec<S>> for EntityId {
@@ -574,6 +738,8 @@ mod tests {
             assert_eq!(dto, EntityId::try_from("a.b.dto:c").unwrap());
             assert_eq!(field, EntityId::try_from("a.b.dto:c.field:d").unwrap());
             assert_eq!(ty, EntityId::try_from("a.b.dto:c.field:d.ty").unwrap());
+            let method = dto.child(EntityType::Rpc, "e").unwrap();
+            assert_eq!(method, EntityId::try_from("a.b.dto:c.rpc:e").unwrap());
         }
 
         #[test]
@@ -588,7 +754,7 @@ mod tests {
                 .is_err());
             assert!(EntityId::try_from("dto:x")
                 .unwrap()
-                .child(EntityType::Rpc, "x")
+                .child(EntityType::Enum, "x")
                 .is_err());
             assert!(EntityId::try_from("dto:x")
                 .unwrap()
@@ -653,10 +819,51 @@ mod tests {
             run_test("a.b.c.r:Name.return_ty", "a.b.c.rpc:Name.return_ty")
         }
 
+        #[test]
+        fn with_error_ty() -> Result<()> {
+            run_test("a.b.c.r:Name.error_ty", "a.b.c.rpc:Name.error_ty")
+        }
+
+        #[test]
+        fn escaped() -> Result<()> {
+            run_test(r"a\.b.d:c\:d\\e", r"a\.b.dto:c\:d\\e")
+        }
+
         fn run_test(from: &str, expected: &str) -> Result<()> {
             let display = format!("{}", EntityId::try_from(from)?);
             assert_eq!(&display, expected);
             Ok(())
         }
     }
+
+    mod escape {
+        use crate::model::{EntityId, EntityType};
+
+        #[test]
+        fn names() {
+            let id = EntityId::try_from(r"a\.b.d:c\:d").unwrap();
+            let expected = EntityId::default()
+                .child(EntityType::Namespace, "a.b")
+                .unwrap()
+                .child(EntityType::Dto, "c:d")
+                .unwrap();
+            assert_eq!(id, expected);
+        }
+
+        #[test]
+        fn round_trip() {
+            let id = EntityId::default()
+                .child(EntityType::Namespace, r"a.b\c")
+                .unwrap()
+                .child(EntityType::Dto, "d:e")
+                .unwrap();
+            assert_eq!(id.to_string().parse::<EntityId>().unwrap(), id);
+        }
+
+        #[test]
+        fn empty_is_root() {
+            assert_eq!(EntityId::try_from("").unwrap(), EntityId::default());
+            assert_eq!(EntityId::default().to_string(), "");
+        }
+    }
 }
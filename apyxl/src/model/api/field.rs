@@ -1,11 +1,14 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::entity::{EntityMut, FindEntity};
 use crate::model::{entity, Attributes, Entity, EntityId, EntityType, Type};
 
 /// A pair of name and type that describe a named instance of a type e.g. within a [Dto] or [Rpc].
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Field<'a> {
     pub name: &'a str,
     pub ty: Type,
+    #[serde(borrow)]
     pub attributes: Attributes<'a>,
 }
 
@@ -1,6 +1,7 @@
 pub use attribute::Attributes;
 pub use attribute::Comment;
-pub use dependencies::Dependencies;
+pub use attribute::CommentStyle;
+pub use dependencies::{Cycle, Dependencies, NamespaceEdge, NamespaceGraph, Reference};
 pub use dto::Dto;
 pub use en::Enum;
 pub use en::EnumValue;
@@ -12,6 +13,7 @@ pub use field::Field;
 pub use namespace::Namespace;
 pub use namespace::NamespaceChild;
 pub use rpc::Rpc;
+pub use service::{service_name, Service, SERVICE_ATTRIBUTE};
 pub use ty::BaseType;
 pub use ty::Type;
 pub use ty::UserTypeName;
@@ -26,6 +28,7 @@ mod entity_id;
 mod field;
 mod namespace;
 mod rpc;
+mod service;
 mod ty;
 pub mod validate;
 
@@ -69,10 +72,7 @@ impl Api<'_> {
                     }
                 }
             }
-            iter = match iter.parent() {
-                None => return None,
-                Some(id) => id,
-            }
+            iter = iter.parent()?
         }
     }
 }
@@ -80,7 +80,7 @@ impl Api<'_> {
 #[cfg(test)]
 mod tests {
     use crate::model::EntityId;
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
 
     #[test]
     fn dto_from_root() {
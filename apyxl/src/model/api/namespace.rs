@@ -2,26 +2,33 @@ use crate::model::api::entity::{Entity, EntityType, ToEntity};
 use crate::model::entity::{EntityMut, FindEntity};
 use crate::model::{Attributes, Dto, EntityId, Enum, Rpc};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
 
 /// A named, nestable wrapper for a set of API entities.
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Namespace<'a> {
     pub name: Cow<'a, str>,
+    #[serde(borrow)]
     pub children: Vec<NamespaceChild<'a>>,
+    #[serde(borrow)]
     pub attributes: Attributes<'a>,
 }
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum NamespaceChild<'a> {
+    #[serde(borrow)]
     Dto(Dto<'a>),
+    #[serde(borrow)]
     Rpc(Rpc<'a>),
+    #[serde(borrow)]
     Enum(Enum<'a>),
+    #[serde(borrow)]
     Namespace(Namespace<'a>),
 }
 
 impl ToEntity for Namespace<'_> {
-    fn to_entity(&self) -> Entity {
+    fn to_entity(&self) -> Entity<'_, '_> {
         Entity::Namespace(self)
     }
 }
@@ -30,10 +37,10 @@ impl<'api> FindEntity<'api> for Namespace<'api> {
     fn find_entity<'a>(&'a self, mut id: EntityId) -> Option<Entity<'a, 'api>> {
         if let Some((ty, name)) = id.pop_front() {
             match ty {
-                EntityType::Namespace => self.namespace(&name).map_or(None, |x| x.find_entity(id)),
-                EntityType::Dto => self.dto(&name).map_or(None, |x| x.find_entity(id)),
-                EntityType::Rpc => self.rpc(&name).map_or(None, |x| x.find_entity(id)),
-                EntityType::Enum => self.en(&name).map_or(None, |x| x.find_entity(id)),
+                EntityType::Namespace => self.namespace(&name).and_then(|x| x.find_entity(id)),
+                EntityType::Dto => self.dto(&name).and_then(|x| x.find_entity(id)),
+                EntityType::Rpc => self.rpc(&name).and_then(|x| x.find_entity(id)),
+                EntityType::Enum => self.en(&name).and_then(|x| x.find_entity(id)),
 
                 EntityType::None | EntityType::Field | EntityType::Type => None,
             }
@@ -47,10 +54,10 @@ impl<'api> FindEntity<'api> for Namespace<'api> {
             match ty {
                 EntityType::Namespace => self
                     .namespace_mut(&name)
-                    .map_or(None, |x| x.find_entity_mut(id)),
-                EntityType::Dto => self.dto_mut(&name).map_or(None, |x| x.find_entity_mut(id)),
-                EntityType::Rpc => self.rpc_mut(&name).map_or(None, |x| x.find_entity_mut(id)),
-                EntityType::Enum => self.en_mut(&name).map_or(None, |x| x.find_entity_mut(id)),
+                    .and_then(|x| x.find_entity_mut(id)),
+                EntityType::Dto => self.dto_mut(&name).and_then(|x| x.find_entity_mut(id)),
+                EntityType::Rpc => self.rpc_mut(&name).and_then(|x| x.find_entity_mut(id)),
+                EntityType::Enum => self.en_mut(&name).and_then(|x| x.find_entity_mut(id)),
 
                 EntityType::None | EntityType::Field | EntityType::Type => None,
             }
@@ -279,70 +286,70 @@ impl<'a> Namespace<'a> {
 
     /// Find a [NamespaceChild] by [EntityId] relative to this [Namespace].
     pub fn find_child(&self, entity_id: &EntityId) -> Option<&NamespaceChild<'a>> {
-        let namespace = self.find_namespace(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.child(&name),
+            (Some(namespace), Some(name)) => namespace.child(name),
             _ => None,
         }
     }
 
     /// Find a [Dto] by [EntityId] relative to this [Namespace].
     pub fn find_dto(&self, entity_id: &EntityId) -> Option<&Dto<'a>> {
-        let namespace = self.find_namespace(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.dto(&name),
+            (Some(namespace), Some(name)) => namespace.dto(name),
             _ => None,
         }
     }
 
     /// Find a [Dto] by [EntityId] relative to this [Namespace].
     pub fn find_dto_mut(&mut self, entity_id: &EntityId) -> Option<&mut Dto<'a>> {
-        let namespace = self.find_namespace_mut(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace_mut(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.dto_mut(&name),
+            (Some(namespace), Some(name)) => namespace.dto_mut(name),
             _ => None,
         }
     }
 
     /// Find a [Rpc] by [EntityId] relative to this [Namespace].
     pub fn find_rpc(&self, entity_id: &EntityId) -> Option<&Rpc<'a>> {
-        let namespace = self.find_namespace(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.rpc(&name),
+            (Some(namespace), Some(name)) => namespace.rpc(name),
             _ => None,
         }
     }
 
     /// Find a [Rpc] by [EntityId] relative to this [Namespace].
     pub fn find_rpc_mut(&mut self, entity_id: &EntityId) -> Option<&mut Rpc<'a>> {
-        let namespace = self.find_namespace_mut(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace_mut(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.rpc_mut(&name),
+            (Some(namespace), Some(name)) => namespace.rpc_mut(name),
             _ => None,
         }
     }
 
     /// Find a [Enum] by [EntityId] relative to this [Namespace].
     pub fn find_enum(&self, entity_id: &EntityId) -> Option<&Enum<'a>> {
-        let namespace = self.find_namespace(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.en(&name),
+            (Some(namespace), Some(name)) => namespace.en(name),
             _ => None,
         }
     }
 
     /// Find a [Enum] by [EntityId] relative to this [Namespace].
     pub fn find_enum_mut(&mut self, entity_id: &EntityId) -> Option<&mut Enum<'a>> {
-        let namespace = self.find_namespace_mut(&unqualified_namespace(&entity_id));
-        let name = unqualified_name(&entity_id);
+        let namespace = self.find_namespace_mut(&unqualified_namespace(entity_id));
+        let name = unqualified_name(entity_id);
         match (namespace, name) {
-            (Some(namespace), Some(name)) => namespace.en_mut(&name),
+            (Some(namespace), Some(name)) => namespace.en_mut(name),
             _ => None,
         }
     }
@@ -390,9 +397,9 @@ impl<'a> Namespace<'a> {
 impl<'a> NamespaceChild<'a> {
     pub fn name(&self) -> &str {
         match self {
-            NamespaceChild::Dto(dto) => &dto.name,
-            NamespaceChild::Rpc(rpc) => &rpc.name,
-            NamespaceChild::Enum(en) => &en.name,
+            NamespaceChild::Dto(dto) => dto.name,
+            NamespaceChild::Rpc(rpc) => rpc.name,
+            NamespaceChild::Enum(en) => en.name,
             NamespaceChild::Namespace(namespace) => &namespace.name,
         }
     }
@@ -421,7 +428,7 @@ impl<'a> NamespaceChild<'a> {
 }
 
 impl ToEntity for NamespaceChild<'_> {
-    fn to_entity(&self) -> Entity {
+    fn to_entity(&self) -> Entity<'_, '_> {
         match self {
             NamespaceChild::Dto(dto) => dto.to_entity(),
             NamespaceChild::Rpc(rpc) => rpc.to_entity(),
@@ -432,7 +439,7 @@ impl ToEntity for NamespaceChild<'_> {
 }
 
 fn unqualified_name(id: &EntityId) -> Option<&str> {
-    if id.len() > 0 {
+    if !id.is_empty() {
         id.component_names().last()
     } else {
         None
@@ -452,8 +459,8 @@ mod tests {
     use std::path::PathBuf;
 
     use crate::model::{chunk, Api, EntityId, Namespace};
-    use crate::test_util::executor::TestExecutor;
     use crate::test_util::{test_dto, test_namespace, test_rpc};
+    use crate::testing::TestExecutor;
 
     #[test]
     fn merge() {
@@ -1,13 +1,28 @@
+use serde::{Deserialize, Serialize};
+
 use crate::model::api::entity::ToEntity;
 use crate::model::entity::{EntityMut, FindEntity};
 use crate::model::{entity, Attributes, Entity, EntityId, EntityType, Field, Type};
 
 /// A single Remote Procedure Call (RPC) within an [Api].
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Rpc<'a> {
     pub name: &'a str,
+    /// Names of generic type parameters, e.g. `T` in `fn get<T>(x: T) -> T`. Referred to within
+    /// the rpc by [crate::model::Type::Generic].
+    #[serde(borrow, default, skip_serializing_if = "Vec::is_empty")]
+    pub type_params: Vec<&'a str>,
+    #[serde(borrow)]
     pub params: Vec<Field<'a>>,
     pub return_type: Option<Type>,
+    /// The type of error the rpc can fail with, e.g. `E` of a Rust `Result<T, E>`, in which case
+    /// the [Rpc::return_type] is `T`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<Type>,
+    /// Whether the rpc is asynchronous, e.g. a Rust `async fn`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_async: bool,
+    #[serde(borrow)]
     pub attributes: Attributes<'a>,
 }
 
@@ -22,7 +37,7 @@ impl<'a> Rpc<'a> {
 }
 
 impl ToEntity for Rpc<'_> {
-    fn to_entity(&self) -> Entity {
+    fn to_entity(&self) -> Entity<'_, '_> {
         Entity::Rpc(self)
     }
 }
@@ -31,11 +46,13 @@ impl<'api> FindEntity<'api> for Rpc<'api> {
     fn find_entity<'a>(&'a self, mut id: EntityId) -> Option<Entity<'a, 'api>> {
         if let Some((ty, name)) = id.pop_front() {
             match ty {
-                EntityType::Field => self.param(&name).map_or(None, |x| x.find_entity(id)),
+                EntityType::Field => self.param(&name).and_then(|x| x.find_entity(id)),
 
                 EntityType::Type => {
                     if entity::subtype::RETURN_TY_ALL.contains(&name.as_str()) {
                         self.return_type.as_ref().map(Entity::Type)
+                    } else if entity::subtype::ERROR_TY_ALL.contains(&name.as_str()) {
+                        self.error_type.as_ref().map(Entity::Type)
                     } else {
                         None
                     }
@@ -55,13 +72,13 @@ impl<'api> FindEntity<'api> for Rpc<'api> {
     fn find_entity_mut<'a>(&'a mut self, mut id: EntityId) -> Option<EntityMut<'a, 'api>> {
         if let Some((ty, name)) = id.pop_front() {
             match ty {
-                EntityType::Field => self
-                    .param_mut(&name)
-                    .map_or(None, |x| x.find_entity_mut(id)),
+                EntityType::Field => self.param_mut(&name).and_then(|x| x.find_entity_mut(id)),
 
                 EntityType::Type => {
                     if entity::subtype::RETURN_TY_ALL.contains(&name.as_str()) {
                         self.return_type.as_mut().map(EntityMut::Type)
+                    } else if entity::subtype::ERROR_TY_ALL.contains(&name.as_str()) {
+                        self.error_type.as_mut().map(EntityMut::Type)
                     } else {
                         None
                     }
@@ -0,0 +1,117 @@
+use crate::model::attribute::User;
+use crate::model::{Namespace, Rpc};
+
+/// Name of the user attribute that assigns an rpc to a [Service], e.g. `#[service(Users)]`.
+pub const SERVICE_ATTRIBUTE: &str = "service";
+
+/// A group of the rpcs within a namespace, so that generators for RPC frameworks can write them
+/// together, e.g. as a gRPC `service`, an OpenAPI tag or a controller, rather than as loose
+/// functions.
+///
+/// Rpcs with a `#[service(Name)]` attribute belong to the service `Name`. The rest belong to the
+/// namespace's default service, which has no name so that generators can name it as fits their
+/// language, e.g. `UserService` for the `user` namespace. Services are in the order of their first
+/// rpc, and rpcs within each service keep their order.
+///
+/// `R` is the rpc type, either [Rpc] from [Namespace::services] or [crate::view::Rpc] from
+/// [crate::view::Namespace::services].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Service<R> {
+    pub name: Option<String>,
+    pub rpcs: Vec<R>,
+}
+
+impl<R> Service<R> {
+    /// Groups `rpcs` by their service, as named by `service_name`, e.g. using [service_name].
+    pub fn group(
+        rpcs: impl IntoIterator<Item = R>,
+        service_name: impl Fn(&R) -> Option<String>,
+    ) -> Vec<Self> {
+        let mut services = Vec::<Self>::new();
+        for rpc in rpcs {
+            let name = service_name(&rpc);
+            match services.iter_mut().find(|service| service.name == name) {
+                Some(service) => service.rpcs.push(rpc),
+                None => services.push(Self {
+                    name,
+                    rpcs: vec![rpc],
+                }),
+            }
+        }
+        services
+    }
+}
+
+/// The name of the service from the first value of the [SERVICE_ATTRIBUTE] in `user`, if any.
+pub fn service_name(user: &[User]) -> Option<String> {
+    user.iter()
+        .find(|attr| attr.name == SERVICE_ATTRIBUTE)?
+        .data
+        .first()
+        .map(|data| data.value.to_string())
+}
+
+impl<'a> Namespace<'a> {
+    /// The rpcs directly within this namespace, grouped by [Service].
+    pub fn services(&self) -> Vec<Service<&Rpc<'a>>> {
+        Service::group(self.rpcs(), |rpc| service_name(&rpc.attributes.user))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::model::Service;
+    use crate::testing::TestExecutor;
+
+    #[test]
+    fn groups_by_attribute() {
+        let mut exe = TestExecutor::new(
+            r#"
+            fn ping() {}
+            #[service(Users)]
+            fn get_user() {}
+            #[service(Orders)]
+            fn get_order() {}
+            fn status() {}
+            #[service(Users)]
+            fn list_users() {}
+            "#,
+        );
+        let model = exe.build();
+        let services = model
+            .api()
+            .services()
+            .into_iter()
+            .map(|service| Service {
+                name: service.name,
+                rpcs: service.rpcs.iter().map(|rpc| rpc.name).collect_vec(),
+            })
+            .collect_vec();
+        assert_eq!(
+            services,
+            vec![
+                Service {
+                    name: None,
+                    rpcs: vec!["ping", "status"],
+                },
+                Service {
+                    name: Some("Users".to_string()),
+                    rpcs: vec!["get_user", "list_users"],
+                },
+                Service {
+                    name: Some("Orders".to_string()),
+                    rpcs: vec!["get_order"],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn empty() {
+        let mut exe = TestExecutor::new("struct dto {}");
+        let model = exe.build();
+        assert!(model.api().services().is_empty());
+    }
+}
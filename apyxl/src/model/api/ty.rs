@@ -1,6 +1,7 @@
 use std::fmt::Debug;
 
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 
 use crate::model::EntityId;
 
@@ -14,7 +15,7 @@ use crate::model::EntityId;
 /// exist after the API is built.
 ///
 /// This is generic so that view::Type can provide relevant view types for variants with data.
-#[derive(Debug, Clone, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub enum BaseType<ApiType, UserTypeName>
 where
     ApiType: Debug + Clone,
@@ -58,8 +59,13 @@ where
     User(UserTypeName),
 
     /// Reference to another type within the API. This must reference an existing type within
-    /// the API when built.
-    Api(ApiType),
+    /// the API when built. The second value is the type arguments of a generic type, e.g. `User`
+    /// in `Page<User>`, or empty.
+    Api(ApiType, Vec<Self>),
+
+    /// A type parameter of the enclosing [crate::model::Dto] or [crate::model::Rpc], e.g. `T`
+    /// in `struct Page<T> { items: Vec<T> }`. See [crate::model::Dto::type_params].
+    Generic(UserTypeName),
 
     /// An array of the contained type.
     Array(Box<Self>),
@@ -79,11 +85,11 @@ pub type Type = BaseType<EntityId, UserTypeName>;
 
 impl Type {
     pub fn new_api(value: &str) -> Result<Self> {
-        Ok(Self::Api(EntityId::try_from(value)?))
+        Ok(Self::Api(EntityId::try_from(value)?, vec![]))
     }
 
     pub fn api(&self) -> Option<&EntityId> {
-        if let Type::Api(id) = self {
+        if let Type::Api(id, _) = self {
             Some(id)
         } else {
             None
@@ -6,7 +6,9 @@ use itertools::Itertools;
 use thiserror::Error;
 
 pub use crate::model::validate::mutation::Mutation;
-use crate::model::{entity, Api, EntityId, EntityType, Field, Type, UNDEFINED_NAMESPACE};
+use crate::model::{
+    entity, Api, EntityId, EntityType, Field, Namespace, Rpc, Type, UNDEFINED_NAMESPACE,
+};
 
 #[derive(Error, Debug, Eq, PartialEq)]
 pub enum ValidationError {
@@ -39,6 +41,9 @@ pub enum ValidationError {
     #[error("Invalid return type for RPC {0}. Type '{1}' must be a valid DTO or enum in the API.")]
     InvalidRpcReturnType(EntityId, EntityId),
 
+    #[error("Invalid error type for RPC {0}. Type '{1}' must be a valid DTO or enum in the API.")]
+    InvalidRpcErrorType(EntityId, EntityId),
+
     #[error("Duplicate DTO or enum definition: '{0}'")]
     DuplicateDtoOrEnum(EntityId),
 
@@ -54,6 +59,30 @@ pub enum ValidationError {
 
 pub type ValidationResult = Result<Option<Mutation>, ValidationError>;
 
+impl ValidationError {
+    /// The most specific entity the error is about, e.g. the field with an invalid type, or the
+    /// parent of an entity with an empty name.
+    pub fn entity_id(&self) -> EntityId {
+        match self {
+            ValidationError::InvalidNamespaceName(id)
+            | ValidationError::InvalidDtoName(id, _)
+            | ValidationError::InvalidRpcName(id, _)
+            | ValidationError::InvalidEnumName(id, _)
+            | ValidationError::InvalidFieldName(id, _)
+            | ValidationError::InvalidEnumValueName(id, _)
+            | ValidationError::InvalidRpcReturnType(id, _)
+            | ValidationError::InvalidRpcErrorType(id, _)
+            | ValidationError::DuplicateDtoOrEnum(id)
+            | ValidationError::DuplicateRpc(id)
+            | ValidationError::DuplicateEnumValue(id, _)
+            | ValidationError::DuplicateFieldName(id, _) => id.clone(),
+            ValidationError::InvalidFieldType(parent_id, name, _, _) => parent_id
+                .child(EntityType::Field, name)
+                .unwrap_or_else(|_| parent_id.clone()),
+        }
+    }
+}
+
 pub fn namespace_names(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
     api.find_namespace(&namespace_id)
         .expect("namespace must exist in api")
@@ -83,25 +112,30 @@ pub fn no_duplicate_dto_enums(api: &Api, namespace_id: EntityId) -> Vec<Validati
         .duplicates()
         .map(|name| {
             Err(ValidationError::DuplicateDtoOrEnum(
-                namespace_id.to_unqualified().child_unqualified(name),
+                EntityId::new_unqualified_vec(namespace_id.component_names().chain([name])),
             ))
         })
         .collect_vec()
 }
 
 pub fn no_duplicate_rpcs(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
-    api.find_namespace(&namespace_id)
-        .expect("namespace must exist in api")
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    let namespace_id = namespace_id.to_qualified_namespaces();
+    let rpcs = namespace
         .rpcs()
         .duplicates_by(|rpc| rpc.name)
-        .map(|rpc| {
-            Err(ValidationError::DuplicateRpc(
-                namespace_id
-                    .to_qualified_namespaces()
-                    .child(EntityType::Rpc, rpc.name)
-                    .unwrap(),
-            ))
-        })
+        .map(|rpc| namespace_id.child(EntityType::Rpc, rpc.name).unwrap());
+    let methods = namespace.dtos().flat_map(|dto| {
+        let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+        dto.rpcs
+            .iter()
+            .duplicates_by(|rpc| rpc.name)
+            .map(move |rpc| dto_id.child(EntityType::Rpc, rpc.name).unwrap())
+    });
+    rpcs.chain(methods)
+        .map(|rpc_id| Err(ValidationError::DuplicateRpc(rpc_id)))
         .collect_vec()
 }
 
@@ -162,28 +196,20 @@ pub fn rpc_names(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
 }
 
 pub fn rpc_param_names(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
-    api.find_namespace(&namespace_id)
-        .expect("namespace must exist in api")
-        .rpcs()
-        .flat_map(|rpc| {
-            field_names(
-                &rpc.params,
-                namespace_id.child(EntityType::Rpc, rpc.name).unwrap(),
-            )
-        })
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    rpcs_with_ids(namespace, &namespace_id)
+        .flat_map(|(rpc_id, rpc)| field_names(&rpc.params, rpc_id))
         .collect_vec()
 }
 
 pub fn rpc_param_names_no_duplicates(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
-    api.find_namespace(&namespace_id)
-        .expect("namespace must exist in api")
-        .rpcs()
-        .flat_map(|rpc| {
-            duplicate_field_names(
-                &rpc.params,
-                namespace_id.child(EntityType::Rpc, rpc.name).unwrap(),
-            )
-        })
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    rpcs_with_ids(namespace, &namespace_id)
+        .flat_map(|(rpc_id, rpc)| duplicate_field_names(&rpc.params, rpc_id))
         .collect_vec()
 }
 
@@ -284,23 +310,21 @@ pub fn dto_field_types(api: &Api, namespace_id: EntityId) -> Vec<ValidationResul
 }
 
 pub fn rpc_param_types(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
-    api.find_namespace(&namespace_id)
-        .expect("namespace must exist in api")
-        .rpcs()
-        .flat_map(|rpc| {
-            let rpc_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
-            field_types(api, &rpc.params, namespace_id.clone(), rpc_id)
-        })
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    rpcs_with_ids(namespace, &namespace_id)
+        .flat_map(|(rpc_id, rpc)| field_types(api, &rpc.params, namespace_id.clone(), rpc_id))
         .collect_vec()
 }
 
 pub fn rpc_return_types(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
-    api.find_namespace(&namespace_id)
-        .expect("namespace must exist in api")
-        .rpcs()
-        .filter_map(|rpc| rpc.return_type.as_ref().map(|ty| (rpc.name, ty)))
-        .map(|(rpc_name, return_type)| {
-            let rpc_id = namespace_id.child(EntityType::Rpc, rpc_name).unwrap();
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    rpcs_with_ids(namespace, &namespace_id)
+        .filter_map(|(rpc_id, rpc)| rpc.return_type.as_ref().map(|ty| (rpc_id, ty)))
+        .map(|(rpc_id, return_type)| {
             let return_ty_id = rpc_id
                 .child(EntityType::Type, entity::subtype::RETURN_TY)
                 .unwrap();
@@ -317,6 +341,29 @@ pub fn rpc_return_types(api: &Api, namespace_id: EntityId) -> Vec<ValidationResu
         .collect_vec()
 }
 
+pub fn rpc_error_types(api: &Api, namespace_id: EntityId) -> Vec<ValidationResult> {
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    rpcs_with_ids(namespace, &namespace_id)
+        .filter_map(|(rpc_id, rpc)| rpc.error_type.as_ref().map(|ty| (rpc_id, ty)))
+        .map(|(rpc_id, error_type)| {
+            let error_ty_id = rpc_id
+                .child(EntityType::Type, entity::subtype::ERROR_TY)
+                .unwrap();
+            match qualify_type(api, &namespace_id, error_type) {
+                Ok(Some(qualified_ty)) => {
+                    Ok(Some(Mutation::new_qualify_type(error_ty_id, qualified_ty)))
+                }
+                Err(err_entity_id) => {
+                    Err(ValidationError::InvalidRpcErrorType(rpc_id, err_entity_id))
+                }
+                _ => Ok(None),
+            }
+        })
+        .collect_vec()
+}
+
 pub fn field_types<'a, 'b: 'a>(
     api: &'b Api<'a>,
     fields: &[Field],
@@ -347,6 +394,25 @@ pub fn field_types<'a, 'b: 'a>(
         .collect_vec()
 }
 
+/// Each rpc within the namespace along with its id, followed by the methods of each dto within
+/// the namespace.
+fn rpcs_with_ids<'n, 'a>(
+    namespace: &'n Namespace<'a>,
+    namespace_id: &'n EntityId,
+) -> impl Iterator<Item = (EntityId, &'n Rpc<'a>)> + 'n {
+    // unwraps ok here because we're iterating known children.
+    let rpcs = namespace
+        .rpcs()
+        .map(|rpc| (namespace_id.child(EntityType::Rpc, rpc.name).unwrap(), rpc));
+    let methods = namespace.dtos().flat_map(|dto| {
+        let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+        dto.rpcs
+            .iter()
+            .map(move |rpc| (dto_id.child(EntityType::Rpc, rpc.name).unwrap(), rpc))
+    });
+    rpcs.chain(methods)
+}
+
 /// Returns a [Type] with all [EntityId]s qualified, recursively. If an [EntityId] does not exist
 /// in the `api`, it returns the [EntityId] which could not be qualified as an error.
 /// If there are no [EntityId]s in the [Type] (i.e. it's all primitives), returns Ok(None).
@@ -357,11 +423,15 @@ fn qualify_type(api: &Api, namespace_id: &EntityId, ty: &Type) -> Result<Option<
     // will wrap the result in its own enum variant so that by the time we reach the top, it has
     // the same structure as the input type `ty`.
     match ty {
-        Type::Api(id) => {
+        Type::Api(id, args) => {
             let qualified_id = api
                 .find_qualified_type_relative(namespace_id, id)
                 .ok_or(id.clone())?;
-            return Ok(Some(Type::Api(qualified_id)));
+            let qualified_args = args
+                .iter()
+                .map(|arg| Ok(qualify_type(api, namespace_id, arg)?.unwrap_or(arg.clone())))
+                .collect::<Result<_, _>>()?;
+            return Ok(Some(Type::Api(qualified_id, qualified_args)));
         }
 
         Type::Array(ty) => {
@@ -407,6 +477,7 @@ fn qualify_type(api: &Api, namespace_id: &EntityId, ty: &Type) -> Result<Option<
         Type::String => {}
         Type::Bytes => {}
         Type::User(_) => {}
+        Type::Generic(_) => {}
     }
     Ok(None)
 }
@@ -458,7 +529,7 @@ mod tests {
 
     use crate::model::validate::rpc_return_types;
     use crate::model::EntityId;
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
 
     #[test]
     fn test_rpc_return_types() {
@@ -501,7 +572,7 @@ mod tests {
     mod qualify_type {
         use crate::model::validate::qualify_type;
         use crate::model::{EntityId, Type};
-        use crate::test_util::executor::TestExecutor;
+        use crate::testing::TestExecutor;
 
         #[test]
         fn primitive() {
@@ -513,11 +584,27 @@ mod tests {
             run_test(
                 "mod ns { struct dto {} }",
                 &EntityId::default(),
-                &Type::Api(EntityId::new_unqualified("ns.dto")),
+                &Type::Api(EntityId::new_unqualified("ns.dto"), vec![]),
                 Some(Type::new_api("ns.d:dto").unwrap()),
             );
         }
 
+        #[test]
+        fn api_args() {
+            run_test(
+                "mod ns { struct dto {} struct Page<T> {} }",
+                &EntityId::try_from("ns").unwrap(),
+                &Type::Api(
+                    EntityId::new_unqualified("Page"),
+                    vec![Type::Api(EntityId::new_unqualified("dto"), vec![])],
+                ),
+                Some(Type::Api(
+                    EntityId::try_from("ns.d:Page").unwrap(),
+                    vec![Type::new_api("ns.d:dto").unwrap()],
+                )),
+            );
+        }
+
         #[test]
         fn array_primitive() {
             run_test(
@@ -533,7 +620,7 @@ mod tests {
             run_test(
                 "mod ns { struct dto {} }",
                 &EntityId::default(),
-                &Type::new_array(Type::Api(EntityId::new_unqualified("ns.dto"))),
+                &Type::new_array(Type::Api(EntityId::new_unqualified("ns.dto"), vec![])),
                 Some(Type::new_array(Type::new_api("ns.d:dto").unwrap())),
             );
         }
@@ -553,7 +640,7 @@ mod tests {
             run_test(
                 "mod ns { struct dto {} }",
                 &EntityId::default(),
-                &Type::new_optional(Type::Api(EntityId::new_unqualified("ns.dto"))),
+                &Type::new_optional(Type::Api(EntityId::new_unqualified("ns.dto"), vec![])),
                 Some(Type::new_optional(Type::new_api("ns.d:dto").unwrap())),
             );
         }
@@ -581,8 +668,8 @@ mod tests {
                 "#,
                 &EntityId::default(),
                 &Type::new_map(
-                    Type::Api(EntityId::new_unqualified("ns0.ns1.en")),
-                    Type::Api(EntityId::new_unqualified("ns0.dto")),
+                    Type::Api(EntityId::new_unqualified("ns0.ns1.en"), vec![]),
+                    Type::Api(EntityId::new_unqualified("ns0.dto"), vec![]),
                 ),
                 Some(Type::new_map(
                     Type::new_api("ns0.ns1.e:en").unwrap(),
@@ -604,8 +691,8 @@ mod tests {
                 "#,
                 &EntityId::default(),
                 &Type::new_array(Type::new_map(
-                    Type::Api(EntityId::new_unqualified("ns0.ns1.en")),
-                    Type::Api(EntityId::new_unqualified("ns0.dto")),
+                    Type::Api(EntityId::new_unqualified("ns0.ns1.en"), vec![]),
+                    Type::Api(EntityId::new_unqualified("ns0.dto"), vec![]),
                 )),
                 Some(Type::new_array(Type::new_map(
                     Type::new_api("ns0.ns1.e:en").unwrap(),
@@ -619,7 +706,19 @@ mod tests {
             run_test_err(
                 "",
                 &EntityId::default(),
-                &Type::Api(EntityId::new_unqualified("dto")),
+                &Type::Api(EntityId::new_unqualified("dto"), vec![]),
+            );
+        }
+
+        #[test]
+        fn error_args() {
+            run_test_err(
+                "struct Page<T> {}",
+                &EntityId::default(),
+                &Type::Api(
+                    EntityId::new_unqualified("Page"),
+                    vec![Type::Api(EntityId::new_unqualified("dto"), vec![])],
+                ),
             );
         }
 
@@ -630,7 +729,7 @@ mod tests {
                 &EntityId::default(),
                 &Type::new_array(Type::new_map(
                     Type::String,
-                    Type::Api(EntityId::new_unqualified("dto")),
+                    Type::Api(EntityId::new_unqualified("dto"), vec![]),
                 )),
             );
         }
@@ -643,21 +742,21 @@ mod tests {
         ) {
             let mut exe = TestExecutor::new(data);
             let api = exe.api();
-            let qualified = qualify_type(&api, namespace_id, &unqualified).unwrap();
+            let qualified = qualify_type(&api, namespace_id, unqualified).unwrap();
             assert_eq!(qualified, expected);
         }
 
         fn run_test_err(data: &str, namespace_id: &EntityId, unqualified: &Type) {
             let mut exe = TestExecutor::new(data);
             let api = exe.api();
-            assert!(qualify_type(&api, namespace_id, &unqualified).is_err());
+            assert!(qualify_type(&api, namespace_id, unqualified).is_err());
         }
     }
 
     mod field_types {
         use crate::model::api::validate::field_types;
         use crate::model::EntityId;
-        use crate::test_util::executor::TestExecutor;
+        use crate::testing::TestExecutor;
 
         #[test]
         fn absolute_path_from_top() {
@@ -73,7 +73,7 @@ mod tests {
         use crate::model::validate::mutation::qualify_type;
         use crate::model::validate::Mutation;
         use crate::model::{EntityId, Type};
-        use crate::test_util::executor::TestExecutor;
+        use crate::testing::TestExecutor;
 
         #[test]
         fn success_changes_type() {
@@ -1,8 +1,14 @@
+use crate::Mode;
+
 #[derive(Debug, Default)]
 pub struct Config {
     /// Prints the API after merging namespaces, but before validation. Useful for debugging
     /// validation.
     pub debug_pre_validate_print: PreValidatePrint,
+
+    /// In [Mode::Lenient], duplicate definitions and unresolved types are fixed up before
+    /// validation and reported as warnings rather than failing the build.
+    pub mode: Mode,
 }
 
 #[derive(Debug, Default)]
@@ -0,0 +1,361 @@
+use crate::model::attribute::Import;
+use crate::model::validate::Mutation;
+use crate::model::{entity, Api, EntityId, EntityType, Field, Rpc, Type};
+
+/// Qualifies every [Type::Api] that refers to an entity through one of the [Import]s of its
+/// namespace, e.g. `social.Friend` with `use crate::service::social;` becomes
+/// `service.social.d:Friend`. Types that don't match any import, or whose imported path can't be
+/// found in the `api`, are left as is for validation to resolve or report.
+pub fn qualify_imported_types(api: &mut Api) {
+    let mut mutations = vec![];
+    collect_imported_types(api, EntityId::default(), &mut mutations);
+    for mutation in mutations {
+        mutation
+            .execute(api)
+            .expect("mutations are only created for types that exist");
+    }
+}
+
+fn collect_imported_types(api: &Api, namespace_id: EntityId, mutations: &mut Vec<Mutation>) {
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+    let imports = &namespace.attributes.imports;
+
+    if !imports.is_empty() {
+        for dto in namespace.dtos() {
+            let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+            for field in &dto.fields {
+                collect_imported_field(api, &namespace_id, imports, &dto_id, field, mutations);
+            }
+            for rpc in &dto.rpcs {
+                let rpc_id = dto_id.child(EntityType::Rpc, rpc.name).unwrap();
+                collect_imported_rpc(api, &namespace_id, imports, &rpc_id, rpc, mutations);
+            }
+        }
+        for rpc in namespace.rpcs() {
+            let rpc_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
+            collect_imported_rpc(api, &namespace_id, imports, &rpc_id, rpc, mutations);
+        }
+    }
+
+    // Like rust modules, child namespaces don't inherit the imports of their parent.
+    for child in namespace.namespaces() {
+        let child_id = namespace_id
+            .child(EntityType::Namespace, &child.name)
+            .unwrap();
+        collect_imported_types(api, child_id, mutations);
+    }
+}
+
+fn collect_imported_rpc(
+    api: &Api,
+    namespace_id: &EntityId,
+    imports: &[Import],
+    rpc_id: &EntityId,
+    rpc: &Rpc,
+    mutations: &mut Vec<Mutation>,
+) {
+    for param in &rpc.params {
+        collect_imported_field(api, namespace_id, imports, rpc_id, param, mutations);
+    }
+    let types = [
+        (entity::subtype::RETURN_TY, &rpc.return_type),
+        (entity::subtype::ERROR_TY, &rpc.error_type),
+    ];
+    for (subtype, ty) in types
+        .into_iter()
+        .filter_map(|(subtype, ty)| ty.as_ref().map(|ty| (subtype, ty)))
+    {
+        if let Some(ty) = qualify_imported(api, namespace_id, imports, ty) {
+            let ty_id = rpc_id.child(EntityType::Type, subtype).unwrap();
+            mutations.push(Mutation::new_qualify_type(ty_id, ty));
+        }
+    }
+}
+
+fn collect_imported_field(
+    api: &Api,
+    namespace_id: &EntityId,
+    imports: &[Import],
+    parent_id: &EntityId,
+    field: &Field,
+    mutations: &mut Vec<Mutation>,
+) {
+    if let Some(ty) = qualify_imported(api, namespace_id, imports, &field.ty) {
+        let ty_id = parent_id
+            .child(EntityType::Field, field.name)
+            .unwrap()
+            .child(EntityType::Type, entity::subtype::TY)
+            .unwrap();
+        mutations.push(Mutation::new_qualify_type(ty_id, ty));
+    }
+}
+
+/// Returns `ty` with every [Type::Api] that refers to an entity through one of the `imports`
+/// qualified, or None if there aren't any.
+fn qualify_imported(
+    api: &Api,
+    namespace_id: &EntityId,
+    imports: &[Import],
+    ty: &Type,
+) -> Option<Type> {
+    match ty {
+        Type::Api(id, args) => {
+            let qualified_id = qualify_imported_id(api, namespace_id, imports, id);
+            let qualified_args = args
+                .iter()
+                .map(|arg| qualify_imported(api, namespace_id, imports, arg))
+                .collect::<Vec<_>>();
+            if qualified_id.is_none() && qualified_args.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Type::Api(
+                qualified_id.unwrap_or(id.clone()),
+                qualified_args
+                    .into_iter()
+                    .zip(args)
+                    .map(|(qualified, arg)| qualified.unwrap_or(arg.clone()))
+                    .collect(),
+            ))
+        }
+        Type::Array(ty) => {
+            qualify_imported(api, namespace_id, imports, ty).map(|ty| Type::Array(Box::new(ty)))
+        }
+        Type::Optional(ty) => {
+            qualify_imported(api, namespace_id, imports, ty).map(|ty| Type::Optional(Box::new(ty)))
+        }
+        Type::Map { key, value } => {
+            let key_ty = qualify_imported(api, namespace_id, imports, key);
+            let value_ty = qualify_imported(api, namespace_id, imports, value);
+            if key_ty.is_some() || value_ty.is_some() {
+                Some(Type::Map {
+                    key: key_ty.map(Box::new).unwrap_or(key.clone()),
+                    value: value_ty.map(Box::new).unwrap_or(value.clone()),
+                })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Named imports take precedence over everything else. Glob imports are only used if `id` can't
+/// be found otherwise, since anything defined in the namespace shadows them.
+fn qualify_imported_id(
+    api: &Api,
+    namespace_id: &EntityId,
+    imports: &[Import],
+    id: &EntityId,
+) -> Option<EntityId> {
+    let names = id.component_names().collect::<Vec<_>>();
+    let (first, rest) = names.split_first()?;
+    let named = imports
+        .iter()
+        .filter(|import| import.name() == Some(*first))
+        .find_map(|import| find_imported(api, namespace_id, import, rest));
+    if named.is_some() {
+        return named;
+    }
+
+    if api.find_qualified_type_relative(namespace_id, id).is_some() {
+        return None;
+    }
+    imports
+        .iter()
+        .filter(|import| import.glob)
+        .find_map(|import| find_imported(api, namespace_id, import, &names))
+}
+
+/// Finds the type at the `import` path followed by `rest`, returning its qualified [EntityId].
+fn find_imported(
+    api: &Api,
+    namespace_id: &EntityId,
+    import: &Import,
+    rest: &[&str],
+) -> Option<EntityId> {
+    let candidates = if import.absolute {
+        vec![import.path.clone()]
+    } else {
+        import.path.resolution_candidates(namespace_id)
+    };
+    candidates.into_iter().find_map(|candidate| {
+        let path =
+            EntityId::new_unqualified_vec(candidate.component_names().chain(rest.iter().copied()));
+        api.find_qualified_type_relative(&EntityId::default(), &path)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::attribute::Import;
+    use crate::model::{Api, EntityId, Type};
+    use crate::testing::TestExecutor;
+
+    use super::qualify_imported_types;
+
+    fn import(path: &str) -> Import {
+        Import {
+            path: EntityId::new_unqualified(path),
+            absolute: true,
+            alias: None,
+            glob: false,
+        }
+    }
+
+    fn field_ty<'a>(api: &'a Api, dto: &str) -> &'a Type {
+        &api.find_dto(&EntityId::new_unqualified(dto))
+            .unwrap()
+            .fields[0]
+            .ty
+    }
+
+    fn assert_qualified(api: &Api, dto: &str, expected: &str) {
+        assert_eq!(
+            field_ty(api, dto),
+            &Type::Api(EntityId::try_from(expected).unwrap(), vec![])
+        );
+    }
+
+    #[test]
+    fn named() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod a {
+                struct dto { field: b::Friend }
+            }
+            mod c {
+                mod b {
+                    struct Friend {}
+                }
+            }
+            "#,
+        );
+        let mut api = exe.api();
+        api.find_namespace_mut(&EntityId::new_unqualified("a"))
+            .unwrap()
+            .attributes
+            .imports
+            .push(import("c.b"));
+        qualify_imported_types(&mut api);
+        assert_qualified(&api, "a.dto", "c.b.d:Friend");
+    }
+
+    #[test]
+    fn alias() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod a {
+                struct dto { field: Buddy }
+            }
+            mod b {
+                struct Friend {}
+            }
+            "#,
+        );
+        let mut api = exe.api();
+        api.find_namespace_mut(&EntityId::new_unqualified("a"))
+            .unwrap()
+            .attributes
+            .imports
+            .push(Import {
+                alias: Some("Buddy".to_string()),
+                ..import("b.Friend")
+            });
+        qualify_imported_types(&mut api);
+        assert_qualified(&api, "a.dto", "b.d:Friend");
+    }
+
+    #[test]
+    fn relative() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod a {
+                mod b {
+                    struct dto { field: Vec<Friend> }
+                }
+                mod social {
+                    struct Friend {}
+                }
+            }
+            "#,
+        );
+        let mut api = exe.api();
+        api.find_namespace_mut(&EntityId::new_unqualified("a.b"))
+            .unwrap()
+            .attributes
+            .imports
+            .push(Import {
+                absolute: false,
+                ..import("social.Friend")
+            });
+        qualify_imported_types(&mut api);
+        assert_eq!(
+            field_ty(&api, "a.b.dto"),
+            &Type::new_array(Type::Api(
+                EntityId::try_from("a.social.d:Friend").unwrap(),
+                vec![]
+            ))
+        );
+    }
+
+    #[test]
+    fn glob_is_shadowed_by_local_types() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod a {
+                struct dto { field: Friend }
+                struct other { field: Local }
+                struct Local {}
+            }
+            mod b {
+                struct Friend {}
+                struct Local {}
+            }
+            "#,
+        );
+        let mut api = exe.api();
+        api.find_namespace_mut(&EntityId::new_unqualified("a"))
+            .unwrap()
+            .attributes
+            .imports
+            .push(Import {
+                glob: true,
+                ..import("b")
+            });
+        qualify_imported_types(&mut api);
+        assert_qualified(&api, "a.dto", "b.d:Friend");
+        assert_eq!(
+            field_ty(&api, "a.other"),
+            &Type::Api(EntityId::new_unqualified("Local"), vec![])
+        );
+    }
+
+    #[test]
+    fn not_inherited_by_child_namespaces() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod a {
+                mod child {
+                    struct dto { field: Friend }
+                }
+            }
+            mod b {
+                struct Friend {}
+            }
+            "#,
+        );
+        let mut api = exe.api();
+        api.find_namespace_mut(&EntityId::new_unqualified("a"))
+            .unwrap()
+            .attributes
+            .imports
+            .push(import("b.Friend"));
+        qualify_imported_types(&mut api);
+        assert_eq!(
+            field_ty(&api, "a.child.dto"),
+            &Type::Api(EntityId::new_unqualified("Friend"), vec![])
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashSet;
+
+use crate::diagnostic::{Diagnostic, Diagnostics};
+use crate::model::validate::Mutation;
+use crate::model::{
+    entity, Api, Attributes, EntityId, EntityType, Field, Namespace, NamespaceChild, Rpc, Type,
+};
+
+/// Removes all but the first of any duplicate definitions within `namespace` and its children,
+/// recursively. Adds a warning to `diagnostics` for each one removed.
+pub fn remove_duplicates(
+    namespace: &mut Namespace,
+    namespace_id: &EntityId,
+    diagnostics: &mut Diagnostics,
+) {
+    // DTOs and enums share a name space since both can be referred to as types.
+    let mut type_names = HashSet::new();
+    let mut rpc_names = HashSet::new();
+    namespace.children.retain(|child| {
+        let (name, attributes, is_first) = match child {
+            NamespaceChild::Dto(dto) => (dto.name, &dto.attributes, type_names.insert(dto.name)),
+            NamespaceChild::Enum(en) => (en.name, &en.attributes, type_names.insert(en.name)),
+            NamespaceChild::Rpc(rpc) => (rpc.name, &rpc.attributes, rpc_names.insert(rpc.name)),
+            NamespaceChild::Namespace(_) => return true,
+        };
+        if !is_first {
+            diagnostics.push(duplicate_warning(namespace_id, name, attributes));
+        }
+        is_first
+    });
+
+    for dto in namespace.dtos_mut() {
+        let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+        retain_first(
+            &mut dto.fields,
+            &dto_id,
+            &dto.attributes,
+            diagnostics,
+            |field| field.name,
+        );
+    }
+    for rpc in namespace.rpcs_mut() {
+        let rpc_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
+        retain_first(
+            &mut rpc.params,
+            &rpc_id,
+            &rpc.attributes,
+            diagnostics,
+            |param| param.name,
+        );
+    }
+    for en in namespace.enums_mut() {
+        let en_id = namespace_id.child(EntityType::Enum, en.name).unwrap();
+        retain_first(
+            &mut en.values,
+            &en_id,
+            &en.attributes,
+            diagnostics,
+            |value| value.name,
+        );
+    }
+    for child in namespace.namespaces_mut() {
+        let child_id = namespace_id
+            .child(EntityType::Namespace, &child.name)
+            .unwrap();
+        remove_duplicates(child, &child_id, diagnostics);
+    }
+}
+
+/// Replaces every [Type::Api] that does not exist in the `api` with a [Type::User] of the same
+/// name, so that generators can still output something for it. Adds a warning to `diagnostics` for
+/// each one replaced.
+pub fn replace_unresolved_types(api: &mut Api, diagnostics: &mut Diagnostics) {
+    let mut mutations = vec![];
+    collect_unresolved_types(api, EntityId::default(), &mut mutations, diagnostics);
+    for mutation in mutations {
+        mutation
+            .execute(api)
+            .expect("mutations are only created for types that exist");
+    }
+}
+
+/// Only entities directly within a namespace have a [crate::model::chunk::Attribute], so the
+/// `parent_attributes` are used to find the chunk of each item.
+fn retain_first<T>(
+    items: &mut Vec<T>,
+    parent_id: &EntityId,
+    parent_attributes: &Attributes,
+    diagnostics: &mut Diagnostics,
+    name: impl Fn(&T) -> &str,
+) {
+    let mut names = HashSet::new();
+    items.retain(|item| {
+        let name = name(item);
+        let is_first = names.insert(name.to_string());
+        if !is_first {
+            diagnostics.push(duplicate_warning(parent_id, name, parent_attributes));
+        }
+        is_first
+    });
+}
+
+fn duplicate_warning(parent_id: &EntityId, name: &str, attributes: &Attributes) -> Diagnostic {
+    Diagnostic::warning(format!(
+        "duplicate definition '{}' ignored",
+        EntityId::new_unqualified_vec(parent_id.component_names().chain([name]))
+    ))
+    .chunk_attr(attributes.chunk.as_ref())
+    .note("only the first definition is kept in lenient mode")
+}
+
+fn collect_unresolved_types(
+    api: &Api,
+    namespace_id: EntityId,
+    mutations: &mut Vec<Mutation>,
+    diagnostics: &mut Diagnostics,
+) {
+    let namespace = api
+        .find_namespace(&namespace_id)
+        .expect("namespace must exist in api");
+
+    for dto in namespace.dtos() {
+        let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+        for field in &dto.fields {
+            collect_unresolved_field(
+                api,
+                &namespace_id,
+                &dto_id,
+                &dto.attributes,
+                field,
+                mutations,
+                diagnostics,
+            );
+        }
+        for rpc in &dto.rpcs {
+            let rpc_id = dto_id.child(EntityType::Rpc, rpc.name).unwrap();
+            collect_unresolved_rpc(api, &namespace_id, &rpc_id, rpc, mutations, diagnostics);
+        }
+    }
+
+    for rpc in namespace.rpcs() {
+        let rpc_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
+        collect_unresolved_rpc(api, &namespace_id, &rpc_id, rpc, mutations, diagnostics);
+    }
+
+    for child in namespace.namespaces() {
+        let child_id = namespace_id
+            .child(EntityType::Namespace, &child.name)
+            .unwrap();
+        collect_unresolved_types(api, child_id, mutations, diagnostics);
+    }
+}
+
+fn collect_unresolved_rpc(
+    api: &Api,
+    namespace_id: &EntityId,
+    rpc_id: &EntityId,
+    rpc: &Rpc,
+    mutations: &mut Vec<Mutation>,
+    diagnostics: &mut Diagnostics,
+) {
+    for param in &rpc.params {
+        collect_unresolved_field(
+            api,
+            namespace_id,
+            rpc_id,
+            &rpc.attributes,
+            param,
+            mutations,
+            diagnostics,
+        );
+    }
+    let types = [
+        (entity::subtype::RETURN_TY, &rpc.return_type),
+        (entity::subtype::ERROR_TY, &rpc.error_type),
+    ];
+    for (subtype, ty) in types
+        .into_iter()
+        .filter_map(|(subtype, ty)| ty.as_ref().map(|ty| (subtype, ty)))
+    {
+        let mut unresolved = vec![];
+        if let Some(ty) = resolve_or_user_type(api, namespace_id, ty, &mut unresolved) {
+            let ty_id = rpc_id.child(EntityType::Type, subtype).unwrap();
+            mutations.push(Mutation::new_qualify_type(ty_id, ty));
+            for id in unresolved {
+                diagnostics.push(unresolved_warning(&id, rpc_id, &rpc.attributes));
+            }
+        }
+    }
+}
+
+fn collect_unresolved_field(
+    api: &Api,
+    namespace_id: &EntityId,
+    parent_id: &EntityId,
+    parent_attributes: &Attributes,
+    field: &Field,
+    mutations: &mut Vec<Mutation>,
+    diagnostics: &mut Diagnostics,
+) {
+    let mut unresolved = vec![];
+    if let Some(ty) = resolve_or_user_type(api, namespace_id, &field.ty, &mut unresolved) {
+        let field_id = parent_id.child(EntityType::Field, field.name).unwrap();
+        let ty_id = field_id
+            .child(EntityType::Type, entity::subtype::TY)
+            .unwrap();
+        mutations.push(Mutation::new_qualify_type(ty_id, ty));
+        for id in unresolved {
+            diagnostics.push(unresolved_warning(&id, &field_id, parent_attributes));
+        }
+    }
+}
+
+fn unresolved_warning(ty_id: &EntityId, user_id: &EntityId, attributes: &Attributes) -> Diagnostic {
+    Diagnostic::warning(format!(
+        "unresolved type '{}' used by '{}'",
+        ty_id,
+        user_id.to_unqualified()
+    ))
+    .chunk_attr(attributes.chunk.as_ref())
+    .note("treated as a user type in lenient mode")
+}
+
+/// Returns `ty` with every [Type::Api] that can't be found in the `api` replaced by a [Type::User],
+/// or None if all of them can be found. Each [EntityId] replaced is added to `unresolved`.
+fn resolve_or_user_type(
+    api: &Api,
+    namespace_id: &EntityId,
+    ty: &Type,
+    unresolved: &mut Vec<EntityId>,
+) -> Option<Type> {
+    match ty {
+        Type::Api(id, args) => {
+            if api.find_qualified_type_relative(namespace_id, id).is_none() {
+                unresolved.push(id.clone());
+                return Some(Type::User(id.to_string()));
+            }
+            let resolved_args = args
+                .iter()
+                .map(|arg| resolve_or_user_type(api, namespace_id, arg, unresolved))
+                .collect::<Vec<_>>();
+            if resolved_args.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Type::Api(
+                id.clone(),
+                resolved_args
+                    .into_iter()
+                    .zip(args)
+                    .map(|(resolved, arg)| resolved.unwrap_or(arg.clone()))
+                    .collect(),
+            ))
+        }
+        Type::Array(ty) => resolve_or_user_type(api, namespace_id, ty, unresolved)
+            .map(|ty| Type::Array(Box::new(ty))),
+        Type::Optional(ty) => resolve_or_user_type(api, namespace_id, ty, unresolved)
+            .map(|ty| Type::Optional(Box::new(ty))),
+        Type::Map { key, value } => {
+            let key_ty = resolve_or_user_type(api, namespace_id, key, unresolved);
+            let value_ty = resolve_or_user_type(api, namespace_id, value, unresolved);
+            if key_ty.is_some() || value_ty.is_some() {
+                Some(Type::Map {
+                    key: key_ty.map(Box::new).unwrap_or(key.clone()),
+                    value: value_ty.map(Box::new).unwrap_or(value.clone()),
+                })
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
@@ -1,18 +1,24 @@
 use std::borrow::Cow;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use itertools::Itertools;
-use log::{debug, error};
+use log::{debug, error, warn};
+use tracing::debug_span;
 
 pub use config::*;
 
+use crate::diagnostic::Diagnostics;
 use crate::model::api::validate;
+use crate::model::entity::{Entity, FindEntity};
 use crate::model::{
     chunk, Api, Chunk, EntityId, Metadata, Model, Namespace, ValidationError, UNDEFINED_NAMESPACE,
 };
-use crate::{generator, output, Generator};
+use crate::{generator, output, Diagnostic, Generator, Mode};
 
 mod config;
+mod imports;
+mod lenient;
 
 /// Helper struct made for parsing [Api]s spread across multiple [Chunk]s. Tracks [Metadata]
 /// associated with entities in the [Api]s.
@@ -24,6 +30,7 @@ pub struct Builder<'a> {
     api: Api<'a>,
     namespace_stack: Vec<String>,
     metadata: Metadata,
+    diagnostics: Diagnostics,
 }
 
 impl Default for Builder<'_> {
@@ -36,15 +43,17 @@ impl Default for Builder<'_> {
             config: Default::default(),
             namespace_stack: Default::default(),
             metadata: Default::default(),
+            diagnostics: Default::default(),
         }
     }
 }
 
 impl<'a> Builder<'a> {
     pub fn with_config(config: Config) -> Self {
-        let mut s = Self::default();
-        s.config = config;
-        s
+        Self {
+            config,
+            ..Default::default()
+        }
     }
 
     pub fn config(&self) -> &Config {
@@ -115,12 +124,37 @@ impl<'a> Builder<'a> {
         self.namespace_stack.clear()
     }
 
-    /// Finalize and validate the model.
-    pub fn build(mut self) -> Result<Model<'a>, Vec<ValidationError>> {
+    /// Finalize and validate the model. Any warnings are logged.
+    pub fn build(self) -> Result<Model<'a>, Vec<ValidationError>> {
+        let (model, diagnostics) = self.build_with_diagnostics();
+        for diagnostic in diagnostics.warnings() {
+            warn!("{}", diagnostic);
+        }
+        model
+    }
+
+    /// Finalize and validate the model. Also returns the warnings added via
+    /// [Builder::diagnostics_mut] and those for anything fixed up in [Mode::Lenient].
+    ///
+    /// If validation fails, the diagnostics also include an error for each [ValidationError],
+    /// pointing at the chunk that defines the entity it's about, e.g. the dto with a field whose
+    /// type doesn't resolve.
+    pub fn build_with_diagnostics(
+        mut self,
+    ) -> (Result<Model<'a>, Vec<ValidationError>>, Diagnostics) {
         dedupe_namespace_children(&mut self.api);
 
+        imports::qualify_imported_types(&mut self.api);
+
+        if self.config.mode == Mode::Lenient {
+            let _span = debug_span!("lenient_fixes").entered();
+            lenient::remove_duplicates(&mut self.api, &EntityId::default(), &mut self.diagnostics);
+            lenient::replace_unresolved_types(&mut self.api, &mut self.diagnostics);
+        }
+
         self.pre_validation_print();
 
+        let _span = debug_span!("validate").entered();
         let (oks, errs): (Vec<_>, Vec<_>) = [
             validate::recurse_api(&self.api, validate::namespace_names),
             validate::recurse_api(&self.api, validate::dto_names),
@@ -132,6 +166,7 @@ impl<'a> Builder<'a> {
             validate::recurse_api(&self.api, validate::rpc_param_names_no_duplicates),
             validate::recurse_api(&self.api, validate::rpc_param_types),
             validate::recurse_api(&self.api, validate::rpc_return_types),
+            validate::recurse_api(&self.api, validate::rpc_error_types),
             validate::recurse_api(&self.api, validate::enum_names),
             validate::recurse_api(&self.api, validate::enum_value_names),
             validate::recurse_api(&self.api, validate::no_duplicate_dto_enums),
@@ -143,14 +178,19 @@ impl<'a> Builder<'a> {
         .partition(|x| x.is_ok());
 
         if !errs.is_empty() {
-            return Err(errs.into_iter().map(Result::unwrap_err).collect_vec());
+            let errs = errs.into_iter().map(Result::unwrap_err).collect_vec();
+            for err in &errs {
+                let diagnostic = error_diagnostic(&self.api, err);
+                self.diagnostics.push(diagnostic);
+            }
+            return (Err(errs), self.diagnostics);
         }
 
-        for mutation in oks.into_iter().map(Result::unwrap).filter_map(|m| m) {
+        for mutation in oks.into_iter().filter_map(Result::unwrap) {
             mutation.execute(&mut self.api).unwrap();
         }
 
-        Ok(Model::new(self.api, self.metadata))
+        (Ok(Model::new(self.api, self.metadata)), self.diagnostics)
     }
 
     pub fn metadata(&self) -> &Metadata {
@@ -161,6 +201,16 @@ impl<'a> Builder<'a> {
         &mut self.metadata
     }
 
+    /// Diagnostics found while building, e.g. syntax skipped by a [crate::Parser] in
+    /// [Mode::Lenient]. Returned by [Builder::build_with_diagnostics].
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    pub fn diagnostics_mut(&mut self) -> &mut Diagnostics {
+        &mut self.diagnostics
+    }
+
     pub fn current_namespace_id(&self) -> EntityId {
         EntityId::new_unqualified_vec(self.namespace_stack.iter())
     }
@@ -176,7 +226,6 @@ impl<'a> Builder<'a> {
             .expect("enter_namespace must always create the namespace if it does not exist, which will guarantee this never fails")
     }
 
-    #[cfg(test)]
     pub fn into_api(self) -> Api<'a> {
         self.api
     }
@@ -190,6 +239,74 @@ impl<'a> Builder<'a> {
     }
 }
 
+/// A [Diagnostic] for `error`, with the chunk of the entity it's about, or of the closest parent
+/// entity that has one. Duplicate definitions also list the chunk of each definition.
+fn error_diagnostic(api: &Api, error: &ValidationError) -> Diagnostic {
+    let duplicates = match error {
+        ValidationError::DuplicateDtoOrEnum(id) => duplicate_chunk_paths(api, id, false),
+        ValidationError::DuplicateRpc(id) => duplicate_chunk_paths(api, id, true),
+        _ => vec![],
+    };
+    if !duplicates.is_empty() {
+        let mut diagnostic = Diagnostic::from(error);
+        diagnostic.chunk = duplicates.first().cloned();
+        for path in &duplicates {
+            diagnostic = diagnostic.note(format!("defined in {}", path.display()));
+        }
+        return diagnostic;
+    }
+
+    let mut entity_id = Some(error.entity_id());
+    while let Some(id) = entity_id {
+        let chunk = api
+            .find_entity(id.clone())
+            .and_then(|entity| entity.attributes().and_then(|attr| attr.chunk.clone()));
+        if chunk.is_some() {
+            return Diagnostic::from(error).chunk_attr(chunk.as_ref());
+        }
+        entity_id = id.parent();
+    }
+    Diagnostic::from(error)
+}
+
+/// Chunk paths of each definition named by `id`: rpcs if `rpcs`, otherwise dtos and enums.
+fn duplicate_chunk_paths(api: &Api, id: &EntityId, rpcs: bool) -> Vec<PathBuf> {
+    let (name, parent_id) = match (id.component_names().last(), id.parent()) {
+        (Some(name), Some(parent_id)) => (name, parent_id.to_qualified_namespaces()),
+        _ => return vec![],
+    };
+    let attributes = match api.find_entity(parent_id) {
+        Some(Entity::Namespace(namespace)) if rpcs => namespace
+            .rpcs()
+            .filter(|rpc| rpc.name == name)
+            .map(|rpc| &rpc.attributes)
+            .collect_vec(),
+        Some(Entity::Namespace(namespace)) => namespace
+            .dtos()
+            .filter(|dto| dto.name == name)
+            .map(|dto| &dto.attributes)
+            .chain(
+                namespace
+                    .enums()
+                    .filter(|en| en.name == name)
+                    .map(|en| &en.attributes),
+            )
+            .collect_vec(),
+        Some(Entity::Dto(dto)) => dto
+            .rpcs
+            .iter()
+            .filter(|rpc| rpc.name == name)
+            .map(|rpc| &rpc.attributes)
+            .collect_vec(),
+        _ => vec![],
+    };
+    attributes
+        .into_iter()
+        .filter_map(|attr| attr.chunk.as_ref()?.relative_file_paths.first().cloned())
+        .collect()
+}
+
+#[allow(clippy::result_large_err)]
 fn dedupe_namespace_children(namespace: &mut Namespace) {
     namespace
         .take_namespaces()
@@ -214,7 +331,7 @@ fn pretty_print_api(api: &Api) {
     let mut output = output::Buffer::default();
     match generator::Rust::default().generate(model.view(), &mut output) {
         Ok(_) => {
-            println!("pre-validation API:\n{}", output.to_string());
+            println!("pre-validation API:\n{}", output);
         }
         Err(err) => error!(
             "error when generating pre-validation API for printing: {}",
@@ -225,8 +342,54 @@ fn pretty_print_api(api: &Api) {
 
 #[cfg(test)]
 mod tests {
-    use crate::model::{Builder, Model, ValidationError};
-    use crate::test_util::executor::TestExecutor;
+    use std::path::PathBuf;
+
+    use crate::model::{Builder, Chunk, Model, ValidationError};
+    use crate::testing::TestExecutor;
+    use crate::{input, parser, Parser};
+
+    #[test]
+    fn duplicate_errors_list_each_chunk() {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::with_relative_file_path("a.rs"), "struct dto {}");
+        input.add_chunk(Chunk::with_relative_file_path("b.rs"), "struct dto {}");
+        let config = parser::Config::default();
+        let mut builder = Builder::default();
+        let options = parser::RustOptions {
+            file_namespaces: false,
+        };
+        parser::Rust::new(options)
+            .parse(&config, &mut input, &mut builder)
+            .unwrap();
+        let (model, diagnostics) = builder.build_with_diagnostics();
+        assert!(model.is_err());
+        let errors = diagnostics.errors().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].chunk, Some(PathBuf::from("a.rs")));
+        assert!(errors[0].notes.contains(&"defined in a.rs".to_string()));
+        assert!(errors[0].notes.contains(&"defined in b.rs".to_string()));
+    }
+
+    #[test]
+    fn validation_errors_point_at_chunk() {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::with_relative_file_path("a.rs"), "struct dto {}");
+        input.add_chunk(
+            Chunk::with_relative_file_path("b.rs"),
+            "struct other { field: Missing }",
+        );
+        let config = parser::Config::default();
+        let mut builder = Builder::default();
+        parser::Rust::default()
+            .parse(&config, &mut input, &mut builder)
+            .unwrap();
+        let (model, diagnostics) = builder.build_with_diagnostics();
+        assert!(model.is_err());
+        let errors = diagnostics.errors().collect::<Vec<_>>();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("Missing"), "{}", errors[0]);
+        assert_eq!(errors[0].chunk, Some(PathBuf::from("b.rs")));
+    }
 
     mod namespace {
         use crate::model::Builder;
@@ -322,7 +485,7 @@ mod tests {
             use crate::model::builder::tests::merge::test_namespace;
             use crate::model::builder::tests::test_builder;
             use crate::model::{Builder, Chunk, EntityId};
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn adds_chunk_metadata_with_current_namespace() {
@@ -334,7 +497,7 @@ mod tests {
                     &Chunk::with_relative_file_path(file_path.clone().unwrap()),
                 );
                 assert_eq!(builder.metadata.chunks.len(), 1);
-                let chunk_metadata = builder.metadata.chunks.get(0).unwrap();
+                let chunk_metadata = builder.metadata.chunks.first().unwrap();
                 assert_eq!(
                     chunk_metadata.root_namespace,
                     EntityId::new_unqualified("blah")
@@ -409,7 +572,7 @@ mod tests {
             ) -> bool {
                 attr.chunk
                     .as_ref()
-                    .map(|attr| attr.relative_file_paths.contains(&file_path))
+                    .map(|attr| attr.relative_file_paths.contains(file_path))
                     .unwrap_or(false)
             }
         }
@@ -528,7 +691,7 @@ mod tests {
     mod build {
         mod dedupe_namespaces {
             use crate::model::builder::tests::build_from_input;
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn within_root() {
@@ -607,7 +770,7 @@ mod tests {
             use crate::model::builder::tests::{assert_contains_error, build_from_input};
             use crate::model::builder::ValidationError;
             use crate::model::EntityId;
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn dtos() {
@@ -698,7 +861,7 @@ mod tests {
             };
             use crate::model::builder::ValidationError;
             use crate::model::EntityId;
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn name_empty() {
@@ -815,7 +978,7 @@ mod tests {
             };
             use crate::model::builder::ValidationError;
             use crate::model::EntityId;
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn name_empty() {
@@ -933,6 +1096,53 @@ mod tests {
                     ),
                 );
             }
+
+            #[test]
+            fn error_type_invalid_linkage() {
+                let mut exe = TestExecutor::new(
+                    r#"
+                    fn rpc() -> Result<(), ns::error> {}
+                    mod ns {
+                        struct definitely_not_error {}
+                    }"#,
+                );
+                let result = build_from_input(&mut exe);
+                assert_contains_error(
+                    &result,
+                    ValidationError::InvalidRpcErrorType(
+                        EntityId::try_from("r:rpc").unwrap(),
+                        EntityId::new_unqualified("ns.error"),
+                    ),
+                );
+            }
+
+            #[test]
+            fn method_types_invalid_linkage() {
+                let mut exe = TestExecutor::new(
+                    r#"
+                    struct dto {}
+                    impl dto {
+                        fn method(&self, param: missing) -> also_missing {}
+                    }"#,
+                );
+                let result = build_from_input(&mut exe);
+                assert_contains_error(
+                    &result,
+                    ValidationError::InvalidFieldType(
+                        EntityId::try_from("d:dto.r:method").unwrap(),
+                        "param".to_string(),
+                        0,
+                        EntityId::new_unqualified("missing"),
+                    ),
+                );
+                assert_contains_error(
+                    &result,
+                    ValidationError::InvalidRpcReturnType(
+                        EntityId::try_from("d:dto.r:method").unwrap(),
+                        EntityId::new_unqualified("also_missing"),
+                    ),
+                );
+            }
         }
 
         mod validate_enum {
@@ -941,7 +1151,7 @@ mod tests {
             };
             use crate::model::builder::ValidationError;
             use crate::model::EntityId;
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn name_empty() {
@@ -1031,7 +1241,7 @@ mod tests {
             use crate::model::builder::tests::{assert_contains_error, build_from_input};
             use crate::model::builder::ValidationError;
             use crate::model::{Builder, EntityId, UNDEFINED_NAMESPACE};
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn root_namespace_undefined_allowed() {
@@ -1085,7 +1295,7 @@ mod tests {
                 };
                 builder.metadata.chunks.push(chunk_metadata.clone());
                 let model = builder.build()?;
-                let actual_chunk_metadata = model.metadata.chunks.get(0).unwrap();
+                let actual_chunk_metadata = model.metadata.chunks.first().unwrap();
                 assert_eq!(
                     actual_chunk_metadata.root_namespace,
                     chunk_metadata.root_namespace
@@ -1098,7 +1308,7 @@ mod tests {
         mod qualifies_types {
             use crate::model::entity::FindEntity;
             use crate::model::{Api, Entity, EntityId};
-            use crate::test_util::executor::TestExecutor;
+            use crate::testing::TestExecutor;
 
             #[test]
             fn dto_field_types() {
@@ -1179,13 +1389,100 @@ mod tests {
                 }
             }
         }
+
+        mod lenient {
+            use crate::model::builder::tests::test_builder;
+            use crate::model::{Builder, Type};
+            use crate::testing::TestExecutor;
+            use crate::Mode;
+
+            fn lenient_builder(exe: &mut TestExecutor) -> Builder<'_> {
+                let mut builder = test_builder(exe);
+                builder.config_mut().mode = Mode::Lenient;
+                builder
+            }
+
+            #[test]
+            fn strict_by_default() {
+                let mut exe = TestExecutor::new("struct dto { field: missing }");
+                assert!(test_builder(&mut exe).build().is_err());
+            }
+
+            #[test]
+            fn unresolved_types_become_user_types() {
+                let mut exe = TestExecutor::new(
+                    r#"
+                    mod ns {
+                        struct known {}
+                        struct dto {
+                            field0: Vec<missing>,
+                            field1: HashMap<known, other::missing>,
+                        }
+                        fn rpc(param: known) -> missing {}
+                    }
+                "#,
+                );
+                let (model, diagnostics) = lenient_builder(&mut exe).build_with_diagnostics();
+                let model = model.unwrap();
+                assert_eq!(diagnostics.warnings().count(), 3);
+
+                let ns = model.api().namespace("ns").unwrap();
+                let dto = ns.dto("dto").unwrap();
+                assert_eq!(
+                    dto.field("field0").unwrap().ty,
+                    Type::new_array(Type::User("missing".to_string()))
+                );
+                match &dto.field("field1").unwrap().ty {
+                    Type::Map { key, value } => {
+                        assert!(key.api().unwrap().is_qualified());
+                        assert_eq!(**value, Type::User("other.missing".to_string()));
+                    }
+                    ty => panic!("unexpected type: {:?}", ty),
+                }
+                assert!(ns.rpc("rpc").unwrap().params[0]
+                    .ty
+                    .api()
+                    .unwrap()
+                    .is_qualified());
+                assert_eq!(
+                    ns.rpc("rpc").unwrap().return_type,
+                    Some(Type::User("missing".to_string()))
+                );
+            }
+
+            #[test]
+            fn keeps_first_of_duplicates() {
+                let mut exe = TestExecutor::new(
+                    r#"
+                    mod ns {
+                        struct dto { field: u32, field: u64 }
+                        enum dto {}
+                        enum en { a, b, a }
+                        fn rpc(param: u32) {}
+                        fn rpc() {}
+                    }
+                "#,
+                );
+                let (model, diagnostics) = lenient_builder(&mut exe).build_with_diagnostics();
+                let model = model.unwrap();
+                assert_eq!(diagnostics.warnings().count(), 4);
+
+                let ns = model.api().namespace("ns").unwrap();
+                assert_eq!(ns.dto("dto").unwrap().fields.len(), 1);
+                assert_eq!(ns.dto("dto").unwrap().fields[0].ty, Type::U32);
+                assert_eq!(ns.enums().count(), 1);
+                assert_eq!(ns.en("en").unwrap().values.len(), 2);
+                assert_eq!(ns.rpcs().count(), 1);
+                assert_eq!(ns.rpc("rpc").unwrap().params.len(), 1);
+            }
+        }
     }
 
-    fn build_from_input(exe: &mut TestExecutor) -> Result<Model, Vec<ValidationError>> {
+    fn build_from_input(exe: &mut TestExecutor) -> Result<Model<'_>, Vec<ValidationError>> {
         test_builder(exe).build()
     }
 
-    fn test_builder(exe: &mut TestExecutor) -> Builder {
+    fn test_builder(exe: &mut TestExecutor) -> Builder<'_> {
         Builder {
             api: exe.api(),
             ..Default::default()
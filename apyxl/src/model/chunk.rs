@@ -1,12 +1,13 @@
 use std::path::PathBuf;
 
 use chumsky::container::Seq;
+use serde::{Deserialize, Serialize};
 
 use crate::model;
 use crate::model::{Attributes, EntityId};
 use crate::view::NamespaceTransform;
 
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Chunk {
     /// Relative path including file name from a common root path shared by the other [Chunk]s from
     /// the [Input]. Typically used by a [crate::Generator] to determine where to put the final file
@@ -22,7 +23,7 @@ impl Chunk {
     }
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct Metadata {
     /// The namespace that all entities within the chunk reside.
     /// Entities will still need to be filtered by the [Attribute] via the [ChunkFilter]
@@ -32,7 +33,7 @@ pub struct Metadata {
     pub chunk: Chunk,
 }
 
-#[derive(Default, Debug, Clone, Eq, PartialEq)]
+#[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 pub struct Attribute {
     /// Some entities (namespaces) can exists in more than one chunk.
     pub relative_file_paths: Vec<PathBuf>,
@@ -86,20 +87,24 @@ mod tests {
 
         #[test]
         fn true_if_chunk_contains_path() {
-            let mut model = model::Attributes::default();
-            model.chunk = Some(Attribute {
-                relative_file_paths: vec![PathBuf::from("a/b/c"), PathBuf::from("d/e/f")],
-            });
+            let model = model::Attributes {
+                chunk: Some(Attribute {
+                    relative_file_paths: vec![PathBuf::from("a/b/c"), PathBuf::from("d/e/f")],
+                }),
+                ..Default::default()
+            };
             assert!(filter_attributes(&model, &PathBuf::from("a/b/c")));
             assert!(filter_attributes(&model, &PathBuf::from("d/e/f")));
         }
 
         #[test]
         fn false_if_chunk_does_not_contain_path() {
-            let mut model = model::Attributes::default();
-            model.chunk = Some(Attribute {
-                relative_file_paths: vec![PathBuf::from("a/b/c"), PathBuf::from("d/e/f")],
-            });
+            let model = model::Attributes {
+                chunk: Some(Attribute {
+                    relative_file_paths: vec![PathBuf::from("a/b/c"), PathBuf::from("d/e/f")],
+                }),
+                ..Default::default()
+            };
             assert!(!filter_attributes(&model, &PathBuf::from("x/y/z")));
         }
     }
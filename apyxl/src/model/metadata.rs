@@ -1,6 +1,14 @@
+use serde::{Deserialize, Serialize};
+
+use crate::config::UserType;
 use crate::model::chunk;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Metadata {
     pub chunks: Vec<chunk::Metadata>,
+
+    /// The [UserType]s from the [crate::parser::Config] the model was parsed with, so that
+    /// [crate::Generator]s can look up their `targets`.
+    pub user_types: Vec<UserType>,
 }
@@ -1,3 +1,7 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::stats::ApiStats;
 use crate::view;
 pub use api::*;
 pub use builder::Builder;
@@ -28,7 +32,8 @@ impl<'a> Model<'a> {
         model
     }
 
-    #[cfg(test)]
+    /// A model without any [Dependencies], e.g. for an [Api] that hasn't been validated yet, since
+    /// building them relies on a valid api.
     pub fn without_deps(api: Api<'a>, metadata: Metadata) -> Self {
         Self {
             api,
@@ -37,7 +42,7 @@ impl<'a> Model<'a> {
         }
     }
 
-    pub fn api(&self) -> &Api {
+    pub fn api(&self) -> &Api<'_> {
         &self.api
     }
 
@@ -49,7 +54,106 @@ impl<'a> Model<'a> {
         &self.dependencies
     }
 
-    pub fn view(&self) -> view::Model {
+    /// Counts of the entities in the [Api], overall and per chunk. See [ApiStats].
+    pub fn stats(&self) -> ApiStats {
+        ApiStats::new(self)
+    }
+
+    pub fn view(&self) -> view::Model<'_, '_> {
         view::Model::new(self)
     }
+
+    /// Serializes the entire model, i.e. the [Api] including all [Attributes] and the [Metadata]
+    /// of each chunk, as pretty-printed JSON. Read back with [Model::from_json].
+    pub fn to_json(&self) -> Result<String> {
+        let json = ModelJson {
+            api: &self.api,
+            metadata: &self.metadata,
+        };
+        serde_json::to_string_pretty(&json).context("serialize model")
+    }
+
+    /// Deserializes a model written by [Model::to_json]. Names within the [Api] borrow from
+    /// `json`. The model isn't validated again, but its [Dependencies] are rebuilt.
+    pub fn from_json(json: &'a str) -> Result<Self> {
+        let json: ModelJson<Api, Metadata> = serde_json::from_str(json).context("parse model")?;
+        Ok(Self::new(json.api, json.metadata))
+    }
+}
+
+/// Serialized form of a [Model]. [Dependencies] aren't included since they're derived from the
+/// [Api].
+#[derive(Serialize, Deserialize)]
+struct ModelJson<A, M> {
+    api: A,
+    #[serde(default)]
+    metadata: M,
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::model::{Builder, Chunk, EntityId, Model};
+    use crate::{input, parser, Parser};
+
+    #[test]
+    fn json_round_trip() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(
+            Chunk::with_relative_file_path("a.rs"),
+            r#"
+            /// A dto.
+            #[flag]
+            pub struct Dto { field: Option<Vec<u32>> }
+            pub fn rpc(dto: Dto) -> Result<Dto, String> {}
+            "#,
+        );
+        let config = parser::Config::default();
+        let mut builder = Builder::default();
+        parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+        let model = builder.build().unwrap();
+
+        let json = model.to_json()?;
+        let round_trip = Model::from_json(&json)?;
+        assert_eq!(round_trip.api(), model.api());
+        assert_eq!(round_trip.to_json()?, json);
+        assert_eq!(
+            round_trip.metadata().chunks[0].chunk,
+            Chunk::with_relative_file_path("a.rs")
+        );
+        assert!(!round_trip
+            .dependencies()
+            .get_for(&EntityId::try_from("a.r:rpc")?)
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn json_round_trip_escaped() -> Result<()> {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(
+            Chunk::with_relative_file_path("a.rs"),
+            r#"
+            /// A "quoted" \ comment.
+            #[api(path = "C:\dir", tag = "a\tb")]
+            pub struct Dto {}
+            "#,
+        );
+        let config = parser::Config::default();
+        let mut builder = Builder::default();
+        parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+        let model = builder.build().unwrap();
+
+        let json = model.to_json()?;
+        let round_trip = Model::from_json(&json)?;
+        assert_eq!(round_trip.api(), model.api());
+        assert_eq!(round_trip.to_json()?, json);
+        Ok(())
+    }
+
+    #[test]
+    fn from_json_invalid() {
+        assert!(Model::from_json("{}").is_err());
+    }
 }
@@ -0,0 +1,216 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, error};
+
+use crate::model::Chunk;
+use crate::output::FileSet;
+use crate::Output;
+
+/// Like [FileSet], creates a file for each [Chunk] using the [Chunk]'s `relative_file_path`, but
+/// all files are first written to a staging directory next to the `output_root`. On
+/// [Output::finish] the staging directory is swapped into place as the new `output_root`, replacing
+/// anything that was there before.
+///
+/// If [Output::finish] is never called (e.g. because a [crate::Generator] failed), the staging
+/// directory is deleted on drop and the `output_root` is left untouched, so a failed run never
+/// leaves a half-generated tree.
+///
+/// The swap uses directory renames, so the staging directory and `output_root` are always on the
+/// same file system.
+///
+/// The guarantee is per `output_root`. With several [AtomicFileSet]s, each is swapped in turn
+/// once every [crate::Generator] has succeeded, so if one swap fails (e.g. a permissions error),
+/// the roots swapped before it keep their new contents and the rest keep their old contents.
+#[derive(Debug)]
+pub struct AtomicFileSet {
+    output_root: PathBuf,
+    staging_root: PathBuf,
+    file_set: Option<FileSet>,
+}
+
+impl AtomicFileSet {
+    pub fn new<P: Into<PathBuf>>(output_root: P) -> Result<Self> {
+        let output_root = output_root.into();
+        if output_root.exists() && !output_root.is_dir() {
+            return Err(anyhow!("specified 'output_root' must be a directory"));
+        }
+        let staging_root = sibling_path(&output_root, "staging")?;
+        if staging_root.exists() {
+            fs::remove_dir_all(&staging_root).context("remove stale staging directory")?;
+        }
+        let file_set = FileSet::new(&staging_root)?;
+        Ok(Self {
+            output_root,
+            staging_root,
+            file_set: Some(file_set),
+        })
+    }
+
+    fn file_set(&mut self) -> Result<&mut FileSet> {
+        self.file_set
+            .as_mut()
+            .ok_or_else(|| anyhow!("AtomicFileSet has already been finished"))
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let backup_root = sibling_path(&self.output_root, "backup")?;
+        if backup_root.exists() {
+            fs::remove_dir_all(&backup_root).context("remove stale backup directory")?;
+        }
+        let has_existing = self.output_root.exists();
+        if has_existing {
+            fs::rename(&self.output_root, &backup_root).context("move output_root to backup")?;
+        }
+        if let Err(err) = fs::rename(&self.staging_root, &self.output_root) {
+            if has_existing {
+                // Roll back.
+                fs::rename(&backup_root, &self.output_root)
+                    .context("restore output_root from backup")?;
+            }
+            return Err(err).context("move staging directory to output_root");
+        }
+        if has_existing {
+            fs::remove_dir_all(&backup_root).context("remove backup directory")?;
+        }
+        debug!("committed {}", self.output_root.display());
+        Ok(())
+    }
+}
+
+impl Output for AtomicFileSet {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.file_set()?.write_chunk(chunk)
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.file_set()?.write_str(data)
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.file_set()?.write(data)
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.file_set()?.newline()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let mut file_set = match self.file_set.take() {
+            None => return Ok(()),
+            Some(file_set) => file_set,
+        };
+        file_set.finish()?;
+        // Close any open files before moving the directory.
+        drop(file_set);
+        self.commit()
+    }
+}
+
+impl Drop for AtomicFileSet {
+    fn drop(&mut self) {
+        if self.file_set.is_none() || !self.staging_root.exists() {
+            return;
+        }
+        // Never finished, discard everything.
+        self.file_set = None;
+        if let Err(err) = fs::remove_dir_all(&self.staging_root) {
+            error!(
+                "failed to remove staging directory '{}': {}",
+                self.staging_root.display(),
+                err
+            );
+        }
+    }
+}
+
+/// Path of a hidden sibling of `path`, e.g. `a/b` -> `a/.b.apyxl-staging-1234`.
+fn sibling_path(path: &Path, kind: &str) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| anyhow!("'output_root' must have a directory name"))?;
+    Ok(path.with_file_name(format!(
+        ".{}.apyxl-{}-{}",
+        name.to_string_lossy(),
+        kind,
+        std::process::id()
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::model::Chunk;
+    use crate::output::AtomicFileSet;
+    use crate::Output;
+
+    #[test]
+    fn nothing_written_before_finish() -> Result<()> {
+        let root = tempdir()?;
+        let output_root = root.path().join("out");
+        let mut output = AtomicFileSet::new(&output_root)?;
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("a")?;
+        assert!(!output_root.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn finish_swaps_into_place() -> Result<()> {
+        let root = tempdir()?;
+        let output_root = root.path().join("out");
+        let mut output = AtomicFileSet::new(&output_root)?;
+        output.write_chunk(&Chunk::with_relative_file_path("a/b"))?;
+        output.write_str("ab")?;
+        output.finish()?;
+        assert_eq!(fs::read_to_string(output_root.join("a/b"))?, "ab");
+        // Only the output_root remains.
+        assert_eq!(fs::read_dir(root.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn finish_replaces_existing() -> Result<()> {
+        let root = tempdir()?;
+        let output_root = root.path().join("out");
+        fs::create_dir(&output_root)?;
+        fs::write(output_root.join("old"), "old")?;
+        let mut output = AtomicFileSet::new(&output_root)?;
+        output.write_chunk(&Chunk::with_relative_file_path("new"))?;
+        output.finish()?;
+        assert!(!output_root.join("old").exists());
+        assert!(output_root.join("new").exists());
+        assert_eq!(fs::read_dir(root.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn drop_without_finish_rolls_back() -> Result<()> {
+        let root = tempdir()?;
+        let output_root = root.path().join("out");
+        fs::create_dir(&output_root)?;
+        fs::write(output_root.join("old"), "old")?;
+        {
+            let mut output = AtomicFileSet::new(&output_root)?;
+            output.write_chunk(&Chunk::with_relative_file_path("new"))?;
+        }
+        assert!(output_root.join("old").exists());
+        assert!(!output_root.join("new").exists());
+        assert_eq!(fs::read_dir(root.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn write_after_finish_errors() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = AtomicFileSet::new(root.path().join("out"))?;
+        output.finish()?;
+        assert!(output.write_str("a").is_err());
+        Ok(())
+    }
+}
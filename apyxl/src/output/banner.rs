@@ -0,0 +1,311 @@
+use std::env;
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use crate::model::Chunk;
+use crate::Output;
+
+pub const DEFAULT_BANNER: &str = "@generated by apyxl — do not edit";
+
+/// Wraps an existing [Output] and writes a banner comment at the start of each [Chunk], e.g.
+/// `// @generated by apyxl — do not edit`.
+///
+/// The comment syntax is chosen per [Chunk] from its `relative_file_path` extension (see
+/// [CommentStyle::from_path]). Chunks without a path, or with an unknown extension, use
+/// [Banner::default_style].
+///
+/// A timestamp can be added with [Banner::with_timestamp]. It is off by default so that generated
+/// output is reproducible.
+#[derive(Debug)]
+pub struct Banner<O: Output> {
+    output: O,
+    text: String,
+    default_style: CommentStyle,
+    timestamp: bool,
+}
+
+/// How to write a single-line comment in a target language.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CommentStyle {
+    /// `// comment`
+    DoubleSlash,
+    /// `# comment`
+    Hash,
+    /// `-- comment`
+    DoubleDash,
+    /// `/* comment */`
+    SlashStar,
+    /// `<!-- comment -->`
+    Xml,
+}
+
+impl<O: Output> Banner<O> {
+    pub fn new(output: O) -> Self {
+        Self {
+            output,
+            text: DEFAULT_BANNER.to_string(),
+            default_style: CommentStyle::DoubleSlash,
+            timestamp: false,
+        }
+    }
+
+    /// Replace the banner text. Multi-line text will be written as one comment per line.
+    pub fn text<S: ToString>(mut self, text: S) -> Self {
+        self.text = text.to_string();
+        self
+    }
+
+    /// [CommentStyle] used when it can't be determined from a [Chunk].
+    pub fn default_style(mut self, style: CommentStyle) -> Self {
+        self.default_style = style;
+        self
+    }
+
+    /// Append the time of generation (UTC) to the banner. If `SOURCE_DATE_EPOCH` is set, it is
+    /// used as the time of generation so that the output is still reproducible.
+    pub fn with_timestamp(mut self, timestamp: bool) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn inner(&self) -> &O {
+        &self.output
+    }
+
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+
+    fn write_banner(&mut self, style: CommentStyle) -> Result<()> {
+        let mut lines = self.text.lines().map(str::to_string).collect::<Vec<_>>();
+        if self.timestamp {
+            lines.push(format!(
+                "generated at {}",
+                utc_timestamp(generation_time(
+                    env::var("SOURCE_DATE_EPOCH").ok().as_deref(),
+                ))
+            ));
+        }
+        for line in lines {
+            let (prefix, suffix) = style.delimiters();
+            self.output.write_str(prefix)?;
+            if !line.is_empty() {
+                self.output.write(' ')?;
+                self.output.write_str(&line)?;
+            }
+            if let Some(suffix) = suffix {
+                self.output.write(' ')?;
+                self.output.write_str(suffix)?;
+            }
+            self.output.newline()?;
+        }
+        self.output.newline()
+    }
+}
+
+impl CommentStyle {
+    /// Picks a [CommentStyle] based on the file extension of `path`, if it is a known extension.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        let style = match extension {
+            "rs" | "ts" | "tsx" | "js" | "jsx" | "go" | "kt" | "kts" | "java" | "swift" | "cs"
+            | "c" | "h" | "cc" | "cpp" | "hpp" | "proto" | "dart" | "scala" => {
+                CommentStyle::DoubleSlash
+            }
+            "py" | "pyi" | "rb" | "sh" | "bash" | "yaml" | "yml" | "toml" | "r" => {
+                CommentStyle::Hash
+            }
+            "sql" | "lua" | "hs" => CommentStyle::DoubleDash,
+            "css" => CommentStyle::SlashStar,
+            "html" | "htm" | "xml" | "md" => CommentStyle::Xml,
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    /// Formats `text` as a single comment line, without a trailing newline.
+    pub fn line(&self, text: &str) -> String {
+        match self.delimiters() {
+            (prefix, None) => format!("{} {}", prefix, text),
+            (prefix, Some(suffix)) => format!("{} {} {}", prefix, text, suffix),
+        }
+    }
+
+    fn delimiters(&self) -> (&'static str, Option<&'static str>) {
+        match self {
+            CommentStyle::DoubleSlash => ("//", None),
+            CommentStyle::Hash => ("#", None),
+            CommentStyle::DoubleDash => ("--", None),
+            CommentStyle::SlashStar => ("/*", Some("*/")),
+            CommentStyle::Xml => ("<!--", Some("-->")),
+        }
+    }
+}
+
+impl<O: Output> Output for Banner<O> {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.output.write_chunk(chunk)?;
+        let style = chunk
+            .relative_file_path
+            .as_ref()
+            .and_then(CommentStyle::from_path)
+            .unwrap_or(self.default_style);
+        self.write_banner(style)
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.output.write_str(data)
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.output.write(data)
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.output.newline()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.output.finish()
+    }
+}
+
+/// `source_date_epoch` (seconds since the unix epoch, see
+/// <https://reproducible-builds.org/specs/source-date-epoch/>) if it is set and valid, otherwise
+/// now.
+fn generation_time(source_date_epoch: Option<&str>) -> SystemTime {
+    source_date_epoch
+        .and_then(|secs| secs.trim().parse::<u64>().ok())
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .unwrap_or_else(SystemTime::now)
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp with second precision, e.g. `2023-06-01T12:00:00Z`.
+fn utc_timestamp(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+
+    // Days since epoch to civil date. See http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use anyhow::Result;
+
+    use crate::model::Chunk;
+    use crate::output::banner::{generation_time, utc_timestamp};
+    use crate::output::{Banner, Buffer, CommentStyle};
+    use crate::Output;
+
+    #[test]
+    fn writes_banner_per_chunk() -> Result<()> {
+        let mut output = Banner::new(Buffer::default());
+        output.write_chunk(&Chunk::with_relative_file_path("a.rs"))?;
+        output.write_str("content")?;
+        output.newline()?;
+        output.write_chunk(&Chunk::with_relative_file_path("b.py"))?;
+        output.write_str("content")?;
+        assert_eq!(
+            output.inner().to_string(),
+            r#"// @generated by apyxl — do not edit
+
+content
+# @generated by apyxl — do not edit
+
+content"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_banner_without_chunk() -> Result<()> {
+        let mut output = Banner::new(Buffer::default());
+        output.write_str("content")?;
+        assert_eq!(output.inner().to_string(), "content");
+        Ok(())
+    }
+
+    #[test]
+    fn custom_multiline_text() -> Result<()> {
+        let mut output = Banner::new(Buffer::default()).text("line 1\n\nline 2");
+        output.write_chunk(&Chunk::with_relative_file_path("a.sql"))?;
+        assert_eq!(output.inner().to_string(), "-- line 1\n--\n-- line 2\n\n");
+        Ok(())
+    }
+
+    #[test]
+    fn block_comment_style() -> Result<()> {
+        let mut output = Banner::new(Buffer::default()).text("x");
+        output.write_chunk(&Chunk::with_relative_file_path("a.html"))?;
+        assert_eq!(output.inner().to_string(), "<!-- x -->\n\n");
+        Ok(())
+    }
+
+    #[test]
+    fn default_style_for_unknown_extension() -> Result<()> {
+        let mut output = Banner::new(Buffer::default())
+            .text("x")
+            .default_style(CommentStyle::Hash);
+        output.write_chunk(&Chunk::with_relative_file_path("a.unknown"))?;
+        output.write_chunk(&Chunk::default())?;
+        assert_eq!(output.inner().to_string(), "# x\n\n# x\n\n");
+        Ok(())
+    }
+
+    #[test]
+    fn timestamp() -> Result<()> {
+        let mut output = Banner::new(Buffer::default())
+            .text("x")
+            .with_timestamp(true);
+        output.write_chunk(&Chunk::with_relative_file_path("a.rs"))?;
+        let data = output.inner().to_string();
+        assert!(data.starts_with("// x\n// generated at "), "{}", data);
+        Ok(())
+    }
+
+    #[test]
+    fn source_date_epoch() {
+        assert_eq!(
+            generation_time(Some("1709210096")),
+            UNIX_EPOCH + Duration::from_secs(1_709_210_096)
+        );
+        assert_ne!(generation_time(Some("invalid")), UNIX_EPOCH);
+        assert_ne!(generation_time(None), UNIX_EPOCH);
+    }
+
+    #[test]
+    fn timestamp_format() {
+        assert_eq!(utc_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            utc_timestamp(UNIX_EPOCH + Duration::from_secs(1_709_210_096)),
+            "2024-02-29T12:34:56Z"
+        );
+    }
+}
@@ -1,15 +1,16 @@
 use crate::model::chunk::Chunk;
 use crate::output::Output;
 use anyhow::Result;
+use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Default)]
 pub struct Buffer {
     data: String,
 }
 
-impl ToString for Buffer {
-    fn to_string(&self) -> String {
-        self.data.clone()
+impl Display for Buffer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.data)
     }
 }
 
@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+use similar::TextDiff;
+
+use crate::model::Chunk;
+use crate::output::incremental_file_set::{is_contained, read_manifest, MANIFEST_FILE_NAME};
+use crate::{Diagnostic, Diagnostics, Output};
+
+/// Generates into memory and, on [Output::finish], compares each file against what is already on
+/// disk within the `output_root` instead of writing it. Intended for CI to verify that generated
+/// code checked into a source tree is up to date. See also [crate::Executor::check].
+///
+/// [Output::finish] fails with a [Diagnostics] error listing every [Difference], if there are any.
+/// Nothing is ever written to disk. Any data written without a [Chunk] is ignored. With
+/// [Check::diff], each [Difference] also comes with a unified diff of the file, e.g. to review
+/// what a run would change before writing it.
+#[derive(Debug, Default)]
+pub struct Check {
+    output_root: PathBuf,
+    prune: bool,
+    diff: bool,
+    diffs: BTreeMap<PathBuf, String>,
+    current: Option<PathBuf>,
+    generated: BTreeMap<PathBuf, String>,
+    differences: Vec<Difference>,
+}
+
+/// A generated file that does not match the file on disk.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Difference {
+    /// Path relative to the `output_root`.
+    pub relative_file_path: PathBuf,
+    pub kind: DifferenceKind,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DifferenceKind {
+    /// The file would be generated but does not exist on disk.
+    Missing,
+    /// The file exists on disk with different content.
+    Changed {
+        /// 1-based line of the first difference.
+        line: usize,
+        generated_lines: usize,
+        existing_lines: usize,
+    },
+    /// The file was generated by a previous run but would no longer be generated. Only reported
+    /// if [Check::prune] is enabled.
+    Stale,
+}
+
+impl Check {
+    pub fn new<P: Into<PathBuf>>(output_root: P) -> Self {
+        Self {
+            output_root: output_root.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Also report files listed in the [crate::output::IncrementalFileSet] manifest that would no
+    /// longer be generated, i.e. those that [crate::output::IncrementalFileSet::prune] would
+    /// delete.
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Also compute a unified diff from the file on disk to the generated file for each file that
+    /// is missing or changed, which is added to its diagnostic. See [Check::unified_diff].
+    pub fn diff(mut self, diff: bool) -> Self {
+        self.diff = diff;
+        self
+    }
+
+    /// The unified diff of the file at `relative_path` found by [Output::finish], if it differs
+    /// and [Check::diff] is enabled.
+    pub fn unified_diff<P: AsRef<Path>>(&self, relative_path: P) -> Option<&str> {
+        self.diffs.get(relative_path.as_ref()).map(String::as_str)
+    }
+
+    /// All [Difference]s found by [Output::finish], sorted by path.
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    fn current_mut(&mut self) -> Option<&mut String> {
+        let path = self.current.as_ref()?;
+        self.generated.get_mut(path)
+    }
+
+    /// The [DifferenceKind] of the generated `data` at `relative_path`, if any, along with the
+    /// existing content on disk.
+    fn compare(
+        &self,
+        relative_path: &Path,
+        data: &str,
+    ) -> Result<Option<(DifferenceKind, String)>> {
+        let path = self.output_root.join(relative_path);
+        if !path.is_file() {
+            return Ok(Some((DifferenceKind::Missing, String::new())));
+        }
+        let existing = fs::read_to_string(&path)?;
+        if existing == data {
+            debug!("up to date: {}", path.display());
+            return Ok(None);
+        }
+        let line = data
+            .lines()
+            .zip(existing.lines())
+            .take_while(|(generated, existing)| generated == existing)
+            .count()
+            + 1;
+        let kind = DifferenceKind::Changed {
+            line,
+            generated_lines: data.lines().count(),
+            existing_lines: existing.lines().count(),
+        };
+        Ok(Some((kind, existing)))
+    }
+
+    fn find_stale(&self) -> Result<Vec<PathBuf>> {
+        let previous = read_manifest(&self.output_root.join(MANIFEST_FILE_NAME))?;
+        Ok(previous
+            .into_iter()
+            .filter(|path| {
+                is_contained(path)
+                    && !self.generated.contains_key(path)
+                    && self.output_root.join(path).is_file()
+            })
+            .collect())
+    }
+
+    fn to_diagnostics(&self) -> Diagnostics {
+        self.differences
+            .iter()
+            .map(|difference| {
+                let path = self.output_root.join(&difference.relative_file_path);
+                let diagnostic =
+                    Diagnostic::error(format!("'{}' {}", path.display(), difference.kind))
+                        .note("generated code is out of date");
+                match self.diffs.get(&difference.relative_file_path) {
+                    None => diagnostic,
+                    Some(diff) => diagnostic.note(diff),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Display for DifferenceKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DifferenceKind::Missing => write!(f, "is missing"),
+            DifferenceKind::Changed {
+                line,
+                generated_lines,
+                existing_lines,
+            } => write!(
+                f,
+                "differs from line {} ({} lines generated, {} lines on disk)",
+                line, generated_lines, existing_lines
+            ),
+            DifferenceKind::Stale => write!(f, "is no longer generated"),
+        }
+    }
+}
+
+impl Output for Check {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        let path = chunk
+            .relative_file_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("all chunks must have file paths when generating to a Check"))?;
+        self.generated.entry(path.clone()).or_default();
+        self.current = Some(path.clone());
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        if let Some(buffer) = self.current_mut() {
+            buffer.push_str(data);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        if let Some(buffer) = self.current_mut() {
+            buffer.push(data);
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write('\n')
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let mut differences = vec![];
+        let mut diffs = BTreeMap::new();
+        for (relative_path, data) in &self.generated {
+            if let Some((kind, existing)) = self.compare(relative_path, data)? {
+                if self.diff {
+                    let path = relative_path.display();
+                    let diff = TextDiff::from_lines(existing.as_str(), data.as_str())
+                        .unified_diff()
+                        .header(&format!("a/{}", path), &format!("b/{}", path))
+                        .to_string();
+                    diffs.insert(relative_path.clone(), diff);
+                }
+                differences.push(Difference {
+                    relative_file_path: relative_path.clone(),
+                    kind,
+                });
+            }
+        }
+        if self.prune {
+            differences.extend(
+                self.find_stale()?
+                    .into_iter()
+                    .map(|relative_path| Difference {
+                        relative_file_path: relative_path,
+                        kind: DifferenceKind::Stale,
+                    }),
+            );
+            differences.sort_by(|a, b| a.relative_file_path.cmp(&b.relative_file_path));
+        }
+        self.differences = differences;
+        self.diffs = diffs;
+        if self.differences.is_empty() {
+            Ok(())
+        } else {
+            Err(self.to_diagnostics().into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::model::Chunk;
+    use crate::output::incremental_file_set::MANIFEST_FILE_NAME;
+    use crate::output::{Check, Difference, DifferenceKind};
+    use crate::{Diagnostics, Output};
+
+    #[test]
+    fn up_to_date() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir(root.path().join("a"))?;
+        fs::write(root.path().join("a/b"), "ab")?;
+        let mut output = Check::new(root.path());
+        generate(&mut output, &[("a/b", "ab")])?;
+        assert!(output.differences().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn reports_differences() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("changed"), "1\n2\nold\n4\n")?;
+        let mut output = Check::new(root.path());
+        let result = generate(&mut output, &[("changed", "1\n2\nnew\n"), ("missing", "")]);
+        assert_eq!(
+            output.differences(),
+            &[
+                Difference {
+                    relative_file_path: PathBuf::from("changed"),
+                    kind: DifferenceKind::Changed {
+                        line: 3,
+                        generated_lines: 3,
+                        existing_lines: 4,
+                    },
+                },
+                Difference {
+                    relative_file_path: PathBuf::from("missing"),
+                    kind: DifferenceKind::Missing,
+                },
+            ]
+        );
+        let err = result.unwrap_err();
+        assert_eq!(Diagnostics::from_error(&err).errors().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn diff() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("changed"), "1\nold\n3\n")?;
+        fs::write(root.path().join("same"), "same\n")?;
+        let mut output = Check::new(root.path()).diff(true);
+        let result = generate(
+            &mut output,
+            &[
+                ("changed", "1\nnew\n3\n"),
+                ("missing", "a\n"),
+                ("same", "same\n"),
+            ],
+        );
+        assert_eq!(
+            output.unified_diff("changed"),
+            Some("--- a/changed\n+++ b/changed\n@@ -1,3 +1,3 @@\n 1\n-old\n+new\n 3\n")
+        );
+        assert_eq!(
+            output.unified_diff("missing"),
+            Some("--- a/missing\n+++ b/missing\n@@ -0,0 +1 @@\n+a\n")
+        );
+        assert_eq!(output.unified_diff("same"), None);
+
+        let err = result.unwrap_err();
+        let diagnostics = Diagnostics::from_error(&err);
+        let changed = diagnostics.errors().next().unwrap();
+        assert!(changed.notes[1].contains("+new"), "{:?}", changed.notes);
+        Ok(())
+    }
+
+    #[test]
+    fn never_writes() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("a"), "old")?;
+        let mut output = Check::new(root.path());
+        assert!(generate(&mut output, &[("a", "new"), ("b", "b")]).is_err());
+        assert_eq!(fs::read_to_string(root.path().join("a"))?, "old");
+        assert!(!root.path().join("b").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn prune_reports_stale_files() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("a"), "a")?;
+        fs::write(root.path().join("stale"), "")?;
+        fs::write(root.path().join(MANIFEST_FILE_NAME), "a\nstale\n")?;
+
+        let mut output = Check::new(root.path());
+        generate(&mut output, &[("a", "a")])?;
+        assert!(output.differences().is_empty());
+
+        let mut output = Check::new(root.path()).prune(true);
+        assert!(generate(&mut output, &[("a", "a")]).is_err());
+        assert_eq!(
+            output.differences(),
+            &[Difference {
+                relative_file_path: PathBuf::from("stale"),
+                kind: DifferenceKind::Stale,
+            }]
+        );
+        Ok(())
+    }
+
+    fn generate(output: &mut Check, files: &[(&str, &str)]) -> Result<()> {
+        for (path, data) in files {
+            output.write_chunk(&Chunk::with_relative_file_path(*path))?;
+            output.write_str(data)?;
+        }
+        output.finish()
+    }
+}
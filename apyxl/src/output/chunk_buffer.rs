@@ -0,0 +1,137 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Stores data in memory separately for each [Chunk], keyed by the [Chunk]'s `relative_file_path`.
+/// Mostly useful for tests that want to assert on individual generated files rather than one
+/// concatenated string.
+///
+/// Data written before the first [Chunk], or for a [Chunk] without a `relative_file_path`, is
+/// available via [ChunkBuffer::unchunked]. Writing the same [Chunk] more than once appends to its
+/// existing data.
+#[derive(Debug, Default)]
+pub struct ChunkBuffer {
+    chunks: BTreeMap<PathBuf, String>,
+    unchunked: String,
+    current: Option<PathBuf>,
+}
+
+impl ChunkBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Data written for the [Chunk] with `relative_file_path`, if any.
+    pub fn get<P: AsRef<Path>>(&self, relative_file_path: P) -> Option<&str> {
+        self.chunks
+            .get(relative_file_path.as_ref())
+            .map(String::as_str)
+    }
+
+    /// Data written without a [Chunk] `relative_file_path`.
+    pub fn unchunked(&self) -> &str {
+        &self.unchunked
+    }
+
+    /// All [Chunk] paths written to, sorted.
+    pub fn paths(&self) -> impl Iterator<Item = &Path> {
+        self.chunks.keys().map(PathBuf::as_path)
+    }
+
+    fn current_mut(&mut self) -> &mut String {
+        match &self.current {
+            None => &mut self.unchunked,
+            Some(path) => self.chunks.entry(path.clone()).or_default(),
+        }
+    }
+}
+
+impl Output for ChunkBuffer {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.current = chunk.relative_file_path.clone();
+        if let Some(path) = &self.current {
+            self.chunks.entry(path.clone()).or_default();
+        }
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.current_mut().push_str(data);
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.current_mut().push(data);
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write('\n')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+
+    use crate::model::Chunk;
+    use crate::output::ChunkBuffer;
+    use crate::Output;
+
+    #[test]
+    fn get_by_chunk_path() -> Result<()> {
+        let mut output = ChunkBuffer::new();
+        output.write_chunk(&Chunk::with_relative_file_path("a/b.rs"))?;
+        output.write_str("ab")?;
+        output.write_chunk(&Chunk::with_relative_file_path("c.rs"))?;
+        output.write('c')?;
+        output.newline()?;
+        assert_eq!(output.get("a/b.rs"), Some("ab"));
+        assert_eq!(output.get("c.rs"), Some("c\n"));
+        assert_eq!(output.get("d.rs"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn empty_chunk_exists() -> Result<()> {
+        let mut output = ChunkBuffer::new();
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        assert_eq!(output.get("a"), Some(""));
+        Ok(())
+    }
+
+    #[test]
+    fn unchunked() -> Result<()> {
+        let mut output = ChunkBuffer::new();
+        output.write_str("x")?;
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("a")?;
+        output.write_chunk(&Chunk::default())?;
+        output.write_str("y")?;
+        assert_eq!(output.unchunked(), "xy");
+        assert_eq!(output.get("a"), Some("a"));
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_chunk_appends() -> Result<()> {
+        let mut output = ChunkBuffer::new();
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("1")?;
+        output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("2")?;
+        assert_eq!(output.get("a"), Some("12"));
+        assert_eq!(
+            output.paths().collect::<Vec<_>>(),
+            vec![Path::new("a"), Path::new("b")]
+        );
+        Ok(())
+    }
+}
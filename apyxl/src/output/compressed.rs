@@ -0,0 +1,264 @@
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Like [crate::output::FileSet], creates a file for each [Chunk] within the `output_root` using
+/// the [Chunk]'s `relative_file_path`, but each file is gzip-compressed and has `.gz` appended to
+/// its name.
+///
+/// Any data written without a [Chunk] is ignored.
+#[derive(Debug)]
+pub struct GzFileSet {
+    output_root: PathBuf,
+    current: Option<GzEncoder<File>>,
+}
+
+impl GzFileSet {
+    pub fn new<P: Into<PathBuf>>(output_root: P) -> Result<Self> {
+        let output_root = output_root.into();
+        fs::create_dir_all(&output_root)?;
+        let dir_metadata = fs::metadata(&output_root).context("output_root")?;
+        if !dir_metadata.is_dir() {
+            return Err(anyhow!("specified 'output_root' must be a directory"));
+        }
+        if fs::read_dir(&output_root)?.count() > 0 {
+            return Err(anyhow!("specified 'output_root' must be empty"));
+        }
+        Ok(Self {
+            output_root,
+            current: None,
+        })
+    }
+
+    fn finish_current(&mut self) -> Result<()> {
+        if let Some(encoder) = self.current.take() {
+            encoder.finish()?;
+        }
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<()> {
+        if let Some(encoder) = &mut self.current {
+            encoder.write_all(bytes)?;
+        }
+        Ok(())
+    }
+}
+
+impl Output for GzFileSet {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        let path = chunk.relative_file_path.as_ref().ok_or_else(|| {
+            anyhow!("all chunks must have file paths when generating to a GzFileSet")
+        })?;
+        self.finish_current()?;
+        let path = self.output_root.join(with_gz_extension(path));
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        self.current = Some(GzEncoder::new(File::create(path)?, Compression::default()));
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.write_bytes(data.as_bytes())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.write_bytes(data.encode_utf8(&mut [0; 4]).as_bytes())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write_bytes(b"\n")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.finish_current()
+    }
+}
+
+fn with_gz_extension(path: &Path) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(".gz");
+    PathBuf::from(path)
+}
+
+/// Writes a single gzip-compressed tar archive containing a file for each [Chunk], using the
+/// [Chunk]'s `relative_file_path` as the path within the archive. Useful for docs bundles or for
+/// attaching generated SDKs to releases.
+///
+/// Entries have a fixed modification time and permissions so that archives are reproducible. The
+/// archive is only complete once [Output::finish] has been called.
+///
+/// Any data written without a [Chunk] is ignored.
+pub struct TarGz {
+    archive_path: PathBuf,
+    builder: Option<tar::Builder<GzEncoder<File>>>,
+    current: Option<(PathBuf, Vec<u8>)>,
+}
+
+impl TarGz {
+    pub fn new<P: Into<PathBuf>>(archive_path: P) -> Result<Self> {
+        let archive_path = archive_path.into();
+        if let Some(parent) = archive_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::create(&archive_path).context("create archive")?;
+        let builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        Ok(Self {
+            archive_path,
+            builder: Some(builder),
+            current: None,
+        })
+    }
+
+    fn append_current(&mut self) -> Result<()> {
+        let (path, data) = match self.current.take() {
+            None => return Ok(()),
+            Some(current) => current,
+        };
+        let builder = self
+            .builder
+            .as_mut()
+            .ok_or_else(|| anyhow!("TarGz has already been finished"))?;
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(0);
+        header.set_cksum();
+        builder.append_data(&mut header, &path, data.as_slice())?;
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, bytes: &[u8]) {
+        if let Some((_, data)) = &mut self.current {
+            data.extend_from_slice(bytes);
+        }
+    }
+}
+
+impl Debug for TarGz {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TarGz")
+            .field("archive_path", &self.archive_path)
+            .finish()
+    }
+}
+
+impl Output for TarGz {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        let path = chunk
+            .relative_file_path
+            .as_ref()
+            .ok_or_else(|| anyhow!("all chunks must have file paths when generating to a TarGz"))?;
+        self.append_current()?;
+        self.current = Some((path.clone(), vec![]));
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.write_bytes(data.as_bytes());
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.write_bytes(data.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write_bytes(b"\n");
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.append_current()?;
+        if let Some(builder) = self.builder.take() {
+            builder.into_inner()?.finish()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::Read;
+    use std::path::Path;
+
+    use anyhow::Result;
+    use flate2::read::GzDecoder;
+    use tempfile::tempdir;
+
+    use crate::model::Chunk;
+    use crate::output::{GzFileSet, TarGz};
+    use crate::Output;
+
+    #[test]
+    fn gz_file_set_compresses_each_chunk() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = GzFileSet::new(root.path())?;
+        output.write_chunk(&Chunk::with_relative_file_path("a/b.rs"))?;
+        output.write_str("ab")?;
+        output.write_chunk(&Chunk::with_relative_file_path("c.rs"))?;
+        output.write('é')?;
+        output.finish()?;
+        assert_eq!(read_gz(&root.path().join("a/b.rs.gz"))?, "ab");
+        assert_eq!(read_gz(&root.path().join("c.rs.gz"))?, "é");
+        Ok(())
+    }
+
+    #[test]
+    fn gz_file_set_chunk_without_path_errors() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = GzFileSet::new(root.path())?;
+        assert!(output.write_chunk(&Chunk::default()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn tar_gz_contains_each_chunk() -> Result<()> {
+        let root = tempdir()?;
+        let archive_path = root.path().join("out.tar.gz");
+        let mut output = TarGz::new(&archive_path)?;
+        output.write_str("ignored")?;
+        output.write_chunk(&Chunk::with_relative_file_path("a/b.rs"))?;
+        output.write_str("ab")?;
+        output.write_chunk(&Chunk::with_relative_file_path("c.rs"))?;
+        output.write_str("c")?;
+        output.newline()?;
+        output.finish()?;
+
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(&archive_path)?));
+        let mut entries = vec![];
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.to_string_lossy().to_string();
+            let mut data = String::new();
+            entry.read_to_string(&mut data)?;
+            entries.push((path, data));
+        }
+        assert_eq!(
+            entries,
+            vec![
+                ("a/b.rs".to_string(), "ab".to_string()),
+                ("c.rs".to_string(), "c\n".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    fn read_gz(path: &Path) -> Result<String> {
+        let mut data = String::new();
+        GzDecoder::new(File::open(path)?).read_to_string(&mut data)?;
+        Ok(data)
+    }
+}
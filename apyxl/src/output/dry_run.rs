@@ -0,0 +1,194 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::path::PathBuf;
+
+use anyhow::Result;
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Records the files that would be written for each [Chunk] along with their byte counts, without
+/// touching disk. Useful for `--dry-run` pipelines and for asserting on generator output in tests.
+///
+/// Data written before the first [Chunk] is recorded under a [Record] with no
+/// `relative_file_path`.
+#[derive(Debug, Default)]
+pub struct DryRun {
+    hash: bool,
+    records: Vec<Record>,
+    hasher: Option<DefaultHasher>,
+}
+
+/// Everything [DryRun] knows about a single [Chunk] that would have been written.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Record {
+    /// The [Chunk]'s `relative_file_path`, if any.
+    pub relative_file_path: Option<PathBuf>,
+    /// Total number of bytes that would have been written.
+    pub bytes: usize,
+    /// Hash of the content that would have been written. Only set if [DryRun::with_hashes] is used.
+    ///
+    /// Hashes are stable within a single build of apyxl, but are not guaranteed to be stable across
+    /// versions. They should only be used to compare outputs within the same process.
+    pub hash: Option<u64>,
+}
+
+impl DryRun {
+    /// Also record a hash of the content written for each [Chunk].
+    pub fn with_hashes() -> Self {
+        Self {
+            hash: true,
+            ..Default::default()
+        }
+    }
+
+    /// All [Record]s in the order their [Chunk]s were written.
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Total number of bytes across all [Record]s.
+    pub fn total_bytes(&self) -> usize {
+        self.records.iter().map(|record| record.bytes).sum()
+    }
+
+    fn start_record(&mut self, relative_file_path: Option<PathBuf>) {
+        self.records.push(Record {
+            relative_file_path,
+            bytes: 0,
+            hash: None,
+        });
+        self.hasher = self.hash.then(DefaultHasher::new);
+    }
+
+    fn record(&mut self, bytes: &[u8]) {
+        if self.records.is_empty() {
+            self.start_record(None);
+        }
+        let record = self.records.last_mut().unwrap();
+        record.bytes += bytes.len();
+        if let Some(hasher) = &mut self.hasher {
+            hasher.write(bytes);
+            record.hash = Some(hasher.finish());
+        }
+    }
+}
+
+impl Output for DryRun {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.start_record(chunk.relative_file_path.clone());
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.record(data.as_bytes());
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.record(data.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write('\n')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use crate::model::Chunk;
+    use crate::output::{DryRun, Record};
+    use crate::Output;
+
+    #[test]
+    fn records_bytes_per_chunk() -> Result<()> {
+        let mut output = DryRun::default();
+        output.write_chunk(&Chunk::with_relative_file_path("a/b"))?;
+        output.write_str("abc")?;
+        output.write_chunk(&Chunk::with_relative_file_path("c"))?;
+        output.write('d')?;
+        output.newline()?;
+        assert_eq!(
+            output.records(),
+            &[
+                Record {
+                    relative_file_path: Some(PathBuf::from("a/b")),
+                    bytes: 3,
+                    hash: None,
+                },
+                Record {
+                    relative_file_path: Some(PathBuf::from("c")),
+                    bytes: 2,
+                    hash: None,
+                },
+            ]
+        );
+        assert_eq!(output.total_bytes(), 5);
+        Ok(())
+    }
+
+    #[test]
+    fn write_without_chunk_is_recorded_without_path() -> Result<()> {
+        let mut output = DryRun::default();
+        output.write_str("abc")?;
+        assert_eq!(output.records().len(), 1);
+        assert_eq!(output.records()[0].relative_file_path, None);
+        assert_eq!(output.records()[0].bytes, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn counts_utf8_bytes() -> Result<()> {
+        let mut output = DryRun::default();
+        output.write('é')?;
+        assert_eq!(output.total_bytes(), 2);
+        Ok(())
+    }
+
+    mod hash {
+        use anyhow::Result;
+
+        use crate::model::Chunk;
+        use crate::output::DryRun;
+        use crate::Output;
+
+        #[test]
+        fn not_recorded_by_default() -> Result<()> {
+            let mut output = DryRun::default();
+            output.write_str("abc")?;
+            assert_eq!(output.records()[0].hash, None);
+            Ok(())
+        }
+
+        #[test]
+        fn same_content_same_hash() -> Result<()> {
+            let mut output = DryRun::with_hashes();
+            output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+            output.write_str("abc")?;
+            output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+            output.write('a')?;
+            output.write_str("bc")?;
+            let records = output.records();
+            assert!(records[0].hash.is_some());
+            assert_eq!(records[0].hash, records[1].hash);
+            Ok(())
+        }
+
+        #[test]
+        fn different_content_different_hash() -> Result<()> {
+            let mut output = DryRun::with_hashes();
+            output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+            output.write_str("abc")?;
+            output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+            output.write_str("abd")?;
+            let records = output.records();
+            assert_ne!(records[0].hash, records[1].hash);
+            Ok(())
+        }
+    }
+}
@@ -58,7 +58,7 @@ impl Output for FileSet {
 
     fn write(&mut self, data: char) -> Result<()> {
         if let Some((_, file)) = &mut self.current {
-            file.write_all(&[data as u8])?;
+            file.write_all(data.encode_utf8(&mut [0; 4]).as_bytes())?;
         }
         Ok(())
     }
@@ -193,6 +193,17 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn write_non_ascii_char() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = FileSet::new(root.path())?;
+        let chunk = Chunk::with_relative_file_path(root.path().join("file"));
+        output.write_chunk(&chunk)?;
+        output.write('—')?;
+        assert_eq!(fs::read_to_string(root.path().join("file"))?, "—");
+        Ok(())
+    }
+
     #[test]
     fn write_without_current_chunk_is_ignored() -> Result<()> {
         let root = tempdir()?;
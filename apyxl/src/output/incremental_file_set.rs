@@ -0,0 +1,341 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, error};
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Name of the file within the `output_root` that lists every file generated by the previous run.
+pub const MANIFEST_FILE_NAME: &str = ".apyxl_generated";
+
+/// Like [crate::output::FileSet], creates a file for each [Chunk] within the `output_root` using
+/// the [Chunk]'s `relative_file_path`, but is intended for regenerating into an existing directory
+/// such as a source tree.
+///
+/// Each file is buffered in memory and only written if its content differs from what is already on
+/// disk, which preserves mtimes and avoids triggering downstream rebuilds.
+///
+/// A manifest of generated files is kept in the `output_root` (see [MANIFEST_FILE_NAME]). If
+/// [IncrementalFileSet::prune] is enabled, files listed by the previous manifest that were not
+/// generated again are deleted on [Output::finish]. [Output::finish] is only called once every
+/// [crate::Generator] has run, so an output shared between multiple [crate::Generator]s only prunes
+/// the files that none of them generated.
+///
+/// Unlike [crate::output::AtomicFileSet], files are written as they are generated: each one is
+/// written once the next [Chunk] starts or [Output::finish] is called, so a failed run leaves the
+/// files generated before the failure in place, and prunes nothing. Any data written without a
+/// [Chunk] is ignored.
+#[derive(Debug, Default)]
+pub struct IncrementalFileSet {
+    output_root: PathBuf,
+    prune: bool,
+    current: Option<(PathBuf, String)>,
+    previous: BTreeSet<PathBuf>,
+    generated: BTreeSet<PathBuf>,
+    written: Vec<PathBuf>,
+    unchanged: Vec<PathBuf>,
+    pruned: Vec<PathBuf>,
+}
+
+impl IncrementalFileSet {
+    pub fn new<P: Into<PathBuf>>(output_root: P) -> Result<Self> {
+        let output_root = output_root.into();
+        fs::create_dir_all(&output_root)?;
+        let dir_metadata = fs::metadata(&output_root).context("output_root")?;
+        if !dir_metadata.is_dir() {
+            return Err(anyhow!("specified 'output_root' must be a directory"));
+        }
+        let previous = read_manifest(&output_root.join(MANIFEST_FILE_NAME))?;
+        Ok(Self {
+            output_root,
+            previous,
+            ..Default::default()
+        })
+    }
+
+    /// Delete files generated by the previous run that were not generated by this one.
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Relative paths of files whose content changed and were written to disk.
+    pub fn written(&self) -> &[PathBuf] {
+        &self.written
+    }
+
+    /// Relative paths of files whose content was identical to what was on disk and were skipped.
+    pub fn unchanged(&self) -> &[PathBuf] {
+        &self.unchanged
+    }
+
+    /// Relative paths of stale files that were deleted.
+    pub fn pruned(&self) -> &[PathBuf] {
+        &self.pruned
+    }
+
+    fn flush_current(&mut self) -> Result<()> {
+        let (relative_path, data) = match self.current.take() {
+            None => return Ok(()),
+            Some(current) => current,
+        };
+        let path = self.output_root.join(&relative_path);
+        let is_unchanged = match fs::read(&path) {
+            Ok(existing) => existing == data.as_bytes(),
+            Err(_) => false,
+        };
+        if is_unchanged {
+            debug!("unchanged: {}", path.display());
+            self.unchanged.push(relative_path.clone());
+        } else {
+            debug!("writing: {}", path.display());
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, data)?;
+            self.written.push(relative_path.clone());
+        }
+        self.generated.insert(relative_path);
+        Ok(())
+    }
+
+    fn prune_stale(&mut self) -> Result<()> {
+        let stale = self
+            .previous
+            .difference(&self.generated)
+            .cloned()
+            .collect::<Vec<_>>();
+        for relative_path in stale {
+            // Only trust manifest entries that stay within the output root.
+            if !is_contained(&relative_path) {
+                error!(
+                    "refusing to prune '{}': not relative to output_root",
+                    relative_path.display()
+                );
+                continue;
+            }
+            let path = self.output_root.join(&relative_path);
+            if path.is_file() {
+                debug!("pruning: {}", path.display());
+                fs::remove_file(&path)?;
+                self.pruned.push(relative_path);
+            }
+        }
+        Ok(())
+    }
+
+    fn write_manifest(&self) -> Result<()> {
+        let mut manifest = self.generated.clone();
+        if !self.prune {
+            // Keep tracking files from previous runs until they are pruned.
+            manifest.extend(self.previous.iter().cloned());
+        }
+        let data = manifest
+            .iter()
+            .map(|path| format!("{}\n", path.to_string_lossy()))
+            .collect::<String>();
+        fs::write(self.output_root.join(MANIFEST_FILE_NAME), data)?;
+        Ok(())
+    }
+}
+
+impl Output for IncrementalFileSet {
+    /// Flushes the current chunk if it changed and starts buffering a new one at `chunk`'s
+    /// `relative_file_path`.
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        let path = chunk.relative_file_path.as_ref().ok_or_else(|| {
+            anyhow!("all chunks must have file paths when generating to an IncrementalFileSet")
+        })?;
+        self.flush_current()?;
+        self.current = Some((path.clone(), String::new()));
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        if let Some((_, buffer)) = &mut self.current {
+            buffer.push_str(data);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        if let Some((_, buffer)) = &mut self.current {
+            buffer.push(data);
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write('\n')
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush_current()?;
+        if self.prune {
+            self.prune_stale()?;
+        }
+        self.write_manifest()
+    }
+}
+
+pub(crate) fn read_manifest(path: &Path) -> Result<BTreeSet<PathBuf>> {
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let data = fs::read_to_string(path).context("read manifest")?;
+    Ok(data
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+pub(crate) fn is_contained(path: &Path) -> bool {
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::model::Chunk;
+    use crate::output::incremental_file_set::MANIFEST_FILE_NAME;
+    use crate::output::IncrementalFileSet;
+    use crate::Output;
+
+    #[test]
+    fn allows_non_empty_root() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("existing"), "data")?;
+        assert!(IncrementalFileSet::new(root.path()).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn writes_new_files() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = IncrementalFileSet::new(root.path())?;
+        generate(&mut output, &[("a/b", "ab"), ("c", "c")])?;
+        assert_eq!(fs::read_to_string(root.path().join("a/b"))?, "ab");
+        assert_eq!(fs::read_to_string(root.path().join("c"))?, "c");
+        assert_eq!(
+            output.written(),
+            &[PathBuf::from("a/b"), PathBuf::from("c")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn skips_unchanged_files() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("a"), "same")?;
+        fs::write(root.path().join("b"), "old")?;
+        let mut output = IncrementalFileSet::new(root.path())?;
+        generate(&mut output, &[("a", "same"), ("b", "new")])?;
+        assert_eq!(output.unchanged(), &[PathBuf::from("a")]);
+        assert_eq!(output.written(), &[PathBuf::from("b")]);
+        assert_eq!(fs::read_to_string(root.path().join("b"))?, "new");
+        Ok(())
+    }
+
+    #[test]
+    fn records_manifest() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = IncrementalFileSet::new(root.path())?;
+        generate(&mut output, &[("b", ""), ("a", "")])?;
+        assert_eq!(
+            fs::read_to_string(root.path().join(MANIFEST_FILE_NAME))?,
+            "a\nb\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn chunk_without_path_errors() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = IncrementalFileSet::new(root.path())?;
+        assert!(output.write_chunk(&Chunk::default()).is_err());
+        Ok(())
+    }
+
+    mod prune {
+        use std::fs;
+        use std::path::PathBuf;
+
+        use anyhow::Result;
+        use tempfile::tempdir;
+
+        use crate::output::incremental_file_set::tests::generate;
+        use crate::output::IncrementalFileSet;
+
+        #[test]
+        fn deletes_stale_generated_files() -> Result<()> {
+            let root = tempdir()?;
+            generate(
+                &mut IncrementalFileSet::new(root.path())?,
+                &[("a", "a"), ("b", "b")],
+            )?;
+            let mut output = IncrementalFileSet::new(root.path())?.prune(true);
+            generate(&mut output, &[("a", "a")])?;
+            assert_eq!(output.pruned(), &[PathBuf::from("b")]);
+            assert!(root.path().join("a").exists());
+            assert!(!root.path().join("b").exists());
+            Ok(())
+        }
+
+        #[test]
+        fn keeps_files_not_in_manifest() -> Result<()> {
+            let root = tempdir()?;
+            fs::write(root.path().join("handwritten"), "data")?;
+            let mut output = IncrementalFileSet::new(root.path())?.prune(true);
+            generate(&mut output, &[("a", "a")])?;
+            assert!(output.pruned().is_empty());
+            assert!(root.path().join("handwritten").exists());
+            Ok(())
+        }
+
+        #[test]
+        fn disabled_keeps_stale_files() -> Result<()> {
+            let root = tempdir()?;
+            generate(
+                &mut IncrementalFileSet::new(root.path())?,
+                &[("a", "a"), ("b", "b")],
+            )?;
+            generate(&mut IncrementalFileSet::new(root.path())?, &[("a", "a")])?;
+            // Still tracked, so a later pruning run can remove it.
+            let mut output = IncrementalFileSet::new(root.path())?.prune(true);
+            generate(&mut output, &[("a", "a")])?;
+            assert_eq!(output.pruned(), &[PathBuf::from("b")]);
+            Ok(())
+        }
+
+        #[test]
+        fn ignores_manifest_entries_outside_root() -> Result<()> {
+            let parent = tempdir()?;
+            let root = parent.path().join("root");
+            fs::create_dir(&root)?;
+            fs::write(parent.path().join("outside"), "data")?;
+            fs::write(root.join(super::MANIFEST_FILE_NAME), "../outside\n")?;
+            let mut output = IncrementalFileSet::new(&root)?.prune(true);
+            generate(&mut output, &[])?;
+            assert!(parent.path().join("outside").exists());
+            Ok(())
+        }
+    }
+
+    fn generate(output: &mut IncrementalFileSet, files: &[(&str, &str)]) -> Result<()> {
+        for (path, data) in files {
+            output.write_chunk(&Chunk::with_relative_file_path(Path::new(path)))?;
+            output.write_str(data)?;
+        }
+        output.finish()
+    }
+}
@@ -95,6 +95,10 @@ impl Output for Indented<'_> {
         self.has_pending_indent = true;
         Ok(())
     }
+
+    fn finish(&mut self) -> Result<()> {
+        self.output.finish()
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use log::Level;
+
+use crate::model::Chunk;
+use crate::Output;
+
+pub const DEFAULT_LOG_TARGET: &str = "apyxl::output";
+
+/// Routes everything written to it to the `log` crate, one log record per line, at a configurable
+/// [Level] and target. Useful for diagnostic-style generators such as [crate::generator::Dbg].
+///
+/// Each [Chunk] with a `relative_file_path` is logged as a `--- CHUNK: <path>` line.
+#[derive(Debug)]
+pub struct Log {
+    level: Level,
+    target: String,
+    line: String,
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self::new(Level::Info)
+    }
+}
+
+impl Log {
+    pub fn new(level: Level) -> Self {
+        Self {
+            level,
+            target: DEFAULT_LOG_TARGET.to_string(),
+            line: String::new(),
+        }
+    }
+
+    /// Log target to use instead of [DEFAULT_LOG_TARGET].
+    pub fn target<S: ToString>(mut self, target: S) -> Self {
+        self.target = target.to_string();
+        self
+    }
+
+    fn log_line(&mut self) {
+        log::log!(target: &self.target, self.level, "{}", self.line);
+        self.line.clear();
+    }
+
+    fn flush(&mut self) {
+        if !self.line.is_empty() {
+            self.log_line();
+        }
+    }
+}
+
+impl Output for Log {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.flush();
+        if let Some(path) = &chunk.relative_file_path {
+            self.line = format!("--- CHUNK: {}", path.to_string_lossy());
+            self.log_line();
+        }
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        let mut lines = data.split('\n');
+        if let Some(first) = lines.next() {
+            self.line.push_str(first);
+        }
+        for line in lines {
+            self.log_line();
+            self.line.push_str(line);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        if data == '\n' {
+            self.log_line();
+        } else {
+            self.line.push(data);
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.log_line();
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush();
+        Ok(())
+    }
+}
+
+impl Drop for Log {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use log::Level;
+
+    use crate::model::Chunk;
+    use crate::output::Log;
+    use crate::Output;
+
+    #[test]
+    fn buffers_partial_lines() -> Result<()> {
+        let mut output = Log::new(Level::Debug).target("test");
+        output.write_str("a")?;
+        output.write('b')?;
+        assert_eq!(output.line, "ab");
+        output.write_str("c\nd")?;
+        assert_eq!(output.line, "d");
+        output.newline()?;
+        assert_eq!(output.line, "");
+        Ok(())
+    }
+
+    #[test]
+    fn chunk_and_finish_flush_line() -> Result<()> {
+        let mut output = Log::default();
+        output.write_str("a")?;
+        output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+        assert_eq!(output.line, "");
+        output.write_str("c")?;
+        output.finish()?;
+        assert_eq!(output.line, "");
+        Ok(())
+    }
+}
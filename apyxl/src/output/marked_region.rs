@@ -0,0 +1,241 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+
+use crate::model::Chunk;
+use crate::output::CommentStyle;
+use crate::Output;
+
+/// Marks the line before the generated region of a file. May be in any comment style.
+pub const BEGIN_MARKER: &str = "<apyxl:begin>";
+/// Marks the line after the generated region of a file. May be in any comment style.
+pub const END_MARKER: &str = "<apyxl:end>";
+
+/// Like [crate::output::FileSet], targets a file for each [Chunk] within the `output_root` using
+/// the [Chunk]'s `relative_file_path`, but only replaces the region between the [BEGIN_MARKER] and
+/// [END_MARKER] lines of existing files, e.g.
+///
+/// ```text
+/// // hand-written code
+/// // <apyxl:begin>
+/// (generated code)
+/// // <apyxl:end>
+/// // more hand-written code
+/// ```
+///
+/// Everything outside of the region, including the marker lines themselves, is left intact. Files
+/// that do not exist yet are created containing only the markers and the generated region, using
+/// the [CommentStyle] for the file's extension. Existing files without exactly one region are an
+/// error rather than being overwritten.
+///
+/// Files are only written if their content changed. Nothing is written to disk until the next
+/// [Chunk] starts or [Output::finish] is called. Any data written without a [Chunk] is ignored.
+#[derive(Debug)]
+pub struct MarkedRegions {
+    output_root: PathBuf,
+    default_style: CommentStyle,
+    current: Option<(PathBuf, String)>,
+}
+
+impl MarkedRegions {
+    pub fn new<P: Into<PathBuf>>(output_root: P) -> Result<Self> {
+        let output_root = output_root.into();
+        fs::create_dir_all(&output_root)?;
+        let dir_metadata = fs::metadata(&output_root).context("output_root")?;
+        if !dir_metadata.is_dir() {
+            return Err(anyhow!("specified 'output_root' must be a directory"));
+        }
+        Ok(Self {
+            output_root,
+            default_style: CommentStyle::DoubleSlash,
+            current: None,
+        })
+    }
+
+    /// [CommentStyle] to use for markers in new files whose extension is not recognized by
+    /// [CommentStyle::from_path]. Defaults to [CommentStyle::DoubleSlash].
+    pub fn default_style(mut self, style: CommentStyle) -> Self {
+        self.default_style = style;
+        self
+    }
+
+    fn flush_current(&mut self) -> Result<()> {
+        let (relative_path, mut region) = match self.current.take() {
+            None => return Ok(()),
+            Some(current) => current,
+        };
+        if !region.is_empty() && !region.ends_with('\n') {
+            region.push('\n');
+        }
+        let path = self.output_root.join(&relative_path);
+        let data = if path.exists() {
+            let existing =
+                fs::read_to_string(&path).with_context(|| format!("read '{}'", path.display()))?;
+            let merged = merge_region(&existing, &region)
+                .with_context(|| format!("merge into '{}'", path.display()))?;
+            if merged == existing {
+                debug!("unchanged: {}", path.display());
+                return Ok(());
+            }
+            merged
+        } else {
+            let style = CommentStyle::from_path(&relative_path).unwrap_or(self.default_style);
+            format!(
+                "{}\n{}{}\n",
+                style.line(BEGIN_MARKER),
+                region,
+                style.line(END_MARKER)
+            )
+        };
+        debug!("writing: {}", path.display());
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, data)?;
+        Ok(())
+    }
+}
+
+/// Replaces the lines between the [BEGIN_MARKER] and [END_MARKER] lines of `existing` with
+/// `region`.
+fn merge_region(existing: &str, region: &str) -> Result<String> {
+    let mut begin = None;
+    let mut end = None;
+    let mut offset = 0;
+    for line in existing.split_inclusive('\n') {
+        if line.contains(BEGIN_MARKER) {
+            if begin.is_some() {
+                return Err(anyhow!("found more than one '{}' marker", BEGIN_MARKER));
+            }
+            begin = Some(offset + line.len());
+        } else if line.contains(END_MARKER) {
+            if begin.is_none() || end.is_some() {
+                return Err(anyhow!("unexpected '{}' marker", END_MARKER));
+            }
+            end = Some(offset);
+        }
+        offset += line.len();
+    }
+    match (begin, end) {
+        (Some(begin), Some(end)) => Ok(format!(
+            "{}{}{}",
+            &existing[..begin],
+            region,
+            &existing[end..]
+        )),
+        (None, _) => Err(anyhow!("missing '{}' marker", BEGIN_MARKER)),
+        (Some(_), None) => Err(anyhow!("missing '{}' marker", END_MARKER)),
+    }
+}
+
+impl Output for MarkedRegions {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        let path = chunk.relative_file_path.as_ref().ok_or_else(|| {
+            anyhow!("all chunks must have file paths when generating to MarkedRegions")
+        })?;
+        self.flush_current()?;
+        self.current = Some((path.clone(), String::new()));
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        if let Some((_, buffer)) = &mut self.current {
+            buffer.push_str(data);
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        if let Some((_, buffer)) = &mut self.current {
+            buffer.push(data);
+        }
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write('\n')
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush_current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::model::Chunk;
+    use crate::output::marked_region::merge_region;
+    use crate::output::MarkedRegions;
+    use crate::Output;
+
+    #[test]
+    fn replaces_region_in_existing_file() -> Result<()> {
+        let root = tempdir()?;
+        let path = root.path().join("a.rs");
+        fs::write(
+            &path,
+            "fn a() {}\n// <apyxl:begin>\nold\n// <apyxl:end>\nfn b() {}\n",
+        )?;
+        let mut output = MarkedRegions::new(root.path())?;
+        output.write_chunk(&Chunk::with_relative_file_path("a.rs"))?;
+        output.write_str("new")?;
+        output.newline()?;
+        output.finish()?;
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "fn a() {}\n// <apyxl:begin>\nnew\n// <apyxl:end>\nfn b() {}\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn creates_new_file_with_markers() -> Result<()> {
+        let root = tempdir()?;
+        let mut output = MarkedRegions::new(root.path())?;
+        output.write_chunk(&Chunk::with_relative_file_path("a/b.py"))?;
+        output.write_str("x = 1")?;
+        output.finish()?;
+        assert_eq!(
+            fs::read_to_string(root.path().join("a/b.py"))?,
+            "# <apyxl:begin>\nx = 1\n# <apyxl:end>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn existing_file_without_markers_errors() -> Result<()> {
+        let root = tempdir()?;
+        let path = root.path().join("a.rs");
+        fs::write(&path, "hand-written\n")?;
+        let mut output = MarkedRegions::new(root.path())?;
+        output.write_chunk(&Chunk::with_relative_file_path("a.rs"))?;
+        output.write_str("new")?;
+        assert!(output.finish().is_err());
+        assert_eq!(fs::read_to_string(&path)?, "hand-written\n");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_empty_region() -> Result<()> {
+        assert_eq!(
+            merge_region("a\n/* <apyxl:begin> */\nb\n/* <apyxl:end> */\n", "")?,
+            "a\n/* <apyxl:begin> */\n/* <apyxl:end> */\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merge_invalid_markers() {
+        assert!(merge_region("<apyxl:end>\n<apyxl:begin>\n", "").is_err());
+        assert!(merge_region("<apyxl:begin>\n<apyxl:begin>\n<apyxl:end>\n", "").is_err());
+        assert!(merge_region("<apyxl:begin>\n", "").is_err());
+        assert!(merge_region("<apyxl:begin>\n<apyxl:end>\n<apyxl:end>\n", "").is_err());
+    }
+}
@@ -2,15 +2,55 @@ use anyhow::Result;
 use std::fmt::Debug;
 
 use crate::model::chunk;
+#[cfg(feature = "fs")]
+pub use atomic_file_set::AtomicFileSet;
+pub use banner::{Banner, CommentStyle, DEFAULT_BANNER};
 pub use buffer::Buffer;
+#[cfg(feature = "fs")]
+pub use check::{Check, Difference, DifferenceKind};
+pub use chunk_buffer::ChunkBuffer;
+#[cfg(feature = "fs")]
+pub use compressed::{GzFileSet, TarGz};
+pub use dry_run::{DryRun, Record};
+#[cfg(feature = "fs")]
 pub use file_set::FileSet;
+#[cfg(feature = "fs")]
+pub use incremental_file_set::IncrementalFileSet;
 pub use indent::Indented;
+pub use log::{Log, DEFAULT_LOG_TARGET};
+#[cfg(feature = "fs")]
+pub use marked_region::{MarkedRegions, BEGIN_MARKER, END_MARKER};
+pub use path_template::{PathTemplate, Templated};
+pub use post_process::{FormatCommand, PostProcessed, PostProcessor};
+pub use progress::{ChunkStats, Progress, ProgressEvent, Stats};
 pub use stdout::StdOut;
+pub use styled::{Indent, LineEnding, Styled};
+pub use writer::Writer;
 
+#[cfg(feature = "fs")]
+mod atomic_file_set;
+mod banner;
 mod buffer;
+#[cfg(feature = "fs")]
+mod check;
+mod chunk_buffer;
+#[cfg(feature = "fs")]
+mod compressed;
+mod dry_run;
+#[cfg(feature = "fs")]
 mod file_set;
+#[cfg(feature = "fs")]
+mod incremental_file_set;
 mod indent;
+mod log;
+#[cfg(feature = "fs")]
+mod marked_region;
+mod path_template;
+mod post_process;
+mod progress;
 mod stdout;
+mod styled;
+mod writer;
 
 /// An [Output] translates data generated by an apyxl [Generator] to some output format.
 pub trait Output: Debug {
@@ -20,4 +60,57 @@ pub trait Output: Debug {
     fn write_str(&mut self, data: &str) -> Result<()>;
     fn write(&mut self, data: char) -> Result<()>;
     fn newline(&mut self) -> Result<()>;
+
+    /// Called once every [crate::Generator] has finished generating, and only if they all
+    /// succeeded. Called once per output, even if it's shared by several [crate::Generator]s. This
+    /// should be used to flush any buffered data and release resources.
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Allows [Output]s that wrap other [Output]s to borrow the wrapped [Output].
+impl<O: Output + ?Sized> Output for &mut O {
+    fn write_chunk(&mut self, chunk: &chunk::Chunk) -> Result<()> {
+        (**self).write_chunk(chunk)
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        (**self).write_str(data)
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        (**self).write(data)
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        (**self).newline()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+}
+
+/// Allows [Output]s that wrap other [Output]s to be composed at runtime, e.g. `Banner<Box<dyn Output>>`.
+impl<O: Output + ?Sized> Output for Box<O> {
+    fn write_chunk(&mut self, chunk: &chunk::Chunk) -> Result<()> {
+        (**self).write_chunk(chunk)
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        (**self).write_str(data)
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        (**self).write(data)
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        (**self).newline()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
 }
@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
+use crate::model::Chunk;
+use crate::{rust_util, Output};
+
+/// Maps a [Chunk]'s `relative_file_path` to a new path using a template such as
+/// `{namespace_path}/{name}.ts` or `proto/{package}.proto`.
+///
+/// Supported variables, given an input chunk path of `a/b/c.rs`:
+/// - `{path}`: the full path without extension, `a/b/c`
+/// - `{dir}`: the parent directory, `a/b`
+/// - `{name}`: the file name without extension, `c`
+/// - `{ext}`: the file extension, `rs`
+/// - `{namespace_path}`: the namespace the chunk represents, `a/b/c`
+/// - `{package}`: the namespace the chunk represents, dot-separated, `a.b.c`
+///
+/// Use `{{` and `}}` for literal braces. Empty path components in the result are removed, so
+/// `{dir}/{name}.ts` with a chunk path of `c.rs` results in `c.ts`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PathTemplate {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Variable {
+    Path,
+    Dir,
+    Name,
+    Ext,
+    NamespacePath,
+    Package,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Segment {
+    Literal(String),
+    Variable(Variable),
+}
+
+impl PathTemplate {
+    pub fn new(template: &str) -> Result<Self> {
+        let mut segments = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            None => {
+                                return Err(anyhow!(
+                                    "unmatched '{{' in path template '{}'",
+                                    template
+                                ))
+                            }
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                        }
+                    }
+                    let variable = Variable::from_name(&name).ok_or_else(|| {
+                        anyhow!(
+                            "unknown variable '{{{}}}' in path template '{}'",
+                            name,
+                            template
+                        )
+                    })?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Variable(variable));
+                }
+                '}' => {
+                    return Err(anyhow!("unmatched '}}' in path template '{}'", template));
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Self { segments })
+    }
+
+    /// Render the template for the `relative_file_path` of a [Chunk].
+    pub fn render(&self, relative_file_path: &Path) -> PathBuf {
+        let rendered = self
+            .segments
+            .iter()
+            .map(|segment| match segment {
+                Segment::Literal(literal) => literal.clone(),
+                Segment::Variable(variable) => variable.value(relative_file_path),
+            })
+            .collect::<String>();
+        rendered
+            .split('/')
+            .filter(|component| !component.is_empty())
+            .collect()
+    }
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "path" => Some(Variable::Path),
+            "dir" => Some(Variable::Dir),
+            "name" => Some(Variable::Name),
+            "ext" => Some(Variable::Ext),
+            "namespace_path" => Some(Variable::NamespacePath),
+            "package" => Some(Variable::Package),
+            _ => None,
+        }
+    }
+
+    fn value(&self, path: &Path) -> String {
+        match self {
+            Variable::Path => join_components(&path.with_extension(""), "/"),
+            Variable::Dir => path
+                .parent()
+                .map(|p| join_components(p, "/"))
+                .unwrap_or_default(),
+            Variable::Name => path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Variable::Ext => path
+                .extension()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            Variable::NamespacePath => rust_util::path_to_entity_id(path)
+                .component_names()
+                .join("/"),
+            Variable::Package => rust_util::path_to_entity_id(path)
+                .component_names()
+                .join("."),
+        }
+    }
+}
+
+fn join_components(path: &Path, separator: &str) -> String {
+    path.iter().map(|c| c.to_string_lossy()).join(separator)
+}
+
+/// Wraps an existing [Output] and rewrites the `relative_file_path` of each [Chunk] using a
+/// [PathTemplate] before passing it on. This lets the file layout be configured per output without
+/// changing the [crate::Generator].
+#[derive(Debug)]
+pub struct Templated<O: Output> {
+    output: O,
+    template: PathTemplate,
+}
+
+impl<O: Output> Templated<O> {
+    pub fn new(output: O, template: PathTemplate) -> Self {
+        Self { output, template }
+    }
+
+    pub fn inner(&self) -> &O {
+        &self.output
+    }
+
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+}
+
+impl<O: Output> Output for Templated<O> {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        match &chunk.relative_file_path {
+            None => self.output.write_chunk(chunk),
+            Some(path) => {
+                let mut chunk = chunk.clone();
+                chunk.relative_file_path = Some(self.template.render(path));
+                self.output.write_chunk(&chunk)
+            }
+        }
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.output.write_str(data)
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.output.write(data)
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.output.newline()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.output.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Result;
+
+    use crate::model::Chunk;
+    use crate::output::{DryRun, PathTemplate, Templated};
+    use crate::Output;
+
+    macro_rules! test {
+        ($name:ident, $template:literal, $input:literal, $expected:literal) => {
+            #[test]
+            fn $name() -> Result<()> {
+                let template = PathTemplate::new($template)?;
+                assert_eq!(template.render(Path::new($input)), PathBuf::from($expected));
+                Ok(())
+            }
+        };
+    }
+
+    test!(literal, "a/b.txt", "x/y.rs", "a/b.txt");
+    test!(path, "{path}.ts", "a/b/c.rs", "a/b/c.ts");
+    test!(dir, "{dir}", "a/b/c.rs", "a/b");
+    test!(name, "{name}", "a/b/c.rs", "c");
+    test!(ext, "{ext}", "a/b/c.rs", "rs");
+    test!(
+        namespace_path,
+        "{namespace_path}/{name}.ts",
+        "a/b/c.rs",
+        "a/b/c/c.ts"
+    );
+    test!(
+        namespace_path_mod_rs,
+        "{namespace_path}.ts",
+        "a/b/mod.rs",
+        "a/b.ts"
+    );
+    test!(
+        package,
+        "proto/{package}.proto",
+        "a/b/c.rs",
+        "proto/a.b.c.proto"
+    );
+    test!(empty_components_removed, "{dir}/{name}.ts", "c.rs", "c.ts");
+    test!(escaped_braces, "{{{name}}}", "a.rs", "{a}");
+
+    #[test]
+    fn unknown_variable_errors() {
+        assert!(PathTemplate::new("{asdf}").is_err());
+    }
+
+    #[test]
+    fn unmatched_brace_errors() {
+        assert!(PathTemplate::new("a}").is_err());
+        assert!(PathTemplate::new("{name").is_err());
+    }
+
+    #[test]
+    fn templated_rewrites_chunk_paths() -> Result<()> {
+        let template = PathTemplate::new("gen/{namespace_path}.ts")?;
+        let mut output = Templated::new(DryRun::default(), template);
+        output.write_chunk(&Chunk::with_relative_file_path("a/b.rs"))?;
+        output.write_chunk(&Chunk::default())?;
+        let records = output.inner().records();
+        assert_eq!(
+            records[0].relative_file_path,
+            Some(PathBuf::from("gen/a/b.ts"))
+        );
+        assert_eq!(records[1].relative_file_path, None);
+        Ok(())
+    }
+}
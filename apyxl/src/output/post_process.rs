@@ -0,0 +1,335 @@
+use std::fmt::{Debug, Formatter};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+use std::thread;
+
+use anyhow::{anyhow, Context, Result};
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// A [PostProcessor] receives the complete content of each [Chunk] before it is written to the
+/// underlying [Output], and returns the content that should be written instead. Typical uses are
+/// formatting, license header insertion, or other content rewriting.
+///
+/// Any `Fn(&Chunk, String) -> Result<String>` is a [PostProcessor].
+pub trait PostProcessor {
+    fn process(&self, chunk: &Chunk, content: String) -> Result<String>;
+}
+
+impl<F: Fn(&Chunk, String) -> Result<String>> PostProcessor for F {
+    fn process(&self, chunk: &Chunk, content: String) -> Result<String> {
+        self(chunk, content)
+    }
+}
+
+/// A [PostProcessor] that pipes the content of each [Chunk] through an external formatter, e.g.
+/// `rustfmt` or `prettier`, and writes what it prints instead. The command is run once per
+/// [Chunk] with the content on stdin, and fails the run if it exits unsuccessfully.
+///
+/// `{path}` in any argument is replaced with the [Chunk]'s relative file path, e.g. for
+/// `prettier --stdin-filepath {path}`. If any [FormatCommand::extension]s are set, chunks with
+/// other extensions are left as is.
+#[derive(Debug, Clone)]
+pub struct FormatCommand {
+    program: String,
+    args: Vec<String>,
+    extensions: Vec<String>,
+}
+
+impl FormatCommand {
+    pub fn new(program: impl ToString, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            extensions: vec![],
+        }
+    }
+
+    /// Splits `command` at whitespace into the program and its arguments, e.g.
+    /// `rustfmt --edition 2021`. Arguments can't contain whitespace.
+    pub fn parse(command: &str) -> Result<Self> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| anyhow!("format command must not be empty"))?;
+        Ok(Self::new(program, &words.collect::<Vec<_>>()))
+    }
+
+    /// Only format chunks whose path has the extension `extension`, without the leading `.`.
+    pub fn extension(mut self, extension: impl ToString) -> Self {
+        self.extensions.push(extension.to_string());
+        self
+    }
+
+    fn applies_to(&self, chunk: &Chunk) -> bool {
+        self.extensions.is_empty()
+            || chunk
+                .relative_file_path
+                .as_ref()
+                .and_then(|path| path.extension())
+                .is_some_and(|ext| self.extensions.iter().any(|e| ext == e.as_str()))
+    }
+}
+
+impl PostProcessor for FormatCommand {
+    fn process(&self, chunk: &Chunk, content: String) -> Result<String> {
+        if !self.applies_to(chunk) {
+            return Ok(content);
+        }
+        let path = chunk
+            .relative_file_path
+            .as_ref()
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut child = Command::new(&self.program)
+            .args(self.args.iter().map(|arg| arg.replace("{path}", &path)))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("run formatter '{}'", self.program))?;
+        // Written from another thread so that a formatter that streams its output can't deadlock
+        // on a full stdout pipe.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || stdin.write_all(content.as_bytes()));
+        let output = child
+            .wait_with_output()
+            .with_context(|| format!("run formatter '{}'", self.program))?;
+        writer
+            .join()
+            .map_err(|_| anyhow!("write to formatter '{}'", self.program))?
+            .with_context(|| format!("write to formatter '{}'", self.program))?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "formatter '{}' failed on '{}' ({}): {}",
+                self.program,
+                path,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        String::from_utf8(output.stdout)
+            .with_context(|| format!("formatter '{}' printed invalid utf-8", self.program))
+    }
+}
+
+/// Wraps an existing [Output] and buffers the content of each [Chunk], passing it through a chain
+/// of [PostProcessor]s in the order they were added before writing it to the wrapped [Output].
+///
+/// Content for a [Chunk] is written when the next [Chunk] starts, or on [PostProcessed::flush] /
+/// [Output::finish]. Data written before the first [Chunk] is processed with [Chunk::default].
+pub struct PostProcessed<O: Output> {
+    output: O,
+    processors: Vec<Rc<dyn PostProcessor>>,
+    current: Option<Chunk>,
+    buffer: String,
+}
+
+impl<O: Output> PostProcessed<O> {
+    pub fn new(output: O) -> Self {
+        Self::with_processors(output, vec![])
+    }
+
+    pub fn with_processors(output: O, processors: Vec<Rc<dyn PostProcessor>>) -> Self {
+        Self {
+            output,
+            processors,
+            current: None,
+            buffer: String::new(),
+        }
+    }
+
+    /// Add a [PostProcessor] to the end of the chain.
+    pub fn processor(mut self, processor: impl PostProcessor + 'static) -> Self {
+        self.processors.push(Rc::new(processor));
+        self
+    }
+
+    pub fn inner(&self) -> &O {
+        &self.output
+    }
+
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+
+    /// Process and write any buffered content for the current [Chunk] without finishing the wrapped
+    /// [Output].
+    pub fn flush(&mut self) -> Result<()> {
+        let chunk = self.current.take();
+        if chunk.is_none() && self.buffer.is_empty() {
+            return Ok(());
+        }
+        let content = std::mem::take(&mut self.buffer);
+        let content = match &chunk {
+            None => self.process(&Chunk::default(), content)?,
+            Some(chunk) => {
+                self.output.write_chunk(chunk)?;
+                self.process(chunk, content)?
+            }
+        };
+        self.output.write_str(&content)
+    }
+
+    fn process(&self, chunk: &Chunk, content: String) -> Result<String> {
+        self.processors
+            .iter()
+            .try_fold(content, |content, processor| {
+                processor.process(chunk, content)
+            })
+    }
+}
+
+impl<O: Output> Debug for PostProcessed<O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostProcessed")
+            .field("output", &self.output)
+            .field("processors", &self.processors.len())
+            .finish()
+    }
+}
+
+impl<O: Output> Output for PostProcessed<O> {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.flush()?;
+        self.current = Some(chunk.clone());
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.buffer.push_str(data);
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.buffer.push(data);
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.buffer.push('\n');
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush()?;
+        self.output.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::{anyhow, Result};
+
+    use crate::model::Chunk;
+    use crate::output::{ChunkBuffer, FormatCommand, PostProcessed};
+    use crate::Output;
+
+    #[test]
+    fn processes_each_chunk() -> Result<()> {
+        let mut output = PostProcessed::new(ChunkBuffer::new())
+            .processor(|_: &Chunk, content: String| Ok(content.to_uppercase()));
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("abc")?;
+        output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+        output.write('d')?;
+        output.newline()?;
+        output.finish()?;
+        assert_eq!(output.inner().get("a"), Some("ABC"));
+        assert_eq!(output.inner().get("b"), Some("D\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn processors_chain_in_order() -> Result<()> {
+        let mut output = PostProcessed::new(ChunkBuffer::new())
+            .processor(|_: &Chunk, content: String| Ok(format!("{}1", content)))
+            .processor(|_: &Chunk, content: String| Ok(format!("{}2", content)));
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("x")?;
+        output.finish()?;
+        assert_eq!(output.inner().get("a"), Some("x12"));
+        Ok(())
+    }
+
+    #[test]
+    fn processor_receives_chunk() -> Result<()> {
+        let mut output =
+            PostProcessed::new(ChunkBuffer::new()).processor(|chunk: &Chunk, content: String| {
+                Ok(format!(
+                    "{}:{}",
+                    chunk
+                        .relative_file_path
+                        .as_ref()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    content
+                ))
+            });
+        output.write_str("x")?;
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("y")?;
+        output.finish()?;
+        assert_eq!(output.inner().unchunked(), ":x");
+        assert_eq!(output.inner().get("a"), Some("a:y"));
+        Ok(())
+    }
+
+    #[test]
+    fn nothing_written_until_flush() -> Result<()> {
+        let mut output = PostProcessed::new(ChunkBuffer::new());
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("x")?;
+        assert_eq!(output.inner().get("a"), None);
+        output.flush()?;
+        assert_eq!(output.inner().get("a"), Some("x"));
+        Ok(())
+    }
+
+    #[test]
+    fn processor_error_is_returned() -> Result<()> {
+        let mut output = PostProcessed::new(ChunkBuffer::new())
+            .processor(|_: &Chunk, _: String| Err(anyhow!("failed")));
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        assert!(output.finish().is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn format_command() -> Result<()> {
+        let mut output = PostProcessed::new(ChunkBuffer::new())
+            .processor(FormatCommand::parse("tr a-z A-Z")?.extension("rs"))
+            .processor(FormatCommand::new("sed", &["s|^|{path}: |"]).extension("ts"));
+        output.write_chunk(&Chunk::with_relative_file_path("a.rs"))?;
+        output.write_str("fn a() {}\n")?;
+        output.write_chunk(&Chunk::with_relative_file_path("dir/b.ts"))?;
+        output.write_str("b\n")?;
+        output.write_chunk(&Chunk::with_relative_file_path("c.py"))?;
+        output.write_str("c\n")?;
+        output.finish()?;
+        assert_eq!(output.inner().get("a.rs"), Some("FN A() {}\n"));
+        assert_eq!(output.inner().get("dir/b.ts"), Some("dir/b.ts: b\n"));
+        assert_eq!(output.inner().get("c.py"), Some("c\n"));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn format_command_failure_is_returned() -> Result<()> {
+        let mut output =
+            PostProcessed::new(ChunkBuffer::new()).processor(FormatCommand::parse("false")?);
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("x")?;
+        assert!(output.finish().is_err());
+
+        let mut output = PostProcessed::new(ChunkBuffer::new())
+            .processor(FormatCommand::parse("apyxl-no-such-formatter")?);
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        assert!(output.finish().is_err());
+        assert!(FormatCommand::parse("  ").is_err());
+        Ok(())
+    }
+}
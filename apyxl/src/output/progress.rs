@@ -0,0 +1,229 @@
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::info;
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Progress reported by [Progress] to its reporter.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum ProgressEvent {
+    /// A [Chunk] was completed, either because a new one was started or the output finished.
+    Chunk(ChunkStats),
+    /// The output finished. Contains totals since the first write to the [Progress].
+    Finished(Stats),
+}
+
+/// Statistics for a single [Chunk].
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ChunkStats {
+    pub relative_file_path: Option<PathBuf>,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+/// Totals across all [Chunk]s.
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Stats {
+    pub chunks: usize,
+    pub bytes: usize,
+    pub elapsed: Duration,
+}
+
+type Reporter = Box<dyn FnMut(&ProgressEvent)>;
+
+/// Wraps an existing [Output] and reports per-[Chunk] progress (bytes written and elapsed time) so
+/// that long generation runs give feedback.
+///
+/// By default progress is reported to the `log` crate at `info` level. Use [Progress::with_reporter]
+/// to supply a custom callback instead.
+pub struct Progress<O: Output> {
+    output: O,
+    reporter: Reporter,
+    started: Option<Instant>,
+    current: Option<(ChunkStats, Instant)>,
+    stats: Stats,
+}
+
+impl<O: Output> Progress<O> {
+    pub fn new(output: O) -> Self {
+        Self::with_reporter(output, log_event)
+    }
+
+    pub fn with_reporter(output: O, reporter: impl FnMut(&ProgressEvent) + 'static) -> Self {
+        Self {
+            output,
+            reporter: Box::new(reporter),
+            started: None,
+            current: None,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Totals for all completed [Chunk]s so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    pub fn inner(&self) -> &O {
+        &self.output
+    }
+
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+
+    fn start(&mut self) {
+        self.started.get_or_insert_with(Instant::now);
+    }
+
+    fn add_bytes(&mut self, bytes: usize) {
+        self.start();
+        if self.current.is_none() {
+            self.current = Some((ChunkStats::default(), Instant::now()));
+        }
+        if let Some((chunk_stats, _)) = &mut self.current {
+            chunk_stats.bytes += bytes;
+        }
+    }
+
+    fn complete_current(&mut self) {
+        if let Some((mut chunk_stats, started)) = self.current.take() {
+            chunk_stats.elapsed = started.elapsed();
+            self.stats.chunks += 1;
+            self.stats.bytes += chunk_stats.bytes;
+            (self.reporter)(&ProgressEvent::Chunk(chunk_stats));
+        }
+    }
+}
+
+impl<O: Output> Debug for Progress<O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Progress")
+            .field("output", &self.output)
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl<O: Output> Output for Progress<O> {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.start();
+        self.complete_current();
+        self.current = Some((
+            ChunkStats {
+                relative_file_path: chunk.relative_file_path.clone(),
+                ..Default::default()
+            },
+            Instant::now(),
+        ));
+        self.output.write_chunk(chunk)
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.add_bytes(data.len());
+        self.output.write_str(data)
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.add_bytes(data.len_utf8());
+        self.output.write(data)
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.add_bytes(1);
+        self.output.newline()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let result = self.output.finish();
+        self.complete_current();
+        self.stats.elapsed = self.started.map(|i| i.elapsed()).unwrap_or_default();
+        (self.reporter)(&ProgressEvent::Finished(self.stats.clone()));
+        result
+    }
+}
+
+fn log_event(event: &ProgressEvent) {
+    match event {
+        ProgressEvent::Chunk(chunk_stats) => info!(
+            "wrote {} ({} bytes) in {:?}",
+            chunk_stats
+                .relative_file_path
+                .as_ref()
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_else(|| "<no chunk>".to_string()),
+            chunk_stats.bytes,
+            chunk_stats.elapsed
+        ),
+        ProgressEvent::Finished(stats) => info!(
+            "finished writing {} chunks ({} bytes) in {:?}",
+            stats.chunks, stats.bytes, stats.elapsed
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+
+    use crate::model::Chunk;
+    use crate::output::{Buffer, Progress, ProgressEvent};
+    use crate::Output;
+
+    #[test]
+    fn reports_each_chunk_and_totals() -> Result<()> {
+        let events = Rc::new(RefCell::new(vec![]));
+        let events_clone = events.clone();
+        let mut output = Progress::with_reporter(Buffer::default(), move |event| {
+            events_clone.borrow_mut().push(event.clone())
+        });
+        output.write_chunk(&Chunk::with_relative_file_path("a"))?;
+        output.write_str("abc")?;
+        output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+        output.write('é')?;
+        output.newline()?;
+        output.finish()?;
+
+        let events = events.borrow();
+        assert_eq!(events.len(), 3);
+        match (&events[0], &events[1], &events[2]) {
+            (ProgressEvent::Chunk(a), ProgressEvent::Chunk(b), ProgressEvent::Finished(stats)) => {
+                assert_eq!(a.relative_file_path, Some(PathBuf::from("a")));
+                assert_eq!(a.bytes, 3);
+                assert_eq!(b.relative_file_path, Some(PathBuf::from("b")));
+                assert_eq!(b.bytes, 3);
+                assert_eq!(stats.chunks, 2);
+                assert_eq!(stats.bytes, 6);
+            }
+            _ => panic!("unexpected events: {:?}", events),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn writes_pass_through() -> Result<()> {
+        let mut output = Progress::new(Buffer::default());
+        output.write_str("abc")?;
+        output.newline()?;
+        assert_eq!(output.inner().to_string(), "abc\n");
+        Ok(())
+    }
+
+    #[test]
+    fn data_without_chunk_is_counted() -> Result<()> {
+        let mut output = Progress::new(Buffer::default());
+        output.write_str("abc")?;
+        output.finish()?;
+        assert_eq!(output.stats().chunks, 1);
+        assert_eq!(output.stats().bytes, 3);
+        Ok(())
+    }
+}
@@ -1,35 +1,101 @@
 use std::io::{stdout, Write};
 
-use crate::model::chunk::Chunk;
 use anyhow::Result;
+use log::error;
 
+use crate::model::chunk::Chunk;
 use crate::output::Output;
 
+/// Size at which the buffer is flushed even within a [Chunk], the same as [std::io::BufWriter].
+const BUFFER_CAPACITY: usize = 8 * 1024;
+
+/// Writes to stdout, buffered. Stdout is only locked while the buffer is flushed, i.e. at the
+/// start of each [Chunk], when the buffer is full, on [Output::finish] and when dropped, so that
+/// other threads (e.g. logging) can still write in between.
 #[derive(Debug, Default)]
-pub struct StdOut {}
+pub struct StdOut {
+    buffer: Vec<u8>,
+}
+
+impl StdOut {
+    fn flush(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let mut stdout = stdout().lock();
+        stdout.write_all(&self.buffer)?;
+        stdout.flush()?;
+        self.buffer.clear();
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, data: &[u8]) -> Result<()> {
+        self.buffer.extend_from_slice(data);
+        if self.buffer.len() >= BUFFER_CAPACITY {
+            self.flush()?;
+        }
+        Ok(())
+    }
+}
 
 impl Output for StdOut {
     fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.flush()?;
         if let Some(path) = &chunk.relative_file_path {
-            stdout().write("---\n".as_bytes())?;
-            stdout().write(format!("--- CHUNK: {} \n", path.to_string_lossy()).as_bytes())?;
-            stdout().write("---\n".as_bytes())?;
+            self.buffer.write_all("---\n".as_bytes())?;
+            writeln!(self.buffer, "--- CHUNK: {} ", path.to_string_lossy())?;
+            self.buffer.write_all("---\n".as_bytes())?;
         }
         Ok(())
     }
 
     fn write_str(&mut self, data: &str) -> Result<()> {
-        let _ = stdout().write(data.as_bytes())?;
-        Ok(())
+        self.write_bytes(data.as_bytes())
     }
 
     fn write(&mut self, data: char) -> Result<()> {
-        let _ = stdout().write(&[data as u8])?;
-        Ok(())
+        self.write_bytes(data.encode_utf8(&mut [0; 4]).as_bytes())
     }
 
     fn newline(&mut self) -> Result<()> {
-        let _ = stdout().write(&[b'\n'])?;
-        Ok(())
+        self.write_bytes(b"\n")
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush()
+    }
+}
+
+impl Drop for StdOut {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!("failed to flush stdout: {}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::stdout;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    use anyhow::Result;
+
+    use crate::output::StdOut;
+    use crate::Output;
+
+    #[test]
+    fn only_locks_stdout_to_flush() -> Result<()> {
+        let mut output = StdOut::default();
+        output.write_str("")?;
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let _lock = stdout().lock();
+            tx.send(()).unwrap();
+        });
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+        output.finish()
     }
 }
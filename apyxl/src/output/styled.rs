@@ -0,0 +1,272 @@
+use anyhow::Result;
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Line ending written for each newline.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+/// Indentation written at the start of each line.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Indent {
+    Tabs,
+    Spaces(usize),
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+/// Wraps an existing [Output] and rewrites line endings and leading indentation so that generated
+/// code matches a team's conventions regardless of what the [crate::Generator] writes.
+///
+/// Leading spaces on each line are interpreted as groups of `source_width` spaces (the indent width
+/// used by the [crate::Generator], 4 by default) and each group is replaced with the configured
+/// [Indent]. Leftover spaces that don't fill a full group are kept as-is. Leading tabs are treated
+/// as one group each.
+#[derive(Debug)]
+pub struct Styled<O: Output> {
+    output: O,
+    line_ending: LineEnding,
+    indent: Indent,
+    source_width: usize,
+    line_start: Option<LineStart>,
+    pending: String,
+}
+
+/// Leading whitespace seen so far on the current line.
+#[derive(Debug, Default)]
+struct LineStart {
+    groups: usize,
+    spaces: usize,
+}
+
+impl<O: Output> Styled<O> {
+    pub fn new(output: O) -> Self {
+        Self {
+            output,
+            line_ending: LineEnding::default(),
+            indent: Indent::default(),
+            source_width: 4,
+            line_start: Some(LineStart::default()),
+            pending: String::new(),
+        }
+    }
+
+    pub fn line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+
+    pub fn indent(mut self, indent: Indent) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Number of spaces the [crate::Generator] uses per indentation level.
+    pub fn source_width(mut self, source_width: usize) -> Self {
+        self.source_width = source_width.max(1);
+        self
+    }
+
+    pub fn inner(&self) -> &O {
+        &self.output
+    }
+
+    pub fn into_inner(self) -> O {
+        self.output
+    }
+
+    fn write_char(&mut self, c: char) -> Result<()> {
+        if c == '\n' {
+            self.flush_line_start()?;
+            self.write_line_ending()?;
+            self.line_start = Some(LineStart::default());
+            return Ok(());
+        }
+        if let Some(line_start) = &mut self.line_start {
+            match c {
+                ' ' => {
+                    line_start.spaces += 1;
+                    if line_start.spaces == self.source_width {
+                        line_start.groups += 1;
+                        line_start.spaces = 0;
+                    }
+                    return Ok(());
+                }
+                '\t' => {
+                    line_start.groups += 1;
+                    return Ok(());
+                }
+                _ => self.flush_line_start()?,
+            }
+        }
+        self.pending.push(c);
+        Ok(())
+    }
+
+    fn flush_line_start(&mut self) -> Result<()> {
+        let line_start = match self.line_start.take() {
+            None => return Ok(()),
+            Some(line_start) => line_start,
+        };
+        for _ in 0..line_start.groups {
+            match self.indent {
+                Indent::Tabs => self.pending.push('\t'),
+                Indent::Spaces(width) => self.pending.push_str(&" ".repeat(width)),
+            }
+        }
+        self.pending.push_str(&" ".repeat(line_start.spaces));
+        Ok(())
+    }
+
+    fn flush_pending(&mut self) -> Result<()> {
+        if !self.pending.is_empty() {
+            self.output.write_str(&self.pending)?;
+            self.pending.clear();
+        }
+        Ok(())
+    }
+
+    fn write_line_ending(&mut self) -> Result<()> {
+        if self.line_ending == LineEnding::CrLf {
+            self.pending.push('\r');
+        }
+        self.flush_pending()?;
+        self.output.newline()
+    }
+}
+
+impl<O: Output> Output for Styled<O> {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        self.flush_line_start()?;
+        self.flush_pending()?;
+        self.line_start = Some(LineStart::default());
+        self.output.write_chunk(chunk)
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        for c in data.chars() {
+            self.write_char(c)?;
+        }
+        self.flush_pending()
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.write_char(data)?;
+        self.flush_pending()
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.write_char('\n')
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.flush_line_start()?;
+        self.flush_pending()?;
+        self.output.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::output::{Buffer, Indent, LineEnding, Styled};
+    use crate::Output;
+
+    #[test]
+    fn defaults_are_unchanged() -> Result<()> {
+        let data = "a {\n    b\n        c\n}\n";
+        assert_eq!(run(Styled::new(Buffer::default()), data)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn crlf() -> Result<()> {
+        let output = Styled::new(Buffer::default()).line_ending(LineEnding::CrLf);
+        assert_eq!(run(output, "a\nb\n")?, "a\r\nb\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn crlf_newline() -> Result<()> {
+        let mut output = Styled::new(Buffer::default()).line_ending(LineEnding::CrLf);
+        output.write('a')?;
+        output.newline()?;
+        assert_eq!(output.inner().to_string(), "a\r\n");
+        Ok(())
+    }
+
+    #[test]
+    fn tabs() -> Result<()> {
+        let output = Styled::new(Buffer::default()).indent(Indent::Tabs);
+        assert_eq!(
+            run(output, "a {\n    b\n        c\n}")?,
+            "a {\n\tb\n\t\tc\n}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn spaces() -> Result<()> {
+        let output = Styled::new(Buffer::default()).indent(Indent::Spaces(2));
+        assert_eq!(run(output, "a\n    b\n        c")?, "a\n  b\n    c");
+        Ok(())
+    }
+
+    #[test]
+    fn source_width() -> Result<()> {
+        let output = Styled::new(Buffer::default())
+            .source_width(2)
+            .indent(Indent::Tabs);
+        assert_eq!(run(output, "a\n  b\n    c")?, "a\n\tb\n\t\tc");
+        Ok(())
+    }
+
+    #[test]
+    fn leftover_spaces_are_kept() -> Result<()> {
+        let output = Styled::new(Buffer::default()).indent(Indent::Tabs);
+        assert_eq!(run(output, "      a")?, "\t  a");
+        Ok(())
+    }
+
+    #[test]
+    fn inner_spaces_are_kept() -> Result<()> {
+        let output = Styled::new(Buffer::default()).indent(Indent::Tabs);
+        assert_eq!(run(output, "    a    b")?, "\ta    b");
+        Ok(())
+    }
+
+    #[test]
+    fn indent_split_across_writes() -> Result<()> {
+        let mut output = Styled::new(Buffer::default()).indent(Indent::Tabs);
+        output.write_str("  ")?;
+        output.write_str("  ")?;
+        output.write('a')?;
+        assert_eq!(output.inner().to_string(), "\ta");
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_whitespace_flushed_on_finish() -> Result<()> {
+        let mut output = Styled::new(Buffer::default()).indent(Indent::Tabs);
+        output.write_str("a\n    ")?;
+        output.finish()?;
+        assert_eq!(output.inner().to_string(), "a\n\t");
+        Ok(())
+    }
+
+    fn run(mut output: Styled<Buffer>, data: &str) -> Result<String> {
+        output.write_str(data)?;
+        output.finish()?;
+        Ok(output.inner().to_string())
+    }
+}
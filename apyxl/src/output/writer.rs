@@ -0,0 +1,84 @@
+use std::fmt::Debug;
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::model::Chunk;
+use crate::Output;
+
+/// Writes to any supplied [Write], such as a file, socket or in-memory `Vec<u8>`.
+///
+/// Each [Chunk] with a `relative_file_path` is written as a `--- CHUNK: <path>` header line, the
+/// same as [crate::output::StdOut].
+#[derive(Debug)]
+pub struct Writer<W: Write + Debug> {
+    writer: W,
+}
+
+impl<W: Write + Debug> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn inner(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write + Debug> Output for Writer<W> {
+    fn write_chunk(&mut self, chunk: &Chunk) -> Result<()> {
+        if let Some(path) = &chunk.relative_file_path {
+            writeln!(self.writer, "--- CHUNK: {}", path.to_string_lossy())?;
+        }
+        Ok(())
+    }
+
+    fn write_str(&mut self, data: &str) -> Result<()> {
+        self.writer.write_all(data.as_bytes())?;
+        Ok(())
+    }
+
+    fn write(&mut self, data: char) -> Result<()> {
+        self.writer
+            .write_all(data.encode_utf8(&mut [0; 4]).as_bytes())?;
+        Ok(())
+    }
+
+    fn newline(&mut self) -> Result<()> {
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use crate::model::Chunk;
+    use crate::output::Writer;
+    use crate::Output;
+
+    #[test]
+    fn writes_to_writer() -> Result<()> {
+        let mut output = Writer::new(Vec::<u8>::new());
+        output.write_str("a")?;
+        output.write_chunk(&Chunk::with_relative_file_path("b"))?;
+        output.write('é')?;
+        output.newline()?;
+        output.finish()?;
+        assert_eq!(
+            String::from_utf8(output.into_inner())?,
+            "a--- CHUNK: b\né\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tracing::debug_span;
+use xxhash_rust::xxh3::Xxh3;
+
+use crate::diagnostic::Diagnostics;
+use crate::input::{ChunkBuffer, Input};
+use crate::model::{chunk, Api, Builder, Chunk};
+use crate::parser::{Config, Parser};
+
+/// Caches the [Api] parsed from each [Chunk] in a directory on disk so that subsequent runs only
+/// parse chunks that changed.
+///
+/// Entries are keyed by a hash of the [Chunk], its data, the [Parser] type, the [Config] and the
+/// apyxl version, so any change to those results in a re-parse. Entries that are no longer used
+/// are left in the directory.
+///
+/// Each [Chunk] is parsed on its own on a cache miss, so this only works with [Parser]s that
+/// don't depend on other [Chunk]s of the same [Input].
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+/// [Api]s of each [Chunk] of an [Input] loaded from a [Cache], in their serialized form. Merging
+/// them into a [Builder] borrows from this, the same way parsing borrows from the [Input].
+#[derive(Debug, Default)]
+pub struct CachedChunks {
+    entries: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry<'a> {
+    #[serde(borrow)]
+    api: Api<'a>,
+    chunks: Vec<chunk::Metadata>,
+    /// Warnings from parsing, e.g. syntax skipped in [crate::Mode::Lenient].
+    #[serde(default)]
+    diagnostics: Diagnostics,
+}
+
+impl Cache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("create parse cache dir '{}'", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    /// Loads the cached [Api] of every [Chunk] in `input`, parsing and caching any that are
+    /// missing.
+    pub fn load<P: Parser, I: Input + ?Sized>(
+        &self,
+        parser: &P,
+        config: &Config,
+        input: &I,
+    ) -> Result<CachedChunks> {
+        let config_json = serde_json::to_string(config)?;
+        let mut entries = vec![];
+        for chunk in input.chunk_iter() {
+            let (chunk, data) = chunk?;
+            let _span = debug_span!("load_chunk", path = ?chunk.relative_file_path).entered();
+            let path = self
+                .dir
+                .join(format!("{:032x}.json", key::<P>(&config_json, chunk, data)));
+            let entry = match fs::read_to_string(&path) {
+                Ok(entry) => {
+                    debug!("parse cache hit: {:?}", chunk.relative_file_path);
+                    entry
+                }
+                Err(_) => {
+                    debug!("parse cache miss: {:?}", chunk.relative_file_path);
+                    let entry = parse_chunk(parser, config, chunk, data)?;
+                    fs::write(&path, &entry)
+                        .with_context(|| format!("write parse cache '{}'", path.display()))?;
+                    entry
+                }
+            };
+            entries.push(entry);
+        }
+        Ok(CachedChunks { entries })
+    }
+}
+
+impl CachedChunks {
+    /// Merges the [Api], [chunk::Metadata] and parse warnings of every [Chunk] into `builder`.
+    pub fn merge_into<'a>(&'a self, builder: &mut Builder<'a>) -> Result<()> {
+        for entry in &self.entries {
+            let entry: Entry<'a> = serde_json::from_str(entry)
+                .map_err(|err| anyhow!("corrupt parse cache: {}", err))?;
+            builder.merge(entry.api);
+            builder.metadata_mut().chunks.extend(entry.chunks);
+            builder.diagnostics_mut().extend(entry.diagnostics);
+        }
+        Ok(())
+    }
+}
+
+fn parse_chunk<P: Parser>(
+    parser: &P,
+    config: &Config,
+    chunk: &Chunk,
+    data: &str,
+) -> Result<String> {
+    let mut input = ChunkBuffer::new();
+    input.add_chunk(chunk.clone(), data);
+    let mut builder = Builder::default();
+    parser.parse(config, &mut input, &mut builder)?;
+    let chunks = std::mem::take(&mut builder.metadata_mut().chunks);
+    let diagnostics = std::mem::take(builder.diagnostics_mut());
+    let entry = Entry {
+        api: builder.into_api(),
+        chunks,
+        diagnostics,
+    };
+    Ok(serde_json::to_string(&entry)?)
+}
+
+fn key<P: Parser>(config_json: &str, chunk: &Chunk, data: &str) -> u128 {
+    let path = chunk
+        .relative_file_path
+        .as_ref()
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut hasher = Xxh3::new();
+    for part in [
+        env!("CARGO_PKG_VERSION"),
+        std::any::type_name::<P>(),
+        config_json,
+        &path,
+        data,
+    ] {
+        // Length-prefixed so that parts can't run into each other.
+        hasher.update(&(part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher.digest128()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::input::ChunkBuffer;
+    use crate::model::{Builder, Chunk};
+    use crate::parser::{Cache, Config};
+    use crate::{parser, Parser};
+
+    fn input(b_data: &str) -> ChunkBuffer {
+        let mut input = ChunkBuffer::new();
+        input.add_chunk(Chunk::with_relative_file_path("a.rs"), "struct dto {}");
+        input.add_chunk(Chunk::with_relative_file_path("b/c.rs"), b_data);
+        input
+    }
+
+    fn entry_count(cache_dir: &std::path::Path) -> Result<usize> {
+        Ok(fs::read_dir(cache_dir)?.count())
+    }
+
+    #[test]
+    fn matches_uncached_parse() -> Result<()> {
+        let dir = tempdir()?;
+        let config = Config::default();
+        let parser = parser::Rust::default();
+        let b_data = "mod ns { struct a {} struct dto { field: a } }";
+
+        let mut uncached_input = input(b_data);
+        let mut uncached = Builder::default();
+        parser.parse(&config, &mut uncached_input, &mut uncached)?;
+        let uncached = uncached.build().unwrap();
+
+        let cache = Cache::new(dir.path())?;
+        // Run twice so the second merge is entirely from cache hits.
+        for _ in 0..2 {
+            let cached_input = input(b_data);
+            let chunks = cache.load(&parser, &config, &cached_input)?;
+            let mut cached = Builder::default();
+            chunks.merge_into(&mut cached)?;
+            let cached = cached.build().unwrap();
+            assert_eq!(cached.api(), uncached.api());
+            assert_eq!(
+                cached.metadata().chunks.len(),
+                uncached.metadata().chunks.len()
+            );
+        }
+        assert_eq!(entry_count(dir.path())?, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn changed_chunk_is_reparsed() -> Result<()> {
+        let dir = tempdir()?;
+        let config = Config::default();
+        let parser = parser::Rust::default();
+        let cache = Cache::new(dir.path())?;
+        cache.load(&parser, &config, &input("struct old {}"))?;
+        let chunks = cache.load(&parser, &config, &input("struct new {}"))?;
+        assert_eq!(entry_count(dir.path())?, 3);
+
+        let mut builder = Builder::default();
+        chunks.merge_into(&mut builder)?;
+        let model = builder.build().unwrap();
+        let b = model.api().namespace("b").unwrap().namespace("c").unwrap();
+        assert!(b.dto("new").is_some());
+        assert!(b.dto("old").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_errors_are_not_cached() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = Cache::new(dir.path())?;
+        let result = cache.load(
+            &parser::Rust::default(),
+            &Config::default(),
+            &input("struct {"),
+        );
+        assert!(result.is_err());
+        assert_eq!(entry_count(dir.path())?, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn escaped_attribute_value() -> Result<()> {
+        let dir = tempdir()?;
+        let config = Config::default();
+        let parser = parser::Rust::default();
+        let cache = Cache::new(dir.path())?;
+        let b_data = r#"#[api(tag = "a\b")] struct tagged {}"#;
+        // The second load is a cache hit, which must deserialize the escaped value.
+        for _ in 0..2 {
+            let chunks = cache.load(&parser, &config, &input(b_data))?;
+            let mut builder = Builder::default();
+            chunks.merge_into(&mut builder)?;
+            let model = builder.build().unwrap();
+            let b = model.api().namespace("b").unwrap().namespace("c").unwrap();
+            let user = &b.dto("tagged").unwrap().attributes.user;
+            assert_eq!(user[0].data[0].value, r"a\b");
+        }
+        Ok(())
+    }
+}
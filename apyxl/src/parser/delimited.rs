@@ -0,0 +1,820 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use anyhow::Result;
+use log::debug;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tracing::debug_span;
+
+use crate::diagnostic::{Diagnostic, Diagnostics, Severity, Span};
+use crate::model::{
+    Api, Attributes, Comment, Dto, EntityId, Enum, EnumValue, EnumValueNumber, Field, Namespace,
+    NamespaceChild, Rpc, Type, UNDEFINED_NAMESPACE,
+};
+use crate::parser::Config;
+use crate::{model, Input, Mode, Parser};
+
+/// A simple, language-neutral, line-based API format, e.g. for exchanging APIs with tools that
+/// don't have a parser of their own.
+///
+/// Each line is a single definition: a keyword followed by its arguments, split by the
+/// [DelimitedOptions::separator]. Blocks opened by `namespace`, `dto`, `rpc` and `enum` are closed
+/// by `end`. Indentation is ignored. With the default [DelimitedOptions]:
+/// ```text
+/// # Ignored comment.
+/// namespace, shop
+///     // Doc comment of the following definition.
+///     dto, Page, T
+///         field, items, list<T>
+///         field, next, optional<string>
+///         rpc, len
+///             return, u64
+///         end
+///     end
+///     enum, Size
+///         value, Small, 1
+///         value, Large
+///     end
+///     rpc, get_item
+///         param, id, u64
+///         return, optional<Item>
+///         error, string
+///     end
+/// end
+/// ```
+///
+/// Any arguments after the name of a `dto` or `rpc` are its generic type parameters. Enum values
+/// without a number are numbered one after the previous value, like rust.
+///
+/// Types are `bool`, `u8`-`u128`, `i8`-`i128`, `f8`-`f128`, `string`, `bytes`, `list<T>`,
+/// `map<K, V>`, `optional<T>`, a [crate::parser::UserType], a type parameter, or otherwise the
+/// `.`-separated id of a dto or enum within the API, e.g. `shop.Size`.
+#[derive(Default)]
+pub struct Delimited {
+    options: DelimitedOptions,
+}
+
+/// Options for the [Delimited] parser, from the `parser.delimited` section of the [Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct DelimitedOptions {
+    /// Separates the keyword and each argument of a line. Separators within the `<>` of a type,
+    /// e.g. `map<K, V>`, are part of the type. Defaults to `,`.
+    pub separator: String,
+    /// Lines starting with this are ignored. Defaults to `#`.
+    pub comment: String,
+    /// Lines starting with this are comments of the following definition, or trailing comments of
+    /// the enclosing block if there isn't one. Defaults to `//`.
+    pub doc_comment: String,
+    pub keywords: DelimitedKeywords,
+}
+
+/// Keyword of each kind of line in the [Delimited] format. Each defaults to its field name.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct DelimitedKeywords {
+    pub namespace: String,
+    pub dto: String,
+    pub field: String,
+    pub rpc: String,
+    pub param: String,
+    #[serde(rename = "return")]
+    pub returns: String,
+    pub error: String,
+    #[serde(rename = "enum")]
+    pub en: String,
+    pub value: String,
+    pub end: String,
+}
+
+impl Default for DelimitedOptions {
+    fn default() -> Self {
+        Self {
+            separator: ",".to_string(),
+            comment: "#".to_string(),
+            doc_comment: "//".to_string(),
+            keywords: DelimitedKeywords::default(),
+        }
+    }
+}
+
+impl Default for DelimitedKeywords {
+    fn default() -> Self {
+        Self {
+            namespace: "namespace".to_string(),
+            dto: "dto".to_string(),
+            field: "field".to_string(),
+            rpc: "rpc".to_string(),
+            param: "param".to_string(),
+            returns: "return".to_string(),
+            error: "error".to_string(),
+            en: "enum".to_string(),
+            value: "value".to_string(),
+            end: "end".to_string(),
+        }
+    }
+}
+
+impl Delimited {
+    pub fn new(options: DelimitedOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl Parser for Delimited {
+    fn parse<'a, I: Input + ?Sized + 'a>(
+        &self,
+        config: &'a Config,
+        input: &'a mut I,
+        builder: &mut model::Builder<'a>,
+    ) -> Result<()> {
+        for chunk in input.chunk_iter() {
+            let (chunk, data) = chunk?;
+            let _span = debug_span!("parse_chunk", path = ?chunk.relative_file_path).entered();
+            debug!("parsing chunk {:?}", chunk.relative_file_path);
+
+            let mut state = State::new(&self.options, config);
+            let mut errors = Diagnostics::default();
+            for (range, line) in lines(data) {
+                if let Err(message) = state.line(line, range.clone()) {
+                    let severity = match config.mode {
+                        Mode::Strict => Severity::Error,
+                        Mode::Lenient => Severity::Warning,
+                    };
+                    let diagnostic = Diagnostic::new(severity, message)
+                        .chunk(chunk)
+                        .span(Span::new(data, range));
+                    match severity {
+                        Severity::Error => {
+                            errors.push(diagnostic.note("errors encountered while parsing"))
+                        }
+                        Severity::Warning => builder
+                            .diagnostics_mut()
+                            .push(diagnostic.note("invalid line skipped in lenient mode")),
+                    }
+                }
+            }
+
+            match state.finish() {
+                Ok(api) if errors.is_empty() => builder.merge_from_chunk(api, chunk),
+                Ok(_) => return Err(errors.into()),
+                Err((message, start)) => {
+                    errors.push(
+                        Diagnostic::error(message)
+                            .chunk(chunk)
+                            .span(Span::new(data, start..data.len()))
+                            .note("errors encountered while parsing"),
+                    );
+                    return Err(errors.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Each non-empty line of `data`, trimmed, along with its byte range within `data`.
+fn lines(data: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    data.split_inclusive('\n')
+        .scan(0, |offset, line| {
+            let start = *offset + (line.len() - line.trim_start().len());
+            *offset += line.len();
+            let trimmed = line.trim();
+            Some((start..start + trimmed.len(), trimmed))
+        })
+        .filter(|(_, line)| !line.is_empty())
+}
+
+/// A definition that is still open, i.e. hasn't reached its `end` yet.
+enum Block<'a> {
+    Namespace(Namespace<'a>),
+    Dto(Dto<'a>),
+    Rpc(Rpc<'a>),
+    Enum(Enum<'a>),
+}
+
+impl<'a> Block<'a> {
+    fn attributes_mut(&mut self) -> &mut Attributes<'a> {
+        match self {
+            Block::Namespace(namespace) => &mut namespace.attributes,
+            Block::Dto(dto) => &mut dto.attributes,
+            Block::Rpc(rpc) => &mut rpc.attributes,
+            Block::Enum(en) => &mut en.attributes,
+        }
+    }
+
+    fn type_params(&self) -> &[&'a str] {
+        match self {
+            Block::Dto(dto) => &dto.type_params,
+            Block::Rpc(rpc) => &rpc.type_params,
+            Block::Namespace(_) | Block::Enum(_) => &[],
+        }
+    }
+}
+
+struct State<'a, 'o> {
+    options: &'o DelimitedOptions,
+    config: &'a Config,
+    /// Open blocks, starting with the root namespace of the chunk.
+    blocks: Vec<Block<'a>>,
+    /// Doc comments not yet attached to a definition.
+    comments: Vec<Comment<'a>>,
+    /// Lines of the doc comment currently being read.
+    comment_lines: Vec<&'a str>,
+}
+
+impl<'a, 'o> State<'a, 'o> {
+    fn new(options: &'o DelimitedOptions, config: &'a Config) -> Self {
+        Self {
+            options,
+            config,
+            blocks: vec![Block::Namespace(Api {
+                name: Cow::Borrowed(UNDEFINED_NAMESPACE),
+                ..Default::default()
+            })],
+            comments: vec![],
+            comment_lines: vec![],
+        }
+    }
+
+    /// Parses a single trimmed, non-empty `line`. On error the line has no effect.
+    fn line(&mut self, line: &'a str, range: Range<usize>) -> Result<(), String> {
+        let options = self.options;
+        if !options.doc_comment.is_empty() {
+            if let Some(text) = line.strip_prefix(options.doc_comment.as_str()) {
+                self.comment_lines
+                    .push(text.strip_prefix(' ').unwrap_or(text));
+                return Ok(());
+            }
+        }
+        self.flush_comment();
+        if !options.comment.is_empty() && line.starts_with(options.comment.as_str()) {
+            return Ok(());
+        }
+
+        let keywords = &options.keywords;
+        let args = split(line, &options.separator);
+        let (keyword, args) = args.split_first().expect("split yields at least one part");
+        let keyword = *keyword;
+        let parent = self.blocks.last().expect("root namespace is never closed");
+
+        if keyword == keywords.end {
+            expect_args(keyword, args, 0, 0)?;
+            if self.blocks.len() == 1 {
+                return Err(format!("'{}' without an open definition", keyword));
+            }
+            let mut block = self.blocks.pop().expect("checked above");
+            let attributes = block.attributes_mut();
+            attributes.trailing_comments = self.take_comments();
+            let span = attributes.span.get_or_insert(range.clone());
+            span.end = range.end;
+            let child = match block {
+                Block::Namespace(namespace) => NamespaceChild::Namespace(namespace),
+                Block::Dto(dto) => NamespaceChild::Dto(dto),
+                Block::Rpc(rpc) => NamespaceChild::Rpc(rpc),
+                Block::Enum(en) => NamespaceChild::Enum(en),
+            };
+            match (self.blocks.last_mut().expect("checked above"), child) {
+                (Block::Dto(dto), NamespaceChild::Rpc(rpc)) => dto.rpcs.push(rpc),
+                (Block::Namespace(namespace), child) => namespace.children.push(child),
+                _ => unreachable!("blocks are only opened within a valid parent"),
+            }
+        } else if keyword == keywords.namespace {
+            expect_parent(keyword, matches!(parent, Block::Namespace(_)))?;
+            expect_args(keyword, args, 1, 1)?;
+            let name = name(args[0])?;
+            let attributes = self.attributes(range);
+            self.blocks.push(Block::Namespace(Namespace {
+                name: Cow::Borrowed(name),
+                attributes,
+                ..Default::default()
+            }));
+        } else if keyword == keywords.dto {
+            expect_parent(keyword, matches!(parent, Block::Namespace(_)))?;
+            expect_args(keyword, args, 1, usize::MAX)?;
+            let name = name(args[0])?;
+            let type_params = args[1..]
+                .iter()
+                .map(|arg| self::name(arg))
+                .collect::<Result<_, _>>()?;
+            let attributes = self.attributes(range);
+            self.blocks.push(Block::Dto(Dto {
+                name,
+                type_params,
+                attributes,
+                ..Default::default()
+            }));
+        } else if keyword == keywords.rpc {
+            expect_parent(
+                keyword,
+                matches!(parent, Block::Namespace(_) | Block::Dto(_)),
+            )?;
+            expect_args(keyword, args, 1, usize::MAX)?;
+            let name = name(args[0])?;
+            let type_params = args[1..]
+                .iter()
+                .map(|arg| self::name(arg))
+                .collect::<Result<_, _>>()?;
+            let attributes = self.attributes(range);
+            self.blocks.push(Block::Rpc(Rpc {
+                name,
+                type_params,
+                attributes,
+                ..Default::default()
+            }));
+        } else if keyword == keywords.en {
+            expect_parent(keyword, matches!(parent, Block::Namespace(_)))?;
+            expect_args(keyword, args, 1, 1)?;
+            let name = name(args[0])?;
+            let attributes = self.attributes(range);
+            self.blocks.push(Block::Enum(Enum {
+                name,
+                attributes,
+                ..Default::default()
+            }));
+        } else if keyword == keywords.field || keyword == keywords.param {
+            let is_field = keyword == keywords.field;
+            expect_parent(
+                keyword,
+                matches!(
+                    (parent, is_field),
+                    (Block::Dto(_), true) | (Block::Rpc(_), false)
+                ),
+            )?;
+            expect_args(keyword, args, 2, 2)?;
+            let field = Field {
+                name: name(args[0])?,
+                ty: self.ty(args[1])?,
+                attributes: self.attributes(range),
+            };
+            match self.blocks.last_mut() {
+                Some(Block::Dto(dto)) => dto.fields.push(field),
+                Some(Block::Rpc(rpc)) => rpc.params.push(field),
+                _ => unreachable!("checked by expect_parent"),
+            }
+        } else if keyword == keywords.returns || keyword == keywords.error {
+            expect_parent(keyword, matches!(parent, Block::Rpc(_)))?;
+            expect_args(keyword, args, 1, 1)?;
+            let ty = self.ty(args[0])?;
+            let Some(Block::Rpc(rpc)) = self.blocks.last_mut() else {
+                unreachable!("checked by expect_parent")
+            };
+            let target = if keyword == keywords.returns {
+                &mut rpc.return_type
+            } else {
+                &mut rpc.error_type
+            };
+            if target.is_some() {
+                return Err(format!(
+                    "'{}' is already set for rpc '{}'",
+                    keyword, rpc.name
+                ));
+            }
+            *target = Some(ty);
+        } else if keyword == keywords.value {
+            let Block::Enum(en) = parent else {
+                return Err(format!("'{}' is not allowed here", keyword));
+            };
+            expect_args(keyword, args, 1, 2)?;
+            let name = name(args[0])?;
+            let number = match args.get(1) {
+                Some(number) => number
+                    .parse::<EnumValueNumber>()
+                    .map_err(|_| format!("invalid enum value number '{}'", number))?,
+                None => en.values.last().map_or(0, |value| value.number + 1),
+            };
+            let attributes = self.attributes(range);
+            let Some(Block::Enum(en)) = self.blocks.last_mut() else {
+                unreachable!("checked above")
+            };
+            en.values.push(EnumValue {
+                name,
+                number,
+                attributes,
+            });
+        } else {
+            return Err(format!("unknown keyword '{}'", keyword));
+        }
+        Ok(())
+    }
+
+    /// Returns the root namespace once all lines are parsed, or an error and the start of the
+    /// first definition that wasn't closed.
+    fn finish(mut self) -> Result<Api<'a>, (String, usize)> {
+        self.flush_comment();
+        if self.blocks.len() > 1 {
+            let start = self.blocks[1]
+                .attributes_mut()
+                .span
+                .as_ref()
+                .map_or(0, |span| span.start);
+            return Err((
+                format!("missing '{}' of definition", self.options.keywords.end),
+                start,
+            ));
+        }
+        let Some(Block::Namespace(mut api)) = self.blocks.pop() else {
+            unreachable!("root is always a namespace")
+        };
+        api.attributes.trailing_comments = self.comments;
+        Ok(api)
+    }
+
+    fn flush_comment(&mut self) {
+        if !self.comment_lines.is_empty() {
+            self.comments
+                .push(Comment::from(std::mem::take(&mut self.comment_lines)));
+        }
+    }
+
+    fn take_comments(&mut self) -> Vec<Comment<'a>> {
+        std::mem::take(&mut self.comments)
+    }
+
+    fn attributes(&mut self, range: Range<usize>) -> Attributes<'a> {
+        Attributes {
+            comments: self.take_comments(),
+            span: Some(range),
+            ..Default::default()
+        }
+    }
+
+    /// Parses `value` as a [Type], see [Delimited] for the supported types.
+    fn ty(&self, value: &str) -> Result<Type, String> {
+        let value = value.trim();
+        if let Some(name) = self
+            .config
+            .user_types
+            .iter()
+            .find_map(|ty| ty.matches(value))
+        {
+            return Ok(Type::User(name));
+        }
+        let ty = match value {
+            "bool" => Type::Bool,
+            "u8" => Type::U8,
+            "u16" => Type::U16,
+            "u32" => Type::U32,
+            "u64" => Type::U64,
+            "u128" => Type::U128,
+            "i8" => Type::I8,
+            "i16" => Type::I16,
+            "i32" => Type::I32,
+            "i64" => Type::I64,
+            "i128" => Type::I128,
+            "f8" => Type::F8,
+            "f16" => Type::F16,
+            "f32" => Type::F32,
+            "f64" => Type::F64,
+            "f128" => Type::F128,
+            "string" => Type::String,
+            "bytes" => Type::Bytes,
+            _ => return self.compound_ty(value),
+        };
+        Ok(ty)
+    }
+
+    fn compound_ty(&self, value: &str) -> Result<Type, String> {
+        let invalid = || format!("invalid type '{}'", value);
+        if let Some((name, rest)) = value.split_once('<') {
+            let inner = rest.strip_suffix('>').ok_or_else(invalid)?;
+            let args = split(inner, ",")
+                .into_iter()
+                .map(|arg| self.ty(arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            return match (name.trim(), args.as_slice()) {
+                ("list", [ty]) => Ok(Type::new_array(ty.clone())),
+                ("optional", [ty]) => Ok(Type::new_optional(ty.clone())),
+                ("map", [key, value]) => Ok(Type::new_map(key.clone(), value.clone())),
+                _ => Err(invalid()),
+            };
+        }
+
+        if let Some(param) = self
+            .blocks
+            .iter()
+            .flat_map(Block::type_params)
+            .find(|param| **param == value)
+        {
+            return Ok(Type::Generic(param.to_string()));
+        }
+        for component in value.split('.') {
+            name(component).map_err(|_| invalid())?;
+        }
+        Ok(Type::Api(EntityId::new_unqualified(value), vec![]))
+    }
+}
+
+/// Splits `line` by `separator`, except within `<>`, and trims each part.
+fn split<'a>(line: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < line.len() {
+        let rest = &line[i..];
+        if depth == 0 && !separator.is_empty() && rest.starts_with(separator) {
+            parts.push(line[start..i].trim());
+            i += separator.len();
+            start = i;
+            continue;
+        }
+        let c = rest.chars().next().expect("i is within line");
+        match c {
+            '<' => depth += 1,
+            '>' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        i += c.len_utf8();
+    }
+    parts.push(line[start..].trim());
+    parts
+}
+
+/// Like rust, names are identifiers, which may be non-ASCII.
+fn name(value: &str) -> Result<&str, String> {
+    let mut chars = value.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if valid {
+        Ok(value)
+    } else {
+        Err(format!("invalid name '{}'", value))
+    }
+}
+
+fn expect_parent(keyword: &str, allowed: bool) -> Result<(), String> {
+    if allowed {
+        Ok(())
+    } else {
+        Err(format!("'{}' is not allowed here", keyword))
+    }
+}
+
+fn expect_args(keyword: &str, args: &[&str], min: usize, max: usize) -> Result<(), String> {
+    if (min..=max).contains(&args.len()) {
+        return Ok(());
+    }
+    let expected = if min == max {
+        min.to_string()
+    } else if max == usize::MAX {
+        format!("at least {}", min)
+    } else {
+        format!("{} to {}", min, max)
+    };
+    Err(format!(
+        "'{}' expects {} argument(s), found {}",
+        keyword,
+        expected,
+        args.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use lazy_static::lazy_static;
+
+    use crate::diagnostic::Diagnostics;
+    use crate::model::{Builder, Comment, EntityId, Model, Type};
+    use crate::parser::{Config, Delimited, DelimitedOptions, UserType};
+    use crate::{input, Mode, Parser};
+
+    lazy_static! {
+        static ref CONFIG: Config = Config {
+            user_types: vec![UserType {
+                parse: "uuid".to_string(),
+                name: "user".to_string(),
+                targets: Default::default(),
+                glob: false,
+            }],
+            ..Default::default()
+        };
+    }
+
+    fn parse<'a>(
+        parser: &Delimited,
+        config: &'a Config,
+        input: &'a mut input::Buffer,
+    ) -> Result<Model<'a>> {
+        let mut builder = Builder::default();
+        parser.parse(config, input, &mut builder)?;
+        Ok(builder.build().unwrap())
+    }
+
+    #[test]
+    fn all_definitions() -> Result<()> {
+        let mut input = input::Buffer::new(
+            r#"
+            # ignored
+            namespace, shop
+                // An item.
+                // Second line.
+                dto, Item
+                    field, id, u64
+                    field, tags, list<string>
+                    field, counts, map<string, optional<i32>>
+                    field, owner, uuid
+                    field, size, shop.Size
+                    rpc, len
+                        return, u64
+                    end
+                end
+                enum, Size
+                    value, Small, 1
+                    value, Large
+                    value, Unknown, -1
+                end
+                rpc, get_item
+                    // The id.
+                    param, id, u64
+                    return, optional<Item>
+                    error, string
+                end
+            end
+            "#,
+        );
+        let model = parse(&Delimited::default(), &CONFIG, &mut input)?;
+        let shop = model.api().namespace("shop").unwrap();
+
+        let item = shop.dto("Item").unwrap();
+        assert_eq!(
+            item.attributes.comments,
+            vec![Comment::unowned(&["An item.", "Second line."])]
+        );
+        assert_eq!(item.field("id").unwrap().ty, Type::U64);
+        assert_eq!(
+            item.field("tags").unwrap().ty,
+            Type::new_array(Type::String)
+        );
+        assert_eq!(
+            item.field("counts").unwrap().ty,
+            Type::new_map(Type::String, Type::new_optional(Type::I32))
+        );
+        assert_eq!(
+            item.field("owner").unwrap().ty,
+            Type::User("user".to_string())
+        );
+        assert_eq!(
+            item.field("size").unwrap().ty,
+            Type::Api(EntityId::try_from("shop.e:Size")?, vec![])
+        );
+        assert_eq!(item.rpc("len").unwrap().return_type, Some(Type::U64));
+
+        let size = shop.en("Size").unwrap();
+        let numbers = size.values.iter().map(|v| v.number).collect::<Vec<_>>();
+        assert_eq!(numbers, vec![1, 2, -1]);
+
+        let rpc = shop.rpc("get_item").unwrap();
+        assert_eq!(
+            rpc.param("id").unwrap().attributes.comments,
+            vec![Comment::unowned(&["The id."])]
+        );
+        assert_eq!(
+            rpc.return_type,
+            Some(Type::new_optional(Type::Api(
+                EntityId::try_from("shop.d:Item")?,
+                vec![]
+            )))
+        );
+        assert_eq!(rpc.error_type, Some(Type::String));
+        Ok(())
+    }
+
+    #[test]
+    fn type_params() -> Result<()> {
+        let mut input = input::Buffer::new(
+            r#"
+            dto, Page, T
+                field, items, list<T>
+                rpc, map, U
+                    param, f, map<T, U>
+                    return, U
+                end
+            end
+            "#,
+        );
+        let model = parse(&Delimited::default(), &CONFIG, &mut input)?;
+        let dto = model.api().dto("Page").unwrap();
+        assert_eq!(dto.type_params, vec!["T"]);
+        assert_eq!(
+            dto.field("items").unwrap().ty,
+            Type::new_array(Type::Generic("T".to_string()))
+        );
+        let rpc = dto.rpc("map").unwrap();
+        assert_eq!(
+            rpc.param("f").unwrap().ty,
+            Type::new_map(
+                Type::Generic("T".to_string()),
+                Type::Generic("U".to_string())
+            )
+        );
+        assert_eq!(rpc.return_type, Some(Type::Generic("U".to_string())));
+        Ok(())
+    }
+
+    #[test]
+    fn custom_options() -> Result<()> {
+        let options: DelimitedOptions = serde_json::from_str(
+            r#"{
+                "separator": "\t",
+                "comment": ";",
+                "keywords": { "dto": "message", "end": "}" }
+            }"#,
+        )?;
+        let mut input = input::Buffer::new("; ignored\nmessage\tdto\nfield\tx\tmap<u8, u8>\n}\n");
+        let model = parse(&Delimited::new(options), &CONFIG, &mut input)?;
+        assert_eq!(
+            model.api().dto("dto").unwrap().field("x").unwrap().ty,
+            Type::new_map(Type::U8, Type::U8)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn spans_and_trailing_comments() -> Result<()> {
+        let data = "dto, a\nfield, x, u8\n// trailing\nend\n";
+        let mut input = input::Buffer::new(data);
+        let model = parse(&Delimited::default(), &CONFIG, &mut input)?;
+        let dto = model.api().dto("a").unwrap();
+        assert_eq!(dto.attributes.span, Some(0..data.len() - 1));
+        assert_eq!(
+            dto.attributes.trailing_comments,
+            vec![Comment::unowned(&["trailing"])]
+        );
+        assert_eq!(dto.fields[0].attributes.span, Some(7..19));
+        Ok(())
+    }
+
+    #[test]
+    fn strict_errors() {
+        for data in [
+            "unknown, a",
+            "field, a, u8",
+            "dto, a\nfield, x\nend",
+            "dto, a\nfield, x, map<u8>\nend",
+            "dto, 1a\nend",
+            "enum, a\nvalue, x, one\nend",
+            "rpc, a\nreturn, u8\nreturn, u8\nend",
+            "end",
+            "dto, a",
+        ] {
+            let mut input = input::Buffer::new(data);
+            let mut builder = Builder::default();
+            let err = Delimited::default()
+                .parse(&CONFIG, &mut input, &mut builder)
+                .unwrap_err();
+            let diagnostics = err.downcast_ref::<Diagnostics>().unwrap();
+            assert!(diagnostics.has_errors(), "{}", data);
+        }
+    }
+
+    #[test]
+    fn strict_error_location() {
+        let mut input = input::Buffer::new("dto, a\n  field, x, map<u8>\nend");
+        let mut builder = Builder::default();
+        let err = Delimited::default()
+            .parse(&CONFIG, &mut input, &mut builder)
+            .unwrap_err();
+        let diagnostic = err
+            .downcast_ref::<Diagnostics>()
+            .unwrap()
+            .iter()
+            .next()
+            .unwrap()
+            .clone();
+        assert_eq!(diagnostic.message, "invalid type 'map<u8>'");
+        let span = diagnostic.span.unwrap();
+        assert_eq!((span.line, span.column), (2, 3));
+    }
+
+    #[test]
+    fn lenient_skips_invalid_lines() -> Result<()> {
+        let config = Config {
+            mode: Mode::Lenient,
+            ..Default::default()
+        };
+        let mut input = input::Buffer::new("dto, a\nfield, x, u8\nunknown\nfield, y\nend");
+        let mut builder = Builder::default();
+        Delimited::default().parse(&config, &mut input, &mut builder)?;
+        assert_eq!(builder.diagnostics().warnings().count(), 2);
+        let model = builder.build().unwrap();
+        assert_eq!(model.api().dto("a").unwrap().fields.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn lenient_still_errors_on_unclosed_definition() {
+        let config = Config {
+            mode: Mode::Lenient,
+            ..Default::default()
+        };
+        let mut input = input::Buffer::new("dto, a\nfield, x, u8");
+        let mut builder = Builder::default();
+        assert!(Delimited::default()
+            .parse(&config, &mut input, &mut builder)
+            .is_err());
+    }
+}
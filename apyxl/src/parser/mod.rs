@@ -1,19 +1,74 @@
 use anyhow::Result;
 
-pub use config::*;
-pub use rust::Rust;
+pub use crate::config::{Config, UserType};
+#[cfg(feature = "fs")]
+pub use cache::{Cache, CachedChunks};
+pub use delimited::{Delimited, DelimitedKeywords, DelimitedOptions};
+pub use rust::{Rust, RustOptions};
 
 use crate::input::Input;
 use crate::model;
 
-mod config;
+#[cfg(feature = "fs")]
+mod cache;
+mod delimited;
 mod rust;
 
 pub trait Parser {
-    fn parse<'a, I: Input + 'a>(
+    fn parse<'a, I: Input + ?Sized + 'a>(
         &self,
         config: &'a Config,
         input: &'a mut I,
         builder: &mut model::Builder<'a>,
     ) -> Result<()>;
+
+    /// File extensions, without the leading `.`, of the API source files this parser reads. Only
+    /// used for introspection, e.g. by [crate::Registry::parser_entries].
+    fn extensions(&self) -> &'static [&'static str] {
+        &[]
+    }
+}
+
+/// Object-safe version of [Parser], so that parsers can be stored and selected at runtime, e.g. by
+/// the [crate::Registry]. Implemented automatically for every [Parser].
+pub trait DynParser {
+    fn parse_dyn<'a>(
+        &self,
+        config: &'a Config,
+        input: &'a mut (dyn Input + 'a),
+        builder: &mut model::Builder<'a>,
+    ) -> Result<()>;
+
+    fn extensions_dyn(&self) -> &'static [&'static str];
+}
+
+impl<P: Parser> DynParser for P {
+    fn parse_dyn<'a>(
+        &self,
+        config: &'a Config,
+        input: &'a mut (dyn Input + 'a),
+        builder: &mut model::Builder<'a>,
+    ) -> Result<()> {
+        self.parse(config, input, builder)
+    }
+
+    fn extensions_dyn(&self) -> &'static [&'static str] {
+        self.extensions()
+    }
+}
+
+impl Parser for Box<dyn DynParser> {
+    fn parse<'a, I: Input + ?Sized + 'a>(
+        &self,
+        config: &'a Config,
+        input: &'a mut I,
+        builder: &mut model::Builder<'a>,
+    ) -> Result<()> {
+        self.as_ref()
+            .parse_dyn(config, input.as_dyn_input(), builder)
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        self.as_ref().extensions_dyn()
+    }
 }
@@ -1,90 +1,298 @@
 use std::borrow::Cow;
+use std::ops::Range;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use chumsky::error;
+use chumsky::input::MapExtra;
 use chumsky::prelude::*;
+use chumsky::text::Char;
 use log::debug;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tracing::debug_span;
 
+use crate::diagnostic::{Diagnostic, Diagnostics, Severity, Span};
 use crate::model::{
-    attribute, Api, Attributes, Comment, Dto, EntityId, Enum, EnumValue, EnumValueNumber, Field,
-    Namespace, NamespaceChild, Rpc, Type, UNDEFINED_NAMESPACE,
+    attribute, Api, Attributes, Chunk, Comment, CommentStyle, Dto, EntityId, Enum, EnumValue,
+    EnumValueNumber, Field, Namespace, NamespaceChild, Rpc, Type, UNDEFINED_NAMESPACE,
 };
 use crate::parser::Config;
-use crate::{model, Input};
+use crate::{model, Input, Mode};
 use crate::{rust_util, Parser as ApyxlParser};
 
 type Error<'a> = extra::Err<Simple<'a, char>>;
+type Extra<'a, 'b> = MapExtra<'a, 'b, &'a str, Error<'a>>;
 
 #[derive(Default)]
-pub struct Rust {}
+pub struct Rust {
+    options: RustOptions,
+}
+
+/// Options for the [Rust] parser, from the `parser.rust` section of the [Config].
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct RustOptions {
+    /// Place the contents of each file within namespaces matching its path, e.g. the contents of
+    /// `a/b.rs` within `a::b`, like rust modules. Defaults to true.
+    pub file_namespaces: bool,
+}
+
+impl Default for RustOptions {
+    fn default() -> Self {
+        Self {
+            file_namespaces: true,
+        }
+    }
+}
+
+impl Rust {
+    pub fn new(options: RustOptions) -> Self {
+        Self { options }
+    }
+}
 
 impl ApyxlParser for Rust {
-    fn parse<'a, I: Input + 'a>(
+    fn parse<'a, I: Input + ?Sized + 'a>(
         &self,
         config: &'a Config,
         input: &'a mut I,
         builder: &mut model::Builder<'a>,
     ) -> Result<()> {
-        for (chunk, data) in input.chunks() {
+        for chunk in input.chunk_iter() {
+            let (chunk, data) = chunk?;
+            let _span = debug_span!("parse_chunk", path = ?chunk.relative_file_path).entered();
             debug!("parsing chunk {:?}", chunk.relative_file_path);
-            if let Some(file_path) = &chunk.relative_file_path {
-                for component in rust_util::path_to_entity_id(file_path).component_names() {
-                    builder.enter_namespace(component)
-                }
+            let file_namespace = chunk
+                .relative_file_path
+                .as_ref()
+                .filter(|_| self.options.file_namespaces)
+                .map(|file_path| rust_util::path_to_entity_id(file_path))
+                .unwrap_or_default();
+            for component in file_namespace.component_names() {
+                builder.enter_namespace(component)
             }
 
-            let imports = multi_comment()
-                .then(use_decl())
-                .padded()
-                .repeated()
-                .collect::<Vec<_>>();
-
-            let children = imports
-                .ignore_then(namespace_children(&config, namespace(&config)).padded())
+            let (output, errs) = inner_doc_comments()
+                .then(namespace_children(config, namespace(config)).padded())
                 .then_ignore(end())
-                .parse(&data)
-                .into_result()
-                .map_err(|err| anyhow!("errors encountered while parsing: {:?}", err))?;
+                .parse(data)
+                .into_output_errors();
+
+            // Errors that were recovered from (lenient mode only) still produce output.
+            let (comments, ((children, imports), trailing_comments)) = match output {
+                Some(output) if config.mode == Mode::Lenient => {
+                    builder.diagnostics_mut().extend(parse_diagnostics(
+                        &errs,
+                        chunk,
+                        data,
+                        Severity::Warning,
+                    ));
+                    output
+                }
+                Some(output) if errs.is_empty() => output,
+                _ => return Err(parse_diagnostics(&errs, chunk, data, Severity::Error).into()),
+            };
 
-            builder.merge_from_chunk(
-                Api {
-                    name: Cow::Borrowed(UNDEFINED_NAMESPACE),
-                    children,
-                    attributes: Default::default(),
+            let mut api = Api {
+                name: Cow::Borrowed(UNDEFINED_NAMESPACE),
+                children,
+                attributes: Attributes {
+                    comments,
+                    trailing_comments,
+                    imports,
+                    ..Default::default()
                 },
-                chunk,
-            );
+            };
+            resolve_imports(&mut api, &file_namespace);
+            builder.merge_from_chunk(api, chunk);
             builder.clear_namespace();
         }
 
         Ok(())
     }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rs"]
+    }
+}
+
+fn parse_diagnostics(
+    errs: &[Simple<char>],
+    chunk: &Chunk,
+    data: &str,
+    severity: Severity,
+) -> Diagnostics {
+    errs.iter()
+        .map(|err| {
+            // Errors don't always include the found char, so look it up from the data instead.
+            let found = err
+                .found()
+                .copied()
+                .or_else(|| data.get(err.span().start..)?.chars().next());
+            let message = match found {
+                Some(c) => format!("unexpected '{}'", c),
+                None => "unexpected end of input".to_string(),
+            };
+            let diagnostic = Diagnostic::new(severity, message)
+                .chunk(chunk)
+                .span(Span::new(data, err.span().into_range()));
+            match severity {
+                Severity::Error => diagnostic.note("errors encountered while parsing"),
+                Severity::Warning => diagnostic.note("unknown item skipped in lenient mode"),
+            }
+        })
+        .collect()
+}
+
+/// Byte range of the input parsed into the current entity, excluding surrounding whitespace.
+fn span(e: &mut Extra) -> Range<usize> {
+    let range = e.span().into_range();
+    let slice = e.slice();
+    let start = range.start + (slice.len() - slice.trim_start().len());
+    start..(range.start + slice.trim_end().len()).max(start)
 }
 
 const ALLOWED_TYPE_NAME_CHARS: &str = "_&<>";
 
+/// Like rust itself, non-ASCII identifiers are allowed. They are kept as written, see
+/// [crate::view::IdentifierPolicy] for normalizing them in generators.
 fn type_name<'a>() -> impl Parser<'a, &'a str, &'a str, Error<'a>> {
     any()
         // first char
-        .filter(|c: &char| c.is_ascii_alphabetic() || ALLOWED_TYPE_NAME_CHARS.contains(*c))
+        .filter(|c: &char| c.is_ident_start() || ALLOWED_TYPE_NAME_CHARS.contains(*c))
         // remaining chars
-        .then(
-            any()
-                .filter(|c: &char| c.is_ascii_alphanumeric() || *c == '_')
-                .repeated(),
-        )
-        .slice()
+        .then(any().filter(|c: &char| c.is_ident_continue()).repeated())
+        .to_slice()
+}
+
+/// A path brought into scope by a `use` declaration, e.g. `a::b::C as D`. Nested groups like
+/// `a::{b::C, D}` produce one per leaf.
+#[derive(Debug, Clone, PartialEq)]
+struct UsePath<'a> {
+    path: Vec<&'a str>,
+    alias: Option<&'a str>,
+    glob: bool,
 }
 
-fn use_decl<'a>() -> impl Parser<'a, &'a str, (), Error<'a>> {
+impl UsePath<'_> {
+    /// The import as written, with any leading `crate`, `self` or `super` still in its path. The
+    /// namespace the `use` is within isn't known until the whole chunk is parsed, so those are
+    /// resolved afterwards by [resolve_imports].
+    fn into_unresolved_import(self) -> attribute::Import {
+        let mut path = self.path;
+        // `a::{self}` imports `a` itself.
+        if path.len() > 1 && path.last() == Some(&"self") {
+            path.pop();
+        }
+        attribute::Import {
+            path: EntityId::new_unqualified_vec(path.into_iter()),
+            absolute: false,
+            alias: self.alias.map(str::to_string),
+            glob: self.glob,
+        }
+    }
+}
+
+/// Resolves the [UsePath::into_unresolved_import]s of `namespace` and every namespace within it,
+/// where `namespace_id` is the id of `namespace` itself.
+fn resolve_imports(namespace: &mut Namespace, namespace_id: &EntityId) {
+    namespace.attributes.imports = std::mem::take(&mut namespace.attributes.imports)
+        .into_iter()
+        .map(|import| resolve_import(import, namespace_id))
+        .collect();
+    for child in namespace.namespaces_mut() {
+        let child_id = EntityId::new_unqualified_vec(
+            namespace_id.component_names().chain([child.name.as_ref()]),
+        );
+        resolve_imports(child, &child_id);
+    }
+}
+
+/// Resolves a leading `crate`, `self` or `super` against the `namespace` the `import` is within
+/// to get an absolute path. Other paths are relative to the namespace, like rust 2018 uniform
+/// paths.
+fn resolve_import(import: attribute::Import, namespace: &EntityId) -> attribute::Import {
+    let path = import.path.component_names().collect::<Vec<_>>();
+    let (absolute, path) = match path.first() {
+        Some(&"crate") => (true, path[1..].to_vec()),
+        Some(&"self") => (
+            true,
+            namespace
+                .component_names()
+                .chain(path[1..].iter().copied())
+                .collect(),
+        ),
+        Some(&"super") => {
+            let supers = path.iter().take_while(|name| **name == "super").count();
+            let ancestors = namespace.len().saturating_sub(supers);
+            (
+                true,
+                namespace
+                    .component_names()
+                    .take(ancestors)
+                    .chain(path[supers..].iter().copied())
+                    .collect(),
+            )
+        }
+        _ => (false, path.to_vec()),
+    };
+    attribute::Import {
+        path: EntityId::new_unqualified_vec(path.into_iter()),
+        absolute,
+        ..import
+    }
+}
+
+fn use_decl<'a>() -> impl Parser<'a, &'a str, Vec<UsePath<'a>>, Error<'a>> {
+    let tree = recursive(|nested| {
+        let prefix = text::ident()
+            .then_ignore(just("::").padded())
+            .repeated()
+            .collect::<Vec<_>>();
+        let glob = just('*').map(|_| {
+            vec![UsePath {
+                path: vec![],
+                alias: None,
+                glob: true,
+            }]
+        });
+        let group = nested
+            .padded()
+            .separated_by(just(','))
+            .allow_trailing()
+            .collect::<Vec<Vec<_>>>()
+            .delimited_by(just('{'), just('}'))
+            .map(|trees| trees.into_iter().flatten().collect::<Vec<_>>());
+        let alias = text::whitespace()
+            .at_least(1)
+            .then(text::keyword("as"))
+            .then(text::whitespace().at_least(1))
+            .ignore_then(text::ident());
+        let name = text::ident().then(alias.or_not()).map(|(name, alias)| {
+            vec![UsePath {
+                path: vec![name],
+                alias,
+                glob: false,
+            }]
+        });
+        just("::")
+            .or_not()
+            .ignore_then(prefix)
+            .then(choice((glob, group, name)))
+            .map(|(prefix, mut paths): (Vec<&str>, Vec<UsePath>)| {
+                for use_path in &mut paths {
+                    use_path.path.splice(0..0, prefix.iter().copied());
+                }
+                paths
+            })
+    });
     text::keyword("pub")
         .then(text::whitespace().at_least(1))
         .or_not()
         .then(text::keyword("use"))
         .then(text::whitespace().at_least(1))
-        .then(text::ident().separated_by(just("::")).at_least(1))
-        .then(just(';'))
-        .ignored()
+        .ignore_then(tree)
+        .then_ignore(just(';'))
 }
 
 // Macro that expands `ty` to the type itself _or_ a ref of the type, e.g. u8 or &u8.
@@ -95,33 +303,32 @@ macro_rules! ty_or_ref {
     };
 }
 
-fn user_ty<'a>(config: &'a Config) -> impl Parser<'a, &'a str, String, Error> + 'a {
+/// The first of the `config.user_types` that matches, see [crate::parser::UserType::match_prefix].
+fn user_ty<'a>(config: &'a Config) -> impl Parser<'a, &'a str, String, Error<'a>> + 'a {
     custom(move |input| {
-        for (i, ty) in config.user_types.iter().enumerate() {
-            let marker = input.save();
-            match input.parse(just(ty.parse.as_str())) {
-                Ok(_) => {
-                    let _ = input.next();
-                    return Ok(ty.name.to_string());
-                }
-                Err(err) => {
-                    input.rewind(marker);
-                    if i == config.user_types.len() - 1 {
-                        return Err(err);
-                    }
+        let before = input.cursor();
+        let rest: &str = input.slice_from(&before..);
+        match config
+            .user_types
+            .iter()
+            .find_map(|ty| ty.match_prefix(rest))
+        {
+            Some((len, name)) => {
+                for _ in rest[..len].chars() {
+                    input.next();
                 }
+                Ok(name)
             }
+            None => Err(error::LabelError::<'a, &'a str, char>::expected_found(
+                None,
+                None,
+                input.span_since(&before),
+            )),
         }
-        // Just need _any error_.
-        Err(error::Error::<&'a str>::expected_found(
-            None,
-            None,
-            input.span_since(input.offset()),
-        ))
     })
 }
 
-fn ty(config: &Config) -> impl Parser<&str, Type, Error> {
+fn ty(config: &Config) -> impl Parser<'_, &str, Type, Error<'_>> {
     recursive(|nested| {
         choice((
             just("bool").map(|_| Type::Bool),
@@ -145,10 +352,14 @@ fn ty(config: &Config) -> impl Parser<&str, Type, Error> {
             just("&str").map(|_| Type::String),
             just("&[u8]").map(|_| Type::Bytes),
             user_ty(config).map(|name| Type::User(name.to_string())),
-            vec(nested.clone()),
-            map(nested.clone()),
-            option(nested),
-            entity_id().map(Type::Api),
+            choice((
+                vec(nested.clone()),
+                slice(nested.clone()),
+                map(nested.clone()),
+                option(nested.clone()),
+                reference(nested.clone()),
+            )),
+            api_ty(nested),
         ))
         .boxed()
     })
@@ -157,27 +368,58 @@ fn ty(config: &Config) -> impl Parser<&str, Type, Error> {
 fn vec<'a>(
     ty: impl Parser<'a, &'a str, Type, Error<'a>>,
 ) -> impl Parser<'a, &'a str, Type, Error<'a>> {
-    just("Vec<")
+    just('&')
+        .or_not()
+        .ignore_then(just("Vec<"))
         .then_ignore(text::whitespace())
         .ignore_then(ty)
         .then_ignore(text::whitespace())
         .then_ignore(just('>'))
-        .map(|inner| Type::new_array(inner))
+        .map(Type::new_array)
+}
+
+/// A borrowed slice, e.g. `&[T]`, is an array of `T` like `Vec<T>`.
+fn slice<'a>(
+    ty: impl Parser<'a, &'a str, Type, Error<'a>>,
+) -> impl Parser<'a, &'a str, Type, Error<'a>> {
+    just("&[")
+        .then_ignore(text::whitespace())
+        .ignore_then(ty)
+        .then_ignore(text::whitespace())
+        .then_ignore(just(']'))
+        .map(Type::new_array)
 }
 
+/// `HashMap<K, V>` or `BTreeMap<K, V>`. The model doesn't keep whether a map is ordered.
 fn map<'a>(
     ty: impl Parser<'a, &'a str, Type, Error<'a>> + Clone,
 ) -> impl Parser<'a, &'a str, Type, Error<'a>> {
-    just("HashMap<")
+    choice((just("HashMap<"), just("BTreeMap<")))
         .then_ignore(text::whitespace())
         .ignore_then(ty.clone())
         .then_ignore(just(',').padded())
         .then(ty)
-        .then_ignore(just('>'))
         .then_ignore(text::whitespace())
+        .then_ignore(just('>'))
         .map(|(key, value)| Type::new_map(key, value))
 }
 
+/// A reference to a type within the API, with any type args, e.g. `Page<User>`.
+fn api_ty<'a>(
+    ty: impl Parser<'a, &'a str, Type, Error<'a>>,
+) -> impl Parser<'a, &'a str, Type, Error<'a>> {
+    let args = ty
+        .padded()
+        .separated_by(just(','))
+        .at_least(1)
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(just('<'), just('>'));
+    entity_id()
+        .then(args.or_not())
+        .map(|(id, args)| Type::Api(id, args.unwrap_or_default()))
+}
+
 fn option<'a>(
     ty: impl Parser<'a, &'a str, Type, Error<'a>>,
 ) -> impl Parser<'a, &'a str, Type, Error<'a>> {
@@ -186,7 +428,42 @@ fn option<'a>(
         .ignore_then(ty)
         .then_ignore(text::whitespace())
         .then_ignore(just('>'))
-        .map(|inner| Type::new_optional(inner))
+        .map(Type::new_optional)
+}
+
+/// Any other reference, e.g. `&'a str` or `&mut [T]`, which is the same as the type it refers to.
+/// References without a lifetime or `mut` to common types are handled by [ty] directly.
+fn reference<'a>(
+    ty: impl Parser<'a, &'a str, Type, Error<'a>> + Clone,
+) -> impl Parser<'a, &'a str, Type, Error<'a>> {
+    let mutable = text::keyword("mut").then(text::whitespace().at_least(1));
+    let slice = ty
+        .clone()
+        .padded()
+        .delimited_by(just('['), just(']'))
+        .map(Type::new_array);
+    just('&')
+        .then(lifetime().then(text::whitespace()).or_not())
+        .then(mutable.or_not())
+        .ignore_then(choice((
+            text::keyword("str").map(|_| Type::String),
+            just("[u8]").map(|_| Type::Bytes),
+            slice,
+            ty,
+        )))
+}
+
+/// A lifetime, e.g. `'a` or `'static`.
+fn lifetime<'a>() -> impl Parser<'a, &'a str, (), Error<'a>> {
+    just('\'').then(text::ident()).ignored()
+}
+
+/// A `where` clause of a struct or fn, e.g. `where T: Clone`, which is skipped.
+fn where_clause<'a>() -> impl Parser<'a, &'a str, (), Error<'a>> {
+    text::keyword("where")
+        .padded()
+        .then(none_of("{;").repeated())
+        .ignored()
 }
 
 fn entity_id<'a>() -> impl Parser<'a, &'a str, EntityId, Error<'a>> {
@@ -197,32 +474,87 @@ fn entity_id<'a>() -> impl Parser<'a, &'a str, EntityId, Error<'a>> {
         .map(|components| EntityId::new_unqualified_vec(components.into_iter()))
 }
 
-fn field<'a>(config: &'a Config) -> impl Parser<'a, &'a str, Field, Error> + 'a {
+fn field<'a>(config: &'a Config) -> impl Parser<'a, &'a str, Field<'a>, Error<'a>> + 'a {
     let field = text::ident()
         .then_ignore(just(':').padded())
         .then(ty(config));
     multi_comment()
         .then(attributes().padded())
+        .then_ignore(visibility())
         .then(field)
-        .map(|((comments, user), (name, ty))| Field {
+        .map_with(|((comments, user), (name, ty)), e| Field {
             name,
             ty,
             attributes: Attributes {
                 comments,
                 user,
+                span: Some(span(e)),
                 ..Default::default()
             },
         })
 }
 
+/// Resolves the quote, backslash and whitespace escapes within a string literal, e.g. `\"` and
+/// `\n`. Other escapes are kept as is.
+fn unescape(value: &str) -> Cow<'_, str> {
+    if !value.contains('\\') {
+        return Cow::Borrowed(value);
+    }
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        let escaped = match (c, chars.peek()) {
+            ('\\', Some('"')) => '"',
+            ('\\', Some('\'')) => '\'',
+            ('\\', Some('\\')) => '\\',
+            ('\\', Some('n')) => '\n',
+            ('\\', Some('r')) => '\r',
+            ('\\', Some('t')) => '\t',
+            ('\\', Some('0')) => '\0',
+            _ => {
+                unescaped.push(c);
+                continue;
+            }
+        };
+        unescaped.push(escaped);
+        chars.next();
+    }
+    Cow::Owned(unescaped)
+}
+
 fn attributes<'a>() -> impl Parser<'a, &'a str, Vec<attribute::User<'a>>, Error<'a>> {
     let name = text::ident();
+    let string = just('\\')
+        .then(any())
+        .ignored()
+        .or(none_of("\\\"").ignored())
+        .repeated()
+        .to_slice()
+        .delimited_by(just('"'), just('"'))
+        .map(|value| (unescape(value), true));
+    let number = just('-')
+        .or_not()
+        .then(text::digits(10))
+        .then(just('.').then(text::digits(10)).or_not())
+        .to_slice()
+        .map(|value| (Cow::Borrowed(value), false));
+    let value = choice((
+        string,
+        number,
+        text::ident().map(|value| (Cow::Borrowed(value), false)),
+    ));
     let data = text::ident()
-        .then(just('=').padded().ignore_then(text::ident()).or_not())
+        .then(just('=').padded().ignore_then(value).or_not())
         .map(|(lhs, rhs)| match rhs {
             None => attribute::UserData::new(None, lhs),
-            Some(rhs) => attribute::UserData::new(Some(lhs), rhs),
-        });
+            Some((rhs, false)) => attribute::UserData::new(Some(lhs), rhs),
+            Some((rhs, true)) => attribute::UserData::new_quoted(Some(lhs), rhs),
+        })
+        .or(value.map(|(value, quoted)| attribute::UserData {
+            key: None,
+            value,
+            quoted,
+        }));
     let data_list = data
         .separated_by(just(',').padded())
         .allow_trailing()
@@ -238,16 +570,77 @@ fn attributes<'a>() -> impl Parser<'a, &'a str, Vec<attribute::User<'a>>, Error<
         .allow_trailing()
         .collect::<Vec<_>>()
         .delimited_by(just("#[").padded(), just(']').padded())
+        .repeated()
+        .collect::<Vec<_>>()
+        .map(|blocks| blocks.into_iter().flatten().collect())
+}
+
+/// An optional `pub` before a field, which is skipped.
+fn visibility<'a>() -> impl Parser<'a, &'a str, (), Error<'a>> {
+    text::keyword("pub")
+        .then(text::whitespace().at_least(1))
         .or_not()
-        .map(|opt| opt.unwrap_or(vec![]))
+        .ignored()
 }
 
-fn dto(config: &Config) -> impl Parser<&str, Dto, Error> {
-    let fields = field(config)
+/// Names of the fields of a tuple struct, by position. Field names borrow from the parsed source,
+/// so positional names have to come from here.
+const TUPLE_FIELD_NAMES: [&str; 16] = [
+    "field0", "field1", "field2", "field3", "field4", "field5", "field6", "field7", "field8",
+    "field9", "field10", "field11", "field12", "field13", "field14", "field15",
+];
+
+/// The fields of a tuple struct, e.g. `(pub u128, String);`, named by [TUPLE_FIELD_NAMES].
+fn tuple_fields<'a>(config: &'a Config) -> impl Parser<'a, &'a str, Vec<Field<'a>>, Error<'a>> {
+    let field = multi_comment()
+        .then(attributes().padded())
+        .then_ignore(visibility())
+        .then(ty(config))
+        .map_with(|((comments, user), ty), e| (comments, user, ty, span(e)));
+    field
         .separated_by(just(',').padded())
         .allow_trailing()
         .collect::<Vec<_>>()
+        .delimited_by(just('(').padded(), just(')').padded())
+        .then_ignore(where_clause().or_not())
+        .then_ignore(just(';').padded())
+        .try_map(|fields, span| {
+            if fields.len() > TUPLE_FIELD_NAMES.len() {
+                return Err(error::LabelError::<'a, &'a str, char>::expected_found(
+                    None, None, span,
+                ));
+            }
+            Ok(fields
+                .into_iter()
+                .zip(TUPLE_FIELD_NAMES)
+                .map(|((comments, user, ty, span), name)| Field {
+                    name,
+                    ty,
+                    attributes: Attributes {
+                        comments,
+                        user,
+                        span: Some(span),
+                        ..Default::default()
+                    },
+                })
+                .collect())
+        })
+}
+
+/// A struct with named fields, a tuple struct, e.g. `struct Id(u128);`, or a unit struct, e.g.
+/// `struct Marker;`, which has no fields.
+fn dto(config: &Config) -> impl Parser<'_, &str, Dto<'_>, Error<'_>> {
+    let named_fields = field(config)
+        .separated_by(just(',').padded())
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .then(multi_comment())
         .delimited_by(just('{').padded(), just('}').padded());
+    let fields = choice((
+        named_fields,
+        tuple_fields(config).map(|fields| (fields, vec![])),
+        just(';').padded().map(|_| (vec![], vec![])),
+    ));
     let name = text::keyword("pub")
         .then(text::whitespace().at_least(1))
         .or_not()
@@ -256,19 +649,86 @@ fn dto(config: &Config) -> impl Parser<&str, Dto, Error> {
     let dto = attributes()
         .padded()
         .then(name)
-        .then(fields)
-        .then_ignore(multi_comment());
-    multi_comment()
-        .then(dto)
-        .map(|(comments, ((user, name), fields))| Dto {
-            name,
-            fields,
-            attributes: Attributes {
-                comments,
-                user,
-                ..Default::default()
-            },
-        })
+        .then(type_params())
+        .then_ignore(where_clause().or_not())
+        .then(fields);
+    multi_comment().then(dto).map_with(
+        |(comments, (((user, name), type_params), (mut fields, trailing_comments))), e| {
+            for field in &mut fields {
+                replace_type_params(&mut field.ty, &type_params);
+            }
+            Dto {
+                name,
+                type_params,
+                fields,
+                rpcs: vec![],
+                attributes: Attributes {
+                    comments,
+                    user,
+                    span: Some(span(e)),
+                    trailing_comments,
+                    ..Default::default()
+                },
+            }
+        },
+    )
+}
+
+/// Generic type parameters, e.g. `<K, V>`. Bounds, e.g. `T: Into<String>`, and lifetime params,
+/// e.g. `'a`, are skipped.
+fn type_params<'a>() -> impl Parser<'a, &'a str, Vec<&'a str>, Error<'a>> {
+    let angled = angle_brackets();
+    let bounds = just(':')
+        .then(choice((none_of("<>,").ignored(), angled)).repeated())
+        .or_not();
+    let param = lifetime()
+        .to(None)
+        .or(text::ident().map(Some))
+        .padded()
+        .then_ignore(bounds);
+    param
+        .separated_by(just(','))
+        .allow_trailing()
+        .collect::<Vec<_>>()
+        .delimited_by(just('<').padded(), just('>').padded())
+        .or_not()
+        .map(|params| params.into_iter().flatten().flatten().collect())
+}
+
+/// Anything within angle brackets, including nested ones, e.g. the type args of `Page<Vec<u8>>`,
+/// which is skipped.
+fn angle_brackets<'a>() -> impl Parser<'a, &'a str, (), Error<'a>> + Clone {
+    recursive(|nested| {
+        choice((none_of("<>").ignored(), nested))
+            .repeated()
+            .delimited_by(just('<'), just('>'))
+            .ignored()
+    })
+}
+
+/// Replaces references to any of the `type_params` within `ty`, which are parsed as API types, with
+/// [Type::Generic].
+fn replace_type_params(ty: &mut Type, type_params: &[&str]) {
+    match ty {
+        Type::Api(id, args) => {
+            if let Some(param) = type_params
+                .iter()
+                .find(|param| *id == EntityId::new_unqualified(param))
+            {
+                *ty = Type::Generic(param.to_string())
+            } else {
+                for arg in args {
+                    replace_type_params(arg, type_params);
+                }
+            }
+        }
+        Type::Array(ty) | Type::Optional(ty) => replace_type_params(ty, type_params),
+        Type::Map { key, value } => {
+            replace_type_params(key, type_params);
+            replace_type_params(value, type_params);
+        }
+        _ => {}
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -295,7 +755,7 @@ fn block_comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
     any()
         .and_is(just("*/").not())
         .repeated()
-        .slice()
+        .to_slice()
         .map(&str::trim)
         .delimited_by(just("/*"), just("*/"))
         .map(|s| {
@@ -304,6 +764,7 @@ fn block_comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
             } else {
                 Comment::default()
             }
+            .with_style(CommentStyle::Block)
         })
 }
 
@@ -319,22 +780,51 @@ fn block_comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
 /// would result in
 /// `vec!["i am", "    a multiline", "comment", ""]`
 fn line_comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
-    let text = any().and_is(just('\n').not()).repeated().slice();
-    let line_start = just("//").then(just(' ').or_not());
+    comment_lines(just("//").then(one_of("/!").not()).ignored())
+}
+
+/// Parses a doc comment where each line starts with `///`, like [line_comment].
+fn doc_comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
+    comment_lines(just("///").ignored()).map(|comment| comment.with_style(CommentStyle::Doc))
+}
+
+/// Parses an inner doc comment where each line starts with `//!`, like [line_comment]. These
+/// document the enclosing file or module rather than the following item.
+fn inner_doc_comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
+    comment_lines(just("//!").ignored()).map(|comment| comment.with_style(CommentStyle::InnerDoc))
+}
+
+/// Parses zero or more [inner_doc_comment]s at the start of a file or module, skipping any other
+/// comments around them.
+fn inner_doc_comments<'a>() -> impl Parser<'a, &'a str, Vec<Comment<'a>>, Error<'a>> {
+    multi_comment()
+        .ignore_then(inner_doc_comment())
+        .padded()
+        .repeated()
+        .collect::<Vec<_>>()
+}
+
+/// Consecutive lines that each start with `prefix` and an optional space.
+fn comment_lines<'a>(
+    prefix: impl Parser<'a, &'a str, (), Error<'a>> + Clone,
+) -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
+    let text = any().and_is(just('\n').not()).repeated().to_slice();
+    let line_start = prefix.then(just(' ').or_not());
     let line = text::inline_whitespace()
         .then(line_start)
         .ignore_then(text)
         .then_ignore(just('\n'));
-    line.map(|s| Cow::Borrowed(s))
+    line.map(Cow::Borrowed)
         .repeated()
         .at_least(1)
         .collect::<Vec<_>>()
         .map(|v| v.into())
 }
 
-/// Parses a single line or block comment group. Each line is an element in the returned vec.
+/// Parses a single line, doc or block comment group. Each line is an element in the returned vec.
+/// Inner doc comments aren't included, see [inner_doc_comments].
 fn comment<'a>() -> impl Parser<'a, &'a str, Comment<'a>, Error<'a>> {
-    choice((line_comment(), block_comment()))
+    choice((doc_comment(), line_comment(), block_comment()))
 }
 
 /// Parses zero or more [comment]s (which are themselves Vec<&str>) into a Vec.
@@ -343,7 +833,11 @@ fn multi_comment<'a>() -> impl Parser<'a, &'a str, Vec<Comment<'a>>, Error<'a>>
 }
 
 fn expr_block<'a>() -> impl Parser<'a, &'a str, Vec<ExprBlock<'a>>, Error<'a>> {
-    let body = none_of("{}").repeated().at_least(1).slice().map(&str::trim);
+    let body = none_of("{}")
+        .repeated()
+        .at_least(1)
+        .to_slice()
+        .map(&str::trim);
     recursive(|nested| {
         choice((
             comment().boxed().padded().map(ExprBlock::Comment),
@@ -356,55 +850,209 @@ fn expr_block<'a>() -> impl Parser<'a, &'a str, Vec<ExprBlock<'a>>, Error<'a>> {
     })
 }
 
-fn rpc(config: &Config) -> impl Parser<&str, Rpc, Error> {
-    let fn_keyword = text::keyword("pub")
+fn rpc(config: &Config) -> impl Parser<'_, &str, Rpc<'_>, Error<'_>> {
+    rpc_with_body(config, expr_block().ignored())
+}
+
+/// An rpc ending in `body`, which is either a block for regular fns, or also just `;` for the
+/// methods of a trait.
+fn rpc_with_body<'a>(
+    config: &'a Config,
+    body: impl Parser<'a, &'a str, (), Error<'a>>,
+) -> impl Parser<'a, &'a str, Rpc<'a>, Error<'a>> {
+    let visibility = text::keyword("pub")
+        .then(text::whitespace().at_least(1))
+        .or_not();
+    let is_async = text::keyword("async")
         .then(text::whitespace().at_least(1))
         .or_not()
-        .then(text::keyword("fn"));
-    let name = fn_keyword.padded().ignore_then(text::ident());
-    let params = field(config)
-        .separated_by(just(',').padded())
-        .allow_trailing()
-        .collect::<Vec<_>>()
+        .map(|keyword| keyword.is_some());
+    let name = visibility
+        .padded()
+        .ignore_then(is_async)
+        .then_ignore(text::keyword("fn").padded())
+        .then(text::ident());
+    // Methods in an `impl` block may take `self`, which isn't kept as a param.
+    let receiver = just('&')
+        .then(lifetime().padded().or_not())
+        .or_not()
+        .then(
+            text::keyword("mut")
+                .then(text::whitespace().at_least(1))
+                .or_not(),
+        )
+        .then(text::keyword("self"))
+        .then(just(',').padded().or_not());
+    let params = receiver
+        .or_not()
+        .ignore_then(
+            field(config)
+                .separated_by(just(',').padded())
+                .allow_trailing()
+                .collect::<Vec<_>>(),
+        )
         .delimited_by(just('(').padded(), just(')').padded());
-    let return_type = just("->").ignore_then(ty(config).padded());
+    let return_type = just("->").ignore_then(
+        result(config)
+            .map(|(ok, err)| (ok, Some(err)))
+            .or(ty(config).map(|ty| (Some(ty), None)))
+            .padded(),
+    );
     multi_comment()
         .then(attributes().padded())
         .then(name)
+        .then(type_params())
         .then(params)
         .then(return_type.or_not())
-        .then_ignore(expr_block().padded())
-        .map(|((((comments, user), name), params), return_type)| Rpc {
-            name,
-            params,
-            return_type,
-            attributes: Attributes {
-                comments,
-                user,
-                ..Default::default()
+        .then_ignore(where_clause().or_not())
+        .then_ignore(body.padded())
+        .map_with(
+            |(((((comments, user), (is_async, name)), type_params), mut params), return_type),
+             e| {
+                let (mut return_type, mut error_type) = return_type.unwrap_or_default();
+                for param in &mut params {
+                    replace_type_params(&mut param.ty, &type_params);
+                }
+                for ty in return_type.iter_mut().chain(&mut error_type) {
+                    replace_type_params(ty, &type_params);
+                }
+                Rpc {
+                    name,
+                    type_params,
+                    params,
+                    return_type,
+                    error_type,
+                    is_async,
+                    attributes: Attributes {
+                        comments,
+                        user,
+                        span: Some(span(e)),
+                        ..Default::default()
+                    },
+                }
             },
+        )
+}
+
+/// A `Result<T, E>` return type, as the success type `T` and error type `E`. `T` may be `()` for
+/// rpcs that don't return anything on success.
+fn result(config: &Config) -> impl Parser<'_, &str, (Option<Type>, Type), Error<'_>> {
+    let ok = just("()").to(None).or(ty(config).map(Some));
+    just("Result<")
+        .then_ignore(text::whitespace())
+        .ignore_then(ok)
+        .then_ignore(just(',').padded())
+        .then(ty(config))
+        .then_ignore(text::whitespace())
+        .then_ignore(just('>'))
+}
+
+/// An `impl` block of methods for a dto, e.g. `impl User { fn name(&self) -> String {} }`. `Self`
+/// types within the methods refer to the dto, and its type params are [Type::Generic]s, e.g. `T`
+/// in `impl<T> Page<T> {}`. The type args after the dto's name and any `where` clause are
+/// skipped. Trait impls aren't supported.
+fn impl_block(config: &Config) -> impl Parser<'_, &str, (&str, Vec<Rpc<'_>>), Error<'_>> {
+    let name = text::keyword("impl")
+        .ignore_then(type_params())
+        .then_ignore(text::whitespace())
+        .then(text::ident())
+        .then_ignore(angle_brackets().padded().or_not())
+        .then_ignore(where_clause().or_not());
+    let rpcs = rpc(config)
+        .repeated()
+        .collect::<Vec<_>>()
+        .then_ignore(multi_comment())
+        .delimited_by(just('{').padded(), just('}').padded());
+    multi_comment()
+        .ignore_then(name.padded())
+        .then(rpcs)
+        .map(|((type_params, name), mut rpcs)| {
+            let self_id = EntityId::new_unqualified(name);
+            for rpc in &mut rpcs {
+                for param in &mut rpc.params {
+                    replace_self_ty(&mut param.ty, &self_id);
+                    replace_type_params(&mut param.ty, &type_params);
+                }
+                for ty in rpc.return_type.iter_mut().chain(&mut rpc.error_type) {
+                    replace_self_ty(ty, &self_id);
+                    replace_type_params(ty, &type_params);
+                }
+            }
+            (name, rpcs)
         })
 }
 
+/// A trait of rpcs, e.g. `trait UserService { fn get(&self, id: Id) -> User; }`, which is parsed
+/// as a namespace of the same name. Methods may have a default body. Supertraits, generic traits
+/// and associated types aren't supported.
+fn trait_block(config: &Config) -> impl Parser<'_, &str, Namespace<'_>, Error<'_>> {
+    let name = text::keyword("pub")
+        .then(text::whitespace().at_least(1))
+        .or_not()
+        .ignore_then(text::keyword("trait").padded())
+        .ignore_then(text::ident());
+    let rpcs = rpc_with_body(config, just(';').ignored().or(expr_block().ignored()))
+        .repeated()
+        .collect::<Vec<_>>()
+        .then(multi_comment())
+        .delimited_by(just('{').padded(), just('}').padded());
+    multi_comment()
+        .then(attributes().padded())
+        .then(name)
+        .then(rpcs)
+        .map_with(
+            |(((comments, user), name), (rpcs, trailing_comments)), e| Namespace {
+                name: Cow::Borrowed(name),
+                children: rpcs.into_iter().map(NamespaceChild::Rpc).collect(),
+                attributes: Attributes {
+                    comments,
+                    user,
+                    span: Some(span(e)),
+                    trailing_comments,
+                    ..Default::default()
+                },
+            },
+        )
+}
+
+fn replace_self_ty(ty: &mut Type, self_id: &EntityId) {
+    match ty {
+        Type::Api(id, args) => {
+            if *id == EntityId::new_unqualified("Self") {
+                *id = self_id.clone();
+            }
+            for arg in args {
+                replace_self_ty(arg, self_id);
+            }
+        }
+        Type::Array(ty) | Type::Optional(ty) => replace_self_ty(ty, self_id),
+        Type::Map { key, value } => {
+            replace_self_ty(key, self_id);
+            replace_self_ty(value, self_id);
+        }
+        _ => {}
+    }
+}
+
 const INVALID_ENUM_NUMBER: EnumValueNumber = EnumValueNumber::MAX;
 fn en_value<'a>() -> impl Parser<'a, &'a str, EnumValue<'a>, Error<'a>> {
-    let number = just('=')
-        .padded()
-        .ignore_then(text::int(10).try_map(|s, span| {
-            str::parse::<EnumValueNumber>(s)
-                .map_err(|_| error::Error::<&'a str>::expected_found(None, None, span))
-        }));
+    let digits = just('-').or_not().then(text::int(10)).to_slice();
+    let number = just('=').padded().ignore_then(digits.try_map(|s, span| {
+        str::parse::<EnumValueNumber>(s)
+            .map_err(|_| error::LabelError::<'a, &'a str, char>::expected_found(None, None, span))
+    }));
     multi_comment()
         .then(attributes().padded())
         .then(text::ident())
         .then(number.or_not())
         .padded()
-        .map(|(((comments, user), name), number)| EnumValue {
+        .map_with(|(((comments, user), name), number), e| EnumValue {
             name,
             number: number.unwrap_or(INVALID_ENUM_NUMBER),
             attributes: Attributes {
                 comments,
                 user,
+                span: Some(span(e)),
                 ..Default::default()
             },
         })
@@ -420,20 +1068,25 @@ fn en<'a>() -> impl Parser<'a, &'a str, Enum<'a>, Error<'a>> {
         .separated_by(just(',').padded())
         .allow_trailing()
         .collect::<Vec<_>>()
+        .then(multi_comment())
         .delimited_by(just('{').padded(), just('}').padded());
     multi_comment()
         .then(attributes().padded())
         .then(name)
         .then(values)
-        .map(|(((comments, user), name), values)| Enum {
-            name,
-            values: apply_enum_value_number_defaults(values),
-            attributes: Attributes {
-                comments,
-                user,
-                ..Default::default()
+        .map_with(
+            |(((comments, user), name), (values, trailing_comments)), e| Enum {
+                name,
+                values: apply_enum_value_number_defaults(values),
+                attributes: Attributes {
+                    comments,
+                    user,
+                    span: Some(span(e)),
+                    trailing_comments,
+                    ..Default::default()
+                },
             },
-        })
+        )
 }
 
 fn apply_enum_value_number_defaults(mut values: Vec<EnumValue>) -> Vec<EnumValue> {
@@ -449,27 +1102,119 @@ fn apply_enum_value_number_defaults(mut values: Vec<EnumValue>) -> Vec<EnumValue
     values
 }
 
+/// The children of a namespace, along with the [UsePath::into_unresolved_import]s of its `use`
+/// declarations and its trailing comments.
 fn namespace_children<'a>(
     config: &'a Config,
-    namespace: impl Parser<'a, &'a str, Namespace<'a>, Error<'a>>,
-) -> impl Parser<'a, &'a str, Vec<NamespaceChild<'a>>, Error<'a>> {
-    choice((
-        dto(config).map(NamespaceChild::Dto),
-        rpc(config).map(NamespaceChild::Rpc),
-        en().map(NamespaceChild::Enum),
-        namespace.map(NamespaceChild::Namespace),
+    namespace: impl Parser<'a, &'a str, Namespace<'a>, Error<'a>> + 'a,
+) -> impl Parser<
+    'a,
+    &'a str,
+    (
+        (Vec<NamespaceChild<'a>>, Vec<attribute::Import>),
+        Vec<Comment<'a>>,
+    ),
+    Error<'a>,
+> {
+    let child = choice((
+        multi_comment()
+            .ignore_then(use_decl())
+            .padded()
+            .map(Item::Use),
+        dto(config).map(|dto| Item::Child(NamespaceChild::Dto(dto))),
+        rpc(config).map(|rpc| Item::Child(NamespaceChild::Rpc(rpc))),
+        en().map(|en| Item::Child(NamespaceChild::Enum(en))),
+        impl_block(config).map_with(|(name, rpcs), e| Item::Impl {
+            name,
+            rpcs,
+            span: e.span(),
+        }),
+        trait_block(config).map(|namespace| Item::Child(NamespaceChild::Namespace(namespace))),
+        namespace.map(|namespace| Item::Child(NamespaceChild::Namespace(namespace))),
     ))
-    .repeated()
-    .collect::<Vec<_>>()
+    .map(Some);
+    let child = match config.mode {
+        Mode::Strict => child.boxed(),
+        Mode::Lenient => child
+            .recover_with(via_parser(unknown_item().to(None)))
+            .boxed(),
+    };
+    child
+        .repeated()
+        .collect::<Vec<_>>()
+        .try_map(move |items, _| attach_impls(items.into_iter().flatten().collect(), config.mode))
+        .then(multi_comment())
+}
+
+/// A parsed item within a namespace. `impl` blocks are only kept until their methods are attached
+/// to their dto.
+#[derive(Clone)]
+#[allow(clippy::large_enum_variant)]
+enum Item<'a> {
+    Child(NamespaceChild<'a>),
+    Use(Vec<UsePath<'a>>),
+    Impl {
+        name: &'a str,
+        rpcs: Vec<Rpc<'a>>,
+        span: SimpleSpan,
+    },
 }
 
-fn namespace(config: &Config) -> impl Parser<&str, Namespace, Error> {
+/// Moves the methods of each `impl` block into the dto of the same name within the namespace. An
+/// `impl` without a dto is an error in [Mode::Strict], and is skipped in [Mode::Lenient]. The
+/// paths of `use` declarations are returned alongside the children.
+fn attach_impls<'a>(
+    items: Vec<Item<'a>>,
+    mode: Mode,
+) -> Result<(Vec<NamespaceChild<'a>>, Vec<attribute::Import>), Simple<'a, char>> {
+    let mut children = vec![];
+    let mut imports = vec![];
+    let mut impls = vec![];
+    for item in items {
+        match item {
+            Item::Child(child) => children.push(child),
+            Item::Use(paths) => {
+                imports.extend(paths.into_iter().map(UsePath::into_unresolved_import))
+            }
+            Item::Impl { name, rpcs, span } => impls.push((name, rpcs, span)),
+        }
+    }
+    for (name, rpcs, span) in impls {
+        let dto = children.iter_mut().find_map(|child| match child {
+            NamespaceChild::Dto(dto) if dto.name == name => Some(dto),
+            _ => None,
+        });
+        match dto {
+            Some(dto) => dto.rpcs.extend(rpcs),
+            None if mode == Mode::Lenient => {}
+            None => {
+                return Err(error::LabelError::<'a, &'a str, char>::expected_found(
+                    None, None, span,
+                ))
+            }
+        }
+    }
+    Ok((children, imports))
+}
+
+/// Any item that isn't otherwise supported, e.g. trait `impl`s, `const`s or `type` aliases. Only
+/// used to skip over them in [Mode::Lenient].
+fn unknown_item<'a>() -> impl Parser<'a, &'a str, (), Error<'a>> {
+    multi_comment()
+        .ignore_then(none_of(";{}").repeated())
+        .then(just(';').ignored().or(expr_block().ignored()))
+        .padded()
+        .ignored()
+}
+
+fn namespace(config: &Config) -> impl Parser<'_, &str, Namespace<'_>, Error<'_>> {
     recursive(|nested| {
         let mod_keyword = text::keyword("pub")
             .then(text::whitespace().at_least(1))
             .or_not()
             .then(text::keyword("mod"));
-        let body = namespace_children(config, nested)
+        let body = inner_doc_comments()
+            .then(namespace_children(config, nested))
             .boxed()
             .delimited_by(just('{').padded(), just('}').padded());
         multi_comment()
@@ -477,15 +1222,23 @@ fn namespace(config: &Config) -> impl Parser<&str, Namespace, Error> {
             .then(mod_keyword.padded().ignore_then(text::ident()))
             // or_not to allow declaration-only in the form:
             //      mod name;
-            .then(just(';').padded().map(|_| None).or(body.map(|c| Some(c))))
-            .map(|(((comments, user), name), children)| Namespace {
-                name: Cow::Borrowed(name),
-                children: children.unwrap_or(vec![]),
-                attributes: Attributes {
-                    comments,
-                    user,
-                    ..Default::default()
-                },
+            .then(just(';').padded().map(|_| None).or(body.map(Some)))
+            .map_with(|(((mut comments, user), name), body), e| {
+                let (mut inner_docs, ((children, imports), trailing_comments)) =
+                    body.unwrap_or_default();
+                comments.append(&mut inner_docs);
+                Namespace {
+                    name: Cow::Borrowed(name),
+                    children,
+                    attributes: Attributes {
+                        comments,
+                        user,
+                        span: Some(span(e)),
+                        trailing_comments,
+                        imports,
+                        ..Default::default()
+                    },
+                }
             })
             .boxed()
     })
@@ -512,8 +1265,11 @@ mod tests {
         static ref CONFIG: Config = Config {
             user_types: vec![UserType {
                 parse: "user_type".to_string(),
-                name: "user".to_string()
-            }]
+                name: "user".to_string(),
+                targets: Default::default(),
+                glob: false,
+            }],
+            ..Default::default()
         };
     }
 
@@ -559,6 +1315,245 @@ mod tests {
         Ok(())
     }
 
+    mod impl_block {
+        use anyhow::Result;
+
+        use crate::model::{Builder, EntityId, Type};
+        use crate::parser::Config;
+        use crate::{input, parser, Mode, Parser};
+
+        #[test]
+        fn attaches_methods_to_dto() -> Result<()> {
+            let mut input = input::Buffer::new(
+                r#"
+            mod ns {
+                impl User {
+                    pub fn name(&self) -> String {}
+                    fn rename(&mut self, name: String) -> Self {}
+                    fn new() -> Self {}
+                }
+                struct User {}
+            }
+            "#,
+            );
+            let config = Config::default();
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            let dto = model
+                .api()
+                .find_dto(&EntityId::try_from("ns.d:User")?)
+                .unwrap();
+            let names = dto.rpcs.iter().map(|rpc| rpc.name).collect::<Vec<_>>();
+            assert_eq!(names, vec!["name", "rename", "new"]);
+            assert_eq!(dto.rpcs[1].params.len(), 1);
+            assert_eq!(
+                dto.rpcs[1].return_type,
+                Some(Type::Api(EntityId::try_from("ns.d:User")?, vec![]))
+            );
+            assert!(model.api().namespace("ns").unwrap().rpc("name").is_none());
+            Ok(())
+        }
+
+        #[test]
+        fn generic() -> Result<()> {
+            let mut input = input::Buffer::new(
+                r#"
+            struct A<T> { v: T }
+            impl<T> A<T> {
+                fn get(&self) -> T {}
+                fn set(&mut self, v: T) {}
+            }
+            struct B<'a> { name: &'a str }
+            impl<'a> B<'a> {
+                fn name(&self) -> String {}
+            }
+            struct C {}
+            impl C where Self: Sized {
+                fn c(&self) {}
+            }
+            struct D<T> { v: T }
+            impl<T: Clone + Into<Vec<u8>>> D<Vec<T>>
+            where
+                T: Default,
+            {
+                fn d(&self) {}
+            }
+            "#,
+            );
+            let config = Config::default();
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            let rpcs = |name: &str| model.api().dto(name).unwrap().rpcs.clone();
+            let a = rpcs("A");
+            assert_eq!(a[0].return_type, Some(Type::Generic("T".to_string())));
+            assert_eq!(a[1].params[0].ty, Type::Generic("T".to_string()));
+            assert_eq!(rpcs("B")[0].name, "name");
+            assert_eq!(rpcs("C")[0].name, "c");
+            assert_eq!(rpcs("D")[0].name, "d");
+            Ok(())
+        }
+
+        #[test]
+        fn receiver_lifetimes() -> Result<()> {
+            let mut input = input::Buffer::new(
+                r#"
+            struct A {}
+            impl A {
+                fn x<'a>(&'a self) -> &'a str {}
+                fn y<'a>(&'a mut self, v: u32) {}
+            }
+            "#,
+            );
+            let config = Config::default();
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            let rpcs = &model.api().dto("A").unwrap().rpcs;
+            assert_eq!(rpcs[0].name, "x");
+            assert!(rpcs[0].params.is_empty());
+            assert_eq!(rpcs[0].return_type, Some(Type::String));
+            assert_eq!(rpcs[1].params.len(), 1);
+            Ok(())
+        }
+
+        #[test]
+        fn strict_errors_without_dto() {
+            let mut input = input::Buffer::new("impl User { fn name(&self) {} }");
+            let mut builder = Builder::default();
+            let result =
+                parser::Rust::default().parse(&Config::default(), &mut input, &mut builder);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn lenient_skips_without_dto() -> Result<()> {
+            let config = Config {
+                mode: Mode::Lenient,
+                ..Default::default()
+            };
+            let mut input = input::Buffer::new("impl User { fn name(&self) {} } fn rpc() {}");
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            assert!(model.api().rpc("rpc").is_some());
+            assert!(model.api().rpc("name").is_none());
+            Ok(())
+        }
+    }
+
+    mod trait_block {
+        use anyhow::Result;
+
+        use crate::model::{Builder, Comment, EntityId, Type};
+        use crate::parser::Config;
+        use crate::{input, parser, Parser};
+
+        #[test]
+        fn parses_as_namespace_of_rpcs() -> Result<()> {
+            let mut input = input::Buffer::new(
+                r#"
+            struct User {}
+            // Manages users.
+            pub trait UserService {
+                // Gets a user.
+                fn get(&self, id: u64) -> User;
+                fn remove(&mut self, id: u64) -> Result<(), String>;
+                fn ping(&self) {}
+            }
+            "#,
+            );
+            let config = Config::default();
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            let service = model.api().namespace("UserService").unwrap();
+            assert_eq!(
+                service.attributes.comments,
+                vec![Comment::unowned(&["Manages users."])]
+            );
+            let names = service.rpcs().map(|rpc| rpc.name).collect::<Vec<_>>();
+            assert_eq!(names, vec!["get", "remove", "ping"]);
+
+            let get = service.rpc("get").unwrap();
+            assert_eq!(get.params.len(), 1);
+            assert_eq!(
+                get.return_type,
+                Some(Type::Api(EntityId::try_from("d:User")?, vec![]))
+            );
+            assert_eq!(
+                get.attributes.comments,
+                vec![Comment::unowned(&["Gets a user."])]
+            );
+            assert_eq!(
+                service.rpc("remove").unwrap().error_type,
+                Some(Type::String)
+            );
+            Ok(())
+        }
+    }
+
+    mod mode {
+        use anyhow::Result;
+
+        use crate::model::Builder;
+        use crate::parser::Config;
+        use crate::{input, parser, Mode, Parser};
+
+        const DATA: &str = r#"
+            struct dto {}
+            impl Display for dto {
+                fn ignored() {}
+            }
+            const X: u32 = 5;
+            mod ns {
+                // comment
+                type Alias = dto;
+                fn rpc() {}
+            }
+        "#;
+
+        #[test]
+        fn strict_errors_on_unknown_items() {
+            let mut input = input::Buffer::new(DATA);
+            let mut builder = Builder::default();
+            let result =
+                parser::Rust::default().parse(&Config::default(), &mut input, &mut builder);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn lenient_skips_unknown_items() -> Result<()> {
+            let config = Config {
+                mode: Mode::Lenient,
+                ..Default::default()
+            };
+            let mut input = input::Buffer::new(DATA);
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            assert_eq!(builder.diagnostics().warnings().count(), 3);
+
+            let model = builder.build().unwrap();
+            assert!(model.api().dto("dto").is_some());
+            assert!(model.api().rpc("ignored").is_none());
+            assert!(model.api().namespace("ns").unwrap().rpc("rpc").is_some());
+            Ok(())
+        }
+
+        #[test]
+        fn lenient_still_errors_on_unclosed_item() {
+            let config = Config {
+                mode: Mode::Lenient,
+                ..Default::default()
+            };
+            let mut input = input::Buffer::new("struct dto {}\nimpl dto {");
+            let mut builder = Builder::default();
+            let result = parser::Rust::default().parse(&config, &mut input, &mut builder);
+            assert!(result.is_err());
+        }
+    }
+
     mod file_path_to_mod {
         use anyhow::Result;
 
@@ -619,6 +1614,164 @@ mod tests {
             assert!(namespace.unwrap().dto("dto").is_some());
             Ok(())
         }
+
+        #[test]
+        fn disabled_by_options() -> Result<()> {
+            let mut input = input::ChunkBuffer::new();
+            input.add_chunk(Chunk::with_relative_file_path("a/b/c.rs"), "struct dto {}");
+            let mut builder = Builder::default();
+            let options = parser::RustOptions {
+                file_namespaces: false,
+            };
+            parser::Rust::new(options).parse(&CONFIG, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+
+            assert!(model.api().dto("dto").is_some());
+            Ok(())
+        }
+    }
+
+    mod use_decl {
+        use anyhow::Result;
+        use chumsky::Parser;
+
+        use crate::model::{Builder, Chunk, EntityId, Type};
+        use crate::parser::rust::tests::{wrap_test_err, CONFIG};
+        use crate::parser::rust::{resolve_import, use_decl, UsePath};
+        use crate::{input, parser, Parser as ApyxlParser};
+
+        fn use_path<'a>(path: &[&'a str], alias: Option<&'a str>, glob: bool) -> UsePath<'a> {
+            UsePath {
+                path: path.to_vec(),
+                alias,
+                glob,
+            }
+        }
+
+        #[test]
+        fn paths() -> Result<()> {
+            let paths = use_decl()
+                .parse("pub use a::{b::C as D, e::*, self};")
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                paths,
+                vec![
+                    use_path(&["a", "b", "C"], Some("D"), false),
+                    use_path(&["a", "e"], None, true),
+                    use_path(&["a", "self"], None, false),
+                ]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn relative_to_file_namespace() {
+            let namespace = EntityId::new_unqualified("a.b");
+            let import = |path: &[&str]| {
+                let import = resolve_import(
+                    use_path(path, None, false).into_unresolved_import(),
+                    &namespace,
+                );
+                (import.absolute, import.path.to_string())
+            };
+            assert_eq!(import(&["crate", "c", "D"]), (true, "c.D".to_string()));
+            assert_eq!(import(&["self", "D"]), (true, "a.b.D".to_string()));
+            assert_eq!(import(&["super", "D"]), (true, "a.D".to_string()));
+            assert_eq!(import(&["super", "super", "D"]), (true, "D".to_string()));
+            assert_eq!(import(&["c", "self"]), (false, "c".to_string()));
+        }
+
+        #[test]
+        fn qualifies_types() -> Result<()> {
+            let mut input = input::ChunkBuffer::new();
+            input.add_chunk(
+                Chunk::with_relative_file_path("service/user.rs"),
+                r#"
+                use crate::service::social;
+                use super::social::Status as FriendStatus;
+                struct User {
+                    friends: Vec<social::Friend>,
+                    status: FriendStatus,
+                }
+                "#,
+            );
+            input.add_chunk(
+                Chunk::with_relative_file_path("service/social.rs"),
+                "struct Friend {} enum Status {}",
+            );
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&CONFIG, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+
+            let dto = model
+                .api()
+                .find_dto(&EntityId::new_unqualified("service.user.User"))
+                .unwrap();
+            assert_eq!(
+                dto.fields[0].ty,
+                Type::new_array(Type::Api(
+                    EntityId::try_from("service.social.d:Friend")?,
+                    vec![]
+                ))
+            );
+            assert_eq!(
+                dto.fields[1].ty,
+                Type::Api(EntityId::try_from("service.social.e:Status")?, vec![])
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn within_inline_namespaces() -> Result<()> {
+            let mut input = input::ChunkBuffer::new();
+            input.add_chunk(
+                Chunk::with_relative_file_path("service.rs"),
+                r#"
+                mod social {
+                    struct Friend {}
+                    enum Status {}
+                    mod feed {
+                        struct Post {}
+                        struct Comment {}
+                    }
+                }
+                mod user {
+                    use super::social::Friend;
+                    use super::social::{Status as FriendStatus, feed::*};
+                    struct User {
+                        friend: Friend,
+                        status: FriendStatus,
+                        posts: Vec<Post>,
+                    }
+                }
+                "#,
+            );
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&CONFIG, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+
+            let dto = model
+                .api()
+                .find_dto(&EntityId::new_unqualified("service.user.User"))
+                .unwrap();
+            assert_eq!(
+                dto.fields[0].ty,
+                Type::Api(EntityId::try_from("service.social.d:Friend")?, vec![])
+            );
+            assert_eq!(
+                dto.fields[1].ty,
+                Type::Api(EntityId::try_from("service.social.e:Status")?, vec![])
+            );
+            assert_eq!(
+                dto.fields[2].ty,
+                Type::new_array(Type::Api(
+                    EntityId::try_from("service.social.feed.d:Post")?,
+                    vec![]
+                ))
+            );
+            Ok(())
+        }
     }
 
     mod ty {
@@ -680,10 +1833,48 @@ mod tests {
 
         test!(str, "&str", Type::String);
         test!(bytes_slice, "&[u8]", Type::Bytes);
+        test!(str_lifetime, "&'a str", Type::String);
+        test!(bytes_slice_static, "&'static [u8]", Type::Bytes);
+        test!(mut_ref, "&mut u32", Type::U32);
+        test!(
+            slice_lifetime_mut,
+            "&'a mut [i32]",
+            Type::new_array(Type::I32)
+        );
         test!(
             entity_id,
             "a::b::c",
-            Type::Api(EntityId::new_unqualified("a.b.c"))
+            Type::Api(EntityId::new_unqualified("a.b.c"), vec![])
+        );
+        test!(
+            entity_id_args,
+            "Page<User>",
+            Type::Api(
+                EntityId::new_unqualified("Page"),
+                vec![Type::Api(EntityId::new_unqualified("User"), vec![])]
+            )
+        );
+        test!(
+            entity_id_args_nested,
+            "Page<Vec<T>>",
+            Type::Api(
+                EntityId::new_unqualified("Page"),
+                vec![Type::new_array(Type::Api(
+                    EntityId::new_unqualified("T"),
+                    vec![]
+                ))]
+            )
+        );
+        test!(
+            entity_id_args_multiple,
+            "a::Pair< u8 , b::c >",
+            Type::Api(
+                EntityId::new_unqualified("a.Pair"),
+                vec![
+                    Type::U8,
+                    Type::Api(EntityId::new_unqualified("b.c"), vec![])
+                ]
+            )
         );
 
         // Vec/Array.
@@ -691,13 +1882,25 @@ mod tests {
         test!(
             vec_api,
             "Vec<a::b::c>",
-            Type::new_array(Type::Api(EntityId::new_unqualified("a.b.c")))
+            Type::new_array(Type::Api(EntityId::new_unqualified("a.b.c"), vec![]))
         );
         test!(
             vec_nested,
             "Vec<Vec<Vec<String>>>",
             Type::new_array(Type::new_array(Type::new_array(Type::String)))
         );
+        test!(vec_ref, "&Vec<i32>", Type::new_array(Type::I32));
+        test!(slice, "&[i32]", Type::new_array(Type::I32));
+        test!(
+            slice_api,
+            "&[a::b::c]",
+            Type::new_array(Type::Api(EntityId::new_unqualified("a.b.c"), vec![]))
+        );
+        test!(
+            slice_nested,
+            "&[Vec<&[u8]>]",
+            Type::new_array(Type::new_array(Type::Bytes))
+        );
 
         // Map.
         test!(
@@ -709,10 +1912,15 @@ mod tests {
             map_api,
             "HashMap<dto, a::b::c>",
             Type::new_map(
-                Type::Api(EntityId::new_unqualified("dto")),
-                Type::Api(EntityId::new_unqualified("a.b.c")),
+                Type::Api(EntityId::new_unqualified("dto"), vec![]),
+                Type::Api(EntityId::new_unqualified("a.b.c"), vec![]),
             )
         );
+        test!(
+            btree_map,
+            "BTreeMap<String, i32>",
+            Type::new_map(Type::String, Type::I32)
+        );
         test!(
             map_nested,
             "HashMap<String, HashMap<HashMap<i32, f32>, String>>",
@@ -727,7 +1935,7 @@ mod tests {
         test!(
             option_api,
             "Option<a::b::c>",
-            Type::new_optional(Type::Api(EntityId::new_unqualified("a.b.c")))
+            Type::new_optional(Type::Api(EntityId::new_unqualified("a.b.c"), vec![]))
         );
         test!(
             option_nested,
@@ -736,6 +1944,22 @@ mod tests {
         );
 
         // Combined complex types.
+        test!(
+            map_of_optional_vec,
+            "HashMap<String, Option<Vec<Item>>>",
+            Type::new_map(
+                Type::String,
+                Type::new_optional(Type::new_array(Type::Api(
+                    EntityId::new_unqualified("Item"),
+                    vec![]
+                )))
+            )
+        );
+        test!(
+            nested_with_spacing,
+            "HashMap< String , Option< Vec< i32 > > >",
+            Type::new_map(Type::String, Type::new_optional(Type::new_array(Type::I32)))
+        );
         test!(
             complex_nested,
             "HashMap<Option<String>, Vec<String>>",
@@ -761,7 +1985,8 @@ mod tests {
     mod user_ty {
         use chumsky::Parser;
 
-        use crate::parser::rust::user_ty;
+        use crate::model::Type;
+        use crate::parser::rust::{dto, user_ty};
         use crate::parser::{Config, UserType};
 
         #[test]
@@ -771,18 +1996,45 @@ mod tests {
                     UserType {
                         parse: "i32".to_string(),
                         name: "int".to_string(),
+                        targets: Default::default(),
+                        glob: false,
                     },
                     UserType {
                         parse: "f32".to_string(),
                         name: "float".to_string(),
+                        targets: Default::default(),
+                        glob: false,
                     },
                 ],
+                ..Default::default()
             };
             let ty = user_ty(&config).parse("i32").into_output().unwrap();
             assert_eq!(ty, "int");
             let ty = user_ty(&config).parse("f32").into_output().unwrap();
             assert_eq!(ty, "float");
         }
+
+        #[test]
+        fn glob() {
+            let config = Config {
+                user_types: vec![UserType {
+                    parse: "crate::ffi::*".to_string(),
+                    name: "ffi_{1}".to_string(),
+                    targets: Default::default(),
+                    glob: true,
+                }],
+                ..Default::default()
+            };
+            let dto = dto(&config)
+                .parse("struct dto { a: Vec<crate::ffi::Handle>, b: crate::ffi::raw::Ptr }")
+                .into_output()
+                .unwrap();
+            assert_eq!(
+                dto.fields[0].ty,
+                Type::new_array(Type::User("ffi_Handle".to_string()))
+            );
+            assert_eq!(dto.fields[1].ty, Type::User("ffi_raw::Ptr".to_string()));
+        }
     }
 
     mod entity_id {
@@ -936,68 +2188,238 @@ mod tests {
         }
 
         #[test]
-        fn comment() -> Result<()> {
-            let ns = namespace(&CONFIG)
+        fn comment() -> Result<()> {
+            let ns = namespace(&CONFIG)
+                .parse(
+                    r#"
+            // multi
+            // line
+            // comment
+            mod ns {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                ns.attributes.comments,
+                vec![Comment::unowned(&["multi", "line", "comment"])]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn attributes() -> Result<()> {
+            let namespace = namespace(&CONFIG)
+                .parse(
+                    r#"
+                    #[flag1, flag2]
+                    mod ns {}
+                    "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                namespace.attributes.user,
+                vec![
+                    attribute::User::new_flag("flag1"),
+                    attribute::User::new_flag("flag2"),
+                ]
+            );
+            Ok(())
+        }
+    }
+
+    mod dto {
+        use anyhow::Result;
+        use chumsky::Parser;
+
+        use crate::model::{attribute, Comment, CommentStyle, EntityId, Type};
+        use crate::parser::rust::dto;
+        use crate::parser::rust::tests::wrap_test_err;
+        use crate::parser::rust::tests::CONFIG;
+
+        #[test]
+        fn empty() -> Result<()> {
+            let dto = dto(&CONFIG)
+                .parse(
+                    r#"
+            struct StructName {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(dto.name, "StructName");
+            assert_eq!(dto.fields.len(), 0);
+            Ok(())
+        }
+
+        #[test]
+        fn unit() -> Result<()> {
+            let dto = dto(&CONFIG)
+                .parse("pub struct Marker;")
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(dto.name, "Marker");
+            assert_eq!(dto.fields.len(), 0);
+            Ok(())
+        }
+
+        #[test]
+        fn tuple() -> Result<()> {
+            let dto = dto(&CONFIG)
+                .parse(
+                    r#"
+            struct Id(
+                // comment
+                pub u128,
+                #[attr]
+                Vec<String>,
+            );
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(dto.name, "Id");
+            assert_eq!(dto.fields.len(), 2);
+            assert_eq!(dto.fields[0].name, "field0");
+            assert_eq!(dto.fields[0].ty, Type::U128);
+            assert_eq!(
+                dto.fields[0].attributes.comments,
+                vec![Comment::unowned(&["comment"])]
+            );
+            assert_eq!(dto.fields[1].name, "field1");
+            assert_eq!(dto.fields[1].ty, Type::Array(Box::new(Type::String)));
+            assert_eq!(dto.fields[1].attributes.user[0].name, "attr");
+            Ok(())
+        }
+
+        #[test]
+        fn pub_fields() -> Result<()> {
+            let dto = dto(&CONFIG)
+                .parse(
+                    r#"
+            struct User {
+                // comment
+                pub id: u64,
+                #[attr]
+                pub name: String,
+                email: String,
+            }
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(dto.fields.len(), 3);
+            assert_eq!(dto.fields[0].name, "id");
+            assert_eq!(dto.fields[0].ty, Type::U64);
+            assert_eq!(
+                dto.fields[0].attributes.comments,
+                vec![Comment::unowned(&["comment"])]
+            );
+            assert_eq!(dto.fields[1].name, "name");
+            assert_eq!(dto.fields[1].attributes.user[0].name, "attr");
+            assert_eq!(dto.fields[2].name, "email");
+            Ok(())
+        }
+
+        #[test]
+        fn tuple_too_many_fields() {
+            let fields = vec!["u8"; 17].join(", ");
+            let data = format!("struct Big({});", fields);
+            assert!(dto(&CONFIG).parse(&data).into_result().is_err());
+        }
+
+        #[test]
+        fn generic() -> Result<()> {
+            let dto = dto(&CONFIG)
+                .parse(
+                    r#"
+            struct Page < K, V > {
+                items: HashMap<K, Vec<V>>,
+                other: Kind,
+            }
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(dto.name, "Page");
+            assert_eq!(dto.type_params, vec!["K", "V"]);
+            assert_eq!(
+                dto.fields[0].ty,
+                Type::new_map(
+                    Type::Generic("K".to_string()),
+                    Type::new_array(Type::Generic("V".to_string()))
+                )
+            );
+            assert_eq!(
+                dto.fields[1].ty,
+                Type::Api(EntityId::new_unqualified("Kind"), vec![])
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn generic_field_types() -> Result<()> {
+            let dto = dto(&CONFIG)
                 .parse(
                     r#"
-            // multi
-            // line
-            // comment
-            mod ns {}
+            struct Resp<T> {
+                page: Page<User>,
+                items: Page<Vec<T>>,
+            }
             "#,
                 )
                 .into_result()
                 .map_err(wrap_test_err)?;
+            let page = |arg| Type::Api(EntityId::new_unqualified("Page"), vec![arg]);
             assert_eq!(
-                ns.attributes.comments,
-                vec![Comment::unowned(&["multi", "line", "comment"])]
+                dto.fields[0].ty,
+                page(Type::Api(EntityId::new_unqualified("User"), vec![]))
+            );
+            assert_eq!(
+                dto.fields[1].ty,
+                page(Type::new_array(Type::Generic("T".to_string())))
             );
             Ok(())
         }
 
         #[test]
-        fn attributes() -> Result<()> {
-            let namespace = namespace(&CONFIG)
+        fn lifetimes_and_where_clause() -> Result<()> {
+            let dto = dto(&CONFIG)
                 .parse(
                     r#"
-                    #[flag1, flag2]
-                    mod ns {}
-                    "#,
+            struct Page<'a, T: Clone + Into<Vec<u8>>> where T: Default {
+                name: &'a str,
+                items: Vec<T>,
+            }
+            "#,
                 )
                 .into_result()
                 .map_err(wrap_test_err)?;
+            assert_eq!(dto.name, "Page");
+            assert_eq!(dto.type_params, vec!["T"]);
+            assert_eq!(dto.fields[0].ty, Type::String);
             assert_eq!(
-                namespace.attributes.user,
-                vec![
-                    attribute::User::new_flag("flag1"),
-                    attribute::User::new_flag("flag2"),
-                ]
+                dto.fields[1].ty,
+                Type::new_array(Type::Generic("T".to_string()))
             );
             Ok(())
         }
-    }
-
-    mod dto {
-        use anyhow::Result;
-        use chumsky::Parser;
-
-        use crate::model::{attribute, Comment};
-        use crate::parser::rust::dto;
-        use crate::parser::rust::tests::wrap_test_err;
-        use crate::parser::rust::tests::CONFIG;
 
         #[test]
-        fn empty() -> Result<()> {
+        fn non_ascii_names() -> Result<()> {
             let dto = dto(&CONFIG)
                 .parse(
                     r#"
-            struct StructName {}
+            struct Größe {
+                höhe: straße::Größe,
+            }
             "#,
                 )
                 .into_result()
                 .map_err(wrap_test_err)?;
-            assert_eq!(dto.name, "StructName");
-            assert_eq!(dto.fields.len(), 0);
+            assert_eq!(dto.name, "Größe");
+            assert_eq!(dto.fields[0].name, "höhe");
             Ok(())
         }
 
@@ -1080,7 +2502,7 @@ mod tests {
             );
             assert_eq!(
                 dto.fields[1].attributes.comments,
-                vec![Comment::unowned(&["comment"])]
+                vec![Comment::unowned(&["comment"]).with_style(CommentStyle::Block)]
             );
             Ok(())
         }
@@ -1112,7 +2534,7 @@ mod tests {
         use anyhow::Result;
         use chumsky::Parser;
 
-        use crate::model::{attribute, Comment};
+        use crate::model::{attribute, Comment, CommentStyle, EntityId, Type};
         use crate::parser::rust::rpc;
         use crate::parser::rust::tests::wrap_test_err;
         use crate::parser::rust::tests::CONFIG;
@@ -1149,6 +2571,39 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn async_fn() -> Result<()> {
+            let rpc = rpc(&CONFIG)
+                .parse(
+                    r#"
+            pub async fn rpc_name() {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(rpc.name, "rpc_name");
+            assert!(rpc.is_async);
+            Ok(())
+        }
+
+        #[test]
+        fn self_receivers() -> Result<()> {
+            for data in [
+                "fn rpc_name(self, x: u32) {}",
+                "fn rpc_name(&self, x: u32) {}",
+                "fn rpc_name(&mut self, x: u32) {}",
+                "fn rpc_name(mut self, x: u32) {}",
+            ] {
+                let rpc = rpc(&CONFIG)
+                    .parse(data)
+                    .into_result()
+                    .map_err(wrap_test_err)?;
+                assert_eq!(rpc.params.len(), 1, "{}", data);
+                assert_eq!(rpc.params[0].name, "x");
+            }
+            Ok(())
+        }
+
         #[test]
         fn fn_keyword_smushed() {
             let rpc = rpc(&CONFIG)
@@ -1254,7 +2709,7 @@ mod tests {
             );
             assert_eq!(
                 rpc.params[1].attributes.comments,
-                vec![Comment::unowned(&["comment"])]
+                vec![Comment::unowned(&["comment"]).with_style(CommentStyle::Block)]
             );
             assert_eq!(
                 rpc.params[2].attributes.comments,
@@ -1333,6 +2788,83 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn result() -> Result<()> {
+            let rpc = rpc(&CONFIG)
+                .parse(
+                    r#"
+            fn rpc_name() -> Result< Vec<u32> , Error > {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(rpc.return_type, Some(Type::new_array(Type::U32)));
+            assert_eq!(
+                rpc.error_type,
+                Some(Type::Api(EntityId::new_unqualified("Error"), vec![]))
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn generic() -> Result<()> {
+            let rpc = rpc(&CONFIG)
+                .parse(
+                    r#"
+            fn rpc_name<T, E>(x: T, y: Vec<u32>) -> Result<Option<T>, E> {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(rpc.type_params, vec!["T", "E"]);
+            assert_eq!(rpc.params[0].ty, Type::Generic("T".to_string()));
+            assert_eq!(rpc.params[1].ty, Type::new_array(Type::U32));
+            assert_eq!(
+                rpc.return_type,
+                Some(Type::new_optional(Type::Generic("T".to_string())))
+            );
+            assert_eq!(rpc.error_type, Some(Type::Generic("E".to_string())));
+            Ok(())
+        }
+
+        #[test]
+        fn lifetimes_and_where_clause() -> Result<()> {
+            let rpc = rpc(&CONFIG)
+                .parse(
+                    r#"
+            fn rpc_name<'a, T>(x: &'a str, y: &'a mut T) -> Option<T>
+            where
+                T: Clone,
+            {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(rpc.type_params, vec!["T"]);
+            assert_eq!(rpc.params[0].ty, Type::String);
+            assert_eq!(rpc.params[1].ty, Type::Generic("T".to_string()));
+            assert_eq!(
+                rpc.return_type,
+                Some(Type::new_optional(Type::Generic("T".to_string())))
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn result_unit() -> Result<()> {
+            let rpc = rpc(&CONFIG)
+                .parse(
+                    r#"
+            fn rpc_name() -> Result<(), String> {}
+            "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(rpc.return_type, None);
+            assert_eq!(rpc.error_type, Some(Type::String));
+            Ok(())
+        }
+
         #[test]
         fn attributes() -> Result<()> {
             let rpc = rpc(&CONFIG)
@@ -1374,6 +2906,16 @@ mod tests {
             Ok(())
         }
 
+        #[test]
+        fn negative() -> Result<()> {
+            let value = en_value()
+                .parse("Value = -12")
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(value.number, -12);
+            Ok(())
+        }
+
         #[test]
         fn attributes() -> Result<()> {
             let value = en_value()
@@ -1400,7 +2942,7 @@ mod tests {
         use anyhow::Result;
         use chumsky::Parser;
 
-        use crate::model::{attribute, Comment, EnumValue, EnumValueNumber};
+        use crate::model::{attribute, Comment, CommentStyle, EnumValue, EnumValueNumber};
         use crate::parser::rust::en;
         use crate::parser::rust::tests::wrap_test_err;
 
@@ -1419,7 +2961,7 @@ mod tests {
                 .into_result()
                 .map_err(wrap_test_err)?;
             assert_eq!(en.name, "en");
-            assert_value(en.values.get(0), "Value0", 0);
+            assert_value(en.values.first(), "Value0", 0);
             assert_value(en.values.get(1), "Value1", 1);
             assert_value(en.values.get(2), "Value2", 2);
             Ok(())
@@ -1441,7 +2983,7 @@ mod tests {
                 .into_result()
                 .map_err(wrap_test_err)?;
             assert_eq!(en.name, "en");
-            assert_value(en.values.get(0), "Value0", 10);
+            assert_value(en.values.first(), "Value0", 10);
             assert_value(en.values.get(1), "Value1", 25);
             assert_value(en.values.get(2), "Value2", 999);
             assert_value(en.values.get(3), "SameNum", 999);
@@ -1464,7 +3006,7 @@ mod tests {
                 .into_result()
                 .map_err(wrap_test_err)?;
             assert_eq!(en.name, "en");
-            assert_value(en.values.get(0), "Value0", 0);
+            assert_value(en.values.first(), "Value0", 0);
             assert_value(en.values.get(1), "Value1", 25);
             assert_value(en.values.get(2), "Value2", 26);
             assert_value(en.values.get(3), "SameNum", 999);
@@ -1516,7 +3058,7 @@ mod tests {
             );
             assert_eq!(
                 en.values[1].attributes.comments,
-                vec![Comment::unowned(&["comment"])]
+                vec![Comment::unowned(&["comment"]).with_style(CommentStyle::Block)]
             );
             assert_eq!(
                 en.values[2].attributes.comments,
@@ -1551,14 +3093,9 @@ mod tests {
             expected_name: &str,
             expected_number: EnumValueNumber,
         ) {
-            assert_eq!(
-                actual,
-                Some(&EnumValue {
-                    name: expected_name,
-                    number: expected_number,
-                    ..Default::default()
-                })
-            );
+            let actual = actual.unwrap();
+            assert_eq!(actual.name, expected_name);
+            assert_eq!(actual.number, expected_number);
         }
     }
 
@@ -1566,10 +3103,11 @@ mod tests {
         use anyhow::Result;
         use chumsky::Parser;
 
-        use crate::model::Comment;
+        use crate::model::{Builder, Comment, CommentStyle};
         use crate::parser::rust::tests::wrap_test_err;
         use crate::parser::rust::tests::CONFIG;
         use crate::parser::rust::{comment, multi_comment, namespace};
+        use crate::{input, parser, Parser as ApyxlParser};
 
         #[test]
         fn empty_comment_err() {
@@ -1625,11 +3163,11 @@ mod tests {
             assert_eq!(
                 value,
                 vec![
-                    Comment::unowned(&["line one"]),
+                    Comment::unowned(&["line one"]).with_style(CommentStyle::Block),
                     Comment::unowned(&["line two", "line three"]),
                     Comment::unowned(&["line four"]),
-                    Comment::unowned(&["line five"]),
-                    Comment::unowned(&["line six"]),
+                    Comment::unowned(&["line five"]).with_style(CommentStyle::Block),
+                    Comment::unowned(&["line six"]).with_style(CommentStyle::Block),
                 ]
             );
             Ok(())
@@ -1641,7 +3179,10 @@ mod tests {
                 .parse("/* block comment */")
                 .into_result()
                 .map_err(wrap_test_err)?;
-            assert_eq!(value, Comment::unowned(&["block comment"]));
+            assert_eq!(
+                value,
+                Comment::unowned(&["block comment"]).with_style(CommentStyle::Block)
+            );
             Ok(())
         }
 
@@ -1683,12 +3224,139 @@ mod tests {
                 .map_err(wrap_test_err)?;
             Ok(())
         }
+
+        #[test]
+        fn doc_comment() -> Result<()> {
+            let value = comment()
+                .parse("/// doc\n///  comment\n")
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                value,
+                Comment::unowned(&["doc", " comment"]).with_style(CommentStyle::Doc)
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn inner_doc_comment_attaches_to_namespace() -> Result<()> {
+            let ns = namespace(&CONFIG)
+                .parse(
+                    r#"
+                    // outer
+                    mod ns {
+                        //! inner
+                        //! doc
+
+                        // dto
+                        struct dto {}
+                    }
+                    "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                ns.attributes.comments,
+                vec![
+                    Comment::unowned(&["outer"]),
+                    Comment::unowned(&["inner", "doc"]).with_style(CommentStyle::InnerDoc),
+                ]
+            );
+            assert_eq!(
+                ns.dto("dto").unwrap().attributes.comments,
+                vec![Comment::unowned(&["dto"])]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn inner_doc_comment_attaches_to_file() -> Result<()> {
+            let mut input = input::Buffer::new(
+                r#"
+                // license
+                //! file doc
+                use asdf;
+                struct dto {}
+                "#,
+            );
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&CONFIG, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            assert_eq!(
+                model.api().attributes.comments,
+                vec![Comment::unowned(&["file doc"]).with_style(CommentStyle::InnerDoc)]
+            );
+            assert!(model
+                .api()
+                .dto("dto")
+                .unwrap()
+                .attributes
+                .comments
+                .is_empty());
+            Ok(())
+        }
+
+        #[test]
+        fn comment_after_entity_attaches_to_next() -> Result<()> {
+            let ns = namespace(&CONFIG)
+                .parse(
+                    r#"
+                    mod ns {
+                        struct a {}
+                        // b
+                        struct b {}
+                    }
+                    "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                ns.dto("b").unwrap().attributes.comments,
+                vec![Comment::unowned(&["b"])]
+            );
+            Ok(())
+        }
+
+        #[test]
+        fn trailing_comments() -> Result<()> {
+            let ns = namespace(&CONFIG)
+                .parse(
+                    r#"
+                    mod ns {
+                        struct dto {
+                            field: u32,
+                            // dto
+                        }
+                        enum en {
+                            Value,
+                            /* en */
+                        }
+                        // ns
+                    }
+                    "#,
+                )
+                .into_result()
+                .map_err(wrap_test_err)?;
+            assert_eq!(
+                ns.attributes.trailing_comments,
+                vec![Comment::unowned(&["ns"])]
+            );
+            assert_eq!(
+                ns.dto("dto").unwrap().attributes.trailing_comments,
+                vec![Comment::unowned(&["dto"])]
+            );
+            assert_eq!(
+                ns.en("en").unwrap().attributes.trailing_comments,
+                vec![Comment::unowned(&["en"]).with_style(CommentStyle::Block)]
+            );
+            Ok(())
+        }
     }
 
     mod expr_block {
         use chumsky::{text, Parser};
 
-        use crate::model::Comment;
+        use crate::model::{Comment, CommentStyle};
         use crate::parser::rust::{expr_block, ExprBlock};
 
         #[test]
@@ -1761,7 +3429,7 @@ mod tests {
             assert_eq!(
                 result.unwrap(),
                 vec![ExprBlock::Nested(vec![ExprBlock::Comment(
-                    Comment::unowned(&["don't break! {{{"])
+                    Comment::unowned(&["don't break! {{{"]).with_style(CommentStyle::Block)
                 )])]
             );
         }
@@ -1786,12 +3454,16 @@ mod tests {
     }
 
     mod attributes {
+        use anyhow::Result;
         use chumsky::Parser;
 
         use crate::model::attribute;
         use crate::model::attribute::UserData;
+        use crate::model::Builder;
         use crate::parser::rust::dto;
         use crate::parser::rust::tests::CONFIG;
+        use crate::parser::Config;
+        use crate::{input, parser, Parser as ApyxlParser};
 
         #[test]
         fn flags() {
@@ -1877,6 +3549,103 @@ mod tests {
             )
         }
 
+        #[test]
+        fn stacked() {
+            run_test(
+                r#"
+                    #[attr0]
+                    #[attr1(a), attr2]
+                    struct dto {}
+                    "#,
+                vec![
+                    attribute::User::new_flag("attr0"),
+                    attribute::User::new("attr1", vec![UserData::new(None, "a")]),
+                    attribute::User::new_flag("attr2"),
+                ],
+            )
+        }
+
+        #[test]
+        fn literals() {
+            run_test(
+                r#"
+                    #[serde(rename = "user id", default), limits(min = -1, max = 2.5, 3)]
+                    struct dto {}
+                    "#,
+                vec![
+                    attribute::User::new(
+                        "serde",
+                        vec![
+                            UserData::new_quoted(Some("rename"), "user id"),
+                            UserData::new(None, "default"),
+                        ],
+                    ),
+                    attribute::User::new(
+                        "limits",
+                        vec![
+                            UserData::new(Some("min"), "-1"),
+                            UserData::new(Some("max"), "2.5"),
+                            UserData::new(None, "3"),
+                        ],
+                    ),
+                ],
+            )
+        }
+
+        #[test]
+        fn escapes() {
+            run_test(
+                r#"
+                    #[api(tag = "a \"b\"", path = "C:\\dir\\", lines = "a\nb", other = "\q")]
+                    struct dto {}
+                    "#,
+                vec![attribute::User::new(
+                    "api",
+                    vec![
+                        UserData::new_quoted(Some("tag"), r#"a "b""#),
+                        UserData::new_quoted(Some("path"), r"C:\dir\"),
+                        UserData::new_quoted(Some("lines"), "a\nb"),
+                        UserData::new_quoted(Some("other"), r"\q"),
+                    ],
+                )],
+            )
+        }
+
+        #[test]
+        fn field_and_rpc() -> Result<()> {
+            let config = Config::default();
+            let mut input = input::Buffer::new(
+                r#"
+                    struct dto {
+                        #[deprecated]
+                        #[serde(rename = "x")]
+                        field: u32,
+                    }
+                    #[deprecated(note = "use other")]
+                    fn rpc() {}
+                    "#,
+            );
+            let mut builder = Builder::default();
+            parser::Rust::default().parse(&config, &mut input, &mut builder)?;
+            let model = builder.build().unwrap();
+            let field = model.api().dto("dto").unwrap().field("field").unwrap();
+            assert_eq!(
+                field.attributes.user,
+                vec![
+                    attribute::User::new_flag("deprecated"),
+                    attribute::User::new("serde", vec![UserData::new_quoted(Some("rename"), "x")]),
+                ]
+            );
+            assert_eq!(
+                model.api().rpc("rpc").unwrap().attributes.user,
+                vec![attribute::User::new(
+                    "deprecated",
+                    vec![UserData::new_quoted(Some("note"), "use other")]
+                )]
+            );
+            Ok(())
+        }
+
         fn run_test(content: &str, expected: Vec<attribute::User>) {
             let dto = dto(&CONFIG).parse(content).into_result().unwrap();
             assert_eq!(dto.attributes.user, expected);
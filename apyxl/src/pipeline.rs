@@ -0,0 +1,823 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+
+use crate::model::EntityId;
+use crate::view::transforms::{
+    AttributeFilter, Case, CaseConversion, FlattenNamespaces, RenameFields, StripAffixes,
+};
+use crate::view::{self, EntityIdFilter, Order, Transformer};
+use crate::{generator, input, output, parser, Baseline, DeadTypes, Executor, Mode, Registry};
+
+/// Names of the pipeline files that [Pipeline::from_config] looks for within a directory, in
+/// order.
+pub const CONFIG_FILE_NAMES: &[&str] = &["apyxl.toml", "apyxl.yaml", "apyxl.yml"];
+
+/// A complete description of an apyxl run that can be loaded from a TOML or YAML file: the input
+/// files, the parser, the [parser::Config], and each generator with its outputs.
+///
+/// Relative paths within a file are relative to the directory containing the file.
+///
+/// Example (TOML):
+/// ```toml
+/// input = ["src/**/*.rs"]
+/// exclude = ["src/**/tests/**"]
+/// output_root = "generated"
+/// scripts = ["transforms.rhai"]
+///
+/// [parser]
+/// name = "rust"
+/// config = "parser_config.json"
+///
+/// [transforms]
+/// exclude_attributes = ["internal"]
+/// rename_fields = { id = "identifier" }
+/// order = "name"
+///
+/// [[generators]]
+/// name = "rust"
+/// output = ["rust"]
+/// formatters = ["rustfmt --edition 2021"]
+///
+/// [config.generator.rust]
+/// import_comments = false
+/// ```
+///
+/// `config` may either be a path to a [parser::Config] in json format, or the [parser::Config]
+/// itself inline, including the options of each parser and generator. For compatibility, it may
+/// instead be set as `parser.config`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Pipeline {
+    /// Globs of files to be parsed as API source files.
+    pub input: Vec<String>,
+    /// Globs of files to leave out of the `input`, e.g. `**/generated/**`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    pub parser: ParserConfig,
+    #[serde(default)]
+    pub config: Option<ParserConfigSource>,
+    /// Directory to cache parsed input in. See [parser::Cache].
+    #[serde(default)]
+    pub parse_cache: Option<PathBuf>,
+    /// Either `strict` (the default) or `lenient`. See [Mode].
+    #[serde(default)]
+    pub mode: Mode,
+    /// Fail the run on breaking changes from a committed baseline. See [Executor::baseline].
+    #[serde(default)]
+    pub baseline: Option<BaselineConfig>,
+    /// Report dtos and enums that nothing uses as warnings. See [Executor::dead_types].
+    #[serde(default)]
+    pub dead_types: Option<DeadTypesConfig>,
+    /// Built-in view transforms, applied for every generator before any `scripts`.
+    #[serde(default)]
+    pub transforms: TransformsConfig,
+    /// Paths to Rhai scripts of view transforms, applied in order for every generator. See
+    /// [crate::view::Script].
+    #[cfg(feature = "script")]
+    #[serde(default)]
+    pub scripts: Vec<PathBuf>,
+    pub generators: Vec<GeneratorConfig>,
+    /// All generator `output`s are relative to this path. Defaults to the root.
+    #[serde(default)]
+    pub output_root: PathBuf,
+    /// Directory that relative paths are relative to.
+    #[serde(skip)]
+    pub root: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParserConfig {
+    pub name: String,
+    #[serde(default)]
+    pub config: Option<ParserConfigSource>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ParserConfigSource {
+    /// Path to a [parser::Config] in json format.
+    Path(PathBuf),
+    Inline(parser::Config),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineConfig {
+    /// Path to the baseline API, as written by the `json` generator. See [Baseline].
+    pub path: PathBuf,
+    /// Path to a file listing the entities allowed to break. See [Baseline::allowlist_file].
+    #[serde(default)]
+    pub allowlist: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DeadTypesConfig {
+    /// Ids of the entities to treat as used along with everything within them, e.g. `events` or
+    /// `events.d:Event`. See [DeadTypes::root].
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Leave the dead types out of the generated output. See [DeadTypes::filter].
+    #[serde(default)]
+    pub filter: bool,
+}
+
+/// The built-in view transforms of a [Pipeline]. See [view::transforms].
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransformsConfig {
+    /// Ids of the entities to include along with everything within them, e.g. `users` or
+    /// `users.d:User`. Everything is included if empty. See [EntityIdFilter::include].
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Ids of the entities to exclude along with everything within them. See
+    /// [EntityIdFilter::exclude].
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Only include dtos, rpcs and enums with one of these user attributes, e.g. `api`. See
+    /// [AttributeFilter::include].
+    #[serde(default)]
+    pub include_attributes: Vec<String>,
+    /// Exclude anything with one of these user attributes, e.g. `internal`. See
+    /// [AttributeFilter::exclude].
+    #[serde(default)]
+    pub exclude_attributes: Vec<String>,
+    /// Renames of dto fields and rpc params, from the old name to the new. See [RenameFields].
+    #[serde(default)]
+    pub rename_fields: BTreeMap<String, String>,
+    /// Prefixes to strip from type names, e.g. `I`. See [StripAffixes].
+    #[serde(default)]
+    pub strip_prefixes: Vec<String>,
+    /// Suffixes to strip from type names, e.g. `Dto`. See [StripAffixes].
+    #[serde(default)]
+    pub strip_suffixes: Vec<String>,
+    /// The [Case] of names per kind of entity. See [CaseConversion].
+    #[serde(default)]
+    pub case: CaseConfig,
+    /// See [FlattenNamespaces].
+    #[serde(default)]
+    pub flatten_namespaces: bool,
+    /// The order of the children of namespaces. See [Transformer::with_order]. The `go`, `kotlin`
+    /// and `typescript` generators use their own `order` option instead.
+    #[serde(default)]
+    pub order: Order,
+}
+
+/// See [CaseConversion].
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CaseConfig {
+    #[serde(default)]
+    pub namespaces: Option<Case>,
+    /// Dtos and enums.
+    #[serde(default)]
+    pub types: Option<Case>,
+    #[serde(default)]
+    pub rpcs: Option<Case>,
+    /// Dto fields and rpc params.
+    #[serde(default)]
+    pub fields: Option<Case>,
+    #[serde(default)]
+    pub enum_values: Option<Case>,
+}
+
+impl TransformsConfig {
+    /// Adds the configured transforms to `transformer`, e.g. an [Executor].
+    pub fn apply<T: Transformer>(&self, mut transformer: T) -> Result<T> {
+        if !self.include.is_empty() || !self.exclude.is_empty() {
+            let mut filter = EntityIdFilter::default();
+            for id in &self.include {
+                filter = filter.include(entity_id(id)?);
+            }
+            for id in &self.exclude {
+                filter = filter.exclude(entity_id(id)?);
+            }
+            transformer = transformer.with_namespace_transform(filter);
+        }
+        if !self.include_attributes.is_empty() || !self.exclude_attributes.is_empty() {
+            let mut filter = AttributeFilter::default();
+            for name in &self.include_attributes {
+                filter = filter.include(name);
+            }
+            for name in &self.exclude_attributes {
+                filter = filter.exclude(name);
+            }
+            transformer = transformer.with_transforms(filter);
+        }
+        if !self.rename_fields.is_empty() {
+            let mut renames = RenameFields::default();
+            for (from, to) in &self.rename_fields {
+                renames = renames.rename(from, to);
+            }
+            transformer = transformer.with_transforms(renames);
+        }
+        if !self.strip_prefixes.is_empty() || !self.strip_suffixes.is_empty() {
+            let mut strip = StripAffixes::default();
+            for prefix in &self.strip_prefixes {
+                strip = strip.prefix(prefix);
+            }
+            for suffix in &self.strip_suffixes {
+                strip = strip.suffix(suffix);
+            }
+            transformer = transformer.with_transforms(strip);
+        }
+        if let Some(case) = self.case.conversion() {
+            transformer = transformer.with_transforms(case);
+        }
+        if self.flatten_namespaces {
+            transformer = transformer.with_transforms(FlattenNamespaces {});
+        }
+        Ok(transformer.with_order(self.order))
+    }
+}
+
+impl CaseConfig {
+    /// The [CaseConversion], unless no case is set.
+    fn conversion(&self) -> Option<CaseConversion> {
+        let cases = [
+            self.namespaces,
+            self.types,
+            self.rpcs,
+            self.fields,
+            self.enum_values,
+        ];
+        if cases.iter().all(Option::is_none) {
+            return None;
+        }
+        let mut conversion = CaseConversion::default();
+        if let Some(case) = self.namespaces {
+            conversion = conversion.namespaces(case);
+        }
+        if let Some(case) = self.types {
+            conversion = conversion.types(case);
+        }
+        if let Some(case) = self.rpcs {
+            conversion = conversion.rpcs(case);
+        }
+        if let Some(case) = self.fields {
+            conversion = conversion.fields(case);
+        }
+        if let Some(case) = self.enum_values {
+            conversion = conversion.enum_values(case);
+        }
+        Some(conversion)
+    }
+}
+
+fn entity_id(id: &str) -> Result<EntityId> {
+    EntityId::try_from(id).map_err(|err| anyhow!("invalid entity id '{}': {}", id, err))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorConfig {
+    pub name: String,
+    /// Path to a WASM module implementing the generator, see [generator::Wasm]. If set, `name` is
+    /// only used to identify the generator rather than looking it up in the [Registry].
+    #[serde(default)]
+    pub wasm: Option<PathBuf>,
+    /// Fuel limit of each run of the `wasm` module, see [generator::Wasm::with_fuel].
+    #[serde(default)]
+    pub wasm_fuel: Option<u64>,
+    /// Paths to empty (or nonexistent) directories, relative to the `output_root`.
+    pub output: Vec<PathBuf>,
+    /// Commands that each generated file is piped through before it's written, in order, e.g.
+    /// `rustfmt --edition 2021`. See [output::FormatCommand].
+    #[serde(default)]
+    pub formatters: Vec<String>,
+}
+
+impl Pipeline {
+    /// Loads a [Pipeline] from `path`, either a pipeline file (see [Pipeline::from_file]) or a
+    /// directory, e.g. the root of a project, containing one of [CONFIG_FILE_NAMES].
+    pub fn from_config<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.is_dir() {
+            return Self::from_file(path);
+        }
+        match CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| path.join(name))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Self::from_file(path),
+            None => Err(anyhow!(
+                "directory '{}' contains none of {}",
+                path.display(),
+                CONFIG_FILE_NAMES.join(", ")
+            )),
+        }
+    }
+
+    /// Loads a [Pipeline] from a file, using the file's extension to pick the format: `.toml`,
+    /// `.yaml` or `.yml`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path)
+            .with_context(|| format!("read pipeline file '{}'", path.display()))?;
+        let pipeline = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml(&data),
+            Some("yaml") | Some("yml") => Self::from_yaml(&data),
+            _ => {
+                return Err(anyhow!(
+                    "pipeline file '{}' must have a .toml, .yaml or .yml extension",
+                    path.display()
+                ))
+            }
+        }
+        .with_context(|| format!("parse pipeline file '{}'", path.display()))?;
+        let root = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        Ok(pipeline.root(root))
+    }
+
+    /// Parses a [Pipeline] in TOML format. Relative paths are relative to the working directory.
+    pub fn from_toml(data: &str) -> Result<Self> {
+        Ok(toml::from_str(data)?)
+    }
+
+    /// Parses a [Pipeline] in YAML format. Relative paths are relative to the working directory.
+    pub fn from_yaml(data: &str) -> Result<Self> {
+        Ok(serde_yaml::from_str(data)?)
+    }
+
+    /// Directory that relative paths are relative to.
+    pub fn root<P: Into<PathBuf>>(mut self, root: P) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// Loads the [parser::Config] from either `config` or `parser.config`, if any.
+    pub fn parser_config(&self) -> Result<Option<parser::Config>> {
+        let source = match (&self.config, &self.parser.config) {
+            (Some(_), Some(_)) => {
+                return Err(anyhow!(
+                    "only one of 'config' and 'parser.config' may be specified"
+                ))
+            }
+            (Some(source), None) | (None, Some(source)) => source,
+            (None, None) => return Ok(None),
+        };
+        match source {
+            ParserConfigSource::Inline(config) => Ok(Some(config.clone())),
+            ParserConfigSource::Path(path) => {
+                let path = self.root.join(path);
+                let data = fs::read_to_string(&path)
+                    .with_context(|| format!("read parser config '{}'", path.display()))?;
+                let config = parser::Config::from_json(&data)
+                    .with_context(|| format!("parse parser config '{}'", path.display()))?;
+                Ok(Some(config))
+            }
+        }
+    }
+
+    /// Runs the pipeline with the built-in parsers and generators, writing every generator's output
+    /// to its [output::FileSet]s.
+    pub fn execute(&self) -> Result<()> {
+        self.execute_with_registry(&Registry::default())
+    }
+
+    /// Runs the pipeline, looking up parser and generator names in `registry`. Each is created with
+    /// its section of the [parser::Config], see [Registry::validate_config].
+    pub fn execute_with_registry(&self, registry: &Registry) -> Result<()> {
+        let config = self.parser_config()?.unwrap_or_default();
+        registry.validate_config(&config)?;
+        let input = input::Glob::new_with_root_excluding(&self.root, &self.input, &self.exclude)?;
+        let parser = registry.create_parser_with_config(&self.parser.name, &config)?;
+        let mut exe = Executor::new(input, parser)
+            .mode(self.mode)
+            .parser_config(config.clone());
+        if let Some(dir) = &self.parse_cache {
+            exe = exe.parse_cache(parser::Cache::new(self.root.join(dir))?);
+        }
+        if let Some(config) = &self.baseline {
+            let mut baseline = Baseline::from_file(self.root.join(&config.path))?;
+            if let Some(path) = &config.allowlist {
+                baseline = baseline.allowlist_file(self.root.join(path))?;
+            }
+            exe = exe.baseline(baseline);
+        }
+        if let Some(config) = &self.dead_types {
+            let mut dead_types = DeadTypes::default().filter(config.filter);
+            for root in &config.roots {
+                let entity_id = EntityId::try_from(root.as_str())
+                    .map_err(|err| anyhow!("invalid dead type root '{}': {}", root, err))?;
+                dead_types = dead_types.root(entity_id);
+            }
+            exe = exe.dead_types(dead_types);
+        }
+        exe = self.transforms.apply(exe)?;
+        #[cfg(feature = "script")]
+        for path in &self.scripts {
+            exe = exe.with_transforms(view::Script::from_file(self.root.join(path))?);
+        }
+        let output_root = self.root.join(&self.output_root);
+        for generator_config in &self.generators {
+            exe = match &generator_config.wasm {
+                None => exe.generator(
+                    registry.create_generator_with_config(&generator_config.name, &config)?,
+                ),
+                Some(path) => {
+                    let mut wasm = generator::Wasm::from_file(self.root.join(path))?;
+                    if let Some(fuel) = generator_config.wasm_fuel {
+                        wasm = wasm.with_fuel(fuel);
+                    }
+                    exe.generator(wasm)
+                }
+            };
+            let formatters = generator_config
+                .formatters
+                .iter()
+                .map(|command| output::FormatCommand::parse(command))
+                .collect::<Result<Vec<_>>>()?;
+            for path in &generator_config.output {
+                let file_set = output::FileSet::new(output_root.join(path))?;
+                if formatters.is_empty() {
+                    exe = exe.output(file_set);
+                } else {
+                    let mut output = output::PostProcessed::new(file_set);
+                    for formatter in &formatters {
+                        output = output.processor(formatter.clone());
+                    }
+                    exe = exe.output(output);
+                }
+            }
+        }
+        exe.execute()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use crate::pipeline::ParserConfigSource;
+    use crate::Pipeline;
+
+    #[test]
+    fn from_toml() -> Result<()> {
+        let pipeline = Pipeline::from_toml(
+            r#"
+            input = ["a/**/*.rs", "b.rs"]
+            exclude = ["a/tests/**"]
+            output_root = "gen"
+
+            [parser]
+            name = "rust"
+            config = "parser_config.json"
+
+            [[generators]]
+            name = "rust"
+            output = ["r0", "r1"]
+
+            [[generators]]
+            name = "dbg"
+            output = ["d"]
+            "#,
+        )?;
+        assert_eq!(pipeline.input, vec!["a/**/*.rs", "b.rs"]);
+        assert_eq!(pipeline.exclude, vec!["a/tests/**"]);
+        assert_eq!(pipeline.output_root, PathBuf::from("gen"));
+        assert_eq!(pipeline.parser.name, "rust");
+        assert!(matches!(
+            pipeline.parser.config,
+            Some(ParserConfigSource::Path(path)) if path.as_os_str() == "parser_config.json"
+        ));
+        assert_eq!(pipeline.generators.len(), 2);
+        assert_eq!(pipeline.generators[0].name, "rust");
+        assert_eq!(
+            pipeline.generators[0].output,
+            vec![PathBuf::from("r0"), PathBuf::from("r1")]
+        );
+        assert_eq!(pipeline.generators[1].name, "dbg");
+        Ok(())
+    }
+
+    #[test]
+    fn from_yaml_with_inline_parser_config() -> Result<()> {
+        let pipeline = Pipeline::from_yaml(
+            r#"
+            input: ["*.rs"]
+            parser:
+              name: rust
+              config:
+                user_types:
+                  - parse: String
+                    name: string
+            generators:
+              - name: rust
+                output: [r]
+            "#,
+        )?;
+        let config = pipeline.parser_config()?.unwrap();
+        assert_eq!(config.user_types.len(), 1);
+        assert_eq!(config.user_types[0].parse, "String");
+        assert_eq!(config.user_types[0].name, "string");
+        Ok(())
+    }
+
+    #[test]
+    fn top_level_config() -> Result<()> {
+        let pipeline = Pipeline::from_toml(
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = [{ name = "rust", output = ["r"] }]
+
+            [config.generator.rust]
+            import_comments = false
+            "#,
+        )?;
+        let config = pipeline.parser_config()?.unwrap();
+        assert_eq!(
+            config.generator["rust"]["import_comments"],
+            serde_json::Value::Bool(false)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn config_in_both_places_errors() -> Result<()> {
+        let pipeline = Pipeline::from_toml(
+            r#"
+            input = ["*.rs"]
+            config = {}
+            parser = { name = "rust", config = {} }
+            generators = []
+            "#,
+        )?;
+        assert!(pipeline.parser_config().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_section_errors() -> Result<()> {
+        let root = tempdir()?;
+        let pipeline = Pipeline::from_toml(
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = [{ name = "rust", output = ["r"] }]
+
+            [config.generator.rust]
+            imports = false
+            "#,
+        )?
+        .root(root.path());
+        let err = pipeline.execute().unwrap_err();
+        assert!(
+            err.to_string().contains("'generator.rust.imports'"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_field_errors() {
+        assert!(Pipeline::from_toml("input = []\nfoo = 1\n").is_err());
+    }
+
+    #[test]
+    fn execute_from_file() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("api.rs"), "pub struct dto {}")?;
+        fs::write(
+            root.path().join("pipeline.toml"),
+            r#"
+            input = ["*.rs"]
+            output_root = "gen"
+            parser = { name = "rust" }
+            generators = [{ name = "rust", output = ["r"] }]
+            "#,
+        )?;
+        Pipeline::from_file(root.path().join("pipeline.toml"))?.execute()?;
+        let generated = fs::read_to_string(root.path().join("gen/r/api.rs"))?;
+        assert!(generated.contains("struct dto"), "{}", generated);
+        Ok(())
+    }
+
+    #[test]
+    fn from_config_directory() -> Result<()> {
+        let root = tempdir()?;
+        assert!(Pipeline::from_config(root.path()).is_err());
+        fs::write(
+            root.path().join("apyxl.yaml"),
+            r#"
+            input: ["*.rs"]
+            parser: { name: "rust" }
+            generators: [{ name: "rust", output: ["r"] }]
+            "#,
+        )?;
+        let pipeline = Pipeline::from_config(root.path())?;
+        assert_eq!(pipeline.root, root.path());
+        assert_eq!(pipeline.generators[0].name, "rust");
+        let pipeline = Pipeline::from_config(root.path().join("apyxl.yaml"))?;
+        assert_eq!(pipeline.generators[0].name, "rust");
+        Ok(())
+    }
+
+    #[test]
+    fn transforms() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(
+            root.path().join("api.rs"),
+            r#"
+            pub struct UserDto { id: u32, user_name: String }
+            #[internal]
+            pub struct Secret {}
+            pub mod b { pub struct Zed {} }
+            pub mod a { pub struct Aye {} }
+            pub mod excluded { pub struct Hidden {} }
+            "#,
+        )?;
+        fs::write(
+            root.path().join("pipeline.toml"),
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = [{ name = "rust", output = ["r"] }]
+
+            [transforms]
+            exclude = ["api.excluded"]
+            exclude_attributes = ["internal"]
+            rename_fields = { id = "identifier" }
+            strip_suffixes = ["Dto"]
+            case = { fields = "camel" }
+            order = "kind_then_name"
+            "#,
+        )?;
+        Pipeline::from_file(root.path().join("pipeline.toml"))?.execute()?;
+        let generated = fs::read_to_string(root.path().join("r/api.rs"))?;
+        assert!(generated.contains("struct User {"), "{}", generated);
+        assert!(generated.contains("identifier: u32"), "{}", generated);
+        assert!(generated.contains("userName: String"), "{}", generated);
+        assert!(!generated.contains("Secret"), "{}", generated);
+        assert!(!generated.contains("Hidden"), "{}", generated);
+        let (a, b) = (generated.find("mod a").unwrap(), generated.find("mod b"));
+        assert!(a < b.unwrap(), "{}", generated);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_transform_entity_id_errors() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(
+            root.path().join("pipeline.toml"),
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = []
+            transforms = { include = ["a.x:y"] }
+            "#,
+        )?;
+        let err = Pipeline::from_file(root.path().join("pipeline.toml"))?
+            .execute()
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("invalid entity id 'a.x:y'"),
+            "{}",
+            err
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "script")]
+    fn scripts() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("api.rs"), "pub struct dto {}")?;
+        fs::write(
+            root.path().join("rename.rhai"),
+            "fn type_name(name) { name.to_upper() }",
+        )?;
+        fs::write(
+            root.path().join("pipeline.toml"),
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            scripts = ["rename.rhai"]
+            generators = [{ name = "rust", output = ["r"] }]
+            "#,
+        )?;
+        Pipeline::from_file(root.path().join("pipeline.toml"))?.execute()?;
+        let generated = fs::read_to_string(root.path().join("r/api.rs"))?;
+        assert!(generated.contains("struct DTO"), "{}", generated);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn formatters() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(root.path().join("api.rs"), "pub struct dto {}")?;
+        fs::write(
+            root.path().join("pipeline.toml"),
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = [{ name = "rust", output = ["r"], formatters = ["tr a-z A-Z"] }]
+            "#,
+        )?;
+        Pipeline::from_file(root.path().join("pipeline.toml"))?.execute()?;
+        let generated = fs::read_to_string(root.path().join("r/api.rs"))?;
+        assert!(generated.contains("STRUCT DTO"), "{}", generated);
+        Ok(())
+    }
+
+    #[test]
+    fn baseline() -> Result<()> {
+        let root = tempdir()?;
+        let run = |api: &str, pipeline: &str| -> Result<()> {
+            fs::write(root.path().join("api.rs"), api)?;
+            fs::write(root.path().join("pipeline.toml"), pipeline)?;
+            Pipeline::from_file(root.path().join("pipeline.toml"))?.execute()
+        };
+        run(
+            "pub struct dto {}",
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = [{ name = "json", output = ["base"] }]
+            "#,
+        )?;
+
+        let gated = r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            baseline = { path = "base/api.json", allowlist = "allowlist.txt" }
+            generators = [{ name = "rust", output = ["r"] }]
+            "#;
+        fs::write(root.path().join("allowlist.txt"), "")?;
+        let err = run("pub struct other {}", gated).unwrap_err();
+        assert!(
+            err.to_string().contains("breaking change from baseline"),
+            "{}",
+            err
+        );
+        fs::write(root.path().join("allowlist.txt"), "api.d:dto\n")?;
+        run("pub struct other {}", gated)
+    }
+
+    #[test]
+    fn dead_types() -> Result<()> {
+        let root = tempdir()?;
+        fs::write(
+            root.path().join("api.rs"),
+            "struct used {} struct unused {} mod events { struct event {} } fn rpc(x: used) {}",
+        )?;
+        fs::write(
+            root.path().join("pipeline.toml"),
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            dead_types = { roots = ["api.events"], filter = true }
+            generators = [{ name = "rust", output = ["r"] }]
+            "#,
+        )?;
+        Pipeline::from_file(root.path().join("pipeline.toml"))?.execute()?;
+        let generated = fs::read_to_string(root.path().join("r/api.rs"))?;
+        assert!(generated.contains("struct used"), "{}", generated);
+        assert!(generated.contains("struct event"), "{}", generated);
+        assert!(!generated.contains("struct unused"), "{}", generated);
+        Ok(())
+    }
+
+    #[test]
+    fn wasm_generator() -> Result<()> {
+        let pipeline = Pipeline::from_toml(
+            r#"
+            input = ["*.rs"]
+            parser = { name = "rust" }
+            generators = [
+                { name = "plugin", wasm = "plugin.wasm", output = ["p"] },
+                { name = "limited", wasm = "plugin.wasm", wasm_fuel = 1000, output = ["l"] },
+            ]
+            "#,
+        )?;
+        assert_eq!(
+            pipeline.generators[0].wasm,
+            Some(PathBuf::from("plugin.wasm"))
+        );
+        assert_eq!(pipeline.generators[0].wasm_fuel, None);
+        assert_eq!(pipeline.generators[1].wasm_fuel, Some(1000));
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_extension_errors() -> Result<()> {
+        let root = tempdir()?;
+        let path = root.path().join("pipeline.txt");
+        fs::write(&path, "")?;
+        assert!(Pipeline::from_file(path).is_err());
+        Ok(())
+    }
+}
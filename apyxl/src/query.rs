@@ -0,0 +1,335 @@
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use crate::model::{Api, Attributes, EntityId, EntityType, Field, Model, Namespace, Type};
+
+/// Where an entity is defined within the parsed input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Location {
+    pub relative_file_path: PathBuf,
+    /// Byte range within the data of the file.
+    pub span: Range<usize>,
+}
+
+/// An entity along with where it is defined.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EntityLocation {
+    pub entity_id: EntityId,
+    pub location: Location,
+}
+
+/// Queries over a built [Model] keyed on source locations, for building editor tooling such as a
+/// language server on top of the parsed model.
+///
+/// Locations are only known for namespaces, dtos, rpcs, enums, fields and rpc params that were
+/// parsed from a [crate::model::Chunk] with a relative file path by a [crate::Parser] that records
+/// [Attributes::span], e.g. [crate::parser::Rust]. Enum values are located as their enum.
+///
+/// ```ignore
+/// let query = Query::new(&model);
+/// if let Some(entity) = query.entity_at(Path::new("api/user.rs"), cursor_offset) {
+///     let references = query.references(&entity.entity_id);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Query<'m> {
+    api: &'m Api<'m>,
+    /// Sorted by file path, then span start, with enclosing entities before nested ones.
+    entities: Vec<EntityLocation>,
+}
+
+impl<'m> Query<'m> {
+    pub fn new(model: &'m Model<'m>) -> Self {
+        let mut entities = vec![];
+        collect_namespace(model.api(), &EntityId::default(), None, &mut entities);
+        entities.sort_by(|a, b| {
+            let (a, b) = (&a.location, &b.location);
+            a.relative_file_path
+                .cmp(&b.relative_file_path)
+                .then(a.span.start.cmp(&b.span.start))
+                .then(b.span.end.cmp(&a.span.end))
+        });
+        Self {
+            api: model.api(),
+            entities,
+        }
+    }
+
+    /// All located entities defined in the file at `relative_file_path`, in source order.
+    pub fn entities_in_file<'q>(
+        &'q self,
+        relative_file_path: &'q Path,
+    ) -> impl Iterator<Item = &'q EntityLocation> + 'q {
+        self.entities
+            .iter()
+            .filter(move |entity| entity.location.relative_file_path == relative_file_path)
+    }
+
+    /// The innermost entity whose definition contains the byte `offset` in the file at
+    /// `relative_file_path`, e.g. the field rather than the dto that contains it.
+    pub fn entity_at(&self, relative_file_path: &Path, offset: usize) -> Option<&EntityLocation> {
+        self.entities
+            .iter()
+            .filter(|entity| {
+                entity.location.relative_file_path == relative_file_path
+                    && entity.location.span.contains(&offset)
+            })
+            .min_by_key(|entity| entity.location.span.len())
+    }
+
+    /// Where the entity with `entity_id` is defined, if it is known.
+    pub fn location(&self, entity_id: &EntityId) -> Option<&Location> {
+        self.entities
+            .iter()
+            .find(|entity| entity.entity_id == *entity_id)
+            .map(|entity| &entity.location)
+    }
+
+    /// All entities whose type refers to the dto or enum with `entity_id`, including within
+    /// arrays, maps and optionals. These are the ids of fields and rpc params, or of the rpc
+    /// itself for return types, so that they can be passed to [Query::location].
+    pub fn references(&self, entity_id: &EntityId) -> Vec<EntityId> {
+        let mut references = vec![];
+        collect_references(self.api, &EntityId::default(), entity_id, &mut references);
+        references
+    }
+}
+
+fn collect_namespace(
+    namespace: &Namespace,
+    namespace_id: &EntityId,
+    file: Option<&Path>,
+    entities: &mut Vec<EntityLocation>,
+) {
+    // unwraps ok here because we're iterating known children.
+    for dto in namespace.dtos() {
+        let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+        let file = push(&dto_id, &dto.attributes, file, entities);
+        collect_fields(&dto.fields, &dto_id, file, entities);
+    }
+    for rpc in namespace.rpcs() {
+        let rpc_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
+        let file = push(&rpc_id, &rpc.attributes, file, entities);
+        collect_fields(&rpc.params, &rpc_id, file, entities);
+    }
+    for en in namespace.enums() {
+        let en_id = namespace_id.child(EntityType::Enum, en.name).unwrap();
+        push(&en_id, &en.attributes, file, entities);
+    }
+    for child in namespace.namespaces() {
+        let child_id = namespace_id
+            .child(EntityType::Namespace, &child.name)
+            .unwrap();
+        let file = push(&child_id, &child.attributes, file, entities);
+        collect_namespace(child, &child_id, file, entities);
+    }
+}
+
+fn collect_fields(
+    fields: &[Field],
+    parent_id: &EntityId,
+    file: Option<&Path>,
+    entities: &mut Vec<EntityLocation>,
+) {
+    for field in fields {
+        let field_id = parent_id.child(EntityType::Field, field.name).unwrap();
+        push(&field_id, &field.attributes, file, entities);
+    }
+}
+
+/// Adds the entity if it is located, and returns the file of its definition, which nested
+/// entities inherit. The span of an entity merged from multiple chunks is from the first one.
+fn push<'a>(
+    entity_id: &EntityId,
+    attributes: &'a Attributes,
+    parent_file: Option<&'a Path>,
+    entities: &mut Vec<EntityLocation>,
+) -> Option<&'a Path> {
+    let file = attributes
+        .chunk
+        .as_ref()
+        .and_then(|chunk| chunk.relative_file_paths.first())
+        .map(PathBuf::as_path)
+        .or(parent_file);
+    if let (Some(file), Some(span)) = (file, &attributes.span) {
+        entities.push(EntityLocation {
+            entity_id: entity_id.clone(),
+            location: Location {
+                relative_file_path: file.to_path_buf(),
+                span: span.clone(),
+            },
+        });
+    }
+    file
+}
+
+fn collect_references(
+    namespace: &Namespace,
+    namespace_id: &EntityId,
+    target: &EntityId,
+    references: &mut Vec<EntityId>,
+) {
+    for dto in namespace.dtos() {
+        let dto_id = namespace_id.child(EntityType::Dto, dto.name).unwrap();
+        for field in &dto.fields {
+            if refers_to(&field.ty, target) {
+                references.push(dto_id.child(EntityType::Field, field.name).unwrap());
+            }
+        }
+    }
+    for rpc in namespace.rpcs() {
+        let rpc_id = namespace_id.child(EntityType::Rpc, rpc.name).unwrap();
+        for param in &rpc.params {
+            if refers_to(&param.ty, target) {
+                references.push(rpc_id.child(EntityType::Field, param.name).unwrap());
+            }
+        }
+        if rpc
+            .return_type
+            .as_ref()
+            .is_some_and(|ty| refers_to(ty, target))
+        {
+            references.push(rpc_id);
+        }
+    }
+    for child in namespace.namespaces() {
+        let child_id = namespace_id
+            .child(EntityType::Namespace, &child.name)
+            .unwrap();
+        collect_references(child, &child_id, target, references);
+    }
+}
+
+/// Whether `ty` is or contains a reference to `target`. Types are fully qualified once the model
+/// is built.
+fn refers_to(ty: &Type, target: &EntityId) -> bool {
+    match ty {
+        Type::Api(id, args) => id == target || args.iter().any(|arg| refers_to(arg, target)),
+        Type::Array(ty) | Type::Optional(ty) => refers_to(ty, target),
+        Type::Map { key, value } => refers_to(key, target) || refers_to(value, target),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use anyhow::Result;
+
+    use crate::model::{Builder, Chunk, EntityId};
+    use crate::parser::{Config, RustOptions};
+    use crate::query::Query;
+    use crate::{input, parser, Parser};
+
+    const USER: &str = r#"
+mod user {
+    struct Id {}
+
+    struct User {
+        id: Id,
+        friends: Vec<Id>,
+    }
+}
+"#;
+
+    const RPC: &str = r#"
+fn get_user(id: user::Id) -> Option<user::User> {}
+enum Kind { A, B }
+"#;
+
+    fn input() -> input::ChunkBuffer {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::with_relative_file_path("user.rs"), USER);
+        input.add_chunk(Chunk::with_relative_file_path("rpc.rs"), RPC);
+        input
+    }
+
+    fn id(value: &str) -> EntityId {
+        EntityId::try_from(value).unwrap()
+    }
+
+    fn run_test(test: impl FnOnce(&Query)) -> Result<()> {
+        let config = Config::default();
+        let mut input = input();
+        let mut builder = Builder::default();
+        parser::Rust::new(RustOptions {
+            file_namespaces: false,
+        })
+        .parse(&config, &mut input, &mut builder)?;
+        let model = builder.build().unwrap();
+        test(&Query::new(&model));
+        Ok(())
+    }
+
+    #[test]
+    fn entities_in_file() -> Result<()> {
+        run_test(|query| {
+            let ids = query
+                .entities_in_file(Path::new("user.rs"))
+                .map(|entity| entity.entity_id.to_string())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                ids,
+                vec![
+                    "user",
+                    "user.dto:Id",
+                    "user.dto:User",
+                    "user.dto:User.field:id",
+                    "user.dto:User.field:friends",
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn entity_at() -> Result<()> {
+        run_test(|query| {
+            let file = Path::new("user.rs");
+            let at = |needle: &str| {
+                let offset = USER.find(needle).unwrap();
+                query
+                    .entity_at(file, offset)
+                    .map(|entity| entity.entity_id.to_string())
+            };
+            assert_eq!(at("mod user"), Some("user".to_string()));
+            assert_eq!(at("User {"), Some("user.dto:User".to_string()));
+            assert_eq!(
+                at("Vec<Id>"),
+                Some("user.dto:User.field:friends".to_string())
+            );
+            assert_eq!(query.entity_at(file, 0), None);
+            assert_eq!(query.entity_at(Path::new("missing.rs"), 10), None);
+
+            let offset = RPC.find("B }").unwrap();
+            let entity = query.entity_at(Path::new("rpc.rs"), offset).unwrap();
+            assert_eq!(entity.entity_id, id("e:Kind"));
+        })
+    }
+
+    #[test]
+    fn location() -> Result<()> {
+        run_test(|query| {
+            let location = query.location(&id("r:get_user.p:id")).unwrap();
+            assert_eq!(location.relative_file_path, Path::new("rpc.rs"));
+            assert_eq!(&RPC[location.span.clone()], "id: user::Id");
+            assert_eq!(query.location(&id("d:Missing")), None);
+        })
+    }
+
+    #[test]
+    fn references() -> Result<()> {
+        run_test(|query| {
+            assert_eq!(
+                query.references(&id("user.d:Id")),
+                vec![
+                    id("r:get_user.p:id"),
+                    id("user.d:User.f:id"),
+                    id("user.d:User.f:friends"),
+                ]
+            );
+            assert_eq!(query.references(&id("user.d:User")), vec![id("r:get_user")]);
+            assert_eq!(query.references(&id("e:Kind")), vec![]);
+        })
+    }
+}
@@ -0,0 +1,441 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Formatter};
+
+use anyhow::{anyhow, Result};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::{self, Config, Sections};
+use crate::generator::Generator;
+use crate::parser::{DynParser, Parser};
+use crate::{generator, parser};
+
+/// Describes a [Parser] or [Generator] registered in a [Registry], so that tooling and docs can be
+/// generated from the registry itself.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize)]
+pub struct RegistryEntry {
+    pub name: String,
+    /// File extensions, without the leading `.`, that the implementation reads or writes. See
+    /// [Parser::extensions] and [Generator::extensions].
+    pub extensions: Vec<String>,
+    /// JSON schema of the implementation's section of the [Config], if it takes options.
+    pub config_schema: Option<serde_json::Value>,
+}
+
+/// Maps names to [Parser] and [Generator] implementations so that they can be selected at runtime,
+/// e.g. by a [crate::Pipeline] file. Downstream crates can register their own implementations
+/// alongside the built-in ones without forking apyxl.
+///
+/// Implementations registered with options (e.g. [Registry::generator_with_options]) are created
+/// with their section of the [Config], e.g. `generator.<name>`, deserialized into their options.
+///
+/// [Registry::default] contains the built-in parsers (`rust`, `delimited`) and generators (`rust`, `dbg`,
+/// `json`, `changelog`, `report`, `graph`, `typescript`, `openapi`, `html`, `kotlin`, `python`, `go`,
+/// `template`).
+/// Registering a name that already exists replaces it.
+pub struct Registry {
+    parsers: BTreeMap<String, Registered<dyn DynParser>>,
+    generators: BTreeMap<String, Registered<dyn Generator>>,
+}
+
+type Factory<T> = Box<dyn Fn(&Config) -> Result<Box<T>>>;
+
+struct Registered<T: ?Sized> {
+    factory: Factory<T>,
+    /// Only set if the implementation takes options.
+    options_schema: Option<fn() -> serde_json::Value>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::empty()
+            .parser_with_options("rust", parser::Rust::new)
+            .parser_with_options("delimited", parser::Delimited::new)
+            .generator_with_options("rust", generator::Rust::new)
+            .generator("dbg", generator::Dbg::default)
+            .generator("json", generator::Json::default)
+            .generator_with_options("go", generator::Go::new)
+            .generator_with_options("html", generator::HtmlDocs::new)
+            .generator_with_options("kotlin", generator::Kotlin::new)
+            .generator_with_options("python", generator::Python::new)
+            .generator_with_options("openapi", generator::OpenApi::new)
+            .generator_with_options("changelog", generator::Changelog::new)
+            .generator_with_options("report", generator::Report::new)
+            .generator_with_options("graph", generator::Graph::new)
+            .generator_with_options("typescript", generator::TypeScript::new)
+            .generator_with_options("template", generator::Template::new)
+    }
+}
+
+impl Registry {
+    /// A [Registry] without any of the built-in implementations.
+    pub fn empty() -> Self {
+        Self {
+            parsers: BTreeMap::new(),
+            generators: BTreeMap::new(),
+        }
+    }
+
+    /// Register a [Parser] created by `factory` under `name`.
+    pub fn parser<P, F>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        P: Parser + 'static,
+        F: Fn() -> P + 'static,
+    {
+        self.parsers.insert(
+            name.to_string(),
+            Registered {
+                factory: Box::new(move |_| Ok(Box::new(factory()))),
+                options_schema: None,
+            },
+        );
+        self
+    }
+
+    /// Register a [Parser] created by `factory` under `name`, with options of type `O` from the
+    /// `parser.<name>` section of the [Config].
+    pub fn parser_with_options<P, O, F>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        P: Parser + 'static,
+        O: DeserializeOwned + Default + JsonSchema,
+        F: Fn(O) -> P + 'static,
+    {
+        let name = name.to_string();
+        let section = name.clone();
+        self.parsers.insert(
+            name,
+            Registered {
+                factory: Box::new(move |config| {
+                    Ok(Box::new(factory(config.parser_options(&section)?)))
+                }),
+                options_schema: Some(config::json_schema::<O>),
+            },
+        );
+        self
+    }
+
+    /// Register a [Generator] created by `factory` under `name`.
+    pub fn generator<G, F>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        G: Generator + 'static,
+        F: Fn() -> G + 'static,
+    {
+        self.generators.insert(
+            name.to_string(),
+            Registered {
+                factory: Box::new(move |_| Ok(Box::new(factory()))),
+                options_schema: None,
+            },
+        );
+        self
+    }
+
+    /// Register a [Generator] created by `factory` under `name`, with options of type `O` from the
+    /// `generator.<name>` section of the [Config].
+    pub fn generator_with_options<G, O, F>(mut self, name: impl ToString, factory: F) -> Self
+    where
+        G: Generator + 'static,
+        O: DeserializeOwned + Default + JsonSchema,
+        F: Fn(O) -> G + 'static,
+    {
+        let name = name.to_string();
+        let section = name.clone();
+        self.generators.insert(
+            name,
+            Registered {
+                factory: Box::new(move |config| {
+                    Ok(Box::new(factory(config.generator_options(&section)?)))
+                }),
+                options_schema: Some(config::json_schema::<O>),
+            },
+        );
+        self
+    }
+
+    /// Create a new instance of the [Parser] registered under `name`, with default options.
+    pub fn create_parser(&self, name: &str) -> Result<Box<dyn DynParser>> {
+        self.create_parser_with_config(name, &Config::default())
+    }
+
+    /// Create a new instance of the [Parser] registered under `name`, with options from its
+    /// section of the `config`.
+    pub fn create_parser_with_config(
+        &self,
+        name: &str,
+        config: &Config,
+    ) -> Result<Box<dyn DynParser>> {
+        create(&self.parsers, "parser", name, config)
+    }
+
+    /// Create a new instance of the [Generator] registered under `name`, with default options.
+    pub fn create_generator(&self, name: &str) -> Result<Box<dyn Generator>> {
+        self.create_generator_with_config(name, &Config::default())
+    }
+
+    /// Create a new instance of the [Generator] registered under `name`, with options from its
+    /// section of the `config`.
+    pub fn create_generator_with_config(
+        &self,
+        name: &str,
+        config: &Config,
+    ) -> Result<Box<dyn Generator>> {
+        create(&self.generators, "generator", name, config)
+    }
+
+    /// Names of all registered parsers, sorted.
+    pub fn parser_names(&self) -> impl Iterator<Item = &str> {
+        self.parsers.keys().map(String::as_str)
+    }
+
+    /// Names of all registered generators, sorted.
+    pub fn generator_names(&self) -> impl Iterator<Item = &str> {
+        self.generators.keys().map(String::as_str)
+    }
+
+    /// A [RegistryEntry] for each registered parser, sorted by name.
+    ///
+    /// Each parser is created to query it, so this should not be called in a hot loop.
+    pub fn parser_entries(&self) -> impl Iterator<Item = RegistryEntry> + '_ {
+        entries(&self.parsers, |parser| parser.extensions_dyn())
+    }
+
+    /// A [RegistryEntry] for each registered generator, sorted by name.
+    ///
+    /// Each generator is created to query it, so this should not be called in a hot loop.
+    pub fn generator_entries(&self) -> impl Iterator<Item = RegistryEntry> + '_ {
+        entries(&self.generators, |generator| generator.extensions())
+    }
+
+    /// Checks that every section of the `config` belongs to a registered implementation that
+    /// takes options, and that each section is valid for it. Errors point at the offending key.
+    pub fn validate_config(&self, config: &Config) -> Result<()> {
+        validate_sections(&self.parsers, "parser", &config.parser, config)?;
+        validate_sections(&self.generators, "generator", &config.generator, config)
+    }
+}
+
+fn create<T: ?Sized>(
+    registered: &BTreeMap<String, Registered<T>>,
+    kind: &str,
+    name: &str,
+    config: &Config,
+) -> Result<Box<T>> {
+    let registered = registered.get(name).ok_or_else(|| {
+        anyhow!(
+            "unknown {} '{}', expected one of: {}",
+            kind,
+            name,
+            registered
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })?;
+    (registered.factory)(config)
+}
+
+fn entries<'a, T: ?Sized>(
+    registered: &'a BTreeMap<String, Registered<T>>,
+    extensions: impl Fn(&T) -> &'static [&'static str] + 'a,
+) -> impl Iterator<Item = RegistryEntry> + 'a {
+    registered.iter().map(move |(name, registered)| {
+        let instance =
+            (registered.factory)(&Config::default()).expect("default options are always valid");
+        RegistryEntry {
+            name: name.clone(),
+            extensions: extensions(&instance)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            config_schema: registered.options_schema.map(|schema| schema()),
+        }
+    })
+}
+
+fn validate_sections<T: ?Sized>(
+    registered: &BTreeMap<String, Registered<T>>,
+    kind: &str,
+    sections: &Sections,
+    config: &Config,
+) -> Result<()> {
+    for name in sections.keys() {
+        match registered.get(name) {
+            None => {
+                return Err(anyhow!(
+                    "invalid config at '{}.{}': unknown {}, expected one of: {}",
+                    kind,
+                    name,
+                    kind,
+                    registered
+                        .keys()
+                        .map(String::as_str)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+            Some(registered) if registered.options_schema.is_none() => {
+                return Err(anyhow!(
+                    "invalid config at '{}.{}': {} '{}' does not take any options",
+                    kind,
+                    name,
+                    kind,
+                    name
+                ))
+            }
+            Some(registered) => {
+                (registered.factory)(config)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Debug for Registry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Registry")
+            .field("parsers", &self.parser_names().collect::<Vec<_>>())
+            .field("generators", &self.generator_names().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use anyhow::Result;
+
+    use crate::config::Config;
+    use crate::output::Buffer;
+    use crate::{generator, input, Executor, Registry};
+
+    #[test]
+    fn builtins() {
+        let registry = Registry::default();
+        assert_eq!(
+            registry.parser_names().collect::<Vec<_>>(),
+            vec!["delimited", "rust"]
+        );
+        assert_eq!(
+            registry.generator_names().collect::<Vec<_>>(),
+            vec![
+                "changelog",
+                "dbg",
+                "go",
+                "graph",
+                "html",
+                "json",
+                "kotlin",
+                "openapi",
+                "python",
+                "report",
+                "rust",
+                "template",
+                "typescript"
+            ]
+        );
+    }
+
+    #[test]
+    fn entries() {
+        let registry = Registry::default();
+        let parsers = registry.parser_entries().collect::<Vec<_>>();
+        assert_eq!(parsers.len(), 2);
+        assert_eq!(parsers[0].name, "delimited");
+        assert!(parsers[0].extensions.is_empty());
+        let schema = parsers[0].config_schema.as_ref().unwrap();
+        assert!(schema["properties"]["separator"].is_object());
+        assert_eq!(parsers[1].name, "rust");
+        assert_eq!(parsers[1].extensions, vec!["rs"]);
+        let schema = parsers[1].config_schema.as_ref().unwrap();
+        assert!(schema["properties"]["file_namespaces"].is_object());
+
+        let generators = registry.generator_entries().collect::<Vec<_>>();
+        assert_eq!(generators.len(), 13);
+        assert_eq!(generators[0].name, "changelog");
+        assert_eq!(generators[0].extensions, vec!["md"]);
+        assert_eq!(generators[1].name, "dbg");
+        assert!(generators[1].extensions.is_empty());
+        assert_eq!(generators[2].name, "go");
+        assert_eq!(generators[2].extensions, vec!["go"]);
+        assert_eq!(generators[3].name, "graph");
+        assert_eq!(generators[3].extensions, vec!["dot", "mmd"]);
+        assert_eq!(generators[4].name, "html");
+        assert_eq!(generators[4].extensions, vec!["html", "css"]);
+        assert_eq!(generators[6].name, "kotlin");
+        assert_eq!(generators[6].extensions, vec!["kt"]);
+        assert_eq!(generators[8].name, "python");
+        assert_eq!(generators[8].extensions, vec!["py"]);
+        assert_eq!(generators[10].name, "rust");
+        assert_eq!(generators[10].extensions, vec!["rs"]);
+        assert!(generators[0].config_schema.is_some());
+        assert!(generators[1].config_schema.is_none());
+        assert!(generators[10].config_schema.is_some());
+        assert_eq!(generators[11].name, "template");
+        assert!(generators[11].extensions.is_empty());
+        assert!(generators[11].config_schema.is_some());
+        assert_eq!(generators[12].name, "typescript");
+        assert_eq!(generators[12].extensions, vec!["ts"]);
+    }
+
+    #[test]
+    fn create_with_options() -> Result<()> {
+        let registry = Registry::default();
+        let config =
+            Config::from_json(r#"{ "generator": { "rust": { "import_comments": false } } }"#)?;
+        let generator = registry.create_generator_with_config("rust", &config)?;
+        assert!(format!("{:?}", generator).contains("import_comments: false"));
+        Ok(())
+    }
+
+    #[test]
+    fn validate_config() -> Result<()> {
+        let registry = Registry::default();
+        let validate = |json: &str| registry.validate_config(&Config::from_json(json).unwrap());
+        validate(r#"{ "parser": { "rust": {} }, "generator": { "rust": {} } }"#)?;
+
+        let err = validate(r#"{ "generator": { "swift": {} } }"#).unwrap_err();
+        assert!(err.to_string().contains("'generator.swift'"), "{}", err);
+        let err = validate(r#"{ "generator": { "dbg": {} } }"#).unwrap_err();
+        assert!(err.to_string().contains("'generator.dbg'"), "{}", err);
+        let err = validate(r#"{ "parser": { "rust": { "unknown": 1 } } }"#).unwrap_err();
+        assert!(err.to_string().contains("'parser.rust.unknown'"), "{}", err);
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_name_errors() {
+        let registry = Registry::empty();
+        assert!(registry.create_parser("rust").is_err());
+        assert!(registry.create_generator("rust").is_err());
+    }
+
+    #[test]
+    fn register_custom_generator() -> Result<()> {
+        let registry = Registry::empty().generator("custom", generator::Dbg::default);
+        assert_eq!(
+            registry.generator_names().collect::<Vec<_>>(),
+            vec!["custom"]
+        );
+        registry.create_generator("custom")?;
+        Ok(())
+    }
+
+    #[test]
+    fn execute_with_registered_implementations() -> Result<()> {
+        let registry = Registry::default();
+        let output = Rc::new(RefCell::new(Buffer::default()));
+        Executor::new(
+            input::Buffer::new("struct dto {}"),
+            registry.create_parser("rust")?,
+        )
+        .generator(registry.create_generator("rust")?)
+        .output_ptr(output.clone())
+        .execute()?;
+        assert!(output.borrow().to_string().contains("struct dto"));
+        Ok(())
+    }
+}
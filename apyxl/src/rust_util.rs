@@ -17,7 +17,7 @@ fn namespace_path(file_path: &Path) -> PathBuf {
         file_path
             .parent()
             .map(Path::to_path_buf)
-            .unwrap_or(PathBuf::default())
+            .unwrap_or_default()
     } else {
         file_path.with_extension("")
     }
@@ -0,0 +1,284 @@
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Serialize, Serializer};
+
+use crate::model::{Api, Attributes, Model, Namespace};
+
+/// Per-phase timings and entity counts of an [crate::Executor::execute], useful for finding slow
+/// phases and sizing pipelines. Collected via [crate::Executor::stats_ptr].
+///
+/// Phases that did not run (e.g. because an earlier phase failed) are left at zero.
+///
+/// Durations are serialized as fractional seconds.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize)]
+pub struct Stats {
+    /// Number of [crate::model::Chunk]s in the [crate::Input].
+    pub chunks: usize,
+    /// Total size of the data of all [crate::model::Chunk]s.
+    pub input_bytes: usize,
+    /// Entities in the validated API.
+    pub entities: EntityCounts,
+    #[serde(serialize_with = "serialize_secs")]
+    pub parse: Duration,
+    #[serde(serialize_with = "serialize_secs")]
+    pub build: Duration,
+    /// Total across all [crate::Generator]s and [crate::Output]s, including view transforms.
+    #[serde(serialize_with = "serialize_secs")]
+    pub generate: Duration,
+    #[serde(serialize_with = "serialize_secs")]
+    pub finish: Duration,
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize)]
+pub struct EntityCounts {
+    pub namespaces: usize,
+    pub dtos: usize,
+    pub rpcs: usize,
+    pub enums: usize,
+    /// [crate::model::Dto] fields and [crate::model::Rpc] params.
+    pub fields: usize,
+    pub enum_values: usize,
+}
+
+/// The size of the API of a [Model], e.g. to track the growth of the API surface over time. See
+/// [Model::stats].
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize)]
+pub struct ApiStats {
+    /// Entities in the whole API.
+    pub entities: EntityCounts,
+    /// Deepest nesting of namespaces below the root, e.g. 2 for `a.b.Dto`.
+    pub max_depth: usize,
+    /// Entities defined in each chunk with a `relative_file_path`, in the order they were parsed.
+    pub chunks: Vec<ChunkStats>,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Serialize)]
+pub struct ChunkStats {
+    pub path: PathBuf,
+    /// Entities defined in the chunk. Namespaces can span several chunks, so are only counted if
+    /// the parser attributed them to the chunk, see [crate::model::chunk::Attribute].
+    pub entities: EntityCounts,
+}
+
+impl Stats {
+    /// Sum of all phase timings.
+    pub fn total(&self) -> Duration {
+        self.parse + self.build + self.generate + self.finish
+    }
+}
+
+impl ApiStats {
+    pub fn new(model: &Model) -> Self {
+        let api = model.api();
+        let chunks = model
+            .metadata()
+            .chunks
+            .iter()
+            .filter_map(|metadata| metadata.chunk.relative_file_path.as_ref())
+            .map(|path| ChunkStats {
+                path: path.clone(),
+                entities: EntityCounts::from_chunk(api, path),
+            })
+            .collect();
+        Self {
+            entities: EntityCounts::from_api(api),
+            max_depth: max_depth(api),
+            chunks,
+        }
+    }
+}
+
+fn max_depth(namespace: &Namespace) -> usize {
+    namespace
+        .namespaces()
+        .map(|child| 1 + max_depth(child))
+        .max()
+        .unwrap_or(0)
+}
+
+impl EntityCounts {
+    /// Counts all entities in the `api`, recursively. The root namespace is not counted.
+    pub fn from_api(api: &Api) -> Self {
+        let mut counts = Self::default();
+        counts.add_namespace(api, &|_| true);
+        counts
+    }
+
+    /// Counts the entities in the `api` that were defined in the chunk at `relative_file_path`.
+    pub fn from_chunk(api: &Api, relative_file_path: &Path) -> Self {
+        let mut counts = Self::default();
+        counts.add_namespace(api, &|attributes| {
+            attributes.chunk.as_ref().is_some_and(|chunk| {
+                chunk
+                    .relative_file_paths
+                    .iter()
+                    .any(|path| path == relative_file_path)
+            })
+        });
+        counts
+    }
+
+    /// Adds the entities within `namespace`, but not the namespace itself, that are `included`.
+    /// The rpcs of a dto are included along with it.
+    fn add_namespace(&mut self, namespace: &Namespace, included: &dyn Fn(&Attributes) -> bool) {
+        for dto in namespace.dtos().filter(|dto| included(&dto.attributes)) {
+            self.dtos += 1;
+            self.fields += dto.fields.len();
+            self.rpcs += dto.rpcs.len();
+            self.fields += dto.rpcs.iter().map(|rpc| rpc.params.len()).sum::<usize>();
+        }
+        for rpc in namespace.rpcs().filter(|rpc| included(&rpc.attributes)) {
+            self.rpcs += 1;
+            self.fields += rpc.params.len();
+        }
+        for en in namespace.enums().filter(|en| included(&en.attributes)) {
+            self.enums += 1;
+            self.enum_values += en.values.len();
+        }
+        for child in namespace.namespaces() {
+            if included(&child.attributes) {
+                self.namespaces += 1;
+            }
+            self.add_namespace(child, included);
+        }
+    }
+}
+
+impl Display for EntityCounts {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} namespaces, {} dtos, {} rpcs, {} enums, {} fields, {} enum values",
+            self.namespaces, self.dtos, self.rpcs, self.enums, self.fields, self.enum_values
+        )
+    }
+}
+
+fn serialize_secs<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "input:    {} chunks, {} bytes",
+            self.chunks, self.input_bytes
+        )?;
+        writeln!(f, "entities: {}", self.entities)?;
+        writeln!(f, "parse:    {:?}", self.parse)?;
+        writeln!(f, "build:    {:?}", self.build)?;
+        writeln!(f, "generate: {:?}", self.generate)?;
+        writeln!(f, "finish:   {:?}", self.finish)?;
+        write!(f, "total:    {:?}", self.total())
+    }
+}
+
+impl Display for ApiStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "entities:  {}", self.entities)?;
+        write!(f, "max depth: {}", self.max_depth)?;
+        for chunk in &self.chunks {
+            write!(f, "\n{}: {}", chunk.path.display(), chunk.entities)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    use crate::chunks;
+    use crate::stats::{ApiStats, ChunkStats, EntityCounts};
+    use crate::testing::TestExecutor;
+    use crate::Stats;
+
+    #[test]
+    fn counts_entities() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct dto { a: u32, b: u32 }
+            impl dto {
+                fn method(x: u32, y: u32) {}
+            }
+            mod ns {
+                mod inner {
+                    enum en { a, b, c }
+                }
+                fn rpc(param: u32) {}
+            }
+            "#,
+        );
+        let model = exe.build();
+        assert_eq!(
+            EntityCounts::from_api(model.api()),
+            EntityCounts {
+                namespaces: 2,
+                dtos: 1,
+                rpcs: 2,
+                enums: 1,
+                fields: 5,
+                enum_values: 3,
+            }
+        );
+    }
+
+    #[test]
+    fn api_stats() {
+        let mut exe = TestExecutor::with_chunks(chunks! {
+            "a.rs" => "struct dto { a: u32 }",
+            "a/b/c.rs" => "enum en { a, b } fn rpc(x: u32, y: u32) {}",
+        });
+        let model = exe.build();
+        let stats = model.stats();
+        assert_eq!(
+            stats,
+            ApiStats {
+                entities: EntityCounts {
+                    namespaces: 3,
+                    dtos: 1,
+                    rpcs: 1,
+                    enums: 1,
+                    fields: 3,
+                    enum_values: 2,
+                },
+                max_depth: 3,
+                chunks: vec![
+                    ChunkStats {
+                        path: PathBuf::from("a.rs"),
+                        entities: EntityCounts {
+                            dtos: 1,
+                            fields: 1,
+                            ..Default::default()
+                        },
+                    },
+                    ChunkStats {
+                        path: PathBuf::from("a/b/c.rs"),
+                        entities: EntityCounts {
+                            rpcs: 1,
+                            enums: 1,
+                            fields: 2,
+                            enum_values: 2,
+                            ..Default::default()
+                        },
+                    },
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn serializes_durations_as_secs() {
+        let stats = Stats {
+            parse: Duration::from_millis(1500),
+            ..Default::default()
+        };
+        let json = serde_json::to_value(stats).unwrap();
+        assert_eq!(json["parse"], 1.5);
+        assert_eq!(json["entities"]["dtos"], 0);
+    }
+}
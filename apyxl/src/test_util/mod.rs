@@ -1,8 +1,6 @@
 use crate::model;
 use std::borrow::Cow;
 
-pub mod executor;
-
 pub const NAMES: &[&str] = &["name0", "name1", "name2", "name3", "name4", "name5"];
 
 pub fn test_namespace(i: usize) -> model::Namespace<'static> {
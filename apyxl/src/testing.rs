@@ -0,0 +1,145 @@
+//! Utilities for testing parsers, generators and transforms against small in-memory APIs. See
+//! [crate::golden::Golden] for tests against fixture directories instead.
+//!
+//! Requires the `testing` feature, e.g. as a dev-dependency.
+
+use itertools::Itertools;
+
+use crate::model::{Builder, Chunk, Metadata};
+use crate::{input, model, output, parser, Generator, Parser};
+
+/// Parses in-memory rust source into an [model::Api] or [model::Model], e.g. to test a
+/// [Generator] or view transform. Panics on any parse or validation error, since that's a bug in
+/// the test itself.
+///
+/// ```
+/// use apyxl::testing::TestExecutor;
+/// use apyxl::{chunks, generator};
+///
+/// let mut exe = TestExecutor::with_chunks(chunks! {
+///     "a.rs" => "struct Dto { b: b::Other }",
+///     "b.rs" => "struct Other {}",
+/// });
+/// let output = exe.generate(&mut generator::Json::default());
+/// assert!(output.get("api.json").unwrap().contains("Other"));
+/// ```
+#[derive(Default)]
+pub struct TestExecutor {
+    input: input::ChunkBuffer,
+    parser: parser::Rust,
+    config: parser::Config,
+}
+
+impl TestExecutor {
+    /// A single chunk without a file path, so everything is placed in the root namespace.
+    pub fn new<S: ToString>(data: S) -> Self {
+        let mut input = input::ChunkBuffer::new();
+        input.add_chunk(Chunk::default(), data);
+        Self::with_chunks(input)
+    }
+
+    /// Each chunk is placed in the namespace matching its file path, like rust modules. See
+    /// [crate::chunks] for creating the `input`.
+    pub fn with_chunks(input: input::ChunkBuffer) -> Self {
+        Self {
+            input,
+            ..Default::default()
+        }
+    }
+
+    /// Parses with `config`, e.g. for its `user_types`.
+    pub fn config(mut self, config: parser::Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// The [model::Api] as parsed, without validation.
+    pub fn api(&mut self) -> model::Api<'_> {
+        let mut builder = Builder::default();
+        self.parser
+            .parse(&self.config, &mut self.input, &mut builder)
+            .expect("failed to parse input");
+        builder.into_api()
+    }
+
+    /// A [model::Model] of the [model::Api] as parsed, without validation.
+    pub fn model(&mut self) -> model::Model<'_> {
+        // Skip deps which rely on valid api.
+        model::Model::without_deps(self.api(), Metadata::default())
+    }
+
+    /// The validated [model::Model].
+    pub fn build(&mut self) -> model::Model<'_> {
+        let mut builder = Builder::default();
+        builder.metadata_mut().user_types = self.config.user_types.clone();
+        self.parser
+            .parse(&self.config, &mut self.input, &mut builder)
+            .expect("failed to parse input");
+        builder.build().unwrap_or_else(|errs| {
+            panic!(
+                "validation errors building api:\n{}",
+                errs.iter().map(ToString::to_string).join("\n")
+            )
+        })
+    }
+
+    /// Runs `generator` on the validated [model::Model], returning everything it wrote.
+    pub fn generate<G: Generator>(&mut self, generator: &mut G) -> output::ChunkBuffer {
+        let model = self.build();
+        let mut output = output::ChunkBuffer::new();
+        generator
+            .generate(model.view(), &mut output)
+            .expect("failed to generate");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::EntityId;
+    use crate::parser::{Config, UserType};
+    use crate::testing::TestExecutor;
+    use crate::{chunks, generator};
+
+    #[test]
+    fn chunks_are_placed_in_file_namespaces() {
+        let mut exe = TestExecutor::with_chunks(chunks! {
+            "a/b.rs" => "struct Dto {}",
+            "c.rs" => "struct Other {}",
+        });
+        let model = exe.build();
+        assert!(model
+            .api()
+            .find_dto(&EntityId::new_unqualified("a.b.Dto"))
+            .is_some());
+        assert!(model
+            .api()
+            .find_dto(&EntityId::new_unqualified("c.Other"))
+            .is_some());
+    }
+
+    #[test]
+    fn generate_with_config() {
+        let config = Config {
+            user_types: vec![UserType {
+                parse: "Uuid".to_string(),
+                name: "uuid".to_string(),
+                targets: [("rust".to_string(), "uuid::Uuid".to_string())].into(),
+                glob: false,
+            }],
+            ..Default::default()
+        };
+        let mut exe = TestExecutor::new("struct Dto { id: Uuid }").config(config);
+        let output = exe.generate(&mut generator::Rust::default());
+        assert_eq!(
+            output.unchunked(),
+            "struct Dto {\n    id: uuid::Uuid,\n}\n\n"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "validation errors building api:\nDuplicate")]
+    fn build_panics_with_validation_errors() {
+        TestExecutor::new("struct Dto {} struct Dto {}").build();
+    }
+}
@@ -1,4 +1,5 @@
 use crate::model;
+use crate::model::attribute::User;
 use crate::model::{chunk, Comment};
 use dyn_clone::DynClone;
 use std::fmt::Debug;
@@ -29,21 +30,49 @@ impl<'v, 'a> Attributes<'v, 'a> {
         comments
     }
 
-    pub fn user(&self) -> &Vec<model::attribute::User<'a>> {
-        &self.target.user
+    /// See [model::Attributes::trailing_comments]. [AttributeTransform]s are applied to these too.
+    pub fn trailing_comments(&self) -> Vec<Comment<'a>> {
+        let mut comments = self.target.trailing_comments.clone();
+        for x in self.xforms {
+            x.comments(&mut comments)
+        }
+        comments
+    }
+
+    /// User attributes in source order, with [AttributeTransform]s applied.
+    pub fn user(&self) -> Vec<User<'_>> {
+        let mut user = self.target.user.clone();
+        for x in self.xforms {
+            x.user(&mut user)
+        }
+        user
+    }
+
+    /// The first user attribute named `name`, after [AttributeTransform]s are applied.
+    pub fn find_user(&self, name: &str) -> Option<User<'_>> {
+        self.user().into_iter().find(|attr| attr.name == name)
+    }
+
+    pub fn has_user(&self, name: &str) -> bool {
+        self.find_user(name).is_some()
     }
 }
 
 pub trait AttributeTransform: Debug + DynClone {
-    fn comments(&self, comment: &mut Vec<Comment>);
+    fn comments(&self, _: &mut Vec<Comment>) {}
+
+    /// Filter, rename, add or change the data of user attributes. Names and data can be replaced
+    /// with strings owned by the transform.
+    fn user<'x>(&'x self, _: &mut Vec<User<'x>>) {}
 }
 
 dyn_clone::clone_trait_object!(AttributeTransform);
 
 #[cfg(test)]
 mod tests {
+    use crate::model::attribute::{User, UserData};
     use crate::model::{Comment, EntityId};
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::{AttributeTransform, Transformer};
     use std::borrow::Cow;
 
@@ -71,6 +100,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn user_transform() {
+        let mut exe = TestExecutor::new(
+            r#"
+                    #[api, internal, serde(rename = "dto2")]
+                    struct dto {}
+                "#,
+        );
+        let model = exe.build();
+        let view = model.view().with_attribute_transform(UserTransform {
+            renamed: "export".to_string(),
+        });
+        let root = view.api();
+        let dto = root
+            .find_dto(&EntityId::try_from("d:dto").unwrap())
+            .unwrap();
+        let attr = dto.attributes();
+        assert_eq!(
+            attr.user(),
+            vec![
+                User::new_flag("export"),
+                User::new("serde", vec![UserData::new(Some("name"), "dto2")]),
+                User::new_flag("generated"),
+            ]
+        );
+        assert!(attr.has_user("export"));
+        assert!(!attr.has_user("internal"));
+        assert_eq!(attr.find_user("serde").unwrap().data.len(), 1);
+    }
+
+    /// Removes `internal`, renames `api`, renames the `rename` arg of `serde` and adds
+    /// `generated`.
+    #[derive(Debug, Clone)]
+    struct UserTransform {
+        renamed: String,
+    }
+    impl AttributeTransform for UserTransform {
+        fn user<'x>(&'x self, user: &mut Vec<User<'x>>) {
+            user.retain(|attr| attr.name != "internal");
+            for attr in user.iter_mut() {
+                if attr.name == "api" {
+                    attr.name = &self.renamed;
+                }
+                for data in &mut attr.data {
+                    if data.key.as_deref() == Some("rename") {
+                        data.key = Some("name".into());
+                        data.quoted = false;
+                    }
+                }
+            }
+            user.push(User::new_flag("generated"));
+        }
+    }
+
     #[derive(Debug, Clone)]
     struct CommentWordFilterTransform {}
     impl AttributeTransform for CommentWordFilterTransform {
@@ -1,7 +1,7 @@
 use crate::model;
 use crate::model::entity::ToEntity;
 use crate::model::EntityType;
-use crate::view::{Attributes, Field, Transforms};
+use crate::view::{Attributes, Field, Rpc, Transforms};
 use dyn_clone::DynClone;
 use std::borrow::Cow;
 use std::fmt::Debug;
@@ -22,6 +22,12 @@ pub trait DtoTransform: Debug + DynClone {
     fn filter_field(&self, _: &model::Field) -> bool {
         true
     }
+
+    /// `true`: included.
+    /// `false`: excluded.
+    fn filter_rpc(&self, _: &model::Rpc) -> bool {
+        true
+    }
 }
 
 dyn_clone::clone_trait_object!(DtoTransform);
@@ -31,7 +37,7 @@ impl<'v, 'a> Dto<'v, 'a> {
         Self { target, xforms }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn name(&self) -> Cow<'_, str> {
         let mut name = Cow::Borrowed(self.target.name);
         for x in &self.xforms.dto {
             x.name(&mut name)
@@ -43,6 +49,11 @@ impl<'v, 'a> Dto<'v, 'a> {
         self.target.entity_type()
     }
 
+    /// Names of generic type parameters, see [model::Dto::type_params].
+    pub fn type_params(&self) -> &[&'a str] {
+        &self.target.type_params
+    }
+
     pub fn fields(&'a self) -> impl Iterator<Item = Field<'v, 'a>> {
         self.target
             .fields
@@ -53,24 +64,42 @@ impl<'v, 'a> Dto<'v, 'a> {
                     field,
                     &self.xforms.dto_field,
                     &self.xforms.entity_id,
+                    &self.xforms.ty,
                     &self.xforms.attr,
+                    &self.xforms.user_ty_targets,
                 )
             })
     }
 
-    pub fn attributes(&self) -> Attributes {
+    /// Methods of the dto.
+    pub fn rpcs(&self) -> impl Iterator<Item = Rpc<'v, 'a>> + '_ {
+        let rpcs = self
+            .target
+            .rpcs
+            .iter()
+            .filter(|rpc| self.filter_rpc(rpc))
+            .map(|rpc| Rpc::new(rpc, self.xforms));
+        self.xforms.order.sorted(rpcs)
+    }
+
+    pub fn attributes(&self) -> Attributes<'_, '_> {
         Attributes::new(&self.target.attributes, &self.xforms.attr)
     }
 
     fn filter_field(&self, field: &model::Field) -> bool {
         self.xforms.dto.iter().all(|x| x.filter_field(field))
+            && self.xforms.dto_field.iter().all(|x| x.filter(field))
+    }
+
+    fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
+        self.xforms.dto.iter().all(|x| x.filter_rpc(rpc))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::model::EntityId;
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::tests::{TestFilter, TestRenamer};
     use crate::view::Transformer;
     use itertools::Itertools;
@@ -126,4 +155,27 @@ mod tests {
 
         assert_eq!(fields, vec!["visible0", "visible1"]);
     }
+
+    #[test]
+    fn rpcs() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct dto {}
+            impl dto {
+                fn visible0(&self) {}
+                fn hidden(&self) {}
+                fn visible1(&self) {}
+            }
+            "#,
+        );
+        let model = exe.model();
+        let view = model.view().with_dto_transform(TestFilter {});
+        let root = view.api();
+        let dto = root
+            .find_dto(&EntityId::try_from("d:dto").unwrap())
+            .unwrap();
+        let rpcs = dto.rpcs().map(|rpc| rpc.name().to_string()).collect_vec();
+
+        assert_eq!(rpcs, vec!["visible0", "visible1"]);
+    }
 }
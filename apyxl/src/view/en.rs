@@ -47,7 +47,7 @@ impl<'v, 'a> Enum<'v, 'a> {
         Self { target, xforms }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn name(&self) -> Cow<'_, str> {
         let mut name = Cow::Borrowed(self.target.name);
         for x in &self.xforms.en {
             x.name(&mut name)
@@ -67,7 +67,7 @@ impl<'v, 'a> Enum<'v, 'a> {
             .map(move |value| EnumValue::new(value, &self.xforms.en_value, &self.xforms.attr))
     }
 
-    pub fn attributes(&self) -> Attributes {
+    pub fn attributes(&self) -> Attributes<'_, '_> {
         Attributes::new(&self.target.attributes, &self.xforms.attr)
     }
 
@@ -89,7 +89,7 @@ impl<'v, 'a> EnumValue<'v, 'a> {
         }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn name(&self) -> Cow<'_, str> {
         let mut name = Cow::Borrowed(self.target.name);
         for x in self.xforms {
             x.name(&mut name)
@@ -105,8 +105,8 @@ impl<'v, 'a> EnumValue<'v, 'a> {
         number
     }
 
-    pub fn attributes(&self) -> Attributes {
-        Attributes::new(&self.target.attributes, &self.attr_xforms)
+    pub fn attributes(&self) -> Attributes<'_, '_> {
+        Attributes::new(&self.target.attributes, self.attr_xforms)
     }
 }
 
@@ -115,7 +115,7 @@ mod tests {
     use itertools::Itertools;
 
     use crate::model::EntityId;
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::tests::{TestFilter, TestRenamer};
     use crate::view::Transformer;
 
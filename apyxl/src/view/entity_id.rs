@@ -31,7 +31,7 @@ impl<'v> EntityId<'v> {
     ///     `namespace1.namespace2.DtoName`
     ///     `namespace1.namespace2.DtoName.field0`
     ///     `namespace1.RpcName.param0`
-    pub fn path(&self) -> Vec<Cow<str>> {
+    pub fn path(&self) -> Vec<Cow<'_, str>> {
         let mut value = self
             .target
             .component_names()
@@ -49,7 +49,7 @@ mod tests {
     use crate::model::EntityId;
     use itertools::Itertools;
 
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::tests::TestRenamer;
     use crate::view::Transformer;
 
@@ -69,7 +69,7 @@ mod tests {
             .find_dto(&EntityId::try_from("dto:dto").unwrap())
             .unwrap();
         let fields = dto.fields().collect_vec();
-        let ty = fields.get(0).unwrap().ty();
+        let ty = fields.first().unwrap().ty();
 
         assert_eq!(
             ty.inner()
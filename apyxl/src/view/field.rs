@@ -3,7 +3,9 @@ use std::borrow::Cow;
 use std::fmt::Debug;
 
 use crate::model;
-use crate::view::{AttributeTransform, Attributes, EntityIdTransform, Type};
+use crate::view::{
+    AttributeTransform, Attributes, EntityIdTransform, Type, TypeTransform, UserTypeTargets,
+};
 
 /// A pair of name and type that describe a named instance of a type e.g. within a [Dto] or [Rpc].
 /// Wraps [model::Dto].
@@ -12,11 +14,19 @@ pub struct Field<'v, 'a> {
     target: &'v model::Field<'a>,
     xforms: &'v Vec<Box<dyn FieldTransform>>,
     entity_id_xforms: &'v Vec<Box<dyn EntityIdTransform>>,
+    ty_xforms: &'v Vec<Box<dyn TypeTransform>>,
     attr_xforms: &'v Vec<Box<dyn AttributeTransform>>,
+    user_ty_targets: &'v UserTypeTargets,
 }
 
 pub trait FieldTransform: Debug + DynClone {
     fn name(&self, _: &mut Cow<str>) {}
+
+    /// `true`: included.
+    /// `false`: excluded.
+    fn filter(&self, _: &model::Field) -> bool {
+        true
+    }
 }
 
 dyn_clone::clone_trait_object!(FieldTransform);
@@ -26,17 +36,21 @@ impl<'v, 'a> Field<'v, 'a> {
         target: &'v model::Field<'a>,
         xforms: &'v Vec<Box<dyn FieldTransform>>,
         entity_id_xforms: &'v Vec<Box<dyn EntityIdTransform>>,
+        ty_xforms: &'v Vec<Box<dyn TypeTransform>>,
         attr_xforms: &'v Vec<Box<dyn AttributeTransform>>,
+        user_ty_targets: &'v UserTypeTargets,
     ) -> Self {
         Self {
             target,
             xforms,
             entity_id_xforms,
+            ty_xforms,
             attr_xforms,
+            user_ty_targets,
         }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn name(&self) -> Cow<'_, str> {
         let mut name = Cow::Borrowed(self.target.name);
         for x in self.xforms {
             x.name(&mut name)
@@ -44,11 +58,16 @@ impl<'v, 'a> Field<'v, 'a> {
         name
     }
 
-    pub fn ty(&self) -> Type {
-        Type::new(&self.target.ty, self.entity_id_xforms)
+    pub fn ty(&self) -> Type<'_> {
+        Type::new(
+            &self.target.ty,
+            self.entity_id_xforms,
+            self.ty_xforms,
+            self.user_ty_targets,
+        )
     }
 
-    pub fn attributes(&self) -> Attributes {
+    pub fn attributes(&self) -> Attributes<'_, '_> {
         Attributes::new(&self.target.attributes, self.attr_xforms)
     }
 }
@@ -58,8 +77,8 @@ mod tests {
     use crate::model::EntityId;
     use itertools::Itertools;
 
-    use crate::test_util::executor::TestExecutor;
-    use crate::view::tests::TestRenamer;
+    use crate::testing::TestExecutor;
+    use crate::view::tests::{TestFilter, TestRenamer};
     use crate::view::Transformer;
 
     #[test]
@@ -78,8 +97,66 @@ mod tests {
             .find_dto(&EntityId::try_from("d:dto").unwrap())
             .unwrap();
         let fields = dto.fields().collect_vec();
-        let field = fields.get(0).unwrap();
+        let field = fields.first().unwrap();
 
         assert_eq!(field.name(), TestRenamer::renamed("field0"));
     }
+
+    #[test]
+    fn filter() {
+        let mut exe = TestExecutor::new(
+            r#"
+                struct dto {
+                    visible0: u32,
+                    hidden: u32,
+                    visible1: u32,
+                }
+            "#,
+        );
+        let model = exe.model();
+        let view = model.view().with_field_transform(TestFilter {});
+        let root = view.api();
+        let dto = root
+            .find_dto(&EntityId::try_from("d:dto").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            dto.fields().map(|f| f.name().to_string()).collect_vec(),
+            vec!["visible0", "visible1"]
+        );
+    }
+
+    #[test]
+    fn params() {
+        let mut exe = TestExecutor::new(
+            r#"
+                struct dto {
+                    field: u32,
+                    hidden_field: u32,
+                }
+                fn rpc(param: u32, hidden_param: u32) {}
+            "#,
+        );
+        let model = exe.model();
+        let view = model
+            .view()
+            .with_param_transform(TestRenamer {})
+            .with_param_transform(TestFilter {});
+        let root = view.api();
+        let dto = root
+            .find_dto(&EntityId::try_from("d:dto").unwrap())
+            .unwrap();
+        let rpc = root
+            .find_rpc(&EntityId::try_from("r:rpc").unwrap())
+            .unwrap();
+
+        assert_eq!(
+            dto.fields().map(|f| f.name().to_string()).collect_vec(),
+            vec!["field", "hidden_field"]
+        );
+        assert_eq!(
+            rpc.params().map(|p| p.name().to_string()).collect_vec(),
+            vec![TestRenamer::renamed("param")]
+        );
+    }
 }
@@ -0,0 +1,122 @@
+use crate::model;
+use crate::model::EntityId;
+use crate::view::NamespaceTransform;
+
+/// A [NamespaceTransform] that includes or excludes namespaces, dtos, rpcs and enums by their
+/// fully qualified [EntityId], e.g. `a.b` or `a.b.d:Dto`, and prunes namespaces left without any
+/// children so that generators don't write empty modules.
+///
+/// An entity is included if it is within any of the [EntityIdFilter::include]d ids (or there are
+/// none), and not within any of the [EntityIdFilter::exclude]d ids. The namespaces leading to an
+/// included id are kept as well.
+#[derive(Debug, Clone, Default)]
+pub struct EntityIdFilter {
+    include: Vec<EntityId>,
+    exclude: Vec<EntityId>,
+}
+
+impl EntityIdFilter {
+    pub fn include(mut self, id: EntityId) -> Self {
+        self.include.push(id);
+        self
+    }
+
+    pub fn exclude(mut self, id: EntityId) -> Self {
+        self.exclude.push(id);
+        self
+    }
+}
+
+impl NamespaceTransform for EntityIdFilter {
+    fn filter_entity_id(&self, id: &model::EntityId) -> bool {
+        let included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|include| id.starts_with(include) || include.starts_with(id));
+        included && !self.exclude.iter().any(|exclude| id.starts_with(exclude))
+    }
+
+    fn prune_empty_namespaces(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::model::EntityId;
+    use crate::testing::TestExecutor;
+    use crate::view::{EntityIdFilter, Namespace, Transformer};
+
+    const API: &str = r#"
+        mod a {
+            mod b {
+                struct X {}
+                fn x() {}
+            }
+            mod c {
+                struct Y {}
+            }
+            enum Z {}
+        }
+        mod empty {}
+    "#;
+
+    fn names(namespace: &Namespace) -> Vec<String> {
+        namespace
+            .children()
+            .map(|child| child.name().to_string())
+            .collect_vec()
+    }
+
+    #[test]
+    fn include() {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let view = model.view().with_namespace_transform(
+            EntityIdFilter::default().include(EntityId::try_from("a.b.d:X").unwrap()),
+        );
+        let root = view.api();
+        assert_eq!(names(&root), vec!["a"]);
+        let a = root
+            .find_namespace(&EntityId::try_from("a").unwrap())
+            .unwrap();
+        assert_eq!(names(&a), vec!["b"]);
+        let b = a.find_namespace(&EntityId::try_from("b").unwrap()).unwrap();
+        assert_eq!(b.id(), &EntityId::try_from("a.b").unwrap());
+        assert_eq!(names(&b), vec!["X"]);
+    }
+
+    #[test]
+    fn exclude_prunes_empty_namespaces() {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let view = model.view().with_namespace_transform(
+            EntityIdFilter::default()
+                .exclude(EntityId::try_from("a.b").unwrap())
+                .exclude(EntityId::try_from("a.c.d:Y").unwrap()),
+        );
+        let root = view.api();
+        assert_eq!(names(&root), vec!["a"]);
+        let a = root.namespaces().next().unwrap();
+        assert_eq!(names(&a), vec!["Z"]);
+        assert!(root
+            .find_namespace(&EntityId::try_from("a.c").unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn exclude_nested() {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let view = model.view().with_namespace_transform(
+            EntityIdFilter::default().exclude(EntityId::try_from("a.b.r:x").unwrap()),
+        );
+        let root = view.api();
+        let a = root.namespaces().next().unwrap();
+        let b = a.namespaces().next().unwrap();
+        assert_eq!(names(&b), vec!["X"]);
+    }
+}
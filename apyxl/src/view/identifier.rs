@@ -0,0 +1,187 @@
+use std::borrow::Cow;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use crate::view::{
+    DtoTransform, EntityIdTransform, EnumTransform, EnumValueTransform, FieldTransform,
+    NamespaceTransform, RpcTransform,
+};
+
+/// How non-ASCII identifiers are written by a [crate::Generator]. Parsers keep identifiers
+/// exactly as written in the source, so targets that don't allow non-ASCII identifiers (or
+/// compare them without normalizing) should pick a policy other than [IdentifierPolicy::Keep].
+///
+/// See [crate::view::Transformer::with_identifier_policy].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentifierPolicy {
+    /// Write identifiers exactly as they were parsed.
+    #[default]
+    Keep,
+
+    /// Normalize identifiers to Unicode Normalization Form C, so that identifiers that look the
+    /// same are always written the same, e.g. `e` + `◌́` becomes `é`.
+    Nfc,
+
+    /// Transliterate identifiers to ASCII, e.g. `Größe` becomes `Grosse`. Characters that have no
+    /// identifier equivalent become `_`.
+    Ascii,
+}
+
+/// Applies an [IdentifierPolicy] to the names of all entities, fields, and [crate::view::EntityId]s.
+#[derive(Debug, Clone)]
+pub struct IdentifierNormalizer {
+    policy: IdentifierPolicy,
+}
+
+impl IdentifierNormalizer {
+    pub fn new(policy: IdentifierPolicy) -> Self {
+        Self { policy }
+    }
+
+    fn apply(&self, value: &mut Cow<str>) {
+        if let Some(normalized) = normalize(value, self.policy) {
+            *value = Cow::Owned(normalized)
+        }
+    }
+}
+
+/// Applies the `policy` to `ident`, or returns `None` if it is already unchanged by the policy.
+pub fn normalize(ident: &str, policy: IdentifierPolicy) -> Option<String> {
+    match policy {
+        IdentifierPolicy::Keep => None,
+        IdentifierPolicy::Nfc if is_nfc(ident) => None,
+        IdentifierPolicy::Nfc => Some(ident.nfc().collect()),
+        IdentifierPolicy::Ascii if ident.is_ascii() => None,
+        IdentifierPolicy::Ascii => Some(to_ascii(ident)),
+    }
+}
+
+fn to_ascii(ident: &str) -> String {
+    let transliterated = deunicode::deunicode_with_tofu(&ident.nfc().collect::<String>(), "_");
+    let mut ascii = transliterated
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect::<String>();
+    if ascii.is_empty() || ascii.starts_with(|c: char| c.is_ascii_digit()) {
+        ascii.insert(0, '_');
+    }
+    ascii
+}
+
+impl NamespaceTransform for IdentifierNormalizer {
+    fn name(&self, value: &mut Cow<str>) {
+        self.apply(value)
+    }
+}
+
+impl DtoTransform for IdentifierNormalizer {
+    fn name(&self, value: &mut Cow<str>) {
+        self.apply(value)
+    }
+}
+
+impl RpcTransform for IdentifierNormalizer {
+    fn name(&self, value: &mut Cow<str>) {
+        self.apply(value)
+    }
+}
+
+impl EnumTransform for IdentifierNormalizer {
+    fn name(&self, value: &mut Cow<str>) {
+        self.apply(value)
+    }
+}
+
+impl EnumValueTransform for IdentifierNormalizer {
+    fn name(&self, value: &mut Cow<str>) {
+        self.apply(value)
+    }
+}
+
+impl FieldTransform for IdentifierNormalizer {
+    fn name(&self, value: &mut Cow<str>) {
+        self.apply(value)
+    }
+}
+
+impl EntityIdTransform for IdentifierNormalizer {
+    fn path(&self, value: &mut Vec<Cow<str>>) {
+        for component in value {
+            self.apply(component)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::EntityId;
+    use crate::testing::TestExecutor;
+    use crate::view::identifier::{normalize, IdentifierPolicy};
+    use crate::view::Transformer;
+
+    #[test]
+    fn keep() {
+        assert_eq!(normalize("Größe", IdentifierPolicy::Keep), None);
+    }
+
+    #[test]
+    fn nfc() {
+        assert_eq!(
+            normalize("cafe\u{301}", IdentifierPolicy::Nfc),
+            Some("caf\u{e9}".to_string())
+        );
+        assert_eq!(normalize("caf\u{e9}", IdentifierPolicy::Nfc), None);
+    }
+
+    #[test]
+    fn ascii() {
+        assert_eq!(normalize("ascii", IdentifierPolicy::Ascii), None);
+        assert_eq!(
+            normalize("Größe", IdentifierPolicy::Ascii),
+            Some("Grosse".to_string())
+        );
+        assert_eq!(
+            normalize("cafe\u{301}", IdentifierPolicy::Ascii),
+            Some("cafe".to_string())
+        );
+        assert_eq!(
+            normalize("北京", IdentifierPolicy::Ascii),
+            Some("Bei_Jing".to_string())
+        );
+        assert_eq!(
+            normalize("١٢", IdentifierPolicy::Ascii),
+            Some("_12".to_string())
+        );
+    }
+
+    #[test]
+    fn view() {
+        let mut exe = TestExecutor::new(
+            r#"
+                mod straße {
+                    struct Größe {
+                        höhe: straße::Größe,
+                    }
+                }
+            "#,
+        );
+        let model = exe.model();
+        let view = model.view().with_identifier_policy(IdentifierPolicy::Ascii);
+        let root = view.api();
+        let namespace = root.namespaces().next().unwrap();
+        assert_eq!(namespace.name(), "strasse");
+        let dto = root
+            .find_dto(&EntityId::try_from("straße.d:Größe").unwrap())
+            .unwrap();
+        assert_eq!(dto.name(), "Grosse");
+        let field = dto.fields().next().unwrap();
+        assert_eq!(field.name(), "hohe");
+        let ty = field.ty();
+        let ty = ty.inner();
+        assert_eq!(ty.api().unwrap().path(), vec!["strasse", "Grosse"]);
+    }
+}
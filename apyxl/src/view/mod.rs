@@ -9,8 +9,13 @@ pub use dto::*;
 pub use en::*;
 pub use entity_id::*;
 pub use field::*;
+pub use filter::*;
+pub use identifier::*;
 pub use namespace::*;
+pub use order::*;
 pub use rpc::*;
+#[cfg(feature = "script")]
+pub use script::*;
 pub use sub_view::*;
 pub use ty::*;
 
@@ -23,9 +28,15 @@ mod dto;
 mod en;
 mod entity_id;
 mod field;
+mod filter;
+mod identifier;
 mod namespace;
+mod order;
 mod rpc;
+#[cfg(feature = "script")]
+mod script;
 mod sub_view;
+pub mod transforms;
 mod ty;
 
 // In everything in this module and submodules:
@@ -50,7 +61,11 @@ pub struct Transforms {
     en: Vec<Box<dyn EnumTransform>>,
     en_value: Vec<Box<dyn EnumValueTransform>>,
     entity_id: Vec<Box<dyn EntityIdTransform>>,
+    ty: Vec<Box<dyn TypeTransform>>,
     attr: Vec<Box<dyn AttributeTransform>>,
+    user_ty_targets: UserTypeTargets,
+    order: Order,
+    errors: transforms::TransformErrors,
 }
 
 impl<'v: 'a, 'a> Model<'v, 'a> {
@@ -63,7 +78,7 @@ impl<'v: 'a, 'a> Model<'v, 'a> {
 
     /// Get the full combined API root with all transforms applied.
     pub fn api(&'v self) -> Namespace<'v, 'a> {
-        Namespace::new(&self.target.api(), &self.xforms)
+        Namespace::new(self.target.api(), &self.xforms)
     }
 
     /// Iterate over [Chunk]s, where each subsection of the API can be viewed through a [SubView]
@@ -98,13 +113,24 @@ impl<'v: 'a, 'a> Model<'v, 'a> {
         })
     }
 
+    /// A [SubView] of only the namespaces `ids`, along with the namespaces leading to them, with
+    /// all transforms applied. See [SubView::at].
+    pub fn subview_at(&self, ids: &[model::EntityId]) -> Result<SubView<'a>> {
+        SubView::at(self.target.api(), ids, self.xforms.clone())
+    }
+
     // todo view::Metadata + metadata xforms
     pub fn metadata(&self) -> &model::Metadata {
-        &self.target.metadata()
+        self.target.metadata()
     }
 
     pub fn dependencies(&self) -> &model::Dependencies {
-        &self.target.dependencies()
+        self.target.dependencies()
+    }
+
+    /// The underlying [model::Model] without any transforms applied.
+    pub fn target(&self) -> &'v model::Model<'a> {
+        self.target
     }
 }
 
@@ -114,7 +140,8 @@ impl Transformer for Model<'_, '_> {
     }
 }
 
-pub(crate) trait Transformer: Sized {
+/// Anything that holds a set of [Transforms], e.g. a view [Model] or an [crate::Executor].
+pub trait Transformer: Sized {
     fn xforms(&mut self) -> &mut Transforms;
 
     fn with_namespace_transform(mut self, xform: impl NamespaceTransform + 'static) -> Self {
@@ -142,23 +169,80 @@ pub(crate) trait Transformer: Sized {
         self
     }
 
+    /// Applied to the fields of dtos. See [Transformer::with_param_transform] for rpc params.
     fn with_field_transform(mut self, xform: impl FieldTransform + 'static) -> Self {
         self.xforms().dto_field.push(Box::new(xform));
         self
     }
 
+    /// Applied to the params of rpcs, including dto methods.
+    fn with_param_transform(mut self, xform: impl FieldTransform + 'static) -> Self {
+        self.xforms().rpc_param.push(Box::new(xform));
+        self
+    }
+
     fn with_entity_id_transform(mut self, xform: impl EntityIdTransform + 'static) -> Self {
         self.xforms().entity_id.push(Box::new(xform));
         self
     }
 
+    fn with_type_transform(mut self, xform: impl TypeTransform + 'static) -> Self {
+        self.xforms().ty.push(Box::new(xform));
+        self
+    }
+
     fn with_attribute_transform(mut self, xform: impl AttributeTransform + 'static) -> Self {
         self.xforms().attr.push(Box::new(xform));
         self
     }
+
+    /// Applies the [IdentifierPolicy] to the names of all entities, fields, and [EntityId]s.
+    fn with_identifier_policy(mut self, policy: IdentifierPolicy) -> Self {
+        if policy == IdentifierPolicy::Keep {
+            return self;
+        }
+        let normalizer = IdentifierNormalizer::new(policy);
+        let xforms = self.xforms();
+        xforms.namespace.push(Box::new(normalizer.clone()));
+        xforms.dto.push(Box::new(normalizer.clone()));
+        xforms.dto_field.push(Box::new(normalizer.clone()));
+        xforms.rpc.push(Box::new(normalizer.clone()));
+        xforms.rpc_param.push(Box::new(normalizer.clone()));
+        xforms.en.push(Box::new(normalizer.clone()));
+        xforms.en_value.push(Box::new(normalizer.clone()));
+        xforms.entity_id.push(Box::new(normalizer));
+        self
+    }
+
+    /// Adds all transforms of a [transforms::TransformSet], e.g. [transforms::CaseConversion].
+    fn with_transforms(mut self, set: impl transforms::TransformSet) -> Self {
+        set.add_to(self.xforms());
+        self
+    }
+
+    /// Replaces the names of user types with the type a specific [crate::Generator] should
+    /// emit. Unlike other transforms, this replaces any previously set [UserTypeTargets].
+    fn with_user_type_targets(mut self, targets: UserTypeTargets) -> Self {
+        self.xforms().user_ty_targets = targets;
+        self
+    }
+
+    /// Lists the children of namespaces and the methods of dtos in `order`, e.g. so that output
+    /// is stable and easy to diff regardless of how the API was parsed. Unlike other transforms,
+    /// this replaces any previously set [Order].
+    fn with_order(mut self, order: Order) -> Self {
+        self.xforms().order = order;
+        self
+    }
 }
 
 impl Transforms {
+    pub fn order(&self) -> Order {
+        self.order
+    }
+    pub fn errors(&self) -> &transforms::TransformErrors {
+        &self.errors
+    }
     pub fn namespace(&self) -> impl Iterator<Item = &Box<dyn NamespaceTransform>> {
         self.namespace.iter()
     }
@@ -177,6 +261,9 @@ impl Transforms {
     pub fn entity_id_xforms(&self) -> impl Iterator<Item = &Box<dyn EntityIdTransform>> {
         self.entity_id.iter()
     }
+    pub fn ty_xforms(&self) -> impl Iterator<Item = &Box<dyn TypeTransform>> {
+        self.ty.iter()
+    }
     pub fn attr_xforms(&self) -> impl Iterator<Item = &Box<dyn AttributeTransform>> {
         self.attr.iter()
     }
@@ -259,6 +346,9 @@ mod tests {
         fn filter_field(&self, field: &model::Field) -> bool {
             !field.name.contains("hidden")
         }
+        fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
+            !rpc.name.contains("hidden")
+        }
     }
 
     impl RpcTransform for TestFilter {
@@ -272,4 +362,10 @@ mod tests {
             !value.name.contains("hidden")
         }
     }
+
+    impl FieldTransform for TestFilter {
+        fn filter(&self, field: &model::Field) -> bool {
+            !field.name.contains("hidden")
+        }
+    }
 }
@@ -10,14 +10,16 @@ use crate::view::{Attributes, Dto, Enum, Rpc, Transforms};
 
 /// A named, nestable wrapper for a set of API entities.
 /// Wraps [model::Namespace].
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Namespace<'v, 'a> {
     target: &'v model::Namespace<'a>,
     xforms: &'v Transforms,
+    /// Fully qualified id of the namespace within the (untransformed) model.
+    id: model::EntityId,
 }
 
 /// Wraps [model::NamespaceChild].
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub enum NamespaceChild<'v, 'a> {
     Dto(Dto<'v, 'a>),
     Rpc(Rpc<'v, 'a>),
@@ -51,23 +53,49 @@ pub trait NamespaceTransform: Debug + DynClone {
     fn filter_enum(&self, _: &model::Enum) -> bool {
         true
     }
+
+    /// Called with the fully qualified id, e.g. `a.b.d:Dto`, of each namespace, dto, rpc and enum
+    /// in addition to the other filters. Ids are those of the model, before any renames.
+    ///
+    /// `true`: included.
+    /// `false`: excluded.
+    fn filter_entity_id(&self, _: &model::EntityId) -> bool {
+        true
+    }
+
+    /// `true`: namespaces without any included dtos, rpcs, enums or non-empty namespaces are
+    /// excluded too, e.g. so that generators don't write empty modules after filtering.
+    fn prune_empty_namespaces(&self) -> bool {
+        false
+    }
+
+    /// `true`: the dtos, rpcs and enums of all included nested namespaces are listed as children
+    /// of the viewed namespace itself, which then has no nested namespaces.
+    fn flatten_namespaces(&self) -> bool {
+        false
+    }
 }
 
 dyn_clone::clone_trait_object!(NamespaceTransform);
 
 impl<'v, 'a> NamespaceChild<'v, 'a> {
-    pub fn new(target: &'v model::NamespaceChild<'a>, xforms: &'v Transforms) -> Self {
+    /// `namespace_id` is the id of the namespace containing `target`.
+    pub fn new(
+        target: &'v model::NamespaceChild<'a>,
+        xforms: &'v Transforms,
+        namespace_id: &model::EntityId,
+    ) -> Self {
         match target {
-            model::NamespaceChild::Dto(target) => NamespaceChild::Dto(Dto::new(target, &xforms)),
-            model::NamespaceChild::Namespace(target) => {
-                NamespaceChild::Namespace(Namespace::new(target, &xforms))
-            }
-            model::NamespaceChild::Enum(target) => NamespaceChild::Enum(Enum::new(target, &xforms)),
-            model::NamespaceChild::Rpc(target) => NamespaceChild::Rpc(Rpc::new(target, &xforms)),
+            model::NamespaceChild::Dto(target) => NamespaceChild::Dto(Dto::new(target, xforms)),
+            model::NamespaceChild::Namespace(target) => NamespaceChild::Namespace(
+                Namespace::new(target, xforms).with_id(child_id(namespace_id, target)),
+            ),
+            model::NamespaceChild::Enum(target) => NamespaceChild::Enum(Enum::new(target, xforms)),
+            model::NamespaceChild::Rpc(target) => NamespaceChild::Rpc(Rpc::new(target, xforms)),
         }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn name(&self) -> Cow<'_, str> {
         match self {
             NamespaceChild::Dto(dto) => dto.name(),
             NamespaceChild::Rpc(rpc) => rpc.name(),
@@ -76,7 +104,7 @@ impl<'v, 'a> NamespaceChild<'v, 'a> {
         }
     }
 
-    pub fn attributes(&self) -> Attributes {
+    pub fn attributes(&self) -> Attributes<'_, '_> {
         match self {
             NamespaceChild::Dto(dto) => dto.attributes(),
             NamespaceChild::Rpc(rpc) => rpc.attributes(),
@@ -96,18 +124,35 @@ impl<'v, 'a> NamespaceChild<'v, 'a> {
 }
 
 impl<'v, 'a> Namespace<'v, 'a> {
+    /// A view of `target` as the root of the API. See [Namespace::with_id] otherwise.
     pub fn new(target: &'v model::Namespace<'a>, xforms: &'v Transforms) -> Self {
-        Self { target, xforms }
+        Self {
+            target,
+            xforms,
+            id: model::EntityId::default(),
+        }
+    }
+
+    /// Sets the fully qualified id of the namespace, which is passed on to
+    /// [NamespaceTransform::filter_entity_id] for the namespace's children.
+    pub fn with_id(mut self, id: model::EntityId) -> Self {
+        self.id = id.to_qualified_namespaces();
+        self
     }
 
     pub fn clone_with_new_transforms(&self, xforms: &'v Transforms) -> Self {
         Self {
             target: self.target,
             xforms,
+            id: self.id.clone(),
         }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn id(&self) -> &model::EntityId {
+        &self.id
+    }
+
+    pub fn name(&self) -> Cow<'_, str> {
         let mut name = self.target.name.clone();
         for x in &self.xforms.namespace {
             x.name(&mut name)
@@ -124,83 +169,156 @@ impl<'v, 'a> Namespace<'v, 'a> {
     }
 
     pub fn children(&'a self) -> impl Iterator<Item = NamespaceChild<'v, 'a>> + 'a {
-        self.target
-            .children
-            .iter()
-            .filter(|child| self.filter_child(child))
-            .map(|child| NamespaceChild::new(child, self.xforms))
-    }
-
-    pub fn attributes(&self) -> Attributes {
+        let flatten = self.is_flattened();
+        let children = self.flattened().into_iter().flat_map(move |ns| {
+            let target = ns.target;
+            let xforms = ns.xforms;
+            let id = ns.id.clone();
+            target
+                .children
+                .iter()
+                .filter(move |child| {
+                    !(flatten && matches!(child, model::NamespaceChild::Namespace(_)))
+                        && ns.filter_child(child)
+                })
+                .map(move |child| NamespaceChild::new(child, xforms, &id))
+        });
+        self.xforms.order.sorted(children)
+    }
+
+    pub fn attributes(&self) -> Attributes<'_, '_> {
         Attributes::new(&self.target.attributes, &self.xforms.attr)
     }
 
     pub fn find_child(&'a self, id: &model::EntityId) -> Option<NamespaceChild<'v, 'a>> {
-        self.target
-            .find_child(id)
-            .filter(|child| self.filter_child(child))
-            .map(|child| NamespaceChild::new(child, self.xforms))
+        let parent_id = self.nested_id(&id.parent()?);
+        let child = self.target.find_child(id)?;
+        self.in_namespace(&parent_id)
+            .filter_child(child)
+            .then(|| NamespaceChild::new(child, self.xforms, &parent_id))
     }
 
     pub fn find_namespace(&'a self, id: &model::EntityId) -> Option<Namespace<'v, 'a>> {
-        self.target
-            .find_namespace(id)
-            .filter(|namespace| {
-                self.xforms
-                    .namespace
-                    .iter()
-                    .all(|x| x.filter_namespace(namespace))
-            })
-            .map(|namespace| Namespace::new(namespace, self.xforms))
+        let namespace = self.target.find_namespace(id)?;
+        let parent_id = self.nested_id(&id.parent()?);
+        self.in_namespace(&parent_id)
+            .filter_namespace(namespace)
+            .then(|| Namespace::new(namespace, self.xforms).with_id(self.nested_id(id)))
     }
 
     pub fn find_dto(&'a self, id: &model::EntityId) -> Option<Dto<'v, 'a>> {
-        self.target
-            .find_dto(id)
-            .filter(|dto| self.filter_dto(dto))
-            .map(|dto| Dto::new(dto, self.xforms))
+        let dto = self.target.find_dto(id)?;
+        self.in_namespace(&self.nested_id(&id.parent()?))
+            .filter_dto(dto)
+            .then(|| Dto::new(dto, self.xforms))
     }
 
     pub fn find_rpc(&'a self, id: &model::EntityId) -> Option<Rpc<'v, 'a>> {
-        self.target
-            .find_rpc(id)
-            .filter(|rpc| self.filter_rpc(rpc))
-            .map(|rpc| Rpc::new(rpc, self.xforms))
+        let rpc = self.target.find_rpc(id)?;
+        self.in_namespace(&self.nested_id(&id.parent()?))
+            .filter_rpc(rpc)
+            .then(|| Rpc::new(rpc, self.xforms))
     }
 
     pub fn find_enum(&'a self, id: &model::EntityId) -> Option<Enum<'v, 'a>> {
-        self.target
-            .find_enum(id)
-            .filter(|en| self.filter_enum(en))
-            .map(|en| Enum::new(en, self.xforms))
+        let en = self.target.find_enum(id)?;
+        self.in_namespace(&self.nested_id(&id.parent()?))
+            .filter_enum(en)
+            .then(|| Enum::new(en, self.xforms))
     }
 
     pub fn namespaces(&'a self) -> impl Iterator<Item = Namespace<'v, 'a>> + 'a {
-        self.target
+        let flatten = self.is_flattened();
+        let namespaces = self
+            .target
             .namespaces()
-            .filter(|ns| self.filter_namespace(ns))
-            .map(|ns| Namespace::new(ns, self.xforms))
+            .filter(move |ns| !flatten && self.filter_namespace(ns))
+            .map(|ns| Namespace::new(ns, self.xforms).with_id(child_id(&self.id, ns)));
+        self.xforms.order.sorted(namespaces)
     }
 
     pub fn dtos(&'a self) -> impl Iterator<Item = Dto<'v, 'a>> {
-        self.target
-            .dtos()
-            .filter(|dto| self.filter_dto(dto))
-            .map(|dto| Dto::new(dto, self.xforms))
+        let dtos = self.flattened().into_iter().flat_map(|ns| {
+            let target = ns.target;
+            let xforms = ns.xforms;
+            target
+                .dtos()
+                .filter(move |dto| ns.filter_dto(dto))
+                .map(move |dto| Dto::new(dto, xforms))
+        });
+        self.xforms.order.sorted(dtos)
     }
 
     pub fn rpcs(&'a self) -> impl Iterator<Item = Rpc<'v, 'a>> {
-        self.target
-            .rpcs()
-            .filter(|rpc| self.filter_rpc(rpc))
-            .map(|rpc| Rpc::new(rpc, self.xforms))
+        let rpcs = self.flattened().into_iter().flat_map(|ns| {
+            let target = ns.target;
+            let xforms = ns.xforms;
+            target
+                .rpcs()
+                .filter(move |rpc| ns.filter_rpc(rpc))
+                .map(move |rpc| Rpc::new(rpc, xforms))
+        });
+        self.xforms.order.sorted(rpcs)
+    }
+
+    /// The [Namespace::rpcs], grouped by [model::Service] using their attributes after
+    /// [crate::view::AttributeTransform]s are applied.
+    pub fn services(&'a self) -> Vec<model::Service<Rpc<'v, 'a>>> {
+        model::Service::group(self.rpcs(), |rpc| {
+            model::service_name(&rpc.attributes().user())
+        })
     }
 
     pub fn enums(&'a self) -> impl Iterator<Item = Enum<'v, 'a>> {
-        self.target
-            .enums()
-            .filter(|en| self.filter_enum(en))
-            .map(|en| Enum::new(en, self.xforms))
+        let enums = self.flattened().into_iter().flat_map(|ns| {
+            let target = ns.target;
+            let xforms = ns.xforms;
+            target
+                .enums()
+                .filter(move |en| ns.filter_enum(en))
+                .map(move |en| Enum::new(en, xforms))
+        });
+        self.xforms.order.sorted(enums)
+    }
+
+    fn is_flattened(&self) -> bool {
+        self.xforms.namespace.iter().any(|x| x.flatten_namespaces())
+    }
+
+    /// This namespace followed by all of its included nested namespaces, depth first, if they're
+    /// flattened into it. Otherwise, only this namespace.
+    fn flattened(&self) -> Vec<Namespace<'v, 'a>> {
+        let mut namespaces = vec![self.clone()];
+        if self.is_flattened() {
+            self.push_nested(&mut namespaces);
+        }
+        namespaces
+    }
+
+    fn push_nested(&self, namespaces: &mut Vec<Namespace<'v, 'a>>) {
+        for nested in self.target.namespaces() {
+            if self.filter_namespace(nested) {
+                let view = Namespace::new(nested, self.xforms).with_id(child_id(&self.id, nested));
+                namespaces.push(view.clone());
+                view.push_nested(namespaces);
+            }
+        }
+    }
+
+    /// Id of the namespace `id`, relative to this one.
+    fn nested_id(&self, id: &model::EntityId) -> model::EntityId {
+        let mut nested = self.id.clone();
+        for name in id.component_names() {
+            nested = nested
+                .child(EntityType::Namespace, name)
+                .expect("namespaces can always be nested");
+        }
+        nested
+    }
+
+    /// A view of the namespace with id `id` used only for its filters, which depend on the id.
+    fn in_namespace(&self, id: &model::EntityId) -> Namespace<'v, 'a> {
+        Namespace::new(self.target, self.xforms).with_id(id.clone())
     }
 
     fn filter_child(&self, child: &model::NamespaceChild) -> bool {
@@ -213,31 +331,72 @@ impl<'v, 'a> Namespace<'v, 'a> {
     }
 
     fn filter_namespace(&self, namespace: &model::Namespace) -> bool {
-        self.xforms
+        let id = child_id(&self.id, namespace);
+        let included = self
+            .xforms
+            .namespace
+            .iter()
+            .all(|x| x.filter_namespace(namespace) && x.filter_entity_id(&id));
+        if !included {
+            return false;
+        }
+        if !self
+            .xforms
             .namespace
             .iter()
-            .all(|x| x.filter_namespace(namespace))
+            .any(|x| x.prune_empty_namespaces())
+        {
+            return true;
+        }
+        let view = Namespace::new(namespace, self.xforms).with_id(id);
+        view.target
+            .children
+            .iter()
+            .any(|child| view.filter_child(child))
     }
 
     fn filter_dto(&self, dto: &model::Dto) -> bool {
-        self.xforms.namespace.iter().all(|x| x.filter_dto(dto))
+        self.filter_id(EntityType::Dto, dto.name)
+            && self.xforms.namespace.iter().all(|x| x.filter_dto(dto))
     }
 
     fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
-        self.xforms.namespace.iter().all(|x| x.filter_rpc(rpc))
+        self.filter_id(EntityType::Rpc, rpc.name)
+            && self.xforms.namespace.iter().all(|x| x.filter_rpc(rpc))
     }
 
     fn filter_enum(&self, en: &model::Enum) -> bool {
-        self.xforms.namespace.iter().all(|x| x.filter_enum(en))
+        self.filter_id(EntityType::Enum, en.name)
+            && self.xforms.namespace.iter().all(|x| x.filter_enum(en))
+    }
+
+    fn filter_id(&self, ty: EntityType, name: &str) -> bool {
+        if self.xforms.namespace.is_empty() {
+            return true;
+        }
+        let id = self
+            .id
+            .child(ty, name)
+            .expect("namespaces can contain any child");
+        self.xforms
+            .namespace
+            .iter()
+            .all(|x| x.filter_entity_id(&id))
     }
 }
 
+fn child_id(namespace_id: &model::EntityId, namespace: &model::Namespace) -> model::EntityId {
+    namespace_id
+        .child(EntityType::Namespace, &namespace.name)
+        .expect("namespaces can always be nested")
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
 
     use crate::model::EntityId;
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::tests::{TestFilter, TestRenamer};
     use crate::view::{NamespaceChild, Transformer};
 
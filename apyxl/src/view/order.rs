@@ -0,0 +1,167 @@
+use std::borrow::Cow;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::model::EntityType;
+use crate::view::{Dto, Enum, Namespace, NamespaceChild, Rpc};
+
+/// The order in which a view lists the children of namespaces and the methods of dtos.
+///
+/// See [crate::view::Transformer::with_order].
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Order {
+    /// The order the parser and the merge of chunks produced.
+    #[default]
+    Source,
+
+    /// Alphabetical by name, after transforms are applied.
+    Name,
+
+    /// Grouped by kind in the order of [EntityType], i.e. namespaces, dtos, rpcs then enums, and
+    /// alphabetical by name within each kind.
+    KindThenName,
+}
+
+/// Anything a view lists that can be sorted by [Order].
+pub(crate) trait Ordered {
+    fn order_name(&self) -> Cow<'_, str>;
+    fn order_kind(&self) -> EntityType;
+}
+
+impl Order {
+    /// Sorts `items` in this order. Sorting is stable, so items with the same name keep their
+    /// source order.
+    pub(crate) fn sort<T: Ordered>(&self, items: &mut [T]) {
+        match self {
+            Order::Source => {}
+            Order::Name => items.sort_by(|a, b| a.order_name().cmp(&b.order_name())),
+            Order::KindThenName => items.sort_by(|a, b| {
+                a.order_kind()
+                    .cmp(&b.order_kind())
+                    .then_with(|| a.order_name().cmp(&b.order_name()))
+            }),
+        }
+    }
+}
+
+impl Order {
+    /// `items` collected and sorted in this order.
+    pub(crate) fn sorted<T: Ordered>(
+        &self,
+        items: impl Iterator<Item = T>,
+    ) -> std::vec::IntoIter<T> {
+        let mut items = items.collect::<Vec<_>>();
+        self.sort(&mut items);
+        items.into_iter()
+    }
+}
+
+macro_rules! impl_ordered {
+    ($($ty:ident),*) => {
+        $(
+            impl Ordered for $ty<'_, '_> {
+                fn order_name(&self) -> Cow<'_, str> {
+                    self.name()
+                }
+                fn order_kind(&self) -> EntityType {
+                    self.entity_type()
+                }
+            }
+        )*
+    };
+}
+
+impl_ordered!(NamespaceChild, Namespace, Dto, Rpc, Enum);
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::model::EntityId;
+    use crate::testing::TestExecutor;
+    use crate::view::{Order, Transformer};
+
+    const API: &str = r#"
+        fn b_rpc() {}
+        struct c_dto {}
+        impl c_dto {
+            fn z() {}
+            fn y() {}
+        }
+        mod d_ns {}
+        enum a_enum {}
+        struct a_dto {}
+    "#;
+
+    fn names(order: Order) -> (Vec<String>, Vec<String>) {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let view = model.view().with_order(order);
+        let root = view.api();
+        let children = root
+            .children()
+            .map(|child| child.name().to_string())
+            .collect_vec();
+        let dto = root
+            .find_dto(&EntityId::try_from("d:c_dto").unwrap())
+            .unwrap();
+        let methods = dto.rpcs().map(|rpc| rpc.name().to_string()).collect_vec();
+        (children, methods)
+    }
+
+    #[test]
+    fn source() {
+        assert_eq!(
+            names(Order::Source),
+            (
+                vec!["b_rpc", "c_dto", "a_enum", "a_dto", "d_ns"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                vec!["z".to_string(), "y".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn name() {
+        assert_eq!(
+            names(Order::Name),
+            (
+                vec!["a_dto", "a_enum", "b_rpc", "c_dto", "d_ns"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                vec!["y".to_string(), "z".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn kind_then_name() {
+        assert_eq!(
+            names(Order::KindThenName),
+            (
+                vec!["d_ns", "a_dto", "c_dto", "b_rpc", "a_enum"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                vec!["y".to_string(), "z".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn dtos() {
+        let mut exe = TestExecutor::new(API);
+        let model = exe.build();
+        let view = model.view().with_order(Order::Name);
+        let root = view.api();
+        assert_eq!(
+            root.dtos().map(|dto| dto.name().to_string()).collect_vec(),
+            vec!["a_dto", "c_dto"]
+        );
+    }
+}
@@ -34,7 +34,7 @@ impl<'v, 'a> Rpc<'v, 'a> {
         Self { target, xforms }
     }
 
-    pub fn name(&self) -> Cow<str> {
+    pub fn name(&self) -> Cow<'_, str> {
         let mut name = Cow::Borrowed(self.target.name);
         for x in &self.xforms.rpc {
             x.name(&mut name)
@@ -46,6 +46,16 @@ impl<'v, 'a> Rpc<'v, 'a> {
         self.target.entity_type()
     }
 
+    /// Names of generic type parameters, see [model::Rpc::type_params].
+    pub fn type_params(&self) -> &[&'a str] {
+        &self.target.type_params
+    }
+
+    /// See [model::Rpc::is_async].
+    pub fn is_async(&self) -> bool {
+        self.target.is_async
+    }
+
     pub fn params(&'a self) -> impl Iterator<Item = Field<'v, 'a>> {
         self.target
             .params
@@ -56,24 +66,43 @@ impl<'v, 'a> Rpc<'v, 'a> {
                     param,
                     &self.xforms.rpc_param,
                     &self.xforms.entity_id,
+                    &self.xforms.ty,
                     &self.xforms.attr,
+                    &self.xforms.user_ty_targets,
                 )
             })
     }
 
-    pub fn return_type(&self) -> Option<Type> {
-        self.target
-            .return_type
-            .as_ref()
-            .map(|target| Type::new(target, &self.xforms.entity_id))
+    pub fn return_type(&self) -> Option<Type<'_>> {
+        self.target.return_type.as_ref().map(|target| {
+            Type::new(
+                target,
+                &self.xforms.entity_id,
+                &self.xforms.ty,
+                &self.xforms.user_ty_targets,
+            )
+        })
+    }
+
+    /// See [model::Rpc::error_type].
+    pub fn error_type(&self) -> Option<Type<'_>> {
+        self.target.error_type.as_ref().map(|target| {
+            Type::new(
+                target,
+                &self.xforms.entity_id,
+                &self.xforms.ty,
+                &self.xforms.user_ty_targets,
+            )
+        })
     }
 
-    pub fn attributes(&self) -> Attributes {
+    pub fn attributes(&self) -> Attributes<'_, '_> {
         Attributes::new(&self.target.attributes, &self.xforms.attr)
     }
 
     fn filter_param(&self, param: &model::Field) -> bool {
         self.xforms.rpc.iter().all(|x| x.filter_param(param))
+            && self.xforms.rpc_param.iter().all(|x| x.filter(param))
     }
 }
 
@@ -82,7 +111,7 @@ mod tests {
     use itertools::Itertools;
 
     use crate::model::EntityId;
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::tests::{TestFilter, TestRenamer};
     use crate::view::Transformer;
 
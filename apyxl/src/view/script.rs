@@ -0,0 +1,359 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context, Result};
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Map, Scope, AST};
+
+use crate::model;
+use crate::model::attribute;
+use crate::view::transforms::{TransformErrors, TransformSet};
+use crate::view::{
+    DtoTransform, EntityIdTransform, EnumTransform, EnumValueTransform, FieldTransform,
+    NamespaceTransform, RpcTransform, Transforms,
+};
+
+/// View transforms written in [Rhai](https://rhai.rs), so that names and filters can be changed
+/// from a [crate::Pipeline] without recompiling. Add with
+/// [crate::view::Transformer::with_transforms].
+///
+/// The script defines any of these functions, which are called for every entity they apply to:
+/// - `namespace_name(name)`, `type_name(name)` (dtos and enums), `rpc_name(name)`,
+///   `field_name(name)` (dto fields and rpc params) and `enum_value_name(name)` return the new name.
+/// - `filter_namespace(entity)`, `filter_dto(entity)`, `filter_rpc(entity)`, `filter_enum(entity)`
+///   and `filter_field(entity)` (dto fields and rpc params) return `false` to exclude the entity.
+///   `entity` is a map with its `name` and the names of its user `attributes`.
+///
+/// Like [crate::view::transforms::CaseConversion], `namespace_name` and `type_name` also apply to
+/// references to types, so that they stay consistent.
+///
+/// ```
+/// use apyxl::view::Script;
+///
+/// let script = Script::new(r#"
+///     fn type_name(name) { name + "Dto" }
+///     fn filter_rpc(rpc) { !rpc.attributes.contains("internal") }
+/// "#).unwrap();
+/// ```
+///
+/// The script's top-level statements are run once, when it's loaded, so they can define
+/// constants for its functions. Errors at that point are returned. Errors within the functions
+/// leave the name unchanged or the entity included, and are added to the
+/// [crate::view::Transforms::errors], which fail the [crate::Executor] run.
+#[derive(Clone)]
+pub struct Script {
+    name: String,
+    engine: Arc<Engine>,
+    ast: Arc<AST>,
+    scope: Scope<'static>,
+    functions: BTreeSet<String>,
+    errors: TransformErrors,
+}
+
+impl Script {
+    /// Compiles and runs the Rhai `source`.
+    pub fn new(source: &str) -> Result<Self> {
+        Self::with_name("script", source)
+    }
+
+    /// Loads a Rhai script from a file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("read script '{}'", path.display()))?;
+        Self::with_name(path.display().to_string(), &source)
+    }
+
+    fn with_name(name: impl ToString, source: &str) -> Result<Self> {
+        let name = name.to_string();
+        let engine = Engine::new();
+        let ast = engine
+            .compile(source)
+            .map_err(|err| anyhow!("compile script '{}': {}", name, err))?;
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|err| anyhow!("run script '{}': {}", name, err))?;
+        let functions = ast.iter_functions().map(|f| f.name.to_string()).collect();
+        Ok(Self {
+            name,
+            engine: Arc::new(engine),
+            ast: Arc::new(ast),
+            scope,
+            functions,
+            errors: TransformErrors::default(),
+        })
+    }
+
+    fn defines(&self, function: &str) -> bool {
+        self.functions.contains(function)
+    }
+
+    /// Calls `function` and casts what it returns, recording any error.
+    fn call<T>(
+        &self,
+        function: &str,
+        arg: Dynamic,
+        cast: impl FnOnce(Dynamic) -> Result<T, &'static str>,
+    ) -> Option<T> {
+        let mut scope = self.scope.clone();
+        let options = CallFnOptions::new().eval_ast(false);
+        let result = self
+            .engine
+            .call_fn_with_options::<Dynamic>(options, &mut scope, &self.ast, function, (arg,))
+            .map_err(|err| err.to_string())
+            .and_then(|value| cast(value).map_err(|ty| format!("unexpected return type {}", ty)));
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.errors.push(format!(
+                    "script '{}' failed in '{}': {}",
+                    self.name, function, err
+                ));
+                None
+            }
+        }
+    }
+
+    fn rename(&self, function: &str, value: &mut Cow<str>) {
+        if !self.defines(function) {
+            return;
+        }
+        if let Some(name) = self.call(function, value.to_string().into(), Dynamic::into_string) {
+            if name != *value {
+                *value = Cow::Owned(name)
+            }
+        }
+    }
+
+    fn filter(&self, function: &str, name: &str, attributes: &model::Attributes) -> bool {
+        if !self.defines(function) {
+            return true;
+        }
+        let mut entity = Map::new();
+        entity.insert("name".into(), name.to_string().into());
+        let attributes = attributes
+            .user
+            .iter()
+            .map(|attribute::User { name, .. }| Dynamic::from(name.to_string()))
+            .collect::<Array>();
+        entity.insert("attributes".into(), attributes.into());
+        self.call(function, entity.into(), |value| value.as_bool())
+            .unwrap_or(true)
+    }
+}
+
+impl Debug for Script {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Script")
+            .field("name", &self.name)
+            .field("functions", &self.functions)
+            .finish()
+    }
+}
+
+impl NamespaceTransform for Script {
+    fn name(&self, value: &mut Cow<str>) {
+        self.rename("namespace_name", value)
+    }
+
+    fn filter_namespace(&self, namespace: &model::Namespace) -> bool {
+        self.filter("filter_namespace", &namespace.name, &namespace.attributes)
+    }
+
+    fn filter_dto(&self, dto: &model::Dto) -> bool {
+        self.filter("filter_dto", dto.name, &dto.attributes)
+    }
+
+    fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
+        self.filter("filter_rpc", rpc.name, &rpc.attributes)
+    }
+
+    fn filter_enum(&self, en: &model::Enum) -> bool {
+        self.filter("filter_enum", en.name, &en.attributes)
+    }
+}
+
+impl DtoTransform for Script {
+    fn name(&self, value: &mut Cow<str>) {
+        self.rename("type_name", value)
+    }
+
+    fn filter_field(&self, field: &model::Field) -> bool {
+        self.filter("filter_field", field.name, &field.attributes)
+    }
+
+    fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
+        self.filter("filter_rpc", rpc.name, &rpc.attributes)
+    }
+}
+
+impl EnumTransform for Script {
+    fn name(&self, value: &mut Cow<str>) {
+        self.rename("type_name", value)
+    }
+}
+
+impl RpcTransform for Script {
+    fn name(&self, value: &mut Cow<str>) {
+        self.rename("rpc_name", value)
+    }
+
+    fn filter_param(&self, param: &model::Field) -> bool {
+        self.filter("filter_field", param.name, &param.attributes)
+    }
+}
+
+impl FieldTransform for Script {
+    fn name(&self, value: &mut Cow<str>) {
+        self.rename("field_name", value)
+    }
+}
+
+impl EnumValueTransform for Script {
+    fn name(&self, value: &mut Cow<str>) {
+        self.rename("enum_value_name", value)
+    }
+}
+
+impl EntityIdTransform for Script {
+    fn path(&self, value: &mut Vec<Cow<str>>) {
+        let len = value.len();
+        for (i, component) in value.iter_mut().enumerate() {
+            if i + 1 == len {
+                self.rename("type_name", component)
+            } else {
+                self.rename("namespace_name", component)
+            }
+        }
+    }
+}
+
+impl TransformSet for Script {
+    fn add_to(mut self, xforms: &mut Transforms) {
+        self.errors = xforms.errors.clone();
+        xforms.namespace.push(Box::new(self.clone()));
+        xforms.dto.push(Box::new(self.clone()));
+        xforms.dto_field.push(Box::new(self.clone()));
+        xforms.rpc.push(Box::new(self.clone()));
+        xforms.rpc_param.push(Box::new(self.clone()));
+        xforms.en.push(Box::new(self.clone()));
+        xforms.en_value.push(Box::new(self.clone()));
+        xforms.entity_id.push(Box::new(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::testing::TestExecutor;
+    use crate::view::{Namespace, Script, Transformer};
+
+    fn names(namespace: &Namespace) -> Vec<String> {
+        namespace
+            .children()
+            .map(|child| child.name().to_string())
+            .collect_vec()
+    }
+
+    #[test]
+    fn renames() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod ns {
+                struct User {
+                    user_id: Kind,
+                }
+                enum Kind {
+                    Admin = 0,
+                }
+                fn get_user(user_id: u32) {}
+            }
+            "#,
+        );
+        let model = exe.build();
+        let script = Script::new(
+            r#"
+            const SUFFIX = "Dto";
+            fn namespace_name(name) { name.to_upper() }
+            fn type_name(name) { name + SUFFIX }
+            fn rpc_name(name) { name.replace("get_", "fetch_"); name }
+            fn field_name(name) { if name == "user_id" { "id" } else { name } }
+            "#,
+        )
+        .unwrap();
+        let view = model.view().with_transforms(script);
+        let root = view.api();
+        assert_eq!(names(&root), vec!["NS"]);
+        let ns = root.namespaces().next().unwrap();
+        assert_eq!(names(&ns), vec!["UserDto", "KindDto", "fetch_user"]);
+
+        let dto = ns.dtos().next().unwrap();
+        let field = dto.fields().next().unwrap();
+        assert_eq!(field.name(), "id");
+        let ty = field.ty();
+        let path = ty.inner().api().unwrap().path().iter().join(".");
+        assert_eq!(path, "NS.KindDto");
+        let rpc = ns.rpcs().next().unwrap();
+        assert_eq!(rpc.params().next().unwrap().name(), "id");
+    }
+
+    #[test]
+    fn filters() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct Visible {
+                a: u32,
+                #[internal]
+                b: u32,
+            }
+            #[internal]
+            struct Hidden {}
+            fn rpc(#[internal] x: u32, y: u32) {}
+            "#,
+        );
+        let model = exe.build();
+        let script = Script::new(
+            r#"
+            fn filter_dto(dto) { !dto.attributes.contains("internal") }
+            fn filter_field(field) { !field.attributes.contains("internal") }
+            "#,
+        )
+        .unwrap();
+        let view = model.view().with_transforms(script);
+        let root = view.api();
+        assert_eq!(names(&root), vec!["Visible", "rpc"]);
+        let dto = root.dtos().next().unwrap();
+        assert_eq!(
+            dto.fields().map(|f| f.name().to_string()).collect_vec(),
+            vec!["a"]
+        );
+        let rpc = root.rpcs().next().unwrap();
+        assert_eq!(
+            rpc.params().map(|p| p.name().to_string()).collect_vec(),
+            vec!["y"]
+        );
+    }
+
+    #[test]
+    fn errors() {
+        assert!(Script::new("fn broken(").is_err());
+        assert!(Script::new("throw \"at load\";").is_err());
+
+        // Runtime errors within functions leave names unchanged, and are recorded once each.
+        let mut exe = TestExecutor::new("struct Dto {}");
+        let model = exe.build();
+        let script = Script::new("fn type_name(name) { name.missing() }").unwrap();
+        let mut view = model.view().with_transforms(script);
+        assert_eq!(names(&view.api()), vec!["Dto"]);
+        assert_eq!(names(&view.api()), vec!["Dto"]);
+        let errors = view.xforms().errors().take();
+        assert_eq!(errors.len(), 1);
+        assert!(errors.has_errors());
+        assert!(view.xforms().errors().take().is_empty());
+    }
+}
@@ -1,3 +1,8 @@
+use std::borrow::Cow;
+
+use anyhow::{anyhow, Result};
+use itertools::Itertools;
+
 use crate::model;
 use crate::view::{Namespace, Transformer, Transforms};
 
@@ -5,7 +10,7 @@ use crate::view::{Namespace, Transformer, Transforms};
 #[derive(Debug, Clone)]
 pub struct SubView<'a> {
     namespace_id: model::EntityId,
-    namespace: &'a model::Namespace<'a>,
+    namespace: Cow<'a, model::Namespace<'a>>,
     xforms: Transforms,
 }
 
@@ -17,17 +22,70 @@ impl<'a> SubView<'a> {
     ) -> Self {
         Self {
             namespace_id: namespace_id.to_qualified_namespaces(),
-            namespace,
+            namespace: Cow::Borrowed(namespace),
             xforms,
         }
     }
 
+    /// A view of only the namespaces `ids` within `api` and everything in them, under a virtual
+    /// root namespace. The namespaces leading to each of `ids` are kept, but none of their other
+    /// children, so that ids of entities in the view are the same as in `api`.
+    ///
+    /// Fails if any of `ids` is not a namespace within `api`.
+    pub fn at(
+        api: &'a model::Namespace<'a>,
+        ids: &[model::EntityId],
+        xforms: Transforms,
+    ) -> Result<Self> {
+        Ok(Self {
+            namespace_id: model::EntityId::default(),
+            namespace: Cow::Owned(slice(api, ids)?),
+            xforms,
+        })
+    }
+
     pub fn root_id(&self) -> &model::EntityId {
         &self.namespace_id
     }
 
     pub fn namespace<'v>(&'v self) -> Namespace<'v, 'a> {
-        Namespace::new(self.namespace, &self.xforms)
+        Namespace::new(&self.namespace, &self.xforms).with_id(self.namespace_id.clone())
+    }
+}
+
+fn slice<'a>(api: &model::Namespace<'a>, ids: &[model::EntityId]) -> Result<model::Namespace<'a>> {
+    let mut root = empty_copy(api);
+    for id in ids {
+        let names = id.component_names().collect_vec();
+        let (name, path) = match names.split_last() {
+            None => return Ok(api.clone()),
+            Some(split) => split,
+        };
+        let namespace = api
+            .find_namespace(id)
+            .ok_or_else(|| anyhow!("could not find namespace with id '{}'", id))?;
+        let mut source = api;
+        let mut sliced = &mut root;
+        for name in path {
+            source = source.namespace(name).expect("found above");
+            if sliced.namespace(name).is_none() {
+                sliced.add_namespace(empty_copy(source));
+            }
+            sliced = sliced.namespace_mut(name).expect("added above");
+        }
+        match sliced.namespace_mut(name) {
+            Some(existing) => *existing = namespace.clone(),
+            None => sliced.add_namespace(namespace.clone()),
+        }
+    }
+    Ok(root)
+}
+
+fn empty_copy<'a>(namespace: &model::Namespace<'a>) -> model::Namespace<'a> {
+    model::Namespace {
+        name: namespace.name.clone(),
+        children: vec![],
+        attributes: namespace.attributes.clone(),
     }
 }
 
@@ -42,7 +100,7 @@ mod tests {
     use crate::model;
     use itertools::Itertools;
 
-    use crate::test_util::executor::TestExecutor;
+    use crate::testing::TestExecutor;
     use crate::view::tests::TestFilter;
     use crate::view::{SubView, Transformer, Transforms};
 
@@ -61,7 +119,7 @@ mod tests {
         let model = exe.model();
         let sub_view = SubView::new(
             model::EntityId::default(),
-            &model.api(),
+            model.api(),
             Transforms::default(),
         )
         .with_namespace_transform(TestFilter {});
@@ -72,16 +130,118 @@ mod tests {
         assert_eq!(namespace.rpcs().count(), 1);
 
         assert_eq!(
-            namespace.namespaces().collect_vec().get(0).unwrap().name(),
+            namespace.namespaces().collect_vec().first().unwrap().name(),
             "visible"
         );
         assert_eq!(
-            namespace.dtos().collect_vec().get(0).unwrap().name(),
+            namespace.dtos().collect_vec().first().unwrap().name(),
             "visible"
         );
         assert_eq!(
-            namespace.rpcs().collect_vec().get(0).unwrap().name(),
+            namespace.rpcs().collect_vec().first().unwrap().name(),
             "visible"
         );
     }
+
+    mod at {
+        use itertools::Itertools;
+
+        use crate::model::EntityId;
+        use crate::testing::TestExecutor;
+        use crate::view::tests::TestFilter;
+        use crate::view::{Namespace, Transformer};
+
+        const API: &str = r#"
+            struct Root {}
+            mod service {
+                struct Service {}
+                mod user {
+                    struct User {}
+                    struct hidden {}
+                }
+                mod social {
+                    mod feed {
+                        struct Post {}
+                    }
+                }
+                mod billing {
+                    struct Invoice {}
+                }
+            }
+        "#;
+
+        fn ids(ids: &[&str]) -> Vec<EntityId> {
+            ids.iter()
+                .map(|id| EntityId::try_from(*id).unwrap())
+                .collect_vec()
+        }
+
+        fn names(namespace: &Namespace) -> Vec<String> {
+            namespace
+                .children()
+                .map(|child| child.name().to_string())
+                .collect_vec()
+        }
+
+        #[test]
+        fn multiple_roots() {
+            let mut exe = TestExecutor::new(API);
+            let model = exe.build();
+            let view = model.view().with_namespace_transform(TestFilter {});
+            let sub_view = view
+                .subview_at(&ids(&["service.user", "service.social"]))
+                .unwrap();
+            let root = sub_view.namespace();
+            assert_eq!(names(&root), vec!["service"]);
+
+            let service = root.namespaces().next().unwrap();
+            assert_eq!(names(&service), vec!["user", "social"]);
+
+            let user = root
+                .find_namespace(&EntityId::try_from("service.user").unwrap())
+                .unwrap();
+            assert_eq!(names(&user), vec!["User"]);
+
+            let post = root.find_dto(&EntityId::try_from("service.social.feed.d:Post").unwrap());
+            assert!(post.is_some());
+        }
+
+        #[test]
+        fn overlapping_roots() {
+            let mut exe = TestExecutor::new(API);
+            let model = exe.build();
+            let view = model.view();
+            let sub_view = view
+                .subview_at(&ids(&["service.social.feed", "service.social"]))
+                .unwrap();
+            let social = sub_view
+                .namespace()
+                .find_namespace(&EntityId::try_from("service.social").unwrap())
+                .map(|ns| names(&ns))
+                .unwrap();
+            assert_eq!(social, vec!["feed"]);
+        }
+
+        #[test]
+        fn root() {
+            let mut exe = TestExecutor::new(API);
+            let model = exe.build();
+            let view = model.view();
+            let sub_view = view
+                .subview_at(&[
+                    EntityId::default(),
+                    EntityId::try_from("service.user").unwrap(),
+                ])
+                .unwrap();
+            assert_eq!(names(&sub_view.namespace()), vec!["Root", "service"]);
+        }
+
+        #[test]
+        fn unknown_namespace() {
+            let mut exe = TestExecutor::new(API);
+            let model = exe.build();
+            let view = model.view();
+            assert!(view.subview_at(&ids(&["service.unknown"])).is_err());
+        }
+    }
 }
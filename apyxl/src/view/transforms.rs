@@ -0,0 +1,738 @@
+//! Ready-made transforms for common needs. Most of them affect several kinds of entities at once,
+//! so they're added to a [crate::view::Transformer] with
+//! [crate::view::Transformer::with_transforms].
+//!
+//! See also [crate::view::EntityIdFilter] and [crate::view::IdentifierNormalizer].
+
+use std::borrow::Cow;
+use std::sync::{Arc, Mutex};
+
+use itertools::Itertools;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::model;
+use crate::model::attribute;
+use crate::view::{
+    DtoTransform, EntityIdTransform, EnumTransform, EnumValueTransform, FieldTransform,
+    NamespaceTransform, RpcTransform, Transforms, TypeTransform,
+};
+use crate::{Diagnostic, Diagnostics};
+
+/// A set of transforms that are added together, see
+/// [crate::view::Transformer::with_transforms].
+pub trait TransformSet {
+    fn add_to(self, xforms: &mut Transforms);
+}
+
+/// Errors from transforms that can't return them, e.g. a [crate::view::Script] function that
+/// throws. Shared by every clone of the [Transforms] they're added to, so that the
+/// [crate::Executor] can fail the generator that used them.
+#[derive(Debug, Default, Clone)]
+pub struct TransformErrors {
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl TransformErrors {
+    pub fn push(&self, error: String) {
+        self.errors.lock().unwrap().push(error);
+    }
+
+    /// Takes the errors pushed so far as error [Diagnostic]s, without duplicates since transforms
+    /// are applied every time an entity is read.
+    pub fn take(&self) -> Diagnostics {
+        std::mem::take(&mut *self.errors.lock().unwrap())
+            .into_iter()
+            .unique()
+            .map(Diagnostic::error)
+            .collect()
+    }
+}
+
+/// A naming convention for identifiers. Words are split at `_`, `-`, whitespace and changes in
+/// case, e.g. `HTTPServerV2` and `http_server_v2` are both the words `http`, `server` and `v2`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Case {
+    /// `snake_case`
+    Snake,
+    /// `SCREAMING_SNAKE_CASE`
+    ScreamingSnake,
+    /// `camelCase`
+    Camel,
+    /// `PascalCase`
+    Pascal,
+}
+
+impl Case {
+    /// `ident` written in this case.
+    pub fn apply(&self, ident: &str) -> String {
+        let words = words(ident);
+        match self {
+            Case::Snake => words
+                .iter()
+                .map(|word| word.to_lowercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            Case::ScreamingSnake => words
+                .iter()
+                .map(|word| word.to_uppercase())
+                .collect::<Vec<_>>()
+                .join("_"),
+            Case::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(i, word)| {
+                    if i == 0 {
+                        word.to_lowercase()
+                    } else {
+                        capitalize(word)
+                    }
+                })
+                .collect(),
+            Case::Pascal => words.iter().map(|word| capitalize(word)).collect(),
+        }
+    }
+}
+
+fn words(ident: &str) -> Vec<&str> {
+    let mut words = vec![];
+    for part in ident.split(|c: char| c == '_' || c == '-' || c.is_whitespace()) {
+        let chars = part.char_indices().collect::<Vec<_>>();
+        let mut start = 0;
+        for i in 1..chars.len() {
+            let (prev, curr) = (chars[i - 1].1, chars[i].1);
+            let next_is_lower = chars.get(i + 1).is_some_and(|(_, c)| c.is_lowercase());
+            let boundary = (prev.is_lowercase() && curr.is_uppercase())
+                || (prev.is_uppercase() && curr.is_uppercase() && next_is_lower);
+            if boundary {
+                words.push(&part[start..chars[i].0]);
+                start = chars[i].0;
+            }
+        }
+        if start < part.len() {
+            words.push(&part[start..]);
+        }
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        None => String::new(),
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+    }
+}
+
+/// Converts names to a [Case] per kind of entity. Kinds without a case are left as is.
+///
+/// Type names (dtos and enums) are converted in [crate::view::EntityId]s as well, along with their
+/// namespaces, so that references to them stay consistent.
+#[derive(Debug, Clone, Default)]
+pub struct CaseConversion {
+    namespaces: Option<Case>,
+    types: Option<Case>,
+    rpcs: Option<Case>,
+    fields: Option<Case>,
+    enum_values: Option<Case>,
+}
+
+impl CaseConversion {
+    pub fn namespaces(mut self, case: Case) -> Self {
+        self.namespaces = Some(case);
+        self
+    }
+
+    /// Dtos and enums.
+    pub fn types(mut self, case: Case) -> Self {
+        self.types = Some(case);
+        self
+    }
+
+    pub fn rpcs(mut self, case: Case) -> Self {
+        self.rpcs = Some(case);
+        self
+    }
+
+    /// Dto fields and rpc params.
+    pub fn fields(mut self, case: Case) -> Self {
+        self.fields = Some(case);
+        self
+    }
+
+    pub fn enum_values(mut self, case: Case) -> Self {
+        self.enum_values = Some(case);
+        self
+    }
+}
+
+fn convert(case: Option<Case>, value: &mut Cow<str>) {
+    if let Some(case) = case {
+        *value = Cow::Owned(case.apply(value))
+    }
+}
+
+impl NamespaceTransform for CaseConversion {
+    fn name(&self, value: &mut Cow<str>) {
+        convert(self.namespaces, value)
+    }
+}
+
+impl DtoTransform for CaseConversion {
+    fn name(&self, value: &mut Cow<str>) {
+        convert(self.types, value)
+    }
+}
+
+impl EnumTransform for CaseConversion {
+    fn name(&self, value: &mut Cow<str>) {
+        convert(self.types, value)
+    }
+}
+
+impl RpcTransform for CaseConversion {
+    fn name(&self, value: &mut Cow<str>) {
+        convert(self.rpcs, value)
+    }
+}
+
+impl FieldTransform for CaseConversion {
+    fn name(&self, value: &mut Cow<str>) {
+        convert(self.fields, value)
+    }
+}
+
+impl EnumValueTransform for CaseConversion {
+    fn name(&self, value: &mut Cow<str>) {
+        convert(self.enum_values, value)
+    }
+}
+
+impl EntityIdTransform for CaseConversion {
+    fn path(&self, value: &mut Vec<Cow<str>>) {
+        let len = value.len();
+        for (i, component) in value.iter_mut().enumerate() {
+            convert(
+                if i + 1 == len {
+                    self.types
+                } else {
+                    self.namespaces
+                },
+                component,
+            )
+        }
+    }
+}
+
+impl TransformSet for CaseConversion {
+    fn add_to(self, xforms: &mut Transforms) {
+        xforms.namespace.push(Box::new(self.clone()));
+        xforms.dto.push(Box::new(self.clone()));
+        xforms.dto_field.push(Box::new(self.clone()));
+        xforms.rpc.push(Box::new(self.clone()));
+        xforms.rpc_param.push(Box::new(self.clone()));
+        xforms.en.push(Box::new(self.clone()));
+        xforms.en_value.push(Box::new(self.clone()));
+        xforms.entity_id.push(Box::new(self));
+    }
+}
+
+/// Strips prefixes and suffixes from type names (dtos and enums), and from the references to
+/// them, e.g. `IUser` or `UserDto` becomes `User`. The first matching prefix and first matching
+/// suffix are stripped, unless nothing would be left of the name.
+#[derive(Debug, Clone, Default)]
+pub struct StripAffixes {
+    prefixes: Vec<String>,
+    suffixes: Vec<String>,
+}
+
+impl StripAffixes {
+    pub fn prefix(mut self, prefix: impl ToString) -> Self {
+        self.prefixes.push(prefix.to_string());
+        self
+    }
+
+    pub fn suffix(mut self, suffix: impl ToString) -> Self {
+        self.suffixes.push(suffix.to_string());
+        self
+    }
+
+    fn strip(&self, value: &mut Cow<str>) {
+        let mut name: &str = value;
+        if let Some(stripped) = self
+            .prefixes
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix.as_str()))
+            .filter(|stripped| !stripped.is_empty())
+        {
+            name = stripped;
+        }
+        if let Some(stripped) = self
+            .suffixes
+            .iter()
+            .find_map(|suffix| name.strip_suffix(suffix.as_str()))
+            .filter(|stripped| !stripped.is_empty())
+        {
+            name = stripped;
+        }
+        if name.len() != value.len() {
+            *value = Cow::Owned(name.to_string())
+        }
+    }
+}
+
+impl DtoTransform for StripAffixes {
+    fn name(&self, value: &mut Cow<str>) {
+        self.strip(value)
+    }
+}
+
+impl EnumTransform for StripAffixes {
+    fn name(&self, value: &mut Cow<str>) {
+        self.strip(value)
+    }
+}
+
+impl EntityIdTransform for StripAffixes {
+    fn path(&self, value: &mut Vec<Cow<str>>) {
+        if let Some(name) = value.last_mut() {
+            self.strip(name)
+        }
+    }
+}
+
+impl TransformSet for StripAffixes {
+    fn add_to(self, xforms: &mut Transforms) {
+        xforms.dto.push(Box::new(self.clone()));
+        xforms.en.push(Box::new(self.clone()));
+        xforms.entity_id.push(Box::new(self));
+    }
+}
+
+/// Renames dto fields and rpc params, e.g. `id` to `identifier`.
+#[derive(Debug, Clone, Default)]
+pub struct RenameFields {
+    renames: Vec<(String, String)>,
+}
+
+impl RenameFields {
+    pub fn rename(mut self, from: impl ToString, to: impl ToString) -> Self {
+        self.renames.push((from.to_string(), to.to_string()));
+        self
+    }
+}
+
+impl FieldTransform for RenameFields {
+    fn name(&self, value: &mut Cow<str>) {
+        if let Some((_, to)) = self.renames.iter().find(|(from, _)| from == value) {
+            *value = Cow::Owned(to.clone())
+        }
+    }
+}
+
+impl TransformSet for RenameFields {
+    fn add_to(self, xforms: &mut Transforms) {
+        xforms.dto_field.push(Box::new(self.clone()));
+        xforms.rpc_param.push(Box::new(self));
+    }
+}
+
+/// Replaces types with other types, e.g. [model::Type::U128] with [model::Type::String] for JSON
+/// targets, wherever they appear. Add with [crate::view::Transformer::with_type_transform].
+#[derive(Debug, Clone, Default)]
+pub struct TypeMapping {
+    mappings: Vec<(model::Type, model::Type)>,
+}
+
+impl TypeMapping {
+    pub fn map(mut self, from: model::Type, to: model::Type) -> Self {
+        self.mappings.push((from, to));
+        self
+    }
+}
+
+impl TypeTransform for TypeMapping {
+    fn ty(&self, value: &mut Cow<model::Type>) {
+        if let Some((_, to)) = self
+            .mappings
+            .iter()
+            .find(|(from, _)| from == value.as_ref())
+        {
+            *value = Cow::Owned(to.clone())
+        }
+    }
+}
+
+/// Includes or excludes entities by their user attributes, e.g. `#[api]` or `#[internal]` in Rust.
+///
+/// If any attributes are [AttributeFilter::include]d, only the dtos, rpcs and enums directly
+/// within namespaces that have at least one of them are included, and namespaces left without any
+/// children are pruned. Anything with an [AttributeFilter::exclude]d attribute is excluded,
+/// including namespaces, fields, params and enum values.
+#[derive(Debug, Clone, Default)]
+pub struct AttributeFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl AttributeFilter {
+    pub fn include(mut self, name: impl ToString) -> Self {
+        self.include.push(name.to_string());
+        self
+    }
+
+    pub fn exclude(mut self, name: impl ToString) -> Self {
+        self.exclude.push(name.to_string());
+        self
+    }
+
+    fn filter(&self, attributes: &model::Attributes) -> bool {
+        (self.include.is_empty() || has_any(attributes, &self.include))
+            && self.filter_excluded(attributes)
+    }
+
+    fn filter_excluded(&self, attributes: &model::Attributes) -> bool {
+        !has_any(attributes, &self.exclude)
+    }
+}
+
+fn has_any(attributes: &model::Attributes, names: &[String]) -> bool {
+    attributes
+        .user
+        .iter()
+        .any(|attribute::User { name, .. }| names.iter().any(|n| n == name))
+}
+
+impl NamespaceTransform for AttributeFilter {
+    fn filter_namespace(&self, namespace: &model::Namespace) -> bool {
+        self.filter_excluded(&namespace.attributes)
+    }
+
+    fn filter_dto(&self, dto: &model::Dto) -> bool {
+        self.filter(&dto.attributes)
+    }
+
+    fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
+        self.filter(&rpc.attributes)
+    }
+
+    fn filter_enum(&self, en: &model::Enum) -> bool {
+        self.filter(&en.attributes)
+    }
+
+    fn prune_empty_namespaces(&self) -> bool {
+        !self.include.is_empty()
+    }
+}
+
+impl DtoTransform for AttributeFilter {
+    fn filter_field(&self, field: &model::Field) -> bool {
+        self.filter_excluded(&field.attributes)
+    }
+
+    fn filter_rpc(&self, rpc: &model::Rpc) -> bool {
+        self.filter_excluded(&rpc.attributes)
+    }
+}
+
+impl RpcTransform for AttributeFilter {
+    fn filter_param(&self, param: &model::Field) -> bool {
+        self.filter_excluded(&param.attributes)
+    }
+}
+
+impl EnumTransform for AttributeFilter {
+    fn filter_value(&self, value: &model::EnumValue) -> bool {
+        self.filter_excluded(&value.attributes)
+    }
+}
+
+impl TransformSet for AttributeFilter {
+    fn add_to(self, xforms: &mut Transforms) {
+        xforms.namespace.push(Box::new(self.clone()));
+        xforms.dto.push(Box::new(self.clone()));
+        xforms.rpc.push(Box::new(self.clone()));
+        xforms.en.push(Box::new(self));
+    }
+}
+
+/// Flattens nested namespaces, so that all dtos, rpcs and enums appear directly within the viewed
+/// namespace, e.g. the API root. References to types keep only the type's name.
+///
+/// Names are not deduplicated, so types with the same name in different namespaces will clash.
+#[derive(Debug, Clone, Default)]
+pub struct FlattenNamespaces {}
+
+impl NamespaceTransform for FlattenNamespaces {
+    fn flatten_namespaces(&self) -> bool {
+        true
+    }
+}
+
+impl EntityIdTransform for FlattenNamespaces {
+    fn path(&self, value: &mut Vec<Cow<str>>) {
+        if value.len() > 1 {
+            value.drain(..value.len() - 1);
+        }
+    }
+}
+
+impl TransformSet for FlattenNamespaces {
+    fn add_to(self, xforms: &mut Transforms) {
+        xforms.namespace.push(Box::new(self.clone()));
+        xforms.entity_id.push(Box::new(self));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use crate::model;
+    use crate::testing::TestExecutor;
+    use crate::view::transforms::{
+        AttributeFilter, Case, CaseConversion, FlattenNamespaces, RenameFields, StripAffixes,
+        TypeMapping,
+    };
+    use crate::view::{InnerType, Namespace, Transformer, Type};
+
+    fn names(namespace: &Namespace) -> Vec<String> {
+        namespace
+            .children()
+            .map(|child| child.name().to_string())
+            .collect_vec()
+    }
+
+    fn api_path(ty: Type) -> Vec<String> {
+        let inner = ty.inner();
+        let id = inner.api().expect("expected an api type");
+        id.path().iter().map(|c| c.to_string()).collect_vec()
+    }
+
+    #[test]
+    fn case_apply() {
+        for (ident, snake, screaming, camel, pascal) in [
+            ("user_id", "user_id", "USER_ID", "userId", "UserId"),
+            ("UserId", "user_id", "USER_ID", "userId", "UserId"),
+            ("userId", "user_id", "USER_ID", "userId", "UserId"),
+            (
+                "HTTPServer",
+                "http_server",
+                "HTTP_SERVER",
+                "httpServer",
+                "HttpServer",
+            ),
+            ("MAX_SIZE", "max_size", "MAX_SIZE", "maxSize", "MaxSize"),
+            (
+                "kebab-case",
+                "kebab_case",
+                "KEBAB_CASE",
+                "kebabCase",
+                "KebabCase",
+            ),
+            ("v2", "v2", "V2", "v2", "V2"),
+        ] {
+            assert_eq!(Case::Snake.apply(ident), snake, "{ident}");
+            assert_eq!(Case::ScreamingSnake.apply(ident), screaming, "{ident}");
+            assert_eq!(Case::Camel.apply(ident), camel, "{ident}");
+            assert_eq!(Case::Pascal.apply(ident), pascal, "{ident}");
+        }
+    }
+
+    #[test]
+    fn case_conversion() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod my_ns {
+                struct user_info {
+                    user_id: UserKind,
+                }
+                enum UserKind {
+                    Admin = 0,
+                }
+                fn get_user(user_id: u32) {}
+            }
+            "#,
+        );
+        let model = exe.build();
+        let view = model.view().with_transforms(
+            CaseConversion::default()
+                .namespaces(Case::Pascal)
+                .types(Case::Pascal)
+                .rpcs(Case::Camel)
+                .fields(Case::Camel)
+                .enum_values(Case::ScreamingSnake),
+        );
+        let root = view.api();
+        assert_eq!(names(&root), vec!["MyNs"]);
+        let ns = root.namespaces().next().unwrap();
+        assert_eq!(names(&ns), vec!["UserInfo", "UserKind", "getUser"]);
+
+        let dto = ns.dtos().next().unwrap();
+        let field = dto.fields().next().unwrap();
+        assert_eq!(field.name(), "userId");
+        assert_eq!(api_path(field.ty()), vec!["MyNs", "UserKind"]);
+
+        let rpc = ns.rpcs().next().unwrap();
+        assert_eq!(rpc.params().next().unwrap().name(), "userId");
+
+        let en = ns.enums().next().unwrap();
+        assert_eq!(en.values().next().unwrap().name(), "ADMIN");
+    }
+
+    #[test]
+    fn strip_affixes() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct IUserDto {
+                kind: KindDto,
+            }
+            enum KindDto {}
+            struct Dto {}
+            "#,
+        );
+        let model = exe.build();
+        let view = model
+            .view()
+            .with_transforms(StripAffixes::default().prefix("I").suffix("Dto"));
+        let root = view.api();
+        assert_eq!(names(&root), vec!["User", "Kind", "Dto"]);
+        let dto = root.dtos().next().unwrap();
+        assert_eq!(api_path(dto.fields().next().unwrap().ty()), vec!["Kind"]);
+    }
+
+    #[test]
+    fn rename_fields() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct Dto {
+                id: u32,
+                other: u32,
+            }
+            fn rpc(id: u32) {}
+            "#,
+        );
+        let model = exe.build();
+        let view = model
+            .view()
+            .with_transforms(RenameFields::default().rename("id", "identifier"));
+        let root = view.api();
+        let dto = root.dtos().next().unwrap();
+        assert_eq!(
+            dto.fields().map(|f| f.name().to_string()).collect_vec(),
+            vec!["identifier", "other"]
+        );
+        let rpc = root.rpcs().next().unwrap();
+        assert_eq!(rpc.params().next().unwrap().name(), "identifier");
+    }
+
+    #[test]
+    fn type_mapping() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct Id {}
+            struct Dto {
+                id: Id,
+                big: Vec<u128>,
+            }
+            "#,
+        );
+        let model = exe.build();
+        let view = model.view().with_type_transform(
+            TypeMapping::default()
+                .map(model::Type::new_api("d:Id").unwrap(), model::Type::U64)
+                .map(model::Type::U128, model::Type::String),
+        );
+        let root = view.api();
+        let dto = root.dtos().nth(1).unwrap();
+        let fields = dto.fields().collect_vec();
+        assert!(matches!(fields[0].ty().inner(), InnerType::U64));
+        assert!(matches!(
+            fields[1].ty().inner(),
+            InnerType::Array(ty) if matches!(*ty, InnerType::String)
+        ));
+    }
+
+    #[test]
+    fn attribute_filter() {
+        let mut exe = TestExecutor::new(
+            r#"
+            mod a {
+                #[api]
+                struct Visible {
+                    x: u32,
+                    #[internal]
+                    hidden: u32,
+                }
+                struct NotTagged {}
+                #[api]
+                enum E {
+                    A = 0,
+                    #[internal]
+                    B = 1,
+                }
+            }
+            mod b {
+                struct NotTagged {}
+            }
+            #[api]
+            #[internal]
+            fn rpc() {}
+            "#,
+        );
+        let model = exe.build();
+        let view = model.view().with_transforms(
+            AttributeFilter::default()
+                .include("api")
+                .exclude("internal"),
+        );
+        let root = view.api();
+        assert_eq!(names(&root), vec!["a"]);
+        let ns = root.namespaces().next().unwrap();
+        assert_eq!(names(&ns), vec!["Visible", "E"]);
+        let dto = ns.dtos().next().unwrap();
+        assert_eq!(
+            dto.fields().map(|f| f.name().to_string()).collect_vec(),
+            vec!["x"]
+        );
+        let en = ns.enums().next().unwrap();
+        assert_eq!(
+            en.values().map(|v| v.name().to_string()).collect_vec(),
+            vec!["A"]
+        );
+    }
+
+    #[test]
+    fn flatten_namespaces() {
+        let mut exe = TestExecutor::new(
+            r#"
+            struct Root {}
+            mod a {
+                struct A {
+                    b: b::B,
+                }
+                mod b {
+                    struct B {}
+                    fn rpc() {}
+                }
+            }
+            "#,
+        );
+        let model = exe.build();
+        let view = model.view().with_transforms(FlattenNamespaces::default());
+        let root = view.api();
+        assert_eq!(names(&root), vec!["Root", "A", "B", "rpc"]);
+        assert_eq!(root.namespaces().count(), 0);
+        assert_eq!(
+            root.dtos().map(|dto| dto.name().to_string()).collect_vec(),
+            vec!["Root", "A", "B"]
+        );
+        assert_eq!(root.rpcs().count(), 1);
+        let dto = root.dtos().nth(1).unwrap();
+        assert_eq!(api_path(dto.fields().next().unwrap().ty()), vec!["B"]);
+    }
+}
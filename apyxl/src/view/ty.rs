@@ -1,26 +1,60 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
 use std::fmt::Debug;
 
+use dyn_clone::DynClone;
+
+use crate::config::{substitute, template_captures, UserType};
 use crate::model;
 use crate::view::{EntityId, EntityIdTransform};
 
 pub type InnerType<'v, 'a> = model::BaseType<EntityId<'v>, &'a str>;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub struct Type<'v> {
-    target: &'v model::Type,
+    target: Cow<'v, model::Type>,
     xforms: &'v Vec<Box<dyn EntityIdTransform>>,
 }
 
+/// Remaps types wholesale, e.g. [model::Type::U128] to [model::Type::String] for targets that
+/// can't represent it, or a [model::Type::User] type to a platform type.
+pub trait TypeTransform: Debug + DynClone {
+    /// Called with every type, including the types nested within arrays, maps and optionals,
+    /// innermost first. [UserTypeTargets] are applied afterwards.
+    fn ty(&self, _: &mut Cow<model::Type>) {}
+}
+
+dyn_clone::clone_trait_object!(TypeTransform);
+
+/// Maps the names of [InnerType::User] types to the type a specific [crate::Generator] should
+/// emit instead, from the `targets` of each [UserType]. Names without a target are unchanged.
+///
+/// See [crate::view::Transformer::with_user_type_targets].
+#[derive(Debug, Default, Clone)]
+pub struct UserTypeTargets {
+    targets: BTreeMap<String, String>,
+    /// Name and target templates of `glob` [UserType]s, whose names aren't known up front.
+    globs: Vec<(String, String)>,
+}
+
 impl<'v> Type<'v> {
-    pub fn new(target: &'v model::Type, xforms: &'v Vec<Box<dyn EntityIdTransform>>) -> Self {
-        Self { target, xforms }
+    pub fn new(
+        target: &'v model::Type,
+        xforms: &'v Vec<Box<dyn EntityIdTransform>>,
+        ty_xforms: &'v Vec<Box<dyn TypeTransform>>,
+        user_ty_targets: &'v UserTypeTargets,
+    ) -> Self {
+        Self {
+            target: transform(target, ty_xforms, user_ty_targets),
+            xforms,
+        }
     }
 
-    pub fn inner(&self) -> InnerType {
-        self.model_to_view_ty(self.target)
+    pub fn inner(&self) -> InnerType<'_, '_> {
+        self.model_to_view_ty(&self.target)
     }
 
-    fn model_to_view_ty<'a>(&'a self, ty: &'a model::Type) -> InnerType {
+    fn model_to_view_ty<'a>(&'a self, ty: &'a model::Type) -> InnerType<'a, 'a> {
         match ty {
             model::Type::Bool => InnerType::Bool,
             model::Type::U8 => InnerType::U8,
@@ -41,7 +75,11 @@ impl<'v> Type<'v> {
             model::Type::String => InnerType::String,
             model::Type::Bytes => InnerType::Bytes,
             model::Type::User(name) => InnerType::User(name),
-            model::Type::Api(id) => InnerType::Api(EntityId::new(id, self.xforms)),
+            model::Type::Api(id, args) => InnerType::Api(
+                EntityId::new(id, self.xforms),
+                args.iter().map(|arg| self.model_to_view_ty(arg)).collect(),
+            ),
+            model::Type::Generic(name) => InnerType::Generic(name),
             model::Type::Array(ty) => InnerType::Array(Box::new(self.model_to_view_ty(ty))),
             model::Type::Map { key, value } => InnerType::Map {
                 key: Box::new(self.model_to_view_ty(key)),
@@ -52,12 +90,163 @@ impl<'v> Type<'v> {
     }
 }
 
+fn transform<'v>(
+    ty: &'v model::Type,
+    xforms: &Vec<Box<dyn TypeTransform>>,
+    user_ty_targets: &UserTypeTargets,
+) -> Cow<'v, model::Type> {
+    if xforms.is_empty() && user_ty_targets.is_empty() {
+        return Cow::Borrowed(ty);
+    }
+    let nested = match ty {
+        model::Type::Array(inner) => match transform(inner, xforms, user_ty_targets) {
+            Cow::Owned(inner) => Some(model::Type::Array(Box::new(inner))),
+            Cow::Borrowed(_) => None,
+        },
+        model::Type::Optional(inner) => match transform(inner, xforms, user_ty_targets) {
+            Cow::Owned(inner) => Some(model::Type::Optional(Box::new(inner))),
+            Cow::Borrowed(_) => None,
+        },
+        model::Type::Map { key, value } => {
+            let key = transform(key, xforms, user_ty_targets);
+            let value = transform(value, xforms, user_ty_targets);
+            match (key, value) {
+                (Cow::Borrowed(_), Cow::Borrowed(_)) => None,
+                (key, value) => Some(model::Type::Map {
+                    key: Box::new(key.into_owned()),
+                    value: Box::new(value.into_owned()),
+                }),
+            }
+        }
+        model::Type::Api(id, args) => {
+            let args = args
+                .iter()
+                .map(|arg| transform(arg, xforms, user_ty_targets))
+                .collect::<Vec<_>>();
+            if args.iter().all(|arg| matches!(arg, Cow::Borrowed(_))) {
+                None
+            } else {
+                let args = args.into_iter().map(Cow::into_owned).collect();
+                Some(model::Type::Api(id.clone(), args))
+            }
+        }
+        _ => None,
+    };
+    let mut ty = nested.map_or(Cow::Borrowed(ty), Cow::Owned);
+    for x in xforms {
+        x.ty(&mut ty);
+    }
+    if let model::Type::User(name) = ty.as_ref() {
+        if let Some(target) = user_ty_targets.get(name) {
+            ty = Cow::Owned(model::Type::User(target.into_owned()));
+        }
+    }
+    ty
+}
+
+impl UserTypeTargets {
+    /// Targets of the generator registered as `generator`, e.g. from
+    /// [model::Metadata::user_types].
+    pub fn new(user_types: &[UserType], generator: &str) -> Self {
+        let mut targets = Self::default();
+        for ty in user_types {
+            if let Some(target) = ty.target(generator) {
+                if ty.glob {
+                    targets.globs.push((ty.name.clone(), target.to_string()));
+                } else {
+                    targets.targets.insert(ty.name.clone(), target.to_string());
+                }
+            }
+        }
+        targets
+    }
+
+    /// The target for the user type `name`. For `glob` user types, the placeholders of the target
+    /// are filled in from `name`.
+    pub fn get(&self, name: &str) -> Option<Cow<'_, str>> {
+        if let Some(target) = self.targets.get(name) {
+            return Some(Cow::Borrowed(target));
+        }
+        self.globs.iter().find_map(|(name_template, target)| {
+            let captures = template_captures(name_template, name)?;
+            Some(Cow::Owned(substitute(target, &captures)))
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty() && self.globs.is_empty()
+    }
+}
+
 impl InnerType<'_, '_> {
-    pub fn api(&self) -> Option<&EntityId> {
-        if let InnerType::Api(id) = self {
+    pub fn api(&self) -> Option<&EntityId<'_>> {
+        if let InnerType::Api(id, _) = self {
             Some(id)
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use crate::model;
+    use crate::model::EntityId;
+    use crate::testing::TestExecutor;
+    use crate::view::{InnerType, Transformer, TypeTransform};
+
+    #[derive(Debug, Clone)]
+    struct U128ToString {}
+    impl TypeTransform for U128ToString {
+        fn ty(&self, ty: &mut Cow<model::Type>) {
+            if **ty == model::Type::U128 {
+                *ty = Cow::Owned(model::Type::String)
+            }
+        }
+    }
+
+    #[test]
+    fn type_transform() {
+        let mut exe = TestExecutor::new(
+            r#"
+                struct dto {
+                    a: u128,
+                    b: Vec<Option<u128>>,
+                    c: u64,
+                }
+                fn rpc(param: u128) -> HashMap<u128, u128> {}
+            "#,
+        );
+        let model = exe.model();
+        let view = model.view().with_type_transform(U128ToString {});
+        let root = view.api();
+        let dto = root
+            .find_dto(&EntityId::try_from("d:dto").unwrap())
+            .unwrap();
+        let fields = dto.fields().collect::<Vec<_>>();
+        assert!(matches!(fields[0].ty().inner(), InnerType::String));
+        match fields[1].ty().inner() {
+            InnerType::Array(ty) => match *ty {
+                InnerType::Optional(ty) => assert!(matches!(*ty, InnerType::String)),
+                _ => panic!("expected optional"),
+            },
+            _ => panic!("expected array"),
+        }
+        assert!(matches!(fields[2].ty().inner(), InnerType::U64));
+
+        let rpc = root
+            .find_rpc(&EntityId::try_from("r:rpc").unwrap())
+            .unwrap();
+        let param = rpc.params().next().unwrap();
+        assert!(matches!(param.ty().inner(), InnerType::String));
+        match rpc.return_type().unwrap().inner() {
+            InnerType::Map { key, value } => {
+                assert!(matches!(*key, InnerType::String));
+                assert!(matches!(*value, InnerType::String));
+            }
+            _ => panic!("expected map"),
+        }
+    }
+}
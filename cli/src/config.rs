@@ -1,27 +1,78 @@
 use anyhow::{anyhow, Result};
-use clap::{Parser, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use itertools::Itertools;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
-#[command(name = "apyxl", author, version, about)]
+#[command(name = "apyxl", author, version, about, subcommand_negates_reqs = true)]
 pub struct Config {
-    /// Unix-style glob of files to be parsed as API source files.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to a pipeline file in TOML or YAML format that declares the input, parser, and each
+    /// generator with its outputs, or a directory containing an 'apyxl.toml', 'apyxl.yaml' or
+    /// 'apyxl.yml'. See [apyxl::Pipeline::from_config].
+    #[arg(long, conflicts_with_all = ["input", "exclude", "parser", "parser_config", "generator", "output", "baseline", "dead_types"])]
+    pub pipeline: Option<PathBuf>,
+
+    /// Unix-style glob of files to be parsed as API source files, or a directory to parse all
+    /// files within.
     ///
     /// If the glob is relative, it will be relative to the current working directory.
-    #[arg(short, long, value_name = "GLOB")]
-    pub input: String,
+    #[arg(short, long, value_name = "GLOB", required_unless_present = "pipeline")]
+    pub input: Option<String>,
+
+    /// Unix-style glob of files to leave out of the --input, e.g. `**/generated/**`. May be
+    /// given more than once. Relative to the current working directory.
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
 
     /// Name of the parser to use.
-    #[arg(short, long)]
-    pub parser: ParserName,
+    #[arg(short, long, required_unless_present = "pipeline")]
+    pub parser: Option<ParserName>,
 
-    /// Path to a [apyxl::parser::Config] in json format.
+    /// Path to a [apyxl::parser::Config] in json format, including the options of each parser and
+    /// generator, e.g. `{ "generator": { "rust": { "import_comments": false } } }`.
     #[arg(long)]
     pub parser_config: Option<PathBuf>,
 
+    /// Directory to cache the parsed API of each input file in, so that later runs only parse
+    /// files that changed.
+    #[arg(long, value_name = "DIR")]
+    pub parse_cache: Option<PathBuf>,
+
+    /// Skip unknown syntax, treat unresolved types as user types and ignore duplicate definitions,
+    /// reporting each as a warning, rather than failing.
+    #[arg(long)]
+    pub lenient: bool,
+
+    /// Path to a baseline API in json format, as written by the `json` generator. The run fails
+    /// without generating anything if the API has breaking changes from it. See
+    /// [apyxl::Baseline].
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Path to a file listing the ids of entities that are allowed to break from the --baseline,
+    /// one per line, e.g. `user.dto:User.field:id`.
+    #[arg(long, value_name = "FILE", requires = "baseline")]
+    pub baseline_allowlist: Option<PathBuf>,
+
+    /// Report dtos and enums that no rpc uses, directly or through other dtos, as warnings. See
+    /// [apyxl::DeadTypes].
+    #[arg(long)]
+    pub dead_types: bool,
+
+    /// Id of an entity to treat as used by --dead-types, along with everything within it, e.g.
+    /// `events` or `events.d:Event`. May be given more than once.
+    #[arg(long, value_name = "ID", requires = "dead_types")]
+    pub dead_type_root: Vec<String>,
+
+    /// Leave the --dead-types out of the generated output.
+    #[arg(long, requires = "dead_types")]
+    pub filter_dead_types: bool,
+
     /// Name of generators to use.
-    #[arg(short, long, required(true))]
+    #[arg(short, long, required_unless_present = "pipeline")]
     pub generator: Vec<GeneratorName>,
 
     /// All relative --outputs will be relative to this path. Defaults to working directory.
@@ -29,7 +80,8 @@ pub struct Config {
     pub output_root: PathBuf,
 
     /// Each argument should be a key=value pair where the key is a [GeneratorName] and the value
-    /// is path to an empty (or nonexistent) directory.
+    /// is path to an empty (or nonexistent) directory. If only one --generator is used, the key
+    /// may be omitted.
     ///
     /// See also --output_root to set the relative root directory.
     ///
@@ -38,51 +90,353 @@ pub struct Config {
     /// would result in a file structure like
     ///     ./root/dir/rrr (generated rust files)
     ///     ./root/dir/ccc (generated cpp files)
-    #[arg(short, long, required=true, value_parser=parse_output)]
+    #[arg(short, long, visible_alias = "out", required_unless_present = "pipeline", value_parser=parse_output)]
     pub output: Vec<Output>,
+
+    /// Run the full pipeline without writing any files. Instead, print each file that would be
+    /// written along with its size in bytes.
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print how long each phase (parse, build, generate and finish) took to stderr.
+    #[arg(long)]
+    pub timings: bool,
+
+    /// Print the number of input chunks and API entities, and how long each phase took, to stderr
+    /// once done. See [apyxl::Stats].
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Format of diagnostics and run summaries. 'json' prints a single line json object per run
+    /// to stdout, with 'success', 'diagnostics' and 'stats' fields, instead of human-readable
+    /// text on stderr.
+    #[arg(long, value_enum, default_value_t = MessageFormat::Human, conflicts_with = "dry_run")]
+    pub message_format: MessageFormat,
+
+    /// Keep running, and generate again whenever an input file changes. Combine with
+    /// --parse-cache and --incremental so that only changed files are parsed and rewritten.
+    #[arg(long, conflicts_with_all = ["pipeline", "dry_run"])]
+    pub watch: bool,
+
+    /// Generate in memory and compare against the files already in each --output directory
+    /// instead of writing them. Exits with an error listing every missing or changed file if the
+    /// generated code is out of date. With --prune, files that would be pruned are also reported.
+    #[arg(long, conflicts_with_all = ["pipeline", "dry_run", "watch", "marked_regions", "compress"])]
+    pub check: bool,
+
+    /// With --check, also print a unified diff of each file that would change, without writing
+    /// anything.
+    #[arg(long, requires = "check")]
+    pub diff: bool,
+
+    /// Allow generating into existing directories, only rewriting files whose content changed.
+    #[arg(long)]
+    pub incremental: bool,
+
+    /// Delete files from a previous --incremental run that were not generated again.
+    /// Implies --incremental.
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Write to a staging directory and swap it into place only once all generators succeed.
+    /// Replaces the entire contents of each output directory. Each directory is swapped separately,
+    /// so if one swap fails the others may already have been replaced.
+    #[arg(long, conflicts_with_all = ["incremental", "prune"])]
+    pub atomic: bool,
+
+    /// Generate into existing files, only replacing the lines between '<apyxl:begin>' and
+    /// '<apyxl:end>' marker comments so that hand-written code around them is kept.
+    #[arg(long, conflicts_with_all = ["incremental", "prune", "atomic"])]
+    pub marked_regions: bool,
+
+    /// Compress generated files. 'gzip' writes each file with a .gz extension, 'tar-gz' writes
+    /// a single archive to each --output path.
+    #[arg(long, conflicts_with_all = ["incremental", "prune", "atomic", "marked_regions"])]
+    pub compress: Option<Compression>,
+
+    /// Prepend a "@generated" banner comment to each generated file.
+    #[arg(long)]
+    pub banner: bool,
+
+    /// Line ending to use in generated files. Defaults to lf.
+    #[arg(long)]
+    pub line_ending: Option<LineEnding>,
+
+    /// Indentation to use in generated files: 'tabs' or a number of spaces.
+    #[arg(long, value_parser=parse_indent)]
+    pub indent: Option<apyxl::output::Indent>,
+
+    /// Each argument should be a key=value pair where the key is a [GeneratorName] and the value
+    /// is a template for the path of each generated file, relative to that generator's --output.
+    ///
+    /// Variables: {path} {dir} {name} {ext} {namespace_path} {package}
+    ///
+    /// Example:
+    ///     --path-template rust={namespace_path}/generated.rs
+    #[arg(long, value_parser=parse_path_template)]
+    pub path_template: Vec<PathTemplate>,
+
+    /// Each argument should be a key=value pair where the key is a [GeneratorName] and the value
+    /// is a command that each file generated by that generator is piped through before it's
+    /// written. Formatters for the same generator are run in order. {path} in the command is
+    /// replaced with the path of the file.
+    ///
+    /// Example:
+    ///     --formatter "rust=rustfmt --edition 2021"
+    ///     --formatter "typescript=prettier --stdin-filepath {path}"
+    #[arg(long, value_parser=parse_formatter)]
+    pub formatter: Vec<Formatter>,
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug)]
 pub enum ParserName {
     Rust,
+    Delimited,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List the parsers and generators available to --pipeline files, with the file extensions
+    /// they support and the schema of their config. See [apyxl::Registry].
+    List {
+        /// Print as json, including the full config schemas, e.g. for generating docs.
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Parse and build a model, then answer a query about it, e.g. `show dto service.user.User`,
+    /// `references Id` or `list rpcs 'user\.get_'`.
+    Query(QueryArgs),
+
+    /// Parse and build a model, then print the number of entities in it, its deepest namespace
+    /// nesting and the entities defined in each input file. See [apyxl::model::Model::stats].
+    Stats(StatsArgs),
+}
+
+/// Input to parse and build a model from, shared by the subcommands that inspect the model.
+#[derive(Args, Debug)]
+pub struct ModelArgs {
+    /// Unix-style glob of files to be parsed as API source files, or a directory to parse all
+    /// files within.
+    #[arg(short, long, value_name = "GLOB")]
+    pub input: String,
+
+    /// Name of the parser to use.
+    #[arg(short, long)]
+    pub parser: ParserName,
+
+    /// Unix-style glob of files to leave out of the --input, e.g. `**/generated/**`. May be
+    /// given more than once. Relative to the current working directory.
+    #[arg(long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+
+    /// Path to a [apyxl::parser::Config] in json format.
+    #[arg(long)]
+    pub parser_config: Option<PathBuf>,
+
+    /// Skip unknown syntax, treat unresolved types as user types and ignore duplicate definitions.
+    #[arg(long)]
+    pub lenient: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct QueryArgs {
+    #[command(flatten)]
+    pub model: ModelArgs,
+
+    /// Print the results as json instead of in the rust debug format.
+    #[arg(long)]
+    pub json: bool,
+
+    /// One of:
+    ///     show [kind] <path>          the matching entities
+    ///     references [kind] <path>    fields, params and rpcs that refer to the matching types
+    ///     list <kind> [regex]         all entities of the kind whose path matches the regex
+    ///
+    /// where kind is one of namespace, dto, rpc or enum, and path is the dot-separated names of
+    /// the entity and its namespaces, or any suffix of them, e.g. `service.user.User` or `User`.
+    #[arg(required = true, num_args = 1..=3, verbatim_doc_comment)]
+    pub query: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    pub model: ModelArgs,
+
+    /// Print the stats as json, e.g. to track the size of the API over time.
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub enum GeneratorName {
     Rust,
+    Json,
+    Changelog,
+    Report,
+    Graph,
+    Typescript,
+    Openapi,
+    Html,
+    Kotlin,
+    Python,
+    Go,
+    Template,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, Eq, PartialEq)]
+pub enum MessageFormat {
+    Human,
+    Json,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug)]
+pub enum Compression {
+    Gzip,
+    TarGz,
 }
 
 #[derive(Clone, Debug)]
-pub struct Output {
+pub struct PathTemplate {
     pub generator: GeneratorName,
+    pub template: apyxl::output::PathTemplate,
+}
+
+#[derive(Clone, Debug)]
+pub struct Formatter {
+    pub generator: GeneratorName,
+    pub command: apyxl::output::FormatCommand,
+}
+
+#[derive(Clone, Debug)]
+pub struct Output {
+    /// None if the output applies to the only generator.
+    pub generator: Option<GeneratorName>,
     pub path: PathBuf,
 }
 
+impl Config {
+    /// Checks constraints between arguments that clap can't express.
+    pub fn validate(&self) -> Result<()> {
+        let has_unnamed_output = self.output.iter().any(|output| output.generator.is_none());
+        if has_unnamed_output && self.generator.len() > 1 {
+            return Err(anyhow!(
+                "outputs must be in the form '<generator>=<output/path>' when using more than one generator"
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn input(&self) -> Result<&str> {
+        self.input
+            .as_deref()
+            .ok_or_else(|| anyhow!("--input is required"))
+    }
+}
+
+/// Glob for an --input relative to `root`, expanding a directory to all files within it.
+pub fn input_glob(root: &Path, input: &str) -> String {
+    if root.join(input).is_dir() {
+        Path::new(input).join("**/*").to_string_lossy().to_string()
+    } else {
+        input.to_string()
+    }
+}
+
+impl Output {
+    pub fn is_for(&self, generator: GeneratorName) -> bool {
+        self.generator.is_none_or(|name| name == generator)
+    }
+}
+
 fn parse_output(arg: &str) -> Result<Output> {
     let vec = arg.split('=').collect_vec();
-    if vec.len() != 2 {
-        return Err(anyhow!(
-            "output must be in the form '<generator>=<output/path>'"
-        ));
+    match vec.as_slice() {
+        [path] => Ok(Output {
+            generator: None,
+            path: PathBuf::from(path),
+        }),
+        [generator, path] => {
+            let generator = GeneratorName::from_str(generator, true)
+                .map_err(|_| anyhow!("'{}' is not a valid generator name", generator))?;
+            Ok(Output {
+                generator: Some(generator),
+                path: PathBuf::from(path),
+            })
+        }
+        _ => Err(anyhow!(
+            "output must be in the form '<generator>=<output/path>' or '<output/path>'"
+        )),
+    }
+}
+
+fn parse_path_template(arg: &str) -> Result<PathTemplate> {
+    let (generator, template) = arg
+        .split_once('=')
+        .ok_or_else(|| anyhow!("path template must be in the form '<generator>=<template>'"))?;
+    let generator = GeneratorName::from_str(generator, true)
+        .map_err(|_| anyhow!("'{}' is not a valid generator name", generator))?;
+    let template = apyxl::output::PathTemplate::new(template)?;
+    Ok(PathTemplate {
+        generator,
+        template,
+    })
+}
+
+fn parse_formatter(arg: &str) -> Result<Formatter> {
+    let (generator, command) = arg
+        .split_once('=')
+        .ok_or_else(|| anyhow!("formatter must be in the form '<generator>=<command>'"))?;
+    let generator = GeneratorName::from_str(generator, true)
+        .map_err(|_| anyhow!("'{}' is not a valid generator name", generator))?;
+    let command = apyxl::output::FormatCommand::parse(command)?;
+    Ok(Formatter { generator, command })
+}
+
+fn parse_indent(arg: &str) -> Result<apyxl::output::Indent> {
+    if arg == "tabs" {
+        return Ok(apyxl::output::Indent::Tabs);
     }
-    let generator = GeneratorName::from_str(vec[0], true)
-        .map_err(|_| anyhow!("'{}' is not a valid generator name", vec[0]))?;
-    let path = PathBuf::from(vec[1]);
-    Ok(Output { generator, path })
+    let spaces = arg
+        .parse::<usize>()
+        .map_err(|_| anyhow!("indent must be 'tabs' or a number of spaces"))?;
+    Ok(apyxl::output::Indent::Spaces(spaces))
 }
 
 impl ParserName {
-    pub fn create_impl(&self) -> impl apyxl::Parser {
-        match self {
-            ParserName::Rust => apyxl::parser::Rust::default(),
-        }
+    /// Name the parser is registered under in the [apyxl::Registry].
+    pub fn registry_name(&self) -> String {
+        registry_name(self)
     }
 }
 
 impl GeneratorName {
-    pub fn create_impl(&self) -> impl apyxl::Generator {
+    /// Name the generator is registered under in the [apyxl::Registry].
+    pub fn registry_name(&self) -> String {
+        registry_name(self)
+    }
+}
+
+fn registry_name<V: ValueEnum>(value: &V) -> String {
+    value
+        .to_possible_value()
+        .expect("no variants are skipped")
+        .get_name()
+        .to_string()
+}
+
+impl LineEnding {
+    pub fn to_impl(self) -> apyxl::output::LineEnding {
         match self {
-            GeneratorName::Rust => apyxl::generator::Rust::default(),
+            LineEnding::Lf => apyxl::output::LineEnding::Lf,
+            LineEnding::Crlf => apyxl::output::LineEnding::CrLf,
         }
     }
 }
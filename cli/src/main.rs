@@ -1,66 +1,421 @@
 use std::cell::RefCell;
-use std::fs::File;
-use std::io::BufReader;
+use std::env;
+use std::fs;
+use std::io::IsTerminal;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::time::Duration;
 
-use anyhow::{Context, Result};
+use anyhow::{anyhow, Context, Result};
 use clap::Parser;
+use tracing_subscriber::fmt::format::FmtSpan;
 
-use crate::config::{Config, GeneratorName, Output};
+use crate::config::{Command, Compression, Config, GeneratorName, MessageFormat, Output};
 
 mod config;
+mod query;
+
+/// How often to check for changed input files with --watch.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 fn main() -> Result<()> {
     env_logger::init();
     let config = Config::parse();
-    let input = apyxl::input::Glob::new(&config.input)?;
-    let parser = parser(&config);
-    let parser_config = parser_config(&config)?;
+    if config.timings {
+        // Each phase is a span, so the span's busy time is the phase's duration.
+        tracing_subscriber::fmt()
+            .with_span_events(FmtSpan::CLOSE)
+            .with_target(false)
+            .with_ansi(std::io::stderr().is_terminal())
+            .with_writer(std::io::stderr)
+            .init();
+    }
+    let reporter = Reporter::new(&config);
+    let result = match &config.command {
+        Some(Command::List { json }) => return list(*json),
+        Some(Command::Query(args)) => query::run(args),
+        Some(Command::Stats(args)) => query::stats(args),
+        None => run(&config, &reporter),
+    };
+    reporter.report(&result);
+    if result.is_err() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+fn run(config: &Config, reporter: &Reporter) -> Result<()> {
+    if let Some(pipeline) = &config.pipeline {
+        return apyxl::Pipeline::from_config(pipeline)?.execute();
+    }
+    config.validate()?;
+    let registry = apyxl::Registry::default();
+    let parser_config = read_parser_config(config.parser_config.as_deref())?;
+    registry.validate_config(&parser_config)?;
+    let input = input(&env::current_dir()?, config.input()?, &config.exclude)?;
+    let parser = parser(config, &registry, &parser_config)?;
     let mut outputs = Vec::<Rc<RefCell<dyn apyxl::Output>>>::new();
-    let mut exe = apyxl::Executor::new(input, parser);
-    if let Some(parser_config) = parser_config {
-        exe = exe.parser_config(parser_config);
+    let mut dry_runs = Vec::<DryRunOutput>::new();
+    let mut exe = apyxl::Executor::new(input, parser).parser_config(parser_config.clone());
+    if let Some(dir) = &config.parse_cache {
+        exe = exe.parse_cache(apyxl::parser::Cache::new(dir)?);
+    }
+    if config.lenient {
+        exe = exe.mode(apyxl::Mode::Lenient);
+    }
+    if let Some(path) = &config.baseline {
+        let mut baseline = apyxl::Baseline::from_file(path)?;
+        if let Some(path) = &config.baseline_allowlist {
+            baseline = baseline.allowlist_file(path)?;
+        }
+        exe = exe.baseline(baseline);
     }
+    if config.dead_types {
+        let mut dead_types = apyxl::DeadTypes::default().filter(config.filter_dead_types);
+        for root in &config.dead_type_root {
+            let entity_id = apyxl::model::EntityId::try_from(root.as_str())
+                .map_err(|err| anyhow!("invalid --dead-type-root '{}': {}", root, err))?;
+            dead_types = dead_types.root(entity_id);
+        }
+        exe = exe.dead_types(dead_types);
+    }
+    exe = exe
+        .diagnostics_ptr(reporter.diagnostics.clone())
+        .stats_ptr(reporter.stats.clone());
     for generator_name in &config.generator {
-        exe = add_generator(*generator_name, &config, exe, &mut outputs)?;
+        let generator = registry
+            .create_generator_with_config(&generator_name.registry_name(), &parser_config)?;
+        exe = add_generator(
+            *generator_name,
+            generator,
+            config,
+            exe,
+            &mut outputs,
+            &mut dry_runs,
+        )?;
+    }
+    if config.watch {
+        let result = exe.watch(WATCH_POLL_INTERVAL, |result| {
+            // Errors don't end a watch, so each run is reported here rather than by main.
+            reporter.report(result);
+            eprintln!("Watching for changes...");
+            ControlFlow::Continue(())
+        });
+        // The last run was already reported.
+        reporter.clear();
+        return result;
+    }
+    if config.check {
+        exe.check()?;
+    } else {
+        exe.execute()?;
+    }
+    print_dry_runs(&dry_runs);
+    Ok(())
+}
+
+/// Prints the [apyxl::Diagnostics] and [apyxl::Stats] of a run in the --message-format.
+struct Reporter {
+    format: MessageFormat,
+    print_stats: bool,
+    diagnostics: Rc<RefCell<apyxl::Diagnostics>>,
+    stats: Rc<RefCell<apyxl::Stats>>,
+}
+
+impl Reporter {
+    fn new(config: &Config) -> Self {
+        Self {
+            format: config.message_format,
+            print_stats: config.stats,
+            diagnostics: Rc::new(RefCell::new(apyxl::Diagnostics::new())),
+            stats: Rc::new(RefCell::new(apyxl::Stats::default())),
+        }
+    }
+
+    fn clear(&self) {
+        *self.diagnostics.borrow_mut() = apyxl::Diagnostics::new();
+        *self.stats.borrow_mut() = apyxl::Stats::default();
+    }
+
+    fn report(&self, result: &Result<()>) {
+        match self.format {
+            MessageFormat::Human => self.report_human(result),
+            MessageFormat::Json => self.report_json(result),
+        }
+    }
+
+    fn report_human(&self, result: &Result<()>) {
+        for warning in self.diagnostics.borrow().warnings() {
+            eprintln!("{}", warning);
+        }
+        match result {
+            // Diagnostics are already formatted for the terminal.
+            Err(err) => match err.downcast_ref::<apyxl::Diagnostics>() {
+                Some(diagnostics) => eprintln!("{}", diagnostics),
+                None => eprintln!("Error: {:?}", err),
+            },
+            Ok(()) if self.print_stats => eprintln!("{}", self.stats.borrow()),
+            Ok(()) => {}
+        }
+    }
+
+    fn report_json(&self, result: &Result<()>) {
+        let mut diagnostics = self.diagnostics.borrow().clone();
+        if let Err(err) = result {
+            // Errors from the executor are already collected, but earlier ones such as an invalid
+            // --input are not.
+            if !diagnostics.has_errors() {
+                diagnostics.extend(apyxl::Diagnostics::from_error(err));
+            }
+        }
+        let summary = serde_json::json!({
+            "success": result.is_ok(),
+            "diagnostics": diagnostics.iter().collect::<Vec<_>>(),
+            "stats": *self.stats.borrow(),
+        });
+        println!("{}", summary);
     }
-    exe.execute()
 }
 
-fn parser(config: &Config) -> impl apyxl::Parser {
-    config.parser.create_impl()
+fn list(json: bool) -> Result<()> {
+    let registry = apyxl::Registry::default();
+    let parsers = registry.parser_entries().collect::<Vec<_>>();
+    let generators = registry.generator_entries().collect::<Vec<_>>();
+    if json {
+        let listing = serde_json::json!({ "parsers": parsers, "generators": generators });
+        println!("{}", serde_json::to_string_pretty(&listing)?);
+        return Ok(());
+    }
+    for (title, entries) in [("Parsers", &parsers), ("Generators", &generators)] {
+        println!("{}:", title);
+        for entry in entries {
+            let extensions = entry
+                .extensions
+                .iter()
+                .map(|ext| format!(".{}", ext))
+                .collect::<Vec<_>>();
+            let mut line = format!("  {}", entry.name);
+            if !extensions.is_empty() {
+                line += &format!(" ({})", extensions.join(", "));
+            }
+            if entry.config_schema.is_some() {
+                line += " [configurable]";
+            }
+            println!("{}", line);
+        }
+    }
+    println!("\nUse --json to include the schema of each config.");
+    Ok(())
 }
 
-fn parser_config(config: &Config) -> Result<Option<apyxl::parser::Config>> {
-    match &config.parser_config {
-        None => Ok(None),
+fn parser(
+    config: &Config,
+    registry: &apyxl::Registry,
+    parser_config: &apyxl::parser::Config,
+) -> Result<impl apyxl::Parser> {
+    let parser = config
+        .parser
+        .ok_or_else(|| anyhow!("--parser is required"))?;
+    registry.create_parser_with_config(&parser.registry_name(), parser_config)
+}
+
+/// The files matching the `input` glob (or within the `input` directory) and none of the
+/// `exclude` globs, relative to `root`. Fails if there are none, rather than generating an empty
+/// API.
+fn input(root: &Path, input: &str, exclude: &[String]) -> Result<apyxl::input::Glob> {
+    let glob = apyxl::input::Glob::new_with_root_excluding(
+        root,
+        &[config::input_glob(root, input)],
+        exclude,
+    )?;
+    if apyxl::Input::chunks(&glob).is_empty() {
+        return Err(anyhow!("--input '{}' matches no files", input));
+    }
+    Ok(glob)
+}
+
+fn read_parser_config(path: Option<&Path>) -> Result<apyxl::parser::Config> {
+    match path {
+        None => Ok(apyxl::parser::Config::default()),
         Some(path) => {
-            let file = File::open(path).context("read parser config")?;
-            let reader = BufReader::new(file);
-            Ok(Some(serde_json::from_reader(reader)?))
+            let data = fs::read_to_string(path).context("read parser config")?;
+            apyxl::parser::Config::from_json(&data)
         }
     }
 }
 
 fn add_generator<I: apyxl::Input, P: apyxl::Parser>(
     generator_name: GeneratorName,
+    generator: Box<dyn apyxl::Generator>,
     config: &Config,
     mut exe: apyxl::Executor<I, P>,
     outputs: &mut Vec<Rc<RefCell<dyn apyxl::Output>>>,
+    dry_runs: &mut Vec<DryRunOutput>,
 ) -> Result<apyxl::Executor<I, P>> {
-    exe = exe.generator(generator_name.create_impl());
+    exe = exe.generator(generator);
     for output_config in &config.output {
-        if output_config.generator == generator_name {
-            let output = output(config, output_config)?;
-            outputs.push(output.clone());
-            exe = exe.output_ptr(output)
+        if output_config.is_for(generator_name) {
+            if config.dry_run {
+                let output = Rc::new(RefCell::new(apyxl::output::DryRun::default()));
+                dry_runs.push(DryRunOutput {
+                    root: config.output_root.join(&output_config.path),
+                    template: path_template(config, generator_name),
+                    output: output.clone(),
+                });
+                exe = exe.output_ptr(output);
+            } else {
+                let output = Rc::new(RefCell::new(output(config, generator_name, output_config)?));
+                outputs.push(output.clone());
+                exe = exe.output_ptr(output)
+            }
         }
     }
     Ok(exe)
 }
 
-fn output(config: &Config, output: &Output) -> Result<Rc<RefCell<apyxl::output::FileSet>>> {
-    Ok(Rc::new(RefCell::new(apyxl::output::FileSet::new(
-        config.output_root.join(&output.path),
-    )?)))
+fn output(
+    config: &Config,
+    generator_name: GeneratorName,
+    output_config: &Output,
+) -> Result<Box<dyn apyxl::Output>> {
+    let path = config.output_root.join(&output_config.path);
+    let mut output: Box<dyn apyxl::Output> = if config.check {
+        Box::new(
+            apyxl::output::Check::new(path)
+                .prune(config.prune)
+                .diff(config.diff),
+        )
+    } else if let Some(compression) = config.compress {
+        match compression {
+            Compression::Gzip => Box::new(apyxl::output::GzFileSet::new(path)?),
+            Compression::TarGz => Box::new(apyxl::output::TarGz::new(path)?),
+        }
+    } else if config.marked_regions {
+        Box::new(apyxl::output::MarkedRegions::new(path)?)
+    } else if config.incremental || config.prune {
+        Box::new(apyxl::output::IncrementalFileSet::new(path)?.prune(config.prune))
+    } else if config.atomic {
+        Box::new(apyxl::output::AtomicFileSet::new(path)?)
+    } else {
+        Box::new(apyxl::output::FileSet::new(path)?)
+    };
+
+    // Formatters run innermost so that they see the final content, including the banner.
+    let formatters = config
+        .formatter
+        .iter()
+        .filter(|formatter| formatter.generator == generator_name)
+        .collect::<Vec<_>>();
+    if !formatters.is_empty() {
+        let mut post_processed = apyxl::output::PostProcessed::new(output);
+        for formatter in formatters {
+            post_processed = post_processed.processor(formatter.command.clone());
+        }
+        output = Box::new(post_processed);
+    }
+
+    // Style is applied innermost so that it also applies to the banner.
+    if config.line_ending.is_some() || config.indent.is_some() {
+        let mut styled = apyxl::output::Styled::new(output);
+        if let Some(line_ending) = config.line_ending {
+            styled = styled.line_ending(line_ending.to_impl());
+        }
+        if let Some(indent) = config.indent {
+            styled = styled.indent(indent);
+        }
+        output = Box::new(styled);
+    }
+    if config.banner {
+        output = Box::new(apyxl::output::Banner::new(output));
+    }
+    // Paths are templated outermost so that everything else sees the final path.
+    if let Some(template) = path_template(config, generator_name) {
+        output = Box::new(apyxl::output::Templated::new(output, template));
+    }
+    // Reported at info level.
+    Ok(Box::new(apyxl::output::Progress::new(output)))
+}
+
+fn path_template(
+    config: &Config,
+    generator_name: GeneratorName,
+) -> Option<apyxl::output::PathTemplate> {
+    config
+        .path_template
+        .iter()
+        .find(|template| template.generator == generator_name)
+        .map(|template| template.template.clone())
+}
+
+struct DryRunOutput {
+    root: PathBuf,
+    template: Option<apyxl::output::PathTemplate>,
+    output: Rc<RefCell<apyxl::output::DryRun>>,
+}
+
+fn print_dry_runs(dry_runs: &[DryRunOutput]) {
+    for dry_run in dry_runs {
+        let output = dry_run.output.borrow();
+        // Data without a chunk path is not written by FileSet either, so skip it.
+        for record in output.records() {
+            if let Some(path) = &record.relative_file_path {
+                let path = match &dry_run.template {
+                    None => path.clone(),
+                    Some(template) => template.render(path),
+                };
+                println!(
+                    "{} ({} bytes)",
+                    dry_run.root.join(path).display(),
+                    record.bytes
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+    use apyxl::Input;
+    use tempfile::tempdir;
+
+    #[test]
+    fn input_literal_file_path() -> Result<()> {
+        let root = tempdir()?;
+        let path = root.path().join("api.rs");
+        fs::write(&path, "struct A {}")?;
+        let cwd = tempdir()?;
+        let input = crate::input(cwd.path(), path.to_str().unwrap(), &[])?;
+        let chunks = input
+            .chunks()
+            .into_iter()
+            .map(|(chunk, data)| (chunk.relative_file_path.clone().unwrap(), data))
+            .collect::<Vec<_>>();
+        assert_eq!(chunks, vec![(PathBuf::from("api.rs"), "struct A {}")]);
+        Ok(())
+    }
+
+    #[test]
+    fn input_excludes() -> Result<()> {
+        let root = tempdir()?;
+        fs::create_dir(root.path().join("generated"))?;
+        fs::write(root.path().join("api.rs"), "struct A {}")?;
+        fs::write(root.path().join("generated/b.rs"), "struct B {}")?;
+        let input = crate::input(root.path(), ".", &["generated/**".to_string()])?;
+        assert_eq!(input.chunks().len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn input_matching_no_files_errors() -> Result<()> {
+        let root = tempdir()?;
+        let result = crate::input(root.path(), "missing.rs", &[]);
+        let err = result.err().unwrap();
+        assert_eq!(err.to_string(), "--input 'missing.rs' matches no files");
+        Ok(())
+    }
 }
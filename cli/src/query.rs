@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::env;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use apyxl::model::{self, EntityId, EntityType, Namespace};
+use apyxl::query::Query;
+use apyxl::{Diagnostic, Diagnostics, Input, Parser};
+use regex::Regex;
+use serde_json::json;
+
+use crate::config::{ModelArgs, QueryArgs, StatsArgs};
+
+/// A single query given to `apyxl query`.
+#[derive(Debug)]
+enum Request {
+    /// `show [kind] <path>`: print the matching entities.
+    Show { kind: Option<Kind>, path: String },
+    /// `references [kind] <path>`: print every field, param and rpc that refers to the matching
+    /// dtos or enums.
+    References { kind: Option<Kind>, path: String },
+    /// `list <kind> [regex]`: print every entity of the kind whose path matches the regex.
+    List { kind: Kind, pattern: Option<Regex> },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Kind {
+    Namespace,
+    Dto,
+    Rpc,
+    Enum,
+}
+
+/// An entity in the model that a [Request] can match.
+struct Entry {
+    id: EntityId,
+    kind: Kind,
+    /// Dot-separated names of the entity and its namespaces, e.g. `service.user.User`.
+    path: String,
+    /// The entity in json and [std::fmt::Debug] format, like [apyxl::generator::Dbg].
+    json: serde_json::Value,
+    debug: String,
+}
+
+/// An entity printed in response to a [Request].
+struct Found<'e> {
+    id: EntityId,
+    entry: Option<&'e Entry>,
+}
+
+/// Parses the input and answers the `args.query` about the built model, printing the result to
+/// stdout.
+pub fn run(args: &QueryArgs) -> Result<()> {
+    let request = Request::parse(&args.query)?;
+    with_model(&args.model, |model, files| {
+        answer(args, &request, model, files)
+    })
+}
+
+/// Parses the input and prints [model::Model::stats] to stdout.
+pub fn stats(args: &StatsArgs) -> Result<()> {
+    with_model(&args.model, |model, _| {
+        let stats = model.stats();
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&stats)?);
+        } else {
+            println!("{}", stats);
+        }
+        Ok(())
+    })
+}
+
+/// Parses the input and builds a model, then calls `f` with it and the contents of each input
+/// file by relative path.
+fn with_model<T>(
+    args: &ModelArgs,
+    f: impl FnOnce(&model::Model, &HashMap<PathBuf, String>) -> Result<T>,
+) -> Result<T> {
+    let registry = apyxl::Registry::default();
+    let parser_config = crate::read_parser_config(args.parser_config.as_deref())?;
+    registry.validate_config(&parser_config)?;
+    let parser =
+        registry.create_parser_with_config(&args.parser.registry_name(), &parser_config)?;
+    let mut input = crate::input(&env::current_dir()?, &args.input, &args.exclude)?;
+    // The model borrows the input mutably, so keep a copy of each file to resolve locations in.
+    let files = input
+        .chunks()
+        .into_iter()
+        .filter_map(|(chunk, data)| Some((chunk.relative_file_path.clone()?, data.to_string())))
+        .collect::<HashMap<_, _>>();
+
+    let mode = if args.lenient {
+        apyxl::Mode::Lenient
+    } else {
+        apyxl::Mode::Strict
+    };
+    let mut builder = model::Builder::with_config(model::builder::Config {
+        mode,
+        ..Default::default()
+    });
+    parser.parse(&parser_config, &mut input, &mut builder)?;
+    builder.metadata_mut().user_types = parser_config.user_types.clone();
+    let model = builder
+        .build()
+        .map_err(|errors| errors.iter().map(Diagnostic::from).collect::<Diagnostics>())?;
+    f(&model, &files)
+}
+
+fn answer(
+    args: &QueryArgs,
+    request: &Request,
+    model: &model::Model,
+    files: &HashMap<PathBuf, String>,
+) -> Result<()> {
+    let mut entries = vec![];
+    collect(model.api(), &EntityId::default(), "", &mut entries)?;
+    let query = Query::new(model);
+    let found = request.answer(&entries, &query)?;
+    let location = |id: &EntityId| location(&query, files, id);
+
+    if args.json {
+        let found = found
+            .iter()
+            .map(|found| {
+                json!({
+                    "id": found.id.to_string(),
+                    "location": location(&found.id),
+                    "entity": found.entry.map(|entry| &entry.json),
+                })
+            })
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&found)?);
+        return Ok(());
+    }
+    for found in &found {
+        match location(&found.id) {
+            None => println!("{}", found.id),
+            Some(location) => println!("{} ({})", found.id, location),
+        }
+        if let Some(entry) = found.entry {
+            println!("{}", entry.debug);
+        }
+    }
+    Ok(())
+}
+
+impl Request {
+    fn parse(words: &[String]) -> Result<Self> {
+        let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+        match words.as_slice() {
+            ["show", path] => Ok(Request::Show {
+                kind: None,
+                path: path.to_string(),
+            }),
+            ["show", kind, path] => Ok(Request::Show {
+                kind: Some(Kind::parse(kind)?),
+                path: path.to_string(),
+            }),
+            ["references", path] => Ok(Request::References {
+                kind: None,
+                path: path.to_string(),
+            }),
+            ["references", kind, path] => Ok(Request::References {
+                kind: Some(Kind::parse(kind)?),
+                path: path.to_string(),
+            }),
+            ["list", kind] => Ok(Request::List {
+                kind: Kind::parse(kind)?,
+                pattern: None,
+            }),
+            ["list", kind, pattern] => Ok(Request::List {
+                kind: Kind::parse(kind)?,
+                pattern: Some(Regex::new(pattern)?),
+            }),
+            _ => Err(anyhow!(
+                "query must be one of 'show [kind] <path>', 'references [kind] <path>' or \
+                'list <kind> [regex]', where kind is one of namespace, dto, rpc or enum"
+            )),
+        }
+    }
+
+    fn answer<'e>(&self, entries: &'e [Entry], query: &Query) -> Result<Vec<Found<'e>>> {
+        match self {
+            Request::Show { kind, path } => Ok(matching(entries, *kind, path)?
+                .map(|entry| Found {
+                    id: entry.id.clone(),
+                    entry: Some(entry),
+                })
+                .collect()),
+            Request::References { kind, path } => Ok(matching(entries, *kind, path)?
+                .flat_map(|entry| query.references(&entry.id))
+                .map(|id| Found { id, entry: None })
+                .collect()),
+            Request::List { kind, pattern } => Ok(entries
+                .iter()
+                .filter(|entry| entry.kind == *kind)
+                .filter(|entry| pattern.as_ref().is_none_or(|re| re.is_match(&entry.path)))
+                .map(|entry| Found {
+                    id: entry.id.clone(),
+                    entry: None,
+                })
+                .collect()),
+        }
+    }
+}
+
+impl Kind {
+    /// Parses a kind, also accepting plurals for readability, e.g. `list dtos`.
+    fn parse(value: &str) -> Result<Self> {
+        match value.strip_suffix('s').unwrap_or(value) {
+            "namespace" | "ns" | "mod" => Ok(Kind::Namespace),
+            "dto" => Ok(Kind::Dto),
+            "rpc" => Ok(Kind::Rpc),
+            "enum" | "en" => Ok(Kind::Enum),
+            _ => Err(anyhow!(
+                "'{}' is not a kind of entity, expected one of namespace, dto, rpc or enum",
+                value
+            )),
+        }
+    }
+}
+
+/// Entries with the `kind` (if any) whose path is `path`, or ends with it, e.g. `Id` matches both
+/// `user.Id` and `order.Id`. Errors if there are none.
+fn matching<'e>(
+    entries: &'e [Entry],
+    kind: Option<Kind>,
+    path: &str,
+) -> Result<impl Iterator<Item = &'e Entry> + 'e> {
+    let suffix = format!(".{}", path);
+    let path = path.to_string();
+    let is_match = move |entry: &&Entry| {
+        kind.is_none_or(|kind| entry.kind == kind)
+            && (entry.path == path || entry.path.ends_with(&suffix))
+    };
+    let mut matches = entries.iter().filter(is_match).peekable();
+    if matches.peek().is_none() {
+        return Err(anyhow!("no entity matches the query"));
+    }
+    Ok(matches)
+}
+
+fn collect(
+    namespace: &Namespace,
+    namespace_id: &EntityId,
+    namespace_path: &str,
+    entries: &mut Vec<Entry>,
+) -> Result<()> {
+    let path = |name: &str| {
+        if namespace_path.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", namespace_path, name)
+        }
+    };
+    for dto in namespace.dtos() {
+        entries.push(Entry {
+            id: namespace_id.child(EntityType::Dto, dto.name)?,
+            kind: Kind::Dto,
+            path: path(dto.name),
+            json: serde_json::to_value(dto)?,
+            debug: format!("{:#?}", dto),
+        });
+    }
+    for rpc in namespace.rpcs() {
+        entries.push(Entry {
+            id: namespace_id.child(EntityType::Rpc, rpc.name)?,
+            kind: Kind::Rpc,
+            path: path(rpc.name),
+            json: serde_json::to_value(rpc)?,
+            debug: format!("{:#?}", rpc),
+        });
+    }
+    for en in namespace.enums() {
+        entries.push(Entry {
+            id: namespace_id.child(EntityType::Enum, en.name)?,
+            kind: Kind::Enum,
+            path: path(en.name),
+            json: serde_json::to_value(en)?,
+            debug: format!("{:#?}", en),
+        });
+    }
+    for child in namespace.namespaces() {
+        let child_id = namespace_id.child(EntityType::Namespace, &child.name)?;
+        let child_path = path(&child.name);
+        entries.push(Entry {
+            id: child_id.clone(),
+            kind: Kind::Namespace,
+            path: child_path.clone(),
+            json: serde_json::to_value(child)?,
+            debug: format!("{:#?}", child),
+        });
+        collect(child, &child_id, &child_path, entries)?;
+    }
+    Ok(())
+}
+
+/// `file:line:column` of the entity's definition, if it is known.
+fn location(query: &Query, files: &HashMap<PathBuf, String>, id: &EntityId) -> Option<String> {
+    let location = query.location(id)?;
+    let data = files.get(&location.relative_file_path)?;
+    let span = apyxl::diagnostic::Span::new(data, location.span.clone());
+    Some(format!(
+        "{}:{}:{}",
+        location.relative_file_path.display(),
+        span.line,
+        span.column
+    ))
+}
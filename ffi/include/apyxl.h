@@ -0,0 +1,65 @@
+/*
+ * C ABI for embedding apyxl. See ffi/src/lib.rs for details on each function.
+ *
+ * All strings are NUL-terminated UTF-8. Functions that can fail return NULL (or false) and, if
+ * `error` is not NULL, set `*error` to a message that must be freed with apyxl_string_free.
+ * Panics are caught and reported the same way.
+ * Strings returned by the apyxl_output_* functions are owned by the ApyxlOutput.
+ */
+#ifndef APYXL_H
+#define APYXL_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct ApyxlInput ApyxlInput;
+typedef struct ApyxlModel ApyxlModel;
+typedef struct ApyxlOutput ApyxlOutput;
+
+/* Creates an empty input. Free with apyxl_input_free, or pass to apyxl_model_parse. */
+ApyxlInput *apyxl_input_new(void);
+
+/* Adds a chunk of `len` bytes of `data`. `relative_file_path` may be NULL. */
+bool apyxl_input_add_chunk(ApyxlInput *input, const char *relative_file_path, const char *data,
+                           size_t len, char **error);
+
+/* Frees an input that was not passed to apyxl_model_parse. */
+void apyxl_input_free(ApyxlInput *input);
+
+/*
+ * Parses and validates `input` with the parser registered as `parser`. Takes ownership of `input`
+ * even on failure. `config_json` is an optional parser config in json format, and may be NULL.
+ * Free with apyxl_model_free.
+ */
+ApyxlModel *apyxl_model_parse(ApyxlInput *input, const char *parser, const char *config_json,
+                              char **error);
+
+void apyxl_model_free(ApyxlModel *model);
+
+/* Runs the generator registered as `generator` over `model`. Free with apyxl_output_free. */
+ApyxlOutput *apyxl_generate(const ApyxlModel *model, const char *generator, char **error);
+
+/* Data generated without a chunk. Empty if there is none. */
+const char *apyxl_output_unchunked(const ApyxlOutput *output);
+
+/* Number of chunks, sorted by path. */
+size_t apyxl_output_chunk_count(const ApyxlOutput *output);
+
+/* Path/data of the chunk at `index`, or NULL if `index` is out of range. */
+const char *apyxl_output_chunk_path(const ApyxlOutput *output, size_t index);
+const char *apyxl_output_chunk_data(const ApyxlOutput *output, size_t index);
+
+void apyxl_output_free(ApyxlOutput *output);
+
+/* Frees a string returned via an `error` out-param. */
+void apyxl_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* APYXL_H */
@@ -0,0 +1,515 @@
+//! C ABI for embedding apyxl in non-Rust build systems and editors. The C declarations are in
+//! `include/apyxl.h`.
+//!
+//! Add chunks of API source to an [ApyxlInput], parse it into an opaque [ApyxlModel], then run any
+//! number of generators over the model, each producing an [ApyxlOutput] with the generated data
+//! for each chunk. Parsers and generators are looked up by name in [apyxl::Registry::default].
+//!
+//! All strings are NUL-terminated UTF-8. Functions that can fail return null (or `false`) and, if
+//! `error` is not null, set `*error` to a message that must be freed with [apyxl_string_free].
+//! Panics are caught and reported the same way rather than unwinding into the caller. Strings
+//! returned by the `apyxl_output_*` functions are owned by the [ApyxlOutput].
+
+use std::ffi::{c_char, CStr, CString};
+use std::mem::ManuallyDrop;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
+
+use anyhow::{anyhow, Result};
+use apyxl::model::{self, Chunk};
+use apyxl::output::ChunkBuffer;
+use apyxl::{input, parser, Diagnostic, Diagnostics, Generator, Output, Parser, Registry};
+
+/// API source to be parsed by [apyxl_model_parse].
+#[derive(Default)]
+pub struct ApyxlInput {
+    chunks: input::ChunkBuffer,
+}
+
+/// A parsed and validated API model.
+pub struct ApyxlModel {
+    /// Borrows from `input` and `config`, which are only freed once it is dropped. See
+    /// [ApyxlModel::drop].
+    model: ManuallyDrop<model::Model<'static>>,
+    input: *mut input::ChunkBuffer,
+    config: *mut parser::Config,
+}
+
+/// Generated data for each chunk, from [apyxl_generate].
+pub struct ApyxlOutput {
+    unchunked: CString,
+    chunks: Vec<(CString, CString)>,
+}
+
+impl ApyxlModel {
+    fn parse(input: input::ChunkBuffer, parser_name: &str, config: parser::Config) -> Result<Self> {
+        let registry = Registry::default();
+        registry.validate_config(&config)?;
+        let parser = registry.create_parser_with_config(parser_name, &config)?;
+
+        let input = Box::into_raw(Box::new(input));
+        let config = Box::into_raw(Box::new(config));
+        // SAFETY: Both are leaked until they are freed either below if building fails, or by
+        // [ApyxlModel::drop] after the model that borrows from them.
+        let model = build(&parser, unsafe { &*config }, unsafe { &mut *input });
+        match model {
+            Ok(model) => Ok(Self {
+                model: ManuallyDrop::new(model),
+                input,
+                config,
+            }),
+            Err(err) => {
+                // SAFETY: Nothing borrows from them anymore since building failed.
+                unsafe {
+                    drop(Box::from_raw(input));
+                    drop(Box::from_raw(config));
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn config(&self) -> &parser::Config {
+        // SAFETY: Only freed on drop.
+        unsafe { &*self.config }
+    }
+
+    fn generate(&self, generator_name: &str) -> Result<ApyxlOutput> {
+        let mut generator =
+            Registry::default().create_generator_with_config(generator_name, self.config())?;
+        let mut output = ChunkBuffer::new();
+        generator.generate(self.model.view(), &mut output)?;
+        output.finish()?;
+        ApyxlOutput::new(&output)
+    }
+}
+
+impl Drop for ApyxlModel {
+    fn drop(&mut self) {
+        // SAFETY: The model is dropped first since it borrows from the input and config, which
+        // were leaked by [ApyxlModel::parse] and are not referenced anywhere else.
+        unsafe {
+            ManuallyDrop::drop(&mut self.model);
+            drop(Box::from_raw(self.input));
+            drop(Box::from_raw(self.config));
+        }
+    }
+}
+
+fn build<'a, P: Parser>(
+    parser: &P,
+    config: &'a parser::Config,
+    input: &'a mut input::ChunkBuffer,
+) -> Result<model::Model<'a>> {
+    let mut builder = model::Builder::with_config(model::builder::Config {
+        mode: config.mode,
+        ..Default::default()
+    });
+    parser.parse(config, input, &mut builder)?;
+    builder.metadata_mut().user_types = config.user_types.clone();
+    builder.build().map_err(|errors| {
+        errors
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Diagnostics>()
+            .into()
+    })
+}
+
+impl ApyxlOutput {
+    fn new(output: &ChunkBuffer) -> Result<Self> {
+        let chunks = output
+            .paths()
+            .map(|path| {
+                let data = output.get(path).unwrap_or_default();
+                Ok((c_string(&path.to_string_lossy())?, c_string(data)?))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            unchunked: c_string(output.unchunked())?,
+            chunks,
+        })
+    }
+}
+
+/// Creates an empty [ApyxlInput]. Free with [apyxl_input_free], or pass to [apyxl_model_parse].
+#[no_mangle]
+pub extern "C" fn apyxl_input_new() -> *mut ApyxlInput {
+    Box::into_raw(Box::default())
+}
+
+/// Adds a chunk of `len` bytes of UTF-8 `data` to the `input`. `relative_file_path` may be null;
+/// parsers use it to place the chunk's contents within namespaces, and chunked generators to
+/// name the generated file.
+///
+/// # Safety
+/// `input` must be a valid [ApyxlInput], `data` must point to at least `len` bytes, and
+/// `relative_file_path` must be null or a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_input_add_chunk(
+    input: *mut ApyxlInput,
+    relative_file_path: *const c_char,
+    data: *const c_char,
+    len: usize,
+    error: *mut *mut c_char,
+) -> bool {
+    let result = catch_panic(|| {
+        let input = input.as_mut().ok_or_else(|| anyhow!("'input' is null"))?;
+        let chunk = match opt_str_arg(relative_file_path, "relative_file_path")? {
+            None => Chunk::default(),
+            Some(path) => Chunk::with_relative_file_path(PathBuf::from(path)),
+        };
+        if data.is_null() && len > 0 {
+            return Err(anyhow!("'data' is null"));
+        }
+        let data = if len == 0 {
+            ""
+        } else {
+            std::str::from_utf8(std::slice::from_raw_parts(data.cast::<u8>(), len))?
+        };
+        input.chunks.add_chunk(chunk, data);
+        Ok(())
+    });
+    result.map_err(|err| set_error(error, err)).is_ok()
+}
+
+/// Frees an [ApyxlInput] that was not passed to [apyxl_model_parse].
+///
+/// # Safety
+/// `input` must be null or a valid [ApyxlInput], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_input_free(input: *mut ApyxlInput) {
+    if !input.is_null() {
+        drop(Box::from_raw(input))
+    }
+}
+
+/// Parses and validates the `input` with the parser registered as `parser`, taking ownership of
+/// the `input` even if parsing fails. `config_json` is an optional [apyxl::parser::Config] in json
+/// format, including the options of each parser and generator.
+///
+/// Returns null on failure. Free with [apyxl_model_free].
+///
+/// # Safety
+/// `input` must be a valid [ApyxlInput], and not be used afterwards. `parser` must be a valid
+/// string, and `config_json` null or a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_model_parse(
+    input: *mut ApyxlInput,
+    parser: *const c_char,
+    config_json: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut ApyxlModel {
+    let result = catch_panic(|| {
+        if input.is_null() {
+            return Err(anyhow!("'input' is null"));
+        }
+        let input = Box::from_raw(input);
+        let parser = str_arg(parser, "parser")?;
+        let config = match opt_str_arg(config_json, "config_json")? {
+            None => parser::Config::default(),
+            Some(json) => parser::Config::from_json(json)?,
+        };
+        ApyxlModel::parse(input.chunks, parser, config)
+    });
+    match result {
+        Ok(model) => Box::into_raw(Box::new(model)),
+        Err(err) => {
+            set_error(error, err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// # Safety
+/// `model` must be null or a valid [ApyxlModel], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_model_free(model: *mut ApyxlModel) {
+    if !model.is_null() {
+        drop(Box::from_raw(model))
+    }
+}
+
+/// Runs the generator registered as `generator` over the `model`, with its options from the
+/// config the `model` was parsed with.
+///
+/// Returns null on failure. Free with [apyxl_output_free].
+///
+/// # Safety
+/// `model` must be a valid [ApyxlModel] and `generator` a valid string.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_generate(
+    model: *const ApyxlModel,
+    generator: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut ApyxlOutput {
+    let result = catch_panic(|| {
+        let model = model.as_ref().ok_or_else(|| anyhow!("'model' is null"))?;
+        model.generate(str_arg(generator, "generator")?)
+    });
+    match result {
+        Ok(output) => Box::into_raw(Box::new(output)),
+        Err(err) => {
+            set_error(error, err);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Data generated without a chunk, e.g. the combined API. Empty if there is none.
+///
+/// # Safety
+/// `output` must be a valid [ApyxlOutput].
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_output_unchunked(output: *const ApyxlOutput) -> *const c_char {
+    let output = &*output;
+    output.unchunked.as_ptr()
+}
+
+/// Number of chunks in the `output`, sorted by path.
+///
+/// # Safety
+/// `output` must be a valid [ApyxlOutput].
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_output_chunk_count(output: *const ApyxlOutput) -> usize {
+    let output = &*output;
+    output.chunks.len()
+}
+
+/// Relative file path of the chunk at `index`, or null if `index` is out of range.
+///
+/// # Safety
+/// `output` must be a valid [ApyxlOutput].
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_output_chunk_path(
+    output: *const ApyxlOutput,
+    index: usize,
+) -> *const c_char {
+    let output = &*output;
+    output
+        .chunks
+        .get(index)
+        .map_or(ptr::null(), |(path, _)| path.as_ptr())
+}
+
+/// Generated data of the chunk at `index`, or null if `index` is out of range.
+///
+/// # Safety
+/// `output` must be a valid [ApyxlOutput].
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_output_chunk_data(
+    output: *const ApyxlOutput,
+    index: usize,
+) -> *const c_char {
+    let output = &*output;
+    output
+        .chunks
+        .get(index)
+        .map_or(ptr::null(), |(_, data)| data.as_ptr())
+}
+
+/// # Safety
+/// `output` must be null or a valid [ApyxlOutput], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_output_free(output: *mut ApyxlOutput) {
+    if !output.is_null() {
+        drop(Box::from_raw(output))
+    }
+}
+
+/// Frees a string returned via an `error` out-param.
+///
+/// # Safety
+/// `string` must be null or a string returned by this library, and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn apyxl_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string))
+    }
+}
+
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    opt_str_arg(ptr, name)?.ok_or_else(|| anyhow!("'{}' is null", name))
+}
+
+unsafe fn opt_str_arg<'a>(ptr: *const c_char, name: &str) -> Result<Option<&'a str>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map(Some)
+        .map_err(|err| anyhow!("'{}' is not valid UTF-8: {}", name, err))
+}
+
+fn c_string(data: &str) -> Result<CString> {
+    CString::new(data).map_err(|_| anyhow!("generated data contains a NUL byte"))
+}
+
+/// Runs `f`, turning a panic into an error, since unwinding across the C ABI is undefined behavior.
+fn catch_panic<T>(f: impl FnOnce() -> Result<T>) -> Result<T> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_string());
+        Err(anyhow!("panicked: {}", message))
+    })
+}
+
+unsafe fn set_error(error: *mut *mut c_char, err: anyhow::Error) {
+    if error.is_null() {
+        return;
+    }
+    let message = format!("{:#}", err).replace('\0', "\\0");
+    *error = CString::new(message)
+        .expect("NUL bytes are escaped")
+        .into_raw();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{c_char, CStr, CString};
+    use std::ptr;
+
+    use crate::*;
+
+    unsafe fn add_chunk(input: *mut ApyxlInput, path: Option<&str>, data: &str) {
+        let path = path.map(|path| CString::new(path).unwrap());
+        let ok = apyxl_input_add_chunk(
+            input,
+            path.as_ref().map_or(ptr::null(), |path| path.as_ptr()),
+            data.as_ptr().cast(),
+            data.len(),
+            ptr::null_mut(),
+        );
+        assert!(ok);
+    }
+
+    unsafe fn take_error(error: *mut c_char) -> String {
+        assert!(!error.is_null());
+        let message = CStr::from_ptr(error).to_str().unwrap().to_string();
+        apyxl_string_free(error);
+        message
+    }
+
+    unsafe fn to_str<'a>(ptr: *const c_char) -> &'a str {
+        CStr::from_ptr(ptr).to_str().unwrap()
+    }
+
+    #[test]
+    fn parse_and_generate() {
+        unsafe {
+            let input = apyxl_input_new();
+            add_chunk(input, Some("a.rs"), "struct A { b: b::B }");
+            add_chunk(input, Some("b.rs"), "struct B {}");
+            let parser = CString::new("rust").unwrap();
+            let config =
+                CString::new(r#"{ "generator": { "rust": { "import_comments": false } } }"#)
+                    .unwrap();
+            let mut error = ptr::null_mut();
+            let model = apyxl_model_parse(input, parser.as_ptr(), config.as_ptr(), &mut error);
+            assert!(!model.is_null());
+            assert!(error.is_null());
+
+            let generator = CString::new("rust").unwrap();
+            let output = apyxl_generate(model, generator.as_ptr(), &mut error);
+            assert!(!output.is_null());
+            assert_eq!(apyxl_output_chunk_count(output), 2);
+            assert_eq!(to_str(apyxl_output_chunk_path(output, 0)), "a.rs");
+            assert_eq!(
+                to_str(apyxl_output_chunk_data(output, 0)),
+                "struct A {\n    b: crate::b::B,\n}\n\n"
+            );
+            assert_eq!(to_str(apyxl_output_chunk_path(output, 1)), "b.rs");
+            assert!(apyxl_output_chunk_path(output, 2).is_null());
+            assert!(to_str(apyxl_output_unchunked(output)).contains("pub mod a"));
+
+            // Generators can be run more than once on the same model.
+            let generator = CString::new("dbg").unwrap();
+            let dbg = apyxl_generate(model, generator.as_ptr(), &mut error);
+            assert!(!dbg.is_null());
+
+            apyxl_output_free(dbg);
+            apyxl_output_free(output);
+            apyxl_model_free(model);
+        }
+    }
+
+    #[test]
+    fn parse_error() {
+        unsafe {
+            let input = apyxl_input_new();
+            add_chunk(input, None, "struct A {");
+            let parser = CString::new("rust").unwrap();
+            let mut error = ptr::null_mut();
+            let model = apyxl_model_parse(input, parser.as_ptr(), ptr::null(), &mut error);
+            assert!(model.is_null());
+            assert!(take_error(error).contains("errors encountered while parsing"));
+        }
+    }
+
+    #[test]
+    fn validation_error() {
+        unsafe {
+            let input = apyxl_input_new();
+            add_chunk(input, None, "struct A { b: Missing }");
+            let parser = CString::new("rust").unwrap();
+            let mut error = ptr::null_mut();
+            let model = apyxl_model_parse(input, parser.as_ptr(), ptr::null(), &mut error);
+            assert!(model.is_null());
+            assert!(take_error(error).contains("Missing"));
+        }
+    }
+
+    #[test]
+    fn unknown_names() {
+        unsafe {
+            let input = apyxl_input_new();
+            let parser = CString::new("unknown").unwrap();
+            let mut error = ptr::null_mut();
+            let model = apyxl_model_parse(input, parser.as_ptr(), ptr::null(), &mut error);
+            assert!(model.is_null());
+            assert!(take_error(error).contains("unknown parser 'unknown'"));
+
+            let input = apyxl_input_new();
+            let parser = CString::new("rust").unwrap();
+            let model = apyxl_model_parse(input, parser.as_ptr(), ptr::null(), &mut error);
+            let generator = CString::new("unknown").unwrap();
+            let output = apyxl_generate(model, generator.as_ptr(), &mut error);
+            assert!(output.is_null());
+            assert!(take_error(error).contains("unknown generator 'unknown'"));
+            apyxl_model_free(model);
+        }
+    }
+
+    #[test]
+    fn panics_are_errors() {
+        let err = catch_panic(|| -> anyhow::Result<()> { panic!("oh no {}", 1) }).unwrap_err();
+        assert_eq!(err.to_string(), "panicked: oh no 1");
+        let err = catch_panic(|| -> anyhow::Result<()> { panic!("oh no") }).unwrap_err();
+        assert_eq!(err.to_string(), "panicked: oh no");
+        assert_eq!(catch_panic(|| Ok(1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        unsafe {
+            let input = apyxl_input_new();
+            let data = [0xffu8, 0xfe];
+            let mut error = ptr::null_mut();
+            let ok = apyxl_input_add_chunk(
+                input,
+                ptr::null(),
+                data.as_ptr().cast(),
+                data.len(),
+                &mut error,
+            );
+            assert!(!ok);
+            assert!(take_error(error).contains("utf-8"));
+            apyxl_input_free(input);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! [wasm_bindgen] bindings for running apyxl in the browser, e.g. to power a web playground.
+//!
+//! Built for `wasm32-unknown-unknown` with apyxl's filesystem support disabled, e.g. with
+//! `wasm-pack build wasm --target web`. From JS:
+//!
+//! ```js
+//! const model = parse("rust", "struct A { b: u32 }", null);
+//! const generated = generate("rust", model, null);
+//! ```
+//!
+//! The model is passed between the two as the JSON of its [apyxl::model::Api] so that it can be
+//! inspected, edited, or stored on the JS side. Parsers and generators are looked up by name in
+//! [apyxl::Registry::default]. `config_json` is an optional [apyxl::parser::Config] in JSON
+//! format, including the options of each parser and generator.
+
+use anyhow::Result;
+use apyxl::model::{self, Api};
+use apyxl::output::Buffer;
+use apyxl::{input, parser, Diagnostic, Diagnostics, Generator, Parser, Registry};
+use wasm_bindgen::prelude::*;
+
+/// Parses and validates `source` with the parser registered as `parser`, and returns the JSON of
+/// the parsed API.
+#[wasm_bindgen]
+pub fn parse(parser: &str, source: &str, config_json: Option<String>) -> Result<String, JsError> {
+    parse_to_json(parser, source, config_json.as_deref()).map_err(js_error)
+}
+
+/// Runs the generator registered as `generator` over the API in `model_json`, as returned by
+/// [parse], and returns everything it wrote.
+#[wasm_bindgen]
+pub fn generate(
+    generator: &str,
+    model_json: &str,
+    config_json: Option<String>,
+) -> Result<String, JsError> {
+    generate_from_json(generator, model_json, config_json.as_deref()).map_err(js_error)
+}
+
+/// Names of all parsers that can be passed to [parse].
+#[wasm_bindgen]
+pub fn parsers() -> Vec<String> {
+    Registry::default()
+        .parser_names()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Names of all generators that can be passed to [generate].
+#[wasm_bindgen]
+pub fn generators() -> Vec<String> {
+    Registry::default()
+        .generator_names()
+        .map(str::to_string)
+        .collect()
+}
+
+fn parse_to_json(parser_name: &str, source: &str, config_json: Option<&str>) -> Result<String> {
+    let config = config(config_json)?;
+    let parser = Registry::default().create_parser_with_config(parser_name, &config)?;
+    let mut input = input::Buffer::new(source);
+    let mut builder = builder(&config);
+    parser.parse(&config, &mut input, &mut builder)?;
+    let model = build(builder)?;
+    Ok(serde_json::to_string(model.api())?)
+}
+
+fn generate_from_json(
+    generator_name: &str,
+    model_json: &str,
+    config_json: Option<&str>,
+) -> Result<String> {
+    let config = config(config_json)?;
+    let mut generator =
+        Registry::default().create_generator_with_config(generator_name, &config)?;
+    let api: Api = serde_json::from_str(model_json)?;
+    let mut builder = builder(&config);
+    builder.merge(api);
+    let model = build(builder)?;
+    let mut output = Buffer::default();
+    generator.generate(model.view(), &mut output)?;
+    Ok(output.to_string())
+}
+
+fn config(config_json: Option<&str>) -> Result<parser::Config> {
+    let config = match config_json {
+        None => parser::Config::default(),
+        Some(json) => parser::Config::from_json(json)?,
+    };
+    Registry::default().validate_config(&config)?;
+    Ok(config)
+}
+
+fn builder<'a>(config: &parser::Config) -> model::Builder<'a> {
+    let mut builder = model::Builder::with_config(model::builder::Config {
+        mode: config.mode,
+        ..Default::default()
+    });
+    builder.metadata_mut().user_types = config.user_types.clone();
+    builder
+}
+
+fn build(builder: model::Builder) -> Result<model::Model> {
+    builder.build().map_err(|errors| {
+        errors
+            .iter()
+            .map(Diagnostic::from)
+            .collect::<Diagnostics>()
+            .into()
+    })
+}
+
+fn js_error(err: anyhow::Error) -> JsError {
+    JsError::new(&format!("{:#}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate_from_json, generators, parse_to_json, parsers};
+
+    #[test]
+    fn parse_then_generate() {
+        let model = parse_to_json("rust", "mod a { struct B { c: u32 } }", None).unwrap();
+        assert!(model.contains("\"B\""), "{}", model);
+
+        let config = r#"{ "generator": { "rust": { "import_comments": false } } }"#;
+        let generated = generate_from_json("rust", &model, Some(config)).unwrap();
+        assert!(generated.contains("pub mod a {"), "{}", generated);
+        assert!(generated.contains("struct B {"), "{}", generated);
+    }
+
+    #[test]
+    fn validates_edited_model() {
+        let model = parse_to_json("rust", "struct A { b: B } struct B {}", None).unwrap();
+        let model = model.replace(r#""name":"B","fields""#, r#""name":"Missing","fields""#);
+        let err = generate_from_json("rust", &model, None).unwrap_err();
+        assert!(format!("{:#}", err).contains("dto:B"), "{:#}", err);
+    }
+
+    #[test]
+    fn errors() {
+        let err = parse_to_json("rust", "struct A {", None).unwrap_err();
+        assert!(err.to_string().contains("errors encountered while parsing"));
+        let err = parse_to_json("unknown", "", None).unwrap_err();
+        assert!(err.to_string().contains("unknown parser 'unknown'"));
+        let err = generate_from_json("unknown", "{}", None).unwrap_err();
+        assert!(err.to_string().contains("unknown generator 'unknown'"));
+    }
+
+    #[test]
+    fn names() {
+        assert!(parsers().contains(&"rust".to_string()));
+        assert!(generators().contains(&"rust".to_string()));
+    }
+}